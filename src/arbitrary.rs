@@ -0,0 +1,261 @@
+//! Property testing strategies (see [proptest](https://docs.rs/proptest)) generating random valid
+//! location references, to drive round-trip invariants (serialize/deserialize, encode/decode) in
+//! the tests of the library and of the downstream integrations.
+//!
+//! The location references are generated as binary physical data, every field within its valid
+//! range, and deserialized: they are therefore exactly representable by the binary format and can
+//! be serialized back without loss.
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use crate::{LocationReference, LocationType, deserialize_binary_openlr};
+
+/// Maximum number of relative LRPs (or polygon corners) of the generated location references.
+const MAX_RELATIVE_POINTS: usize = 8;
+
+/// Strategy generating random valid location references of any type.
+pub fn arbitrary_location_reference() -> impl Strategy<Value = LocationReference> {
+    prop_oneof![
+        arbitrary_location_reference_of(LocationType::Line),
+        arbitrary_location_reference_of(LocationType::GeoCoordinate),
+        arbitrary_location_reference_of(LocationType::PointAlongLine),
+        arbitrary_location_reference_of(LocationType::PoiWithAccessPoint),
+        arbitrary_location_reference_of(LocationType::Circle),
+        arbitrary_location_reference_of(LocationType::Rectangle),
+        arbitrary_location_reference_of(LocationType::Grid),
+        arbitrary_location_reference_of(LocationType::Polygon),
+        arbitrary_location_reference_of(LocationType::ClosedLine),
+    ]
+}
+
+/// Strategy generating random valid location references of the given type.
+pub fn arbitrary_location_reference_of(
+    location_type: LocationType,
+) -> BoxedStrategy<LocationReference> {
+    let data = match location_type {
+        LocationType::Line => line_data().boxed(),
+        LocationType::GeoCoordinate => geo_coordinate_data().boxed(),
+        LocationType::PointAlongLine => point_along_line_data().boxed(),
+        LocationType::PoiWithAccessPoint => poi_data().boxed(),
+        LocationType::Circle => circle_data().boxed(),
+        LocationType::Rectangle => rectangle_data().boxed(),
+        LocationType::Grid => grid_data().boxed(),
+        LocationType::Polygon => polygon_data().boxed(),
+        LocationType::ClosedLine => closed_line_data().boxed(),
+    };
+
+    // relative coordinates may overflow the valid coordinates range
+    data.prop_filter_map("invalid location reference", |data| {
+        deserialize_binary_openlr(&data).ok()
+    })
+    .boxed()
+}
+
+const fn header(location_type: u8) -> u8 {
+    3 + (location_type << 3)
+}
+
+fn line_data() -> impl Strategy<Value = Vec<u8>> {
+    (
+        coordinate(),
+        path_attributes(),
+        vec(
+            (relative_coordinate(), path_attributes()),
+            0..MAX_RELATIVE_POINTS,
+        ),
+        relative_coordinate(),
+        any::<u8>(),
+        offset(),
+        offset(),
+    )
+        .prop_map(
+            |(coordinate, first, relative_points, last_coordinate, line, pos, neg)| {
+                let mut data = vec![header(1)];
+                data.extend(coordinate);
+                data.extend(first);
+
+                for (coordinate, attributes) in relative_points {
+                    data.extend(coordinate);
+                    data.extend(attributes);
+                }
+
+                data.extend(last_coordinate);
+                let flags = (u8::from(pos.is_some()) << 1) + u8::from(neg.is_some());
+                data.extend(line_attributes(line, 0, flags));
+                data.extend(pos);
+                data.extend(neg);
+                data
+            },
+        )
+}
+
+fn geo_coordinate_data() -> impl Strategy<Value = Vec<u8>> {
+    coordinate().prop_map(|coordinate| [[header(4)].as_slice(), &coordinate].concat())
+}
+
+fn point_along_line_data() -> impl Strategy<Value = Vec<u8>> {
+    (
+        coordinate(),
+        any::<u8>(),
+        0..4u8,
+        any::<u8>(),
+        relative_coordinate(),
+        any::<u8>(),
+        0..4u8,
+        offset(),
+    )
+        .prop_map(
+            |(coordinate, first, orientation, dnp, last_coordinate, last, side, offset)| {
+                let mut data = vec![header(5)];
+                data.extend(coordinate);
+                data.extend(line_attributes(first, orientation, first >> 5));
+                data.push(dnp);
+                data.extend(last_coordinate);
+                data.extend(line_attributes(last, side, u8::from(offset.is_some()) << 1));
+                data.extend(offset);
+                data
+            },
+        )
+}
+
+fn poi_data() -> impl Strategy<Value = Vec<u8>> {
+    (point_along_line_data(), relative_coordinate())
+        .prop_map(|(point, coordinate)| [point.as_slice(), &coordinate].concat())
+}
+
+fn circle_data() -> impl Strategy<Value = Vec<u8>> {
+    (coordinate(), any::<u32>()).prop_map(|(center, radius)| {
+        [[header(0)].as_slice(), &center, &radius.to_be_bytes()].concat()
+    })
+}
+
+fn rectangle_data() -> impl Strategy<Value = Vec<u8>> {
+    (coordinate(), coordinate())
+        .prop_filter("rectangle corners must differ", |(c1, c2)| c1 != c2)
+        .prop_map(|(lower_left, upper_right)| {
+            [[header(8)].as_slice(), &lower_left, &upper_right].concat()
+        })
+}
+
+fn grid_data() -> impl Strategy<Value = Vec<u8>> {
+    (rectangle_data(), 2..=u16::MAX, 2..=u16::MAX).prop_map(|(rectangle, columns, rows)| {
+        [
+            rectangle.as_slice(),
+            &columns.to_be_bytes(),
+            &rows.to_be_bytes(),
+        ]
+        .concat()
+    })
+}
+
+fn polygon_data() -> impl Strategy<Value = Vec<u8>> {
+    (
+        coordinate(),
+        vec(relative_coordinate(), 2..MAX_RELATIVE_POINTS),
+    )
+        .prop_map(|(coordinate, corners)| {
+            let mut data = vec![header(2)];
+            data.extend(coordinate);
+            data.extend(corners.into_iter().flatten());
+            data
+        })
+}
+
+fn closed_line_data() -> impl Strategy<Value = Vec<u8>> {
+    (
+        coordinate(),
+        path_attributes(),
+        vec(
+            (relative_coordinate(), path_attributes()),
+            1..MAX_RELATIVE_POINTS,
+        ),
+        any::<u8>(),
+    )
+        .prop_map(|(coordinate, first, relative_points, last)| {
+            let mut data = vec![header(11)];
+            data.extend(coordinate);
+            data.extend(first);
+
+            for (coordinate, attributes) in relative_points {
+                data.extend(coordinate);
+                data.extend(attributes);
+            }
+
+            data.extend(line_attributes(last, 0, 0));
+            data
+        })
+}
+
+/// Absolute coordinate in 24-bit resolution: longitude in [-180, 180) and latitude in [-90, 90].
+fn coordinate() -> impl Strategy<Value = [u8; 6]> {
+    (-(1 << 23) + 1..(1 << 23), -(1 << 22)..=(1 << 22)).prop_map(|(lon, lat): (i32, i32)| {
+        let [_, lon @ ..] = lon.to_be_bytes();
+        let [_, lat @ ..] = lat.to_be_bytes();
+        [lon[0], lon[1], lon[2], lat[0], lat[1], lat[2]]
+    })
+}
+
+fn relative_coordinate() -> impl Strategy<Value = [u8; 4]> {
+    (any::<i16>(), any::<i16>()).prop_map(|(lon, lat)| {
+        let [lon1, lon2] = lon.to_be_bytes();
+        let [lat1, lat2] = lat.to_be_bytes();
+        [lon1, lon2, lat1, lat2]
+    })
+}
+
+/// Attributes of a line with its lowest FRC to next point and DNP, without reserved bits.
+fn path_attributes() -> impl Strategy<Value = [u8; 3]> {
+    (any::<u8>(), any::<u8>()).prop_map(|(line, dnp)| {
+        let [first, second] = line_attributes(line, 0, line >> 5);
+        [first, second, dnp]
+    })
+}
+
+/// Takes FRC, FOW and bearing from the random bits of the line.
+const fn line_attributes(line: u8, orientation_or_side: u8, lfrcnp_or_flags: u8) -> [u8; 2] {
+    let frc_fow = line & 0b11_1111;
+    let bearing = line.rotate_left(3) & 0b1_1111;
+    [
+        frc_fow + (orientation_or_side << 6),
+        bearing + ((lfrcnp_or_flags & 0b111) << 5),
+    ]
+}
+
+fn offset() -> impl Strategy<Value = Option<u8>> {
+    proptest::option::of(any::<u8>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DeserializeOptions, deserialize_base64_openlr, deserialize_binary_openlr_with_options,
+        serialize_base64_openlr, serialize_binary_openlr,
+    };
+
+    proptest! {
+        #[test]
+        fn arbitrary_location_reference_binary_round_trip(
+            location in arbitrary_location_reference()
+        ) {
+            let data = serialize_binary_openlr(&location).unwrap();
+            prop_assert_eq!(location.encoded_size(), data.len());
+            let options = DeserializeOptions::strict();
+            prop_assert_eq!(
+                &deserialize_binary_openlr_with_options(&data, &options).unwrap(),
+                &location
+            );
+
+            let code = serialize_base64_openlr(&location).unwrap();
+            prop_assert_eq!(deserialize_base64_openlr(&code).unwrap(), location);
+        }
+
+        #[test]
+        fn arbitrary_location_reference_type(
+            location in arbitrary_location_reference_of(LocationType::Line)
+        ) {
+            prop_assert_eq!(location.location_type(), LocationType::Line);
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! Offline bulk processing of newline-delimited files.
+
+use std::io::{self, BufRead, Write};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+
+use crate::format::geojson::location_geometry;
+use crate::{
+    DecoderConfig, DirectedGraph, EncoderConfig, Location, decode_base64_openlr_with_warnings,
+    encode_base64_openlr_with_warnings,
+};
+
+/// What to do with each record of the input file.
+#[derive(Debug, Clone, Copy)]
+pub enum BatchMode {
+    /// Decodes the `code` of each record and writes the record with its `location`.
+    Decode(DecoderConfig),
+    /// Decodes the `code` of each record and writes a GeoJSON feature with the location geometry
+    /// and the record as properties.
+    DecodeGeoJson(DecoderConfig),
+    /// Encodes the `location` of each record and writes the record with its `code`.
+    Encode(EncoderConfig),
+}
+
+/// Number of records processed by a batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BatchSummary {
+    pub records: usize,
+    pub errors: usize,
+}
+
+/// Processes the newline-delimited input records and writes the results to the output, one JSON
+/// object per line (NDJSON).
+///
+/// Each input line can either be a Base64 OpenLR code or a JSON object record containing it in the
+/// `code` field (or the location in the `location` field when encoding). All the fields of an input
+/// record are copied into its output record, together with the result and the `warnings`.
+/// Records that cannot be processed are written with an `error` field instead of failing the batch.
+/// Empty lines are skipped.
+pub fn process_file<G>(
+    graph: &G,
+    input: impl BufRead,
+    mut output: impl Write,
+    mode: BatchMode,
+) -> io::Result<BatchSummary>
+where
+    G: DirectedGraph,
+    G::EdgeId: Serialize + DeserializeOwned,
+{
+    let mut summary = BatchSummary::default();
+
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let result = parse_record(line).and_then(|record| match mode {
+            BatchMode::Decode(config) => decode_record(&config, graph, record),
+            BatchMode::DecodeGeoJson(config) => decode_geojson_record(&config, graph, record),
+            BatchMode::Encode(config) => encode_record(&config, graph, record),
+        });
+
+        let record = result.unwrap_or_else(|error| {
+            summary.errors += 1;
+            with_error(mode, line, error)
+        });
+
+        serde_json::to_writer(&mut output, &record)?;
+        output.write_all(b"\n")?;
+        summary.records += 1;
+    }
+
+    output.flush()?;
+    Ok(summary)
+}
+
+/// Parses the line as a JSON object record, otherwise the line is considered to be a code.
+fn parse_record(line: &str) -> Result<Map<String, Value>, String> {
+    if line.starts_with('{') {
+        serde_json::from_str(line).map_err(|e| format!("Invalid record: {e}"))
+    } else {
+        Ok(Map::from_iter([("code".to_string(), json!(line))]))
+    }
+}
+
+fn decode_record<G>(
+    config: &DecoderConfig,
+    graph: &G,
+    mut record: Map<String, Value>,
+) -> Result<Value, String>
+where
+    G: DirectedGraph,
+    G::EdgeId: Serialize,
+{
+    let (location, warnings) = decode(config, graph, &record)?;
+
+    record.insert("location".to_string(), json!(location));
+    record.insert("warnings".to_string(), json!(warnings));
+    Ok(Value::Object(record))
+}
+
+fn decode_geojson_record<G>(
+    config: &DecoderConfig,
+    graph: &G,
+    mut record: Map<String, Value>,
+) -> Result<Value, String>
+where
+    G: DirectedGraph,
+    G::EdgeId: Serialize,
+{
+    let (location, warnings) = decode(config, graph, &record)?;
+    let geometry = location_geometry(graph, &location).map_err(|e| e.to_string())?;
+
+    record.insert("location".to_string(), json!(location));
+    record.insert("warnings".to_string(), json!(warnings));
+
+    Ok(json!({
+        "type": "Feature",
+        "geometry": geometry,
+        "properties": record,
+    }))
+}
+
+fn decode<G: DirectedGraph>(
+    config: &DecoderConfig,
+    graph: &G,
+    record: &Map<String, Value>,
+) -> Result<(Location<G::EdgeId>, Vec<String>), String> {
+    let code = record
+        .get("code")
+        .and_then(Value::as_str)
+        .ok_or("Missing code")?;
+
+    let (location, warnings) =
+        decode_base64_openlr_with_warnings(config, graph, code).map_err(|e| e.to_string())?;
+
+    Ok((location, warnings.iter().map(ToString::to_string).collect()))
+}
+
+fn encode_record<G>(
+    config: &EncoderConfig,
+    graph: &G,
+    mut record: Map<String, Value>,
+) -> Result<Value, String>
+where
+    G: DirectedGraph,
+    G::EdgeId: DeserializeOwned,
+{
+    let location = record.get("location").ok_or("Missing location")?;
+    let location: Location<G::EdgeId> =
+        serde_json::from_value(location.clone()).map_err(|e| format!("Invalid location: {e}"))?;
+
+    let (code, warnings) =
+        encode_base64_openlr_with_warnings(config, graph, location).map_err(|e| e.to_string())?;
+
+    let warnings: Vec<_> = warnings.iter().map(ToString::to_string).collect();
+    record.insert("code".to_string(), json!(code));
+    record.insert("warnings".to_string(), json!(warnings));
+    Ok(Value::Object(record))
+}
+
+/// Output record of a failed input record: the input record (or line if it isn't a valid record)
+/// with the error message.
+fn with_error(mode: BatchMode, line: &str, error: String) -> Value {
+    let mut record =
+        parse_record(line).unwrap_or_else(|_| Map::from_iter([("line".into(), json!(line))]));
+    record.insert("error".to_string(), json!(error));
+
+    match mode {
+        BatchMode::Decode(_) | BatchMode::Encode(_) => Value::Object(record),
+        BatchMode::DecodeGeoJson(_) => json!({
+            "type": "Feature",
+            "geometry": null,
+            "properties": record,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{NETWORK_GRAPH, NetworkGraph};
+
+    fn process(input: &str, mode: BatchMode) -> (BatchSummary, Vec<Value>) {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let mut output = vec![];
+        let summary = process_file(graph, input.as_bytes(), &mut output, mode).unwrap();
+
+        let records = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        (summary, records)
+    }
+
+    #[test]
+    fn batch_process_file_decode_001() {
+        let input = "CwmShiVYczPJBgCs/y0zAQ==\n\n{\"id\": 7, \"code\": \"invalid\"}\nnot json {\n";
+        let (summary, records) = process(input, BatchMode::Decode(DecoderConfig::default()));
+
+        assert_eq!(
+            summary,
+            BatchSummary {
+                records: 3,
+                errors: 2
+            }
+        );
+
+        assert_eq!(records[0]["code"], "CwmShiVYczPJBgCs/y0zAQ==");
+        assert_eq!(records[0]["location"]["type"], "Line");
+        assert_eq!(
+            records[0]["location"]["path"],
+            json!([8717174, 8717175, 109783])
+        );
+        assert_eq!(records[0]["warnings"], json!([]));
+        assert!(records[0].get("error").is_none());
+
+        assert_eq!(records[1]["id"], 7);
+        assert!(records[1]["error"].is_string());
+        assert!(records[2]["error"].is_string());
+    }
+
+    #[test]
+    fn batch_process_file_decode_geojson_001() {
+        let input = "{\"id\": \"a\", \"code\": \"CwmShiVYczPJBgCs/y0zAQ==\"}";
+        let (summary, records) = process(input, BatchMode::DecodeGeoJson(DecoderConfig::default()));
+
+        assert_eq!(summary.errors, 0);
+        assert_eq!(records[0]["type"], "Feature");
+        assert_eq!(records[0]["geometry"]["type"], "LineString");
+        assert_eq!(records[0]["properties"]["id"], "a");
+    }
+
+    #[test]
+    fn batch_process_file_encode_001() {
+        let input = concat!(
+            r#"{"id": 1, "location": {"type": "Line", "path": [8717174, 8717175, 109783], "#,
+            r#""pos_offset": 0.0, "neg_offset": 0.0}}"#,
+            "\n",
+            r#"{"id": 2, "location": {"type": "Line", "path": [], "#,
+            r#""pos_offset": 0.0, "neg_offset": 0.0}}"#,
+        );
+
+        let (summary, records) = process(input, BatchMode::Encode(EncoderConfig::default()));
+
+        assert_eq!(
+            summary,
+            BatchSummary {
+                records: 2,
+                errors: 1
+            }
+        );
+        assert_eq!(records[0]["code"], "CwmShiVYcjPJBgCs/y0zAQ==");
+        assert_eq!(records[1]["id"], 2);
+        assert!(records[1]["error"].is_string());
+    }
+}
@@ -0,0 +1,342 @@
+//! Field by field comparison of location references and decoded locations, used to validate the
+//! results of this crate against the results of another OpenLR implementation (e.g. the Java
+//! reference implementation) during a migration.
+//!
+//! The outputs of the other implementation are loaded from their serialized (Base64) form with
+//! [`compare_base64_openlr`], or can be compared directly once deserialized.
+
+use std::fmt::Debug;
+
+use crate::{
+    Bearing, Coordinate, DeserializeError, Length, LineAttributes, Location, LocationReference,
+    Offset, Point, deserialize_base64_openlr,
+};
+
+/// Maximum differences accepted between two compared values before reporting a divergence.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerances {
+    /// Maximum difference of longitude and latitude (degrees).
+    pub coordinate: f64,
+    /// Maximum bearing difference.
+    pub bearing: Bearing,
+    /// Maximum difference of the distances to next point.
+    pub dnp: Length,
+    /// Maximum difference of the relative offsets of location references (range [0, 1)).
+    pub relative_offset: f64,
+    /// Maximum difference of the offsets of decoded locations.
+    pub offset: Length,
+}
+
+impl Default for Tolerances {
+    fn default() -> Self {
+        Self {
+            // a couple of units of the binary format resolution
+            coordinate: 5e-5,
+            // one bearing sector of the binary format
+            bearing: Bearing::from_degrees(12),
+            // one DNP interval of the binary format
+            dnp: Length::from_meters(58.6),
+            relative_offset: 1.0 / Offset::BUCKETS,
+            offset: Length::from_meters(1.0),
+        }
+    }
+}
+
+/// Difference between the expected and the actual value of a field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// Path of the field, e.g. `points[1].line.bearing`.
+    pub field: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Compares two Base64 OpenLR codes: the expected code (e.g. produced by the Java reference
+/// implementation) and the actual code (produced by this crate).
+pub fn compare_base64_openlr(
+    expected: impl AsRef<[u8]>,
+    actual: impl AsRef<[u8]>,
+    tolerances: &Tolerances,
+) -> Result<Vec<Divergence>, DeserializeError> {
+    let expected = deserialize_base64_openlr(expected)?;
+    let actual = deserialize_base64_openlr(actual)?;
+    Ok(compare_location_references(&expected, &actual, tolerances))
+}
+
+/// Compares two location references field by field and returns all the divergences.
+pub fn compare_location_references(
+    expected: &LocationReference,
+    actual: &LocationReference,
+    tolerances: &Tolerances,
+) -> Vec<Divergence> {
+    let mut comparison = Comparison::new(tolerances);
+
+    use LocationReference::*;
+    match (expected, actual) {
+        (Line(expected), Line(actual)) => {
+            comparison.points("points", &expected.points, &actual.points);
+            comparison.offset("offsets.pos", expected.offsets.pos, actual.offsets.pos);
+            comparison.offset("offsets.neg", expected.offsets.neg, actual.offsets.neg);
+        }
+        (GeoCoordinate(expected), GeoCoordinate(actual)) => {
+            comparison.coordinate("coordinate", *expected, *actual);
+        }
+        (PointAlongLine(expected), PointAlongLine(actual)) => {
+            comparison.points("points", &expected.points, &actual.points);
+            comparison.offset("offset", expected.offset, actual.offset);
+            comparison.equal("orientation", expected.orientation, actual.orientation);
+            comparison.equal("side", expected.side, actual.side);
+        }
+        (Poi(expected), Poi(actual)) => {
+            let (e, a) = (&expected.point, &actual.point);
+            comparison.points("point.points", &e.points, &a.points);
+            comparison.offset("point.offset", e.offset, a.offset);
+            comparison.equal("point.orientation", e.orientation, a.orientation);
+            comparison.equal("point.side", e.side, a.side);
+            comparison.coordinate("coordinate", expected.coordinate, actual.coordinate);
+        }
+        (Circle(expected), Circle(actual)) => {
+            comparison.coordinate("center", expected.center, actual.center);
+            comparison.equal("radius", expected.radius.round(), actual.radius.round());
+        }
+        (Rectangle(expected), Rectangle(actual)) => {
+            comparison.coordinate("lower_left", expected.lower_left, actual.lower_left);
+            comparison.coordinate("upper_right", expected.upper_right, actual.upper_right);
+        }
+        (Grid(expected), Grid(actual)) => {
+            let (e, a) = (&expected.rect, &actual.rect);
+            comparison.coordinate("rect.lower_left", e.lower_left, a.lower_left);
+            comparison.coordinate("rect.upper_right", e.upper_right, a.upper_right);
+            comparison.equal("size", expected.size, actual.size);
+        }
+        (Polygon(expected), Polygon(actual)) => {
+            comparison.equal("corners.len", expected.corners.len(), actual.corners.len());
+            for (i, (&e, &a)) in expected.corners.iter().zip(&actual.corners).enumerate() {
+                comparison.coordinate(&format!("corners[{i}]"), e, a);
+            }
+        }
+        (ClosedLine(expected), ClosedLine(actual)) => {
+            comparison.points("points", &expected.points, &actual.points);
+            comparison.line("last_line", &expected.last_line, &actual.last_line);
+        }
+        (expected, actual) => {
+            comparison.equal("type", expected.location_type(), actual.location_type());
+        }
+    }
+
+    comparison.divergences
+}
+
+/// Compares two decoded locations field by field and returns all the divergences.
+pub fn compare_locations<EdgeId: Debug + PartialEq>(
+    expected: &Location<EdgeId>,
+    actual: &Location<EdgeId>,
+    tolerances: &Tolerances,
+) -> Vec<Divergence> {
+    let mut comparison = Comparison::new(tolerances);
+
+    use Location::*;
+    match (expected, actual) {
+        (Line(expected), Line(actual)) => {
+            comparison.equal("path", &expected.path, &actual.path);
+            comparison.length("pos_offset", expected.pos_offset, actual.pos_offset);
+            comparison.length("neg_offset", expected.neg_offset, actual.neg_offset);
+        }
+        (GeoCoordinate(expected), GeoCoordinate(actual)) => {
+            comparison.coordinate("coordinate", *expected, *actual);
+        }
+        (PointAlongLine(expected), PointAlongLine(actual)) => {
+            comparison.equal("path", &expected.path, &actual.path);
+            comparison.length("offset", expected.offset, actual.offset);
+            comparison.equal("orientation", expected.orientation, actual.orientation);
+            comparison.equal("side", expected.side, actual.side);
+        }
+        (Poi(expected), Poi(actual)) => {
+            let (e, a) = (&expected.point, &actual.point);
+            comparison.equal("point.path", &e.path, &a.path);
+            comparison.length("point.offset", e.offset, a.offset);
+            comparison.equal("point.orientation", e.orientation, a.orientation);
+            comparison.equal("point.side", e.side, a.side);
+            comparison.coordinate("coordinate", expected.coordinate, actual.coordinate);
+        }
+        (ClosedLine(expected), ClosedLine(actual)) => {
+            comparison.equal("path", &expected.path, &actual.path);
+        }
+        (Area(expected), Area(actual)) => {
+            comparison.equal("area", &expected.area, &actual.area);
+            comparison.equal("edges", &expected.edges, &actual.edges);
+        }
+        (Line(_) | GeoCoordinate(_) | PointAlongLine(_) | Poi(_) | ClosedLine(_) | Area(_), _) => {
+            comparison.diverge("type", expected, actual);
+        }
+    }
+
+    comparison.divergences
+}
+
+struct Comparison<'a> {
+    tolerances: &'a Tolerances,
+    divergences: Vec<Divergence>,
+}
+
+impl<'a> Comparison<'a> {
+    const fn new(tolerances: &'a Tolerances) -> Self {
+        Self {
+            tolerances,
+            divergences: vec![],
+        }
+    }
+
+    fn diverge(&mut self, field: &str, expected: impl Debug, actual: impl Debug) {
+        self.divergences.push(Divergence {
+            field: field.to_string(),
+            expected: format!("{expected:?}"),
+            actual: format!("{actual:?}"),
+        });
+    }
+
+    fn equal<T: Debug + PartialEq>(&mut self, field: &str, expected: T, actual: T) {
+        if expected != actual {
+            self.diverge(field, expected, actual);
+        }
+    }
+
+    fn coordinate(&mut self, field: &str, expected: Coordinate, actual: Coordinate) {
+        if (expected.lon - actual.lon).abs() > self.tolerances.coordinate
+            || (expected.lat - actual.lat).abs() > self.tolerances.coordinate
+        {
+            self.diverge(field, expected, actual);
+        }
+    }
+
+    fn length(&mut self, field: &str, expected: Length, actual: Length) {
+        if (expected - actual).meters().abs() > self.tolerances.offset.meters() {
+            self.diverge(field, expected, actual);
+        }
+    }
+
+    fn offset(&mut self, field: &str, expected: Offset, actual: Offset) {
+        if (expected.range() - actual.range()).abs() > self.tolerances.relative_offset {
+            self.diverge(field, expected, actual);
+        }
+    }
+
+    fn line(&mut self, field: &str, expected: &LineAttributes, actual: &LineAttributes) {
+        self.equal(&format!("{field}.frc"), expected.frc, actual.frc);
+        self.equal(&format!("{field}.fow"), expected.fow, actual.fow);
+
+        if expected.bearing.difference(&actual.bearing) > self.tolerances.bearing {
+            self.diverge(
+                &format!("{field}.bearing"),
+                expected.bearing,
+                actual.bearing,
+            );
+        }
+    }
+
+    fn points(&mut self, field: &str, expected: &[Point], actual: &[Point]) {
+        self.equal(&format!("{field}.len"), expected.len(), actual.len());
+
+        for (i, (e, a)) in expected.iter().zip(actual).enumerate() {
+            let field = format!("{field}[{i}]");
+            self.coordinate(&format!("{field}.coordinate"), e.coordinate, a.coordinate);
+            self.line(&format!("{field}.line"), &e.line, &a.line);
+
+            match (e.path, a.path) {
+                (Some(e), Some(a)) => {
+                    self.equal(&format!("{field}.path.lfrcnp"), e.lfrcnp, a.lfrcnp);
+                    if (e.dnp - a.dnp).meters().abs() > self.tolerances.dnp.meters() {
+                        self.diverge(&format!("{field}.path.dnp"), e.dnp, a.dnp);
+                    }
+                }
+                (None, None) => {}
+                (e, a) => self.diverge(&format!("{field}.path"), e, a),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::LineLocation;
+
+    #[test]
+    fn compare_base64_openlr_001() {
+        let tolerances = Tolerances::default();
+
+        let divergences = compare_base64_openlr(
+            "CwmShiVYczPJBgCs/y0zAQ==",
+            "CwmShiVYcjPJBgCs/y0zAQ==",
+            &tolerances,
+        )
+        .unwrap();
+        assert_eq!(divergences, vec![]);
+
+        let tolerances = Tolerances {
+            coordinate: 0.0,
+            ..Default::default()
+        };
+
+        let divergences = compare_base64_openlr(
+            "CwmShiVYczPJBgCs/y0zAQ==",
+            "CwmShiVYcjPJBgCs/y0zAQ==",
+            &tolerances,
+        )
+        .unwrap();
+        assert_eq!(divergences.len(), 2);
+        assert_eq!(divergences[0].field, "points[0].coordinate");
+        assert_eq!(divergences[1].field, "points[1].coordinate");
+    }
+
+    #[test]
+    fn compare_base64_openlr_002() {
+        let divergences = compare_base64_openlr(
+            "CwmShiVYczPJBgCs/y0zAQ==",
+            "CwmTaSVYpTPZCP4a/5UjYQUH",
+            &Tolerances::default(),
+        )
+        .unwrap();
+
+        let fields: Vec<_> = divergences.iter().map(|d| d.field.as_str()).collect();
+        assert!(fields.contains(&"points[0].coordinate"));
+        assert!(fields.contains(&"offsets.pos"));
+        assert!(fields.contains(&"offsets.neg"));
+    }
+
+    #[test]
+    fn compare_locations_001() {
+        let expected = Location::Line(LineLocation {
+            path: vec![1, 2, 3],
+            pos_offset: Length::from_meters(10.0),
+            neg_offset: Length::ZERO,
+        });
+
+        let actual = Location::Line(LineLocation {
+            path: vec![1, 2, 3],
+            pos_offset: Length::from_meters(10.5),
+            neg_offset: Length::from_meters(2.0),
+        });
+
+        let divergences = compare_locations(&expected, &actual, &Tolerances::default());
+
+        assert_eq!(
+            divergences,
+            vec![Divergence {
+                field: "neg_offset".to_string(),
+                expected: format!("{:?}", Length::ZERO),
+                actual: format!("{:?}", Length::from_meters(2.0)),
+            }]
+        );
+
+        let divergences = compare_locations(
+            &expected,
+            &Location::GeoCoordinate(Coordinate::default()),
+            &Tolerances::default(),
+        );
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].field, "type");
+    }
+}
@@ -0,0 +1,240 @@
+//! Conformance runner of the binary physical format against golden test vectors, such as the
+//! reference test vectors of the OpenLR Java implementation, to certify that the serializer and
+//! the deserializer of this crate interoperate with other implementations.
+//!
+//! A corpus is a CSV file of cases `name,code` (the Base64 OpenLR code of the case) and, for each
+//! case, the expected location reference in OpenLR XML (see [`crate::deserialize_xml_openlr`]).
+//! The CSV may start with a `name,code` header, empty lines and lines starting with `#` are
+//! skipped.
+//!
+//! Each case is checked in both directions: the code is deserialized and compared with the
+//! expected location reference, and the expected location reference is serialized and
+//! deserialized back without divergences.
+
+use std::fs;
+use std::path::Path;
+
+use crate::{
+    ConformanceError, DeserializeError, Divergence, LocationReference, SerializeError, Tolerances,
+    compare_location_references, deserialize_base64_openlr, deserialize_xml_openlr,
+    serialize_base64_openlr,
+};
+
+/// Golden test vector: an OpenLR code and the location reference it is expected to represent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConformanceCase {
+    pub name: String,
+    /// Base64 OpenLR code.
+    pub code: String,
+    pub expected: LocationReference,
+}
+
+/// Outcome of a conformance case.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaseOutcome {
+    Passed,
+    /// The code cannot be deserialized.
+    DeserializeFailed(DeserializeError),
+    /// The deserialized code diverges from the expected location reference.
+    Diverged(Vec<Divergence>),
+    /// The expected location reference cannot be serialized.
+    SerializeFailed(SerializeError),
+    /// The expected location reference diverges once serialized and deserialized back.
+    RoundTripDiverged(Vec<Divergence>),
+}
+
+/// Outcome of a single case of the corpus.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaseReport {
+    pub name: String,
+    pub outcome: CaseOutcome,
+}
+
+impl CaseReport {
+    pub fn is_passed(&self) -> bool {
+        self.outcome == CaseOutcome::Passed
+    }
+}
+
+/// Outcomes of all the cases of a corpus, in the same order of the cases.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ConformanceReport {
+    pub cases: Vec<CaseReport>,
+}
+
+impl ConformanceReport {
+    /// Returns true only if all the cases passed.
+    pub fn is_conformant(&self) -> bool {
+        self.cases.iter().all(CaseReport::is_passed)
+    }
+
+    pub fn passed(&self) -> usize {
+        self.cases.iter().filter(|case| case.is_passed()).count()
+    }
+
+    /// Gets the cases that didn't pass.
+    pub fn failures(&self) -> impl Iterator<Item = &CaseReport> {
+        self.cases.iter().filter(|case| !case.is_passed())
+    }
+}
+
+/// Loads the corpus of a directory: the cases are listed by the file `cases.csv` and the expected
+/// location reference of each case is read from the XML file named after the case (`<name>.xml`).
+pub fn load_directory(dir: impl AsRef<Path>) -> Result<Vec<ConformanceCase>, ConformanceError> {
+    let dir = dir.as_ref();
+    let csv = fs::read_to_string(dir.join("cases.csv"))?;
+    load_cases(&csv, |name| {
+        fs::read_to_string(dir.join(format!("{name}.xml")))
+    })
+}
+
+/// Loads the cases of the CSV, reading the XML of the expected location reference of each case by
+/// its name.
+pub fn load_cases(
+    csv: &str,
+    mut read_expected: impl FnMut(&str) -> Result<String, std::io::Error>,
+) -> Result<Vec<ConformanceCase>, ConformanceError> {
+    let mut cases = vec![];
+
+    for (index, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || (index == 0 && line == "name,code") {
+            continue;
+        }
+
+        let line_number = index + 1;
+        let Some((name, code)) = line.split_once(',') else {
+            return Err(ConformanceError::InvalidCsv(line_number));
+        };
+
+        let (name, code) = (name.trim(), code.trim());
+        if name.is_empty() || code.is_empty() || code.contains(',') {
+            return Err(ConformanceError::InvalidCsv(line_number));
+        }
+
+        let expected = deserialize_xml_openlr(&read_expected(name)?)
+            .map_err(|error| ConformanceError::InvalidExpected(line_number, error))?;
+
+        cases.push(ConformanceCase {
+            name: name.to_string(),
+            code: code.to_string(),
+            expected,
+        });
+    }
+
+    Ok(cases)
+}
+
+/// Runs the cases against the serializer and the deserializer of the binary format.
+pub fn run_conformance(cases: &[ConformanceCase], tolerances: &Tolerances) -> ConformanceReport {
+    let cases = cases
+        .iter()
+        .map(|case| CaseReport {
+            name: case.name.clone(),
+            outcome: run_case(case, tolerances),
+        })
+        .collect();
+
+    ConformanceReport { cases }
+}
+
+fn run_case(case: &ConformanceCase, tolerances: &Tolerances) -> CaseOutcome {
+    let actual = match deserialize_base64_openlr(&case.code) {
+        Ok(actual) => actual,
+        Err(error) => return CaseOutcome::DeserializeFailed(error),
+    };
+
+    let divergences = compare_location_references(&case.expected, &actual, tolerances);
+    if !divergences.is_empty() {
+        return CaseOutcome::Diverged(divergences);
+    }
+
+    let round_trip = match serialize_base64_openlr(&case.expected) {
+        Ok(code) => deserialize_base64_openlr(code),
+        Err(error) => return CaseOutcome::SerializeFailed(error),
+    };
+
+    match round_trip {
+        Ok(actual) => {
+            let divergences = compare_location_references(&case.expected, &actual, tolerances);
+            if divergences.is_empty() {
+                CaseOutcome::Passed
+            } else {
+                CaseOutcome::RoundTripDiverged(divergences)
+            }
+        }
+        Err(error) => CaseOutcome::DeserializeFailed(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use test_log::test;
+
+    use super::*;
+    use crate::serialize_xml_openlr;
+
+    const CODES: [(&str, &str); 2] = [
+        ("line", "CwmShiVYczPJBgCs/y0zAQ=="),
+        ("point_along_line", "K/6P+SKSuBJGGAUn/1gSUyM="),
+    ];
+
+    fn read_expected(name: &str) -> Result<String, std::io::Error> {
+        let (_, code) = CODES
+            .iter()
+            .find(|(case, _)| *case == name)
+            .ok_or(ErrorKind::NotFound)?;
+        let location = deserialize_base64_openlr(code).map_err(|_| ErrorKind::InvalidData)?;
+        serialize_xml_openlr(&location).map_err(|_| ErrorKind::InvalidData.into())
+    }
+
+    #[test]
+    fn conformance_load_cases() {
+        let csv = "name,code\n# comment\n\nline,CwmShiVYczPJBgCs/y0zAQ==\n";
+        let cases = load_cases(csv, read_expected).unwrap();
+
+        assert_eq!(cases.len(), 1);
+        assert_eq!(cases[0].name, "line");
+        assert_eq!(cases[0].code, "CwmShiVYczPJBgCs/y0zAQ==");
+        assert_eq!(
+            cases[0].expected,
+            deserialize_base64_openlr("CwmShiVYczPJBgCs/y0zAQ==").unwrap()
+        );
+
+        assert_eq!(
+            load_cases("line", read_expected),
+            Err(ConformanceError::InvalidCsv(1))
+        );
+        assert_eq!(
+            load_cases("unknown,CwmShiVYczPJBgCs/y0zAQ==", read_expected),
+            Err(ConformanceError::IO(ErrorKind::NotFound))
+        );
+    }
+
+    #[test]
+    fn conformance_run() {
+        let csv = CODES
+            .map(|(name, code)| format!("{name},{code}"))
+            .join("\n");
+        let mut cases = load_cases(&csv, read_expected).unwrap();
+
+        let report = run_conformance(&cases, &Tolerances::default());
+        assert!(report.is_conformant());
+        assert_eq!(report.passed(), 2);
+
+        cases[0].code = CODES[1].1.to_string();
+        cases[1].code = "not base64".to_string();
+
+        let report = run_conformance(&cases, &Tolerances::default());
+        assert!(!report.is_conformant());
+        assert_eq!(report.passed(), 0);
+        assert_eq!(report.failures().count(), 2);
+        assert!(matches!(report.cases[0].outcome, CaseOutcome::Diverged(_)));
+        assert_eq!(
+            report.cases[1].outcome,
+            CaseOutcome::DeserializeFailed(DeserializeError::InvalidBase64)
+        );
+    }
+}
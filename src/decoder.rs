@@ -1,28 +1,77 @@
 //! The decoder resolves a (map-dependent) location using its own map.
 //! This map might differ from the one used during encoding.
+//!
+//! Decoding is deterministic: the ties between candidates are broken by a total order, so that the
+//! same location is decoded on every run and platform (whatever the order the graph returns its
+//! edges in).
+//! - Candidate lines of equal rating are sorted by bearing difference, then by edge.
+//! - Candidate line pairs of equal rating are sorted by bearing difference, then by the edges of
+//!   the first and of the second LRP.
+//! - Routes of equal cost are resolved expanding the edges exiting each vertex in edge order.
 
+mod area;
+mod batch;
 mod candidates;
+mod consistency;
 mod line;
+mod metrics;
+mod pipeline;
+mod report;
 mod resolver;
 mod route;
 mod shortest_path;
+mod visitor;
+
+use std::time::{Duration, Instant};
 
 use base64::Engine;
 use base64::prelude::BASE64_STANDARD;
 
-use crate::decoder::line::{decode_closed_line, decode_line, decode_poi, decode_point_along_line};
+use crate::decoder::area::decode_area;
+#[cfg(feature = "rayon")]
+pub use crate::decoder::area::find_area_coverage_parallel;
+pub use crate::decoder::area::{AreaCoverage, find_area_coverage};
+pub use crate::decoder::batch::{Decoder, LocationDecoder};
+pub use crate::decoder::candidates::{
+    CandidateLine, CandidateLinePair, CandidateLines, find_candidate_lines,
+};
+pub(crate) use crate::decoder::line::decode_line;
+use crate::decoder::line::{
+    decode_closed_line, decode_line_with_partial_matches, decode_poi, decode_point_along_line,
+};
+pub use crate::decoder::metrics::DecoderMetrics;
+pub use crate::decoder::pipeline::{
+    CandidateFinder, DecoderPipeline, DecoderStages, DefaultCandidateFinder, DefaultOffsetResolver,
+    DefaultPairSelector, DefaultRouteResolver, OffsetResolver, PairSelector, RouteResolver,
+};
+pub use crate::decoder::report::{
+    CandidateLineReport, CandidateNodeReport, DecodeReport, LrpCandidates, PartialMatch,
+    Projection, RejectedPair, RejectionReason, RouteGap,
+};
+pub use crate::decoder::resolver::UTurnPolicy;
+pub use crate::decoder::route::{CandidateRoute, CandidateRoutes};
+pub use crate::decoder::visitor::{
+    LocationVisitor, decode_base64_openlr_with_visitor, decode_binary_openlr_with_visitor,
+};
 use crate::error::DecodeError;
+use crate::graph::memo::EdgeLengthMemo;
+use crate::graph::remote::ReplayGraph;
 use crate::model::RatingScore;
+use crate::routing::RouteCost;
 use crate::{
-    Bearing, DirectedGraph, Length, Location, LocationReference, deserialize_binary_openlr,
+    Area, AsyncDirectedGraph, AsyncGraphError, Bearing, DecodeWarning, DirectedGraph,
+    FrcVarianceTable, Length, Location, LocationGeometry, LocationReference, OffsetBucketPosition,
+    deserialize_binary_openlr, deserialize_binary_openlr_compatible,
 };
 
 #[derive(Debug, Clone, Copy)]
-pub struct DecoderConfig {
+pub struct DecoderConfig<S = DecoderPipeline> {
     /// Maximum distance from the LRP to the nodes of the graph that will be considered.
     pub max_node_distance: Length,
     /// The length of the segment used to compute the lines bearing (distance from the start of
-    /// the segment to its end).
+    /// the segment to its end), both for the candidate lines starting at nodes and for the lines
+    /// the LRPs are projected into. Networks digitised at a coarse resolution may need a longer
+    /// segment, networks with detailed junction geometries a shorter one.
     pub bearing_distance: Length,
     /// Maximum bearing difference between the candidate line bearing and the LRP bearing for the
     /// candidate to be accepted.
@@ -35,12 +84,94 @@ pub struct DecoderConfig {
     pub projected_line_factor: f64,
     /// Minimum rating score for a line to be accepted as candidate.
     pub min_line_rating: RatingScore,
+    /// Maximum number of candidate lines kept for each LRP (the best rated ones). Unbounded if
+    /// None. Bounds the candidate pairs explored between consecutive LRPs on dense networks.
+    pub max_candidate_lines: Option<usize>,
+    /// Candidate lines rated below this fraction of the rating of the best candidate line of the
+    /// same LRP are discarded. Zero keeps every candidate line above the minimum rating.
+    pub min_candidate_rating_ratio: f64,
+    /// Lines the LRP is projected into within this distance of the vertex where they continue
+    /// into a candidate line from a node (the end vertex, or the start vertex for the last LRP)
+    /// are discarded as near duplicates of that candidate line. Zero disables the pruning.
+    pub projection_dedup_distance: Length,
     /// Maximum number of resolver retries.
     pub max_number_retries: usize,
+    /// Maximum number of resolver retries across all the pairs of consecutive LRPs of a location
+    /// reference, including the retries of the pairs revisited by backtracking. Unbounded if None.
+    pub max_total_retries: Option<usize>,
+    /// Revisits the candidate lines chosen for the previous LRPs when no route can be found
+    /// between two consecutive LRPs (as the reference decoder does), instead of failing with
+    /// [`DecodeError::RouteNotFound`]. Backtracking can try exponentially many candidate lines
+    /// pairs, bound it with [`DecoderConfig::max_total_retries`].
+    pub backtracking: bool,
     /// Variance allowed to the resolver when computing distance between LRPs.
     pub next_point_variance: Length,
+    /// Map FRCs accepted for the lowest FRC to the next point of each LRP: candidate lines of
+    /// other classes are rejected, and routes between LRPs are searched up to the lowest accepted
+    /// class.
+    pub frc_variance: FrcVarianceTable,
+    /// Removes the first and the last edges of the decoded line locations that are entirely covered
+    /// by the offsets, reducing the offsets accordingly (as the specification requires). Otherwise
+    /// the decoded locations keep the whole path of the routes between their first and last LRPs,
+    /// with offsets that can exceed the length of their first and last edges.
+    pub trim_offset_edges: bool,
+    /// Interprets the side of road of the point locations relative to the driving direction of
+    /// left-hand traffic countries, as some providers encode it: the side of the road the vehicles
+    /// drive on (the kerb side) is the left side of the line. The sides of the decoded locations
+    /// are mirrored, so that they are always relative to the direction of the line (see
+    /// [`EncoderConfig::left_hand_traffic`](crate::EncoderConfig::left_hand_traffic)).
+    pub left_hand_traffic: bool,
+    /// Position within their buckets of the relative offsets of the location reference, when
+    /// converted back to meters.
+    pub offset_bucket_position: OffsetBucketPosition,
+    /// Offsets that end within this distance of a node of the decoded path are snapped to the node.
+    /// Zero disables the snapping.
+    pub offset_snap_distance: Length,
+    /// Maximum ratio between the decoded offsets (summed) and the length of the decoded path: a
+    /// location reference with longer offsets is rejected with
+    /// [`LocationError::OffsetsExceedLength`](crate::LocationError::OffsetsExceedLength). Offsets
+    /// can never cover the whole path, so only ratios lower than 1 are stricter than the default.
+    pub max_offsets_ratio: f64,
     /// Factor applied to reduce the rating of the top K best candidate LRP lines on the same line.
     pub same_line_degradation: f64,
+    /// If only the first or the last LRP of a line location has no candidate lines, decode the
+    /// location without it instead of failing (the location is flagged with a warning).
+    pub trim_unmatched_endpoints: bool,
+    /// If no route is found between two consecutive LRPs of a line location, split the location
+    /// there and decode each part on its own instead of failing. The longest decoded part is
+    /// returned, the gaps are flagged with warnings and all the parts are recorded in the report.
+    pub allow_partial_matches: bool,
+    /// Also accept line location references serialized with the legacy version 2 of the binary
+    /// physical format, still emitted by some TMC and TPEG feeds (see
+    /// [`deserialize_binary_openlr_compatible`]).
+    pub accept_legacy_version: bool,
+    /// Check at runtime (even in release builds) that the graph behaves as the decoder expects,
+    /// failing with [`DecodeError::InconsistentGraph`] otherwise. Useful to validate new graph
+    /// implementations, at the cost of a slower decoding. Always checked in debug builds.
+    pub check_graph_consistency: bool,
+    /// Handling of the routes that make a U-turn on the line an LRP was projected into: the
+    /// location would turn back in the middle of a line, which is forbidden on most roads.
+    pub projected_u_turns: UTurnPolicy,
+    /// Cost minimized by the route searches between consecutive LRPs. The specification resolves
+    /// the shortest paths, some providers encode their location references along the fastest ones.
+    /// The max distance to the next LRP is checked against the length of the routes anyway.
+    pub route_cost: RouteCost,
+    /// Max number of nodes visited by a single route search between two LRPs before the decoding
+    /// fails with [`DecodeError::SearchLimitExceeded`]. Unbounded if None.
+    /// Bounds the work of the decoder on corrupt (or malicious) location references with huge DNPs.
+    pub max_visited_nodes: Option<usize>,
+    /// Max time spent by a single route search between two LRPs before the decoding fails with
+    /// [`DecodeError::SearchLimitExceeded`]. Unbounded if None.
+    pub max_search_time: Option<Duration>,
+    /// Max time spent resolving the routes between all the LRPs of a location reference before the
+    /// decoding fails with [`DecodeError::SearchLimitExceeded`]. Checked before trying each
+    /// candidate lines pair. Unbounded if None.
+    pub max_total_search_time: Option<Duration>,
+    /// Receiver of the counters and durations of the decoder work, shared by every decoding that
+    /// uses this config. No metrics are recorded if None.
+    pub metrics: Option<&'static dyn DecoderMetrics>,
+    /// Stages of the decoder pipeline.
+    pub stages: S,
 }
 
 impl Default for DecoderConfig {
@@ -53,16 +184,131 @@ impl Default for DecoderConfig {
             line_factor: 3.0,
             projected_line_factor: 0.95,
             min_line_rating: RatingScore::from(700.0),
+            max_candidate_lines: None,
+            min_candidate_rating_ratio: 0.0,
+            projection_dedup_distance: Length::ZERO,
             max_number_retries: 8,
+            max_total_retries: None,
+            backtracking: false,
             next_point_variance: Length::from_meters(150.0),
+            frc_variance: FrcVarianceTable::default(),
+            trim_offset_edges: true,
+            left_hand_traffic: false,
+            offset_bucket_position: OffsetBucketPosition::Middle,
+            offset_snap_distance: Length::ZERO,
+            max_offsets_ratio: 1.0,
             same_line_degradation: 0.85,
+            trim_unmatched_endpoints: false,
+            allow_partial_matches: false,
+            accept_legacy_version: false,
+            check_graph_consistency: false,
+            projected_u_turns: UTurnPolicy::Allow,
+            route_cost: RouteCost::Length,
+            max_visited_nodes: None,
+            max_search_time: None,
+            max_total_search_time: None,
+            metrics: None,
+            stages: DecoderPipeline::default(),
+        }
+    }
+}
+
+impl<C, P, R, O> DecoderConfig<DecoderPipeline<C, P, R, O>> {
+    /// Replaces the stage that finds and rates the candidate lines of the LRPs.
+    pub fn with_candidate_finder<T: CandidateFinder>(
+        self,
+        candidate_finder: T,
+    ) -> DecoderConfig<DecoderPipeline<T, P, R, O>> {
+        self.map_stages(|stages| DecoderPipeline {
+            candidate_finder,
+            pair_selector: stages.pair_selector,
+            route_resolver: stages.route_resolver,
+            offset_resolver: stages.offset_resolver,
+        })
+    }
+
+    /// Replaces the stage that selects the candidate line pairs tried by the route resolver.
+    pub fn with_pair_selector<T: PairSelector>(
+        self,
+        pair_selector: T,
+    ) -> DecoderConfig<DecoderPipeline<C, T, R, O>> {
+        self.map_stages(|stages| DecoderPipeline {
+            candidate_finder: stages.candidate_finder,
+            pair_selector,
+            route_resolver: stages.route_resolver,
+            offset_resolver: stages.offset_resolver,
+        })
+    }
+
+    /// Replaces the stage that resolves the routes between consecutive LRPs.
+    pub fn with_route_resolver<T: RouteResolver>(
+        self,
+        route_resolver: T,
+    ) -> DecoderConfig<DecoderPipeline<C, P, T, O>> {
+        self.map_stages(|stages| DecoderPipeline {
+            candidate_finder: stages.candidate_finder,
+            pair_selector: stages.pair_selector,
+            route_resolver,
+            offset_resolver: stages.offset_resolver,
+        })
+    }
+
+    /// Replaces the stage that resolves the offsets of the location.
+    pub fn with_offset_resolver<T: OffsetResolver>(
+        self,
+        offset_resolver: T,
+    ) -> DecoderConfig<DecoderPipeline<C, P, R, T>> {
+        self.map_stages(|stages| DecoderPipeline {
+            candidate_finder: stages.candidate_finder,
+            pair_selector: stages.pair_selector,
+            route_resolver: stages.route_resolver,
+            offset_resolver,
+        })
+    }
+}
+
+impl<S> DecoderConfig<S> {
+    fn map_stages<T>(self, f: impl FnOnce(S) -> T) -> DecoderConfig<T> {
+        DecoderConfig {
+            max_node_distance: self.max_node_distance,
+            bearing_distance: self.bearing_distance,
+            max_bearing_difference: self.max_bearing_difference,
+            node_factor: self.node_factor,
+            line_factor: self.line_factor,
+            projected_line_factor: self.projected_line_factor,
+            min_line_rating: self.min_line_rating,
+            max_candidate_lines: self.max_candidate_lines,
+            min_candidate_rating_ratio: self.min_candidate_rating_ratio,
+            projection_dedup_distance: self.projection_dedup_distance,
+            max_number_retries: self.max_number_retries,
+            max_total_retries: self.max_total_retries,
+            backtracking: self.backtracking,
+            next_point_variance: self.next_point_variance,
+            frc_variance: self.frc_variance,
+            trim_offset_edges: self.trim_offset_edges,
+            left_hand_traffic: self.left_hand_traffic,
+            offset_bucket_position: self.offset_bucket_position,
+            offset_snap_distance: self.offset_snap_distance,
+            max_offsets_ratio: self.max_offsets_ratio,
+            same_line_degradation: self.same_line_degradation,
+            trim_unmatched_endpoints: self.trim_unmatched_endpoints,
+            allow_partial_matches: self.allow_partial_matches,
+            accept_legacy_version: self.accept_legacy_version,
+            check_graph_consistency: self.check_graph_consistency,
+            projected_u_turns: self.projected_u_turns,
+            route_cost: self.route_cost,
+            max_visited_nodes: self.max_visited_nodes,
+            max_search_time: self.max_search_time,
+            max_total_search_time: self.max_total_search_time,
+            metrics: self.metrics,
+            stages: f(self.stages),
         }
     }
 }
 
 /// Decodes an OpenLR Location Reference encoded in Base64.
-pub fn decode_base64_openlr<G: DirectedGraph>(
-    config: &DecoderConfig,
+pub fn decode_base64_openlr<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
     graph: &G,
     data: impl AsRef<[u8]>,
 ) -> Result<Location<G::EdgeId>, DecodeError<G::Error>> {
@@ -73,25 +319,170 @@ pub fn decode_base64_openlr<G: DirectedGraph>(
 }
 
 /// Decodes an OpenLR Location Reference encoded in binary.
-pub fn decode_binary_openlr<G: DirectedGraph>(
-    config: &DecoderConfig,
+pub fn decode_binary_openlr<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
     graph: &G,
     data: &[u8],
+) -> Result<Location<G::EdgeId>, DecodeError<G::Error>> {
+    decode_binary(config, graph, data, None, &mut vec![])
+}
+
+/// Decodes an OpenLR Location Reference encoded in Base64.
+/// Returns the decoded location together with the non-fatal issues found while decoding.
+#[allow(clippy::type_complexity)]
+pub fn decode_base64_openlr_with_warnings<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    data: impl AsRef<[u8]>,
+) -> Result<(Location<G::EdgeId>, Vec<DecodeWarning>), DecodeError<G::Error>> {
+    let data = BASE64_STANDARD
+        .decode(data)
+        .map_err(|e| DecodeError::DeserializeError(e.into()))?;
+    decode_binary_openlr_with_warnings(config, graph, &data)
+}
+
+/// Decodes an OpenLR Location Reference encoded in binary.
+/// Returns the decoded location together with the non-fatal issues found while decoding.
+#[allow(clippy::type_complexity)]
+pub fn decode_binary_openlr_with_warnings<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    data: &[u8],
+) -> Result<(Location<G::EdgeId>, Vec<DecodeWarning>), DecodeError<G::Error>> {
+    let mut warnings = vec![];
+    let location = decode_binary(config, graph, data, None, &mut warnings)?;
+    Ok((location, warnings))
+}
+
+/// Decodes an OpenLR Location Reference encoded in Base64.
+/// The report is filled with the diagnostics collected during decoding, even if decoding fails.
+pub fn decode_base64_openlr_with_report<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    data: impl AsRef<[u8]>,
+    report: &mut DecodeReport<G::EdgeId>,
+) -> Result<Location<G::EdgeId>, DecodeError<G::Error>> {
+    let data = BASE64_STANDARD
+        .decode(data)
+        .map_err(|e| DecodeError::DeserializeError(e.into()))?;
+    decode_binary_openlr_with_report(config, graph, &data, report)
+}
+
+/// Decodes an OpenLR Location Reference encoded in binary.
+/// The report is filled with the diagnostics collected during decoding, even if decoding fails.
+pub fn decode_binary_openlr_with_report<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    data: &[u8],
+    report: &mut DecodeReport<G::EdgeId>,
+) -> Result<Location<G::EdgeId>, DecodeError<G::Error>> {
+    let mut warnings = vec![];
+    let location = decode_binary(config, graph, data, Some(&mut *report), &mut warnings);
+    report.warnings.extend(warnings);
+    location
+}
+
+/// Decodes an OpenLR Location Reference encoded in binary on a graph whose data is fetched
+/// asynchronously. The decoding is replayed each time it requests graph data that has not been
+/// fetched yet, until all the data it needs is fetched.
+pub async fn decode_binary_openlr_async<G: AsyncDirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    data: &[u8],
+) -> Result<Location<G::EdgeId>, DecodeError<AsyncGraphError<G::Error>>> {
+    let mut graph = ReplayGraph::new(graph);
+
+    loop {
+        if let Some(location) = graph.replay(|graph| decode_binary_openlr(config, graph, data)) {
+            return location;
+        }
+
+        graph
+            .fetch_missing()
+            .await
+            .map_err(|e| DecodeError::GraphError(AsyncGraphError::Graph(e)))?;
+    }
+}
+
+/// Decodes the geometry of an OpenLR Location Reference without a map, only from its coordinates.
+/// Useful to visualize location references without implementing [`DirectedGraph`], see
+/// [`LocationReference::approximate_geometry`].
+pub fn decode_to_geometry(location: &LocationReference) -> LocationGeometry {
+    location.approximate_geometry()
+}
+
+fn decode_binary<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    data: &[u8],
+    report: Option<&mut DecodeReport<G::EdgeId>>,
+    warnings: &mut Vec<DecodeWarning>,
+) -> Result<Location<G::EdgeId>, DecodeError<G::Error>> {
+    let start = Instant::now();
+    let location = decode_binary_location(config, graph, data, report, warnings);
+
+    if let Some(metrics) = config.metrics {
+        metrics.on_decode(start.elapsed(), location.is_ok());
+    }
+    location
+}
+
+fn decode_binary_location<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    data: &[u8],
+    report: Option<&mut DecodeReport<G::EdgeId>>,
+    warnings: &mut Vec<DecodeWarning>,
 ) -> Result<Location<G::EdgeId>, DecodeError<G::Error>> {
     // Step – 1 Decode physical data and check its validity
-    let location = deserialize_binary_openlr(data).map_err(DecodeError::DeserializeError)?;
+    let location = if config.accept_legacy_version {
+        deserialize_binary_openlr_compatible(data)
+    } else {
+        deserialize_binary_openlr(data)
+    }
+    .map_err(DecodeError::DeserializeError)?;
+
+    decode_location(config, graph, location, report, warnings)
+}
+
+/// Decodes an OpenLR Location Reference that is already deserialized.
+pub(crate) fn decode_location_reference<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    location: LocationReference,
+) -> Result<Location<G::EdgeId>, DecodeError<G::Error>> {
+    let start = Instant::now();
+    let location = decode_location(config, graph, location, None, &mut vec![]);
+
+    if let Some(metrics) = config.metrics {
+        metrics.on_decode(start.elapsed(), location.is_ok());
+    }
+    location
+}
+
+fn decode_location<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    location: LocationReference,
+    report: Option<&mut DecodeReport<G::EdgeId>>,
+    warnings: &mut Vec<DecodeWarning>,
+) -> Result<Location<G::EdgeId>, DecodeError<G::Error>> {
+    let graph = &EdgeLengthMemo::new(graph);
 
     use LocationReference::*;
     match location {
-        Line(line) => decode_line(config, graph, line).map(Location::Line),
+        Line(line) => decode_line_with_partial_matches(config, graph, line, report, warnings)
+            .map(Location::Line),
         GeoCoordinate(coordinate) => Ok(Location::GeoCoordinate(coordinate)),
-        PointAlongLine(point) => {
-            decode_point_along_line(config, graph, point).map(Location::PointAlongLine)
+        PointAlongLine(point) => decode_point_along_line(config, graph, point, report, warnings)
+            .map(Location::PointAlongLine),
+        Poi(poi) => decode_poi(config, graph, poi, report, warnings).map(Location::Poi),
+        ClosedLine(line) => {
+            decode_closed_line(config, graph, line, report, warnings).map(Location::ClosedLine)
         }
-        Poi(poi) => decode_poi(config, graph, poi).map(Location::Poi),
-        ClosedLine(line) => decode_closed_line(config, graph, line).map(Location::ClosedLine),
-        Circle(_) | Rectangle(_) | Grid(_) | Polygon(_) => Err(
-            DecodeError::LocationTypeNotSupported(location.location_type()),
-        ),
+        Circle(circle) => decode_area(graph, Area::Circle(circle)).map(Location::Area),
+        Rectangle(rectangle) => decode_area(graph, Area::Rectangle(rectangle)).map(Location::Area),
+        Grid(grid) => decode_area(graph, Area::Grid(grid)).map(Location::Area),
+        Polygon(polygon) => decode_area(graph, Area::Polygon(polygon)).map(Location::Area),
     }
 }
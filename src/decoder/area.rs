@@ -0,0 +1,256 @@
+use tracing::debug;
+
+use crate::location::AreaLocation;
+use crate::{Area, DecodeError, DirectedGraph, Length};
+
+/// Distance between the coordinates sampled along the edges that cross the boundary of the area,
+/// to find whether the edges intersect the area.
+const AREA_SAMPLE_DISTANCE: Length = Length::from_meters(10.0);
+
+/// Edges and vertices of the graph covered by an area (see [`find_area_coverage`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct AreaCoverage<EdgeId, VertexId> {
+    /// Edges that intersect the area (also the edges that only cross it), sorted by ID.
+    pub edges: Vec<EdgeId>,
+    /// Vertices inside the area (or on its boundary), sorted by ID.
+    pub vertices: Vec<VertexId>,
+}
+
+/// Finds the edges that intersect the area and the vertices inside the area.
+///
+/// An edge intersects the area if any of its vertices is inside the area, or otherwise if any of
+/// the coordinates sampled along its geometry (every 10 meters) is inside the area, so that the
+/// long edges that cross small areas are found.
+pub fn find_area_coverage<G: DirectedGraph>(
+    graph: &G,
+    area: &Area,
+) -> Result<AreaCoverage<G::EdgeId, G::VertexId>, G::Error> {
+    let (center, radius) = area.bounding_circle();
+
+    let mut edges = vec![];
+    for (edge, _) in graph.nearest_edges_within_distance(center, radius)? {
+        if is_edge_intersecting(graph, area, edge)? {
+            edges.push(edge);
+        }
+    }
+    edges.sort_unstable();
+    edges.dedup();
+
+    let mut vertices = vec![];
+    for (vertex, _) in graph.nearest_vertices_within_distance(center, radius)? {
+        if area.contains(&graph.get_vertex_coordinate(vertex)?) {
+            vertices.push(vertex);
+        }
+    }
+    vertices.sort_unstable();
+    vertices.dedup();
+
+    Ok(AreaCoverage { edges, vertices })
+}
+
+/// Finds the edges that intersect the area and the vertices inside the area, as
+/// [`find_area_coverage`], checking the edges and the vertices in parallel chunks on the rayon
+/// global thread pool. Useful for large areas (for example metropolitan areas), whose scan takes
+/// seconds on a single thread.
+#[cfg(feature = "rayon")]
+pub fn find_area_coverage_parallel<G>(
+    graph: &G,
+    area: &Area,
+) -> Result<AreaCoverage<G::EdgeId, G::VertexId>, G::Error>
+where
+    G: DirectedGraph + Sync,
+    G::EdgeId: Send + Sync,
+    G::VertexId: Send + Sync,
+    G::Error: Send,
+{
+    use rayon::prelude::*;
+
+    /// Number of edges (or vertices) checked by each parallel task.
+    const CHUNK_SIZE: usize = 256;
+
+    let (center, radius) = area.bounding_circle();
+
+    let candidates: Vec<_> = graph
+        .nearest_edges_within_distance(center, radius)?
+        .map(|(edge, _)| edge)
+        .collect();
+    let chunks = candidates
+        .par_chunks(CHUNK_SIZE)
+        .map(|chunk| {
+            let mut edges = vec![];
+            for &edge in chunk {
+                if is_edge_intersecting(graph, area, edge)? {
+                    edges.push(edge);
+                }
+            }
+            Ok(edges)
+        })
+        .collect::<Result<Vec<_>, G::Error>>()?;
+    let mut edges = chunks.concat();
+    edges.sort_unstable();
+    edges.dedup();
+
+    let candidates: Vec<_> = graph
+        .nearest_vertices_within_distance(center, radius)?
+        .map(|(vertex, _)| vertex)
+        .collect();
+    let chunks = candidates
+        .par_chunks(CHUNK_SIZE)
+        .map(|chunk| {
+            let mut vertices = vec![];
+            for &vertex in chunk {
+                if area.contains(&graph.get_vertex_coordinate(vertex)?) {
+                    vertices.push(vertex);
+                }
+            }
+            Ok(vertices)
+        })
+        .collect::<Result<Vec<_>, G::Error>>()?;
+    let mut vertices = chunks.concat();
+    vertices.sort_unstable();
+    vertices.dedup();
+
+    Ok(AreaCoverage { edges, vertices })
+}
+
+/// Decodes an area location into the edges of the graph covered by the area (see
+/// [`find_area_coverage`]).
+pub fn decode_area<G: DirectedGraph>(
+    graph: &G,
+    area: Area,
+) -> Result<AreaLocation<G::EdgeId>, DecodeError<G::Error>> {
+    debug!("Decoding {area:?}");
+
+    let AreaCoverage { edges, .. } = find_area_coverage(graph, &area)?;
+    Ok(AreaLocation { area, edges })
+}
+
+/// Returns true if any vertex of the edge, or any coordinate sampled along the edge, is inside the
+/// area.
+fn is_edge_intersecting<G: DirectedGraph>(
+    graph: &G,
+    area: &Area,
+    edge: G::EdgeId,
+) -> Result<bool, G::Error> {
+    for vertex in [
+        graph.get_edge_start_vertex(edge)?,
+        graph.get_edge_end_vertex(edge)?,
+    ] {
+        if area.contains(&graph.get_vertex_coordinate(vertex)?) {
+            return Ok(true);
+        }
+    }
+
+    let length = graph.get_edge_length(edge)?;
+    let mut distance = AREA_SAMPLE_DISTANCE;
+    while distance < length {
+        if area.contains(&graph.get_coordinate_along_edge(edge, distance)?) {
+            return Ok(true);
+        }
+        distance += AREA_SAMPLE_DISTANCE;
+    }
+
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+    use crate::{Circle, Coordinate, Length, Rectangle};
+
+    #[test]
+    fn decoder_decode_area_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let area = Area::Circle(Circle {
+            center: Coordinate {
+                lon: 13.46112,
+                lat: 52.51711,
+            },
+            radius: Length::from_meters(250.0),
+        });
+
+        let location = decode_area(graph, area.clone()).unwrap();
+        assert_eq!(location.area, area);
+        assert!(!location.edges.is_empty());
+        assert!(location.edges.is_sorted());
+        assert!(location.edges.contains(&EdgeId(8717174)));
+        assert!(location.edges.contains(&EdgeId(-8717174)));
+
+        let coverage = find_area_coverage(graph, &area).unwrap();
+        assert_eq!(coverage.edges, location.edges);
+        assert!(!coverage.vertices.is_empty());
+        assert!(coverage.vertices.is_sorted());
+        for &vertex in &coverage.vertices {
+            assert!(area.contains(&graph.get_vertex_coordinate(vertex).unwrap()));
+        }
+
+        // every edge touching a vertex inside the area is covered
+        for &vertex in &coverage.vertices {
+            for (edge, _) in graph.vertex_edges(vertex).unwrap() {
+                assert!(coverage.edges.contains(&edge));
+            }
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn decoder_find_area_coverage_parallel() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let area = Area::Circle(Circle {
+            center: Coordinate {
+                lon: 13.46112,
+                lat: 52.51711,
+            },
+            radius: Length::from_meters(1000.0),
+        });
+
+        let coverage = find_area_coverage_parallel(graph, &area).unwrap();
+        assert!(!coverage.edges.is_empty());
+        assert_eq!(coverage, find_area_coverage(graph, &area).unwrap());
+    }
+
+    #[test]
+    fn decoder_decode_area_crossing_edge() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let edge = EdgeId(8717174);
+        let length = graph.get_edge_length(edge).unwrap();
+        assert!(length > Length::from_meters(60.0));
+
+        // small circle in the middle of the edge, away from both its vertices
+        let center = graph.get_coordinate_along_edge(edge, length / 2.0).unwrap();
+        let area = Area::Circle(Circle {
+            center,
+            radius: Length::from_meters(10.0),
+        });
+        for vertex in [
+            graph.get_edge_start_vertex(edge).unwrap(),
+            graph.get_edge_end_vertex(edge).unwrap(),
+        ] {
+            assert!(!area.contains(&graph.get_vertex_coordinate(vertex).unwrap()));
+        }
+
+        let coverage = find_area_coverage(graph, &area).unwrap();
+        assert!(coverage.edges.contains(&edge));
+        assert!(coverage.edges.contains(&EdgeId(-8717174)));
+        assert_eq!(coverage.vertices, vec![]);
+    }
+
+    #[test]
+    fn decoder_decode_area_002() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let area = Area::Rectangle(Rectangle {
+            lower_left: Coordinate { lon: 0.0, lat: 0.0 },
+            upper_right: Coordinate { lon: 1.0, lat: 1.0 },
+        });
+
+        let location = decode_area(graph, area.clone()).unwrap();
+        assert_eq!(location.edges, Vec::<EdgeId>::new());
+        assert_eq!(find_area_coverage(graph, &area).unwrap().vertices, vec![]);
+    }
+}
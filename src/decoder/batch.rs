@@ -0,0 +1,196 @@
+use std::sync::Arc;
+
+use crate::graph::memo::{DecoderContext, DecoderMemo, SearchLimitsKey};
+use crate::{
+    DecodeError, DecoderConfig, DecoderPipeline, DecoderStages, DirectedGraph, Location,
+    decode_base64_openlr,
+};
+
+/// Decoder of OpenLR Location References, implemented by [`Decoder`].
+///
+/// Services can depend on this trait instead of a specific graph and decoder stages, and replace
+/// the decoder in their tests.
+pub trait LocationDecoder {
+    type EdgeId;
+    type Error;
+
+    /// Decodes an OpenLR Location Reference encoded in Base64.
+    fn decode(&self, code: &str) -> Result<Location<Self::EdgeId>, DecodeError<Self::Error>>;
+}
+
+/// Decoder of many location references on the same graph, for example the references of a traffic
+/// feed. The nearest vertices and edges queries, the edge lengths and the shortest paths are cached
+/// across the decoded references, as long as the decoder lives (or as long as the shared
+/// [`DecoderContext`] lives).
+pub struct Decoder<'a, G: DirectedGraph, S = DecoderPipeline> {
+    config: DecoderConfig<S>,
+    graph: DecoderMemo<'a, G>,
+}
+
+impl<'a, G: DirectedGraph, S: DecoderStages> Decoder<'a, G, S> {
+    pub fn new(config: DecoderConfig<S>, graph: &'a G) -> Self {
+        let graph = DecoderMemo::new(graph).with_search_limits(search_limits(&config));
+        Self { config, graph }
+    }
+
+    /// Creates the decoder that caches at most the given number of nearest vertices (and edges)
+    /// queries, evicting the least recently used.
+    pub fn with_spatial_capacity(config: DecoderConfig<S>, graph: &'a G, capacity: usize) -> Self {
+        let graph = DecoderMemo::with_spatial_capacity(graph, capacity)
+            .with_search_limits(search_limits(&config));
+        Self { config, graph }
+    }
+
+    /// Creates the decoder that caches its results in the context, shared with the decoders of
+    /// the other threads. The graph version invalidates the results cached on the other versions
+    /// of the graph (see [`DecoderContext`]).
+    pub fn with_context(
+        config: DecoderConfig<S>,
+        graph: &'a G,
+        graph_version: u64,
+        context: Arc<DecoderContext<G::VertexId, G::EdgeId>>,
+    ) -> Self {
+        let graph = DecoderMemo::with_context(graph, graph_version, context)
+            .with_search_limits(search_limits(&config));
+        Self { config, graph }
+    }
+
+    pub const fn config(&self) -> &DecoderConfig<S> {
+        &self.config
+    }
+
+    /// Gets the graph the references are decoded on.
+    pub fn graph(&self) -> &'a G {
+        self.graph.graph()
+    }
+
+    /// Decodes an OpenLR Location Reference encoded in Base64.
+    pub fn decode(
+        &self,
+        data: impl AsRef<[u8]>,
+    ) -> Result<Location<G::EdgeId>, DecodeError<G::Error>> {
+        decode_base64_openlr(&self.config, &self.graph, data)
+    }
+
+    /// Decodes the OpenLR Location References encoded in Base64.
+    /// Returns the result of each reference in the same order of the codes.
+    #[allow(clippy::type_complexity)]
+    pub fn decode_batch(
+        &self,
+        codes: &[&str],
+    ) -> Vec<Result<Location<G::EdgeId>, DecodeError<G::Error>>> {
+        codes.iter().map(|code| self.decode(code)).collect()
+    }
+}
+
+/// Limits of the shortest path searches of the decoder, that only share the memoized paths with
+/// the decoders with the same limits.
+const fn search_limits<S>(config: &DecoderConfig<S>) -> SearchLimitsKey {
+    (config.max_visited_nodes, config.max_search_time)
+}
+
+impl<G: DirectedGraph, S: DecoderStages> LocationDecoder for Decoder<'_, G, S> {
+    type EdgeId = G::EdgeId;
+    type Error = G::Error;
+
+    fn decode(&self, code: &str) -> Result<Location<Self::EdgeId>, DecodeError<Self::Error>> {
+        Decoder::decode(self, code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::DeserializeError;
+    use crate::graph::tests::{NETWORK_GRAPH, NetworkGraph};
+
+    const CODES: [&str; 3] = [
+        "CwmShiVYczPJBgCs/y0zAQ==",
+        "CwmShiVYczPJBgCs/y0zAQ==",
+        "CwmTaSVYpTPZCP4a/5UjYQUH",
+    ];
+
+    #[test]
+    fn decoder_decode_batch_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let decoder = Decoder::new(DecoderConfig::default(), graph);
+
+        let locations = decoder.decode_batch(&CODES);
+        assert_eq!(locations.len(), CODES.len());
+
+        for (code, location) in CODES.iter().zip(locations) {
+            assert_eq!(
+                location,
+                decode_base64_openlr(&DecoderConfig::default(), graph, code)
+            );
+        }
+
+        // the same references decoded again are served by the cache
+        assert_eq!(
+            decoder.decode_batch(&CODES[..1]),
+            decoder.decode_batch(&CODES[1..2])
+        );
+    }
+
+    #[test]
+    fn decoder_decode_batch_002() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let decoder = Decoder::new(DecoderConfig::default(), graph);
+
+        let locations = decoder.decode_batch(&["CwmShiVYczPJBgCs/y0zAQ==", "not base64"]);
+        assert!(locations[0].is_ok());
+        assert_eq!(
+            locations[1],
+            Err(DecodeError::DeserializeError(
+                DeserializeError::InvalidBase64
+            ))
+        );
+    }
+
+    #[test]
+    fn decoder_with_context_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let context = Arc::new(DecoderContext::new());
+
+        std::thread::scope(|scope| {
+            for code in CODES {
+                let context = Arc::clone(&context);
+                scope.spawn(move || {
+                    let decoder =
+                        Decoder::with_context(DecoderConfig::default(), graph, 1, context);
+                    assert_eq!(
+                        decoder.decode(code),
+                        decode_base64_openlr(&DecoderConfig::default(), graph, code)
+                    );
+                });
+            }
+        });
+        assert_eq!(context.graph_version(), 1);
+
+        // decoders on an older graph version bypass the shared caches
+        let decoder =
+            Decoder::with_context(DecoderConfig::default(), graph, 0, Arc::clone(&context));
+        assert!(decoder.decode(CODES[0]).is_ok());
+        assert_eq!(context.graph_version(), 1);
+
+        // decoders on a newer graph version clear them
+        let decoder =
+            Decoder::with_context(DecoderConfig::default(), graph, 2, Arc::clone(&context));
+        assert!(decoder.decode(CODES[0]).is_ok());
+        assert_eq!(context.graph_version(), 2);
+    }
+
+    #[test]
+    fn decoder_location_decoder_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let decoder: &dyn LocationDecoder<EdgeId = _, Error = _> =
+            &Decoder::new(DecoderConfig::default(), graph);
+
+        assert_eq!(
+            decoder.decode(CODES[2]),
+            decode_base64_openlr(&DecoderConfig::default(), graph, CODES[2])
+        );
+    }
+}
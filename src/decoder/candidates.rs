@@ -3,8 +3,13 @@ use std::fmt::{self, Debug};
 
 use tracing::{debug, trace};
 
+use crate::decoder::consistency::{check_nearest_edges, check_nearest_vertices};
+use crate::graph::get_edge_end_bearing;
 use crate::model::RatingScore;
-use crate::{Bearing, DecodeError, DecoderConfig, DirectedGraph, Fow, Frc, Length, Point};
+use crate::{
+    Bearing, DecodeError, DecoderConfig, DecoderStages, DirectedGraph, EdgeEnd, Fow, Frc, Length,
+    Point, Rating,
+};
 
 /// List of candidate nodes for a Location Reference Point (LRP).
 /// Nodes are sorted based on their distance to the point (closest to farthest).
@@ -40,6 +45,8 @@ pub struct CandidateLine<EdgeId> {
     pub lrp: Point,
     pub edge: EdgeId,
     pub rating: RatingScore,
+    /// Bearing of the line at the LRP (or at the LRP projection into the line).
+    pub bearing: Bearing,
     /// If this line is the result of a projection of the LRP into it, this represents the distance
     /// from the beginning of the line (start vertex) to the point where the LRP was projected.
     pub distance_to_projection: Option<Length>,
@@ -49,6 +56,11 @@ impl<EdgeId> CandidateLine<EdgeId> {
     pub const fn is_projected(&self) -> bool {
         self.distance_to_projection.is_some()
     }
+
+    /// Returns the difference between the bearing of the line and the bearing of its LRP.
+    pub const fn bearing_difference(&self) -> Bearing {
+        self.bearing.difference(&self.lrp.line.bearing)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -57,6 +69,15 @@ pub struct CandidateLinePair<EdgeId> {
     pub line_lrp2: CandidateLine<EdgeId>,
 }
 
+impl<EdgeId> CandidateLinePair<EdgeId> {
+    /// Returns the sum of the bearing differences (in degrees) between the lines and their LRPs.
+    /// Used to break ties between pairs of equal rating, lower is better.
+    pub const fn bearing_difference(&self) -> u16 {
+        self.line_lrp1.bearing_difference().degrees()
+            + self.line_lrp2.bearing_difference().degrees()
+    }
+}
+
 impl<EdgeId: PartialEq> CandidateLinePair<EdgeId> {
     pub fn rating(&self, same_line_degradation: f64) -> RatingScore {
         let mut rating = self.line_lrp1.rating * self.line_lrp2.rating;
@@ -123,8 +144,8 @@ impl<EdgeId: Debug> fmt::Display for ProvisionalCandidateLine<EdgeId> {
 /// If no candidate node has been determined for a location reference point the decoder should try
 /// to determine a candidate line directly. The LRP coordinate can be projected onto lines which are
 /// not far away from that coordinate.
-pub fn find_candidate_nodes<G, I>(
-    config: &DecoderConfig,
+pub fn find_candidate_nodes<G, I, S: DecoderStages>(
+    config: &DecoderConfig<S>,
     graph: &G,
     points: I,
 ) -> Result<impl ExactSizeIterator<Item = CandidateNodes<G::VertexId>>, DecodeError<G::Error>>
@@ -140,23 +161,43 @@ where
 
             let nodes: Vec<_> = graph
                 .nearest_vertices_within_distance(lrp.coordinate, config.max_node_distance)?
-                .map(|(vertex, distance_to_lrp)| {
-                    debug_assert!(distance_to_lrp <= config.max_node_distance);
-                    CandidateNode {
-                        vertex,
-                        distance_to_lrp,
-                    }
+                .map(|(vertex, distance_to_lrp)| CandidateNode {
+                    vertex,
+                    distance_to_lrp,
                 })
                 .collect();
 
-            debug_assert!(nodes.is_sorted_by_key(|n| n.distance_to_lrp));
-            Ok::<_, G::Error>(CandidateNodes { lrp, nodes })
+            check_nearest_vertices(config, &nodes)?;
+            Ok::<_, DecodeError<G::Error>>(CandidateNodes { lrp, nodes })
         })
         .collect::<Result<_, _>>()?;
 
     Ok(nodes.into_iter())
 }
 
+/// Finds the candidate lines of a single LRP as the decoder does (see
+/// [`DefaultCandidateFinder`](crate::DefaultCandidateFinder)): the lines from the nodes close to
+/// the LRP and the lines the LRP is projected into, rated and sorted by rating (descending) and
+/// pruned as configured.
+///
+/// Useful to reuse the decoder candidates outside the decoding of a location, for example to build
+/// a map matcher. Fails with [`DecodeError::CandidatesNotFound`] if the LRP has no candidate line.
+pub fn find_candidate_lines<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    lrp: &Point,
+) -> Result<CandidateLines<G::EdgeId>, DecodeError<G::Error>> {
+    let nodes = find_candidate_nodes(config, graph, [*lrp])?;
+    let lines = find_lrps_candidate_lines(config, graph, nodes)?;
+    lines
+        .into_iter()
+        .next()
+        .ok_or(DecodeError::CandidatesNotFound {
+            index: 0,
+            lrp: *lrp,
+        })
+}
+
 /// For each location reference point the decoder tries to determine lines which should fulfill the
 /// following constraints:
 /// - The start node, end node for the last location reference point or projection point shall be
@@ -174,8 +215,8 @@ where
 ///
 /// If no candidate line can be found for a location reference point, the decoder should report an
 /// error and stop further processing.
-pub fn find_candidate_lines<G: DirectedGraph, I>(
-    config: &DecoderConfig,
+pub fn find_lrps_candidate_lines<G: DirectedGraph, I, S: DecoderStages>(
+    config: &DecoderConfig<S>,
     graph: &G,
     candidate_nodes: I,
 ) -> Result<Vec<CandidateLines<G::EdgeId>>, DecodeError<G::Error>>
@@ -205,10 +246,17 @@ where
         );
 
         if lines.is_empty() {
-            return Err(DecodeError::CandidatesNotFound(*lrp));
+            return Err(DecodeError::CandidatesNotFound {
+                index: i,
+                lrp: *lrp,
+            });
         }
 
-        lines.sort_unstable_by_key(|line| Reverse(line.rating));
+        // ties are broken by bearing difference and then by edge to keep the order of parallel
+        // lines deterministic
+        lines.sort_unstable_by_key(|line| {
+            (Reverse(line.rating), line.bearing_difference(), line.edge)
+        });
 
         // keep at least 1 candidate line, otherwise remove everything below min acceptable rating
         if let Some(best_rating) = lines.first().map(|l| l.rating) {
@@ -221,6 +269,7 @@ where
             };
 
             lines.truncate(position);
+            prune_candidate_lines(config, best_rating, lines);
         }
 
         debug!(
@@ -236,8 +285,29 @@ where
     Ok(candidate_lines)
 }
 
-fn find_candidate_lines_from_nodes<G: DirectedGraph>(
-    config: &DecoderConfig,
+/// Prunes the candidate lines (sorted by rating) as configured: keeps the best candidate lines up
+/// to the max number of candidates, and only the ones rated close enough to the best rating.
+fn prune_candidate_lines<EdgeId, S>(
+    config: &DecoderConfig<S>,
+    best_rating: RatingScore,
+    lines: &mut Vec<CandidateLine<EdgeId>>,
+) {
+    if config.min_candidate_rating_ratio > 0.0 {
+        let min_rating = best_rating * config.min_candidate_rating_ratio;
+        let position = lines
+            .iter()
+            .position(|line| line.rating < min_rating)
+            .unwrap_or(lines.len());
+        lines.truncate(position.max(1));
+    }
+
+    if let Some(max_candidate_lines) = config.max_candidate_lines {
+        lines.truncate(max_candidate_lines.max(1));
+    }
+}
+
+fn find_candidate_lines_from_nodes<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
     graph: &G,
     candidate_nodes: CandidateNodes<G::VertexId>,
 ) -> Result<CandidateLines<G::EdgeId>, DecodeError<G::Error>> {
@@ -262,13 +332,14 @@ fn find_candidate_lines_from_nodes<G: DirectedGraph>(
         let mut candidates: Vec<_> = edges
             .into_iter()
             .map(|(edge, _)| {
-                let bearing = if lrp.is_last() {
-                    let edge_length = graph.get_edge_length(edge)?;
-                    graph.get_edge_bearing(edge, edge_length, config.bearing_distance.reverse())?
+                let at = if lrp.is_last() {
+                    EdgeEnd::End
                 } else {
-                    graph.get_edge_bearing(edge, Length::ZERO, config.bearing_distance)?
+                    EdgeEnd::Start
                 };
 
+                let bearing = get_edge_end_bearing(graph, edge, at, config.bearing_distance)?;
+
                 let line = ProvisionalCandidateLine {
                     lrp,
                     edge,
@@ -290,28 +361,48 @@ fn find_candidate_lines_from_nodes<G: DirectedGraph>(
     Ok(candidate_lines)
 }
 
-fn append_projected_candidate_lines<G: DirectedGraph>(
-    config: &DecoderConfig,
+/// Appends the candidate lines found by projecting the LRP into the nearest edges. The projections
+/// are searched even if no candidate node was found within the max node distance, which is often
+/// the case for long edges of sparse networks where the LRP lies far from any vertex.
+fn append_projected_candidate_lines<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
     graph: &G,
     candidate_lines: &mut CandidateLines<G::EdgeId>,
 ) -> Result<(), DecodeError<G::Error>> {
     let lrp = candidate_lines.lrp;
     debug!("Finding candidates from projected lines");
 
-    let projected_lines: Vec<_> = graph
+    let nearest_edges: Vec<_> = graph
         .nearest_edges_within_distance(lrp.coordinate, config.max_node_distance)?
+        .collect();
+    check_nearest_edges(config, &nearest_edges)?;
+
+    let projected_lines: Vec<_> = nearest_edges
+        .into_iter()
         .map(|(edge, distance_to_lrp)| {
-            debug_assert!(distance_to_lrp <= config.max_node_distance);
             let distance_to_projection = graph.get_distance_along_edge(edge, lrp.coordinate)?;
 
             // if distance is 0 or equal to the edge length it would essentially represent a
             // line based on a node, instead of the outcome of the LRP projection
+            let edge_length = graph.get_edge_length(edge)?;
             if distance_to_projection.floor() <= Length::ZERO
-                || distance_to_projection.ceil() >= graph.get_edge_length(edge)?
+                || distance_to_projection.ceil() >= edge_length
             {
                 return Ok(None);
             }
 
+            if is_duplicate_projection(
+                config,
+                graph,
+                candidate_lines,
+                edge,
+                distance_to_projection,
+                edge_length,
+            )? {
+                trace!("Discarding projection into {edge:?}: duplicates a line from a node");
+                return Ok(None);
+            }
+
             let bearing = if lrp.is_last() {
                 graph.get_edge_bearing(
                     edge,
@@ -372,6 +463,50 @@ fn append_projected_candidate_lines<G: DirectedGraph>(
     Ok(())
 }
 
+/// Returns true if the LRP projection into the edge lies within the configured distance of the
+/// vertex where the edge continues into a candidate line from a node: the projection would follow
+/// the same way as that candidate line.
+fn is_duplicate_projection<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    candidate_lines: &CandidateLines<G::EdgeId>,
+    edge: G::EdgeId,
+    distance_to_projection: Length,
+    edge_length: Length,
+) -> Result<bool, G::Error> {
+    if config.projection_dedup_distance <= Length::ZERO || candidate_lines.lines.is_empty() {
+        return Ok(false);
+    }
+
+    let lrp = candidate_lines.lrp;
+    let (distance_to_vertex, vertex) = if lrp.is_last() {
+        (distance_to_projection, graph.get_edge_start_vertex(edge)?)
+    } else {
+        (
+            edge_length - distance_to_projection,
+            graph.get_edge_end_vertex(edge)?,
+        )
+    };
+
+    if distance_to_vertex > config.projection_dedup_distance {
+        return Ok(false);
+    }
+
+    for line in &candidate_lines.lines {
+        let line_vertex = if lrp.is_last() {
+            graph.get_edge_end_vertex(line.edge)?
+        } else {
+            graph.get_edge_start_vertex(line.edge)?
+        };
+
+        if !line.is_projected() && line_vertex == vertex {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
 /// All candidate lines for a location reference point shall be rated according to the following
 /// criteria:
 /// - The start node, end node for the last location reference point or projection point shall be as
@@ -387,13 +522,21 @@ fn append_projected_candidate_lines<G: DirectedGraph>(
 /// function.
 ///
 /// The candidate lines should be ordered in a way that the best matching line comes first.
-fn rate_line<EdgeId: Debug + Copy>(
-    config: &DecoderConfig,
+/// Gets the highest rating a candidate line can have: a line starting at the LRP coordinate with
+/// excellent bearing, FRC and FOW ratings.
+pub fn max_line_rating<S: DecoderStages>(config: &DecoderConfig<S>) -> RatingScore {
+    let excellent_rating = Frc::rating_score(Rating::Excellent);
+    config.node_factor * RatingScore::from(config.max_node_distance)
+        + config.line_factor * (excellent_rating + excellent_rating + excellent_rating)
+}
+
+fn rate_line<EdgeId: Debug + Copy, S: DecoderStages>(
+    config: &DecoderConfig<S>,
     lrp: Point,
     line: ProvisionalCandidateLine<EdgeId>,
 ) -> Option<CandidateLine<EdgeId>> {
     if let Some(path) = &lrp.path
-        && !line.frc.is_within_variance(&path.lfrcnp)
+        && !config.frc_variance.accepts(path.lfrcnp, line.frc)
     {
         trace!("Candidate FRC variance out of bounds: {line}");
         return None;
@@ -435,6 +578,7 @@ fn rate_line<EdgeId: Debug + Copy>(
         edge: line.edge,
         distance_to_projection: line.distance_to_projection,
         rating,
+        bearing: line.bearing,
     })
 }
 
@@ -443,7 +587,9 @@ mod tests {
     use test_log::test;
 
     use super::*;
-    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph, VertexId};
+    use crate::graph::tests::{
+        EdgeId, NETWORK_GRAPH, NetworkGraph, PrecomputedBearingGraph, VertexId,
+    };
     use crate::{Coordinate, LineAttributes, PathAttributes};
 
     #[test]
@@ -491,7 +637,28 @@ mod tests {
             .map(|node| (node.vertex, (node.distance_to_lrp.meters() * 100.0).round()))
             .collect();
 
-        assert_eq!(nodes, [(VertexId(68), 174.0), (VertexId(20), 216.0)]);
+        // the distance is already rounded to millimeters with fixed-point lengths
+        let distance = if cfg!(feature = "fixed-point-length") {
+            217.0
+        } else {
+            216.0
+        };
+
+        // the vertex coordinates stored in single precision are off by a few centimeters
+        let tolerance = if cfg!(feature = "precision-f32") {
+            10.0
+        } else {
+            0.0
+        };
+
+        let vertices: Vec<_> = nodes.iter().map(|&(vertex, _)| vertex).collect();
+        assert_eq!(vertices, [VertexId(68), VertexId(20)]);
+        for (&(_, actual), expected) in nodes.iter().zip([174.0, distance]) {
+            assert!(
+                (actual - expected).abs() <= tolerance,
+                "{actual} != {expected}"
+            );
+        }
     }
 
     #[test]
@@ -676,7 +843,7 @@ mod tests {
             },
         ];
 
-        let lines: Vec<_> = find_candidate_lines(&config, graph, points)
+        let lines: Vec<_> = find_lrps_candidate_lines(&config, graph, points)
             .unwrap()
             .into_iter()
             .map(|candidate| {
@@ -754,7 +921,7 @@ mod tests {
             },
         ];
 
-        let lines = find_candidate_lines(&config, graph, points).unwrap();
+        let lines = find_lrps_candidate_lines(&config, graph, points).unwrap();
 
         let lines: Vec<_> = lines
             .into_iter()
@@ -862,7 +1029,7 @@ mod tests {
             },
         ];
 
-        let lines = find_candidate_lines(&config, graph, points).unwrap();
+        let lines = find_lrps_candidate_lines(&config, graph, points.clone()).unwrap();
 
         let lines: Vec<_> = lines
             .into_iter()
@@ -890,5 +1057,190 @@ mod tests {
                 vec![(EdgeId(7531947), None)]
             ]
         );
+
+        let edges = |config: &DecoderConfig| -> Vec<Vec<EdgeId>> {
+            find_lrps_candidate_lines(config, graph, points.clone())
+                .unwrap()
+                .into_iter()
+                .map(|candidate| candidate.lines.into_iter().map(|line| line.edge).collect())
+                .collect()
+        };
+
+        // the projection into 109783 ends 1m before vertex 20 where 6770340 starts
+        let dedup_config = DecoderConfig {
+            projection_dedup_distance: Length::from_meters(5.0),
+            ..config
+        };
+        assert_eq!(edges(&dedup_config)[1], [EdgeId(6770340), EdgeId(-6828301)]);
+
+        let max_config = DecoderConfig {
+            max_candidate_lines: Some(1),
+            ..config
+        };
+        assert!(edges(&max_config).iter().all(|lines| lines.len() == 1));
+
+        let ratio_config = DecoderConfig {
+            min_candidate_rating_ratio: 1.0,
+            ..config
+        };
+        for candidate in find_lrps_candidate_lines(&ratio_config, graph, points.clone()).unwrap() {
+            let best_rating = candidate.lines[0].rating;
+            assert!(
+                candidate
+                    .lines
+                    .iter()
+                    .all(|line| line.rating == best_rating)
+            );
+        }
+    }
+
+    #[test]
+    fn decoder_find_candidate_lines_005() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let config = DecoderConfig::default();
+
+        let lrp = Point {
+            coordinate: Coordinate {
+                lon: 13.46112,
+                lat: 52.51711,
+            },
+            line: LineAttributes {
+                frc: Frc::Frc6,
+                fow: Fow::SingleCarriageway,
+                bearing: Bearing::from_degrees(107),
+            },
+            path: Some(PathAttributes {
+                lfrcnp: Frc::Frc6,
+                dnp: Length::from_meters(381.0),
+            }),
+        };
+
+        let candidates = find_candidate_lines(&config, graph, &lrp).unwrap();
+        let nodes = find_candidate_nodes(&config, graph, [lrp]).unwrap();
+        assert_eq!(
+            vec![candidates],
+            find_lrps_candidate_lines(&config, graph, nodes).unwrap()
+        );
+
+        let far_lrp = Point {
+            coordinate: Coordinate {
+                lon: 13.0,
+                lat: 52.0,
+            },
+            ..lrp
+        };
+        assert!(matches!(
+            find_candidate_lines(&config, graph, &far_lrp),
+            Err(DecodeError::CandidatesNotFound { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn decoder_find_candidate_lines_004() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let config = DecoderConfig {
+            max_node_distance: Length::from_meters(5.0),
+            max_bearing_difference: Bearing::from_degrees(90),
+            ..Default::default()
+        };
+
+        let lrp = Point {
+            coordinate: Coordinate {
+                lon: 13.4615506,
+                lat: 52.5170544,
+            },
+            line: LineAttributes {
+                frc: Frc::Frc6,
+                fow: Fow::SingleCarriageway,
+                bearing: Bearing::from_degrees(107),
+            },
+            path: Some(PathAttributes {
+                lfrcnp: Frc::Frc6,
+                dnp: Length::from_meters(70.0),
+            }),
+        };
+
+        // no vertex is within the max node distance of the LRP
+        let nodes: Vec<_> = find_candidate_nodes(&config, graph, [lrp])
+            .unwrap()
+            .collect();
+        assert_eq!(nodes, [CandidateNodes { lrp, nodes: vec![] }]);
+
+        let lines = find_lrps_candidate_lines(&config, graph, nodes).unwrap();
+
+        let lines: Vec<_> = lines
+            .into_iter()
+            .map(|candidate| {
+                candidate
+                    .lines
+                    .into_iter()
+                    .map(|line| (line.edge, line.distance_to_projection.map(|d| d.round())))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        assert_eq!(
+            lines,
+            [vec![(EdgeId(8717174), Some(Length::from_meters(29.0)))]]
+        );
+    }
+
+    #[test]
+    fn decoder_find_candidate_lines_precomputed_bearing() {
+        let config = DecoderConfig {
+            max_node_distance: Length::from_meters(10.0),
+            ..Default::default()
+        };
+
+        let lrp = Point {
+            coordinate: Coordinate {
+                lon: 13.46112,
+                lat: 52.51711,
+            },
+            line: LineAttributes {
+                frc: Frc::Frc6,
+                fow: Fow::SingleCarriageway,
+                bearing: Bearing::from_degrees(107),
+            },
+            path: Some(PathAttributes {
+                lfrcnp: Frc::Frc6,
+                dnp: Length::from_meters(381.0),
+            }),
+        };
+
+        let node_lines = |lines: &CandidateLines<EdgeId>| -> Vec<_> {
+            lines
+                .lines
+                .iter()
+                .filter(|line| !line.is_projected())
+                .map(|line| (line.edge, line.bearing, line.rating))
+                .collect()
+        };
+
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let computed = node_lines(&find_candidate_lines(&config, graph, &lrp).unwrap());
+        assert!(!computed.is_empty());
+
+        // the precomputed bearing matches the LRP bearing exactly and rates the lines higher
+        let graph = PrecomputedBearingGraph(lrp.line.bearing);
+        let precomputed = node_lines(&find_candidate_lines(&config, &graph, &lrp).unwrap());
+        assert!(
+            precomputed
+                .iter()
+                .all(|&(_, bearing, _)| bearing == lrp.line.bearing)
+        );
+        for &(edge, _, computed_rating) in &computed {
+            let Some(&(_, _, rating)) = precomputed.iter().find(|&&(e, ..)| e == edge) else {
+                unreachable!()
+            };
+            assert!(rating >= computed_rating);
+        }
+
+        // the precomputed bearing opposite to the LRP bearing rules out the lines of the nodes
+        let graph = PrecomputedBearingGraph(Bearing::from_degrees(107 + 180));
+        if let Ok(lines) = find_candidate_lines(&config, &graph, &lrp) {
+            assert!(node_lines(&lines).is_empty());
+        }
     }
 }
@@ -0,0 +1,153 @@
+//! Runtime checks of the assumptions the decoder makes about the graph.
+//!
+//! The checks are always enabled in debug builds, and in release builds only if enabled by
+//! [`DecoderConfig::check_graph_consistency`].
+
+use crate::decoder::candidates::CandidateNode;
+use crate::decoder::route::CandidateRoutes;
+use crate::graph::path::find_path_disconnection;
+use crate::{DecodeError, DecoderConfig, DirectedGraph, GraphConsistencyError, Length};
+
+const fn is_enabled<S>(config: &DecoderConfig<S>) -> bool {
+    config.check_graph_consistency || cfg!(debug_assertions)
+}
+
+/// Checks that the nearest vertices are within the max node distance and sorted by distance.
+pub fn check_nearest_vertices<E, S, VertexId>(
+    config: &DecoderConfig<S>,
+    nodes: &[CandidateNode<VertexId>],
+) -> Result<(), DecodeError<E>> {
+    if !is_enabled(config) {
+        return Ok(());
+    }
+
+    if let Some(node) = nodes
+        .iter()
+        .find(|n| n.distance_to_lrp > config.max_node_distance)
+    {
+        return Err(DecodeError::InconsistentGraph(
+            GraphConsistencyError::VertexBeyondMaxDistance {
+                distance: node.distance_to_lrp,
+                max_distance: config.max_node_distance,
+            },
+        ));
+    }
+
+    if !nodes.is_sorted_by_key(|n| n.distance_to_lrp) {
+        return Err(DecodeError::InconsistentGraph(
+            GraphConsistencyError::VerticesNotSorted,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks that the nearest edges are within the max node distance and sorted by distance.
+pub fn check_nearest_edges<E, S, EdgeId>(
+    config: &DecoderConfig<S>,
+    edges: &[(EdgeId, Length)],
+) -> Result<(), DecodeError<E>> {
+    if !is_enabled(config) {
+        return Ok(());
+    }
+
+    if let Some(&(_, distance)) = edges.iter().find(|(_, d)| *d > config.max_node_distance) {
+        return Err(DecodeError::InconsistentGraph(
+            GraphConsistencyError::EdgeBeyondMaxDistance {
+                distance,
+                max_distance: config.max_node_distance,
+            },
+        ));
+    }
+
+    if !edges.is_sorted_by_key(|(_, distance)| *distance) {
+        return Err(DecodeError::InconsistentGraph(
+            GraphConsistencyError::EdgesNotSorted,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks that the path of the resolved routes is connected.
+pub fn check_routes_connected<G: DirectedGraph, S>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    routes: &CandidateRoutes<G::EdgeId>,
+) -> Result<(), DecodeError<G::Error>> {
+    if !is_enabled(config) {
+        return Ok(());
+    }
+
+    if let Some(index) = find_path_disconnection(graph, &routes.to_path())? {
+        return Err(DecodeError::InconsistentGraph(
+            GraphConsistencyError::DisconnectedRoute { index },
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{EdgeId, VertexId};
+
+    #[test]
+    fn check_nearest_vertices_001() {
+        let config = DecoderConfig::default();
+        let node = |distance| CandidateNode {
+            vertex: VertexId(1),
+            distance_to_lrp: Length::from_meters(distance),
+        };
+
+        let result = check_nearest_vertices::<(), _, _>(&config, &[node(1.0), node(2.0)]);
+        assert_eq!(result, Ok(()));
+
+        let result = check_nearest_vertices::<(), _, _>(&config, &[node(2.0), node(1.0)]);
+        assert_eq!(
+            result,
+            Err(DecodeError::InconsistentGraph(
+                GraphConsistencyError::VerticesNotSorted
+            ))
+        );
+
+        let result = check_nearest_vertices::<(), _, _>(&config, &[node(1.0), node(101.0)]);
+        assert_eq!(
+            result,
+            Err(DecodeError::InconsistentGraph(
+                GraphConsistencyError::VertexBeyondMaxDistance {
+                    distance: Length::from_meters(101.0),
+                    max_distance: config.max_node_distance
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn check_nearest_edges_001() {
+        let config = DecoderConfig::default();
+        let edge = |distance| (EdgeId(1), Length::from_meters(distance));
+
+        let result = check_nearest_edges::<(), _, _>(&config, &[edge(1.0), edge(1.0)]);
+        assert_eq!(result, Ok(()));
+
+        let result = check_nearest_edges::<(), _, _>(&config, &[edge(3.0), edge(1.0)]);
+        assert_eq!(
+            result,
+            Err(DecodeError::InconsistentGraph(
+                GraphConsistencyError::EdgesNotSorted
+            ))
+        );
+
+        let result = check_nearest_edges::<(), _, _>(&config, &[edge(150.0)]);
+        assert!(matches!(
+            result,
+            Err(DecodeError::InconsistentGraph(
+                GraphConsistencyError::EdgeBeyondMaxDistance { .. }
+            ))
+        ));
+    }
+}
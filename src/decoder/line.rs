@@ -1,11 +1,15 @@
 use tracing::debug;
 
-use crate::decoder::candidates::{find_candidate_lines, find_candidate_nodes};
-use crate::decoder::resolver::resolve_routes;
-use crate::location::ClosedLineLocation;
+use crate::decoder::candidates::CandidateLines;
+use crate::decoder::pipeline::{CandidateFinder, OffsetResolver, RouteResolver};
+use crate::decoder::report::{DecodeReport, PartialMatch, RouteGap};
+use crate::decoder::route::CandidateRoutes;
+use crate::graph::path::path_length;
+use crate::location::{ClosedLineLocation, get_path_coordinate};
 use crate::{
-    ClosedLine, DecodeError, DecoderConfig, DirectedGraph, Length, Line, LineLocation, Offsets,
-    Poi, PoiLocation, Point, PointAlongLine, PointAlongLineLocation,
+    Bearing, ClosedLine, DecodeError, DecodeWarning, DecoderConfig, DecoderStages, DirectedGraph,
+    Length, Line, LineLocation, LocationError, Offset, Offsets, Poi, PoiLocation, Point,
+    PointAlongLine, PointAlongLineLocation,
 };
 
 /// 1. Decode physical data and check its validity.
@@ -15,48 +19,524 @@ use crate::{
 /// 5. Determine shortest-path(s) between two subsequent location reference points.
 /// 6. Check validity of the calculated shortest-path(s).
 /// 7. Concatenate shortest-path(s) to form the location and trim path according to the offsets.
-pub fn decode_line<G: DirectedGraph>(
-    config: &DecoderConfig,
+pub fn decode_line<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
     graph: &G,
     line: Line,
+    report: Option<&mut DecodeReport<G::EdgeId>>,
+    warnings: &mut Vec<DecodeWarning>,
 ) -> Result<LineLocation<G::EdgeId>, DecodeError<G::Error>> {
+    // Step – 1..6 Resolve the routes between the LRPs and the offsets
+    let (routes, pos_offset, neg_offset) = resolve_line(config, graph, line, report, warnings)?;
+
+    // Step – 7 Concatenate and trim path according to the offsets
+    let location = LineLocation {
+        path: routes.to_path(),
+        pos_offset,
+        neg_offset,
+    };
+
+    let location = if config.trim_offset_edges {
+        location.trim(graph)?
+    } else {
+        let length = location.path_length(graph)?;
+        if pos_offset + neg_offset >= length {
+            return Err(LocationError::OffsetsExceedLength {
+                pos_offset,
+                neg_offset,
+                length,
+            }
+            .into());
+        }
+        location
+    };
+
+    debug_assert!(!location.path.is_empty());
+    debug_assert!(location.path.windows(2).all(|w| w[0] != w[1]));
+
+    Ok(location)
+}
+
+/// Resolves the routes between the LRPs of the line location and the offsets of the location
+/// relative to the start of the first route and to the end of the last route.
+#[allow(clippy::type_complexity)]
+pub(crate) fn resolve_line<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    mut line: Line,
+    mut report: Option<&mut DecodeReport<G::EdgeId>>,
+    warnings: &mut Vec<DecodeWarning>,
+) -> Result<(CandidateRoutes<G::EdgeId>, Length, Length), DecodeError<G::Error>> {
     debug!("Decoding {line:?} with {config:?}");
+    check_points_plausibility(config, &line.points)?;
+    line.offsets = line
+        .offsets
+        .with_bucket_position(config.offset_bucket_position);
 
     // Step – 2 For each location reference point find candidate nodes
-    let lrps_count = line.points.len();
-    let nodes = find_candidate_nodes(config, graph, line.points)?;
-    debug_assert_eq!(nodes.len(), lrps_count);
-
     // Step – 3 For each location reference point find candidate lines
     // Step – 4 Rate candidate lines for each location reference point
-    let lines = find_candidate_lines(config, graph, nodes)?;
+    let lrps_count = line.points.len();
+    if let Some(report) = report.as_mut() {
+        report.record_candidate_nodes(config, graph, &line.points)?;
+    }
+
+    let lines = config
+        .stages
+        .candidate_finder()
+        .find_candidates(config, graph, line.points)?;
     debug_assert_eq!(lines.len(), lrps_count);
 
+    if let Some(metrics) = config.metrics {
+        lines
+            .iter()
+            .for_each(|lrp| metrics.on_candidate_lines(lrp.lines.len()));
+    }
+
+    if let Some(report) = report.as_mut() {
+        report.record_candidate_lines(graph, &lines)?;
+    }
+
     // Step – 5 Determine shortest-path(s) between all subsequent location reference points
     // Step – 6 Check validity of the calculated shortest-path(s)
-    let routes = resolve_routes(config, graph, &lines, line.offsets)?;
+    let route_resolver = config.stages.route_resolver();
+    let routes = if let Some(report) = report.as_mut() {
+        route_resolver.resolve_routes_with_rejections(
+            config,
+            graph,
+            &lines,
+            line.offsets,
+            &mut report.rejected_pairs,
+        )?
+    } else {
+        route_resolver.resolve_routes(config, graph, &lines, line.offsets)?
+    };
     debug_assert!(!routes.is_empty() && routes.len() < lrps_count);
+    check_routes(config, graph, &lines, &routes, warnings)?;
 
-    // Step – 7 Concatenate and trim path according to the offsets
-    let (pos_offset, neg_offset) = routes.calculate_offsets(graph, line.offsets)?;
+    if let Some(report) = report {
+        report.record_routes(config, &routes);
+    }
 
-    let location = LineLocation {
-        path: routes.to_path(),
-        pos_offset,
-        neg_offset,
+    // Step – 7 Resolve the offsets of the location
+    let (mut pos_offset, mut neg_offset) =
+        config
+            .stages
+            .offset_resolver()
+            .resolve_offsets(config, graph, &routes, line.offsets)?;
+
+    if pos_offset < Length::ZERO {
+        warnings.push(DecodeWarning::PositiveOffsetTrimmed(pos_offset));
+        pos_offset = Length::ZERO;
     }
-    .trim(graph)?;
 
-    debug_assert!(!location.path.is_empty());
-    debug_assert!(location.path.windows(2).all(|w| w[0] != w[1]));
+    if neg_offset < Length::ZERO {
+        warnings.push(DecodeWarning::NegativeOffsetTrimmed(neg_offset));
+        neg_offset = Length::ZERO;
+    }
+
+    let (pos_offset, neg_offset) = snap_offsets(config, graph, &routes, pos_offset, neg_offset)?;
+    check_offsets_ratio(config, graph, &routes, pos_offset, neg_offset)?;
+
+    Ok((routes, pos_offset, neg_offset))
+}
+
+/// Snaps the offsets to the nodes of the path that are within the configured snap distance, as long
+/// as the offsets don't cover the whole path once snapped.
+fn snap_offsets<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    routes: &CandidateRoutes<G::EdgeId>,
+    pos_offset: Length,
+    neg_offset: Length,
+) -> Result<(Length, Length), G::Error> {
+    let max_distance = config.offset_snap_distance;
+    if max_distance <= Length::ZERO {
+        return Ok((pos_offset, neg_offset));
+    }
+
+    let snapped_pos = snap_offset(graph, routes.edges(), pos_offset, max_distance)?;
+    let snapped_neg = snap_offset(graph, routes.edges().rev(), neg_offset, max_distance)?;
+
+    if snapped_pos + snapped_neg < routes_length(graph, routes)? {
+        debug!("Offsets {pos_offset} {neg_offset} snapped to {snapped_pos} {snapped_neg}");
+        Ok((snapped_pos, snapped_neg))
+    } else {
+        Ok((pos_offset, neg_offset))
+    }
+}
+
+/// Gets the distance from the start of the edges to the node closest to the offset, if within the
+/// max distance, otherwise returns the offset unchanged.
+fn snap_offset<G: DirectedGraph>(
+    graph: &G,
+    mut edges: impl Iterator<Item = G::EdgeId>,
+    offset: Length,
+    max_distance: Length,
+) -> Result<Length, G::Error> {
+    let distance = |node: Length| Length::from_meters((offset - node).meters().abs());
+
+    let mut snapped = offset;
+    let mut snapped_distance = max_distance;
+    let mut node = Length::ZERO;
+
+    loop {
+        if distance(node) <= snapped_distance {
+            snapped_distance = distance(node);
+            snapped = node;
+        }
+
+        // the next nodes are even farther from the offset
+        if node > offset + max_distance {
+            break;
+        }
+
+        let Some(edge) = edges.next() else {
+            break;
+        };
+        node += graph.get_edge_length(edge)?;
+    }
+
+    Ok(snapped)
+}
+
+/// Returns the length of the concatenated routes, from the length of their edges.
+fn routes_length<G: DirectedGraph>(
+    graph: &G,
+    routes: &CandidateRoutes<G::EdgeId>,
+) -> Result<Length, G::Error> {
+    routes.edges().try_fold(Length::ZERO, |acc, edge| {
+        Ok(acc + graph.get_edge_length(edge)?)
+    })
+}
+
+/// Rejects the offsets if they cover a larger part of the path than allowed by the configuration.
+fn check_offsets_ratio<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    routes: &CandidateRoutes<G::EdgeId>,
+    pos_offset: Length,
+    neg_offset: Length,
+) -> Result<(), DecodeError<G::Error>> {
+    let length = routes_length(graph, routes)?;
+    if (pos_offset + neg_offset).meters() > config.max_offsets_ratio * length.meters() {
+        return Err(LocationError::OffsetsExceedLength {
+            pos_offset,
+            neg_offset,
+            length,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Decodes the line location, and if enabled by the configuration, retries without the first or
+/// the last LRP when it's the only one that has no candidate lines.
+///
+/// The offset that refers to the removed LRP is dropped. When the last LRP is removed the previous
+/// LRP becomes the last one, and its line is expected to be the continuation of the incoming line.
+pub fn decode_line_with_endpoint_trimming<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    line: Line,
+    mut report: Option<&mut DecodeReport<G::EdgeId>>,
+    warnings: &mut Vec<DecodeWarning>,
+) -> Result<LineLocation<G::EdgeId>, DecodeError<G::Error>> {
+    if !config.trim_unmatched_endpoints || line.points.len() < 3 {
+        return decode_line(config, graph, line, report, warnings);
+    }
+
+    let mut trimmed_line = line.clone();
+    let (index, lrp) = match decode_line(config, graph, line, report.as_deref_mut(), warnings) {
+        Err(DecodeError::CandidatesNotFound { index, lrp }) => (index, lrp),
+        result => return result,
+    };
+
+    let Line { points, offsets } = &mut trimmed_line;
+    let (warning, first_lrp) = if points.first() == Some(&lrp) {
+        points.remove(0);
+        offsets.pos = Offset::ZERO;
+        (DecodeWarning::HeadTrimmed(lrp.coordinate), 1)
+    } else if points.last() == Some(&lrp) {
+        points.pop();
+        offsets.neg = Offset::ZERO;
+
+        if let Some(last) = points.last_mut() {
+            into_last_point(last);
+        }
+
+        (DecodeWarning::TailTrimmed(lrp.coordinate), 0)
+    } else {
+        return Err(DecodeError::CandidatesNotFound { index, lrp });
+    };
+
+    debug!("Decoding without unmatched LRP: {warning}");
+    warnings.push(warning);
+    decode_line(config, graph, trimmed_line, report, warnings)
+        .map_err(|error| shift_lrp_index(error, first_lrp))
+}
+
+/// Decodes the line location, and if enabled by the configuration, splits the location where no
+/// route is found between two consecutive LRPs and decodes each part on its own.
+///
+/// The longest decoded part is returned, every gap between the parts is flagged with a warning,
+/// and all the parts are recorded in the report (together with the diagnostics of the attempt to
+/// decode the whole location). Parts made of a single LRP cannot be decoded and become part of the
+/// gaps. Fails if no part can be decoded, or if a part fails for any reason other than a missing
+/// route.
+pub fn decode_line_with_partial_matches<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    line: Line,
+    mut report: Option<&mut DecodeReport<G::EdgeId>>,
+    warnings: &mut Vec<DecodeWarning>,
+) -> Result<LineLocation<G::EdgeId>, DecodeError<G::Error>> {
+    if !config.allow_partial_matches {
+        return decode_line_with_endpoint_trimming(config, graph, line, report, warnings);
+    }
+
+    let points = line.points.clone();
+    let error = match decode_line_with_endpoint_trimming(
+        config,
+        graph,
+        line.clone(),
+        report.as_deref_mut(),
+        warnings,
+    ) {
+        Err(error @ DecodeError::RouteNotFound { .. }) => error,
+        result => return result,
+    };
+
+    let mut parts = vec![];
+    decode_line_parts(config, graph, line, 0, &mut parts, warnings)?;
+
+    // the LRPs before, between and after the decoded parts form the gaps
+    let mut gaps = vec![];
+    let mut gap_start = 0;
+    let bounds = parts.iter().map(|&(first, last, _)| (first, last));
+    for (first, last) in bounds.chain([(points.len() - 1, points.len() - 1)]) {
+        if first > gap_start {
+            gaps.push(RouteGap {
+                first_lrp: gap_start,
+                last_lrp: first,
+                dnp: points[gap_start..first].iter().map(Point::dnp).sum(),
+            });
+        }
+        gap_start = last;
+    }
+
+    let mut longest: Option<(Length, &LineLocation<G::EdgeId>)> = None;
+    for (_, _, location) in &parts {
+        let length = path_length(graph, &location.path)?;
+        let length = length - location.pos_offset - location.neg_offset;
+
+        if longest.is_none_or(|(longest, _)| length > longest) {
+            longest = Some((length, location));
+        }
+    }
+
+    let Some((_, location)) = longest else {
+        return Err(error);
+    };
+    let location = location.clone();
+
+    for gap in &gaps {
+        debug!("Decoding without route: {gap:?}");
+        warnings.push(DecodeWarning::RouteGap {
+            first: gap.first_lrp,
+            last: gap.last_lrp,
+        });
+    }
+
+    if let Some(report) = report {
+        report.partial_match = Some(PartialMatch {
+            segments: parts.into_iter().map(|(_, _, location)| location).collect(),
+            gaps,
+        });
+    }
 
     Ok(location)
 }
 
-pub fn decode_point_along_line<G: DirectedGraph>(
-    config: &DecoderConfig,
+/// Decodes the line (whose first LRP has the given index in the whole location) into parts,
+/// splitting it recursively where no route is found. The parts are collected with the indices of
+/// their first and last LRPs.
+#[allow(clippy::type_complexity)]
+fn decode_line_parts<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    line: Line,
+    first_lrp: usize,
+    parts: &mut Vec<(usize, usize, LineLocation<G::EdgeId>)>,
+    warnings: &mut Vec<DecodeWarning>,
+) -> Result<(), DecodeError<G::Error>> {
+    let last_lrp = first_lrp + line.points.len() - 1;
+
+    let (lrps, stats) =
+        match decode_line_with_endpoint_trimming(config, graph, line.clone(), None, warnings) {
+            Ok(location) => {
+                parts.push((first_lrp, last_lrp, location));
+                return Ok(());
+            }
+            Err(DecodeError::RouteNotFound { lrps, stats, .. }) => (lrps, stats),
+            Err(error) => return Err(shift_lrp_index(error, first_lrp)),
+        };
+
+    // trimmed endpoints can differ from the original LRPs but their coordinates
+    let Some(index) = line
+        .points
+        .windows(2)
+        .position(|w| w[0].coordinate == lrps.0.coordinate && w[1].coordinate == lrps.1.coordinate)
+    else {
+        return Err(DecodeError::RouteNotFound {
+            index: first_lrp,
+            lrps,
+            stats,
+        });
+    };
+
+    let Line {
+        mut points,
+        offsets,
+    } = line;
+    let tail = Line {
+        points: points.split_off(index + 1),
+        offsets: Offsets {
+            pos: Offset::ZERO,
+            neg: offsets.neg,
+        },
+    };
+
+    if let Some(last) = points.last_mut() {
+        into_last_point(last);
+    }
+
+    let head = Line {
+        points,
+        offsets: Offsets {
+            pos: offsets.pos,
+            neg: Offset::ZERO,
+        },
+    };
+
+    if head.points.len() > 1 {
+        decode_line_parts(config, graph, head, first_lrp, parts, warnings)?;
+    }
+
+    if tail.points.len() > 1 {
+        decode_line_parts(config, graph, tail, first_lrp + index + 1, parts, warnings)?;
+    }
+
+    Ok(())
+}
+
+/// Shifts the LRP index of the error by the index of the first LRP of the decoded line in the whole
+/// location.
+fn shift_lrp_index<E>(error: DecodeError<E>, first_lrp: usize) -> DecodeError<E> {
+    match error {
+        DecodeError::CandidatesNotFound { index, lrp } => DecodeError::CandidatesNotFound {
+            index: index + first_lrp,
+            lrp,
+        },
+        DecodeError::RouteNotFound { index, lrps, stats } => DecodeError::RouteNotFound {
+            index: index + first_lrp,
+            lrps,
+            stats,
+        },
+        DecodeError::CorruptReference {
+            index,
+            dnp,
+            distance,
+        } => DecodeError::CorruptReference {
+            index: index + first_lrp,
+            dnp,
+            distance,
+        },
+        error @ (DecodeError::GraphError(_)
+        | DecodeError::InvalidLocation(_)
+        | DecodeError::LocationTypeNotSupported(_)
+        | DecodeError::DeserializeError(_)
+        | DecodeError::InconsistentGraph(_)
+        | DecodeError::SearchLimitExceeded { .. }) => error,
+    }
+}
+
+/// Turns the LRP into the last LRP of a line: its bearing is computed backward along the incoming
+/// line, and it has no path to a next point.
+fn into_last_point(lrp: &mut Point) {
+    let bearing = (lrp.line.bearing.degrees() + 180) % 360;
+    lrp.line.bearing = Bearing::from_degrees(bearing);
+    lrp.path = None;
+}
+
+/// Fails fast if the LRPs cannot belong to a valid location reference: the distance to next point
+/// of every LRP (but the last) must be positive and cannot be shorter than the straight-line
+/// distance to the next LRP (allowing the next point variance).
+fn check_points_plausibility<E, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    points: &[Point],
+) -> Result<(), DecodeError<E>> {
+    for (index, window) in points.windows(2).enumerate() {
+        let [lrp1, lrp2] = [&window[0], &window[1]];
+        let dnp = lrp1.dnp();
+        let distance = lrp1.coordinate.distance(&lrp2.coordinate);
+
+        if dnp <= Length::ZERO || distance > dnp + config.next_point_variance {
+            debug!("Implausible LRP {index}: {distance} to next LRP but DNP is {dnp}");
+            return Err(DecodeError::CorruptReference {
+                index,
+                dnp,
+                distance,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Collects the warnings of the resolved routes: routes that didn't use the best rated candidate
+/// lines and routes whose length deviates from the DNP by more than half of the allowed variance.
+fn check_routes<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    lines: &[CandidateLines<G::EdgeId>],
+    routes: &CandidateRoutes<G::EdgeId>,
+    warnings: &mut Vec<DecodeWarning>,
+) -> Result<(), DecodeError<G::Error>> {
+    for (index, (route, window)) in routes.iter().zip(lines.windows(2)).enumerate() {
+        let is_best_candidate = |candidates: &CandidateLines<G::EdgeId>, edge| {
+            candidates.best_candidate().map(|line| line.edge) == Some(edge)
+        };
+
+        if !is_best_candidate(&window[0], route.first_candidate_edge())
+            || !is_best_candidate(&window[1], route.last_candidate_edge())
+        {
+            warnings.push(DecodeWarning::CandidateFallback(index));
+        }
+
+        let dnp = route.first_candidate().lrp.dnp();
+        let deviation =
+            Length::from_meters((route.distance_between_lrps(graph)? - dnp).meters().abs());
+
+        if deviation > config.next_point_variance * 0.5 {
+            warnings.push(DecodeWarning::DnpDeviation {
+                index,
+                dnp,
+                deviation,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+pub fn decode_point_along_line<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
     graph: &G,
     point: PointAlongLine,
+    report: Option<&mut DecodeReport<G::EdgeId>>,
+    warnings: &mut Vec<DecodeWarning>,
 ) -> Result<PointAlongLineLocation<G::EdgeId>, DecodeError<G::Error>> {
     debug!("Decoding {point:?} with {config:?}");
 
@@ -65,24 +545,32 @@ pub fn decode_point_along_line<G: DirectedGraph>(
         offsets: Offsets::positive(point.offset),
     };
 
-    let line = decode_line(config, graph, line)?;
+    let line = decode_line(config, graph, line, report, warnings)?;
+    let coordinate = get_path_coordinate(graph, &line.path, line.pos_offset)?;
 
     Ok(PointAlongLineLocation {
         path: line.path,
         offset: line.pos_offset,
         orientation: point.orientation,
-        side: point.side,
+        side: if config.left_hand_traffic {
+            point.side.mirrored()
+        } else {
+            point.side
+        },
+        coordinate,
     })
 }
 
-pub fn decode_poi<G: DirectedGraph>(
-    config: &DecoderConfig,
+pub fn decode_poi<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
     graph: &G,
     poi: Poi,
+    report: Option<&mut DecodeReport<G::EdgeId>>,
+    warnings: &mut Vec<DecodeWarning>,
 ) -> Result<PoiLocation<G::EdgeId>, DecodeError<G::Error>> {
     debug!("Decoding {poi:?} with {config:?}");
 
-    let point = decode_point_along_line(config, graph, poi.point)?;
+    let point = decode_point_along_line(config, graph, poi.point, report, warnings)?;
 
     Ok(PoiLocation {
         point,
@@ -90,10 +578,12 @@ pub fn decode_poi<G: DirectedGraph>(
     })
 }
 
-pub fn decode_closed_line<G: DirectedGraph>(
-    config: &DecoderConfig,
+pub fn decode_closed_line<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
     graph: &G,
     mut line: ClosedLine,
+    report: Option<&mut DecodeReport<G::EdgeId>>,
+    warnings: &mut Vec<DecodeWarning>,
 ) -> Result<ClosedLineLocation<G::EdgeId>, DecodeError<G::Error>> {
     debug!("Decoding {line:?} with {config:?}");
 
@@ -110,7 +600,7 @@ pub fn decode_closed_line<G: DirectedGraph>(
         offsets: Offsets::ZERO,
     };
 
-    let line = decode_line(config, graph, line)?;
+    let line = decode_line(config, graph, line, report, warnings)?;
     debug_assert_eq!(line.pos_offset, Length::ZERO);
     debug_assert_eq!(line.neg_offset, Length::ZERO);
 
@@ -123,7 +613,11 @@ mod tests {
 
     use super::*;
     use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
-    use crate::{DecoderConfig, Length, Location, Orientation, SideOfRoad, decode_base64_openlr};
+    use crate::{
+        DecoderConfig, EncoderConfig, Frc, Length, Location, LocationReference, Offset,
+        OffsetBucketPosition, Orientation, SideOfRoad, decode_base64_openlr,
+        decode_base64_openlr_with_warnings, deserialize_base64_openlr, encode_base64_openlr,
+    };
 
     #[test]
     fn decode_line_location_reference_001() {
@@ -164,6 +658,87 @@ mod tests {
         );
     }
 
+    #[test]
+    fn decode_line_location_reference_untrimmed_offsets() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        // the positive offset covers the whole first edge (136m) of the location
+        let Ok(LocationReference::Line(mut line)) =
+            deserialize_base64_openlr("CwmShiVYczPJBgCs/y0zAQ==")
+        else {
+            unreachable!()
+        };
+        line.offsets.pos = Offset::from_bucket(100);
+        let path = vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)];
+        let pos_offset = line.offsets.pos.meters(path_length(graph, &path).unwrap());
+        assert!(pos_offset > graph.get_edge_length(EdgeId(8717174)).unwrap());
+
+        let config = DecoderConfig {
+            trim_offset_edges: false,
+            ..Default::default()
+        };
+        let untrimmed = decode_line(&config, graph, line.clone(), None, &mut vec![]).unwrap();
+        let trimmed =
+            decode_line(&DecoderConfig::default(), graph, line, None, &mut vec![]).unwrap();
+
+        assert_eq!(
+            untrimmed,
+            LineLocation {
+                path,
+                pos_offset,
+                neg_offset: Length::ZERO,
+            }
+        );
+        assert!(untrimmed.path.len() > trimmed.path.len());
+        assert_eq!(trimmed.path, [EdgeId(8717175), EdgeId(109783)]);
+        assert_eq!(untrimmed.trim(graph), Ok(trimmed));
+    }
+
+    #[test]
+    fn decode_line_location_reference_search_limits() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let config = DecoderConfig {
+            max_visited_nodes: Some(1),
+            ..Default::default()
+        };
+        let location = decode_base64_openlr(&config, graph, "CwmTaSVYpTPZCP4a/5UjYQUH");
+        assert!(matches!(
+            location,
+            Err(DecodeError::SearchLimitExceeded {
+                visited_nodes: 2,
+                ..
+            })
+        ));
+
+        let config = DecoderConfig {
+            max_visited_nodes: Some(1000),
+            max_search_time: Some(std::time::Duration::from_secs(60)),
+            ..Default::default()
+        };
+        assert!(decode_base64_openlr(&config, graph, "CwmTaSVYpTPZCP4a/5UjYQUH").is_ok());
+    }
+
+    #[test]
+    fn decode_error_source() {
+        use std::error::Error;
+
+        use crate::DeserializeError;
+
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let config = DecoderConfig::default();
+        let Err(error) = decode_base64_openlr(&config, graph, "not base64!") else {
+            unreachable!()
+        };
+
+        assert!(matches!(error, DecodeError::DeserializeError(_)));
+        assert_eq!(
+            error.source().map(|source| source.to_string()),
+            Some(DeserializeError::InvalidBase64.to_string())
+        );
+    }
+
     #[test]
     fn decode_point_along_line_location_reference_001() {
         let graph: &NetworkGraph = &NETWORK_GRAPH;
@@ -178,7 +753,372 @@ mod tests {
                 offset: Length::from_meters(39.98046875),
                 orientation: Orientation::Unknown,
                 side: SideOfRoad::OnRoadOrUnknown,
+                coordinate: graph
+                    .get_coordinate_along_edge(EdgeId(109782), Length::from_meters(39.98046875))
+                    .ok(),
             })
         );
     }
+
+    #[test]
+    fn decode_point_along_line_left_hand_traffic() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let Ok(Location::PointAlongLine(mut point)) =
+            decode_base64_openlr(&DecoderConfig::default(), graph, "KwmTQyVYUDPRA/+y/2czQTk=")
+        else {
+            unreachable!()
+        };
+        point.side = SideOfRoad::Right;
+
+        let encoder_config = EncoderConfig {
+            left_hand_traffic: true,
+            ..Default::default()
+        };
+        let code = encode_base64_openlr(
+            &encoder_config,
+            graph,
+            Location::PointAlongLine(point.clone()),
+        )
+        .unwrap();
+
+        let Ok(LocationReference::PointAlongLine(reference)) = deserialize_base64_openlr(&code)
+        else {
+            unreachable!()
+        };
+        assert_eq!(reference.side, SideOfRoad::Left);
+
+        let decoder_config = DecoderConfig {
+            left_hand_traffic: true,
+            ..Default::default()
+        };
+        let Ok(Location::PointAlongLine(decoded)) =
+            decode_base64_openlr(&decoder_config, graph, &code)
+        else {
+            unreachable!()
+        };
+        assert_eq!(decoded.side, SideOfRoad::Right);
+    }
+
+    #[test]
+    fn decode_line_location_reference_warnings_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let config = DecoderConfig::default();
+        let (_, warnings) =
+            decode_base64_openlr_with_warnings(&config, graph, "CwmShiVYczPJBgCs/y0zAQ==").unwrap();
+
+        assert_eq!(warnings, vec![]);
+    }
+
+    #[test]
+    fn decode_line_location_reference_warnings_002() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let config = DecoderConfig::default();
+        let LocationReference::Line(mut line) =
+            deserialize_base64_openlr("CwmShiVYczPJBgCs/y0zAQ==").unwrap()
+        else {
+            unreachable!()
+        };
+
+        let path = line.points[0].path.as_mut().unwrap();
+        let dnp = path.dnp + Length::from_meters(100.0);
+        path.dnp = dnp;
+
+        let mut warnings = vec![];
+        let location = decode_line(&config, graph, line, None, &mut warnings).unwrap();
+
+        assert_eq!(
+            location.path,
+            vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)]
+        );
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(matches!(
+            warnings[0],
+            DecodeWarning::DnpDeviation { index: 0, dnp: d, deviation }
+                if d == dnp && deviation > Length::from_meters(75.0)
+        ));
+    }
+
+    fn encode_with_intermediates() -> Line {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let config = EncoderConfig {
+            max_lrp_distance: Length::from_meters(150.0),
+            ..Default::default()
+        };
+
+        let location = Location::Line(LineLocation {
+            path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        });
+
+        let LocationReference::Line(line) =
+            deserialize_base64_openlr(encode_base64_openlr(&config, graph, location).unwrap())
+                .unwrap()
+        else {
+            unreachable!()
+        };
+
+        assert!(line.points.len() > 2);
+        line
+    }
+
+    #[test]
+    fn decode_line_endpoint_trimming_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let mut line = encode_with_intermediates();
+        line.points[0].coordinate.lon += 0.1;
+        let unmatched = line.points[0].coordinate;
+        // keep the LRPs plausible, the first LRP is just not matched by the graph
+        let distance = unmatched.distance(&line.points[1].coordinate);
+        if let Some(path) = line.points[0].path.as_mut() {
+            path.dnp = distance;
+        }
+
+        let config = DecoderConfig::default();
+        let mut warnings = vec![];
+        let location =
+            decode_line_with_endpoint_trimming(&config, graph, line.clone(), None, &mut warnings);
+        assert!(matches!(
+            location,
+            Err(DecodeError::CandidatesNotFound { index: 0, .. })
+        ));
+
+        let config = DecoderConfig {
+            trim_unmatched_endpoints: true,
+            ..Default::default()
+        };
+        let location =
+            decode_line_with_endpoint_trimming(&config, graph, line, None, &mut warnings).unwrap();
+
+        assert_eq!(
+            location,
+            LineLocation {
+                path: vec![EdgeId(8717175), EdgeId(109783)],
+                pos_offset: Length::ZERO,
+                neg_offset: Length::ZERO
+            }
+        );
+        assert_eq!(warnings, vec![DecodeWarning::HeadTrimmed(unmatched)]);
+    }
+
+    #[test]
+    fn decode_line_endpoint_trimming_002() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let mut line = encode_with_intermediates();
+        let last = line.points.len() - 1;
+        line.points[last].coordinate.lat += 0.1;
+        let unmatched = line.points[last].coordinate;
+        // keep the LRPs plausible, the last LRP is just not matched by the graph
+        let distance = unmatched.distance(&line.points[last - 1].coordinate);
+        if let Some(path) = line.points[last - 1].path.as_mut() {
+            path.dnp = distance;
+        }
+
+        let config = DecoderConfig {
+            trim_unmatched_endpoints: true,
+            ..Default::default()
+        };
+
+        let mut warnings = vec![];
+        let location =
+            decode_line_with_endpoint_trimming(&config, graph, line, None, &mut warnings).unwrap();
+
+        assert_eq!(
+            location.path,
+            vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)]
+        );
+        assert_eq!(location.pos_offset, Length::ZERO);
+        assert!(location.neg_offset > Length::ZERO);
+        assert_eq!(warnings, vec![DecodeWarning::TailTrimmed(unmatched)]);
+    }
+
+    #[test]
+    fn decode_line_partial_match_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let mut line = encode_with_intermediates();
+        let last = line.points.len() - 1;
+        // no route to the last LRP is made of lines important enough to be accepted
+        if let Some(path) = line.points[last - 1].path.as_mut() {
+            path.lfrcnp = Frc::Frc0;
+        }
+        let gap_dnp = line.points[last - 1].dnp();
+
+        let config = DecoderConfig::default();
+        let location =
+            decode_line_with_partial_matches(&config, graph, line.clone(), None, &mut vec![]);
+        assert!(matches!(location, Err(DecodeError::RouteNotFound { .. })));
+
+        let config = DecoderConfig {
+            allow_partial_matches: true,
+            ..Default::default()
+        };
+
+        let mut report = DecodeReport::default();
+        let mut warnings = vec![];
+        let location = decode_line_with_partial_matches(
+            &config,
+            graph,
+            line,
+            Some(&mut report),
+            &mut warnings,
+        )
+        .unwrap();
+
+        assert_eq!(location.path.first(), Some(&EdgeId(8717174)));
+        assert_eq!(location.pos_offset, Length::ZERO);
+        assert_eq!(
+            warnings.last(),
+            Some(&DecodeWarning::RouteGap {
+                first: last - 1,
+                last
+            })
+        );
+
+        let PartialMatch { segments, gaps } = report.partial_match.unwrap();
+        assert_eq!(segments, [location]);
+        assert_eq!(gaps.len(), 1);
+        assert_eq!((gaps[0].first_lrp, gaps[0].last_lrp), (last - 1, last));
+        assert_eq!(gaps[0].dnp, gap_dnp);
+    }
+
+    #[test]
+    fn decode_line_offsets_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let data = "CwmTaSVYpTPZCP4a/5UjYQUH";
+
+        let decode = |config: &DecoderConfig| {
+            let Location::Line(location) = decode_base64_openlr(config, graph, data).unwrap()
+            else {
+                unreachable!()
+            };
+            location
+        };
+
+        let location = decode(&DecoderConfig {
+            offset_bucket_position: OffsetBucketPosition::Start,
+            ..Default::default()
+        });
+        assert!(location.pos_offset < Length::from_meters(10.505859375));
+        assert!(location.neg_offset < Length::from_meters(14.326171875));
+
+        let location = decode(&DecoderConfig {
+            offset_bucket_position: OffsetBucketPosition::End,
+            ..Default::default()
+        });
+        assert!(location.pos_offset > Length::from_meters(10.505859375));
+        assert!(location.neg_offset > Length::from_meters(14.326171875));
+    }
+
+    #[test]
+    fn decode_line_offsets_002() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let data = "CwmTaSVYpTPZCP4a/5UjYQUH";
+
+        let config = DecoderConfig {
+            offset_snap_distance: Length::from_meters(12.0),
+            ..Default::default()
+        };
+        let location = decode_base64_openlr(&config, graph, data).unwrap();
+
+        assert_eq!(
+            location,
+            Location::Line(LineLocation {
+                path: vec![
+                    EdgeId(1653344),
+                    EdgeId(4997411),
+                    EdgeId(5359424),
+                    EdgeId(5359425)
+                ],
+                pos_offset: Length::ZERO,
+                neg_offset: Length::from_meters(14.326171875)
+            })
+        );
+
+        let config = DecoderConfig {
+            offset_snap_distance: Length::from_meters(15.0),
+            ..Default::default()
+        };
+        let location = decode_base64_openlr(&config, graph, data).unwrap();
+
+        assert_eq!(
+            location,
+            Location::Line(LineLocation {
+                path: vec![
+                    EdgeId(1653344),
+                    EdgeId(4997411),
+                    EdgeId(5359424),
+                    EdgeId(5359425)
+                ],
+                pos_offset: Length::ZERO,
+                neg_offset: Length::ZERO
+            })
+        );
+    }
+
+    #[test]
+    fn decode_line_offsets_003() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let config = DecoderConfig {
+            max_offsets_ratio: 0.01,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            decode_base64_openlr(&config, graph, "CwmTaSVYpTPZCP4a/5UjYQUH"),
+            Err(DecodeError::InvalidLocation(
+                LocationError::OffsetsExceedLength { .. }
+            ))
+        ));
+
+        // the location has no offsets
+        let location = decode_base64_openlr(&config, graph, "CwmShiVYczPJBgCs/y0zAQ==").unwrap();
+        assert_eq!(
+            location,
+            Location::Line(LineLocation {
+                path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+                pos_offset: Length::ZERO,
+                neg_offset: Length::ZERO
+            })
+        );
+    }
+
+    #[test]
+    fn decode_line_plausibility_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let config = DecoderConfig::default();
+
+        let mut line = encode_with_intermediates();
+        line.points[1].coordinate.lat += 0.01;
+
+        let location = decode_line(&config, graph, line, None, &mut vec![]);
+        assert!(matches!(
+            location,
+            Err(DecodeError::CorruptReference { index: 0, dnp, distance })
+                if distance > dnp + config.next_point_variance
+        ));
+    }
+
+    #[test]
+    fn decode_line_plausibility_002() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let mut line = encode_with_intermediates();
+        if let Some(path) = line.points[1].path.as_mut() {
+            path.dnp = Length::ZERO;
+        }
+
+        let location = decode_line(&DecoderConfig::default(), graph, line, None, &mut vec![]);
+        assert!(matches!(
+            location,
+            Err(DecodeError::CorruptReference { index: 1, dnp, .. }) if dnp.is_zero()
+        ));
+    }
 }
@@ -0,0 +1,107 @@
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// Receives the counters and the durations of the decoder work, to export them as metrics (for
+/// example Prometheus counters and histograms) without parsing the logs.
+///
+/// The decoder calls:
+/// - [`on_candidate_lines`](Self::on_candidate_lines) once per LRP of the line locations, with the
+///   number of candidate lines found for it;
+/// - [`on_route_search`](Self::on_route_search) once per shortest path search between two LRPs
+///   (searches answered by a memoized graph are not run, hence not recorded);
+/// - [`on_retry`](Self::on_retry) each time the resolver tries another candidate lines pair after
+///   the first one of two consecutive LRPs;
+/// - [`on_decode`](Self::on_decode) once per location reference, when its decoding ends.
+///
+/// The metrics are shared by all the decodings that use the same config (also across threads),
+/// so they are recorded through a shared reference. Every method does nothing by default.
+pub trait DecoderMetrics: Debug + Sync {
+    /// Called with the number of candidate lines found for an LRP.
+    fn on_candidate_lines(&self, count: usize) {
+        let _ = count;
+    }
+
+    /// Called after a shortest path search between two LRPs, with the number of nodes it expanded,
+    /// the time it took and whether a route was found (false also if the search was aborted by
+    /// the search limits).
+    fn on_route_search(&self, expanded_nodes: usize, elapsed: Duration, found: bool) {
+        let _ = (expanded_nodes, elapsed, found);
+    }
+
+    /// Called when the resolver retries another candidate lines pair of two consecutive LRPs.
+    fn on_retry(&self) {}
+
+    /// Called when the decoding of a location reference ends, with the time it took and whether
+    /// it succeeded.
+    fn on_decode(&self, elapsed: Duration, success: bool) {
+        let _ = (elapsed, success);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{NETWORK_GRAPH, NetworkGraph};
+    use crate::{DecoderConfig, decode_base64_openlr, decode_binary_openlr};
+
+    #[derive(Debug, Default)]
+    struct Counters {
+        candidate_lines: AtomicUsize,
+        route_searches: AtomicUsize,
+        expanded_nodes: AtomicUsize,
+        retries: AtomicUsize,
+        decodings: AtomicUsize,
+        failures: AtomicUsize,
+    }
+
+    impl DecoderMetrics for Counters {
+        fn on_candidate_lines(&self, count: usize) {
+            self.candidate_lines.fetch_add(count, Ordering::Relaxed);
+        }
+
+        fn on_route_search(&self, expanded_nodes: usize, _: Duration, _: bool) {
+            self.route_searches.fetch_add(1, Ordering::Relaxed);
+            self.expanded_nodes
+                .fetch_add(expanded_nodes, Ordering::Relaxed);
+        }
+
+        fn on_retry(&self) {
+            self.retries.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_decode(&self, _: Duration, success: bool) {
+            self.decodings.fetch_add(1, Ordering::Relaxed);
+            if !success {
+                self.failures.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    #[test]
+    fn decoder_metrics_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let counters: &'static Counters = Box::leak(Box::default());
+
+        let config = DecoderConfig {
+            metrics: Some(counters),
+            ..DecoderConfig::default()
+        };
+
+        decode_base64_openlr(&config, graph, "CwmShiVYczPJBgCs/y0zAQ==").unwrap();
+        assert_eq!(counters.decodings.load(Ordering::Relaxed), 1);
+        assert_eq!(counters.failures.load(Ordering::Relaxed), 0);
+        assert!(counters.candidate_lines.load(Ordering::Relaxed) >= 2);
+        assert_eq!(
+            counters.route_searches.load(Ordering::Relaxed) == 0,
+            counters.expanded_nodes.load(Ordering::Relaxed) == 0
+        );
+
+        decode_binary_openlr(&config, graph, &[]).unwrap_err();
+        assert_eq!(counters.decodings.load(Ordering::Relaxed), 2);
+        assert_eq!(counters.failures.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,295 @@
+//! Stages of the decoder pipeline.
+//!
+//! Each stage is defined by a trait with a default implementation that follows the OpenLR
+//! specification. A stage can be replaced (e.g. when it misbehaves on a specific map) without
+//! re-implementing the other stages of the decoder, see [`DecoderConfig::with_candidate_finder`].
+
+use std::fmt::Debug;
+
+use crate::decoder::candidates::{
+    CandidateLinePair, CandidateLines, find_candidate_nodes, find_lrps_candidate_lines,
+};
+use crate::decoder::report::RejectedPair;
+use crate::decoder::resolver::{
+    resolve_routes, resolve_routes_with_rejections, resolve_top_k_candidate_pairs,
+};
+use crate::decoder::route::CandidateRoutes;
+use crate::{DecodeError, DecoderConfig, DirectedGraph, Length, Offsets, Point};
+
+/// Finds the candidate lines of each Location Reference Point (LRP) and rates them.
+pub trait CandidateFinder: Debug {
+    /// Returns the candidate lines of each LRP (in the same order of the LRPs). The candidate lines
+    /// of each LRP are sorted by rating (descending) and cannot be empty.
+    fn find_candidates<G: DirectedGraph, S: DecoderStages>(
+        &self,
+        config: &DecoderConfig<S>,
+        graph: &G,
+        points: Vec<Point>,
+    ) -> Result<Vec<CandidateLines<G::EdgeId>>, DecodeError<G::Error>>;
+}
+
+/// Selects the candidate line pairs of two consecutive LRPs that will be tried by the route
+/// resolver.
+pub trait PairSelector: Debug {
+    /// Returns the candidate line pairs in the order they should be tried (best first).
+    /// The best single line edge (if any) is the edge that is the best candidate of all the LRPs
+    /// but that didn't form a valid route.
+    fn select_pairs<G: DirectedGraph, S: DecoderStages>(
+        &self,
+        config: &DecoderConfig<S>,
+        graph: &G,
+        lines_lrp1: &CandidateLines<G::EdgeId>,
+        lines_lrp2: &CandidateLines<G::EdgeId>,
+        best_single_line_edge: Option<G::EdgeId>,
+    ) -> Result<Vec<CandidateLinePair<G::EdgeId>>, DecodeError<G::Error>>;
+}
+
+/// Resolves the routes between all the consecutive LRPs.
+pub trait RouteResolver: Debug {
+    /// Returns one route for each pair of consecutive LRPs, the routes must be connected.
+    fn resolve_routes<G: DirectedGraph, S: DecoderStages>(
+        &self,
+        config: &DecoderConfig<S>,
+        graph: &G,
+        candidate_lines: &[CandidateLines<G::EdgeId>],
+        offsets: Offsets,
+    ) -> Result<CandidateRoutes<G::EdgeId>, DecodeError<G::Error>>;
+
+    /// Resolves the routes like [`RouteResolver::resolve_routes`], collecting the candidate line
+    /// pairs that were tried but rejected. By default no rejected pair is collected.
+    fn resolve_routes_with_rejections<G: DirectedGraph, S: DecoderStages>(
+        &self,
+        config: &DecoderConfig<S>,
+        graph: &G,
+        candidate_lines: &[CandidateLines<G::EdgeId>],
+        offsets: Offsets,
+        rejected: &mut Vec<RejectedPair<G::EdgeId>>,
+    ) -> Result<CandidateRoutes<G::EdgeId>, DecodeError<G::Error>> {
+        let _ = rejected;
+        self.resolve_routes(config, graph, candidate_lines, offsets)
+    }
+}
+
+/// Resolves the offsets of the location along the path of the routes.
+pub trait OffsetResolver: Debug {
+    /// Returns the positive and negative offsets, negative values are trimmed by the decoder.
+    fn resolve_offsets<G: DirectedGraph, S: DecoderStages>(
+        &self,
+        config: &DecoderConfig<S>,
+        graph: &G,
+        routes: &CandidateRoutes<G::EdgeId>,
+        offsets: Offsets,
+    ) -> Result<(Length, Length), DecodeError<G::Error>>;
+}
+
+/// The stages used by the decoder.
+pub trait DecoderStages: Debug {
+    type CandidateFinder: CandidateFinder;
+    type PairSelector: PairSelector;
+    type RouteResolver: RouteResolver;
+    type OffsetResolver: OffsetResolver;
+
+    fn candidate_finder(&self) -> &Self::CandidateFinder;
+    fn pair_selector(&self) -> &Self::PairSelector;
+    fn route_resolver(&self) -> &Self::RouteResolver;
+    fn offset_resolver(&self) -> &Self::OffsetResolver;
+}
+
+/// Pipeline of decoder stages, each stage uses its default implementation unless overridden.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DecoderPipeline<
+    C = DefaultCandidateFinder,
+    P = DefaultPairSelector,
+    R = DefaultRouteResolver,
+    O = DefaultOffsetResolver,
+> {
+    pub candidate_finder: C,
+    pub pair_selector: P,
+    pub route_resolver: R,
+    pub offset_resolver: O,
+}
+
+impl<C, P, R, O> DecoderStages for DecoderPipeline<C, P, R, O>
+where
+    C: CandidateFinder,
+    P: PairSelector,
+    R: RouteResolver,
+    O: OffsetResolver,
+{
+    type CandidateFinder = C;
+    type PairSelector = P;
+    type RouteResolver = R;
+    type OffsetResolver = O;
+
+    fn candidate_finder(&self) -> &C {
+        &self.candidate_finder
+    }
+
+    fn pair_selector(&self) -> &P {
+        &self.pair_selector
+    }
+
+    fn route_resolver(&self) -> &R {
+        &self.route_resolver
+    }
+
+    fn offset_resolver(&self) -> &O {
+        &self.offset_resolver
+    }
+}
+
+/// Finds the candidate nodes close to each LRP and their candidate lines, projecting the LRP into
+/// close-by lines when needed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultCandidateFinder;
+
+impl CandidateFinder for DefaultCandidateFinder {
+    fn find_candidates<G: DirectedGraph, S: DecoderStages>(
+        &self,
+        config: &DecoderConfig<S>,
+        graph: &G,
+        points: Vec<Point>,
+    ) -> Result<Vec<CandidateLines<G::EdgeId>>, DecodeError<G::Error>> {
+        let nodes = find_candidate_nodes(config, graph, points)?;
+        find_lrps_candidate_lines(config, graph, nodes)
+    }
+}
+
+/// Selects the top K rated candidate line pairs, where K is bounded by the maximum number of
+/// resolver retries.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultPairSelector;
+
+impl PairSelector for DefaultPairSelector {
+    fn select_pairs<G: DirectedGraph, S: DecoderStages>(
+        &self,
+        config: &DecoderConfig<S>,
+        _: &G,
+        lines_lrp1: &CandidateLines<G::EdgeId>,
+        lines_lrp2: &CandidateLines<G::EdgeId>,
+        best_single_line_edge: Option<G::EdgeId>,
+    ) -> Result<Vec<CandidateLinePair<G::EdgeId>>, DecodeError<G::Error>> {
+        Ok(resolve_top_k_candidate_pairs(
+            config,
+            lines_lrp1,
+            lines_lrp2,
+            best_single_line_edge,
+        ))
+    }
+}
+
+/// Resolves the shortest routes between the candidate line pairs selected by the pair selector.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultRouteResolver;
+
+impl RouteResolver for DefaultRouteResolver {
+    fn resolve_routes<G: DirectedGraph, S: DecoderStages>(
+        &self,
+        config: &DecoderConfig<S>,
+        graph: &G,
+        candidate_lines: &[CandidateLines<G::EdgeId>],
+        offsets: Offsets,
+    ) -> Result<CandidateRoutes<G::EdgeId>, DecodeError<G::Error>> {
+        resolve_routes(config, graph, candidate_lines, offsets)
+    }
+
+    fn resolve_routes_with_rejections<G: DirectedGraph, S: DecoderStages>(
+        &self,
+        config: &DecoderConfig<S>,
+        graph: &G,
+        candidate_lines: &[CandidateLines<G::EdgeId>],
+        offsets: Offsets,
+        rejected: &mut Vec<RejectedPair<G::EdgeId>>,
+    ) -> Result<CandidateRoutes<G::EdgeId>, DecodeError<G::Error>> {
+        resolve_routes_with_rejections(config, graph, candidate_lines, offsets, rejected)
+    }
+}
+
+/// Calculates the offsets from the location reference offsets and the projections of the first
+/// and last LRPs into the routes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultOffsetResolver;
+
+impl OffsetResolver for DefaultOffsetResolver {
+    fn resolve_offsets<G: DirectedGraph, S: DecoderStages>(
+        &self,
+        _: &DecoderConfig<S>,
+        graph: &G,
+        routes: &CandidateRoutes<G::EdgeId>,
+        offsets: Offsets,
+    ) -> Result<(Length, Length), DecodeError<G::Error>> {
+        routes.calculate_offsets(graph, offsets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{NETWORK_GRAPH, NetworkGraph};
+    use crate::{Location, decode_base64_openlr};
+
+    #[derive(Debug, Clone, Copy)]
+    struct ZeroOffsetResolver;
+
+    impl OffsetResolver for ZeroOffsetResolver {
+        fn resolve_offsets<G: DirectedGraph, S: DecoderStages>(
+            &self,
+            _: &DecoderConfig<S>,
+            _: &G,
+            _: &CandidateRoutes<G::EdgeId>,
+            _: Offsets,
+        ) -> Result<(Length, Length), DecodeError<G::Error>> {
+            Ok((Length::ZERO, Length::ZERO))
+        }
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    struct NoPairSelector;
+
+    impl PairSelector for NoPairSelector {
+        fn select_pairs<G: DirectedGraph, S: DecoderStages>(
+            &self,
+            _: &DecoderConfig<S>,
+            _: &G,
+            _: &CandidateLines<G::EdgeId>,
+            _: &CandidateLines<G::EdgeId>,
+            _: Option<G::EdgeId>,
+        ) -> Result<Vec<CandidateLinePair<G::EdgeId>>, DecodeError<G::Error>> {
+            Ok(vec![])
+        }
+    }
+
+    #[test]
+    fn decoder_pipeline_offset_resolver_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let code = "CwmTaSVYpTPZCP4a/5UjYQUH";
+
+        let Location::Line(line) =
+            decode_base64_openlr(&DecoderConfig::default(), graph, code).unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(line.pos_offset > Length::ZERO);
+
+        let config = DecoderConfig::default().with_offset_resolver(ZeroOffsetResolver);
+        let Location::Line(zero_offsets_line) = decode_base64_openlr(&config, graph, code).unwrap()
+        else {
+            unreachable!()
+        };
+
+        assert_eq!(zero_offsets_line.pos_offset, Length::ZERO);
+        assert_eq!(zero_offsets_line.neg_offset, Length::ZERO);
+    }
+
+    #[test]
+    fn decoder_pipeline_pair_selector_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let config = DecoderConfig::default().with_pair_selector(NoPairSelector);
+        let location = decode_base64_openlr(&config, graph, "CwmTaSVYpTPZCP4a/5UjYQUH");
+
+        assert!(matches!(location, Err(DecodeError::RouteNotFound { .. })));
+    }
+}
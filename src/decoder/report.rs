@@ -0,0 +1,378 @@
+use crate::decoder::candidates::{CandidateLines, find_candidate_nodes, max_line_rating};
+use crate::decoder::route::CandidateRoutes;
+use crate::model::RatingScore;
+use crate::{
+    Coordinate, DecodeError, DecodeWarning, DecoderConfig, DecoderStages, DirectedGraph, Length,
+    LineLocation, Point,
+};
+
+/// Diagnostics collected while decoding a location reference.
+/// Useful to understand (and visualize) how the LRPs were matched against the graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeReport<EdgeId> {
+    /// Candidate nodes and accepted candidate lines of each LRP, in the same order of the LRPs.
+    pub candidates: Vec<LrpCandidates<EdgeId>>,
+    /// Candidate line pairs that were tried but couldn't form a valid route, in the order they
+    /// were tried.
+    pub rejected_pairs: Vec<RejectedPair<EdgeId>>,
+    /// Non-fatal issues found while decoding.
+    pub warnings: Vec<DecodeWarning>,
+    /// Confidence of the decoded location in [0, 1]: the mean rating of the candidate lines used
+    /// by the resolved routes, relative to the highest possible rating.
+    /// None if the routes couldn't be resolved.
+    pub confidence: Option<f64>,
+    /// Resolved routes between each pair of consecutive LRPs, before being concatenated and
+    /// trimmed by the offsets. Empty if the routes couldn't be resolved.
+    pub routes: CandidateRoutes<EdgeId>,
+    /// Parts of the line location decoded on their own because no route was found between some
+    /// of its LRPs, only if partial matches are allowed (see
+    /// [`DecoderConfig::allow_partial_matches`]).
+    pub partial_match: Option<PartialMatch<EdgeId>>,
+}
+
+impl<EdgeId> Default for DecodeReport<EdgeId> {
+    fn default() -> Self {
+        Self {
+            candidates: vec![],
+            rejected_pairs: vec![],
+            warnings: vec![],
+            confidence: None,
+            routes: CandidateRoutes::from(vec![]),
+            partial_match: None,
+        }
+    }
+}
+
+/// Line location decoded in parts, split where no route was found between consecutive LRPs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialMatch<EdgeId> {
+    /// Decoded parts of the location, in the same order of the LRPs.
+    pub segments: Vec<LineLocation<EdgeId>>,
+    /// Parts of the location that couldn't be decoded, before, between or after the segments.
+    pub gaps: Vec<RouteGap>,
+}
+
+/// Part of a line location that couldn't be decoded: no route was found from the first LRP to the
+/// last LRP of the gap (the LRPs in between, if any, are not part of any decoded segment).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RouteGap {
+    /// Index of the first LRP of the gap.
+    pub first_lrp: usize,
+    /// Index of the last LRP of the gap.
+    pub last_lrp: usize,
+    /// Sum of the DNPs of the LRPs of the gap: the expected length of the missing route.
+    pub dnp: Length,
+}
+
+/// Candidate nodes and lines accepted for a Location Reference Point (LRP).
+#[derive(Debug, Clone, PartialEq)]
+pub struct LrpCandidates<EdgeId> {
+    pub lrp: Point,
+    /// Nodes close to the LRP sorted by their distance (ascending).
+    pub nodes: Vec<CandidateNodeReport>,
+    /// Lines sorted by their rating (descending - higher rating is better).
+    pub lines: Vec<CandidateLineReport<EdgeId>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CandidateNodeReport {
+    pub coordinate: Coordinate,
+    pub distance_to_lrp: Length,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CandidateLineReport<EdgeId> {
+    pub edge: EdgeId,
+    pub rating: RatingScore,
+    /// Where the LRP was snapped on the edge, only if the LRP was projected onto the line.
+    pub projection: Option<Projection>,
+}
+
+/// Projection of an LRP onto an edge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Projection {
+    /// Distance from the start vertex of the edge to the projected point (following the edge).
+    pub distance_along_edge: Length,
+    /// Coordinate of the projected point on the edge geometry.
+    pub coordinate: Coordinate,
+}
+
+/// Candidate lines of two consecutive LRPs that were not used to resolve the route between them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RejectedPair<EdgeId> {
+    /// Index of the first LRP of the pair.
+    pub lrp_index: usize,
+    /// Candidate edges of the first and of the second LRP.
+    pub edges: (EdgeId, EdgeId),
+    pub reason: RejectionReason,
+}
+
+/// Why a candidate lines pair was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectionReason {
+    /// No shortest path between the lines within the maximum route length.
+    RouteNotFound,
+    /// The shortest path is shorter than the DNP (allowing the next point variance).
+    RouteTooShort,
+    /// The previous route doesn't end on the first line of the pair and couldn't be re-computed.
+    AlternativeRouteNotFound,
+    /// The route (trimmed by the offsets) is a loop.
+    PathLoop,
+    /// The route turns back along the line an LRP was projected into (see
+    /// [`DecoderConfig::projected_u_turns`]).
+    ProjectedUTurn,
+}
+
+impl<EdgeId: Copy> DecodeReport<EdgeId> {
+    /// Replaces the candidates of a previous decoding attempt (if any) with the candidate nodes.
+    pub(crate) fn record_candidate_nodes<G, S>(
+        &mut self,
+        config: &DecoderConfig<S>,
+        graph: &G,
+        points: &[Point],
+    ) -> Result<(), DecodeError<G::Error>>
+    where
+        G: DirectedGraph<EdgeId = EdgeId>,
+        S: DecoderStages,
+    {
+        self.candidates.clear();
+        self.rejected_pairs.clear();
+
+        for candidate_nodes in find_candidate_nodes(config, graph, points.iter().copied())? {
+            let nodes = candidate_nodes
+                .nodes
+                .into_iter()
+                .map(|node| {
+                    Ok::<_, G::Error>(CandidateNodeReport {
+                        coordinate: graph.get_vertex_coordinate(node.vertex)?,
+                        distance_to_lrp: node.distance_to_lrp,
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+
+            self.candidates.push(LrpCandidates {
+                lrp: candidate_nodes.lrp,
+                nodes,
+                lines: vec![],
+            });
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn record_candidate_lines<G>(
+        &mut self,
+        graph: &G,
+        candidate_lines: &[CandidateLines<EdgeId>],
+    ) -> Result<(), G::Error>
+    where
+        G: DirectedGraph<EdgeId = EdgeId>,
+    {
+        for (i, CandidateLines { lrp, lines }) in candidate_lines.iter().enumerate() {
+            let lines = lines
+                .iter()
+                .map(|line| {
+                    let projection = line
+                        .distance_to_projection
+                        .map(|distance_along_edge| {
+                            Ok::<_, G::Error>(Projection {
+                                distance_along_edge,
+                                coordinate: graph
+                                    .get_coordinate_along_edge(line.edge, distance_along_edge)?,
+                            })
+                        })
+                        .transpose()?;
+
+                    Ok(CandidateLineReport {
+                        edge: line.edge,
+                        rating: line.rating,
+                        projection,
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+
+            if let Some(candidates) = self.candidates.get_mut(i) {
+                candidates.lines = lines;
+            } else {
+                self.candidates.push(LrpCandidates {
+                    lrp: *lrp,
+                    nodes: vec![],
+                    lines,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn record_routes<S: DecoderStages>(
+        &mut self,
+        config: &DecoderConfig<S>,
+        routes: &CandidateRoutes<EdgeId>,
+    ) {
+        self.routes = routes.clone();
+        let ratings: Vec<f64> = routes
+            .iter()
+            .enumerate()
+            .flat_map(|(i, route)| {
+                // the first candidate of a route is the last candidate of the previous route
+                let first = (i == 0).then(|| route.first_candidate().rating);
+                first.into_iter().chain([route.last_candidate().rating])
+            })
+            .map(f64::from)
+            .collect();
+
+        if ratings.is_empty() {
+            return;
+        }
+
+        let mean = ratings.iter().sum::<f64>() / ratings.len() as f64;
+        let confidence = mean / f64::from(max_line_rating(config));
+        self.confidence = Some(confidence.clamp(0.0, 1.0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+    use crate::{
+        DecoderConfig, Location, LocationReference, decode_base64_openlr_with_report,
+        deserialize_base64_openlr,
+    };
+
+    #[test]
+    fn decoder_report_projected_candidates_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let config = DecoderConfig {
+            min_line_rating: RatingScore::from(500.0),
+            ..Default::default()
+        };
+
+        let mut report = DecodeReport::default();
+        decode_base64_openlr_with_report(&config, graph, "CwmShiVYczPJBgCs/y0zAQ==", &mut report)
+            .unwrap();
+
+        assert_eq!(report.candidates.len(), 2);
+        assert!(report.rejected_pairs.is_empty());
+        assert!(report.confidence.is_some_and(|c| c > 0.5 && c <= 1.0));
+
+        let projected: Vec<_> = report
+            .candidates
+            .iter()
+            .flat_map(|c| &c.lines)
+            .filter_map(|line| Some((line.edge, line.projection?)))
+            .collect();
+
+        assert!(!projected.is_empty());
+
+        for (edge, projection) in projected {
+            let distance = graph
+                .get_distance_along_edge(edge, projection.coordinate)
+                .unwrap();
+            assert_eq!(distance.round(), projection.distance_along_edge.round());
+        }
+
+        let line = report.candidates[1]
+            .lines
+            .iter()
+            .find(|line| line.edge == EdgeId(-6828301))
+            .unwrap();
+
+        assert_eq!(
+            line.projection
+                .map(|p| (p.distance_along_edge.round(), p.coordinate)),
+            Some((
+                Length::from_meters(58.0),
+                Coordinate {
+                    lon: 13.4618471,
+                    lat: 52.5143784
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn decoder_report_routes_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let code = "CwmTaSVYpTPZCP4a/5UjYQUH";
+
+        let mut report = DecodeReport::default();
+        let location =
+            decode_base64_openlr_with_report(&DecoderConfig::default(), graph, code, &mut report)
+                .unwrap();
+
+        let Ok(LocationReference::Line(line)) = deserialize_base64_openlr(code) else {
+            unreachable!()
+        };
+
+        assert_eq!(report.routes.len(), line.points.len() - 1);
+        assert_eq!(report.routes.segments().count(), report.routes.len());
+        assert_eq!(
+            report.routes.segment(0),
+            report.routes.first().map(|r| &r.path)
+        );
+        assert!(report.routes.segment(report.routes.len()).is_none());
+
+        let line_location = report.routes.to_line_location(graph, line.offsets).unwrap();
+        assert_eq!(location, Location::Line(line_location));
+    }
+
+    #[test]
+    fn decoder_report_rejected_pairs_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let config = DecoderConfig {
+            next_point_variance: Length::ZERO,
+            ..Default::default()
+        };
+
+        let mut report = DecodeReport::default();
+        let location = decode_base64_openlr_with_report(
+            &config,
+            graph,
+            "CwmShiVYczPJBgCs/y0zAQ==",
+            &mut report,
+        );
+
+        assert!(matches!(location, Err(DecodeError::RouteNotFound { .. })));
+        assert!(report.routes.is_empty());
+        assert_eq!(report.confidence, None);
+
+        let nodes: Vec<_> = report.candidates.iter().map(|c| c.nodes.len()).collect();
+        assert_eq!(nodes, [2, 9]);
+
+        for LrpCandidates { lrp, nodes, .. } in &report.candidates {
+            assert!(
+                nodes
+                    .windows(2)
+                    .all(|w| w[0].distance_to_lrp <= w[1].distance_to_lrp)
+            );
+            for node in nodes {
+                let distance = lrp.coordinate.distance(&node.coordinate);
+                assert_eq!(distance.round(), node.distance_to_lrp.round());
+            }
+        }
+
+        assert_eq!(report.rejected_pairs.len(), 9);
+        assert!(report.rejected_pairs.iter().all(|pair| pair.lrp_index == 0));
+        assert_eq!(
+            report.rejected_pairs[..2],
+            [
+                RejectedPair {
+                    lrp_index: 0,
+                    edges: (EdgeId(8717174), EdgeId(109783)),
+                    reason: RejectionReason::RouteTooShort,
+                },
+                RejectedPair {
+                    lrp_index: 0,
+                    edges: (EdgeId(8717174), EdgeId(6770340)),
+                    reason: RejectionReason::RouteNotFound,
+                },
+            ]
+        );
+    }
+}
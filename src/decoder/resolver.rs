@@ -1,16 +1,41 @@
 use std::cmp::Reverse;
 use std::collections::BinaryHeap;
 use std::fmt::Debug;
+use std::time::Instant;
 
 use rustc_hash::FxHashMap;
 use tracing::debug;
 
 use crate::decoder::candidates::{CandidateLine, CandidateLinePair, CandidateLines};
+use crate::decoder::consistency::check_routes_connected;
+use crate::decoder::pipeline::PairSelector;
+use crate::decoder::report::{RejectedPair, RejectionReason};
 use crate::decoder::route::{CandidateRoute, CandidateRoutes};
-use crate::decoder::shortest_path::shortest_path;
-use crate::graph::path::{Path, is_path_connected, is_path_loop};
+use crate::decoder::shortest_path::{SearchLimits, shortest_path};
+use crate::graph::path::{Path, is_opposite_direction, is_path_loop};
 use crate::model::RatingScore;
-use crate::{DecodeError, DecoderConfig, DirectedGraph, Frc, Length, Offsets};
+use crate::{
+    DecodeError, DecoderConfig, DecoderStages, DirectedGraph, Length, Offsets, RouteSearchStats,
+};
+
+/// A route between a candidate lines pair, or why the pair was rejected.
+type ResolvedRoute<EdgeId> = Result<CandidateRoute<EdgeId>, RejectionReason>;
+
+/// Handling of the routes that turn back along the line an LRP was projected into (for example a
+/// route from an LRP projected into a line to the next LRP projected into the opposite line).
+/// Such routes make an implicit U-turn that is not allowed by the road network, usually because
+/// the LRPs were matched on the wrong carriageway.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UTurnPolicy {
+    /// U-turns are accepted as any other route.
+    #[default]
+    Allow,
+    /// Routes with a U-turn are only accepted if no other candidate lines pair of the same LRPs
+    /// can be resolved into a route.
+    Penalize,
+    /// Routes with a U-turn are rejected.
+    Reject,
+}
 
 /// The decoder needs to compute a shortest-path between each pair of subsequent location reference
 /// points. For each pair of location reference points suitable candidate lines must be chosen. The
@@ -42,11 +67,23 @@ use crate::{DecodeError, DecoderConfig, DirectedGraph, Frc, Length, Offsets};
 /// distance to next point information of the first location reference point of a pair. If the
 /// length information differ too much the decoder could decide to try a different pair of candidate
 /// lines (see also Step – 5) or to fail and report an error.
-pub fn resolve_routes<G: DirectedGraph>(
-    config: &DecoderConfig,
+pub fn resolve_routes<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    candidate_lines: &[CandidateLines<G::EdgeId>],
+    offsets: Offsets,
+) -> Result<CandidateRoutes<G::EdgeId>, DecodeError<G::Error>> {
+    resolve_routes_with_rejections(config, graph, candidate_lines, offsets, &mut vec![])
+}
+
+/// Resolves the routes like [`resolve_routes`], collecting the candidate line pairs that were
+/// tried but rejected.
+pub fn resolve_routes_with_rejections<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
     graph: &G,
     candidate_lines: &[CandidateLines<G::EdgeId>],
     offsets: Offsets,
+    rejected: &mut Vec<RejectedPair<G::EdgeId>>,
 ) -> Result<CandidateRoutes<G::EdgeId>, DecodeError<G::Error>> {
     debug!("Resolving routes for {} LRPs", candidate_lines.len());
     let best_edge = find_best_candidate_edge(candidate_lines);
@@ -55,49 +92,266 @@ pub fn resolve_routes<G: DirectedGraph>(
         resolve_single_line_routes(graph, candidate_lines, best_edge, offsets).transpose()
     }) {
         let routes = routes?;
-        debug_assert!(is_path_connected(graph, &routes.to_path())?);
+        check_routes_connected(config, graph, &routes)?;
         return Ok(routes);
     }
 
-    let mut routes: CandidateRoutes<_> = Vec::with_capacity(candidate_lines.len() - 1).into();
+    let routes_count = candidate_lines.len() - 1;
+    let mut routes: CandidateRoutes<_> = Vec::with_capacity(routes_count).into();
+    let mut lrp_pairs: Vec<LrpPairs<G::EdgeId>> = Vec::with_capacity(routes_count);
+
+    let start = Instant::now();
+    let mut retries = 0;
+    let mut backtracked_settled_edges = 0;
+    // the furthest pair of LRPs that couldn't be resolved into a route
+    let mut failure: Option<(usize, RouteSearchStats)> = None;
+
+    while routes.len() < routes_count {
+        let lrp_index = routes.len();
+        let [candidates_lrp1, candidates_lrp2] =
+            [&candidate_lines[lrp_index], &candidate_lines[lrp_index + 1]];
+
+        if lrp_pairs.len() == lrp_index {
+            let pairs = config.stages.pair_selector().select_pairs(
+                config,
+                graph,
+                candidates_lrp1,
+                candidates_lrp2,
+                best_edge,
+            )?;
+            lrp_pairs.push(LrpPairs::new(pairs));
+        }
 
-    for window in candidate_lines.windows(2) {
-        let [candidates_lrp1, candidates_lrp2] = [&window[0], &window[1]];
-        let routes_count = routes.len();
+        if let Some(max_time) = config.max_total_search_time
+            && start.elapsed() >= max_time
+        {
+            let settled_edges = lrp_pairs.iter().map(|pairs| pairs.stats.settled_edges);
+            return Err(DecodeError::SearchLimitExceeded {
+                visited_nodes: backtracked_settled_edges + settled_edges.sum::<usize>(),
+                elapsed: start.elapsed(),
+            });
+        }
 
-        let pairs =
-            resolve_top_k_candidate_pairs(config, candidates_lrp1, candidates_lrp2, best_edge);
+        let current = &mut lrp_pairs[lrp_index];
+        let retries_exhausted = config.max_total_retries.is_some_and(|max| retries >= max);
 
         // Find the first candidates pair that can be used to construct a valid route between the
         // two consecutive LRPs, also try to find an alternative route if consecutive best pairs are
         // not connected to each other.
-        for candidates in pairs {
-            let route = resolve_candidate_route(config, graph, candidates)?
-                .map(|route| resolve_alternative_route(config, graph, &mut routes, route))
-                .transpose()?
-                .flatten();
-
-            if let Some(route) = route {
-                let (pos_offset, neg_offset) = route.calculate_offsets(graph, offsets)?;
-                if !is_path_loop(graph, &route.path.edges, pos_offset, neg_offset)? {
-                    routes.push(route);
-                    break;
+        if let Some(candidates) = current.pairs.get(current.next).cloned()
+            && (current.next == 0 || !retries_exhausted)
+        {
+            if current.next > 0 {
+                retries += 1;
+                if let Some(metrics) = config.metrics {
+                    metrics.on_retry();
                 }
             }
+            current.next += 1;
+            current.stats.pairs_tried += 1;
+
+            try_candidate_pair(
+                config,
+                graph,
+                &mut routes,
+                current,
+                candidates,
+                offsets,
+                lrp_index,
+                rejected,
+            )?;
+            continue;
+        }
+
+        // route with a projected U-turn tried only if no other pair is accepted
+        if let Some(route) = current.penalized_route.take() {
+            let edges = (
+                route.candidates.line_lrp1.edge,
+                route.candidates.line_lrp2.edge,
+            );
+
+            if let Err(reason) = push_route(
+                config,
+                graph,
+                &mut routes,
+                route,
+                offsets,
+                &mut current.stats,
+            )? {
+                rejected.push(RejectedPair {
+                    lrp_index,
+                    edges,
+                    reason,
+                });
+            }
+            continue;
+        }
+
+        if failure.is_none_or(|(index, _)| index <= lrp_index) {
+            failure = Some((lrp_index, current.stats));
         }
 
-        if routes.len() == routes_count {
-            return Err(DecodeError::RouteNotFound((
-                candidates_lrp1.lrp,
-                candidates_lrp2.lrp,
-            )));
+        // revisit the candidate lines pairs of the previous LRPs
+        if config.backtracking && lrp_index > 0 && !retries_exhausted {
+            debug!("Backtracking from LRP {lrp_index}");
+            backtracked_settled_edges += current.stats.settled_edges;
+            lrp_pairs.pop();
+            routes.pop();
+            continue;
         }
+
+        let (index, stats) = failure.unwrap_or((lrp_index, current.stats));
+        return Err(DecodeError::RouteNotFound {
+            index,
+            lrps: (candidate_lines[index].lrp, candidate_lines[index + 1].lrp),
+            stats: Box::new(stats),
+        });
     }
 
-    debug_assert!(is_path_connected(graph, &routes.to_path())?);
+    check_routes_connected(config, graph, &routes)?;
     Ok(routes)
 }
 
+/// The candidate lines pairs of two consecutive LRPs, in the order they are tried, and the state of
+/// their resolution into a route.
+#[derive(Debug)]
+struct LrpPairs<EdgeId> {
+    pairs: Vec<CandidateLinePair<EdgeId>>,
+    /// Index of the next pair to try.
+    next: usize,
+    /// Route with a projected U-turn, tried only if no other pair is accepted.
+    penalized_route: Option<CandidateRoute<EdgeId>>,
+    stats: RouteSearchStats,
+}
+
+impl<EdgeId> LrpPairs<EdgeId> {
+    fn new(pairs: Vec<CandidateLinePair<EdgeId>>) -> Self {
+        Self {
+            pairs,
+            next: 0,
+            penalized_route: None,
+            stats: RouteSearchStats::default(),
+        }
+    }
+}
+
+/// Tries to resolve the candidate lines pair into a route and to push it into the routes, records
+/// the pair as rejected otherwise. Routes with a projected U-turn are set aside if penalized.
+#[allow(clippy::too_many_arguments)]
+fn try_candidate_pair<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    routes: &mut CandidateRoutes<G::EdgeId>,
+    lrp_pairs: &mut LrpPairs<G::EdgeId>,
+    candidates: CandidateLinePair<G::EdgeId>,
+    offsets: Offsets,
+    lrp_index: usize,
+    rejected: &mut Vec<RejectedPair<G::EdgeId>>,
+) -> Result<(), DecodeError<G::Error>> {
+    let edges = (candidates.line_lrp1.edge, candidates.line_lrp2.edge);
+    let stats = &mut lrp_pairs.stats;
+
+    let reason = match resolve_candidate_route(config, graph, candidates, stats)? {
+        Ok(route)
+            if config.projected_u_turns != UTurnPolicy::Allow
+                && has_projected_u_turn(graph, &route)? =>
+        {
+            if config.projected_u_turns == UTurnPolicy::Penalize
+                && lrp_pairs.penalized_route.is_none()
+            {
+                lrp_pairs.penalized_route = Some(route);
+                return Ok(());
+            }
+            RejectionReason::ProjectedUTurn
+        }
+        Ok(route) => match push_route(config, graph, routes, route, offsets, stats)? {
+            Ok(()) => {
+                if let Some(route) = lrp_pairs.penalized_route.take() {
+                    rejected.push(RejectedPair {
+                        lrp_index,
+                        edges: (
+                            route.candidates.line_lrp1.edge,
+                            route.candidates.line_lrp2.edge,
+                        ),
+                        reason: RejectionReason::ProjectedUTurn,
+                    });
+                }
+                return Ok(());
+            }
+            Err(reason) => reason,
+        },
+        Err(reason) => reason,
+    };
+
+    rejected.push(RejectedPair {
+        lrp_index,
+        edges,
+        reason,
+    });
+    Ok(())
+}
+
+/// Pushes the route (or its alternative) into the routes, unless its path trimmed by the offsets
+/// is a loop. Returns why the route was rejected otherwise.
+fn push_route<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    routes: &mut CandidateRoutes<G::EdgeId>,
+    route: CandidateRoute<G::EdgeId>,
+    offsets: Offsets,
+    stats: &mut RouteSearchStats,
+) -> Result<Result<(), RejectionReason>, DecodeError<G::Error>> {
+    let route = match resolve_alternative_route(config, graph, routes, route, stats)? {
+        Ok(route) => route,
+        Err(reason) => return Ok(Err(reason)),
+    };
+
+    let (pos_offset, neg_offset) = route.calculate_offsets(graph, offsets)?;
+    if is_path_loop(graph, &route.path.edges, pos_offset, neg_offset)? {
+        return Ok(Err(RejectionReason::PathLoop));
+    }
+
+    routes.push(route);
+    Ok(Ok(()))
+}
+
+/// Returns true if the route turns back along the line of one of its LRPs that was projected into
+/// the line: the projected line is followed (or preceded) by the line in the opposite direction.
+fn has_projected_u_turn<G: DirectedGraph>(
+    graph: &G,
+    route: &CandidateRoute<G::EdgeId>,
+) -> Result<bool, G::Error> {
+    let CandidateLinePair {
+        line_lrp1,
+        line_lrp2,
+    } = &route.candidates;
+
+    let edges = &route.path.edges;
+
+    // the edge of LRP2 is not part of the route if LRP2 is not the last LRP
+    let last_edges = if line_lrp2.lrp.is_last() {
+        edges.len().checked_sub(2).map(|i| (edges[i], edges[i + 1]))
+    } else {
+        edges.last().map(|&edge| (edge, line_lrp2.edge))
+    };
+
+    if line_lrp1.is_projected()
+        && let [first, second, ..] = edges.as_slice()
+        && is_opposite_direction(graph, *first, *second)?
+    {
+        return Ok(true);
+    }
+
+    if line_lrp2.is_projected()
+        && let Some((previous, last)) = last_edges
+        && is_opposite_direction(graph, previous, last)?
+    {
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
 /// Returns Some edge only if all the candidate lines have the same edge as best candidate.
 /// Otherwise returns None.
 fn find_best_candidate_edge<EdgeId: Copy + PartialEq>(
@@ -142,11 +396,7 @@ fn resolve_single_line_routes<G: DirectedGraph>(
                 vec![]
             };
 
-            let length = edges
-                .iter()
-                .try_fold(Length::ZERO, |acc, &e| Ok(acc + graph.get_edge_length(e)?))?;
-
-            let path = Path { length, edges };
+            let path = Path::from_edges(graph, edges)?;
             Ok::<_, G::Error>(CandidateRoute { path, candidates })
         })
         .collect::<Result<_, _>>()?;
@@ -163,11 +413,12 @@ fn resolve_single_line_routes<G: DirectedGraph>(
     Ok(Some(routes))
 }
 
-fn resolve_candidate_route<G: DirectedGraph>(
-    config: &DecoderConfig,
+fn resolve_candidate_route<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
     graph: &G,
     candidates: CandidateLinePair<G::EdgeId>,
-) -> Result<Option<CandidateRoute<G::EdgeId>>, DecodeError<G::Error>> {
+    stats: &mut RouteSearchStats,
+) -> Result<ResolvedRoute<G::EdgeId>, DecodeError<G::Error>> {
     let CandidateLinePair {
         line_lrp1:
             CandidateLine {
@@ -190,30 +441,42 @@ fn resolve_candidate_route<G: DirectedGraph>(
             vec![]
         };
 
-        let length = edges.iter().try_fold(Length::ZERO, |acc, &e| {
-            Ok::<_, G::Error>(acc + graph.get_edge_length(e)?)
-        })?;
-
-        let path = Path { length, edges };
-        return Ok(Some(CandidateRoute { path, candidates }));
+        let path = Path::from_edges(graph, edges)?;
+        return Ok(Ok(CandidateRoute { path, candidates }));
     }
 
     // LRP1 lfrcnp (lowest FRC to the next point) encoded up to edge before LRP2, but the shortest
     // path implementation is edge-based and checks include the destination edge (LRP2 first edge)
     let destination_frc = graph.get_edge_frc(edge_lrp2)?;
-    let lfrcnp = Frc::from_value(lrp1.lfrcnp().value() + Frc::variance(&lrp1.lfrcnp()));
-    let lfrcnp = lfrcnp.unwrap_or(Frc::Frc7).max(destination_frc);
+    let lfrcnp = config.frc_variance.lowest_accepted(lrp1.lfrcnp());
+    let lfrcnp = lfrcnp.unwrap_or(lrp1.lfrcnp()).max(destination_frc);
 
     let max_length = max_route_length(config, graph, &candidates)?;
 
     debug!("Finding route: {edge_lrp1:?} -> {edge_lrp2:?} (max={max_length} lfrcnp={lfrcnp:?})");
 
-    if let Some(mut path) = shortest_path(graph, edge_lrp1, edge_lrp2, lfrcnp, max_length)? {
+    let limits = SearchLimits {
+        max_visited_nodes: config.max_visited_nodes,
+        max_search_time: config.max_search_time,
+    };
+
+    let path = shortest_path(
+        graph,
+        edge_lrp1,
+        edge_lrp2,
+        lfrcnp,
+        max_length,
+        config.route_cost,
+        &limits,
+        stats,
+        config.metrics,
+    )?;
+    if let Some(mut path) = path {
         let min_length = lrp1.dnp() - config.next_point_variance;
 
         if path.length < min_length {
             debug!("{path:?} length is shorter than expected: {min_length}");
-            return Ok(None);
+            return Ok(Err(RejectionReason::RouteTooShort));
         }
 
         if !lrp2.is_last()
@@ -226,21 +489,23 @@ fn resolve_candidate_route<G: DirectedGraph>(
         debug_assert!(path.length <= max_length, "{} > {max_length}", path.length);
 
         debug!("Route found: {edge_lrp1:?} -> {edge_lrp2:?}: {path:?}");
-        return Ok(Some(CandidateRoute { path, candidates }));
+        return Ok(Ok(CandidateRoute { path, candidates }));
     }
 
     debug!("Route not found: {edge_lrp1:?} -> {edge_lrp2:?}");
-    Ok(None)
+    Ok(Err(RejectionReason::RouteNotFound))
 }
 
 /// Updates the last route with an alternative if this cannot be connected to the given new route.
-/// Returns the new given route or None if the altenative is needed but cannot be computed.
-fn resolve_alternative_route<G: DirectedGraph>(
-    config: &DecoderConfig,
+/// Returns the new given route or the rejection reason if the altenative is needed but cannot be
+/// computed.
+fn resolve_alternative_route<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
     graph: &G,
     routes: &mut [CandidateRoute<G::EdgeId>],
     new_route: CandidateRoute<G::EdgeId>,
-) -> Result<Option<CandidateRoute<G::EdgeId>>, DecodeError<G::Error>> {
+    stats: &mut RouteSearchStats,
+) -> Result<ResolvedRoute<G::EdgeId>, DecodeError<G::Error>> {
     if let Some(last_route) = routes.last_mut() {
         // if the previous route ends on a line that is not the start of this new route
         // then the previous route needs to be re-computed
@@ -250,19 +515,19 @@ fn resolve_alternative_route<G: DirectedGraph>(
                 line_lrp2: new_route.first_candidate(),
             };
 
-            if let Some(route) = resolve_candidate_route(config, graph, candidates)? {
+            if let Ok(route) = resolve_candidate_route(config, graph, candidates, stats)? {
                 *last_route = route;
             } else {
-                return Ok(None);
+                return Ok(Err(RejectionReason::AlternativeRouteNotFound));
             }
         }
     }
 
-    Ok(Some(new_route))
+    Ok(Ok(new_route))
 }
 
-fn max_route_length<G: DirectedGraph>(
-    config: &DecoderConfig,
+fn max_route_length<G: DirectedGraph, S: DecoderStages>(
+    config: &DecoderConfig<S>,
     graph: &G,
     candidates: &CandidateLinePair<G::EdgeId>,
 ) -> Result<Length, DecodeError<G::Error>> {
@@ -286,8 +551,11 @@ fn max_route_length<G: DirectedGraph>(
     Ok(max_distance.ceil())
 }
 
-fn resolve_top_k_candidate_pairs<EdgeId: Debug + Copy + PartialEq>(
-    config: &DecoderConfig,
+/// Selects the K best rated candidate line pairs (K being the max number of retries plus one), sorted
+/// by rating (descending). Ties are broken by bearing difference (ascending) and then by the edges
+/// of the first and of the second LRP, so that the order is the same on every run and platform.
+pub fn resolve_top_k_candidate_pairs<EdgeId: Debug + Copy + Ord, S: DecoderStages>(
+    config: &DecoderConfig<S>,
     lines_lrp1: &CandidateLines<EdgeId>,
     lines_lrp2: &CandidateLines<EdgeId>,
     best_single_line_edge: Option<EdgeId>,
@@ -322,24 +590,25 @@ fn resolve_top_k_candidate_pairs<EdgeId: Debug + Copy + PartialEq>(
             pair_ratings.push(Reverse(pair_rating));
 
             if pair_ratings.len() <= k_size {
-                rating_pairs
-                    .entry(pair_rating)
-                    .or_default()
-                    .push(candidate_pair);
-
+                insert_candidate_pair(rating_pairs.entry(pair_rating).or_default(), candidate_pair);
                 continue;
             }
 
             let worst_rating = match pair_ratings.pop() {
-                Some(Reverse(rating)) if pair_rating <= rating => continue,
+                Some(Reverse(rating)) if pair_rating < rating => continue,
+                Some(Reverse(rating)) if pair_rating == rating => {
+                    // replaces the worst pair of equal rating if the bearings match better
+                    if let Some(pairs) = rating_pairs.get_mut(&rating) {
+                        insert_candidate_pair(pairs, candidate_pair);
+                        pairs.pop();
+                    }
+                    continue;
+                }
                 Some(Reverse(rating)) => rating,
                 None => continue,
             };
 
-            rating_pairs
-                .entry(pair_rating)
-                .or_default()
-                .push(candidate_pair);
+            insert_candidate_pair(rating_pairs.entry(pair_rating).or_default(), candidate_pair);
 
             if let Some(pairs) = rating_pairs.get_mut(&worst_rating)
                 && pairs.len() > 1
@@ -353,7 +622,8 @@ fn resolve_top_k_candidate_pairs<EdgeId: Debug + Copy + PartialEq>(
 
     let mut candidates = Vec::with_capacity(k_size);
     while let Some(Reverse(rating)) = pair_ratings.pop() {
-        candidates.extend(rating_pairs.remove(&rating).into_iter().flatten());
+        // pairs of equal rating are reversed here since all the candidates are reversed below
+        candidates.extend(rating_pairs.remove(&rating).into_iter().flatten().rev());
     }
     candidates.reverse();
 
@@ -377,14 +647,32 @@ fn resolve_top_k_candidate_pairs<EdgeId: Debug + Copy + PartialEq>(
     candidates
 }
 
+/// Inserts the candidate pair into pairs of equal rating, keeping them sorted by bearing difference
+/// (ascending) so that ties prefer the lines whose bearings best match the LRPs. Pairs with equal
+/// bearing difference are sorted by their edges.
+fn insert_candidate_pair<EdgeId: Copy + Ord>(
+    pairs: &mut Vec<CandidateLinePair<EdgeId>>,
+    pair: CandidateLinePair<EdgeId>,
+) {
+    let key = |p: &CandidateLinePair<EdgeId>| {
+        (p.bearing_difference(), p.line_lrp1.edge, p.line_lrp2.edge)
+    };
+    let pair_key = key(&pair);
+    let index = pairs.partition_point(|p| key(p) <= pair_key);
+    pairs.insert(index, pair);
+}
+
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use test_log::test;
 
     use super::*;
     use crate::decoder::candidates::CandidateLine;
-    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
-    use crate::{Bearing, Coordinate, Fow, LineAttributes, PathAttributes, Point};
+    use crate::graph::network::{LineDirection, NetworkLine};
+    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph, VertexId};
+    use crate::{Bearing, Coordinate, Fow, Frc, LineAttributes, PathAttributes, Point};
 
     #[test]
     fn decoder_resolve_top_k_candidate_pairs_001() {
@@ -398,6 +686,7 @@ mod tests {
             edge: 1,
             distance_to_projection: None,
             rating: RatingScore::from(926.3),
+            bearing: Bearing::NORTH,
         };
 
         let line2 = CandidateLine {
@@ -405,6 +694,7 @@ mod tests {
             edge: 2,
             distance_to_projection: Some(Length::from_meters(141.6)),
             rating: RatingScore::from(880.4),
+            bearing: Bearing::NORTH,
         };
 
         let line3 = CandidateLine {
@@ -412,6 +702,7 @@ mod tests {
             edge: 3,
             distance_to_projection: None,
             rating: RatingScore::from(924.9),
+            bearing: Bearing::NORTH,
         };
 
         let line4 = CandidateLine {
@@ -419,6 +710,7 @@ mod tests {
             edge: 4,
             distance_to_projection: None,
             rating: RatingScore::from(100.0),
+            bearing: Bearing::NORTH,
         };
 
         let line5 = CandidateLine {
@@ -426,6 +718,7 @@ mod tests {
             edge: 5,
             distance_to_projection: None,
             rating: RatingScore::from(10.0),
+            bearing: Bearing::NORTH,
         };
 
         let pairs = resolve_top_k_candidate_pairs(
@@ -464,6 +757,106 @@ mod tests {
         );
     }
 
+    #[test]
+    fn decoder_resolve_top_k_candidate_pairs_002() {
+        let config = DecoderConfig {
+            max_number_retries: 1,
+            ..Default::default()
+        };
+
+        let lrp = Point {
+            line: LineAttributes {
+                bearing: Bearing::from_degrees(90),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // parallel lines with equal rating, only their bearing is different
+        let parallel_line = |edge, bearing| CandidateLine {
+            lrp,
+            edge,
+            distance_to_projection: None,
+            rating: RatingScore::from(900.0),
+            bearing: Bearing::from_degrees(bearing),
+        };
+
+        let line1 = parallel_line(1, 110);
+        let line2 = parallel_line(2, 95);
+        let line3 = parallel_line(3, 80);
+        let line4 = parallel_line(4, 90);
+
+        let pairs = resolve_top_k_candidate_pairs(
+            &config,
+            &CandidateLines {
+                lrp,
+                lines: vec![line1, line2],
+            },
+            &CandidateLines {
+                lrp,
+                lines: vec![line3, line4],
+            },
+            None,
+        );
+
+        assert_eq!(
+            pairs,
+            [
+                CandidateLinePair {
+                    line_lrp1: line2,
+                    line_lrp2: line4
+                },
+                CandidateLinePair {
+                    line_lrp1: line2,
+                    line_lrp2: line3
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn decoder_resolve_top_k_candidate_pairs_003() {
+        let config = DecoderConfig {
+            max_number_retries: 2,
+            ..Default::default()
+        };
+
+        // equal rating and bearing difference: the pairs are sorted by edges
+        let line = |edge| CandidateLine {
+            lrp: Point::default(),
+            edge,
+            distance_to_projection: None,
+            rating: RatingScore::from(900.0),
+            bearing: Bearing::NORTH,
+        };
+
+        let [line1, line2, line3, line4] = [line(1), line(2), line(3), line(4)];
+
+        for (lines_lrp1, lines_lrp2) in [
+            (vec![line1, line2], vec![line3, line4]),
+            (vec![line2, line1], vec![line4, line3]),
+        ] {
+            let pairs = resolve_top_k_candidate_pairs(
+                &config,
+                &CandidateLines {
+                    lrp: Point::default(),
+                    lines: lines_lrp1,
+                },
+                &CandidateLines {
+                    lrp: Point::default(),
+                    lines: lines_lrp2,
+                },
+                None,
+            );
+
+            let edges: Vec<_> = pairs
+                .iter()
+                .map(|pair| (pair.line_lrp1.edge, pair.line_lrp2.edge))
+                .collect();
+            assert_eq!(edges, [(1, 3), (1, 4), (2, 3)]);
+        }
+    }
+
     #[test]
     fn decoder_resolve_routes_001() {
         let graph: &NetworkGraph = &NETWORK_GRAPH;
@@ -508,6 +901,7 @@ mod tests {
             lrp: first_lrp,
             edge: EdgeId(8717174),
             rating: RatingScore::from(926.3),
+            bearing: Bearing::NORTH,
             distance_to_projection: None,
         };
 
@@ -515,6 +909,7 @@ mod tests {
             lrp: first_lrp,
             edge: EdgeId(4925291),
             rating: RatingScore::from(880.4),
+            bearing: Bearing::NORTH,
             distance_to_projection: Some(Length::from_meters(141.6)),
         };
 
@@ -522,6 +917,7 @@ mod tests {
             lrp: last_lrp,
             edge: EdgeId(109783),
             rating: RatingScore::from(924.9),
+            bearing: Bearing::NORTH,
             distance_to_projection: None,
         };
 
@@ -593,6 +989,7 @@ mod tests {
             lrp: first_lrp,
             edge: EdgeId(8717174),
             rating: RatingScore::from(1128.7),
+            bearing: Bearing::NORTH,
             distance_to_projection: Some(Length::from_meters(29.0)),
         };
 
@@ -600,6 +997,7 @@ mod tests {
             lrp: last_lrp,
             edge: EdgeId(8717174),
             rating: RatingScore::from(1122.7),
+            bearing: Bearing::NORTH,
             distance_to_projection: Some(Length::from_meters(99.0)),
         };
 
@@ -607,6 +1005,7 @@ mod tests {
             lrp: last_lrp,
             edge: EdgeId(4925291),
             rating: RatingScore::from(900.0),
+            bearing: Bearing::NORTH,
             distance_to_projection: None,
         };
 
@@ -694,6 +1093,7 @@ mod tests {
             lrp: first_lrp,
             edge: EdgeId(8717174),
             rating: RatingScore::from(1128.7),
+            bearing: Bearing::NORTH,
             distance_to_projection: Some(Length::from_meters(29.0)),
         };
 
@@ -701,6 +1101,7 @@ mod tests {
             lrp: second_lrp,
             edge: EdgeId(8717174),
             rating: RatingScore::from(1122.7),
+            bearing: Bearing::NORTH,
             distance_to_projection: Some(Length::from_meters(99.0)),
         };
 
@@ -708,6 +1109,7 @@ mod tests {
             lrp: last_lrp,
             edge: EdgeId(109783),
             rating: RatingScore::from(924.9),
+            bearing: Bearing::NORTH,
             distance_to_projection: None,
         };
 
@@ -813,6 +1215,7 @@ mod tests {
             lrp: first_lrp,
             edge: EdgeId(8717174),
             rating: RatingScore::from(1194.8),
+            bearing: Bearing::NORTH,
             distance_to_projection: None,
         };
 
@@ -820,6 +1223,7 @@ mod tests {
             lrp: first_lrp,
             edge: EdgeId(4925291),
             rating: RatingScore::from(1135.3),
+            bearing: Bearing::NORTH,
             distance_to_projection: Some(Length::from_meters(142.0)),
         };
 
@@ -827,6 +1231,7 @@ mod tests {
             lrp: second_lrp,
             edge: EdgeId(6770340),
             rating: RatingScore::from(1193.5),
+            bearing: Bearing::NORTH,
             distance_to_projection: None,
         };
 
@@ -834,6 +1239,7 @@ mod tests {
             lrp: second_lrp,
             edge: EdgeId(109783),
             rating: RatingScore::from(1137.7),
+            bearing: Bearing::NORTH,
             distance_to_projection: Some(Length::from_meters(191.0)),
         };
 
@@ -841,6 +1247,7 @@ mod tests {
             lrp: last_lrp,
             edge: EdgeId(7531947),
             rating: RatingScore::from(1176.0),
+            bearing: Bearing::NORTH,
             distance_to_projection: None,
         };
 
@@ -952,6 +1359,7 @@ mod tests {
             lrp: first_lrp,
             edge: EdgeId(-7292030),
             rating: RatingScore::from(1000.0),
+            bearing: Bearing::NORTH,
             distance_to_projection: None,
         };
 
@@ -959,6 +1367,7 @@ mod tests {
             lrp: second_lrp,
             edge: EdgeId(-5530113),
             rating: RatingScore::from(2000.0),
+            bearing: Bearing::NORTH,
             distance_to_projection: None,
         };
 
@@ -966,6 +1375,7 @@ mod tests {
             lrp: second_lrp,
             edge: EdgeId(-7292029),
             rating: RatingScore::from(100.0),
+            bearing: Bearing::NORTH,
             distance_to_projection: None,
         };
 
@@ -973,6 +1383,7 @@ mod tests {
             lrp: last_lrp,
             edge: EdgeId(-7292028),
             rating: RatingScore::from(1000.0),
+            bearing: Bearing::NORTH,
             distance_to_projection: None,
         };
 
@@ -1094,6 +1505,7 @@ mod tests {
             lrp,
             edge: EdgeId(16218),
             rating: RatingScore::from(1000.0),
+            bearing: Bearing::NORTH,
             distance_to_projection: None,
         };
 
@@ -1150,6 +1562,7 @@ mod tests {
                         },
                         edge: EdgeId(16218),
                         rating: RatingScore::from(1000.0),
+                        bearing: Bearing::NORTH,
                         distance_to_projection: None
                     },
                     line_lrp2: CandidateLine {
@@ -1170,6 +1583,7 @@ mod tests {
                         },
                         edge: EdgeId(16218),
                         rating: RatingScore::from(1000.0),
+                        bearing: Bearing::NORTH,
                         distance_to_projection: None
                     }
                 }
@@ -1202,6 +1616,7 @@ mod tests {
                         },
                         edge: EdgeId(16218),
                         rating: RatingScore::from(1000.0),
+                        bearing: Bearing::NORTH,
                         distance_to_projection: None
                     },
                     line_lrp2: CandidateLine {
@@ -1222,6 +1637,7 @@ mod tests {
                         },
                         edge: EdgeId(16218),
                         rating: RatingScore::from(1000.0),
+                        bearing: Bearing::NORTH,
                         distance_to_projection: None
                     }
                 }
@@ -1254,6 +1670,7 @@ mod tests {
                         },
                         edge: EdgeId(16218),
                         rating: RatingScore::from(1000.0),
+                        bearing: Bearing::NORTH,
                         distance_to_projection: None
                     },
                     line_lrp2: CandidateLine {
@@ -1271,10 +1688,271 @@ mod tests {
                         },
                         edge: EdgeId(16218),
                         rating: RatingScore::from(1000.0),
+                        bearing: Bearing::NORTH,
                         distance_to_projection: None
                     }
                 }
             }
         );
     }
+
+    #[test]
+    fn decoder_resolve_routes_007() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let config = DecoderConfig::default();
+
+        let first_lrp = Point {
+            coordinate: Coordinate {
+                lon: 13.46112,
+                lat: 52.51711,
+            },
+            line: LineAttributes {
+                frc: Frc::Frc6,
+                fow: Fow::SingleCarriageway,
+                bearing: Bearing::from_degrees(107),
+            },
+            path: Some(PathAttributes {
+                lfrcnp: Frc::Frc6,
+                dnp: Length::from_meters(100.0),
+            }),
+        };
+
+        let last_lrp = Point {
+            coordinate: Coordinate {
+                lon: 13.46284,
+                lat: 52.51500,
+            },
+            line: LineAttributes {
+                frc: Frc::Frc6,
+                fow: Fow::SingleCarriageway,
+                bearing: Bearing::from_degrees(17),
+            },
+            path: None,
+        };
+
+        let candidate_lines = [
+            CandidateLines {
+                lrp: first_lrp,
+                lines: vec![CandidateLine {
+                    lrp: first_lrp,
+                    edge: EdgeId(8717174),
+                    rating: RatingScore::from(926.3),
+                    bearing: Bearing::NORTH,
+                    distance_to_projection: None,
+                }],
+            },
+            CandidateLines {
+                lrp: last_lrp,
+                lines: vec![CandidateLine {
+                    lrp: last_lrp,
+                    edge: EdgeId(109783),
+                    rating: RatingScore::from(924.9),
+                    bearing: Bearing::NORTH,
+                    distance_to_projection: None,
+                }],
+            },
+        ];
+
+        let Err(DecodeError::RouteNotFound { index, lrps, stats }) =
+            resolve_routes(&config, graph, &candidate_lines, Offsets::default())
+        else {
+            unreachable!()
+        };
+
+        assert_eq!(index, 0);
+        assert_eq!(lrps, (first_lrp, last_lrp));
+        assert_eq!(stats.pairs_tried, 1);
+        assert!(stats.settled_edges > 0);
+        assert_eq!(stats.max_length, Length::from_meters(250.0));
+        assert!(stats.max_distance_reached <= stats.max_length);
+        assert!(stats.lowest_frc >= Frc::Frc6);
+    }
+
+    #[test]
+    fn decoder_resolve_routes_projected_u_turn() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let first_lrp = Point {
+            coordinate: Coordinate {
+                lon: 13.46284,
+                lat: 52.51500,
+            },
+            line: LineAttributes {
+                frc: Frc::Frc6,
+                fow: Fow::SingleCarriageway,
+                bearing: Bearing::from_degrees(17),
+            },
+            path: Some(PathAttributes {
+                lfrcnp: Frc::Frc6,
+                dnp: Length::from_meters(100.0),
+            }),
+        };
+
+        let last_lrp = Point {
+            path: None,
+            ..first_lrp
+        };
+
+        let line_first_lrp = CandidateLine {
+            lrp: first_lrp,
+            edge: EdgeId(8717175),
+            rating: RatingScore::from(950.0),
+            bearing: Bearing::NORTH,
+            distance_to_projection: Some(Length::from_meters(20.0)),
+        };
+
+        // opposite line of the first LRP projected line
+        let line1_last_lrp = CandidateLine {
+            lrp: last_lrp,
+            edge: EdgeId(-8717175),
+            rating: RatingScore::from(950.0),
+            bearing: Bearing::NORTH,
+            distance_to_projection: Some(Length::from_meters(20.0)),
+        };
+
+        let line2_last_lrp = CandidateLine {
+            lrp: last_lrp,
+            edge: EdgeId(109783),
+            rating: RatingScore::from(900.0),
+            bearing: Bearing::NORTH,
+            distance_to_projection: None,
+        };
+
+        let resolve = |projected_u_turns, last_lines| {
+            let config = DecoderConfig {
+                projected_u_turns,
+                ..Default::default()
+            };
+
+            let candidate_lines = [
+                CandidateLines {
+                    lrp: first_lrp,
+                    lines: vec![line_first_lrp],
+                },
+                CandidateLines {
+                    lrp: last_lrp,
+                    lines: last_lines,
+                },
+            ];
+
+            resolve_routes(&config, graph, &candidate_lines, Offsets::default())
+                .map(|routes| routes.to_path())
+        };
+
+        let u_turn = vec![EdgeId(8717175), EdgeId(-8717175)];
+        let no_u_turn = vec![EdgeId(8717175), EdgeId(109783)];
+        let lines = vec![line1_last_lrp, line2_last_lrp];
+
+        assert_eq!(
+            resolve(UTurnPolicy::Allow, lines.clone()),
+            Ok(u_turn.clone())
+        );
+        assert_eq!(
+            resolve(UTurnPolicy::Penalize, lines.clone()),
+            Ok(no_u_turn.clone())
+        );
+        assert_eq!(resolve(UTurnPolicy::Reject, lines), Ok(no_u_turn));
+
+        assert_eq!(
+            resolve(UTurnPolicy::Penalize, vec![line1_last_lrp]),
+            Ok(u_turn)
+        );
+        assert!(matches!(
+            resolve(UTurnPolicy::Reject, vec![line1_last_lrp]),
+            Err(DecodeError::RouteNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn decoder_resolve_routes_backtracking() {
+        // the best candidate of the second LRP (3) is a dead end, while its other candidate (4) can
+        // only be reached from the worst candidate of the first LRP (2)
+        let coordinate = |vertex: u64| Coordinate {
+            lon: 13.0 + 0.001 * vertex as f64,
+            lat: 52.0,
+        };
+        let line = |id, start, end| NetworkLine {
+            id: EdgeId(id),
+            start: VertexId(start),
+            end: VertexId(end),
+            length: Length::from_meters(100.0),
+            frc: Frc::Frc2,
+            fow: Fow::SingleCarriageway,
+            direction: LineDirection::Forward,
+            geometry: vec![coordinate(start), coordinate(end)],
+        };
+        let graph = &NetworkGraph::from_lines([
+            line(1, 1, 2),
+            line(2, 3, 4),
+            line(3, 2, 5),
+            line(4, 4, 6),
+            line(5, 6, 7),
+        ]);
+
+        let lrp = |dnp: Option<f64>| Point {
+            path: dnp.map(|dnp| PathAttributes {
+                lfrcnp: Frc::Frc7,
+                dnp: Length::from_meters(dnp),
+            }),
+            ..Default::default()
+        };
+        let (lrp1, lrp2, lrp3) = (lrp(Some(100.0)), lrp(Some(100.0)), lrp(None));
+
+        let candidates = |lrp: Point, lines: &[(i64, f64)]| CandidateLines {
+            lrp,
+            lines: lines
+                .iter()
+                .map(|&(edge, rating)| CandidateLine {
+                    lrp,
+                    edge: EdgeId(edge),
+                    rating: RatingScore::from(rating),
+                    bearing: Bearing::NORTH,
+                    distance_to_projection: None,
+                })
+                .collect(),
+        };
+        let candidate_lines = [
+            candidates(lrp1, &[(1, 900.0), (2, 800.0)]),
+            candidates(lrp2, &[(3, 900.0), (4, 800.0)]),
+            candidates(lrp3, &[(5, 900.0)]),
+        ];
+
+        let resolve = |config: DecoderConfig| {
+            resolve_routes(&config, graph, &candidate_lines, Offsets::default())
+        };
+
+        assert!(matches!(
+            resolve(DecoderConfig::default()),
+            Err(DecodeError::RouteNotFound { index: 1, .. })
+        ));
+
+        let routes = resolve(DecoderConfig {
+            backtracking: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let paths: Vec<_> = routes
+            .iter()
+            .map(|route| route.path.edges.clone())
+            .collect();
+        assert_eq!(paths, [vec![EdgeId(2)], vec![EdgeId(4), EdgeId(5)]]);
+
+        assert!(matches!(
+            resolve(DecoderConfig {
+                backtracking: true,
+                max_total_retries: Some(1),
+                ..Default::default()
+            }),
+            Err(DecodeError::RouteNotFound { index: 1, .. })
+        ));
+
+        assert!(matches!(
+            resolve(DecoderConfig {
+                backtracking: true,
+                max_total_search_time: Some(Duration::ZERO),
+                ..Default::default()
+            }),
+            Err(DecodeError::SearchLimitExceeded { .. })
+        ));
+    }
 }
@@ -3,7 +3,7 @@ use std::ops::{Deref, DerefMut};
 
 use crate::decoder::candidates::{CandidateLine, CandidateLinePair};
 use crate::graph::path::Path;
-use crate::{DecodeError, DirectedGraph, Length, Offsets};
+use crate::{DecodeError, DirectedGraph, Length, LineLocation, Offsets};
 
 /// The shortest route between two (consecutive) LRPs.
 #[derive(Debug, Clone, PartialEq)]
@@ -48,6 +48,40 @@ impl<EdgeId: Debug + Copy + PartialEq> CandidateRoutes<EdgeId> {
         self.edges().collect()
     }
 
+    /// Gets the path of each route (sub-path between two consecutive LRPs), in the same order of
+    /// the LRPs.
+    pub fn segments(&self) -> impl DoubleEndedIterator<Item = &Path<EdgeId>> {
+        self.0.iter().map(|r| &r.path)
+    }
+
+    /// Gets the path of the route that starts at the LRP of the given index, if any.
+    pub fn segment(&self, index: usize) -> Option<&Path<EdgeId>> {
+        self.0.get(index).map(|r| &r.path)
+    }
+
+    /// Concatenates the routes into a line location trimmed by the offsets calculated from the
+    /// projections of the LRPs (see [`CandidateRoutes::calculate_offsets`]).
+    /// Negative offsets are clamped to zero.
+    pub fn to_line_location<G>(
+        &self,
+        graph: &G,
+        offsets: Offsets,
+    ) -> Result<LineLocation<EdgeId>, DecodeError<G::Error>>
+    where
+        G: DirectedGraph<EdgeId = EdgeId>,
+    {
+        let (pos_offset, neg_offset) = self.calculate_offsets(graph, offsets)?;
+
+        let location = LineLocation {
+            path: self.to_path(),
+            pos_offset: pos_offset.max(Length::ZERO),
+            neg_offset: neg_offset.max(Length::ZERO),
+        }
+        .trim(graph)?;
+
+        Ok(location)
+    }
+
     /// Gets the positive and negative offsets calculated from the projections of the LRPs
     /// into the first and last route (sub-path) respectively.
     pub fn calculate_offsets<G>(
@@ -119,6 +153,26 @@ impl<EdgeId: Copy> CandidateRoute<EdgeId> {
         }
     }
 
+    /// Gets the distance between the two LRPs when following the route, that is the route length
+    /// without the parts before the first LRP projection and after the second LRP projection.
+    pub fn distance_between_lrps<G>(&self, graph: &G) -> Result<Length, DecodeError<G::Error>>
+    where
+        G: DirectedGraph<EdgeId = EdgeId>,
+    {
+        let last_candidate = self.last_candidate();
+        let length = self.path.length - self.distance_from_start();
+
+        if last_candidate.lrp.is_last() {
+            Ok(length - self.distance_to_end(graph)?)
+        } else {
+            // the route stops at the start of the line of the second LRP
+            Ok(length
+                + last_candidate
+                    .distance_to_projection
+                    .unwrap_or(Length::ZERO))
+        }
+    }
+
     /// Gets the positive and negative offsets calculated from the projections of the LRPs.
     pub fn calculate_offsets<G>(
         &self,
@@ -201,6 +255,7 @@ mod tests {
             lrp: first_lrp,
             edge: EdgeId(8717174),
             rating: RatingScore::from(926.3),
+            bearing: Bearing::NORTH,
             distance_to_projection: None,
         };
 
@@ -208,6 +263,7 @@ mod tests {
             lrp: last_lrp,
             edge: EdgeId(109783),
             rating: RatingScore::from(924.9),
+            bearing: Bearing::NORTH,
             distance_to_projection: None,
         };
 
@@ -266,6 +322,7 @@ mod tests {
             lrp: first_lrp,
             edge: EdgeId(8717174),
             rating: RatingScore::from(926.3),
+            bearing: Bearing::NORTH,
             distance_to_projection: Some(Length::from_meters(10.0)),
         };
 
@@ -273,6 +330,7 @@ mod tests {
             lrp: last_lrp,
             edge: EdgeId(109783),
             rating: RatingScore::from(924.9),
+            bearing: Bearing::NORTH,
             distance_to_projection: Some(Length::from_meters(92.0)),
         };
 
@@ -332,6 +390,7 @@ mod tests {
             lrp: first_lrp,
             edge: EdgeId(8717174),
             rating: RatingScore::from(1128.7),
+            bearing: Bearing::NORTH,
             distance_to_projection: Some(Length::from_meters(20.0)),
         };
 
@@ -339,6 +398,7 @@ mod tests {
             lrp: last_lrp,
             edge: EdgeId(8717174),
             rating: RatingScore::from(1122.7),
+            bearing: Bearing::NORTH,
             distance_to_projection: Some(Length::from_meters(36.0)),
         };
 
@@ -414,6 +474,7 @@ mod tests {
             lrp: first_lrp,
             edge: EdgeId(8717174),
             rating: RatingScore::from(1128.7),
+            bearing: Bearing::NORTH,
             distance_to_projection: Some(Length::from_meters(20.0)),
         };
 
@@ -421,6 +482,7 @@ mod tests {
             lrp: second_lrp,
             edge: EdgeId(8717174),
             rating: RatingScore::from(1122.7),
+            bearing: Bearing::NORTH,
             distance_to_projection: Some(Length::from_meters(36.0)),
         };
 
@@ -428,6 +490,7 @@ mod tests {
             lrp: last_lrp,
             edge: EdgeId(109783),
             rating: RatingScore::from(924.9),
+            bearing: Bearing::NORTH,
             distance_to_projection: None,
         };
 
@@ -515,6 +578,7 @@ mod tests {
             lrp: first_lrp,
             edge: EdgeId(8717174),
             rating: RatingScore::from(1194.8),
+            bearing: Bearing::NORTH,
             distance_to_projection: None,
         };
 
@@ -522,6 +586,7 @@ mod tests {
             lrp: second_lrp,
             edge: EdgeId(6770340),
             rating: RatingScore::from(1193.5),
+            bearing: Bearing::NORTH,
             distance_to_projection: None,
         };
 
@@ -529,6 +594,7 @@ mod tests {
             lrp: last_lrp,
             edge: EdgeId(7531947),
             rating: RatingScore::from(1176.0),
+            bearing: Bearing::NORTH,
             distance_to_projection: None,
         };
 
@@ -616,6 +682,7 @@ mod tests {
             lrp: first_lrp,
             edge: EdgeId(8717174),
             rating: RatingScore::from(1194.8),
+            bearing: Bearing::NORTH,
             distance_to_projection: Some(Length::from_meters(10.0)),
         };
 
@@ -623,6 +690,7 @@ mod tests {
             lrp: second_lrp,
             edge: EdgeId(6770340),
             rating: RatingScore::from(1193.5),
+            bearing: Bearing::NORTH,
             distance_to_projection: Some(Length::from_meters(5.0)),
         };
 
@@ -630,6 +698,7 @@ mod tests {
             lrp: last_lrp,
             edge: EdgeId(7531947),
             rating: RatingScore::from(1176.0),
+            bearing: Bearing::NORTH,
             distance_to_projection: Some(Length::from_meters(27.0)),
         };
 
@@ -1,19 +1,74 @@
-use std::cmp::Reverse;
+use std::time::{Duration, Instant};
 
-use radix_heap::RadixHeapMap;
-use rustc_hash::FxHashMap;
 use tracing::trace;
 
-use crate::graph::dijkstra::unpack_path;
-use crate::graph::path::{Path, is_path_connected};
-use crate::{DecodeError, DirectedGraph, Frc, Length};
+use crate::graph::path::Path;
+use crate::routing::{RouteCost, search_path};
+use crate::{DecodeError, DecoderMetrics, DirectedGraph, Frc, Length, RouteSearchStats};
 
+/// Bounds of the search space of a single shortest path search (see
+/// [`crate::DecoderConfig::max_visited_nodes`] and [`crate::DecoderConfig::max_search_time`]).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SearchLimits {
+    pub max_visited_nodes: Option<usize>,
+    pub max_search_time: Option<Duration>,
+}
+
+impl SearchLimits {
+    fn check(&self, visited_nodes: usize, start: Instant) -> Result<(), (usize, Duration)> {
+        let exceeded = self
+            .max_visited_nodes
+            .is_some_and(|max| visited_nodes > max)
+            || self
+                .max_search_time
+                .is_some_and(|max| start.elapsed() >= max);
+
+        if exceeded {
+            Err((visited_nodes, start.elapsed()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn shortest_path<G: DirectedGraph>(
     graph: &G,
     origin: G::EdgeId,
     destination: G::EdgeId,
     lowest_frc: Frc,
     max_length: Length,
+    cost: RouteCost,
+    limits: &SearchLimits,
+    stats: &mut RouteSearchStats,
+    metrics: Option<&dyn DecoderMetrics>,
+) -> Result<Option<Path<G::EdgeId>>, DecodeError<G::Error>> {
+    graph.memoize_shortest_path(origin, destination, lowest_frc, max_length, cost, || {
+        search_shortest_path(
+            graph,
+            origin,
+            destination,
+            lowest_frc,
+            max_length,
+            cost,
+            limits,
+            stats,
+            metrics,
+        )
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn search_shortest_path<G: DirectedGraph>(
+    graph: &G,
+    origin: G::EdgeId,
+    destination: G::EdgeId,
+    lowest_frc: Frc,
+    max_length: Length,
+    cost: RouteCost,
+    limits: &SearchLimits,
+    stats: &mut RouteSearchStats,
+    metrics: Option<&dyn DecoderMetrics>,
 ) -> Result<Option<Path<G::EdgeId>>, DecodeError<G::Error>> {
     trace!(
         "Computing shortest path {origin:?} {:?} -> {destination:?} {:?}",
@@ -21,62 +76,44 @@ pub fn shortest_path<G: DirectedGraph>(
         graph.get_edge_end_vertex(destination)
     );
 
-    let origin_length = graph.get_edge_length(origin)?;
-    let mut shortest_distances = FxHashMap::from_iter([(origin, origin_length)]);
-    let mut previous_map: FxHashMap<G::EdgeId, G::EdgeId> = FxHashMap::default();
-    let mut heap = RadixHeapMap::from_iter([(Reverse(origin_length), origin)]);
-
-    while let Some((Reverse(h_distance), h_edge)) = heap.pop() {
-        if h_edge == destination {
-            // Unpacking: the shortest path from destination back to origin
-            let edges = unpack_path(&previous_map, destination);
-            debug_assert!(is_path_connected(graph, &edges)?, "{edges:?}");
-
-            return Ok(Some(Path {
-                length: h_distance,
-                edges,
-            }));
-        }
-
-        // check if we already know a cheaper way to get to the end of this path from the origin
-        let shortest_distance = *shortest_distances.get(&h_edge).unwrap_or(&Length::MAX);
-        if h_distance > shortest_distance {
-            continue;
-        }
-
-        let exiting_edges = graph.vertex_exiting_edges(graph.get_edge_end_vertex(h_edge)?)?;
+    stats.lowest_frc = lowest_frc;
+    stats.max_length = max_length;
 
-        for (edge, _) in exiting_edges {
-            if graph.is_turn_restricted(h_edge, edge)? {
-                continue;
-            }
+    let start = Instant::now();
+    let mut visited_nodes = 0;
 
-            let distance = h_distance + graph.get_edge_length(edge)?;
-            let frc = graph.get_edge_frc(edge)?;
-
-            if distance > max_length {
-                trace!("Element distance too far: {edge:?} {distance} > {max_length}");
-                continue;
-            }
+    let on_settled = |distance| {
+        stats.settled_edges += 1;
+        stats.max_distance_reached = stats.max_distance_reached.max(distance);
 
-            if frc > lowest_frc {
-                trace!("Element FRC too low: {edge:?} {frc:?} > {lowest_frc:?}");
-                continue;
-            }
+        // the end vertex of every settled edge is visited to expand the search
+        visited_nodes += 1;
+        limits
+            .check(visited_nodes, start)
+            .map_err(
+                |(visited_nodes, elapsed)| DecodeError::SearchLimitExceeded {
+                    visited_nodes,
+                    elapsed,
+                },
+            )
+    };
 
-            let shortest_distance = *shortest_distances.get(&edge).unwrap_or(&Length::MAX);
+    let path = search_path(
+        graph,
+        origin,
+        destination,
+        lowest_frc,
+        max_length,
+        &cost,
+        |_| Ok(Length::ZERO),
+        on_settled,
+    );
 
-            // check if we can follow the current path to reach the neighbor in a cheaper way
-            if distance < shortest_distance {
-                // Relax: we have now found a better way that we are going to explore
-                shortest_distances.insert(edge, distance);
-                previous_map.insert(edge, h_edge);
-                heap.push(Reverse(distance), edge);
-            }
-        }
+    if let Some(metrics) = metrics {
+        let found = matches!(path, Ok(Some(_)));
+        metrics.on_route_search(visited_nodes, start.elapsed(), found);
     }
-
-    Ok(None)
+    path
 }
 
 #[cfg(test)]
@@ -84,7 +121,10 @@ mod tests {
     use test_log::test;
 
     use super::*;
-    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+    use crate::GraphView;
+    use crate::graph::tests::{
+        EdgeId, NETWORK_GRAPH, NetworkGraph, PARALLEL_EDGE, RESTRICTED_NETWORK_GRAPH,
+    };
 
     #[test]
     fn decoder_shortest_path_001() {
@@ -96,7 +136,11 @@ mod tests {
                 EdgeId(8717174),
                 EdgeId(8717174),
                 Frc::Frc7,
-                Length::MAX
+                Length::MAX,
+                RouteCost::Length,
+                &SearchLimits::default(),
+                &mut RouteSearchStats::default(),
+                None
             )
             .unwrap()
             .unwrap(),
@@ -117,7 +161,11 @@ mod tests {
                 EdgeId(8717174),
                 EdgeId(109783),
                 Frc::Frc7,
-                Length::MAX
+                Length::MAX,
+                RouteCost::Length,
+                &SearchLimits::default(),
+                &mut RouteSearchStats::default(),
+                None
             )
             .unwrap()
             .unwrap(),
@@ -128,14 +176,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn decoder_shortest_path_graph_view() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let view = GraphView::new(graph);
+
+        for frc in [Frc::Frc7, Frc::Frc5, Frc::Frc2] {
+            assert_eq!(
+                shortest_path(
+                    &view,
+                    EdgeId(16218),
+                    EdgeId(961826),
+                    frc,
+                    Length::MAX,
+                    RouteCost::Length,
+                    &SearchLimits::default(),
+                    &mut RouteSearchStats::default(),
+                    None
+                )
+                .unwrap(),
+                shortest_path(
+                    graph,
+                    EdgeId(16218),
+                    EdgeId(961826),
+                    frc,
+                    Length::MAX,
+                    RouteCost::Length,
+                    &SearchLimits::default(),
+                    &mut RouteSearchStats::default(),
+                    None
+                )
+                .unwrap()
+            );
+        }
+    }
+
     #[test]
     fn decoder_shortest_path_003() {
         let graph: &NetworkGraph = &NETWORK_GRAPH;
 
         assert_eq!(
-            shortest_path(graph, EdgeId(16218), EdgeId(961826), Frc::Frc7, Length::MAX)
-                .unwrap()
-                .unwrap(),
+            shortest_path(
+                graph,
+                EdgeId(16218),
+                EdgeId(961826),
+                Frc::Frc7,
+                Length::MAX,
+                RouteCost::Length,
+                &SearchLimits::default(),
+                &mut RouteSearchStats::default(),
+                None
+            )
+            .unwrap()
+            .unwrap(),
             Path {
                 length: Length::from_meters(753.0),
                 edges: vec![
@@ -152,6 +245,7 @@ mod tests {
     #[test]
     fn decoder_shortest_path_004() {
         let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let mut stats = RouteSearchStats::default();
 
         assert_eq!(
             shortest_path(
@@ -159,11 +253,20 @@ mod tests {
                 EdgeId(16218),
                 EdgeId(961826),
                 Frc::Frc7,
-                Length::from_meters(752.0)
+                Length::from_meters(752.0),
+                RouteCost::Length,
+                &SearchLimits::default(),
+                &mut stats,
+                None
             )
             .unwrap(),
             None
         );
+
+        assert!(stats.settled_edges > 0);
+        assert!(stats.max_distance_reached <= Length::from_meters(752.0));
+        assert_eq!(stats.max_length, Length::from_meters(752.0));
+        assert_eq!(stats.lowest_frc, Frc::Frc7);
     }
 
     #[test]
@@ -176,7 +279,11 @@ mod tests {
                 EdgeId(-4232179),
                 EdgeId(-4232179),
                 Frc::Frc7,
-                Length::MAX
+                Length::MAX,
+                RouteCost::Length,
+                &SearchLimits::default(),
+                &mut RouteSearchStats::default(),
+                None
             )
             .unwrap()
             .unwrap(),
@@ -197,7 +304,11 @@ mod tests {
                 EdgeId(16218),
                 EdgeId(3227046),
                 Frc::Frc7,
-                Length::MAX
+                Length::MAX,
+                RouteCost::Length,
+                &SearchLimits::default(),
+                &mut RouteSearchStats::default(),
+                None
             )
             .unwrap()
             .unwrap(),
@@ -234,7 +345,11 @@ mod tests {
                 EdgeId(1653344),
                 EdgeId(5359425),
                 Frc::Frc7,
-                Length::MAX
+                Length::MAX,
+                RouteCost::Length,
+                &SearchLimits::default(),
+                &mut RouteSearchStats::default(),
+                None
             )
             .unwrap()
             .unwrap(),
@@ -249,4 +364,70 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn decoder_shortest_path_008() {
+        let graph: &NetworkGraph = &RESTRICTED_NETWORK_GRAPH;
+
+        let path = shortest_path(
+            graph,
+            EdgeId(8717174),
+            EdgeId(109783),
+            Frc::Frc7,
+            Length::MAX,
+            RouteCost::Length,
+            &SearchLimits::default(),
+            &mut RouteSearchStats::default(),
+            None,
+        )
+        .unwrap()
+        .unwrap();
+
+        // the shortest path through the edge 8717175 is restricted
+        assert_eq!(path.edges, [EdgeId(8717174), PARALLEL_EDGE, EdgeId(109783)]);
+        assert!(path.length > Length::from_meters(379.0));
+    }
+
+    #[test]
+    fn decoder_shortest_path_search_limits() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let search = |limits: SearchLimits| {
+            shortest_path(
+                graph,
+                EdgeId(16218),
+                EdgeId(3227046),
+                Frc::Frc7,
+                Length::MAX,
+                RouteCost::Length,
+                &limits,
+                &mut RouteSearchStats::default(),
+                None,
+            )
+        };
+
+        let limits = SearchLimits {
+            max_visited_nodes: Some(100_000),
+            max_search_time: Some(Duration::from_secs(60)),
+        };
+        assert_eq!(search(limits).unwrap().unwrap().edges.len(), 15);
+
+        let limits = SearchLimits {
+            max_visited_nodes: Some(5),
+            max_search_time: None,
+        };
+        let Err(DecodeError::SearchLimitExceeded { visited_nodes, .. }) = search(limits) else {
+            unreachable!()
+        };
+        assert_eq!(visited_nodes, 6);
+
+        let limits = SearchLimits {
+            max_visited_nodes: None,
+            max_search_time: Some(Duration::ZERO),
+        };
+        assert!(matches!(
+            search(limits),
+            Err(DecodeError::SearchLimitExceeded { .. })
+        ));
+    }
 }
@@ -0,0 +1,228 @@
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+
+use crate::decoder::line::resolve_line;
+use crate::graph::memo::EdgeLengthMemo;
+use crate::location::get_path_cut;
+use crate::{
+    CandidateLine, DecodeError, DecoderConfig, DecoderStages, DirectedGraph, Length, Line,
+    LocationError, LocationReference, deserialize_binary_openlr,
+    deserialize_binary_openlr_compatible,
+};
+
+/// Receives the decoded line location piece by piece, while the decoder resolves it.
+///
+/// Implementors can build their own output structures without materialising the whole path of
+/// the location. The decoder calls, in order:
+/// 1. [`on_lrp_matched`](Self::on_lrp_matched) once per LRP, with the candidate line it was
+///    matched to;
+/// 2. [`on_route_segment`](Self::on_route_segment) once per route between consecutive LRPs, with
+///    the edges of the route that belong to the location (already trimmed by the offsets);
+/// 3. [`on_offsets`](Self::on_offsets) once, with the offsets left on the first and last edges.
+///
+/// Every method does nothing by default.
+pub trait LocationVisitor<EdgeId> {
+    /// Called for the LRP of the given index with the candidate line it was matched to.
+    fn on_lrp_matched(&mut self, index: usize, line: &CandidateLine<EdgeId>) {
+        let _ = (index, line);
+    }
+
+    /// Called for the route that starts at the LRP of the given index, with the edges of the
+    /// route that belong to the location. Routes entirely cut out by the offsets are skipped.
+    fn on_route_segment(&mut self, index: usize, edges: &[EdgeId]) {
+        let _ = (index, edges);
+    }
+
+    /// Called with the offsets from the start of the first edge and from the end of the last edge
+    /// of the location.
+    fn on_offsets(&mut self, pos_offset: Length, neg_offset: Length) {
+        let _ = (pos_offset, neg_offset);
+    }
+}
+
+/// Decodes an OpenLR Line Location Reference encoded in Base64, driving the visitor with the
+/// decoded location instead of returning it.
+pub fn decode_base64_openlr_with_visitor<G, S, V>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    data: impl AsRef<[u8]>,
+    visitor: &mut V,
+) -> Result<(), DecodeError<G::Error>>
+where
+    G: DirectedGraph,
+    S: DecoderStages,
+    V: LocationVisitor<G::EdgeId>,
+{
+    let data = BASE64_STANDARD
+        .decode(data)
+        .map_err(|e| DecodeError::DeserializeError(e.into()))?;
+    decode_binary_openlr_with_visitor(config, graph, &data, visitor)
+}
+
+/// Decodes an OpenLR Line Location Reference encoded in binary, driving the visitor with the
+/// decoded location instead of returning it.
+/// Fails with [`DecodeError::LocationTypeNotSupported`] for any other type of location.
+pub fn decode_binary_openlr_with_visitor<G, S, V>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    data: &[u8],
+    visitor: &mut V,
+) -> Result<(), DecodeError<G::Error>>
+where
+    G: DirectedGraph,
+    S: DecoderStages,
+    V: LocationVisitor<G::EdgeId>,
+{
+    let location = if config.accept_legacy_version {
+        deserialize_binary_openlr_compatible(data)
+    } else {
+        deserialize_binary_openlr(data)
+    }
+    .map_err(DecodeError::DeserializeError)?;
+    let graph = &EdgeLengthMemo::new(graph);
+
+    if let LocationReference::Line(line) = location {
+        decode_line_with_visitor(config, graph, line, visitor)
+    } else {
+        Err(DecodeError::LocationTypeNotSupported(
+            location.location_type(),
+        ))
+    }
+}
+
+fn decode_line_with_visitor<G, S, V>(
+    config: &DecoderConfig<S>,
+    graph: &G,
+    line: Line,
+    visitor: &mut V,
+) -> Result<(), DecodeError<G::Error>>
+where
+    G: DirectedGraph,
+    S: DecoderStages,
+    V: LocationVisitor<G::EdgeId>,
+{
+    let (routes, pos_offset, neg_offset) = resolve_line(config, graph, line, None, &mut vec![])?;
+
+    let length = routes.edges().try_fold(Length::ZERO, |acc, edge| {
+        Ok::<_, G::Error>(acc + graph.get_edge_length(edge)?)
+    })?;
+    let edges_count: usize = routes.iter().map(|route| route.path.edges.len()).sum();
+
+    if edges_count == 0 {
+        return Err(LocationError::EmptyPath.into());
+    } else if pos_offset + neg_offset >= length {
+        return Err(LocationError::OffsetsExceedLength {
+            pos_offset,
+            neg_offset,
+            length,
+        }
+        .into());
+    }
+
+    // the location path is the concatenation of the routes trimmed to [start, end)
+    let start_cut = get_path_cut(graph, routes.edges(), pos_offset)?;
+    let (start, start_cut_length) = start_cut.unwrap_or((0, Length::ZERO));
+    let end_cut = get_path_cut(graph, routes.edges().rev(), neg_offset)?;
+    let end_cut = end_cut.map(|(i, length)| (edges_count - i, length));
+    let (end, end_cut_length) = end_cut.unwrap_or((edges_count, Length::ZERO));
+
+    for (index, route) in routes.iter().enumerate() {
+        visitor.on_lrp_matched(index, &route.first_candidate());
+    }
+    if let Some(route) = routes.last() {
+        visitor.on_lrp_matched(routes.len(), &route.last_candidate());
+    }
+
+    let mut route_start = 0;
+    for (index, route) in routes.iter().enumerate() {
+        let edges = &route.path.edges;
+        let route_end = route_start + edges.len();
+
+        let first = start.clamp(route_start, route_end) - route_start;
+        let last = end.clamp(route_start, route_end) - route_start;
+        if first < last {
+            visitor.on_route_segment(index, &edges[first..last]);
+        }
+
+        route_start = route_end;
+    }
+
+    visitor.on_offsets(pos_offset - start_cut_length, neg_offset - end_cut_length);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+    use crate::{Location, LocationType, decode_base64_openlr};
+
+    #[derive(Debug, Default)]
+    struct CollectingVisitor {
+        lrps: Vec<(usize, EdgeId)>,
+        path: Vec<EdgeId>,
+        offsets: Option<(Length, Length)>,
+    }
+
+    impl LocationVisitor<EdgeId> for CollectingVisitor {
+        fn on_lrp_matched(&mut self, index: usize, line: &CandidateLine<EdgeId>) {
+            self.lrps.push((index, line.edge));
+        }
+
+        fn on_route_segment(&mut self, _: usize, edges: &[EdgeId]) {
+            self.path.extend_from_slice(edges);
+        }
+
+        fn on_offsets(&mut self, pos_offset: Length, neg_offset: Length) {
+            self.offsets = Some((pos_offset, neg_offset));
+        }
+    }
+
+    #[test]
+    fn decode_with_visitor_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let config = DecoderConfig::default();
+
+        for code in ["CwmShiVYczPJBgCs/y0zAQ==", "CwmTaSVYpTPZCP4a/5UjYQUH"] {
+            let Ok(Location::Line(location)) = decode_base64_openlr(&config, graph, code) else {
+                unreachable!()
+            };
+
+            let mut visitor = CollectingVisitor::default();
+            decode_base64_openlr_with_visitor(&config, graph, code, &mut visitor).unwrap();
+
+            assert_eq!(visitor.path, location.path);
+            assert_eq!(
+                visitor.offsets,
+                Some((location.pos_offset, location.neg_offset))
+            );
+            assert!(visitor.lrps.len() >= 2);
+            assert!(visitor.lrps.iter().enumerate().all(|(i, &(j, _))| i == j));
+        }
+    }
+
+    #[test]
+    fn decode_with_visitor_002() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let config = DecoderConfig::default();
+
+        let mut visitor = CollectingVisitor::default();
+        let result = decode_base64_openlr_with_visitor(
+            &config,
+            graph,
+            "KwmTQyVYUDPRA/+y/2czQTk=",
+            &mut visitor,
+        );
+
+        assert_eq!(
+            result,
+            Err(DecodeError::LocationTypeNotSupported(
+                LocationType::PointAlongLine
+            ))
+        );
+        assert!(visitor.path.is_empty());
+    }
+}
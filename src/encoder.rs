@@ -1,27 +1,81 @@
 //! The OpenLR encoder generates a map-independent location reference for a (map-dependent)
 //! location.
 
+mod batch;
 mod expansion;
 mod line;
 mod lrp;
+mod metrics;
+mod report;
 mod resolver;
 mod shortest_path;
+mod trace;
 
 use base64::Engine;
 use base64::prelude::BASE64_STANDARD;
 
+#[cfg(feature = "rayon")]
+pub use crate::encoder::batch::encode_batch_parallel;
+pub use crate::encoder::batch::{Encoder, LocationEncoder};
+pub use crate::encoder::line::{ResolvedLine, resolve_line_lrps, simplify_line_location};
 use crate::encoder::line::{encode_closed_line, encode_line, encode_poi, encode_point_along_line};
+pub use crate::encoder::lrp::{
+    lrp_line_attributes, lrp_path_attributes, lrp_projected_line_attributes,
+};
+pub use crate::encoder::metrics::EncoderMetrics;
+pub use crate::encoder::report::{
+    CoverageStep, EncodeReport, IntermediateLrp, IntermediateReason, ResolvedLrp,
+};
+pub use crate::encoder::trace::{encode_from_trace, match_trace};
+use crate::graph::remote::ReplayGraph;
+use crate::routing::RouteCost;
 use crate::{
-    DirectedGraph, EncodeError, Length, Location, LocationReference, serialize_binary_openlr,
+    AsyncDirectedGraph, AsyncGraphError, BearingRounding, DirectedGraph, EncodeError,
+    EncodeWarning, Length, Location, LocationReference, serialize_binary_openlr,
 };
 
 #[derive(Debug, Clone, Copy)]
 pub struct EncoderConfig {
     /// The maximum distance allowed between consecutive LRPs.
+    /// Shortened for locations at high latitudes, where the LRPs would otherwise be too far apart
+    /// to be serialized relative to each other.
     pub max_lrp_distance: Length,
     /// The length of the segment used to compute the lines bearing (distance from the start of
     /// the segment to its end).
     pub bearing_distance: Length,
+    /// Rounding of the lines bearing into the sectors of the binary format. The specification
+    /// (and the default) rounds down, other implementations may round to the closest sector.
+    pub bearing_rounding: BearingRounding,
+    /// If set, looks for alternative paths between consecutive LRPs that are at most this ratio
+    /// longer than the location (for example 0.05 for 5% longer), and adds intermediate LRPs to
+    /// rule them out, so that the location reference decodes to the same path on maps where the
+    /// lines lengths differ slightly.
+    pub alternative_path_ratio: Option<f64>,
+    /// Max number of nodes expanded by all the route searches of a location before the encoding
+    /// fails with [`EncodeError::SearchLimitExceeded`]. Unbounded if None.
+    pub max_expanded_nodes: Option<usize>,
+    /// Max number of shortest paths computed to cover a location (each one possibly adding an
+    /// intermediate LRP) before the encoding fails with [`EncodeError::SearchLimitExceeded`].
+    /// Unbounded if None.
+    pub max_coverage_iterations: Option<usize>,
+    /// Simplifies the line locations before encoding them (see [`simplify_line_location`]),
+    /// otherwise the encoding fails if the location path repeats an edge.
+    pub simplify: bool,
+    /// The maximum distance of the points of a trace from the lines they are matched to (see
+    /// [`encode_from_trace`]).
+    pub max_trace_distance: Length,
+    /// Cost minimized by the route searches that check whether the location follows the routes
+    /// between consecutive LRPs. Must match the one of the decoders (see
+    /// [`DecoderConfig::route_cost`](crate::DecoderConfig::route_cost)).
+    pub route_cost: RouteCost,
+    /// Encodes the side of road of the point locations relative to the driving direction of
+    /// left-hand traffic countries: the sides of the locations (relative to the direction of the
+    /// line) are mirrored. Must match the one of the decoders (see
+    /// [`DecoderConfig::left_hand_traffic`](crate::DecoderConfig::left_hand_traffic)).
+    pub left_hand_traffic: bool,
+    /// Receiver of the counters of the encoder work, shared by every encoding that uses this
+    /// config. No metrics are recorded if None.
+    pub metrics: Option<&'static dyn EncoderMetrics>,
 }
 
 impl Default for EncoderConfig {
@@ -36,6 +90,15 @@ impl Default for EncoderConfig {
         Self {
             max_lrp_distance: DEFAULT_MAX_LRP_DISTANCE,
             bearing_distance: Length::from_meters(20.0),
+            bearing_rounding: BearingRounding::Floor,
+            alternative_path_ratio: None,
+            max_expanded_nodes: None,
+            max_coverage_iterations: None,
+            simplify: true,
+            max_trace_distance: Length::from_meters(50.0),
+            route_cost: RouteCost::Length,
+            left_hand_traffic: false,
+            metrics: None,
         }
     }
 }
@@ -46,8 +109,8 @@ pub fn encode_base64_openlr<G: DirectedGraph>(
     graph: &G,
     location: Location<G::EdgeId>,
 ) -> Result<String, EncodeError<G::Error>> {
-    let data = encode_binary_openlr(config, graph, location)?;
-    Ok(BASE64_STANDARD.encode(data))
+    let (data, _) = encode_base64_openlr_with_warnings(config, graph, location)?;
+    Ok(data)
 }
 
 /// Encodes an OpenLR Location Reference into binary.
@@ -55,16 +118,103 @@ pub fn encode_binary_openlr<G: DirectedGraph>(
     config: &EncoderConfig,
     graph: &G,
     location: Location<G::EdgeId>,
+) -> Result<Vec<u8>, EncodeError<G::Error>> {
+    let (data, _) = encode_binary_openlr_with_warnings(config, graph, location)?;
+    Ok(data)
+}
+
+/// Encodes an OpenLR Location Reference into binary on a graph whose data is fetched
+/// asynchronously. The encoding is replayed each time it requests graph data that has not been
+/// fetched yet, until all the data it needs is fetched.
+pub async fn encode_binary_openlr_async<G: AsyncDirectedGraph>(
+    config: &EncoderConfig,
+    graph: &G,
+    location: Location<G::EdgeId>,
+) -> Result<Vec<u8>, EncodeError<AsyncGraphError<G::Error>>> {
+    let mut graph = ReplayGraph::new(graph);
+
+    loop {
+        let run = |graph: &_| encode_binary_openlr(config, graph, location.clone());
+        if let Some(data) = graph.replay(run) {
+            return data;
+        }
+
+        graph
+            .fetch_missing()
+            .await
+            .map_err(|e| EncodeError::GraphError(AsyncGraphError::Graph(e)))?;
+    }
+}
+
+/// Encodes an OpenLR Location Reference into Base64.
+/// Also returns the non-fatal issues found while encoding the location.
+pub fn encode_base64_openlr_with_warnings<G: DirectedGraph>(
+    config: &EncoderConfig,
+    graph: &G,
+    location: Location<G::EdgeId>,
+) -> Result<(String, Vec<EncodeWarning>), EncodeError<G::Error>> {
+    let (data, warnings) = encode_binary_openlr_with_warnings(config, graph, location)?;
+    Ok((BASE64_STANDARD.encode(data), warnings))
+}
+
+/// Encodes an OpenLR Location Reference into binary.
+/// Also returns the non-fatal issues found while encoding the location.
+#[allow(clippy::type_complexity)]
+pub fn encode_binary_openlr_with_warnings<G: DirectedGraph>(
+    config: &EncoderConfig,
+    graph: &G,
+    location: Location<G::EdgeId>,
+) -> Result<(Vec<u8>, Vec<EncodeWarning>), EncodeError<G::Error>> {
+    let mut warnings = vec![];
+    let data = encode_binary(config, graph, location, None, &mut warnings)?;
+    Ok((data, warnings))
+}
+
+/// Encodes an OpenLR Location Reference into Base64.
+/// The report is filled with the diagnostics collected during encoding, even if encoding fails.
+pub fn encode_base64_openlr_with_report<G: DirectedGraph>(
+    config: &EncoderConfig,
+    graph: &G,
+    location: Location<G::EdgeId>,
+    report: &mut EncodeReport<G::EdgeId>,
+) -> Result<String, EncodeError<G::Error>> {
+    let data = encode_binary_openlr_with_report(config, graph, location, report)?;
+    Ok(BASE64_STANDARD.encode(data))
+}
+
+/// Encodes an OpenLR Location Reference into binary.
+/// The report is filled with the diagnostics collected during encoding, even if encoding fails.
+pub fn encode_binary_openlr_with_report<G: DirectedGraph>(
+    config: &EncoderConfig,
+    graph: &G,
+    location: Location<G::EdgeId>,
+    report: &mut EncodeReport<G::EdgeId>,
+) -> Result<Vec<u8>, EncodeError<G::Error>> {
+    let mut warnings = vec![];
+    let data = encode_binary(config, graph, location, Some(&mut *report), &mut warnings);
+    report.warnings.extend(warnings);
+    data
+}
+
+fn encode_binary<G: DirectedGraph>(
+    config: &EncoderConfig,
+    graph: &G,
+    location: Location<G::EdgeId>,
+    report: Option<&mut EncodeReport<G::EdgeId>>,
+    warnings: &mut Vec<EncodeWarning>,
 ) -> Result<Vec<u8>, EncodeError<G::Error>> {
     use LocationReference::*;
     let location = match location {
-        Location::Line(line) => encode_line(config, graph, line).map(Line)?,
+        Location::Line(line) => encode_line(config, graph, line, report, warnings).map(Line)?,
         Location::GeoCoordinate(coordinate) => GeoCoordinate(coordinate),
         Location::PointAlongLine(point) => {
-            encode_point_along_line(config, graph, point).map(PointAlongLine)?
+            encode_point_along_line(config, graph, point, report, warnings).map(PointAlongLine)?
+        }
+        Location::Poi(poi) => encode_poi(config, graph, poi, report, warnings).map(Poi)?,
+        Location::ClosedLine(line) => {
+            encode_closed_line(config, graph, line, report, warnings).map(ClosedLine)?
         }
-        Location::Poi(poi) => encode_poi(config, graph, poi).map(Poi)?,
-        Location::ClosedLine(line) => encode_closed_line(config, graph, line).map(ClosedLine)?,
+        Location::Area(area) => area.area.into(),
     };
 
     // Step – 10 Create physical representation of the location reference.
@@ -0,0 +1,175 @@
+use crate::graph::memo::EdgeLengthMemo;
+use crate::{DirectedGraph, EncodeError, EncoderConfig, Location, encode_base64_openlr};
+
+/// Encoder of OpenLR Location References, implemented by [`Encoder`].
+///
+/// Services can depend on this trait instead of a specific graph, and replace the encoder in
+/// their tests.
+pub trait LocationEncoder {
+    type EdgeId;
+    type Error;
+
+    /// Encodes an OpenLR Location Reference into Base64.
+    fn encode(&self, location: Location<Self::EdgeId>) -> Result<String, EncodeError<Self::Error>>;
+}
+
+/// Encoder of many locations on the same graph. The edge lengths are cached across the encoded
+/// locations, as long as the encoder lives.
+pub struct Encoder<'a, G: DirectedGraph> {
+    config: EncoderConfig,
+    graph: EdgeLengthMemo<'a, G>,
+}
+
+impl<'a, G: DirectedGraph> Encoder<'a, G> {
+    pub fn new(config: EncoderConfig, graph: &'a G) -> Self {
+        Self {
+            config,
+            graph: EdgeLengthMemo::new(graph),
+        }
+    }
+
+    pub const fn config(&self) -> &EncoderConfig {
+        &self.config
+    }
+
+    /// Gets the graph the locations are encoded on.
+    pub fn graph(&self) -> &'a G {
+        self.graph.graph()
+    }
+
+    /// Encodes an OpenLR Location Reference into Base64.
+    pub fn encode(&self, location: Location<G::EdgeId>) -> Result<String, EncodeError<G::Error>> {
+        encode_base64_openlr(&self.config, &self.graph, location)
+    }
+
+    /// Encodes the locations into OpenLR Location References in Base64.
+    /// Returns the result of each location in the same order of the locations.
+    pub fn encode_batch(
+        &self,
+        locations: impl IntoIterator<Item = Location<G::EdgeId>>,
+    ) -> Vec<Result<String, EncodeError<G::Error>>> {
+        locations
+            .into_iter()
+            .map(|location| self.encode(location))
+            .collect()
+    }
+}
+
+impl<G: DirectedGraph> LocationEncoder for Encoder<'_, G> {
+    type EdgeId = G::EdgeId;
+    type Error = G::Error;
+
+    fn encode(&self, location: Location<Self::EdgeId>) -> Result<String, EncodeError<Self::Error>> {
+        Encoder::encode(self, location)
+    }
+}
+
+/// Encodes the locations into OpenLR Location References in Base64, in parallel on the rayon
+/// global thread pool. Returns the result of each location in the same order of the locations.
+#[cfg(feature = "rayon")]
+pub fn encode_batch_parallel<G>(
+    config: &EncoderConfig,
+    graph: &G,
+    locations: impl IntoIterator<Item = Location<G::EdgeId>>,
+) -> Vec<Result<String, EncodeError<G::Error>>>
+where
+    G: DirectedGraph + Sync,
+    G::EdgeId: Send,
+    G::Error: Send,
+{
+    use rayon::prelude::*;
+
+    let locations: Vec<_> = locations.into_iter().collect();
+    locations
+        .into_par_iter()
+        .map(|location| encode_base64_openlr(config, graph, location))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+    use crate::{Length, LineLocation};
+
+    #[test]
+    fn encoder_encode_batch_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let encoder = Encoder::new(EncoderConfig::default(), graph);
+
+        let locations = [
+            vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+            vec![EdgeId(8717174), EdgeId(8717175)],
+            vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+        ]
+        .map(|path| {
+            Location::Line(LineLocation {
+                path,
+                pos_offset: Length::ZERO,
+                neg_offset: Length::ZERO,
+            })
+        });
+
+        let codes = encoder.encode_batch(locations.clone());
+        assert_eq!(codes.len(), locations.len());
+        assert_eq!(codes[0], codes[2]);
+
+        for (location, code) in locations.into_iter().zip(codes) {
+            assert_eq!(
+                code,
+                encode_base64_openlr(&EncoderConfig::default(), graph, location)
+            );
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn encoder_encode_batch_parallel_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let config = EncoderConfig::default();
+
+        let locations: Vec<_> = [
+            vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+            vec![EdgeId(8717174), EdgeId(8717175)],
+            vec![EdgeId(8717174), EdgeId(-8717174)],
+        ]
+        .into_iter()
+        .cycle()
+        .take(30)
+        .map(|path| {
+            Location::Line(LineLocation {
+                path,
+                pos_offset: Length::ZERO,
+                neg_offset: Length::ZERO,
+            })
+        })
+        .collect();
+
+        let codes = encode_batch_parallel(&config, graph, locations.clone());
+        assert_eq!(codes.len(), locations.len());
+
+        for (location, code) in locations.into_iter().zip(codes) {
+            assert_eq!(code, encode_base64_openlr(&config, graph, location));
+        }
+    }
+
+    #[test]
+    fn encoder_location_encoder_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let encoder: &dyn LocationEncoder<EdgeId = _, Error = _> =
+            &Encoder::new(EncoderConfig::default(), graph);
+
+        let location = Location::Line(LineLocation {
+            path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        });
+
+        assert_eq!(
+            encoder.encode(location.clone()),
+            encode_base64_openlr(&EncoderConfig::default(), graph, location)
+        );
+    }
+}
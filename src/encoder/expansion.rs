@@ -1,5 +1,8 @@
-use crate::graph::path::{Path, is_node_valid, is_opposite_direction};
-use crate::{DirectedGraph, EncodeError, EncoderConfig, Length, LineLocation};
+use crate::encoder::report::EncodeReport;
+use crate::graph::path::{
+    Path, find_path_disconnection, is_node_valid, is_opposite_direction, is_turn_restricted_after,
+};
+use crate::{DirectedGraph, EncodeError, EncodeWarning, EncoderConfig, Length, LineLocation};
 
 /// Returns the line expanded by forward and backward paths so that the start and the end of the
 /// location are in valid nodes.
@@ -20,9 +23,20 @@ pub fn line_location_with_expansion<G: DirectedGraph>(
     config: &EncoderConfig,
     graph: &G,
     mut line: LineLocation<G::EdgeId>,
+    report: Option<&mut EncodeReport<G::EdgeId>>,
+    warnings: &mut Vec<EncodeWarning>,
 ) -> Result<LineLocation<G::EdgeId>, EncodeError<G::Error>> {
-    let prefix = edge_backward_expansion(config, graph, &line)?;
-    let mut postfix = edge_forward_expansion(config, graph, &line)?;
+    let prefix = edge_backward_expansion(config, graph, &line, warnings)?;
+    let mut postfix = edge_forward_expansion(config, graph, &line, warnings)?;
+
+    if let Some(metrics) = config.metrics {
+        metrics.on_expansion(prefix.length, postfix.length);
+    }
+
+    if let Some(report) = report {
+        report.backward_expansion = prefix.clone();
+        report.forward_expansion = postfix.clone();
+    }
 
     let mut path = prefix.edges;
     path.reserve_exact(line.path.len() + postfix.edges.len());
@@ -37,11 +51,13 @@ pub fn line_location_with_expansion<G: DirectedGraph>(
 }
 
 /// Returns the expansion path in forward direction (from the line last edge).
-/// The path cannot contain any turn restriction, in which case this function returns an empty one.
+/// The path cannot contain any turn (or via-way) restriction, in which case this function returns
+/// an empty one.
 fn edge_forward_expansion<G: DirectedGraph>(
     config: &EncoderConfig,
     graph: &G,
     line: &LineLocation<G::EdgeId>,
+    warnings: &mut Vec<EncodeWarning>,
 ) -> Result<Path<G::EdgeId>, EncodeError<G::Error>> {
     let mut expansion = Path::default();
     let mut edge = line.path[line.path.len() - 1];
@@ -53,9 +69,9 @@ fn edge_forward_expansion<G: DirectedGraph>(
 
         match resolve_edge_expansion(config, graph, line, offset, &expansion, edge, candidates)? {
             Some((e, length)) => {
-                if let Some(last_edge) = expansion.edges.last()
-                    && graph.is_turn_restricted(*last_edge, e)?
-                {
+                let previous = expansion.edges.iter().rev().chain(line.path.iter().rev());
+                if is_turn_restricted_after(graph, previous.copied(), e)? {
+                    warnings.push(EncodeWarning::ForwardExpansionTruncated);
                     return Ok(Path::default());
                 }
 
@@ -64,25 +80,24 @@ fn edge_forward_expansion<G: DirectedGraph>(
                 offset += length;
                 edge = e;
             }
-            None => break,
+            None => {
+                warnings.push(EncodeWarning::ForwardExpansionTruncated);
+                break;
+            }
         };
     }
 
-    if let Some(&e) = expansion.edges.first()
-        && graph.is_turn_restricted(edge, e)?
-    {
-        return Ok(Path::default());
-    }
-
     Ok(expansion)
 }
 
 /// Returns the expansion path in backward direction (into the line first edge).
-/// The path cannot contain any turn restriction, in which case this function returns an empty one.
+/// The path cannot contain any turn (or via-way) restriction, in which case this function returns
+/// an empty one.
 fn edge_backward_expansion<G: DirectedGraph>(
     config: &EncoderConfig,
     graph: &G,
     line: &LineLocation<G::EdgeId>,
+    warnings: &mut Vec<EncodeWarning>,
 ) -> Result<Path<G::EdgeId>, EncodeError<G::Error>> {
     let mut expansion = Path::default();
     let mut edge = line.path[0];
@@ -94,9 +109,8 @@ fn edge_backward_expansion<G: DirectedGraph>(
 
         match resolve_edge_expansion(config, graph, line, offset, &expansion, edge, candidates)? {
             Some((e, length)) => {
-                if let Some(last_edge) = expansion.edges.last()
-                    && graph.is_turn_restricted(e, *last_edge)?
-                {
+                if graph.is_turn_restricted(e, edge)? {
+                    warnings.push(EncodeWarning::BackwardExpansionTruncated);
                     return Ok(Path::default());
                 }
 
@@ -105,15 +119,19 @@ fn edge_backward_expansion<G: DirectedGraph>(
                 offset += length;
                 edge = e;
             }
-            None => break,
+            None => {
+                warnings.push(EncodeWarning::BackwardExpansionTruncated);
+                break;
+            }
         };
     }
 
     expansion.edges.reverse();
 
-    if let Some(&e) = expansion.edges.last()
-        && graph.is_turn_restricted(e, edge)?
-    {
+    // via-way restrictions starting in the expansion can only be checked once it is complete
+    let path: Vec<_> = expansion.edges.iter().chain(&line.path).copied().collect();
+    if !expansion.edges.is_empty() && find_path_disconnection(graph, &path)?.is_some() {
+        warnings.push(EncodeWarning::BackwardExpansionTruncated);
         return Ok(Path::default());
     }
 
@@ -266,7 +284,7 @@ mod tests {
         };
 
         assert_eq!(
-            line_location_with_expansion(&config, graph, line.clone()).unwrap(),
+            line_location_with_expansion(&config, graph, line.clone(), None, &mut vec![]).unwrap(),
             line,
             "Start VertexId(68) and End VertexId(20) are both valid nodes"
         );
@@ -285,7 +303,7 @@ mod tests {
         };
 
         assert_eq!(
-            line_location_with_expansion(&config, graph, line).unwrap(),
+            line_location_with_expansion(&config, graph, line, None, &mut vec![]).unwrap(),
             LineLocation {
                 path: vec![EdgeId(16219), EdgeId(7430347)],
                 pos_offset: Length::ZERO,
@@ -308,7 +326,7 @@ mod tests {
         };
 
         assert_eq!(
-            line_location_with_expansion(&config, graph, line).unwrap(),
+            line_location_with_expansion(&config, graph, line, None, &mut vec![]).unwrap(),
             LineLocation {
                 path: vec![EdgeId(16219), EdgeId(7430347)],
                 pos_offset: Length::from_meters(109.0),
@@ -331,7 +349,7 @@ mod tests {
         };
 
         assert_eq!(
-            edge_forward_expansion(&config, graph, &line).unwrap(),
+            edge_forward_expansion(&config, graph, &line, &mut vec![]).unwrap(),
             Path {
                 edges: vec![EdgeId(-9044471), EdgeId(-9044472)],
                 length: Length::from_meters(26.0)
@@ -353,7 +371,7 @@ mod tests {
         };
 
         assert_eq!(
-            edge_backward_expansion(&config, graph, &line).unwrap(),
+            edge_backward_expansion(&config, graph, &line, &mut vec![]).unwrap(),
             Path {
                 edges: vec![EdgeId(-9044470), EdgeId(-9044471)],
                 length: Length::from_meters(31.0)
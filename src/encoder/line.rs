@@ -1,10 +1,13 @@
 use tracing::debug;
 
 use crate::encoder::expansion::line_location_with_expansion;
+use crate::encoder::lrp::LocRefPoints;
+use crate::encoder::report::{EncodeReport, ResolvedLrp};
 use crate::encoder::resolver::resolve_lrps;
+use crate::graph::path::is_node_valid;
 use crate::{
-    ClosedLine, ClosedLineLocation, DirectedGraph, EncodeError, EncoderConfig, Length, Line,
-    LineLocation, Offsets, Poi, PoiLocation, PointAlongLine, PointAlongLineLocation,
+    ClosedLine, ClosedLineLocation, DirectedGraph, EncodeError, EncodeWarning, EncoderConfig,
+    Length, Line, LineLocation, Offsets, Poi, PoiLocation, PointAlongLine, PointAlongLineLocation,
 };
 
 /// 1. Check validity of the location and offsets to be encoded.
@@ -29,30 +32,202 @@ pub fn encode_line<G: DirectedGraph>(
     config: &EncoderConfig,
     graph: &G,
     line: LineLocation<G::EdgeId>,
+    report: Option<&mut EncodeReport<G::EdgeId>>,
+    warnings: &mut Vec<EncodeWarning>,
 ) -> Result<Line, EncodeError<G::Error>> {
     debug!("Encoding {line:?} with {config:?}");
 
+    // Step – 1..9 Resolve the LRPs trimmed by the offsets
+    let lrps = resolve_line(config, graph, line, report, warnings)?;
+
+    let (pos_offset, neg_offset) = (lrps.pos_offset, lrps.neg_offset);
+    let head_length = lrps[0].point.dnp();
+    let tail_length = lrps[lrps.len() - 2].point.dnp();
+
+    let line = Line::from(lrps);
+
+    // offsets are encoded relative to the length of the first and last LRP paths
+    let encoded = line.offsets.distance_from_start(head_length);
+    if (encoded - pos_offset).meters().abs() >= 1.0 {
+        warnings.push(EncodeWarning::PositiveOffsetRounded {
+            requested: pos_offset,
+            encoded,
+        });
+    }
+
+    let encoded = line.offsets.distance_to_end(tail_length);
+    if (encoded - neg_offset).meters().abs() >= 1.0 {
+        warnings.push(EncodeWarning::NegativeOffsetRounded {
+            requested: neg_offset,
+            encoded,
+        });
+    }
+
+    Ok(line)
+}
+
+/// LRPs of a line location resolved by the encoder, before they are converted into the location
+/// reference (see [`resolve_line_lrps`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedLine<EdgeId> {
+    /// The LRPs that cover the expanded location, trimmed by the offsets.
+    pub lrps: Vec<ResolvedLrp<EdgeId>>,
+    /// Distance from the first LRP to the start of the location, always shorter than the distance
+    /// to the next point of the first LRP.
+    pub pos_offset: Length,
+    /// Distance from the end of the location to the last LRP, always shorter than the distance to
+    /// the next point of the last but one LRP.
+    pub neg_offset: Length,
+}
+
+/// Resolves the LRPs of a line location, the steps of the encoder before the location reference is
+/// created from the LRPs:
+/// 1. The location is trimmed by its offsets: the edges fully covered by an offset are removed and
+///    the offset is reduced by their length.
+/// 2. The location is expanded by the edges before and after it, until its start and end are on
+///    valid nodes: the length of the edges added before (after) the location is added to the
+///    positive (negative) offset.
+/// 3. The expanded location is covered by the LRPs (see [`EncoderConfig::max_lrp_distance`]).
+/// 4. The LRPs covered by an offset are removed: the offset is reduced by their distance to the
+///    next point, so that the offsets are relative to the first and last remaining LRPs.
+///
+/// Fails if the offsets exceed the length of the location, or if the expanded offsets cannot be
+/// trimmed into valid LRPs.
+pub fn resolve_line_lrps<G: DirectedGraph>(
+    config: &EncoderConfig,
+    graph: &G,
+    line: LineLocation<G::EdgeId>,
+) -> Result<ResolvedLine<G::EdgeId>, EncodeError<G::Error>> {
+    let lrps = resolve_line(config, graph, line, None, &mut vec![])?;
+
+    Ok(ResolvedLine {
+        lrps: lrps.iter().map(ResolvedLrp::from).collect(),
+        pos_offset: lrps.pos_offset,
+        neg_offset: lrps.neg_offset,
+    })
+}
+
+fn resolve_line<G: DirectedGraph>(
+    config: &EncoderConfig,
+    graph: &G,
+    line: LineLocation<G::EdgeId>,
+    mut report: Option<&mut EncodeReport<G::EdgeId>>,
+    warnings: &mut Vec<EncodeWarning>,
+) -> Result<LocRefPoints<G::EdgeId>, EncodeError<G::Error>> {
     // Step – 1 Check validity of the location and offsets to be encoded
+    let line = if config.simplify {
+        simplify_line(graph, line, warnings)?
+    } else {
+        line
+    };
     let line = line.trim(graph)?;
 
     // Step – 2 Adjust start and end node of the location to represent valid map nodes
-    let line = line_location_with_expansion(config, graph, line)?;
+    let line = line_location_with_expansion(config, graph, line, report.as_deref_mut(), warnings)?;
     debug_assert!(!line.path.is_empty());
+    let last_edge = line.path[line.path.len() - 1];
 
     // Step – 3..8 Split location into intermediate LRPs until full coverage
-    let lrps = resolve_lrps(config, graph, line)?;
+    let lrps = resolve_lrps(config, graph, line, report.as_deref_mut())?;
     debug_assert!(lrps.len() > 1);
 
+    for lrp in lrps
+        .iter()
+        .filter(|lrp| lrp.projection_coordinate.is_none())
+    {
+        let vertex = match lrp.edges.first() {
+            Some(&edge) => graph.get_edge_start_vertex(edge)?,
+            None => graph.get_edge_end_vertex(last_edge)?,
+        };
+
+        if !is_node_valid(graph, vertex)? {
+            warnings.push(EncodeWarning::LrpOnInvalidNode(lrp.point.coordinate));
+        }
+    }
+
     // Step – 9 Trim LRPs if the offset values exceeds the length of the corresponding path
     let lrps = lrps.trim(config, graph)?;
+    if let Some(report) = report {
+        report.record_lrps(&lrps);
+    }
+
+    Ok(lrps)
+}
+
+/// Simplifies the line location before encoding it (see [`EncoderConfig::simplify`]): removes the
+/// immediate duplicate edges of the location path, and the zero-length edges (stubs) that some
+/// routers produce: at the start and at the end of the path, and inside the path if the previous
+/// and the next edges are connected without the stub (a zero-length loop). Returns the simplified
+/// location together with the warnings describing the removed edges.
+///
+/// Inner stubs between two distinct vertices are kept: the path would be disconnected without them.
+///
+/// The consecutive edges between invalid nodes don't need to be merged: the encoder never places
+/// LRPs on invalid nodes, unless required to follow the location or the max LRP distance.
+#[allow(clippy::type_complexity)]
+pub fn simplify_line_location<G: DirectedGraph>(
+    graph: &G,
+    line: LineLocation<G::EdgeId>,
+) -> Result<(LineLocation<G::EdgeId>, Vec<EncodeWarning>), G::Error> {
+    let mut warnings = vec![];
+    let line = simplify_line(graph, line, &mut warnings)?;
+    Ok((line, warnings))
+}
+
+fn simplify_line<G: DirectedGraph>(
+    graph: &G,
+    mut line: LineLocation<G::EdgeId>,
+    warnings: &mut Vec<EncodeWarning>,
+) -> Result<LineLocation<G::EdgeId>, G::Error> {
+    let mut path = Vec::with_capacity(line.path.len());
+
+    for (index, &edge) in line.path.iter().enumerate() {
+        if path.last() == Some(&edge) {
+            warnings.push(EncodeWarning::DuplicateEdgeRemoved(index));
+        } else {
+            path.push(edge);
+        }
+    }
+
+    let mut start = 0;
+    while start < path.len() && graph.get_edge_length(path[start])? == Length::ZERO {
+        start += 1;
+    }
+
+    let mut end = path.len();
+    while end > start && graph.get_edge_length(path[end - 1])? == Length::ZERO {
+        end -= 1;
+    }
+
+    let mut simplified = Vec::with_capacity(end - start);
+    for (index, &edge) in path.iter().enumerate().take(end).skip(start) {
+        if let (Some(&previous), Some(&next)) = (simplified.last(), path.get(index + 1))
+            && index + 1 < end
+            && previous != next
+            && graph.get_edge_length(edge)? == Length::ZERO
+            && graph.get_edge_end_vertex(previous)? == graph.get_edge_start_vertex(next)?
+        {
+            continue;
+        }
+        simplified.push(edge);
+    }
 
-    Ok(lrps.into())
+    if simplified.len() < path.len() {
+        let removed = path.len() - simplified.len();
+        warnings.push(EncodeWarning::ZeroLengthEdgesRemoved(removed));
+        path = simplified;
+    }
+
+    line.path = path;
+    Ok(line)
 }
 
 pub fn encode_point_along_line<G: DirectedGraph>(
     config: &EncoderConfig,
     graph: &G,
     point: PointAlongLineLocation<G::EdgeId>,
+    report: Option<&mut EncodeReport<G::EdgeId>>,
+    warnings: &mut Vec<EncodeWarning>,
 ) -> Result<PointAlongLine, EncodeError<G::Error>> {
     debug!("Encoding {point:?} with {config:?}");
 
@@ -62,13 +237,17 @@ pub fn encode_point_along_line<G: DirectedGraph>(
         neg_offset: Length::ZERO,
     };
 
-    let line = encode_line(config, graph, line)?;
+    let line = encode_line(config, graph, line, report, warnings)?;
 
     Ok(PointAlongLine {
         points: [line.points[0], line.points[line.points.len() - 1]],
         offset: line.offsets.pos,
         orientation: point.orientation,
-        side: point.side,
+        side: if config.left_hand_traffic {
+            point.side.mirrored()
+        } else {
+            point.side
+        },
     })
 }
 
@@ -76,10 +255,21 @@ pub fn encode_poi<G: DirectedGraph>(
     config: &EncoderConfig,
     graph: &G,
     poi: PoiLocation<G::EdgeId>,
+    report: Option<&mut EncodeReport<G::EdgeId>>,
+    warnings: &mut Vec<EncodeWarning>,
 ) -> Result<Poi, EncodeError<G::Error>> {
     debug!("Encoding {poi:?} with {config:?}");
 
-    let point = encode_point_along_line(config, graph, poi.point)?;
+    let point = encode_point_along_line(config, graph, poi.point, report, warnings)?;
+
+    // the POI coordinate is serialized relative to the first LRP coordinate
+    let lrp = point.points[0].coordinate;
+    if !poi.coordinate.is_relative_in_range(&lrp) {
+        return Err(EncodeError::PoiOutOfRange {
+            poi: poi.coordinate,
+            lrp,
+        });
+    }
 
     Ok(Poi {
         point,
@@ -91,6 +281,8 @@ pub fn encode_closed_line<G: DirectedGraph>(
     config: &EncoderConfig,
     graph: &G,
     line: ClosedLineLocation<G::EdgeId>,
+    report: Option<&mut EncodeReport<G::EdgeId>>,
+    warnings: &mut Vec<EncodeWarning>,
 ) -> Result<ClosedLine, EncodeError<G::Error>> {
     debug!("Encoding {line:?} with {config:?}");
 
@@ -100,7 +292,7 @@ pub fn encode_closed_line<G: DirectedGraph>(
         neg_offset: Length::ZERO,
     };
 
-    let mut line = encode_line(config, graph, line)?;
+    let mut line = encode_line(config, graph, line, report, warnings)?;
     debug_assert_eq!(line.offsets, Offsets::ZERO);
 
     let last_line = line.points[line.points.len() - 1].line;
@@ -117,10 +309,14 @@ mod tests {
     use test_log::test;
 
     use super::*;
-    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+    use crate::graph::network::{LineDirection, NetworkLine};
+    use crate::graph::path::is_path_connected;
+    use crate::graph::tests::{
+        EdgeId, NETWORK_GRAPH, NetworkGraph, PARALLEL_EDGE, PARALLEL_NETWORK_GRAPH, VertexId,
+    };
     use crate::{
-        DecoderConfig, Length, Location, Orientation, SideOfRoad, decode_base64_openlr,
-        encode_base64_openlr,
+        Coordinate, DecoderConfig, Fow, Frc, Length, Location, LocationError, Orientation,
+        SideOfRoad, decode_base64_openlr, encode_base64_openlr, encode_base64_openlr_with_warnings,
     };
 
     #[test]
@@ -158,6 +354,27 @@ mod tests {
         assert_eq!(decoded, line);
     }
 
+    #[test]
+    fn encoder_encode_line_location_reference_003() {
+        let graph: &NetworkGraph = &PARALLEL_NETWORK_GRAPH;
+
+        for path in [
+            vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+            vec![EdgeId(8717174), PARALLEL_EDGE, EdgeId(109783)],
+        ] {
+            let line = Location::Line(LineLocation {
+                path,
+                pos_offset: Length::ZERO,
+                neg_offset: Length::ZERO,
+            });
+
+            let encoded =
+                encode_base64_openlr(&EncoderConfig::default(), graph, line.clone()).unwrap();
+            let decoded = decode_base64_openlr(&DecoderConfig::default(), graph, &encoded).unwrap();
+            assert_eq!(decoded, line);
+        }
+    }
+
     #[test]
     fn encoder_encode_point_along_line_location_reference_001() {
         let graph: &NetworkGraph = &NETWORK_GRAPH;
@@ -167,10 +384,317 @@ mod tests {
             offset: Length::from_meters(39.98046875),
             orientation: Orientation::Backward,
             side: SideOfRoad::Left,
+            coordinate: graph
+                .get_coordinate_along_edge(EdgeId(109782), Length::from_meters(39.98046875))
+                .ok(),
         });
 
         let encoded = encode_base64_openlr(&EncoderConfig::default(), graph, line.clone()).unwrap();
         let decoded = decode_base64_openlr(&DecoderConfig::default(), graph, &encoded).unwrap();
         assert_eq!(decoded, line);
     }
+
+    #[test]
+    fn encoder_encode_poi_location_reference_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let point = PointAlongLineLocation {
+            path: vec![EdgeId(109782)],
+            offset: Length::from_meters(39.98046875),
+            orientation: Orientation::Backward,
+            side: SideOfRoad::Left,
+            coordinate: graph
+                .get_coordinate_along_edge(EdgeId(109782), Length::from_meters(39.98046875))
+                .ok(),
+        };
+
+        let poi = Location::Poi(PoiLocation {
+            point: point.clone(),
+            coordinate: Coordinate {
+                lon: 13.46271,
+                lat: 52.51589,
+            },
+        });
+
+        let encoded = encode_base64_openlr(&EncoderConfig::default(), graph, poi.clone()).unwrap();
+        let decoded = decode_base64_openlr(&DecoderConfig::default(), graph, &encoded).unwrap();
+        assert_eq!(decoded, poi);
+
+        let poi = Location::Poi(PoiLocation {
+            point,
+            coordinate: Coordinate {
+                lon: 13.86271,
+                lat: 52.51589,
+            },
+        });
+
+        assert!(matches!(
+            encode_base64_openlr(&EncoderConfig::default(), graph, poi),
+            Err(EncodeError::PoiOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn encoder_encode_line_warnings_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let line = Location::Line(LineLocation {
+            path: vec![
+                EdgeId(16218),
+                EdgeId(16219),
+                EdgeId(7430347),
+                EdgeId(961825),
+            ],
+            pos_offset: Length::from_meters(7.0),
+            neg_offset: Length::ZERO,
+        });
+
+        let (_, warnings) =
+            encode_base64_openlr_with_warnings(&EncoderConfig::default(), graph, line).unwrap();
+
+        assert_eq!(
+            warnings,
+            [EncodeWarning::PositiveOffsetRounded {
+                requested: Length::from_meters(7.0),
+                encoded: Length::from_meters(8.01171875)
+            }]
+        );
+    }
+
+    #[test]
+    fn encoder_encode_line_warnings_002() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let config = EncoderConfig {
+            max_lrp_distance: Length::from_meters(15.0),
+            ..Default::default()
+        };
+
+        let line = Location::Line(LineLocation {
+            path: vec![EdgeId(-9044470)],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        });
+
+        let (_, warnings) = encode_base64_openlr_with_warnings(&config, graph, line).unwrap();
+
+        assert_eq!(
+            warnings,
+            [
+                EncodeWarning::ForwardExpansionTruncated,
+                EncodeWarning::LrpOnInvalidNode(Coordinate {
+                    lon: 13.4590704,
+                    lat: 52.5144901
+                }),
+                EncodeWarning::LrpOnInvalidNode(Coordinate {
+                    lon: 13.4592303,
+                    lat: 52.5144292
+                })
+            ]
+        );
+    }
+
+    #[test]
+    fn encoder_encode_line_warnings_003() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let line = Location::Line(LineLocation {
+            path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+            pos_offset: Length::from_meters(10.0),
+            neg_offset: Length::from_meters(10.0),
+        });
+
+        let (_, warnings) =
+            encode_base64_openlr_with_warnings(&EncoderConfig::default(), graph, line).unwrap();
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn encoder_encode_line_warnings_004() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let line = Location::Line(LineLocation {
+            path: vec![
+                EdgeId(8717174),
+                EdgeId(8717174),
+                EdgeId(8717175),
+                EdgeId(109783),
+                EdgeId(109783),
+            ],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        });
+
+        let (encoded, warnings) =
+            encode_base64_openlr_with_warnings(&EncoderConfig::default(), graph, line).unwrap();
+
+        assert_eq!(encoded, "CwmShiVYcjPJBgCs/y0zAQ==");
+        assert_eq!(
+            warnings,
+            [
+                EncodeWarning::DuplicateEdgeRemoved(1),
+                EncodeWarning::DuplicateEdgeRemoved(4)
+            ]
+        );
+    }
+
+    #[test]
+    fn encoder_simplify_line_location_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let line = LineLocation {
+            path: vec![
+                EdgeId(8717174),
+                EdgeId(8717175),
+                EdgeId(8717175),
+                EdgeId(109783),
+            ],
+            pos_offset: Length::from_meters(10.0),
+            neg_offset: Length::ZERO,
+        };
+
+        let (simplified, warnings) = simplify_line_location(graph, line.clone()).unwrap();
+        assert_eq!(
+            simplified,
+            LineLocation {
+                path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+                ..line.clone()
+            }
+        );
+        assert_eq!(warnings, [EncodeWarning::DuplicateEdgeRemoved(2)]);
+
+        let (_, warnings) = simplify_line_location(graph, simplified).unwrap();
+        assert!(warnings.is_empty());
+
+        // the location is encoded as it is if not simplified
+        let config = EncoderConfig {
+            simplify: false,
+            ..Default::default()
+        };
+        let error = encode_base64_openlr(&config, graph, Location::Line(line)).unwrap_err();
+        assert_eq!(
+            error,
+            EncodeError::InvalidLocation(LocationError::DuplicateEdge { index: 2 })
+        );
+    }
+
+    #[test]
+    fn encoder_simplify_line_location_inner_stubs() {
+        let coordinate = |lon| Coordinate { lon, lat: 52.51711 };
+        let line = |id, start, end, length, geometry| NetworkLine {
+            id: EdgeId(id),
+            start: VertexId(start),
+            end: VertexId(end),
+            length: Length::from_meters(length),
+            frc: Frc::Frc5,
+            fow: Fow::SingleCarriageway,
+            direction: LineDirection::Forward,
+            geometry,
+        };
+
+        let (a, b, c, d) = (
+            coordinate(13.46112),
+            coordinate(13.46212),
+            coordinate(13.46312),
+            coordinate(13.46412),
+        );
+        let graph = NetworkGraph::from_lines([
+            line(1, 1, 2, 67.6, vec![a, b]),
+            // zero-length loop
+            line(2, 2, 2, 0.0, vec![b, b]),
+            line(3, 2, 3, 67.6, vec![b, c]),
+            // zero-length stub between distinct vertices
+            line(4, 3, 4, 0.0, vec![c, c]),
+            line(5, 4, 5, 67.6, vec![c, d]),
+        ]);
+
+        let line = LineLocation {
+            path: vec![EdgeId(1), EdgeId(2), EdgeId(3), EdgeId(4), EdgeId(5)],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        };
+
+        // the loop is removed, the stub connects the path
+        let (simplified, warnings) = simplify_line_location(&graph, line.clone()).unwrap();
+        assert_eq!(
+            simplified.path,
+            [EdgeId(1), EdgeId(3), EdgeId(4), EdgeId(5)]
+        );
+        assert_eq!(warnings, [EncodeWarning::ZeroLengthEdgesRemoved(1)]);
+        assert!(is_path_connected(&graph, &simplified.path).unwrap());
+    }
+    #[test]
+    fn encoder_resolve_line_lrps_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let config = EncoderConfig::default();
+
+        let path = vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)];
+        let path_length: Length = path
+            .iter()
+            .map(|&e| graph.get_edge_length(e).unwrap())
+            .sum();
+
+        for (pos_offset, neg_offset) in [(0.0, 0.0), (10.0, 20.0), (150.0, 0.0), (0.0, 200.0)] {
+            let (pos_offset, neg_offset) = (
+                Length::from_meters(pos_offset),
+                Length::from_meters(neg_offset),
+            );
+
+            let line = LineLocation {
+                path: path.clone(),
+                pos_offset,
+                neg_offset,
+            };
+
+            let resolved = resolve_line_lrps(&config, graph, line).unwrap();
+            assert!(resolved.lrps.len() >= 2);
+
+            let (first, tail) = (&resolved.lrps[0], &resolved.lrps[resolved.lrps.len() - 2]);
+            assert!(resolved.pos_offset < first.point.dnp());
+            assert!(resolved.neg_offset < tail.point.dnp());
+
+            // the expansion and the trimming preserve the length of the location
+            let lrps_length: Length = resolved.lrps.iter().map(|lrp| lrp.point.dnp()).sum();
+            let length = lrps_length - resolved.pos_offset - resolved.neg_offset;
+            let expected = path_length - pos_offset - neg_offset;
+            assert!(
+                (length - expected).meters().abs() < 1e-6,
+                "{length} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn encoder_resolve_line_lrps_002() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let config = EncoderConfig::default();
+
+        // offset overflowing the first edge of the location
+        let line = LineLocation {
+            path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+            pos_offset: Length::from_meters(150.0),
+            neg_offset: Length::ZERO,
+        };
+
+        let resolved = resolve_line_lrps(&config, graph, line).unwrap();
+        assert!(resolved.pos_offset < resolved.lrps[0].point.dnp());
+
+        // offsets overflowing the whole location
+        for (pos_offset, neg_offset) in [(379.0, 0.0), (0.0, 400.0), (200.0, 200.0)] {
+            let line = LineLocation {
+                path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+                pos_offset: Length::from_meters(pos_offset),
+                neg_offset: Length::from_meters(neg_offset),
+            };
+
+            let error = resolve_line_lrps(&config, graph, line).unwrap_err();
+            assert!(
+                matches!(
+                    error,
+                    EncodeError::InvalidLocation(LocationError::OffsetsExceedLength { .. })
+                ),
+                "{error:?}"
+            );
+        }
+    }
 }
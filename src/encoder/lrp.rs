@@ -3,9 +3,11 @@ use std::ops::Deref;
 
 use tracing::{debug, trace};
 
+use crate::graph::get_edge_end_bearing;
+use crate::graph::path::path_length;
 use crate::{
-    Coordinate, DirectedGraph, EncodeError, EncoderConfig, Length, Line, LineAttributes, Offset,
-    Offsets, PathAttributes, Point,
+    Coordinate, DirectedGraph, EdgeEnd, EncodeError, EncoderConfig, Frc, Length, Line,
+    LineAttributes, Offset, Offsets, PathAttributes, Point,
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -58,26 +60,8 @@ impl<EdgeId: Copy> LocRefPoint<EdgeId> {
     {
         let first_edge = edges[0];
         let coordinate = graph.get_vertex_coordinate(graph.get_edge_start_vertex(first_edge)?)?;
-        let projection = Length::ZERO;
-        let bearing_distance = config.bearing_distance;
-
-        let lfrcnp = edges
-            .iter()
-            .try_fold(graph.get_edge_frc(first_edge)?, |acc, &e| {
-                Ok::<_, G::Error>(acc.max(graph.get_edge_frc(e)?))
-            })?;
-
-        let dnp = edges.iter().try_fold(Length::ZERO, |acc, &e| {
-            Ok::<_, G::Error>(acc + graph.get_edge_length(e)?)
-        })?;
-
-        let line = LineAttributes {
-            frc: graph.get_edge_frc(first_edge)?,
-            fow: graph.get_edge_fow(first_edge)?,
-            bearing: graph.get_edge_bearing(first_edge, projection, bearing_distance)?,
-        };
-
-        let path = PathAttributes { lfrcnp, dnp };
+        let line = lrp_line_attributes(config, graph, first_edge, EdgeEnd::Start)?;
+        let path = lrp_path_attributes(graph, &edges)?;
 
         Ok(Self {
             edges,
@@ -100,14 +84,7 @@ impl<EdgeId: Copy> LocRefPoint<EdgeId> {
         G: DirectedGraph<EdgeId = EdgeId>,
     {
         let coordinate = graph.get_vertex_coordinate(graph.get_edge_end_vertex(edge)?)?;
-        let projection = graph.get_edge_length(edge)?;
-        let bearing_distance = config.bearing_distance.reverse();
-
-        let line = LineAttributes {
-            frc: graph.get_edge_frc(edge)?,
-            fow: graph.get_edge_fow(edge)?,
-            bearing: graph.get_edge_bearing(edge, projection, bearing_distance)?,
-        };
+        let line = lrp_line_attributes(config, graph, edge, EdgeEnd::End)?;
 
         Ok(Self {
             edges: vec![],
@@ -131,22 +108,16 @@ impl<EdgeId: Copy> LocRefPoint<EdgeId> {
     where
         G: DirectedGraph<EdgeId = EdgeId>,
     {
-        let bearing_distance = config.bearing_distance;
-        let lfrcnp = graph.get_edge_frc(edge)?;
-        let dnp = graph.get_edge_length(edge)? - projection;
-
-        let line = LineAttributes {
-            frc: graph.get_edge_frc(edge)?,
-            fow: graph.get_edge_fow(edge)?,
-            bearing: graph.get_edge_bearing(edge, projection, bearing_distance)?,
-        };
+        let line = lrp_projected_line_attributes(config, graph, edge, projection, EdgeEnd::Start)?;
+        let mut path = lrp_path_attributes(graph, &[edge])?;
+        path.dnp -= projection;
 
         Ok(Self {
             edges: vec![edge],
             point: Point {
                 coordinate,
                 line,
-                path: Some(PathAttributes { lfrcnp, dnp }),
+                path: Some(path),
             },
             projection_coordinate: Some(coordinate),
         })
@@ -163,13 +134,7 @@ impl<EdgeId: Copy> LocRefPoint<EdgeId> {
         G: DirectedGraph<EdgeId = EdgeId>,
     {
         let projection = graph.get_distance_along_edge(edge, coordinate)?;
-        let bearing_distance = config.bearing_distance.reverse();
-
-        let line = LineAttributes {
-            frc: graph.get_edge_frc(edge)?,
-            fow: graph.get_edge_fow(edge)?,
-            bearing: graph.get_edge_bearing(edge, projection, bearing_distance)?,
-        };
+        let line = lrp_projected_line_attributes(config, graph, edge, projection, EdgeEnd::End)?;
 
         Ok(Self {
             edges: vec![],
@@ -183,6 +148,66 @@ impl<EdgeId: Copy> LocRefPoint<EdgeId> {
     }
 }
 
+/// Computes the attributes of the line of an LRP at a node, as the encoder does: the LRP is at the
+/// start vertex of the edge (at the end vertex for the last LRP), and the bearing is computed along
+/// the edge from that vertex (see [`EncoderConfig::bearing_distance`]).
+///
+/// Useful to build LRPs from other sources (for example TMC location tables) with the same
+/// attributes the encoder would compute.
+pub fn lrp_line_attributes<G: DirectedGraph>(
+    config: &EncoderConfig,
+    graph: &G,
+    edge: G::EdgeId,
+    at: EdgeEnd,
+) -> Result<LineAttributes, G::Error> {
+    let bearing = get_edge_end_bearing(graph, edge, at, config.bearing_distance)?;
+
+    Ok(LineAttributes {
+        frc: graph.get_edge_frc(edge)?,
+        fow: graph.get_edge_fow(edge)?,
+        bearing: bearing.round_to_sector(config.bearing_rounding),
+    })
+}
+
+/// Computes the attributes of the line of an LRP projected into the edge at the given distance from
+/// its start vertex, as the encoder does: the bearing is computed towards the end vertex of the
+/// edge, or towards its start vertex for the last LRP (`EdgeEnd::End`).
+pub fn lrp_projected_line_attributes<G: DirectedGraph>(
+    config: &EncoderConfig,
+    graph: &G,
+    edge: G::EdgeId,
+    projection: Length,
+    at: EdgeEnd,
+) -> Result<LineAttributes, G::Error> {
+    let bearing_distance = match at {
+        EdgeEnd::Start => config.bearing_distance,
+        EdgeEnd::End => config.bearing_distance.reverse(),
+    };
+    let bearing = graph.get_edge_bearing(edge, projection, bearing_distance)?;
+
+    Ok(LineAttributes {
+        frc: graph.get_edge_frc(edge)?,
+        fow: graph.get_edge_fow(edge)?,
+        bearing: bearing.round_to_sector(config.bearing_rounding),
+    })
+}
+
+/// Computes the attributes of the path from an LRP to the next one, as the encoder does: the
+/// LFRCNP is the lowest FRC of the edges of the path and the DNP is the length of the path.
+pub fn lrp_path_attributes<G: DirectedGraph>(
+    graph: &G,
+    edges: &[G::EdgeId],
+) -> Result<PathAttributes, G::Error> {
+    let lfrcnp = edges.iter().try_fold(Frc::Frc0, |acc, &e| {
+        Ok::<_, G::Error>(acc.max(graph.get_edge_frc(e)?))
+    })?;
+
+    Ok(PathAttributes {
+        lfrcnp,
+        dnp: path_length(graph, edges)?,
+    })
+}
+
 impl<EdgeId: Copy + Debug> LocRefPoints<EdgeId> {
     /// Trim the LRPs according to the positive and negative offsets.
     pub fn trim<G>(
@@ -214,7 +239,11 @@ impl<EdgeId: Copy + Debug> LocRefPoints<EdgeId> {
         }
 
         if self.lrps.len() < 2 {
-            return Err(EncodeError::InvalidLrpOffsets);
+            return Err(EncodeError::InvalidLrpOffsets {
+                pos_offset: self.pos_offset,
+                neg_offset: self.neg_offset,
+                lrps: self.lrps.len(),
+            });
         }
 
         let mut lrps_rev = self.lrps.iter_mut().rev();
@@ -482,4 +511,32 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn encoder_lrp_attributes_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let config = EncoderConfig::default();
+
+        let edges = vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)];
+        let path = lrp_path_attributes(graph, &edges).unwrap();
+        assert_eq!(path.dnp, Length::from_meters(379.0));
+
+        let line = lrp_line_attributes(&config, graph, edges[0], EdgeEnd::Start).unwrap();
+        let lrp = LocRefPoint::node(&config, graph, edges.clone()).unwrap();
+        assert_eq!(lrp.point.line, line);
+        assert_eq!(lrp.point.path, Some(path));
+
+        let line = lrp_line_attributes(&config, graph, edges[2], EdgeEnd::End).unwrap();
+        let lrp = LocRefPoint::last_node(&config, graph, edges[2]).unwrap();
+        assert_eq!(lrp.point.line, line);
+
+        // projected at the start vertex: the same bearing of the LRP at the node
+        let projected =
+            lrp_projected_line_attributes(&config, graph, edges[0], Length::ZERO, EdgeEnd::Start)
+                .unwrap();
+        assert_eq!(
+            projected,
+            lrp_line_attributes(&config, graph, edges[0], EdgeEnd::Start).unwrap()
+        );
+    }
 }
@@ -0,0 +1,100 @@
+use std::fmt::Debug;
+
+use crate::{IntermediateReason, Length};
+
+/// Receives the counters of the encoder work, to export them as metrics and monitor the quality of
+/// the location references across map updates (for example more intermediate LRPs or longer
+/// expansions for the same locations).
+///
+/// The encoder calls, for every line (including the lines of the other location types):
+/// - [`on_expansion`](Self::on_expansion) once, with the lengths the line was expanded by to start
+///   and end at valid nodes;
+/// - [`on_coverage_iteration`](Self::on_coverage_iteration) once per shortest path computed to
+///   cover the line;
+/// - [`on_intermediate_lrp`](Self::on_intermediate_lrp) once per intermediate LRP added, with the
+///   reason it was added;
+/// - [`on_lrps_resolved`](Self::on_lrps_resolved) once, with the number of LRPs of the line.
+///
+/// The metrics are shared by all the encodings that use the same config (also across threads),
+/// so they are recorded through a shared reference. Every method does nothing by default.
+pub trait EncoderMetrics: Debug + Sync {
+    /// Called with the length of the backward expansion (before the first edge of the line) and
+    /// of the forward expansion (after the last edge of the line).
+    fn on_expansion(&self, backward: Length, forward: Length) {
+        let _ = (backward, forward);
+    }
+
+    /// Called before each shortest path search that covers (a part of) the line.
+    fn on_coverage_iteration(&self) {}
+
+    /// Called for each intermediate LRP added to the location reference.
+    fn on_intermediate_lrp(&self, reason: IntermediateReason) {
+        let _ = reason;
+    }
+
+    /// Called with the number of LRPs (including the first and the last one) resolved for the line.
+    fn on_lrps_resolved(&self, count: usize) {
+        let _ = count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+    use crate::{EncoderConfig, LineLocation, Location, encode_base64_openlr};
+
+    #[derive(Debug, Default)]
+    struct Counters {
+        expansions: AtomicUsize,
+        coverage_iterations: AtomicUsize,
+        intermediates: AtomicUsize,
+        lrps: AtomicUsize,
+    }
+
+    impl EncoderMetrics for Counters {
+        fn on_expansion(&self, _: Length, _: Length) {
+            self.expansions.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_coverage_iteration(&self) {
+            self.coverage_iterations.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_intermediate_lrp(&self, _: IntermediateReason) {
+            self.intermediates.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_lrps_resolved(&self, count: usize) {
+            self.lrps.fetch_add(count, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn encoder_metrics_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let counters: &'static Counters = Box::leak(Box::default());
+
+        let config = EncoderConfig {
+            metrics: Some(counters),
+            ..EncoderConfig::default()
+        };
+
+        let location = Location::Line(LineLocation {
+            path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        });
+        encode_base64_openlr(&config, graph, location).unwrap();
+
+        let lrps = counters.lrps.load(Ordering::Relaxed);
+        assert_eq!(counters.expansions.load(Ordering::Relaxed), 1);
+        assert!(counters.coverage_iterations.load(Ordering::Relaxed) >= 1);
+        assert!(lrps >= 2);
+        assert_eq!(counters.intermediates.load(Ordering::Relaxed), lrps - 2);
+    }
+}
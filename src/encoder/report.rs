@@ -0,0 +1,227 @@
+use crate::encoder::lrp::LocRefPoint;
+use crate::{Coordinate, EncodeWarning, Path, Point};
+
+/// Diagnostics collected while encoding a location.
+/// Useful to understand (and visualize) how the location was covered by the LRPs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodeReport<EdgeId> {
+    /// Path prepended to the location so that it starts on a valid node.
+    pub backward_expansion: Path<EdgeId>,
+    /// Path appended to the location so that it ends on a valid node.
+    pub forward_expansion: Path<EdgeId>,
+    /// Shortest paths that cover the (expanded) location, in the order they were computed.
+    pub coverage: Vec<CoverageStep<EdgeId>>,
+    /// LRPs added between the first and the last LRP, before the LRPs are trimmed by the offsets.
+    pub intermediates: Vec<IntermediateLrp>,
+    /// Resolved LRPs of the location reference, after being trimmed by the offsets.
+    /// Empty if the LRPs couldn't be resolved.
+    pub lrps: Vec<ResolvedLrp<EdgeId>>,
+    /// Non-fatal issues found while encoding.
+    pub warnings: Vec<EncodeWarning>,
+}
+
+impl<EdgeId> Default for EncodeReport<EdgeId> {
+    fn default() -> Self {
+        Self {
+            backward_expansion: Path::default(),
+            forward_expansion: Path::default(),
+            coverage: vec![],
+            intermediates: vec![],
+            lrps: vec![],
+            warnings: vec![],
+        }
+    }
+}
+
+/// Part of the location covered by a single shortest path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageStep<EdgeId> {
+    /// Edges of the location covered by the shortest path.
+    pub edges: Vec<EdgeId>,
+    /// True if the shortest path diverges from the rest of the location, in which case a new
+    /// intermediate LRP is added at the end of the covered edges.
+    pub diverges: bool,
+}
+
+/// LRP added between the first and the last LRP of the location.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntermediateLrp {
+    pub coordinate: Coordinate,
+    pub reason: IntermediateReason,
+}
+
+/// Why an intermediate LRP was added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntermediateReason {
+    /// The shortest path diverges from the location.
+    ShortestPathDivergence,
+    /// The distance to the next LRP exceeds the maximum LRP distance.
+    MaxLrpDistanceExceeded,
+    /// The path to the next LRP has a nearly equal alternative (see
+    /// [`EncoderConfig::alternative_path_ratio`](crate::EncoderConfig::alternative_path_ratio)).
+    AlternativePath,
+}
+
+/// Location Reference Point (LRP) resolved by the encoder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedLrp<EdgeId> {
+    pub point: Point,
+    /// The shortest path to the next LRP (empty for the last LRP).
+    pub edges: Vec<EdgeId>,
+    /// Where the LRP was placed on the line, only if the LRP is not on a node.
+    pub projection: Option<Coordinate>,
+}
+
+impl<EdgeId: Copy> EncodeReport<EdgeId> {
+    pub(crate) fn record_intermediate(
+        &mut self,
+        lrp: &LocRefPoint<EdgeId>,
+        reason: IntermediateReason,
+    ) {
+        self.intermediates.push(IntermediateLrp {
+            coordinate: lrp.point.coordinate,
+            reason,
+        });
+    }
+
+    pub(crate) fn record_lrps(&mut self, lrps: &[LocRefPoint<EdgeId>]) {
+        self.lrps = lrps.iter().map(ResolvedLrp::from).collect();
+    }
+}
+
+impl<EdgeId: Copy> From<&LocRefPoint<EdgeId>> for ResolvedLrp<EdgeId> {
+    fn from(lrp: &LocRefPoint<EdgeId>) -> Self {
+        Self {
+            point: lrp.point,
+            edges: lrp.edges.clone(),
+            projection: lrp.projection_coordinate,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+    use crate::{
+        EncoderConfig, Length, LineLocation, Location, LocationReference,
+        deserialize_base64_openlr, encode_base64_openlr_with_report,
+    };
+
+    #[test]
+    fn encoder_report_expansion_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let line = LineLocation {
+            path: vec![EdgeId(16219)],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        };
+
+        let mut report = EncodeReport::default();
+        let code = encode_base64_openlr_with_report(
+            &EncoderConfig::default(),
+            graph,
+            Location::Line(line),
+            &mut report,
+        )
+        .unwrap();
+
+        assert_eq!(report.backward_expansion, Path::default());
+        assert_eq!(
+            report.forward_expansion,
+            Path {
+                length: Length::from_meters(78.0),
+                edges: vec![EdgeId(7430347)]
+            }
+        );
+        assert_eq!(
+            report.coverage,
+            [CoverageStep {
+                edges: vec![EdgeId(16219), EdgeId(7430347)],
+                diverges: false
+            }]
+        );
+        assert!(report.intermediates.is_empty());
+        assert!(report.warnings.is_empty());
+
+        let Ok(LocationReference::Line(line)) = deserialize_base64_openlr(code) else {
+            unreachable!()
+        };
+
+        assert_eq!(report.lrps.len(), line.points.len());
+        assert_eq!(report.lrps[0].edges, [EdgeId(16219), EdgeId(7430347)]);
+        assert!(report.lrps[1].edges.is_empty());
+        assert_eq!(
+            report.lrps[0].point.dnp(),
+            Length::from_meters(187.0),
+            "Expanded location length"
+        );
+    }
+
+    #[test]
+    fn encoder_report_intermediates_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let config = EncoderConfig {
+            max_lrp_distance: Length::from_meters(100.0),
+            ..Default::default()
+        };
+
+        let line = LineLocation {
+            path: vec![EdgeId(16218), EdgeId(16219)],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        };
+
+        let mut report = EncodeReport::default();
+        encode_base64_openlr_with_report(&config, graph, Location::Line(line), &mut report)
+            .unwrap();
+
+        assert_eq!(
+            report.coverage,
+            [
+                CoverageStep {
+                    edges: vec![EdgeId(16218)],
+                    diverges: true
+                },
+                CoverageStep {
+                    edges: vec![EdgeId(16219)],
+                    diverges: true
+                },
+                CoverageStep {
+                    edges: vec![EdgeId(7430347)],
+                    diverges: false
+                }
+            ]
+        );
+
+        use IntermediateReason::*;
+        assert_eq!(
+            report
+                .intermediates
+                .iter()
+                .map(|lrp| lrp.reason)
+                .collect::<Vec<_>>(),
+            [
+                MaxLrpDistanceExceeded,
+                MaxLrpDistanceExceeded,
+                ShortestPathDivergence,
+                MaxLrpDistanceExceeded,
+                ShortestPathDivergence
+            ]
+        );
+
+        // the last intermediate LRP is trimmed since the location ends before reaching it
+        assert_eq!(report.lrps.len(), report.intermediates.len() + 1);
+        let lrps = &report.lrps[1..report.lrps.len() - 1];
+        for (intermediate, lrp) in report.intermediates.iter().zip(lrps) {
+            assert_eq!(intermediate.coordinate, lrp.point.coordinate);
+        }
+
+        let projected = report.lrps.iter().filter(|lrp| lrp.projection.is_some());
+        assert_eq!(projected.count(), 3);
+    }
+}
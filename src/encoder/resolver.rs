@@ -1,30 +1,93 @@
 use tracing::{debug, trace, warn};
 
-use crate::EncodeError::InvalidLrp;
 use crate::encoder::lrp::{LocRefPoint, LocRefPoints};
-use crate::encoder::shortest_path::{Intermediate, ShortestPath, shortest_path_location};
-use crate::{DirectedGraph, EncodeError, EncoderConfig, LineLocation};
+use crate::encoder::report::{CoverageStep, EncodeReport, IntermediateReason};
+use crate::encoder::shortest_path::{
+    Intermediate, SearchBudget, ShortestPath, alternative_path_intermediate, shortest_path_location,
+};
+use crate::{DirectedGraph, EncodeError, EncoderConfig, Length, LineLocation};
 
 /// Resolves all the LRPs that should be necessary to encode the given line.
 pub fn resolve_lrps<G: DirectedGraph>(
     config: &EncoderConfig,
     graph: &G,
     line: LineLocation<G::EdgeId>,
+    mut report: Option<&mut EncodeReport<G::EdgeId>>,
 ) -> Result<LocRefPoints<G::EdgeId>, EncodeError<G::Error>> {
     debug!("Resolving LRPs for {} edges", line.path.len());
+    let config = &EncoderConfig {
+        max_lrp_distance: max_relative_lrp_distance(config, graph, &line.path)?,
+        ..*config
+    };
+
     let mut location: Vec<G::EdgeId> = line.path.clone();
 
     let last_edge = location[location.len() - 1];
     let mut candidate_lrps = vec![];
+    // why the next LRP is added, if it's an intermediate
+    let mut reason = IntermediateReason::ShortestPathDivergence;
+    let mut budget = SearchBudget::new(config.max_expanded_nodes, config.max_coverage_iterations);
 
     // Step – 7 Find shortest paths until the whole location is covered by a concatenation of these.
     while !location.is_empty() {
+        budget.next_iteration()?;
+        if let Some(metrics) = config.metrics {
+            metrics.on_coverage_iteration();
+        }
+
         // Step - 3 Determine coverage of the location by a shortest-path.
-        match shortest_path_location(graph, &location, config.max_lrp_distance)? {
+        let shortest_path = shortest_path_location(
+            graph,
+            &location,
+            config.max_lrp_distance,
+            config.route_cost,
+            &mut budget,
+        )?;
+
+        // Determine LRPs for alternative paths: the path covered up to the next LRP should not
+        // have any nearly equal alternative
+        let covered = match shortest_path {
+            ShortestPath::Location => Some(&location[..]),
+            ShortestPath::Intermediate(Intermediate { location_index }) => {
+                location.get(..=location_index)
+            }
+            ShortestPath::NotFound => None,
+        };
+
+        if let Some((covered, max_ratio)) = covered.zip(config.alternative_path_ratio)
+            && let Some(Intermediate { location_index }) = alternative_path_intermediate(
+                graph,
+                covered,
+                max_ratio,
+                config.route_cost,
+                &mut budget,
+            )?
+        {
+            let mut loc = location.split_off(location_index);
+            std::mem::swap(&mut location, &mut loc);
+            trace!("Found (node) intermediate LRP for alternative paths of {loc:?}");
+            if let Some(report) = report.as_mut() {
+                report.coverage.push(CoverageStep {
+                    edges: loc.clone(),
+                    diverges: false,
+                });
+            }
+            candidate_lrps.push((LocRefPoint::node(config, graph, loc)?, reason));
+            reason = IntermediateReason::AlternativePath;
+            continue;
+        }
+
+        match shortest_path {
             // Step – 4 Check whether the calculated shortest-path covers the location completely.
             ShortestPath::Location => {
                 trace!("Found (node) LRP for {location:?}");
-                candidate_lrps.push(LocRefPoint::node(config, graph, location)?);
+                if let Some(report) = report.as_mut() {
+                    report.coverage.push(CoverageStep {
+                        edges: location.clone(),
+                        diverges: false,
+                    });
+                }
+                candidate_lrps.push((LocRefPoint::node(config, graph, location)?, reason));
                 break;
             }
             // Step – 6 Restart shortest path calculation between the new intermediate location
@@ -33,30 +96,72 @@ pub fn resolve_lrps<G: DirectedGraph>(
                 let mut loc = location.split_off(location_index);
                 std::mem::swap(&mut location, &mut loc);
                 trace!("Found (node) intermediate LRP for {loc:?}");
-                candidate_lrps.push(LocRefPoint::node(config, graph, loc)?);
+                if let Some(report) = report.as_mut() {
+                    report.coverage.push(CoverageStep {
+                        edges: loc.clone(),
+                        diverges: true,
+                    });
+                }
+                candidate_lrps.push((LocRefPoint::node(config, graph, loc)?, reason));
+                reason = IntermediateReason::ShortestPathDivergence;
             }
             ShortestPath::NotFound => {
-                return Err(EncodeError::RouteNotFound);
+                let end_index = line.path.len() - 1;
+                return Err(EncodeError::RouteNotFound {
+                    start_index: end_index + 1 - location.len(),
+                    end_index,
+                });
             }
         }
     }
 
-    candidate_lrps.push(LocRefPoint::last_node(config, graph, last_edge)?);
+    candidate_lrps.push((LocRefPoint::last_node(config, graph, last_edge)?, reason));
 
-    let lrp_edges = || candidate_lrps.iter().flat_map(|lrp| &lrp.edges);
+    let lrp_edges = || candidate_lrps.iter().flat_map(|(lrp, _)| &lrp.edges);
     debug_assert_eq!(line.path.len(), lrp_edges().count());
 
     // Step – 8 Check validity of the location reference path.
-    if line.path.iter().zip(lrp_edges()).any(|(e1, e2)| e1 != e2) {
+    if let Some(index) = line
+        .path
+        .iter()
+        .zip(lrp_edges())
+        .position(|(e1, e2)| e1 != e2)
+    {
         warn!("Resolved LRPs don't exactly cover the location edges");
-        return Err(InvalidLrp);
+        return Err(EncodeError::InvalidLrp { index });
     }
 
     // Step – 9 Add a sufficient number of additional intermediate location reference points if the
     // distance between two location reference points exceeds the maximum distance.
     let mut lrps = Vec::with_capacity(candidate_lrps.len());
-    for lrp in candidate_lrps {
+    for (lrp, reason) in candidate_lrps {
+        let is_intermediate = !lrps.is_empty() && !lrp.point.is_last();
+        let lrps_count = lrps.len();
         split_lrp(config, graph, lrp, &mut lrps)?;
+
+        if let Some(metrics) = config.metrics {
+            if is_intermediate {
+                metrics.on_intermediate_lrp(reason);
+            }
+
+            for _ in &lrps[lrps_count + 1..] {
+                metrics.on_intermediate_lrp(IntermediateReason::MaxLrpDistanceExceeded);
+            }
+        }
+
+        if let Some(report) = report.as_mut() {
+            if is_intermediate {
+                report.record_intermediate(&lrps[lrps_count], reason);
+            }
+
+            for lrp in &lrps[lrps_count + 1..] {
+                report.record_intermediate(lrp, IntermediateReason::MaxLrpDistanceExceeded);
+            }
+        }
+    }
+
+    if let Some(metrics) = config.metrics {
+        metrics.on_lrps_resolved(lrps.len());
     }
 
     Ok(LocRefPoints {
@@ -66,6 +171,34 @@ pub fn resolve_lrps<G: DirectedGraph>(
     })
 }
 
+/// Returns the max distance between consecutive LRPs of the config, shortened at high latitudes so
+/// that every LRP can be serialized relative to the previous one: the LRPs are closer than their
+/// DNP, which is in turn limited by the max LRP distance.
+fn max_relative_lrp_distance<G: DirectedGraph>(
+    config: &EncoderConfig,
+    graph: &G,
+    path: &[G::EdgeId],
+) -> Result<Length, G::Error> {
+    let mut max_lrp_distance = config.max_lrp_distance;
+
+    // the range of relative coordinates is the shortest at the location vertex closest to a pole
+    for &edge in path {
+        for vertex in [
+            graph.get_edge_start_vertex(edge)?,
+            graph.get_edge_end_vertex(edge)?,
+        ] {
+            let distance = graph.get_vertex_coordinate(vertex)?.max_relative_distance();
+            max_lrp_distance = max_lrp_distance.min(distance);
+        }
+    }
+
+    if max_lrp_distance < config.max_lrp_distance {
+        debug!("Max LRP distance shortened to {max_lrp_distance} for relative coordinates");
+    }
+
+    Ok(max_lrp_distance)
+}
+
 /// If the maximum distance between two subsequent location reference points is exceeded additional
 /// location reference points shall be placed at valid nodes along the location reference path
 /// between these two location reference points. If placing on valid nodes is not possible an
@@ -128,7 +261,7 @@ mod tests {
     use test_log::test;
 
     use super::*;
-    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph, PARALLEL_NETWORK_GRAPH};
     use crate::{Bearing, Coordinate, Fow, Frc, Length, LineAttributes, PathAttributes, Point};
 
     #[test]
@@ -143,7 +276,7 @@ mod tests {
             neg_offset: Length::ZERO,
         };
 
-        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line).unwrap();
+        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line, None).unwrap();
         assert_eq!(lrps.len(), 2);
 
         assert_eq!(
@@ -202,7 +335,7 @@ mod tests {
             neg_offset: Length::ZERO,
         };
 
-        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line).unwrap();
+        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line, None).unwrap();
         assert_eq!(lrps.len(), 2);
 
         assert_eq!(
@@ -267,7 +400,7 @@ mod tests {
             neg_offset: Length::ZERO,
         };
 
-        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line).unwrap();
+        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line, None).unwrap();
         assert_eq!(lrps.len(), 3);
 
         assert_eq!(
@@ -358,7 +491,7 @@ mod tests {
             neg_offset: Length::ZERO,
         };
 
-        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line).unwrap();
+        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line, None).unwrap();
         assert_eq!(lrps.len(), 3);
 
         assert_eq!(
@@ -447,7 +580,7 @@ mod tests {
             neg_offset: Length::ZERO,
         };
 
-        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line).unwrap();
+        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line, None).unwrap();
         assert_eq!(lrps.len(), 3);
 
         assert_eq!(
@@ -536,7 +669,7 @@ mod tests {
             neg_offset: Length::ZERO,
         };
 
-        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line).unwrap();
+        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line, None).unwrap();
         assert_eq!(lrps.len(), 5);
 
         assert_eq!(
@@ -664,7 +797,7 @@ mod tests {
             neg_offset: Length::ZERO,
         };
 
-        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line).unwrap();
+        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line, None).unwrap();
         assert_eq!(lrps.len(), 2);
 
         assert_eq!(
@@ -728,7 +861,7 @@ mod tests {
             neg_offset: Length::from_meters(14.0),
         };
 
-        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line).unwrap();
+        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line, None).unwrap();
         assert_eq!(lrps.len(), 2);
 
         assert_eq!(
@@ -792,7 +925,7 @@ mod tests {
             neg_offset: Length::ZERO,
         };
 
-        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line).unwrap();
+        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line, None).unwrap();
         assert_eq!(lrps.len(), 2);
 
         assert_eq!(
@@ -851,7 +984,7 @@ mod tests {
             neg_offset: Length::ZERO,
         };
 
-        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line).unwrap();
+        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line, None).unwrap();
         assert_eq!(lrps.len(), 2);
 
         assert_eq!(
@@ -913,7 +1046,7 @@ mod tests {
             neg_offset: Length::ZERO,
         };
 
-        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line).unwrap();
+        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line, None).unwrap();
         assert_eq!(lrps.len(), 3);
 
         assert_eq!(
@@ -998,7 +1131,7 @@ mod tests {
             neg_offset: Length::ZERO,
         };
 
-        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line).unwrap();
+        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line, None).unwrap();
         assert_eq!(lrps.len(), 6);
 
         assert_eq!(
@@ -1161,7 +1294,7 @@ mod tests {
             neg_offset: Length::ZERO,
         };
 
-        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line).unwrap();
+        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line, None).unwrap();
         assert_eq!(lrps.len(), 5);
 
         assert_eq!(
@@ -1279,4 +1412,40 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn encoder_resolve_lrps_014() {
+        let graph: &NetworkGraph = &PARALLEL_NETWORK_GRAPH;
+
+        let line = LineLocation {
+            path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        };
+
+        let config = EncoderConfig::default();
+        let LocRefPoints { lrps, .. } = resolve_lrps(&config, graph, line.clone(), None).unwrap();
+        assert_eq!(lrps.len(), 2);
+
+        // the parallel edge is a nearly equal alternative to the edge 8717175
+        let config = EncoderConfig {
+            alternative_path_ratio: Some(0.5),
+            ..Default::default()
+        };
+
+        let mut report = EncodeReport::default();
+        let LocRefPoints { lrps, .. } =
+            resolve_lrps(&config, graph, line, Some(&mut report)).unwrap();
+        assert_eq!(lrps.len(), 3);
+        assert_eq!(lrps[0].edges, [EdgeId(8717174)]);
+        assert_eq!(lrps[1].edges, [EdgeId(8717175), EdgeId(109783)]);
+        assert_eq!(
+            report
+                .intermediates
+                .iter()
+                .map(|lrp| lrp.reason)
+                .collect::<Vec<_>>(),
+            [IntermediateReason::AlternativePath]
+        );
+    }
 }
@@ -3,11 +3,12 @@ use std::fmt::Debug;
 use std::hash::Hash;
 
 use radix_heap::RadixHeapMap;
-use rustc_hash::{FxBuildHasher, FxHashMap};
+use rustc_hash::{FxBuildHasher, FxHashMap, FxHashSet};
 use tracing::{debug, warn};
 
-use crate::graph::dijkstra::unpack_path;
-use crate::graph::path::{is_node_valid, is_path_loop};
+use crate::graph::dijkstra::{ShortestDistances, previous_edges, unpack_path};
+use crate::graph::path::{is_node_valid, is_path_loop, is_turn_restricted_after};
+use crate::routing::{EdgeCost, RouteCost, path_cost};
 use crate::{DirectedGraph, EncodeError, Length, LocationError};
 
 /// Represents a subset, or the totality, of the location that is a shortest path.
@@ -32,6 +33,56 @@ pub struct Intermediate {
     pub location_index: usize,
 }
 
+/// Bounds of the search space of the encoder, shared by all the route searches of a location (see
+/// [`crate::EncoderConfig::max_expanded_nodes`] and
+/// [`crate::EncoderConfig::max_coverage_iterations`]).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchBudget {
+    max_expanded_nodes: Option<usize>,
+    max_iterations: Option<usize>,
+    expanded_nodes: usize,
+    iterations: usize,
+}
+
+impl SearchBudget {
+    pub const fn new(max_expanded_nodes: Option<usize>, max_iterations: Option<usize>) -> Self {
+        Self {
+            max_expanded_nodes,
+            max_iterations,
+            expanded_nodes: 0,
+            iterations: 0,
+        }
+    }
+
+    /// Spends the budget of a node expanded by a route search.
+    pub fn expand_node<E>(&mut self) -> Result<(), EncodeError<E>> {
+        self.expanded_nodes += 1;
+        if self
+            .max_expanded_nodes
+            .is_some_and(|max| self.expanded_nodes > max)
+        {
+            return Err(self.exceeded());
+        }
+        Ok(())
+    }
+
+    /// Spends the budget of an iteration of the loop covering the location with shortest paths.
+    pub fn next_iteration<E>(&mut self) -> Result<(), EncodeError<E>> {
+        self.iterations += 1;
+        if self.max_iterations.is_some_and(|max| self.iterations > max) {
+            return Err(self.exceeded());
+        }
+        Ok(())
+    }
+
+    const fn exceeded<E>(&self) -> EncodeError<E> {
+        EncodeError::SearchLimitExceeded {
+            expanded_nodes: self.expanded_nodes,
+            iterations: self.iterations,
+        }
+    }
+}
+
 /// Returns the shortest route that follow the given location up until the location path diverges
 /// from the shortest path, in which case the path route is split at an intermediate edge.
 ///
@@ -54,16 +105,20 @@ pub struct Intermediate {
 ///
 /// If no shortest-path can be calculated the encoding should fail. But this should never happen as
 /// the location consists of concatenated lines.
+///
+/// The paths minimize the given cost, the max LRP distance is checked against their length.
 pub fn shortest_path_location<G: DirectedGraph>(
     graph: &G,
     location: &[G::EdgeId],
     max_lrp_distance: Length,
+    cost: RouteCost,
+    budget: &mut SearchBudget,
 ) -> Result<ShortestPath, EncodeError<G::Error>> {
     debug!("Computing shortest path following {location:?}");
 
     let (origin, destination) = match location.first().zip(location.last()) {
         Some((origin, destination)) => (*origin, *destination),
-        _ => return Err(LocationError::Empty.into()),
+        _ => return Err(LocationError::EmptyPath.into()),
     };
 
     if origin == destination && location.len() > 1 {
@@ -88,15 +143,15 @@ pub fn shortest_path_location<G: DirectedGraph>(
         }));
     }
 
-    let max_length = location.iter().try_fold(Length::ZERO, |acc, &e| {
-        Ok::<_, G::Error>(acc + graph.get_edge_length(e)?)
-    })?;
+    let max_cost = path_cost(graph, location, &cost)?;
 
     let origin_length = graph.get_edge_length(origin)?;
+    let origin_cost = cost.cost(graph, origin)?;
 
-    let mut shortest_distances = FxHashMap::from_iter([(origin, origin_length)]);
+    let mut shortest_costs = ShortestDistances::new(graph);
+    shortest_costs.insert(origin, origin_cost);
     let mut previous_map: FxHashMap<G::EdgeId, G::EdgeId> = FxHashMap::default();
-    let mut heap = RadixHeapMap::from_iter([(Reverse(origin_length), origin)]);
+    let mut heap = RadixHeapMap::from_iter([(Reverse(origin_cost), (origin, origin_length))]);
     let mut intermediator = Intermediator::new(graph, location, max_lrp_distance)?;
 
     let mut location_idx = FxHashMap::with_capacity_and_hasher(location.len(), FxBuildHasher);
@@ -105,7 +160,7 @@ pub fn shortest_path_location<G: DirectedGraph>(
         location_idx.insert(*e, i);
     }
 
-    while let Some((Reverse(h_distance), h_edge)) = heap.pop() {
+    while let Some((Reverse(h_cost), (h_edge, h_distance))) = heap.pop() {
         if let Some(&location_index) = location_idx.get(&h_edge) {
             // Step – 5 Determine the position of a new intermediate location reference point
             if let Some(intermediate) =
@@ -142,32 +197,34 @@ pub fn shortest_path_location<G: DirectedGraph>(
         }
 
         // check if we already know a cheaper way to get to the end of this path from the origin
-        let shortest_distance = *shortest_distances.get(&h_edge).unwrap_or(&Length::MAX);
-        if h_distance > shortest_distance {
+        if h_cost > shortest_costs.get(h_edge) {
             continue;
         }
 
+        budget.expand_node()?;
         let exiting_edges = graph.vertex_exiting_edges(graph.get_edge_end_vertex(h_edge)?)?;
 
         for (edge, _) in exiting_edges {
-            if graph.is_turn_restricted(h_edge, edge)? {
+            // the path to the settled edge is final: via-way restrictions are checked against it
+            // only, even if another path to the same edge could have avoided the restriction
+            let previous = previous_edges(&previous_map, h_edge);
+            if is_turn_restricted_after(graph, previous, edge)? {
                 continue;
             }
 
-            let distance = h_distance + graph.get_edge_length(edge)?;
+            let edge_cost = h_cost + cost.cost(graph, edge)?;
 
-            if distance > max_length {
+            if edge_cost > max_cost {
                 continue;
             }
 
-            let shortest_distance = *shortest_distances.get(&edge).unwrap_or(&Length::MAX);
-
             // check if we can follow the current path to reach the neighbor in a cheaper way
-            if distance < shortest_distance {
+            if edge_cost < shortest_costs.get(edge) {
                 // Relax: we have now found a better way that we are going to explore
-                shortest_distances.insert(edge, distance);
+                let distance = h_distance + graph.get_edge_length(edge)?;
+                shortest_costs.insert(edge, edge_cost);
                 previous_map.insert(edge, h_edge);
-                heap.push(Reverse(distance), edge);
+                heap.push(Reverse(edge_cost), (edge, distance));
             }
         }
     }
@@ -175,6 +232,100 @@ pub fn shortest_path_location<G: DirectedGraph>(
     Ok(ShortestPath::NotFound)
 }
 
+/// Returns the intermediate that rules out the alternative paths of the location (a path of least
+/// cost between its first and last edges), if there is any alternative at most the given ratio
+/// costlier than the location. Alternative paths don't follow any of the inner edges of the location.
+///
+/// Nearly equal alternatives may be the shortest path on the map of the decoder, where the lines
+/// lengths are slightly different. The intermediate is the inner edge that starts closest to the
+/// middle of the location, preferably at a valid node.
+pub fn alternative_path_intermediate<G: DirectedGraph>(
+    graph: &G,
+    location: &[G::EdgeId],
+    max_ratio: f64,
+    cost: RouteCost,
+    budget: &mut SearchBudget,
+) -> Result<Option<Intermediate>, EncodeError<G::Error>> {
+    let [origin, inner @ .., destination] = location else {
+        return Ok(None);
+    };
+
+    if inner.is_empty() || origin == destination {
+        return Ok(None);
+    }
+
+    let lengths = location
+        .iter()
+        .map(|&e| graph.get_edge_length(e))
+        .collect::<Result<Vec<_>, _>>()?;
+    let length: Length = lengths.iter().copied().sum();
+
+    let location_cost = path_cost(graph, location, &cost)?;
+
+    let inner_edges: FxHashSet<G::EdgeId> = inner.iter().copied().collect();
+    let max_cost = location_cost * (1.0 + max_ratio);
+
+    let origin_cost = cost.cost(graph, *origin)?;
+    let mut shortest_costs = ShortestDistances::new(graph);
+    shortest_costs.insert(*origin, origin_cost);
+    let mut previous_map: FxHashMap<G::EdgeId, G::EdgeId> = FxHashMap::default();
+    let mut heap = RadixHeapMap::from_iter([(Reverse(origin_cost), *origin)]);
+    let mut alternative = None;
+
+    while let Some((Reverse(h_cost), h_edge)) = heap.pop() {
+        if h_edge == *destination {
+            alternative = Some(h_cost);
+            break;
+        }
+
+        if h_cost > shortest_costs.get(h_edge) {
+            continue;
+        }
+
+        budget.expand_node()?;
+        let exiting_edges = graph.vertex_exiting_edges(graph.get_edge_end_vertex(h_edge)?)?;
+
+        for (edge, _) in exiting_edges {
+            let previous = previous_edges(&previous_map, h_edge);
+            if inner_edges.contains(&edge) || is_turn_restricted_after(graph, previous, edge)? {
+                continue;
+            }
+
+            let edge_cost = h_cost + cost.cost(graph, edge)?;
+            if edge_cost <= max_cost && edge_cost < shortest_costs.get(edge) {
+                shortest_costs.insert(edge, edge_cost);
+                previous_map.insert(edge, h_edge);
+                heap.push(Reverse(edge_cost), edge);
+            }
+        }
+    }
+
+    let Some(alternative) = alternative else {
+        return Ok(None);
+    };
+    debug!("Found alternative path of cost {alternative} to {location:?} of {location_cost}");
+
+    let half_length = length * 0.5;
+    let mut intermediate: Option<(bool, Length, usize)> = None;
+    let mut distance = Length::ZERO;
+
+    for (location_index, (&edge, &edge_length)) in location.iter().zip(&lengths).enumerate() {
+        if location_index > 0 && location_index < location.len() - 1 {
+            let is_invalid = !is_node_valid(graph, graph.get_edge_start_vertex(edge)?)?;
+            let deviation = Length::from_meters((distance - half_length).meters().abs());
+            let candidate = (is_invalid, deviation, location_index);
+
+            if intermediate.is_none_or(|intermediate| candidate < intermediate) {
+                intermediate = Some(candidate);
+            }
+        }
+
+        distance += edge_length;
+    }
+
+    Ok(intermediate.map(|(_, _, location_index)| Intermediate { location_index }))
+}
+
 /// Splits the location, if this doesn't follow the shortest path, at intermediate edges.
 #[derive(Debug)]
 struct Intermediator<'a, G: DirectedGraph> {
@@ -191,7 +342,7 @@ impl<'a, G: DirectedGraph> Intermediator<'a, G> {
         location: &'a [G::EdgeId],
         max_lrp_distance: Length,
     ) -> Result<Self, EncodeError<G::Error>> {
-        let last_edge = location.first().copied().ok_or(LocationError::Empty)?;
+        let last_edge = location.first().copied().ok_or(LocationError::EmptyPath)?;
         let last_edge_index = 0;
 
         Ok(Self {
@@ -350,7 +501,10 @@ mod tests {
     use test_log::test;
 
     use super::*;
-    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+    use crate::graph::tests::{
+        EdgeId, NETWORK_GRAPH, NetworkGraph, PARALLEL_EDGE, PARALLEL_NETWORK_GRAPH,
+        RESTRICTED_NETWORK_GRAPH,
+    };
 
     #[test]
     fn encoder_shortest_path_location_001() {
@@ -358,7 +512,14 @@ mod tests {
 
         let location = [EdgeId(-9044470), EdgeId(-9044471)];
 
-        let route = shortest_path_location(graph, &location, Length::MAX).unwrap();
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::MAX,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
 
         assert_eq!(route, ShortestPath::Location);
     }
@@ -369,7 +530,14 @@ mod tests {
 
         let location = [EdgeId(-9044470), EdgeId(-9044471), EdgeId(-9044472)];
 
-        let route = shortest_path_location(graph, &location, Length::MAX).unwrap();
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::MAX,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
 
         assert_eq!(route, ShortestPath::Location);
     }
@@ -380,7 +548,14 @@ mod tests {
 
         let location = [EdgeId(-9044472), EdgeId(4993083)];
 
-        let route = shortest_path_location(graph, &location, Length::MAX).unwrap();
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::MAX,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
 
         assert_eq!(route, ShortestPath::Location);
     }
@@ -397,7 +572,14 @@ mod tests {
             EdgeId(7516885),
         ];
 
-        let route = shortest_path_location(graph, &location, Length::MAX).unwrap();
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::MAX,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
 
         assert_eq!(
             route,
@@ -418,7 +600,14 @@ mod tests {
             EdgeId(7516885),
         ];
 
-        let route = shortest_path_location(graph, &location, Length::MAX).unwrap();
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::MAX,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
 
         assert_eq!(
             route,
@@ -432,7 +621,14 @@ mod tests {
 
         let location = [EdgeId(-7519159), EdgeId(5104156), EdgeId(-7519157)];
 
-        let route = shortest_path_location(graph, &location, Length::MAX).unwrap();
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::MAX,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
 
         assert_eq!(
             route,
@@ -456,7 +652,14 @@ mod tests {
             EdgeId(7430361),
         ];
 
-        let route = shortest_path_location(graph, &location, Length::MAX).unwrap();
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::MAX,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
 
         assert_eq!(
             route,
@@ -476,7 +679,14 @@ mod tests {
             EdgeId(7516884),
         ];
 
-        let route = shortest_path_location(graph, &location, Length::MAX).unwrap();
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::MAX,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
 
         assert_eq!(
             route,
@@ -494,7 +704,14 @@ mod tests {
             EdgeId(7516885),
         ];
 
-        let route = shortest_path_location(graph, &location, Length::MAX).unwrap();
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::MAX,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
 
         assert_eq!(
             route,
@@ -515,7 +732,14 @@ mod tests {
             EdgeId(7292030),
         ];
 
-        let route = shortest_path_location(graph, &location, Length::MAX).unwrap();
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::MAX,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
 
         assert_eq!(
             route,
@@ -536,7 +760,14 @@ mod tests {
             EdgeId(-7516884),
         ];
 
-        let route = shortest_path_location(graph, &location, Length::MAX).unwrap();
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::MAX,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
 
         assert_eq!(
             route,
@@ -571,7 +802,14 @@ mod tests {
             EdgeId(-869554),
         ];
 
-        let route = shortest_path_location(graph, &location, Length::MAX).unwrap();
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::MAX,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
 
         assert_eq!(
             route,
@@ -585,7 +823,14 @@ mod tests {
 
         let location = [EdgeId(-9044470), EdgeId(-9044471), EdgeId(-9044472)];
 
-        let route = shortest_path_location(graph, &location, Length::from_meters(19.0)).unwrap();
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::from_meters(19.0),
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
 
         assert_eq!(
             route,
@@ -599,7 +844,14 @@ mod tests {
 
         let location = [EdgeId(-9044470), EdgeId(-9044471), EdgeId(-9044472)];
 
-        let route = shortest_path_location(graph, &location, Length::from_meters(30.0)).unwrap();
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::from_meters(30.0),
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
 
         assert_eq!(
             route,
@@ -613,7 +865,14 @@ mod tests {
 
         let location = [EdgeId(-9044470), EdgeId(-9044471), EdgeId(-9044472)];
 
-        let route = shortest_path_location(graph, &location, Length::from_meters(31.0)).unwrap();
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::from_meters(31.0),
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
 
         assert_eq!(
             route,
@@ -627,7 +886,14 @@ mod tests {
 
         let location = [EdgeId(8717174), EdgeId(8717175), EdgeId(109783)];
 
-        let route = shortest_path_location(graph, &location, Length::MAX).unwrap();
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::MAX,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
 
         assert_eq!(route, ShortestPath::Location);
     }
@@ -643,8 +909,128 @@ mod tests {
             EdgeId(5359425),
         ];
 
-        let route = shortest_path_location(graph, &location, Length::MAX).unwrap();
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::MAX,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
+
+        assert_eq!(route, ShortestPath::Location);
+    }
+
+    #[test]
+    fn encoder_shortest_path_location_018() {
+        let location = [EdgeId(8717174), PARALLEL_EDGE, EdgeId(109783)];
+
+        let graph: &NetworkGraph = &PARALLEL_NETWORK_GRAPH;
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::MAX,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
+        assert!(matches!(route, ShortestPath::Intermediate(_)));
+
+        // the shortest path through the edge 8717175 is restricted
+        let graph: &NetworkGraph = &RESTRICTED_NETWORK_GRAPH;
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::MAX,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
+        assert_eq!(route, ShortestPath::Location);
+    }
+
+    #[test]
+    fn encoder_alternative_path_intermediate_001() {
+        let graph: &NetworkGraph = &PARALLEL_NETWORK_GRAPH;
+
+        let location = [EdgeId(8717174), EdgeId(8717175), EdgeId(109783)];
 
+        let intermediate = alternative_path_intermediate(
+            graph,
+            &location,
+            0.5,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
+        assert_eq!(intermediate, Some(Intermediate { location_index: 1 }));
+
+        // the alternative through the parallel edge is too long
+        let intermediate = alternative_path_intermediate(
+            graph,
+            &location,
+            0.01,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
+        assert_eq!(intermediate, None);
+
+        // no inner edges
+        let location = [EdgeId(8717174), EdgeId(8717175)];
+        let intermediate = alternative_path_intermediate(
+            graph,
+            &location,
+            0.5,
+            RouteCost::Length,
+            &mut SearchBudget::default(),
+        )
+        .unwrap();
+        assert_eq!(intermediate, None);
+    }
+
+    #[test]
+    fn encoder_shortest_path_location_search_budget() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let location = [EdgeId(8717174), EdgeId(8717175), EdgeId(109783)];
+
+        let mut budget = SearchBudget::new(Some(1000), None);
+        let route = shortest_path_location(
+            graph,
+            &location,
+            Length::MAX,
+            RouteCost::Length,
+            &mut budget,
+        )
+        .unwrap();
         assert_eq!(route, ShortestPath::Location);
+
+        let mut budget = SearchBudget::new(Some(1), None);
+        let error = shortest_path_location(
+            graph,
+            &location,
+            Length::MAX,
+            RouteCost::Length,
+            &mut budget,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error,
+            EncodeError::SearchLimitExceeded {
+                expanded_nodes: 2,
+                iterations: 0
+            }
+        );
+
+        let mut budget = SearchBudget::new(None, Some(1));
+        assert!(budget.next_iteration::<()>().is_ok());
+        assert_eq!(
+            budget.next_iteration::<()>(),
+            Err(EncodeError::SearchLimitExceeded {
+                expanded_nodes: 0,
+                iterations: 2
+            })
+        );
     }
 }
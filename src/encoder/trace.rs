@@ -0,0 +1,178 @@
+use tracing::debug;
+
+use crate::decoder::decode_line;
+use crate::{
+    Coordinate, DecodeError, DecoderConfig, DirectedGraph, EncodeError, EncoderConfig, Fow, Frc,
+    Length, Line, LineAttributes, LineLocation, Location, LocationError, Offsets, PathAttributes,
+    Point, encode_base64_openlr,
+};
+
+/// Consecutive points of a trace closer than this distance are merged: their bearing would be
+/// dominated by the noise of the positioning.
+const MIN_TRACE_POINTS_DISTANCE: Length = Length::from_meters(1.0);
+
+/// Encodes the line location that best matches the trace (an ordered list of coordinates, for
+/// example GPS fixes) into an OpenLR Line Location Reference in Base64 (see [`match_trace`]).
+pub fn encode_from_trace<G: DirectedGraph>(
+    config: &EncoderConfig,
+    graph: &G,
+    trace: &[Coordinate],
+) -> Result<String, EncodeError<G::Error>> {
+    let location = match_trace(config, graph, trace)?;
+    encode_base64_openlr(config, graph, Location::Line(location))
+}
+
+/// Matches the trace (an ordered list of coordinates, for example GPS fixes) to the connected path
+/// of the graph that best follows it, from the projection of the first point to the projection of
+/// the last one.
+///
+/// The trace is decoded as if every point was an LRP, with the bearing of the trace at the point
+/// and the FRC and FOW of the nearest line: the candidate lines of the points are rated and
+/// connected by shortest paths like the LRPs of a location reference. Points closer than 1m to the
+/// previous one are skipped, the indices of the points in [`EncodeError::TraceNotMatched`] refer
+/// to the remaining ones.
+pub fn match_trace<G: DirectedGraph>(
+    config: &EncoderConfig,
+    graph: &G,
+    trace: &[Coordinate],
+) -> Result<LineLocation<G::EdgeId>, EncodeError<G::Error>> {
+    debug!("Matching trace of {} points", trace.len());
+
+    let mut coordinates: Vec<Coordinate> = Vec::with_capacity(trace.len());
+    for &coordinate in trace {
+        if coordinates
+            .last()
+            .is_none_or(|last| last.distance(&coordinate) >= MIN_TRACE_POINTS_DISTANCE)
+        {
+            coordinates.push(coordinate);
+        }
+    }
+
+    if coordinates.len() < 2 {
+        return Err(LocationError::EmptyPath.into());
+    }
+
+    let last_index = coordinates.len() - 1;
+    let points = coordinates
+        .iter()
+        .enumerate()
+        .map(|(index, &coordinate)| {
+            let (frc, fow) = match graph
+                .nearest_edges_within_distance(coordinate, config.max_trace_distance)?
+                .next()
+            {
+                Some((edge, _)) => (graph.get_edge_frc(edge)?, graph.get_edge_fow(edge)?),
+                None => (Frc::Frc7, Fow::Undefined),
+            };
+
+            // the bearing of the last point is measured backwards, like the one of the last LRP
+            let (bearing, path) = if index < last_index {
+                let next = &coordinates[index + 1];
+                let path = PathAttributes {
+                    lfrcnp: Frc::Frc7,
+                    dnp: coordinate.distance(next),
+                };
+                (coordinate.bearing_to(next), Some(path))
+            } else {
+                (coordinate.bearing_to(&coordinates[index - 1]), None)
+            };
+
+            Ok(Point {
+                coordinate,
+                line: LineAttributes { frc, fow, bearing },
+                path,
+            })
+        })
+        .collect::<Result<_, G::Error>>()?;
+
+    // unlike the LRPs, the trace points are not placed on the nodes: their projections into the
+    // lines are as likely as the lines from the nearest nodes
+    let decoder_config = DecoderConfig {
+        max_node_distance: config.max_trace_distance,
+        projected_line_factor: 1.0,
+        max_visited_nodes: config.max_expanded_nodes,
+        ..DecoderConfig::default()
+    };
+
+    let line = Line {
+        points,
+        offsets: Offsets::default(),
+    };
+
+    decode_line(&decoder_config, graph, line, None, &mut vec![]).map_err(|error| match error {
+        DecodeError::GraphError(error) => EncodeError::GraphError(error),
+        DecodeError::InvalidLocation(error) => EncodeError::InvalidLocation(error),
+        error @ (DecodeError::LocationTypeNotSupported(_)
+        | DecodeError::DeserializeError(_)
+        | DecodeError::CandidatesNotFound { .. }
+        | DecodeError::RouteNotFound { .. }
+        | DecodeError::CorruptReference { .. }
+        | DecodeError::InconsistentGraph(_)
+        | DecodeError::SearchLimitExceeded { .. }) => EncodeError::TraceNotMatched(error),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::decode_base64_openlr;
+    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+
+    #[test]
+    fn encoder_match_trace_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let config = EncoderConfig::default();
+
+        for code in ["CwmShiVYczPJBgCs/y0zAQ==", "CwmTaSVYpTPZCP4a/5UjYQUH"] {
+            let Ok(Location::Line(expected)) =
+                decode_base64_openlr(&DecoderConfig::default(), graph, code)
+            else {
+                unreachable!()
+            };
+
+            let trace = Location::Line(expected.clone()).coordinates(graph).unwrap();
+            let location = match_trace(&config, graph, &trace).unwrap();
+
+            assert_eq!(location.path, expected.path);
+            assert!((location.pos_offset - expected.pos_offset).meters().abs() < 1.0);
+            assert!((location.neg_offset - expected.neg_offset).meters().abs() < 1.0);
+
+            let code = encode_from_trace(&config, graph, &trace).unwrap();
+            let Ok(Location::Line(decoded)) =
+                decode_base64_openlr(&DecoderConfig::default(), graph, code)
+            else {
+                unreachable!()
+            };
+            assert_eq!(decoded.path, expected.path);
+        }
+    }
+
+    #[test]
+    fn encoder_match_trace_002() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let config = EncoderConfig::default();
+
+        let point = Coordinate {
+            lon: 13.454214,
+            lat: 52.5157088,
+        };
+        assert_eq!(
+            match_trace(&config, graph, &[point, point]),
+            Err(EncodeError::InvalidLocation(LocationError::EmptyPath))
+        );
+
+        let far = Coordinate {
+            lon: 13.0,
+            lat: 52.0,
+        };
+        let result: Result<LineLocation<EdgeId>, _> = match_trace(&config, graph, &[far, point]);
+        assert!(matches!(
+            result,
+            Err(EncodeError::TraceNotMatched(
+                DecodeError::CandidatesNotFound { index: 0, .. }
+            ))
+        ));
+    }
+}
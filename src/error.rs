@@ -1,8 +1,9 @@
 use std::io::ErrorKind;
+use std::time::Duration;
 
 use thiserror::Error;
 
-use crate::{Bearing, Coordinate, GridSize, Length, LocationType, Offset, Point, Rectangle};
+use crate::{Bearing, Coordinate, Frc, GridSize, Length, LocationType, Offset, Point, Rectangle};
 
 #[derive(Error, Debug, PartialEq, Clone, Copy)]
 pub enum DeserializeError {
@@ -10,6 +11,10 @@ pub enum DeserializeError {
     InvalidBase64,
     #[error("OpenLR buffer I/O error: {0:?}")]
     IO(ErrorKind),
+    #[error("OpenLR data ends before the location reference is complete")]
+    UnexpectedEnd,
+    #[error("OpenLR container framing is not valid")]
+    InvalidContainer,
     #[error("OpenLR version {0} not supported")]
     VersionNotSupported(u8),
     #[error("OpenLR header is not valid: {0:08b}")]
@@ -24,6 +29,16 @@ pub enum DeserializeError {
     InvalidSideOfRoad(u8),
     #[error("OpenLR Coordinate is not valid: {0:?}")]
     InvalidCoordinate(#[from] CoordinateError),
+    #[error("OpenLR extended precision coordinates are not valid")]
+    InvalidPrecision,
+    #[error("OpenLR data has {0} trailing bytes")]
+    TrailingBytes(usize),
+    #[error("OpenLR reserved bits are set: {0:08b}")]
+    ReservedBitsSet(u8),
+    #[error("OpenLR XML is not valid")]
+    InvalidXml,
+    #[error("OpenLR XML element is missing: {0}")]
+    MissingXmlElement(&'static str),
 }
 
 #[derive(Error, Debug, PartialEq, Clone, Copy)]
@@ -46,9 +61,13 @@ pub enum SerializeError {
     InvalidCoordinate(#[from] CoordinateError),
     #[error("OpenLR Length is not valid: {0:?}")]
     InvalidLength(Length),
+    #[error("OpenLR extended precision cannot represent the location coordinates")]
+    InvalidPrecision,
+    #[error("OpenLR relative Coordinate is too far from the previous one: {0}")]
+    RelativeCoordinateOutOfRange(Coordinate),
 }
 
-#[derive(Error, Debug, PartialEq, Clone, Copy)]
+#[derive(Error, Debug, PartialEq, Clone)]
 pub enum DecodeError<GraphError> {
     #[error(transparent)]
     GraphError(#[from] GraphError),
@@ -57,43 +76,250 @@ pub enum DecodeError<GraphError> {
     #[error("Decoding {0:?} is not supported")]
     LocationTypeNotSupported(LocationType),
     #[error("Cannot decode location: {0}")]
-    DeserializeError(DeserializeError),
-    #[error("Cannot find candidates for {0:?}")]
-    CandidatesNotFound(Point),
-    #[error("Cannot find route between LRPs {0:?}")]
-    RouteNotFound((Point, Point)),
+    DeserializeError(#[source] DeserializeError),
+    #[error("Cannot find candidates for LRP {index} at {}", lrp.coordinate)]
+    CandidatesNotFound {
+        /// Index of the LRP in the decoded line.
+        index: usize,
+        lrp: Point,
+    },
+    #[error("Cannot find route between LRPs {index} and {}: {stats:?}", index + 1)]
+    RouteNotFound {
+        /// Index of the first LRP of the pair in the decoded line.
+        index: usize,
+        lrps: (Point, Point),
+        stats: Box<RouteSearchStats>,
+    },
+    #[error(
+        "Location reference is corrupt: LRP {index} is {distance} away from the next LRP but its distance to next point is {dnp}"
+    )]
+    CorruptReference {
+        index: usize,
+        dnp: Length,
+        distance: Length,
+    },
+    #[error("Inconsistent graph: {0}")]
+    InconsistentGraph(#[source] GraphConsistencyError),
+    #[error("Route search aborted after visiting {visited_nodes} nodes in {elapsed:?}")]
+    SearchLimitExceeded {
+        visited_nodes: usize,
+        elapsed: Duration,
+    },
+}
+
+/// Statistics of the route searches between two consecutive LRPs that failed to find a route.
+/// Tell map gaps (the searches settle few edges) apart from a too strict configuration (the
+/// searches stop at the max length or at the lowest FRC).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RouteSearchStats {
+    /// Number of candidate line pairs tried.
+    pub pairs_tried: usize,
+    /// Number of edges settled by all the shortest path searches.
+    pub settled_edges: usize,
+    /// Longest distance from the origin settled by any of the shortest path searches.
+    pub max_distance_reached: Length,
+    /// Max route length allowed by the last shortest path search.
+    pub max_length: Length,
+    /// Lowest FRC allowed by the last shortest path search.
+    pub lowest_frc: Frc,
+}
+
+/// Assumption of the decoder about the behavior of the graph that doesn't hold.
+/// Only checked in release builds if enabled by the configuration.
+#[derive(Error, Debug, PartialEq, Clone, Copy)]
+pub enum GraphConsistencyError {
+    #[error("Nearest vertex at {distance} exceeds the max distance {max_distance}")]
+    VertexBeyondMaxDistance {
+        distance: Length,
+        max_distance: Length,
+    },
+    #[error("Nearest vertices are not sorted by distance")]
+    VerticesNotSorted,
+    #[error("Nearest edge at {distance} exceeds the max distance {max_distance}")]
+    EdgeBeyondMaxDistance {
+        distance: Length,
+        max_distance: Length,
+    },
+    #[error("Nearest edges are not sorted by distance")]
+    EdgesNotSorted,
+    #[error("Resolved route path is not connected at edge index {index}")]
+    DisconnectedRoute { index: usize },
+}
+
+/// Error of the network graph (see [`crate::NetworkGraph`]).
+#[cfg(any(test, feature = "network"))]
+#[derive(Error, Debug, PartialEq, Clone, Copy)]
+pub enum NetworkGraphError {
+    #[error("Network graph vertex not found: {0:?}")]
+    VertexNotFound(crate::graph::network::NetworkVertexId),
+    #[error("Network graph edge not found: {0:?}")]
+    EdgeNotFound(crate::graph::network::NetworkEdgeId),
+}
+
+/// Error reading the network lines from GeoJSON (see [`crate::NetworkLine::from_geojson`]).
+#[cfg(any(test, feature = "network"))]
+#[derive(Error, Debug, PartialEq, Clone, Copy)]
+pub enum NetworkGeojsonError {
+    #[error("Network GeoJSON is not a valid FeatureCollection")]
+    InvalidGeojson,
+    #[error("Network GeoJSON feature {feature} has an invalid or missing {property}")]
+    InvalidProperty {
+        feature: usize,
+        property: &'static str,
+    },
+}
+
+/// Error of the (de)serialization of the network graph snapshots (see
+/// [`crate::NetworkGraph::to_snapshot`]).
+#[cfg(feature = "snapshot")]
+#[derive(Error, Debug, PartialEq, Clone, Copy)]
+pub enum NetworkSnapshotError {
+    #[error("Network graph snapshot has an invalid header or an unsupported version")]
+    InvalidHeader,
+    #[error("Network graph snapshot data is invalid")]
+    InvalidData,
+    #[error("Network graph cannot be serialized into a snapshot")]
+    Serialize,
+}
+
+/// Error of the graph built from OSM data (see [`crate::OsmGraph`]).
+#[cfg(feature = "osm")]
+#[derive(Error, Debug, PartialEq, Clone, Copy)]
+pub enum OsmGraphError {
+    #[error("OSM graph vertex not found: {0:?}")]
+    VertexNotFound(crate::OsmVertexId),
+    #[error("OSM graph edge not found: {0:?}")]
+    EdgeNotFound(crate::OsmEdgeId),
+}
+
+/// Error reading OSM data to build the graph (see [`crate::OsmGraph::from_pbf`]).
+#[cfg(feature = "osm")]
+#[derive(Error, Debug, PartialEq, Clone, Copy)]
+pub enum OsmError {
+    #[error("OSM PBF I/O error: {0:?}")]
+    IO(ErrorKind),
+    #[error("OSM PBF blob is not valid")]
+    InvalidBlob,
+    #[error("OSM PBF blob compression is not supported")]
+    UnsupportedCompression,
+    #[error("OSM PBF file requires a feature that is not supported")]
+    UnsupportedFeature,
 }
 
+#[cfg(feature = "osm")]
+impl From<std::io::Error> for OsmError {
+    fn from(error: std::io::Error) -> Self {
+        Self::IO(error.kind())
+    }
+}
+
+#[cfg(feature = "osm")]
+impl From<prost::DecodeError> for OsmError {
+    fn from(_: prost::DecodeError) -> Self {
+        Self::InvalidBlob
+    }
+}
+
+/// Error loading a conformance corpus (see [`crate::conformance::load_directory`]).
+#[cfg(feature = "conformance")]
+#[derive(Error, Debug, PartialEq, Clone, Copy)]
+pub enum ConformanceError {
+    #[error("Conformance corpus I/O error: {0:?}")]
+    IO(ErrorKind),
+    #[error("Conformance CSV line {0} is not valid")]
+    InvalidCsv(usize),
+    #[error("Conformance expected location reference of line {0} is not valid: {1}")]
+    InvalidExpected(usize, #[source] DeserializeError),
+}
+
+#[cfg(feature = "conformance")]
+impl From<std::io::Error> for ConformanceError {
+    fn from(error: std::io::Error) -> Self {
+        Self::IO(error.kind())
+    }
+}
+
+/// Error of the graph used by the async decoder and encoder (see [`crate::AsyncDirectedGraph`]).
 #[derive(Error, Debug, PartialEq, Clone, Copy)]
+pub enum AsyncGraphError<GraphError> {
+    #[error(transparent)]
+    Graph(GraphError),
+    /// Graph data requested before being fetched: the decoding (or encoding) is replayed once
+    /// the data is fetched, therefore never returned by the async functions.
+    #[error("Graph data has not been fetched yet")]
+    Missing,
+}
+
+#[derive(Error, Debug, PartialEq, Clone)]
 pub enum EncodeError<GraphError> {
     #[error(transparent)]
     GraphError(#[from] GraphError),
     #[error("Cannot encode location: {0}")]
     InvalidLocation(#[from] LocationError<GraphError>),
     #[error("Cannot encode location: {0}")]
-    SerializeError(SerializeError),
+    SerializeError(#[source] SerializeError),
     #[error("Cannot find intermediate at location index {0}")]
     IntermediateError(usize),
-    #[error("Cannot find route between LRPs")]
-    RouteNotFound,
-    #[error("Cannot construct a valid LRP for location")]
-    InvalidLrp,
+    #[error("Cannot find route from location index {start_index} to {end_index}")]
+    RouteNotFound {
+        /// Location index of the first edge of the route.
+        start_index: usize,
+        /// Location index of the last edge of the route.
+        end_index: usize,
+    },
+    #[error("Cannot construct a valid LRP for location: LRPs diverge at location index {index}")]
+    InvalidLrp { index: usize },
     #[error("Maximum distance between consecutive LRPs exceeded")]
     MaxDistanceExceeded,
-    #[error("Cannot construct valid LRP offsets for location")]
-    InvalidLrpOffsets,
+    #[error("Cannot construct valid LRP offsets {pos_offset} and {neg_offset} for {lrps} LRPs")]
+    InvalidLrpOffsets {
+        pos_offset: Length,
+        neg_offset: Length,
+        /// Number of LRPs left after trimming the ones covered by the offsets.
+        lrps: usize,
+    },
+    #[error("POI {poi} is too far from the first LRP {lrp} to be encoded relative to it")]
+    PoiOutOfRange { poi: Coordinate, lrp: Coordinate },
+    #[error(
+        "Route search aborted after expanding {expanded_nodes} nodes in {iterations} iterations"
+    )]
+    SearchLimitExceeded {
+        expanded_nodes: usize,
+        iterations: usize,
+    },
+    #[error("Cannot match trace to the graph: {0}")]
+    TraceNotMatched(#[source] DecodeError<GraphError>),
+}
+
+/// Error re-encoding a location reference on the local map (see [`crate::reencode`]).
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum ReencodeError<GraphError> {
+    #[error("Cannot re-encode location reference: {0}")]
+    DecodeError(#[source] DecodeError<GraphError>),
+    #[error("Cannot re-encode location reference: {0}")]
+    EncodeError(#[source] EncodeError<GraphError>),
+    #[error("Cannot re-encode location reference: {0}")]
+    DeserializeError(#[source] DeserializeError),
 }
 
 #[derive(Error, Debug, PartialEq, Clone, Copy)]
 pub enum LocationError<GraphError> {
     #[error(transparent)]
     GraphError(#[from] GraphError),
-    #[error("Invalid offsets {0:?}")]
-    InvalidOffsets((Length, Length)),
-    #[error("Location is empty")]
-    Empty,
-    #[error("Location is not connected")]
-    NotConnected,
+    #[error("Location path is empty")]
+    EmptyPath,
+    #[error("Location path is not connected at edge index {index}")]
+    DisconnectedPath { index: usize },
+    #[error("Location path repeats the previous edge at index {index}")]
+    DuplicateEdge { index: usize },
+    #[error("Location offsets {pos_offset} + {neg_offset} exceed the path length {length}")]
+    OffsetsExceedLength {
+        pos_offset: Length,
+        neg_offset: Length,
+        length: Length,
+    },
+    #[error("Location edge at index {index} is one-way")]
+    OneWayEdge { index: usize },
 }
 
 #[derive(Error, Debug, PartialEq, Clone, Copy)]
@@ -1 +1,12 @@
+#[cfg(feature = "arrow")]
+pub mod arrow;
 pub mod binary;
+pub mod container;
+#[cfg(feature = "geo")]
+pub mod geo;
+#[cfg(feature = "geojson")]
+pub mod geojson;
+#[cfg(feature = "postgis")]
+pub mod wkb;
+#[cfg(feature = "xml")]
+pub mod xml;
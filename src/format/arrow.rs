@@ -0,0 +1,183 @@
+//! Arrow record batches (and Parquet files) of batch decode results.
+
+use std::io::Write;
+use std::sync::{Arc, LazyLock};
+
+use arrow_array::builder::{
+    BinaryBuilder, Float64Builder, Int64Builder, ListBuilder, StringBuilder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use parquet::errors::ParquetError;
+
+use crate::format::wkb::location_wkb;
+use crate::{
+    DecodeReport, DecoderConfig, DirectedGraph, Location, decode_base64_openlr_with_report,
+};
+
+static DECODE_SCHEMA: LazyLock<SchemaRef> = LazyLock::new(|| {
+    Arc::new(Schema::new(vec![
+        Field::new("code", DataType::Utf8, false),
+        Field::new_list(
+            "edge_ids",
+            Field::new_list_field(DataType::Int64, true),
+            true,
+        ),
+        Field::new("geometry", DataType::Binary, true),
+        Field::new("confidence", DataType::Float64, true),
+        Field::new("error", DataType::Utf8, true),
+    ]))
+});
+
+/// Gets the schema of the record batches of decode results:
+/// - `code`: the Base64 OpenLR code.
+/// - `edge_ids`: the edges of the decoded location (null if decoding failed).
+/// - `geometry`: the WKB geometry of the decoded location (null if decoding failed), see
+///   [`Location::coordinates`].
+/// - `confidence`: the confidence of the decoded location (see [`DecodeReport::confidence`]).
+/// - `error`: the decoding error message (null if decoding succeeded).
+pub fn decode_batch_schema() -> SchemaRef {
+    Arc::clone(&DECODE_SCHEMA)
+}
+
+/// Decodes all the Base64 OpenLR codes into a record batch with one row for each code, failures
+/// are reported in the `error` column of their row.
+pub fn decode_record_batch<G, I>(
+    config: &DecoderConfig,
+    graph: &G,
+    codes: I,
+) -> Result<RecordBatch, ArrowError>
+where
+    G: DirectedGraph,
+    G::EdgeId: Into<i64>,
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut code_column = StringBuilder::new();
+    let mut edges_column = ListBuilder::new(Int64Builder::new());
+    let mut geometry_column = BinaryBuilder::new();
+    let mut confidence_column = Float64Builder::new();
+    let mut error_column = StringBuilder::new();
+
+    for code in codes {
+        let code = code.as_ref();
+        code_column.append_value(code);
+
+        let mut report = DecodeReport::default();
+        let decoded = decode_base64_openlr_with_report(config, graph, code, &mut report)
+            .map_err(|e| e.to_string())
+            .and_then(|location| {
+                let coordinates = location.coordinates(graph).map_err(|e| e.to_string())?;
+                Ok((location, coordinates))
+            });
+
+        confidence_column.append_option(report.confidence);
+
+        match decoded {
+            Ok((location, coordinates)) => {
+                geometry_column.append_value(location_wkb(&location, &coordinates));
+                edges_column.append_value(location_edges(location).into_iter().map(Some));
+                error_column.append_null();
+            }
+            Err(error) => {
+                geometry_column.append_null();
+                edges_column.append_null();
+                error_column.append_value(error);
+            }
+        }
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(code_column.finish()),
+        Arc::new(edges_column.finish()),
+        Arc::new(geometry_column.finish()),
+        Arc::new(confidence_column.finish()),
+        Arc::new(error_column.finish()),
+    ];
+
+    RecordBatch::try_new(decode_batch_schema(), columns)
+}
+
+/// Writes the record batches into a Parquet file.
+pub fn write_parquet<'a, W, I>(writer: W, batches: I) -> Result<(), ParquetError>
+where
+    W: Write + Send,
+    I: IntoIterator<Item = &'a RecordBatch>,
+{
+    let mut writer = ArrowWriter::try_new(writer, decode_batch_schema(), None)?;
+
+    for batch in batches {
+        writer.write(batch)?;
+    }
+
+    writer.close()?;
+    Ok(())
+}
+
+fn location_edges<EdgeId: Into<i64>>(location: Location<EdgeId>) -> Vec<i64> {
+    let path = match location {
+        Location::Line(line) => line.path,
+        Location::GeoCoordinate(_) => vec![],
+        Location::PointAlongLine(point) => point.path,
+        Location::Poi(poi) => poi.point.path,
+        Location::ClosedLine(line) => line.path,
+        Location::Area(area) => area.edges,
+    };
+
+    path.into_iter().map(Into::into).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::Array;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Float64Type, Int64Type};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{NETWORK_GRAPH, NetworkGraph};
+
+    #[test]
+    fn arrow_decode_record_batch_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let config = DecoderConfig::default();
+
+        let batch =
+            decode_record_batch(&config, graph, ["CwmShiVYczPJBgCs/y0zAQ==", "invalid"]).unwrap();
+
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.schema(), decode_batch_schema());
+
+        let edges = batch.column(1).as_list::<i32>();
+        assert_eq!(
+            edges.value(0).as_primitive::<Int64Type>().values().to_vec(),
+            vec![8717174, 8717175, 109783]
+        );
+        assert!(edges.is_null(1));
+
+        let geometry = batch.column(2).as_binary::<i32>();
+        assert_eq!(geometry.value(0).len(), 1 + 4 + 4 + 4 * 16);
+        assert!(geometry.is_null(1));
+
+        let confidence = batch.column(3).as_primitive::<Float64Type>();
+        assert!(confidence.value(0) > 0.0 && confidence.value(0) <= 1.0);
+        assert!(confidence.is_null(1));
+
+        let error = batch.column(4).as_string::<i32>();
+        assert!(error.is_null(0));
+        assert!(!error.value(1).is_empty());
+
+        let mut parquet = vec![];
+        write_parquet(&mut parquet, [&batch]).unwrap();
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(bytes::Bytes::from(parquet))
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let batches: Vec<_> = reader.map(Result::unwrap).collect();
+        assert_eq!(batches, vec![batch]);
+    }
+}
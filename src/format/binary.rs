@@ -1,6 +1,18 @@
 mod encoding;
+#[cfg(feature = "extended-precision")]
+mod extended;
 mod reader;
+mod stream;
 mod writer;
 
-pub use reader::{deserialize_base64_openlr, deserialize_binary_openlr};
+#[cfg(feature = "extended-precision")]
+pub use extended::{
+    EXTENDED_VERSION, serialize_base64_openlr_extended, serialize_binary_openlr_extended,
+};
+pub use reader::{
+    DeserializeOptions, deserialize_base64_openlr, deserialize_base64_openlr_compatible,
+    deserialize_base64_openlr_with_options, deserialize_binary_openlr,
+    deserialize_binary_openlr_compatible, deserialize_binary_openlr_with_options,
+};
+pub use stream::{deserialize_openlr_from_reader, serialize_openlr_to_writer};
 pub use writer::{serialize_base64_openlr, serialize_binary_openlr};
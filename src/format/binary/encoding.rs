@@ -1,7 +1,7 @@
-use crate::model::Offsets;
+use crate::model::{EARTH_RADIUS_METERS, Offsets};
 use crate::{
-    Bearing, Coordinate, DeserializeError, Fow, Frc, GridSize, Length, LineAttributes, Offset,
-    Orientation, SerializeError, SideOfRoad,
+    Bearing, BearingRounding, Coordinate, DeserializeError, Fow, Frc, GridSize, Length,
+    LineAttributes, Offset, Orientation, SerializeError, SideOfRoad,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -146,6 +146,33 @@ impl Coordinate {
         previous_degrees + degrees / Self::DECA_MICRO_DEG_FACTOR
     }
 
+    /// Returns true if the coordinate can be represented relative to the previous coordinate
+    /// (in a 16-bit resolution), that is, if it is within ~0.33 degrees from it.
+    pub(crate) fn is_relative_in_range(&self, previous: &Self) -> bool {
+        let in_range = |degrees: f64, previous_degrees: f64| {
+            let degrees = (Self::DECA_MICRO_DEG_FACTOR * (degrees - previous_degrees)).round();
+            (i16::MIN as f64..=i16::MAX as f64).contains(&degrees)
+        };
+
+        in_range(self.lon, previous.lon) && in_range(self.lat, previous.lat)
+    }
+
+    /// Returns a distance (with some margin) within which any coordinate can be represented
+    /// relative to this one (see [`Coordinate::is_relative_in_range`]). The range in longitude
+    /// shrinks towards the poles, from ~36 km at the equator to ~12 km at 70° of latitude.
+    pub(crate) fn max_relative_distance(&self) -> Length {
+        const MARGIN: f64 = 0.9;
+        let max_degrees = i16::MAX as f64 / Self::DECA_MICRO_DEG_FACTOR;
+
+        let lon_distance = self.distance(&Self {
+            lon: self.lon + max_degrees,
+            lat: self.lat,
+        });
+
+        let lat_distance = Length::from_meters(max_degrees.to_radians() * EARTH_RADIUS_METERS);
+        lon_distance.min(lat_distance) * MARGIN
+    }
+
     /// Returns the big-endian relative degrees representation in a 16-bit resolution.
     pub(crate) fn degrees_into_be_bytes_relative(degrees: f64, previous_degrees: f64) -> [u8; 2] {
         let degrees = (Self::DECA_MICRO_DEG_FACTOR * (degrees - previous_degrees)).round() as i16;
@@ -200,6 +227,20 @@ impl Bearing {
         Self::from_degrees(degrees)
     }
 
+    /// Returns the bearing snapped to the middle of the sector chosen by the rounding, so that it
+    /// is serialized into that sector. The bearing is returned as is if rounded down, which is what
+    /// the serialization does.
+    pub(crate) fn round_to_sector(self, rounding: BearingRounding) -> Self {
+        let sector = self.degrees() as f64 / Self::BEAR_SECTOR;
+        let sector = match rounding {
+            BearingRounding::Floor => return self,
+            BearingRounding::Round => sector.round(),
+            BearingRounding::HalfEven => sector.round_ties_even(),
+        };
+
+        Self::from_byte(sector as u8 % 32)
+    }
+
     pub(crate) fn try_into_byte(self) -> Result<u8, SerializeError> {
         let degrees = self.degrees();
         if !(0..360).contains(&degrees) {
@@ -349,6 +390,70 @@ mod tests {
         }
     }
 
+    #[test]
+    fn openlr_binary_bearing_rounding() {
+        let sector = |degrees, rounding| {
+            Bearing::from_degrees(degrees)
+                .round_to_sector(rounding)
+                .try_into_byte()
+                .unwrap()
+        };
+
+        for degrees in 0..360 {
+            let bearing = Bearing::from_degrees(degrees);
+            assert_eq!(bearing.round_to_sector(BearingRounding::Floor), bearing);
+            assert_eq!(
+                sector(degrees, BearingRounding::Floor),
+                bearing.try_into_byte().unwrap()
+            );
+            assert_eq!(
+                sector(degrees, BearingRounding::Round),
+                sector(degrees, BearingRounding::HalfEven)
+            );
+        }
+
+        assert_eq!(sector(5, BearingRounding::Floor), 0);
+        assert_eq!(sector(5, BearingRounding::Round), 0);
+        assert_eq!(sector(6, BearingRounding::Floor), 0);
+        assert_eq!(sector(6, BearingRounding::Round), 1);
+        assert_eq!(sector(354, BearingRounding::Floor), 31);
+        assert_eq!(sector(355, BearingRounding::Round), 0);
+    }
+
+    #[test]
+    fn openlr_binary_max_relative_distance() {
+        let max_degrees = i16::MAX as f64 / Coordinate::DECA_MICRO_DEG_FACTOR;
+
+        let mut previous_distance = Length::MAX;
+        for lat in [0.0, 45.0, -45.0, 70.0, 80.0] {
+            let coordinate = Coordinate { lon: 13.4, lat };
+            let distance = coordinate.max_relative_distance();
+            assert!(distance <= previous_distance || lat < 0.0);
+            previous_distance = distance;
+
+            let east = Coordinate {
+                lon: coordinate.lon + max_degrees,
+                lat,
+            };
+            let north = Coordinate {
+                lon: coordinate.lon,
+                lat: coordinate.lat + max_degrees,
+            };
+            assert!(coordinate.distance(&east) > distance);
+            assert!(coordinate.distance(&north) > distance);
+        }
+
+        let coordinate = Coordinate {
+            lon: 0.0,
+            lat: 70.0,
+        };
+        assert_relative_eq!(
+            coordinate.max_relative_distance().meters(),
+            11_215.0,
+            epsilon = 10.0
+        );
+    }
+
     #[test]
     fn openlr_binary_encode_decode_relative_degrees() {
         let assert_degrees_relative_eq = |degrees, previous| {
@@ -0,0 +1,261 @@
+//! Extended precision profile of the binary format (not part of the OpenLR standard).
+//!
+//! The 24-bit resolution of the absolute coordinates (and 16-bit resolution of the relative ones)
+//! limits the precision of the coordinates to ~2.4 meters, which is not enough for indoor or high
+//! accuracy use cases. The extended profile is signaled by the version [`EXTENDED_VERSION`] in the
+//! header and is laid out as follows:
+//! - The location reference serialized with the standard profile (with the extended version).
+//! - For each coordinate of the location (in the order they are serialized) the difference between
+//!   the coordinate and its standard binary representation, as big-endian longitude and latitude
+//!   16-bit integers in 10^-7 degrees (~1 cm).
+//! - The number of coordinates of the location as a single byte.
+//!
+//! Readers that don't support the extended profile reject the location by its version.
+
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+
+use crate::format::binary::reader::{DeserializeOptions, deserialize_standard_binary_openlr};
+use crate::format::binary::writer::serialize_standard_binary_openlr;
+use crate::{Coordinate, DeserializeError, LocationReference, SerializeError};
+
+/// Version of the header that signals the extended precision profile.
+pub const EXTENDED_VERSION: u8 = 4;
+
+const STANDARD_VERSION: u8 = 3;
+const VERSION_MASK: u8 = 0b111;
+const DEGREES_FACTOR: f64 = 10_000_000.0;
+const RESIDUAL_SIZE: usize = 4;
+
+/// Serializes an OpenLR Location Reference into Base64 with the extended precision profile.
+pub fn serialize_base64_openlr_extended(
+    location: &LocationReference,
+) -> Result<String, SerializeError> {
+    let data = serialize_binary_openlr_extended(location)?;
+    Ok(BASE64_STANDARD.encode(data))
+}
+
+/// Serializes an OpenLR Location Reference into binary with the extended precision profile.
+pub fn serialize_binary_openlr_extended(
+    location: &LocationReference,
+) -> Result<Vec<u8>, SerializeError> {
+    let mut data = serialize_standard_binary_openlr(location)?;
+
+    // the residuals are relative to the coordinates as read back from the standard profile
+    let mut standard_location =
+        deserialize_standard_binary_openlr(&data, &DeserializeOptions::default())
+            .map_err(|_| SerializeError::InvalidPrecision)?;
+    let mut location = location.clone();
+
+    let coordinates = coordinates_mut(&mut location);
+    let standard_coordinates = coordinates_mut(&mut standard_location);
+
+    let count = u8::try_from(coordinates.len()).map_err(|_| SerializeError::InvalidPrecision)?;
+    if standard_coordinates.len() != coordinates.len() {
+        return Err(SerializeError::InvalidPrecision);
+    }
+
+    for (coordinate, standard) in coordinates.into_iter().zip(standard_coordinates) {
+        for (degrees, standard_degrees) in [
+            (coordinate.lon, standard.lon),
+            (coordinate.lat, standard.lat),
+        ] {
+            let residual = ((degrees - standard_degrees) * DEGREES_FACTOR).round();
+            if residual < i16::MIN as f64 || residual > i16::MAX as f64 {
+                return Err(SerializeError::InvalidPrecision);
+            }
+            data.extend((residual as i16).to_be_bytes());
+        }
+    }
+
+    data.push(count);
+    data[0] = (data[0] & !VERSION_MASK) | EXTENDED_VERSION;
+
+    Ok(data)
+}
+
+/// Deserializes the binary representation of an OpenLR Location Reference serialized with the
+/// extended precision profile.
+pub(crate) fn deserialize_binary_openlr_extended(
+    data: &[u8],
+    options: &DeserializeOptions,
+) -> Result<LocationReference, DeserializeError> {
+    let (&count, data) = data
+        .split_last()
+        .ok_or(DeserializeError::InvalidPrecision)?;
+
+    let residuals_size = count as usize * RESIDUAL_SIZE;
+    let standard_size = data
+        .len()
+        .checked_sub(residuals_size)
+        .ok_or(DeserializeError::InvalidPrecision)?;
+
+    let (standard_data, residuals) = data.split_at(standard_size);
+    let mut standard_data = standard_data.to_vec();
+    if let Some(header) = standard_data.first_mut() {
+        *header = (*header & !VERSION_MASK) | STANDARD_VERSION;
+    }
+
+    let mut location = deserialize_standard_binary_openlr(&standard_data, options)?;
+
+    let coordinates = coordinates_mut(&mut location);
+    if coordinates.len() != count as usize {
+        return Err(DeserializeError::InvalidPrecision);
+    }
+
+    for (coordinate, residual) in coordinates
+        .into_iter()
+        .zip(residuals.chunks_exact(RESIDUAL_SIZE))
+    {
+        let degrees = |bytes: [u8; 2]| i16::from_be_bytes(bytes) as f64 / DEGREES_FACTOR;
+        let lon = coordinate.lon + degrees([residual[0], residual[1]]);
+        let lat = coordinate.lat + degrees([residual[2], residual[3]]);
+        *coordinate = Coordinate::with_policy(lon, lat, options.coordinate_policy)?;
+    }
+
+    Ok(location)
+}
+
+/// Returns true if the data is serialized with the extended precision profile.
+pub(crate) fn is_extended(data: &[u8]) -> bool {
+    data.first()
+        .is_some_and(|header| header & VERSION_MASK == EXTENDED_VERSION)
+}
+
+/// Gets all the coordinates of the location in the same order they are serialized.
+fn coordinates_mut(location: &mut LocationReference) -> Vec<&mut Coordinate> {
+    match location {
+        LocationReference::Line(line) => {
+            line.points.iter_mut().map(|p| &mut p.coordinate).collect()
+        }
+        LocationReference::ClosedLine(line) => {
+            line.points.iter_mut().map(|p| &mut p.coordinate).collect()
+        }
+        LocationReference::GeoCoordinate(coordinate) => vec![coordinate],
+        LocationReference::PointAlongLine(point) => {
+            point.points.iter_mut().map(|p| &mut p.coordinate).collect()
+        }
+        LocationReference::Poi(poi) => {
+            let [first, last] = &mut poi.point.points;
+            vec![
+                &mut first.coordinate,
+                &mut last.coordinate,
+                &mut poi.coordinate,
+            ]
+        }
+        LocationReference::Circle(circle) => vec![&mut circle.center],
+        LocationReference::Rectangle(rectangle) => {
+            vec![&mut rectangle.lower_left, &mut rectangle.upper_right]
+        }
+        LocationReference::Grid(grid) => {
+            vec![&mut grid.rect.lower_left, &mut grid.rect.upper_right]
+        }
+        LocationReference::Polygon(polygon) => polygon.corners.iter_mut().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::{
+        Bearing, Circle, Fow, Frc, Length, Line, LineAttributes, Offsets, PathAttributes, Point,
+        deserialize_base64_openlr, deserialize_binary_openlr, serialize_binary_openlr,
+    };
+
+    fn assert_coordinate_eq(left: Coordinate, right: Coordinate) {
+        assert!((left.lon - right.lon).abs() < 1e-7, "{left} != {right}");
+        assert!((left.lat - right.lat).abs() < 1e-7, "{left} != {right}");
+    }
+
+    fn point(lon: f64, lat: f64, path: Option<PathAttributes>) -> Point {
+        Point {
+            coordinate: Coordinate { lon, lat },
+            line: LineAttributes {
+                frc: Frc::Frc3,
+                fow: Fow::SingleCarriageway,
+                bearing: Bearing::from_degrees(141),
+            },
+            path,
+        }
+    }
+
+    #[test]
+    fn openlr_extended_precision_line_001() {
+        let path = Some(PathAttributes {
+            lfrcnp: Frc::Frc3,
+            dnp: Length::from_meters(557.0),
+        });
+
+        let line = LocationReference::Line(Line {
+            points: vec![
+                point(6.1268198, 49.6085178, path),
+                point(6.1283698, 49.6039878, path),
+                point(6.1281511, 49.6030303, None),
+            ],
+            offsets: Offsets::default(),
+        });
+
+        let data = serialize_binary_openlr_extended(&line).unwrap();
+        let standard_data = serialize_binary_openlr(&line).unwrap();
+        assert_eq!(data.len(), standard_data.len() + 3 * RESIDUAL_SIZE + 1);
+        assert_eq!(data[0] & VERSION_MASK, EXTENDED_VERSION);
+
+        let LocationReference::Line(decoded) = deserialize_binary_openlr(&data).unwrap() else {
+            unreachable!()
+        };
+        let LocationReference::Line(expected) = line else {
+            unreachable!()
+        };
+
+        for (decoded, expected) in decoded.points.iter().zip(&expected.points) {
+            assert_coordinate_eq(decoded.coordinate, expected.coordinate);
+        }
+    }
+
+    #[test]
+    fn openlr_extended_precision_circle_001() {
+        let circle = LocationReference::Circle(Circle {
+            center: Coordinate {
+                lon: 5.1019064,
+                lat: 52.1055673,
+            },
+            radius: Length::from_meters(300.0),
+        });
+
+        let base64 = serialize_base64_openlr_extended(&circle).unwrap();
+        let LocationReference::Circle(decoded) = deserialize_base64_openlr(base64).unwrap() else {
+            unreachable!()
+        };
+
+        assert_coordinate_eq(
+            decoded.center,
+            Coordinate {
+                lon: 5.1019064,
+                lat: 52.1055673,
+            },
+        );
+        assert_eq!(decoded.radius, Length::from_meters(300.0));
+    }
+
+    #[test]
+    fn openlr_extended_precision_invalid_001() {
+        let circle = LocationReference::Circle(Circle {
+            center: Coordinate {
+                lon: 5.1019064,
+                lat: 52.1055673,
+            },
+            radius: Length::from_meters(300.0),
+        });
+
+        let mut data = serialize_binary_openlr_extended(&circle).unwrap();
+        data.pop();
+        data.push(u8::MAX);
+
+        assert_eq!(
+            deserialize_binary_openlr(&data),
+            Err(DeserializeError::InvalidPrecision)
+        );
+    }
+}
@@ -1,15 +1,43 @@
-use std::io::{Cursor, Read};
-
 use base64::Engine;
 use base64::prelude::BASE64_STANDARD;
 
 use crate::format::binary::encoding::EncodedAttributes;
+#[cfg(feature = "extended-precision")]
+use crate::format::binary::extended::{deserialize_binary_openlr_extended, is_extended};
 use crate::{
-    Bearing, Circle, ClosedLine, Coordinate, DeserializeError, Fow, Frc, Grid, GridSize, Length,
-    Line, LineAttributes, LocationReference, LocationType, Offset, PathAttributes, Poi, Point,
-    PointAlongLine, Polygon, Rectangle,
+    Bearing, Circle, ClosedLine, Coordinate, CoordinatePolicy, DeserializeError, Fow, Frc, Grid,
+    GridSize, Length, Line, LineAttributes, LocationReference, LocationType, Offset,
+    PathAttributes, Poi, Point, PointAlongLine, Polygon, Rectangle,
 };
 
+const VERSION: u8 = 3;
+const LEGACY_VERSION: u8 = 2;
+const HEADER_RESERVED_BITS: u8 = 0b1000_0000;
+const ORIENTATION_RESERVED_BITS: u8 = 0b11;
+const FLAGS_RESERVED_BITS: u8 = 0b100;
+const NEGATIVE_OFFSET_FLAG: u8 = 0b001;
+
+/// Options of the binary deserialization.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeserializeOptions {
+    /// Reject the location references with trailing bytes or with reserved bits set, which are
+    /// otherwise ignored. Useful to detect corrupted feeds. Bearings are encoded with 5 bits and
+    /// are therefore always within range.
+    pub strict: bool,
+    /// Handling of the coordinates out of the valid ranges, which are rejected by default.
+    pub coordinate_policy: CoordinatePolicy,
+}
+
+impl DeserializeOptions {
+    /// Options that reject any data that does not follow the physical format exactly.
+    pub const fn strict() -> Self {
+        Self {
+            strict: true,
+            coordinate_policy: CoordinatePolicy::Reject,
+        }
+    }
+}
+
 /// Deserializes an OpenLR Location Reference encoded in Base64.
 pub fn deserialize_base64_openlr(
     data: impl AsRef<[u8]>,
@@ -20,46 +48,147 @@ pub fn deserialize_base64_openlr(
 
 /// Deserializes a binary representation of an OpenLR Location Reference.
 pub fn deserialize_binary_openlr(data: &[u8]) -> Result<LocationReference, DeserializeError> {
-    use LocationReference::*;
+    deserialize_binary_openlr_with_options(data, &DeserializeOptions::default())
+}
 
-    let mut reader = OpenLrBinaryReader::new(data);
+/// Deserializes an OpenLR Location Reference encoded in Base64 with the given options.
+pub fn deserialize_base64_openlr_with_options(
+    data: impl AsRef<[u8]>,
+    options: &DeserializeOptions,
+) -> Result<LocationReference, DeserializeError> {
+    let data = BASE64_STANDARD.decode(data)?;
+    deserialize_binary_openlr_with_options(&data, options)
+}
 
-    match reader.read_header()? {
-        LocationType::Line => Ok(Line(reader.read_line()?)),
-        LocationType::GeoCoordinate => Ok(GeoCoordinate(reader.read_coordinate()?)),
-        LocationType::PointAlongLine => Ok(PointAlongLine(reader.read_point_along_line()?)),
-        LocationType::PoiWithAccessPoint => Ok(Poi(reader.read_poi()?)),
-        LocationType::Circle => Ok(Circle(reader.read_circle()?)),
-        LocationType::Rectangle => Ok(Rectangle(reader.read_rectangle()?)),
-        LocationType::Grid => Ok(Grid(reader.read_grid()?)),
-        LocationType::Polygon => Ok(Polygon(reader.read_polygon()?)),
-        LocationType::ClosedLine => Ok(ClosedLine(reader.read_closed_line()?)),
+/// Deserializes a binary representation of an OpenLR Location Reference with the given options.
+pub fn deserialize_binary_openlr_with_options(
+    data: &[u8],
+    options: &DeserializeOptions,
+) -> Result<LocationReference, DeserializeError> {
+    #[cfg(feature = "extended-precision")]
+    if is_extended(data) {
+        return deserialize_binary_openlr_extended(data, options);
     }
+
+    deserialize_standard_binary_openlr(data, options)
+}
+
+/// Deserializes an OpenLR Location Reference encoded in Base64, also accepting line locations
+/// serialized with the legacy version 2 of the physical format.
+pub fn deserialize_base64_openlr_compatible(
+    data: impl AsRef<[u8]>,
+) -> Result<LocationReference, DeserializeError> {
+    let data = BASE64_STANDARD.decode(data)?;
+    deserialize_binary_openlr_compatible(&data)
+}
+
+/// Deserializes a binary representation of an OpenLR Location Reference, also accepting line
+/// locations serialized with the legacy version 2 of the physical format.
+///
+/// Version 2 only defines line locations, laid out as in version 3 except for the offsets, which
+/// are absolute lengths encoded as DNP intervals. They are converted to the relative offsets of
+/// version 3 using the DNP of the first LRP (positive offset) and of the last but one LRP
+/// (negative offset).
+pub fn deserialize_binary_openlr_compatible(
+    data: &[u8],
+) -> Result<LocationReference, DeserializeError> {
+    if data.first().map(|header| header & 0b111) == Some(LEGACY_VERSION) {
+        let options = DeserializeOptions::default();
+        let mut reader = OpenLrBinaryReader::new(data, LEGACY_VERSION, &options);
+        let LocationType::Line = reader.read_header()? else {
+            return Err(DeserializeError::VersionNotSupported(LEGACY_VERSION));
+        };
+        return Ok(LocationReference::Line(reader.read_line()?));
+    }
+
+    deserialize_binary_openlr(data)
+}
+
+/// Deserializes a binary representation of an OpenLR Location Reference serialized with the
+/// standard profile.
+pub(crate) fn deserialize_standard_binary_openlr(
+    data: &[u8],
+    options: &DeserializeOptions,
+) -> Result<LocationReference, DeserializeError> {
+    use LocationReference::*;
+
+    let mut reader = OpenLrBinaryReader::new(data, VERSION, options);
+
+    let location = match reader.read_header()? {
+        LocationType::Line => Line(reader.read_line()?),
+        LocationType::GeoCoordinate => GeoCoordinate(reader.read_coordinate()?),
+        LocationType::PointAlongLine => PointAlongLine(reader.read_point_along_line()?),
+        LocationType::PoiWithAccessPoint => Poi(reader.read_poi()?),
+        LocationType::Circle => Circle(reader.read_circle()?),
+        LocationType::Rectangle => Rectangle(reader.read_rectangle()?),
+        LocationType::Grid => Grid(reader.read_grid()?),
+        LocationType::Polygon => Polygon(reader.read_polygon()?),
+        LocationType::ClosedLine => ClosedLine(reader.read_closed_line()?),
+    };
+
+    reader.check_trailing_bytes()?;
+    Ok(location)
 }
 
 #[derive(Debug)]
 struct OpenLrBinaryReader<'a> {
-    cursor: Cursor<&'a [u8]>,
+    data: &'a [u8],
+    /// Number of bytes read so far.
+    position: usize,
+    /// Version of the physical format accepted in the header.
+    version: u8,
+    /// Reject trailing bytes and reserved bits set.
+    strict: bool,
+    coordinate_policy: CoordinatePolicy,
 }
 
 impl<'a> OpenLrBinaryReader<'a> {
-    const fn new(data: &'a [u8]) -> Self {
+    const fn new(data: &'a [u8], version: u8, options: &DeserializeOptions) -> Self {
         Self {
-            cursor: Cursor::new(data),
+            data,
+            position: 0,
+            version,
+            strict: options.strict,
+            coordinate_policy: options.coordinate_policy,
         }
     }
 
+    fn check_trailing_bytes(&self) -> Result<(), DeserializeError> {
+        let trailing_bytes = self.len().saturating_sub(self.position);
+        if self.strict && trailing_bytes > 0 {
+            return Err(DeserializeError::TrailingBytes(trailing_bytes));
+        }
+        Ok(())
+    }
+
+    const fn check_reserved_bits(&self, byte: u8, reserved: u8) -> Result<(), DeserializeError> {
+        if self.strict && byte & reserved != 0 {
+            return Err(DeserializeError::ReservedBitsSet(byte));
+        }
+        Ok(())
+    }
+
     const fn len(&self) -> usize {
-        self.cursor.get_ref().len()
+        self.data.len()
+    }
+
+    /// Reads the next N bytes, failing if the data ends before them.
+    fn read_bytes<const N: usize>(&mut self) -> Result<[u8; N], DeserializeError> {
+        let bytes = self
+            .data
+            .get(self.position..self.position + N)
+            .and_then(|bytes| <[u8; N]>::try_from(bytes).ok())
+            .ok_or(DeserializeError::UnexpectedEnd)?;
+        self.position += N;
+        Ok(bytes)
     }
 
     fn read_header(&mut self) -> Result<LocationType, DeserializeError> {
-        let mut header = [0u8; 1];
-        self.cursor.read_exact(&mut header)?;
-        let header = header[0];
+        let [header] = self.read_bytes()?;
+        self.check_reserved_bits(header, HEADER_RESERVED_BITS)?;
 
         let version = header & 0b111;
-        if version != 3 {
+        if version != self.version {
             return Err(DeserializeError::VersionNotSupported(version));
         }
 
@@ -81,11 +210,12 @@ impl<'a> OpenLrBinaryReader<'a> {
     }
 
     fn read_line(&mut self) -> Result<Line, DeserializeError> {
-        let relative_points_count = (self.len() - 9) / 7;
+        let relative_points_count = self.len().saturating_sub(9) / 7;
         let mut line = Line::with_capacity(1 + relative_points_count);
 
         let mut coordinate = self.read_coordinate()?;
         let mut attributes = self.read_attributes()?;
+        self.check_reserved_bits(attributes.orientation_or_side, ORIENTATION_RESERVED_BITS)?;
 
         for _ in 0..relative_points_count {
             let dnp = self.read_dnp()?;
@@ -101,34 +231,47 @@ impl<'a> OpenLrBinaryReader<'a> {
 
             coordinate = self.read_relative_coordinate(coordinate)?;
             attributes = self.read_attributes()?;
+            self.check_reserved_bits(attributes.orientation_or_side, ORIENTATION_RESERVED_BITS)?;
         }
 
+        self.check_reserved_bits(attributes.lfrcnp_or_flags, FLAGS_RESERVED_BITS)?;
         line.points.push(Point {
             coordinate,
             line: attributes.line,
             path: None,
         });
 
-        let mut read_offset = |offset_flag| {
-            if offset_flag {
-                self.read_offset()
-            } else {
+        let first_dnp = line.points.first().map_or(Length::ZERO, Point::dnp);
+        let last_dnp = line
+            .points
+            .iter()
+            .rev()
+            .nth(1)
+            .map_or(Length::ZERO, Point::dnp);
+
+        let mut read_offset = |offset_flag: bool, dnp: Length| {
+            if !offset_flag {
                 Ok(Offset::default())
+            } else if self.version == LEGACY_VERSION {
+                self.read_legacy_offset(dnp)
+            } else {
+                self.read_offset()
             }
         };
 
-        line.offsets.pos = read_offset(attributes.pos_offset_flag())?;
-        line.offsets.neg = read_offset(attributes.neg_offset_flag())?;
+        line.offsets.pos = read_offset(attributes.pos_offset_flag(), first_dnp)?;
+        line.offsets.neg = read_offset(attributes.neg_offset_flag(), last_dnp)?;
 
         Ok(line)
     }
 
     fn read_closed_line(&mut self) -> Result<ClosedLine, DeserializeError> {
-        let relative_points_count = (self.len() - 12) / 7;
+        let relative_points_count = self.len().saturating_sub(12) / 7;
         let mut line = ClosedLine::with_capacity(1 + relative_points_count);
 
         let mut coordinate = self.read_coordinate()?;
         let attributes = self.read_attributes()?;
+        self.check_reserved_bits(attributes.orientation_or_side, ORIENTATION_RESERVED_BITS)?;
         let dnp = self.read_dnp()?;
         line.points.push(Point {
             coordinate,
@@ -142,6 +285,7 @@ impl<'a> OpenLrBinaryReader<'a> {
         for _ in 0..relative_points_count {
             coordinate = self.read_relative_coordinate(coordinate)?;
             let attributes = self.read_attributes()?;
+            self.check_reserved_bits(attributes.orientation_or_side, ORIENTATION_RESERVED_BITS)?;
             let dnp = self.read_dnp()?;
             line.points.push(Point {
                 coordinate,
@@ -154,6 +298,9 @@ impl<'a> OpenLrBinaryReader<'a> {
         }
 
         let attributes = self.read_attributes()?;
+        self.check_reserved_bits(attributes.orientation_or_side, ORIENTATION_RESERVED_BITS)?;
+        // the last LRP has neither lowest FRC to next point nor offsets
+        self.check_reserved_bits(attributes.lfrcnp_or_flags, 0b111)?;
         line.last_line = attributes.line;
 
         Ok(line)
@@ -177,6 +324,11 @@ impl<'a> OpenLrBinaryReader<'a> {
         let coordinate = self.read_relative_coordinate(coordinate)?;
         let attributes = self.read_attributes()?;
         let side = attributes.side()?;
+        // a point along line has no negative offset
+        self.check_reserved_bits(
+            attributes.lfrcnp_or_flags,
+            FLAGS_RESERVED_BITS | NEGATIVE_OFFSET_FLAG,
+        )?;
 
         let point_2 = Point {
             coordinate,
@@ -245,7 +397,7 @@ impl<'a> OpenLrBinaryReader<'a> {
     }
 
     fn read_polygon(&mut self) -> Result<Polygon, DeserializeError> {
-        let relative_corners_count = (self.len() - 7) / 4;
+        let relative_corners_count = self.len().saturating_sub(7) / 4;
         let mut polygon = Polygon::with_capacity(1 + relative_corners_count);
 
         let mut coordinate = self.read_coordinate()?;
@@ -261,14 +413,13 @@ impl<'a> OpenLrBinaryReader<'a> {
 
     fn read_coordinate(&mut self) -> Result<Coordinate, DeserializeError> {
         let mut read_degrees = || -> Result<f64, DeserializeError> {
-            let mut c = [0u8; 3];
-            self.cursor.read_exact(&mut c)?;
+            let c = self.read_bytes()?;
             Ok(Coordinate::degrees_from_be_bytes(c))
         };
 
         let lon = read_degrees()?;
         let lat = read_degrees()?;
-        let coordinate = Coordinate::new(lon, lat)?;
+        let coordinate = Coordinate::with_policy(lon, lat, self.coordinate_policy)?;
         Ok(coordinate)
     }
 
@@ -277,20 +428,18 @@ impl<'a> OpenLrBinaryReader<'a> {
         previous: Coordinate,
     ) -> Result<Coordinate, DeserializeError> {
         let mut read_degrees = |previous| -> Result<f64, DeserializeError> {
-            let mut c = [0u8; 2];
-            self.cursor.read_exact(&mut c)?;
+            let c = self.read_bytes()?;
             Ok(Coordinate::degrees_from_be_bytes_relative(c, previous))
         };
 
         let lon = read_degrees(previous.lon)?;
         let lat = read_degrees(previous.lat)?;
-        let coordinate = Coordinate::new(lon, lat)?;
+        let coordinate = Coordinate::with_policy(lon, lat, self.coordinate_policy)?;
         Ok(coordinate)
     }
 
     fn read_attributes(&mut self) -> Result<EncodedAttributes, DeserializeError> {
-        let mut attributes = [0u8; 2];
-        self.cursor.read_exact(&mut attributes)?;
+        let attributes: [u8; 2] = self.read_bytes()?;
 
         let fow = Fow::try_from_byte(attributes[0] & 0b111)?;
         let frc = Frc::try_from_byte((attributes[0] >> 3) & 0b111)?;
@@ -306,26 +455,32 @@ impl<'a> OpenLrBinaryReader<'a> {
     }
 
     fn read_dnp(&mut self) -> Result<Length, DeserializeError> {
-        let mut dnp = [0u8; 1];
-        self.cursor.read_exact(&mut dnp)?;
-        Ok(Length::dnp_from_byte(dnp[0]))
+        let [dnp] = self.read_bytes()?;
+        Ok(Length::dnp_from_byte(dnp))
     }
 
     fn read_offset(&mut self) -> Result<Offset, DeserializeError> {
-        let mut offset = [0u8; 1];
-        self.cursor.read_exact(&mut offset)?;
-        Ok(Offset::from_byte(offset[0]))
+        let [offset] = self.read_bytes()?;
+        Ok(Offset::from_byte(offset))
+    }
+
+    /// Reads an offset of the version 2 of the physical format (an absolute length) relative to the
+    /// given DNP.
+    fn read_legacy_offset(&mut self, dnp: Length) -> Result<Offset, DeserializeError> {
+        let [offset] = self.read_bytes()?;
+        Ok(Offset::relative(Length::dnp_from_byte(offset), dnp))
     }
 
     fn read_radius(&mut self) -> Result<Length, DeserializeError> {
-        let mut radius = [0u8; 4];
-        let length = self.cursor.read(&mut radius)?;
-        Ok(Length::radius_from_be_bytes(&radius[..length]))
+        // the radius takes the remaining 1 to 4 bytes
+        let remaining = self.data.get(self.position..).unwrap_or_default();
+        let radius = remaining.get(..4).unwrap_or(remaining);
+        self.position += radius.len();
+        Ok(Length::radius_from_be_bytes(radius))
     }
 
     fn read_grid_size(&mut self) -> Result<GridSize, DeserializeError> {
-        let mut size = [0u8; 4];
-        self.cursor.read_exact(&mut size)?;
+        let size = self.read_bytes()?;
         Ok(GridSize::from_be_bytes(size))
     }
 }
@@ -354,6 +509,134 @@ mod tests {
         );
     }
 
+    #[test]
+    fn openlr_deserialize_version_2_compatible() {
+        let location =
+            deserialize_base64_openlr_compatible("CgRbWyNG9BpsCQCb/jsbtAT/6/+jK1kC").unwrap();
+
+        let LocationReference::Line(line) = location else {
+            unreachable!()
+        };
+
+        let dnps: Vec<_> = line.points.iter().map(Point::dnp).collect();
+        assert_eq!(
+            dnps,
+            [
+                Length::from_meters(557.0),
+                Length::from_meters(264.0),
+                Length::ZERO
+            ]
+        );
+
+        // 2 DNP intervals (147m) of the first LRP DNP
+        assert_eq!(
+            line.offsets,
+            Offsets {
+                pos: Offset::from_bucket(67),
+                neg: Offset::default()
+            }
+        );
+
+        assert_eq!(
+            deserialize_base64_openlr_compatible("CwRbWyNG9RpsCQCb/jsbtAT/6/+jK1lE").unwrap(),
+            deserialize_base64_openlr("CwRbWyNG9RpsCQCb/jsbtAT/6/+jK1lE").unwrap()
+        );
+    }
+
+    #[test]
+    fn openlr_deserialize_truncated() {
+        let data = BASE64_STANDARD
+            .decode("CwRbWyNG9RpsCQCb/jsbtAT/6/+jK1lE")
+            .unwrap();
+
+        for length in [0, 1, 5, data.len() - 1] {
+            assert_eq!(
+                deserialize_binary_openlr(&data[..length]),
+                Err(DeserializeError::UnexpectedEnd),
+                "{length}"
+            );
+        }
+    }
+
+    #[test]
+    fn openlr_deserialize_strict_001() {
+        let options = DeserializeOptions::strict();
+
+        for code in [
+            "CwRbWyNG9RpsCQCb/jsbtAT/6/+jK1lE",
+            "I+djotZ9eA==",
+            "K/6P+SKSuBJGGAUn/1gSUyM=",
+        ] {
+            assert_eq!(
+                deserialize_base64_openlr_with_options(code, &options).unwrap(),
+                deserialize_base64_openlr(code).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn openlr_deserialize_strict_002() {
+        let options = DeserializeOptions::strict();
+
+        // trailing byte
+        let mut data = BASE64_STANDARD.decode("I+djotZ9eA==").unwrap();
+        data.push(0);
+        assert!(deserialize_binary_openlr(&data).is_ok());
+        assert_eq!(
+            deserialize_binary_openlr_with_options(&data, &options).unwrap_err(),
+            DeserializeError::TrailingBytes(1)
+        );
+
+        // reserved bit of the header
+        let mut data = BASE64_STANDARD.decode("I+djotZ9eA==").unwrap();
+        data[0] |= 0b1000_0000;
+        assert!(deserialize_binary_openlr(&data).is_ok());
+        assert_eq!(
+            deserialize_binary_openlr_with_options(&data, &options).unwrap_err(),
+            DeserializeError::ReservedBitsSet(data[0])
+        );
+
+        // reserved bits of the first LRP attributes of a line
+        let mut data = BASE64_STANDARD
+            .decode("CwRbWyNG9RpsCQCb/jsbtAT/6/+jK1lE")
+            .unwrap();
+        data[7] |= 0b1100_0000;
+        assert!(deserialize_binary_openlr(&data).is_ok());
+        assert_eq!(
+            deserialize_binary_openlr_with_options(&data, &options).unwrap_err(),
+            DeserializeError::ReservedBitsSet(0b11)
+        );
+    }
+
+    #[test]
+    fn openlr_deserialize_coordinate_policy() {
+        // latitude of 180 degrees
+        let data = [0b0010_0011, 0, 0, 0, 0x7F, 0xFF, 0xFF];
+
+        assert!(matches!(
+            deserialize_binary_openlr(&data).unwrap_err(),
+            DeserializeError::InvalidCoordinate(_)
+        ));
+
+        for (coordinate_policy, lat) in [
+            (CoordinatePolicy::Clamp, 90.0),
+            (CoordinatePolicy::Normalize, 0.0),
+        ] {
+            let options = DeserializeOptions {
+                coordinate_policy,
+                ..Default::default()
+            };
+
+            let LocationReference::GeoCoordinate(coordinate) =
+                deserialize_binary_openlr_with_options(&data, &options).unwrap()
+            else {
+                unreachable!()
+            };
+
+            assert_eq!(coordinate.lat.round(), lat);
+        }
+    }
+
     #[test]
     fn openlr_deserialize_invalid_header() {
         assert_eq!(
@@ -0,0 +1,90 @@
+//! Binary location references embedded in [`std::io`] streams.
+//!
+//! The rest of the binary format reads the location references from byte slices and writes them
+//! into vectors, without going through [`std::io`]. The crate still requires `std` (the model uses
+//! the `std` float functions and the errors implement `std::error::Error`).
+
+use std::io::{Read, Write};
+
+use crate::{
+    DeserializeError, LocationReference, SerializeError, deserialize_binary_openlr,
+    serialize_binary_openlr,
+};
+
+/// Deserializes the binary representation of an OpenLR Location Reference of the given length
+/// from a reader, leaving the reader right after it. Useful for location references embedded in
+/// larger streams (such as TPEG frames or protobuf messages).
+///
+/// The binary format is not self-delimiting (the number of LRPs and some location types are
+/// inferred from the length of the data), so the length has to be known from the enclosing stream.
+pub fn deserialize_openlr_from_reader<R: Read>(
+    mut reader: R,
+    length: usize,
+) -> Result<LocationReference, DeserializeError> {
+    let mut data = vec![0; length];
+    reader.read_exact(&mut data)?;
+    deserialize_binary_openlr(&data)
+}
+
+/// Serializes the binary representation of an OpenLR Location Reference into a writer, returning
+/// the number of bytes written. Useful for location references embedded in larger streams (such as
+/// TPEG frames or protobuf messages), which have to record this length to read the location back
+/// (see [`deserialize_openlr_from_reader`]).
+pub fn serialize_openlr_to_writer<W: Write>(
+    location: &LocationReference,
+    mut writer: W,
+) -> Result<usize, SerializeError> {
+    let data = serialize_binary_openlr(location)?;
+    writer.write_all(&data)?;
+    Ok(data.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, ErrorKind};
+
+    use base64::Engine;
+    use base64::prelude::BASE64_STANDARD;
+    use test_log::test;
+
+    use super::*;
+    use crate::deserialize_base64_openlr;
+
+    #[test]
+    fn openlr_deserialize_from_reader() {
+        let line = BASE64_STANDARD
+            .decode("CwRbWyNG9RpsCQCb/jsbtAT/6/+jK1lE")
+            .unwrap();
+        let coordinate = BASE64_STANDARD.decode("I+djotZ9eA==").unwrap();
+
+        let mut stream = Cursor::new([line.as_slice(), coordinate.as_slice(), &[0xFF]].concat());
+
+        assert_eq!(
+            deserialize_openlr_from_reader(&mut stream, line.len()).unwrap(),
+            deserialize_binary_openlr(&line).unwrap()
+        );
+        assert_eq!(
+            deserialize_openlr_from_reader(&mut stream, coordinate.len()).unwrap(),
+            deserialize_binary_openlr(&coordinate).unwrap()
+        );
+        assert_eq!(stream.position(), 24 + 7);
+
+        assert_eq!(
+            deserialize_openlr_from_reader(&mut stream, 7).unwrap_err(),
+            DeserializeError::IO(ErrorKind::UnexpectedEof)
+        );
+    }
+
+    #[test]
+    fn openlr_serialize_to_writer() {
+        let location = deserialize_base64_openlr("CwRbWyNG9RpsCQCb/jsbtAT/6/+jK1lE").unwrap();
+
+        let mut stream = vec![0xFF];
+        let length = serialize_openlr_to_writer(&location, &mut stream).unwrap();
+
+        assert_eq!(length, 24);
+        assert_eq!(location.encoded_size(), 24);
+        assert_eq!(stream[0], 0xFF);
+        assert_eq!(stream[1..], serialize_binary_openlr(&location).unwrap());
+    }
+}
@@ -1,5 +1,3 @@
-use std::io::{Cursor, Write};
-
 use base64::Engine;
 use base64::prelude::BASE64_STANDARD;
 
@@ -19,6 +17,13 @@ pub fn serialize_base64_openlr(location: &LocationReference) -> Result<String, S
 
 /// Serializes an OpenLR Location Reference into binary.
 pub fn serialize_binary_openlr(location: &LocationReference) -> Result<Vec<u8>, SerializeError> {
+    serialize_standard_binary_openlr(location)
+}
+
+/// Serializes an OpenLR Location Reference into binary with the standard profile.
+pub(crate) fn serialize_standard_binary_openlr(
+    location: &LocationReference,
+) -> Result<Vec<u8>, SerializeError> {
     use LocationReference::*;
 
     let mut writer = OpenLrBinaryWriter::default();
@@ -36,12 +41,66 @@ pub fn serialize_binary_openlr(location: &LocationReference) -> Result<Vec<u8>,
         ClosedLine(line) => writer.write_closed_line(line)?,
     };
 
-    Ok(writer.cursor.into_inner())
+    Ok(writer.data)
+}
+
+impl LocationReference {
+    /// Returns the exact length in bytes of the binary representation of the location reference
+    /// (see [`serialize_binary_openlr`]), without serializing it. Useful to pre-size buffers or to
+    /// check payload limits up front. Not meaningful if the serialization of the location fails.
+    pub fn encoded_size(&self) -> usize {
+        const HEADER: usize = 1;
+        const COORDINATE: usize = 6;
+        const RELATIVE_COORDINATE: usize = 4;
+        const ATTRIBUTES: usize = 2;
+        const DNP: usize = 1;
+        const OFFSET: usize = 1;
+        const RADIUS: usize = 4;
+        const GRID_SIZE: usize = 4;
+
+        const FIRST_POINT: usize = COORDINATE + ATTRIBUTES + DNP;
+        const RELATIVE_POINT: usize = RELATIVE_COORDINATE + ATTRIBUTES + DNP;
+        const LAST_POINT: usize = RELATIVE_COORDINATE + ATTRIBUTES;
+        const RECTANGLE: usize = 2 * COORDINATE;
+
+        let offsets_size = |offsets: Offsets| {
+            let flags = offsets.into_byte();
+            OFFSET * ((flags >> 1) + (flags & 1)) as usize
+        };
+
+        let body = match self {
+            Self::Line(Line { points, offsets }) => {
+                let relative_points = points.len().saturating_sub(2);
+                FIRST_POINT + RELATIVE_POINT * relative_points + LAST_POINT + offsets_size(*offsets)
+            }
+            Self::GeoCoordinate(_) => COORDINATE,
+            Self::PointAlongLine(point) => {
+                FIRST_POINT + LAST_POINT + offsets_size(Offsets::positive(point.offset))
+            }
+            Self::Poi(Poi { point, .. }) => {
+                FIRST_POINT
+                    + LAST_POINT
+                    + offsets_size(Offsets::positive(point.offset))
+                    + RELATIVE_COORDINATE
+            }
+            Self::Circle(_) => COORDINATE + RADIUS,
+            Self::Rectangle(_) => RECTANGLE,
+            Self::Grid(_) => RECTANGLE + GRID_SIZE,
+            Self::Polygon(Polygon { corners }) => {
+                COORDINATE + RELATIVE_COORDINATE * corners.len().saturating_sub(1)
+            }
+            Self::ClosedLine(ClosedLine { points, .. }) => {
+                FIRST_POINT + RELATIVE_POINT * points.len().saturating_sub(1) + ATTRIBUTES
+            }
+        };
+
+        HEADER + body
+    }
 }
 
 #[derive(Debug, Default)]
 struct OpenLrBinaryWriter {
-    cursor: Cursor<Vec<u8>>,
+    data: Vec<u8>,
 }
 
 impl OpenLrBinaryWriter {
@@ -59,7 +118,7 @@ impl OpenLrBinaryWriter {
         };
 
         let header = VERSION + (location_type << 3);
-        self.cursor.write_all(&[header])?;
+        self.data.push(header);
         Ok(())
     }
 
@@ -213,14 +272,14 @@ impl OpenLrBinaryWriter {
             return Err(CoordinateError::InvalidLocation(*coordinate).into());
         }
 
-        let mut write_degrees = |degrees| -> Result<(), SerializeError> {
+        let mut write_degrees = |degrees| {
             let bytes = Coordinate::degrees_into_be_bytes(degrees);
-            self.cursor.write_all(&bytes)?;
-            Ok(())
+            self.data.extend_from_slice(&bytes);
         };
 
-        write_degrees(coordinate.lon)?;
-        write_degrees(coordinate.lat)
+        write_degrees(coordinate.lon);
+        write_degrees(coordinate.lat);
+        Ok(())
     }
 
     fn write_relative_coordinate(
@@ -234,14 +293,17 @@ impl OpenLrBinaryWriter {
             return Err(CoordinateError::InvalidLocation(coordinate).into());
         }
 
-        let mut write_degrees = |degrees, previous| -> Result<(), SerializeError> {
+        if !coordinate.is_relative_in_range(&previous) {
+            return Err(SerializeError::RelativeCoordinateOutOfRange(coordinate));
+        }
+
+        let mut write_degrees = |degrees, previous| {
             let bytes = Coordinate::degrees_into_be_bytes_relative(degrees, previous);
-            self.cursor.write_all(&bytes)?;
-            Ok(())
+            self.data.extend_from_slice(&bytes);
         };
 
-        write_degrees(coordinate.lon, previous.lon)?;
-        write_degrees(coordinate.lat, previous.lat)?;
+        write_degrees(coordinate.lon, previous.lon);
+        write_degrees(coordinate.lat, previous.lat);
         Ok(coordinate)
     }
 
@@ -252,31 +314,31 @@ impl OpenLrBinaryWriter {
 
         let first_byte = fow + (frc << 3) + (attributes.orientation_or_side << 6);
         let second_byte = bearing + (attributes.lfrcnp_or_flags << 5);
-        self.cursor.write_all(&[first_byte, second_byte])?;
+        self.data.extend_from_slice(&[first_byte, second_byte]);
         Ok(())
     }
 
     fn write_dnp(&mut self, dnp: Length) -> Result<(), SerializeError> {
         let dnp = dnp.try_dnp_into_byte()?;
-        self.cursor.write_all(&[dnp])?;
+        self.data.push(dnp);
         Ok(())
     }
 
     fn write_radius(&mut self, radius: Length) -> Result<(), SerializeError> {
         let radius = radius.try_radius_into_be_bytes()?;
-        self.cursor.write_all(&radius)?;
+        self.data.extend_from_slice(&radius);
         Ok(())
     }
 
     fn write_offset(&mut self, offset: Offset) -> Result<(), SerializeError> {
         let offset = offset.try_into_byte()?;
-        self.cursor.write_all(&[offset])?;
+        self.data.push(offset);
         Ok(())
     }
 
     fn write_grid_size(&mut self, size: &GridSize) -> Result<(), SerializeError> {
         let size = size.try_into_be_bytes()?;
-        self.cursor.write_all(&size)?;
+        self.data.extend_from_slice(&size);
         Ok(())
     }
 }
@@ -758,6 +820,36 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn openlr_serialize_poi_location_reference_002() {
+        let point = Point {
+            coordinate: Coordinate {
+                lon: 13.46112,
+                lat: 52.51711,
+            },
+            ..Point::default()
+        };
+
+        let poi = LocationReference::Poi(Poi {
+            point: PointAlongLine {
+                points: [point, point],
+                ..PointAlongLine::default()
+            },
+            coordinate: Coordinate {
+                lon: 13.86112,
+                lat: 52.51711,
+            },
+        });
+
+        assert_eq!(
+            serialize_binary_openlr(&poi),
+            Err(SerializeError::RelativeCoordinateOutOfRange(Coordinate {
+                lon: 13.86112,
+                lat: 52.51711,
+            }))
+        );
+    }
+
     #[test]
     fn openlr_serialize_polygon_location_reference_001() {
         assert_serde_eq(LocationReference::Polygon(Polygon {
@@ -826,6 +918,9 @@ mod tests {
     }
 
     fn assert_serde_eq(location: LocationReference) {
+        let data = serialize_binary_openlr(&location).unwrap();
+        assert_eq!(location.encoded_size(), data.len());
+
         let encoded = serialize_base64_openlr(&location).unwrap();
         let decoded_location = deserialize_base64_openlr(&encoded).unwrap();
         assert_eq!(location, decoded_location);
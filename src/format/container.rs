@@ -0,0 +1,205 @@
+//! Containers of the traffic information standards that embed OpenLR binary location references.
+//!
+//! - DATEX II publications carry each location reference as the Base64 text of an `openlrBinary`
+//!   element (with any namespace prefix).
+//! - TPEG location referencing containers are sequences of components, each framed by its id (a
+//!   single byte) and by the length of its data (an `IntUnLoMB`: 7 bits per byte, most significant
+//!   group first, with the highest bit set on every byte but the last). The data of the OpenLR
+//!   components is the binary location reference.
+
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+
+use crate::{DeserializeError, LocationReference, deserialize_binary_openlr};
+
+/// Local name of the DATEX II element of the Base64 OpenLR location references.
+const DATEX2_OPENLR_ELEMENT: &str = "openlrBinary";
+
+/// Max number of bytes of a TPEG `IntUnLoMB` (32 bits of value).
+const TPEG_LENGTH_MAX_BYTES: usize = 5;
+
+/// Gets the Base64 payloads (without the surrounding whitespace) of the `openlrBinary` elements of
+/// a DATEX II document, in document order.
+///
+/// The document is scanned for the elements without being validated: the elements nested in
+/// comments or CDATA sections are extracted as well.
+pub fn datex2_openlr_payloads(xml: &str) -> Result<Vec<&str>, DeserializeError> {
+    let mut payloads = vec![];
+    let mut rest = xml;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let tag_end = rest.find('>').ok_or(DeserializeError::InvalidXml)?;
+        let tag = &rest[..tag_end];
+        rest = &rest[tag_end + 1..];
+
+        let name = tag.split_whitespace().next().unwrap_or_default();
+        let local_name = name.rsplit(':').next().unwrap_or_default();
+        if local_name != DATEX2_OPENLR_ELEMENT || name.starts_with('/') || tag.ends_with('/') {
+            continue;
+        }
+
+        let closing_tag = format!("</{name}>");
+        let end = rest
+            .find(&closing_tag)
+            .ok_or(DeserializeError::MissingXmlElement(DATEX2_OPENLR_ELEMENT))?;
+        payloads.push(rest[..end].trim());
+        rest = &rest[end + closing_tag.len()..];
+    }
+
+    Ok(payloads)
+}
+
+/// Deserializes the OpenLR Location References of the `openlrBinary` elements of a DATEX II
+/// document (see [`datex2_openlr_payloads`]).
+pub fn deserialize_datex2_openlr(xml: &str) -> Result<Vec<LocationReference>, DeserializeError> {
+    datex2_openlr_payloads(xml)?
+        .into_iter()
+        .map(|payload| {
+            let data = BASE64_STANDARD.decode(payload)?;
+            deserialize_binary_openlr(&data)
+        })
+        .collect()
+}
+
+/// Gets the data of the components with the given id of a TPEG location referencing container, in
+/// container order. The components with any other id are skipped.
+pub fn tpeg_openlr_payloads(data: &[u8], component_id: u8) -> Result<Vec<&[u8]>, DeserializeError> {
+    let mut payloads = vec![];
+    let mut position = 0;
+
+    while let Some(&id) = data.get(position) {
+        position += 1;
+        let length = read_tpeg_length(data, &mut position)?;
+        let payload = data
+            .get(position..position + length)
+            .ok_or(DeserializeError::UnexpectedEnd)?;
+        position += length;
+
+        if id == component_id {
+            payloads.push(payload);
+        }
+    }
+
+    Ok(payloads)
+}
+
+/// Deserializes the OpenLR Location References of the components with the given id of a TPEG
+/// location referencing container (see [`tpeg_openlr_payloads`]).
+pub fn deserialize_tpeg_openlr(
+    data: &[u8],
+    component_id: u8,
+) -> Result<Vec<LocationReference>, DeserializeError> {
+    tpeg_openlr_payloads(data, component_id)?
+        .into_iter()
+        .map(deserialize_binary_openlr)
+        .collect()
+}
+
+/// Reads the `IntUnLoMB` length of a TPEG component.
+fn read_tpeg_length(data: &[u8], position: &mut usize) -> Result<usize, DeserializeError> {
+    let mut length = 0;
+
+    for _ in 0..TPEG_LENGTH_MAX_BYTES {
+        let byte = *data.get(*position).ok_or(DeserializeError::UnexpectedEnd)?;
+        *position += 1;
+        length = (length << 7) | usize::from(byte & 0x7F);
+
+        if byte & 0x80 == 0 {
+            return Ok(length);
+        }
+    }
+
+    Err(DeserializeError::InvalidContainer)
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::deserialize_base64_openlr;
+
+    #[test]
+    fn container_datex2_openlr() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <d2:payload xmlns:d2="http://datex2.eu/schema/3/d2Payload">
+                <loc:openlrExtendedLinear>
+                    <loc:openlrBinary>
+                        CwRbWyNG9RpsCQCb/jsbtAT/6/+jK1lE
+                    </loc:openlrBinary>
+                </loc:openlrExtendedLinear>
+                <openlrBinary/>
+                <openlrBinary>I+djotZ9eA==</openlrBinary>
+            </d2:payload>"#;
+
+        assert_eq!(
+            datex2_openlr_payloads(xml).unwrap(),
+            ["CwRbWyNG9RpsCQCb/jsbtAT/6/+jK1lE", "I+djotZ9eA=="]
+        );
+        assert_eq!(
+            deserialize_datex2_openlr(xml).unwrap(),
+            [
+                deserialize_base64_openlr("CwRbWyNG9RpsCQCb/jsbtAT/6/+jK1lE").unwrap(),
+                deserialize_base64_openlr("I+djotZ9eA==").unwrap()
+            ]
+        );
+
+        assert_eq!(
+            datex2_openlr_payloads("<a><b/></a>").unwrap(),
+            Vec::<&str>::new()
+        );
+        assert_eq!(
+            datex2_openlr_payloads("<openlrBinary>I+djotZ9eA==</a>"),
+            Err(DeserializeError::MissingXmlElement("openlrBinary"))
+        );
+        assert_eq!(
+            deserialize_datex2_openlr("<openlrBinary>not base64</openlrBinary>"),
+            Err(DeserializeError::InvalidBase64)
+        );
+    }
+
+    #[test]
+    fn container_tpeg_openlr() {
+        const OPENLR_ID: u8 = 8;
+
+        let line = BASE64_STANDARD
+            .decode("CwRbWyNG9RpsCQCb/jsbtAT/6/+jK1lE")
+            .unwrap();
+        let coordinate = BASE64_STANDARD.decode("I+djotZ9eA==").unwrap();
+        let other = vec![0xAA; 200];
+
+        // 200 = 0b1_1001000: two bytes IntUnLoMB length
+        let data = [
+            &[OPENLR_ID, line.len() as u8][..],
+            &line,
+            &[1, 0b1000_0001, 0b0100_1000],
+            &other,
+            &[OPENLR_ID, coordinate.len() as u8],
+            &coordinate,
+        ]
+        .concat();
+
+        assert_eq!(
+            tpeg_openlr_payloads(&data, OPENLR_ID).unwrap(),
+            [line.as_slice(), coordinate.as_slice()]
+        );
+        assert_eq!(tpeg_openlr_payloads(&data, 1).unwrap(), [other.as_slice()]);
+        assert_eq!(
+            deserialize_tpeg_openlr(&data, OPENLR_ID).unwrap(),
+            [
+                deserialize_binary_openlr(&line).unwrap(),
+                deserialize_binary_openlr(&coordinate).unwrap()
+            ]
+        );
+
+        assert_eq!(
+            tpeg_openlr_payloads(&data[..data.len() - 1], OPENLR_ID),
+            Err(DeserializeError::UnexpectedEnd)
+        );
+        assert_eq!(
+            tpeg_openlr_payloads(&[OPENLR_ID, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01], OPENLR_ID),
+            Err(DeserializeError::InvalidContainer)
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! Conversions between the coordinates, areas and locations and the geometries of the
+//! [geo](https://docs.rs/geo) crate.
+
+use geo::{Coord, Geometry, GeometryCollection, LineString, Point, Rect};
+
+use crate::location::get_path_coordinates;
+use crate::{
+    Area, Coordinate, CoordinateError, DirectedGraph, LineLocation, Location, Polygon, Rectangle,
+};
+
+impl From<Coordinate> for Coord {
+    fn from(Coordinate { lon, lat }: Coordinate) -> Self {
+        Self { x: lon, y: lat }
+    }
+}
+
+impl From<Coordinate> for Point {
+    fn from(coordinate: Coordinate) -> Self {
+        Self(coordinate.into())
+    }
+}
+
+impl TryFrom<Coord> for Coordinate {
+    type Error = CoordinateError;
+
+    fn try_from(Coord { x, y }: Coord) -> Result<Self, Self::Error> {
+        Self::new(x, y)
+    }
+}
+
+impl TryFrom<Point> for Coordinate {
+    type Error = CoordinateError;
+
+    fn try_from(point: Point) -> Result<Self, Self::Error> {
+        Self::try_from(point.0)
+    }
+}
+
+impl From<&Rectangle> for Rect {
+    fn from(rectangle: &Rectangle) -> Self {
+        Self::new(rectangle.lower_left, rectangle.upper_right)
+    }
+}
+
+impl From<&Polygon> for geo::Polygon {
+    fn from(polygon: &Polygon) -> Self {
+        Self::new(ring(&polygon.corners), vec![])
+    }
+}
+
+impl From<&Area> for geo::Polygon {
+    /// Gets the polygon of the area boundary (circles are approximated by a regular polygon).
+    fn from(area: &Area) -> Self {
+        Self::new(ring(&area.boundary()), vec![])
+    }
+}
+
+impl<EdgeId: Copy> LineLocation<EdgeId> {
+    /// Gets the line string of the location, starting and ending at the offsets.
+    /// The geometry of the edges is approximated by the segments between their vertices.
+    pub fn to_line_string<G>(&self, graph: &G) -> Result<LineString, G::Error>
+    where
+        G: DirectedGraph<EdgeId = EdgeId>,
+    {
+        let coordinates =
+            get_path_coordinates(graph, &self.path, self.pos_offset, self.neg_offset)?;
+        Ok(coordinates.into_iter().map(Coord::from).collect())
+    }
+}
+
+impl<EdgeId: Copy> Location<EdgeId> {
+    /// Gets the geometry of the location from its coordinates (see [`Location::coordinates`]): a
+    /// Point for point locations, a LineString for line locations and a Polygon for area
+    /// locations.
+    pub fn to_geometry<G>(&self, graph: &G) -> Result<Geometry, G::Error>
+    where
+        G: DirectedGraph<EdgeId = EdgeId>,
+    {
+        let coordinates = self.coordinates(graph)?;
+
+        let geometry = match self {
+            Self::GeoCoordinate(_) | Self::PointAlongLine(_) | Self::Poi(_) => {
+                match coordinates.first() {
+                    Some(&coordinate) => Geometry::Point(coordinate.into()),
+                    None => Geometry::GeometryCollection(GeometryCollection::default()),
+                }
+            }
+            Self::Line(_) | Self::ClosedLine(_) => {
+                Geometry::LineString(coordinates.into_iter().map(Coord::from).collect())
+            }
+            Self::Area(_) => Geometry::Polygon(geo::Polygon::new(ring(&coordinates), vec![])),
+        };
+
+        Ok(geometry)
+    }
+}
+
+/// Gets the ring of the corners (closed by the polygon).
+fn ring(corners: &[Coordinate]) -> LineString {
+    corners.iter().copied().map(Coord::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+    use crate::{Circle, Length};
+
+    #[test]
+    fn geo_coordinate_conversions() {
+        let coordinate = Coordinate { lon: 1.0, lat: 2.0 };
+
+        let point = Point::from(coordinate);
+        assert_eq!(point, Point::new(1.0, 2.0));
+        assert_eq!(Coordinate::try_from(point), Ok(coordinate));
+
+        assert!(Coordinate::try_from(Point::new(200.0, 2.0)).is_err());
+        assert!(Coordinate::try_from(Coord { x: 1.0, y: 95.0 }).is_err());
+    }
+
+    #[test]
+    fn geo_area_polygon() {
+        let rectangle = Rectangle {
+            lower_left: Coordinate { lon: 0.0, lat: 0.0 },
+            upper_right: Coordinate { lon: 1.0, lat: 2.0 },
+        };
+
+        let rect = Rect::from(&rectangle);
+        assert_eq!(rect.min(), Coord { x: 0.0, y: 0.0 });
+        assert_eq!(rect.max(), Coord { x: 1.0, y: 2.0 });
+
+        let polygon = geo::Polygon::from(&Area::Rectangle(rectangle));
+        assert_eq!(polygon.exterior().0.len(), 5);
+        assert!(
+            rectangle
+                .corners()
+                .into_iter()
+                .all(|corner| polygon.exterior().0.contains(&corner.into()))
+        );
+
+        let circle = Area::Circle(Circle {
+            center: Coordinate { lon: 0.0, lat: 0.0 },
+            radius: Length::from_meters(100.0),
+        });
+        let polygon = geo::Polygon::from(&circle);
+        assert!(polygon.exterior().is_closed());
+        assert_eq!(polygon.exterior().0.len(), circle.boundary().len() + 1);
+    }
+
+    #[test]
+    fn geo_line_location_line_string() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let line = LineLocation {
+            path: vec![EdgeId(8717174), EdgeId(8717175)],
+            pos_offset: Length::from_meters(10.0),
+            neg_offset: Length::from_meters(5.0),
+        };
+
+        let line_string = line.to_line_string(graph).unwrap();
+        let location = Location::Line(line);
+        let coordinates = location.coordinates(graph).unwrap();
+
+        assert_eq!(line_string.0.len(), 3);
+        assert_eq!(
+            line_string,
+            coordinates.into_iter().map(Coord::from).collect()
+        );
+        assert_eq!(
+            location.to_geometry(graph).unwrap(),
+            Geometry::LineString(line_string)
+        );
+    }
+}
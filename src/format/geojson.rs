@@ -0,0 +1,239 @@
+//! GeoJSON representation of the location references and of the decoded locations.
+//!
+//! Useful to debug and visualize how a location reference matches the map, for example by loading
+//! the feature collections of both the location reference and of the decoded location in a GIS.
+
+use serde_json::{Value, json};
+
+use crate::{Coordinate, DirectedGraph, Location, LocationGeometry, LocationReference, Point};
+
+/// Gets the GeoJSON FeatureCollection of the location reference: a Point feature for each LRP
+/// (with its attributes as properties) followed by the feature of the location geometry,
+/// approximated from the coordinates of the location reference without a map.
+pub fn location_reference_geojson(location: &LocationReference) -> Value {
+    let mut features: Vec<_> = location
+        .points()
+        .enumerate()
+        .map(|(index, point)| lrp_feature(index, point))
+        .collect();
+
+    let geometry = match location.approximate_geometry() {
+        LocationGeometry::Point(coordinate) => point_geometry(&coordinate),
+        LocationGeometry::LineString(coordinates) => line_string_geometry(&coordinates),
+        LocationGeometry::Polygon(mut ring) => {
+            ring.extend(ring.first().copied());
+            let ring: Vec<_> = ring.iter().map(position).collect();
+            json!({ "type": "Polygon", "coordinates": [ring] })
+        }
+    };
+
+    features.push(json!({
+        "type": "Feature",
+        "geometry": geometry,
+        "properties": { "role": "location" },
+    }));
+
+    json!({ "type": "FeatureCollection", "features": features })
+}
+
+/// Gets the GeoJSON FeatureCollection of the decoded location: the feature of the location
+/// geometry (see [`Location::coordinates_3d`]) followed by a LineString feature for each edge of
+/// the location path (or of the edges covered by an area), in the same order of the location.
+pub fn location_geojson<G: DirectedGraph>(
+    graph: &G,
+    location: &Location<G::EdgeId>,
+) -> Result<Value, G::Error> {
+    let mut features = vec![json!({
+        "type": "Feature",
+        "geometry": location_geometry(graph, location)?,
+        "properties": { "role": "location" },
+    })];
+
+    let edges: &[G::EdgeId] = match location {
+        Location::Line(line) => &line.path,
+        Location::ClosedLine(line) => &line.path,
+        Location::PointAlongLine(point) => &point.path,
+        Location::Poi(poi) => &poi.point.path,
+        Location::Area(area) => &area.edges,
+        Location::GeoCoordinate(_) => &[],
+    };
+
+    for (index, &edge) in edges.iter().enumerate() {
+        let start = graph.get_vertex_coordinate(graph.get_edge_start_vertex(edge)?)?;
+        let end = graph.get_vertex_coordinate(graph.get_edge_end_vertex(edge)?)?;
+
+        features.push(json!({
+            "type": "Feature",
+            "geometry": line_string_geometry(&[start, end]),
+            "properties": {
+                "role": "edge",
+                "index": index,
+                "length": graph.get_edge_length(edge)?.meters(),
+                "frc": graph.get_edge_frc(edge)?.value(),
+                "fow": graph.get_edge_fow(edge)?.value(),
+            },
+        }));
+    }
+
+    Ok(json!({ "type": "FeatureCollection", "features": features }))
+}
+
+/// Gets the GeoJSON geometry of the location: a Point for point locations, a LineString for line
+/// locations and a Polygon for area locations. Positions include the elevation if the graph has
+/// elevation data.
+pub(crate) fn location_geometry<G: DirectedGraph>(
+    graph: &G,
+    location: &Location<G::EdgeId>,
+) -> Result<Value, G::Error> {
+    let mut positions = location
+        .coordinates_3d(graph)?
+        .into_iter()
+        .map(|c| match c.elevation {
+            Some(elevation) => json!([c.lon, c.lat, elevation]),
+            None => json!([c.lon, c.lat]),
+        });
+
+    let geometry = match location {
+        Location::GeoCoordinate(_) | Location::PointAlongLine(_) | Location::Poi(_) => {
+            json!({ "type": "Point", "coordinates": positions.next() })
+        }
+        Location::Line(_) | Location::ClosedLine(_) => {
+            json!({ "type": "LineString", "coordinates": positions.collect::<Vec<_>>() })
+        }
+        Location::Area(_) => {
+            let mut ring: Vec<_> = positions.collect();
+            ring.extend(ring.first().cloned());
+            json!({ "type": "Polygon", "coordinates": [ring] })
+        }
+    };
+
+    Ok(geometry)
+}
+
+fn lrp_feature(index: usize, point: &Point) -> Value {
+    let mut properties = json!({
+        "role": "lrp",
+        "index": index,
+        "frc": point.line.frc.value(),
+        "fow": point.line.fow.value(),
+        "bearing": point.line.bearing.degrees(),
+    });
+
+    if let (Some(path), Some(properties)) = (point.path, properties.as_object_mut()) {
+        properties.insert("lfrcnp".into(), path.lfrcnp.value().into());
+        properties.insert("dnp".into(), path.dnp.meters().into());
+    }
+
+    json!({
+        "type": "Feature",
+        "geometry": point_geometry(&point.coordinate),
+        "properties": properties,
+    })
+}
+
+fn point_geometry(coordinate: &Coordinate) -> Value {
+    json!({ "type": "Point", "coordinates": position(coordinate) })
+}
+
+fn line_string_geometry(coordinates: &[Coordinate]) -> Value {
+    let positions: Vec<_> = coordinates.iter().map(position).collect();
+    json!({ "type": "LineString", "coordinates": positions })
+}
+
+fn position(coordinate: &Coordinate) -> Value {
+    json!([coordinate.lon, coordinate.lat])
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{NETWORK_GRAPH, NetworkGraph};
+    use crate::{Circle, DecoderConfig, Length, decode_base64_openlr, deserialize_base64_openlr};
+
+    #[test]
+    fn geojson_location_reference_001() {
+        let location = deserialize_base64_openlr("CwmTaSVYpTPZCP4a/5UjYQUH").unwrap();
+        let geojson = location_reference_geojson(&location);
+
+        assert_eq!(geojson["type"], "FeatureCollection");
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), location.points().len() + 1);
+
+        let lrps = &features[..features.len() - 1];
+        for (feature, point) in lrps.iter().zip(location.points()) {
+            assert_eq!(feature["properties"]["role"], "lrp");
+            assert_eq!(feature["geometry"]["type"], "Point");
+            assert_eq!(
+                feature["geometry"]["coordinates"],
+                json!([point.coordinate.lon, point.coordinate.lat])
+            );
+            assert_eq!(
+                feature["properties"]["dnp"].as_f64(),
+                point.path.map(|path| path.dnp.meters())
+            );
+        }
+
+        let feature = &features[features.len() - 1];
+        assert_eq!(feature["properties"]["role"], "location");
+        assert_eq!(feature["geometry"]["type"], "LineString");
+    }
+
+    #[test]
+    fn geojson_location_reference_002() {
+        let location = LocationReference::Circle(Circle {
+            center: Coordinate {
+                lon: 13.0,
+                lat: 52.0,
+            },
+            radius: Length::from_meters(100.0),
+        });
+
+        let geojson = location_reference_geojson(&location);
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 1);
+
+        let geometry = &features[0]["geometry"];
+        assert_eq!(geometry["type"], "Polygon");
+
+        let ring = geometry["coordinates"][0].as_array().unwrap();
+        assert!(ring.len() > 3);
+        assert_eq!(ring.first(), ring.last());
+    }
+
+    #[test]
+    fn geojson_location_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let location =
+            decode_base64_openlr(&DecoderConfig::default(), graph, "CwmTaSVYpTPZCP4a/5UjYQUH")
+                .unwrap();
+
+        let Location::Line(line) = &location else {
+            unreachable!()
+        };
+
+        let geojson = location_geojson(graph, &location).unwrap();
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), line.path.len() + 1);
+        assert_eq!(features[0]["properties"]["role"], "location");
+        assert_eq!(features[0]["geometry"]["type"], "LineString");
+
+        let edges = &features[1..];
+        for (index, (feature, &edge)) in edges.iter().zip(&line.path).enumerate() {
+            assert_eq!(feature["properties"]["role"], "edge");
+            assert_eq!(feature["properties"]["index"], index);
+            assert_eq!(
+                feature["properties"]["length"].as_f64(),
+                Some(graph.get_edge_length(edge).unwrap().meters())
+            );
+        }
+
+        for window in edges.windows(2) {
+            let end = &window[0]["geometry"]["coordinates"][1];
+            let start = &window[1]["geometry"]["coordinates"][0];
+            assert_eq!(end, start);
+        }
+    }
+}
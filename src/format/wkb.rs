@@ -0,0 +1,338 @@
+//! Well-Known Binary (WKB) and Well-Known Text (WKT) representations of the decoded locations
+//! geometry.
+//!
+//! Also provides the PostGIS Extended WKB (EWKB) geometry, and rows in the text format of the
+//! PostgreSQL `COPY` command to bulk load decoded locations.
+
+use std::fmt::Write;
+
+use crate::{Coordinate, DirectedGraph, Location};
+
+const LITTLE_ENDIAN: u8 = 1;
+const WKB_POINT: u32 = 1;
+const WKB_LINE_STRING: u32 = 2;
+const WKB_POLYGON: u32 = 3;
+const EWKB_SRID_FLAG: u32 = 0x20000000;
+
+/// Spatial reference system of the coordinates: WGS84.
+pub const SRID_WGS84: u32 = 4326;
+
+/// Gets the (little endian) WKB geometry of the location from its coordinates: a Point for point
+/// locations, a LineString for line locations and a Polygon for area locations.
+pub fn location_wkb<EdgeId>(location: &Location<EdgeId>, coordinates: &[Coordinate]) -> Vec<u8> {
+    write_wkb(location, coordinates, None)
+}
+
+/// Gets the (little endian) PostGIS EWKB geometry of the location from its coordinates, with the
+/// WGS84 SRID (4326).
+pub fn location_ewkb<EdgeId>(location: &Location<EdgeId>, coordinates: &[Coordinate]) -> Vec<u8> {
+    write_wkb(location, coordinates, Some(SRID_WGS84))
+}
+
+/// Gets the WKT geometry of the location from its coordinates: a Point for point locations, a
+/// LineString for line locations and a Polygon for area locations.
+pub fn location_wkt<EdgeId>(location: &Location<EdgeId>, coordinates: &[Coordinate]) -> String {
+    let mut wkt = String::new();
+
+    match location {
+        Location::GeoCoordinate(_) | Location::PointAlongLine(_) | Location::Poi(_) => {
+            if let Some(Coordinate { lon, lat }) = coordinates.first() {
+                let _ = write!(wkt, "POINT({lon} {lat})");
+            } else {
+                wkt.push_str("POINT EMPTY");
+            }
+        }
+        Location::Line(_) | Location::ClosedLine(_) => {
+            wkt.push_str("LINESTRING(");
+            write_wkt_coordinates(&mut wkt, coordinates);
+            wkt.push(')');
+        }
+        Location::Area(_) => {
+            // a single exterior ring, closed by repeating its first coordinate
+            wkt.push_str("POLYGON((");
+            write_wkt_coordinates(&mut wkt, coordinates.iter().chain(coordinates.first()));
+            wkt.push_str("))");
+        }
+    }
+
+    wkt
+}
+
+impl<EdgeId: Copy> Location<EdgeId> {
+    /// Gets the (little endian) WKB geometry of the location (see [`location_wkb`]).
+    pub fn to_wkb<G>(&self, graph: &G) -> Result<Vec<u8>, G::Error>
+    where
+        G: DirectedGraph<EdgeId = EdgeId>,
+    {
+        Ok(location_wkb(self, &self.coordinates(graph)?))
+    }
+
+    /// Gets the WKT geometry of the location (see [`location_wkt`]).
+    pub fn to_wkt<G>(&self, graph: &G) -> Result<String, G::Error>
+    where
+        G: DirectedGraph<EdgeId = EdgeId>,
+    {
+        Ok(location_wkt(self, &self.coordinates(graph)?))
+    }
+}
+
+/// Gets a row in the text format of the PostgreSQL `COPY` command with two columns: the edge IDs
+/// of the location (as array) and its EWKB geometry (as hex string). The row ends with a newline.
+pub fn location_copy_row<G>(graph: &G, location: &Location<G::EdgeId>) -> Result<String, G::Error>
+where
+    G: DirectedGraph,
+    G::EdgeId: Into<i64>,
+{
+    let coordinates = location.coordinates(graph)?;
+    let geometry = to_hex(&location_ewkb(location, &coordinates));
+
+    let path = match location {
+        Location::Line(line) => &line.path[..],
+        Location::GeoCoordinate(_) => &[],
+        Location::PointAlongLine(point) => &point.path,
+        Location::Poi(poi) => &poi.point.path,
+        Location::ClosedLine(line) => &line.path,
+        Location::Area(area) => &area.edges,
+    };
+
+    let edges: Vec<String> = path.iter().map(|&e| e.into().to_string()).collect();
+    let edges = format!("{{{}}}", edges.join(","));
+
+    Ok(copy_row([Some(edges.as_str()), Some(geometry.as_str())]))
+}
+
+/// Gets a row in the text format of the PostgreSQL `COPY` command: values are tab separated and
+/// escaped, null values are written as `\N`. The row ends with a newline.
+pub fn copy_row<'a>(values: impl IntoIterator<Item = Option<&'a str>>) -> String {
+    let mut row = String::new();
+
+    for (i, value) in values.into_iter().enumerate() {
+        if i > 0 {
+            row.push('\t');
+        }
+
+        let Some(value) = value else {
+            row.push_str("\\N");
+            continue;
+        };
+
+        for c in value.chars() {
+            match c {
+                '\\' => row.push_str("\\\\"),
+                '\t' => row.push_str("\\t"),
+                '\n' => row.push_str("\\n"),
+                '\r' => row.push_str("\\r"),
+                c => row.push(c),
+            }
+        }
+    }
+
+    row.push('\n');
+    row
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(2 * bytes.len()), |mut hex, b| {
+            let _ = write!(hex, "{b:02X}");
+            hex
+        })
+}
+
+fn write_wkb<EdgeId>(
+    location: &Location<EdgeId>,
+    coordinates: &[Coordinate],
+    srid: Option<u32>,
+) -> Vec<u8> {
+    let mut wkb = Vec::with_capacity(13 + 16 * coordinates.len());
+    wkb.push(LITTLE_ENDIAN);
+
+    let write_type = |wkb: &mut Vec<u8>, geometry_type: u32| {
+        if let Some(srid) = srid {
+            wkb.extend((geometry_type | EWKB_SRID_FLAG).to_le_bytes());
+            wkb.extend(srid.to_le_bytes());
+        } else {
+            wkb.extend(geometry_type.to_le_bytes());
+        }
+    };
+
+    match location {
+        Location::GeoCoordinate(_) | Location::PointAlongLine(_) | Location::Poi(_) => {
+            write_type(&mut wkb, WKB_POINT);
+            let coordinate = coordinates.first().copied().unwrap_or(Coordinate {
+                lon: f64::NAN,
+                lat: f64::NAN,
+            });
+            write_coordinate(&mut wkb, coordinate);
+        }
+        Location::Line(_) | Location::ClosedLine(_) => {
+            write_type(&mut wkb, WKB_LINE_STRING);
+            wkb.extend((coordinates.len() as u32).to_le_bytes());
+            for &coordinate in coordinates {
+                write_coordinate(&mut wkb, coordinate);
+            }
+        }
+        Location::Area(_) => {
+            // a single exterior ring, closed by repeating its first coordinate
+            let ring = coordinates.iter().chain(coordinates.first());
+            write_type(&mut wkb, WKB_POLYGON);
+            wkb.extend(1u32.to_le_bytes());
+            wkb.extend((ring.clone().count() as u32).to_le_bytes());
+            for &coordinate in ring {
+                write_coordinate(&mut wkb, coordinate);
+            }
+        }
+    }
+
+    wkb
+}
+
+fn write_coordinate(wkb: &mut Vec<u8>, Coordinate { lon, lat }: Coordinate) {
+    wkb.extend(lon.to_le_bytes());
+    wkb.extend(lat.to_le_bytes());
+}
+
+fn write_wkt_coordinates<'a>(
+    wkt: &mut String,
+    coordinates: impl IntoIterator<Item = &'a Coordinate>,
+) {
+    for (i, Coordinate { lon, lat }) in coordinates.into_iter().enumerate() {
+        if i > 0 {
+            wkt.push(',');
+        }
+        let _ = write!(wkt, "{lon} {lat}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+    use crate::{Area, AreaLocation, Length, LineLocation, Polygon};
+
+    #[test]
+    fn wkb_line_string_001() {
+        let location: Location<i64> = Location::Line(LineLocation {
+            path: vec![],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        });
+        let coordinates = [
+            Coordinate { lon: 1.0, lat: 2.0 },
+            Coordinate { lon: 3.0, lat: 4.0 },
+        ];
+
+        let wkb = location_wkb(&location, &coordinates);
+
+        assert_eq!(wkb.len(), 1 + 4 + 4 + 2 * 16);
+        assert_eq!(&wkb[..9], &[1, 2, 0, 0, 0, 2, 0, 0, 0]);
+        assert_eq!(&wkb[9..17], &1.0f64.to_le_bytes());
+        assert_eq!(&wkb[33..41], &4.0f64.to_le_bytes());
+    }
+
+    #[test]
+    fn wkb_point_001() {
+        let coordinate = Coordinate { lon: 1.0, lat: 2.0 };
+        let wkb = location_wkb(&Location::<i64>::GeoCoordinate(coordinate), &[coordinate]);
+
+        assert_eq!(wkb.len(), 1 + 4 + 16);
+        assert_eq!(&wkb[..5], &[1, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn ewkb_point_001() {
+        let coordinate = Coordinate { lon: 1.0, lat: 2.0 };
+        let ewkb = location_ewkb(&Location::<i64>::GeoCoordinate(coordinate), &[coordinate]);
+
+        assert_eq!(ewkb.len(), 1 + 4 + 4 + 16);
+        assert_eq!(&ewkb[..9], &[1, 1, 0, 0, 0x20, 0xE6, 0x10, 0, 0]);
+        assert_eq!(
+            to_hex(&ewkb),
+            "0101000020E6100000000000000000F03F0000000000000040"
+        );
+    }
+
+    #[test]
+    fn wkt_001() {
+        let coordinates = [
+            Coordinate { lon: 1.0, lat: 2.0 },
+            Coordinate { lon: 3.5, lat: 4.0 },
+            Coordinate { lon: 3.5, lat: 2.0 },
+        ];
+
+        let point = Location::<i64>::GeoCoordinate(coordinates[0]);
+        assert_eq!(location_wkt(&point, &coordinates[..1]), "POINT(1 2)");
+        assert_eq!(location_wkt(&point, &[]), "POINT EMPTY");
+
+        let line: Location<i64> = Location::Line(LineLocation {
+            path: vec![],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        });
+        assert_eq!(
+            location_wkt(&line, &coordinates),
+            "LINESTRING(1 2,3.5 4,3.5 2)"
+        );
+
+        let area = Location::<i64>::Area(AreaLocation {
+            area: Area::Polygon(Polygon {
+                corners: coordinates.to_vec(),
+            }),
+            edges: vec![],
+        });
+        assert_eq!(
+            location_wkt(&area, &coordinates),
+            "POLYGON((1 2,3.5 4,3.5 2,1 2))"
+        );
+    }
+
+    #[test]
+    fn location_wkt_wkb_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let location = Location::Line(LineLocation {
+            path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        });
+        let coordinates = location.coordinates(graph).unwrap();
+
+        assert_eq!(
+            location.to_wkb(graph).unwrap(),
+            location_wkb(&location, &coordinates)
+        );
+
+        let wkt = location.to_wkt(graph).unwrap();
+        assert!(wkt.starts_with("LINESTRING("));
+        assert_eq!(wkt.matches(',').count(), coordinates.len() - 1);
+    }
+
+    #[test]
+    fn copy_row_001() {
+        assert_eq!(
+            copy_row([Some("a\tb"), None, Some("c\\d\ne")]),
+            "a\\tb\t\\N\tc\\\\d\\ne\n"
+        );
+    }
+
+    #[test]
+    fn location_copy_row_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let location = Location::Line(LineLocation {
+            path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        });
+
+        let row = location_copy_row(graph, &location).unwrap();
+        let (edges, geometry) = row.trim_end().split_once('\t').unwrap();
+
+        assert_eq!(edges, "{8717174,8717175,109783}");
+        assert!(geometry.starts_with("0102000020E6100000"));
+        assert_eq!(geometry.len(), 2 * (1 + 4 + 4 + 4 + 4 * 16));
+    }
+}
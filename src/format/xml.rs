@@ -0,0 +1,825 @@
+//! XML physical format of the OpenLR Location References (OpenLR XML v1.5).
+//!
+//! Unlike the binary format, the XML format stores the offsets as absolute distances (in meters)
+//! from the start and to the end of the location reference path, they are converted into the
+//! relative offsets of the model using the distance to next point of the LRPs they refer to.
+
+use std::fmt::Write;
+
+use quick_xml::Reader;
+use quick_xml::escape::{escape, unescape};
+use quick_xml::events::Event;
+use strum::IntoEnumIterator;
+
+use crate::{
+    Bearing, Circle, ClosedLine, Coordinate, DeserializeError, Fow, Frc, Grid, GridSize, Length,
+    Line, LineAttributes, LocationReference, Offset, Offsets, Orientation, PathAttributes, Poi,
+    Point, PointAlongLine, Polygon, Rectangle, SerializeError, SideOfRoad,
+};
+
+const NAMESPACE: &str = "http://www.openlr.org/openlr";
+
+/// Serializes an OpenLR Location Reference into XML.
+pub fn serialize_xml_openlr(location: &LocationReference) -> Result<String, SerializeError> {
+    use LocationReference::*;
+
+    let reference = match location {
+        Line(line) => Element::new("LineLocationReference").with_children(write_line(line)?),
+        GeoCoordinate(coordinate) => Element::new("PointLocationReference").with_child(
+            Element::new("GeoCoordinate").with_child(write_coordinate("Coordinates", coordinate)?),
+        ),
+        PointAlongLine(point) => Element::new("PointLocationReference").with_child(
+            Element::new("PointAlongLine").with_children(write_point_along_line(point)?),
+        ),
+        Poi(poi) => Element::new("PointLocationReference").with_child(write_poi(poi)?),
+        Circle(circle) => Element::new("AreaLocationReference").with_child(write_circle(circle)?),
+        Rectangle(rectangle) => Element::new("AreaLocationReference").with_child(
+            Element::new("RectangleLocationReference").with_child(write_rectangle(rectangle)?),
+        ),
+        Grid(grid) => Element::new("AreaLocationReference").with_child(write_grid(grid)?),
+        Polygon(polygon) => {
+            Element::new("AreaLocationReference").with_child(write_polygon(polygon)?)
+        }
+        ClosedLine(line) => {
+            Element::new("AreaLocationReference").with_child(write_closed_line(line)?)
+        }
+    };
+
+    let root = Element::new("OpenLR")
+        .with_child(Element::new("XMLLocationReference").with_child(reference));
+
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    root.write(&mut xml, 0, Some(NAMESPACE));
+    Ok(xml)
+}
+
+/// Deserializes an XML representation of an OpenLR Location Reference.
+pub fn deserialize_xml_openlr(xml: &str) -> Result<LocationReference, DeserializeError> {
+    let root = Element::parse_document(xml)?;
+    if root.name != "OpenLR" {
+        return Err(DeserializeError::MissingXmlElement("OpenLR"));
+    }
+
+    let reference = root.child("XMLLocationReference")?;
+
+    if let Some(line) = reference.find("LineLocationReference") {
+        return Ok(LocationReference::Line(read_line(line)?));
+    }
+
+    if let Some(point) = reference.find("PointLocationReference") {
+        if let Some(coordinate) = point.find("GeoCoordinate") {
+            let coordinate = read_coordinate(coordinate.child("Coordinates")?)?;
+            return Ok(LocationReference::GeoCoordinate(coordinate));
+        } else if let Some(point) = point.find("PointAlongLine") {
+            let point = read_point_along_line(point)?;
+            return Ok(LocationReference::PointAlongLine(point));
+        } else if let Some(poi) = point.find("PoiWithAccessPoint") {
+            return Ok(LocationReference::Poi(read_poi(poi)?));
+        }
+
+        return Err(DeserializeError::MissingXmlElement("PointAlongLine"));
+    }
+
+    let area = reference.child("AreaLocationReference")?;
+
+    if let Some(circle) = area.find("CircleLocationReference") {
+        Ok(LocationReference::Circle(read_circle(circle)?))
+    } else if let Some(rectangle) = area.find("RectangleLocationReference") {
+        let rectangle = read_rectangle(rectangle.child("Rectangle")?)?;
+        Ok(LocationReference::Rectangle(rectangle))
+    } else if let Some(grid) = area.find("GridLocationReference") {
+        Ok(LocationReference::Grid(read_grid(grid)?))
+    } else if let Some(polygon) = area.find("PolygonLocationReference") {
+        Ok(LocationReference::Polygon(read_polygon(polygon)?))
+    } else if let Some(line) = area.find("ClosedLineLocationReference") {
+        Ok(LocationReference::ClosedLine(read_closed_line(line)?))
+    } else {
+        Err(DeserializeError::MissingXmlElement(
+            "CircleLocationReference",
+        ))
+    }
+}
+
+fn write_line(line: &Line) -> Result<Vec<Element>, SerializeError> {
+    let Line { points, offsets } = line;
+    let (last_point, points) = match points.split_last() {
+        Some((last_point, points)) if !points.is_empty() => (last_point, points),
+        _ => return Err(SerializeError::InvalidLine),
+    };
+
+    let mut elements = points
+        .iter()
+        .map(|point| write_point("LocationReferencePoint", point))
+        .collect::<Result<Vec<_>, _>>()?;
+    elements.push(write_point("LastLocationReferencePoint", last_point)?);
+
+    // offsets are relative to the first and last (but one) LRP distance to next point
+    let pos_dnp = points.first().map(Point::dnp).unwrap_or(Length::ZERO);
+    let neg_dnp = points.last().map(Point::dnp).unwrap_or(Length::ZERO);
+    elements.extend(write_offsets(offsets, pos_dnp, neg_dnp)?);
+
+    Ok(elements)
+}
+
+fn read_line(element: &Element) -> Result<Line, DeserializeError> {
+    let mut points = element
+        .children("LocationReferencePoint")
+        .map(read_point)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if points.is_empty() {
+        return Err(DeserializeError::MissingXmlElement(
+            "LocationReferencePoint",
+        ));
+    }
+
+    let pos_dnp = points.first().map(Point::dnp).unwrap_or(Length::ZERO);
+    let neg_dnp = points.last().map(Point::dnp).unwrap_or(Length::ZERO);
+    let offsets = read_offsets(element, pos_dnp, neg_dnp)?;
+
+    points.push(read_point(element.child("LastLocationReferencePoint")?)?);
+
+    Ok(Line { points, offsets })
+}
+
+fn write_point_along_line(point: &PointAlongLine) -> Result<Vec<Element>, SerializeError> {
+    let PointAlongLine {
+        points: [first_point, last_point],
+        offset,
+        orientation,
+        side,
+    } = point;
+
+    let mut elements = vec![
+        write_point("LocationReferencePoint", first_point)?,
+        write_point("LastLocationReferencePoint", last_point)?,
+    ];
+
+    let offsets = Offsets::positive(*offset);
+    elements.extend(write_offsets(&offsets, first_point.dnp(), Length::ZERO)?);
+    elements.push(Element::value("SideOfRoad", side_of_road_name(*side)));
+    elements.push(Element::value(
+        "Orientation",
+        orientation_name(*orientation),
+    ));
+
+    Ok(elements)
+}
+
+fn read_point_along_line(element: &Element) -> Result<PointAlongLine, DeserializeError> {
+    let first_point = read_point(element.child("LocationReferencePoint")?)?;
+    let last_point = read_point(element.child("LastLocationReferencePoint")?)?;
+    let offsets = read_offsets(element, first_point.dnp(), Length::ZERO)?;
+
+    let side = match element.find("SideOfRoad") {
+        Some(side) => parse_side_of_road(side.text())?,
+        None => SideOfRoad::default(),
+    };
+
+    let orientation = match element.find("Orientation") {
+        Some(orientation) => parse_orientation(orientation.text())?,
+        None => Orientation::default(),
+    };
+
+    Ok(PointAlongLine {
+        points: [first_point, last_point],
+        offset: offsets.pos,
+        orientation,
+        side,
+    })
+}
+
+fn write_poi(poi: &Poi) -> Result<Element, SerializeError> {
+    let Poi { point, coordinate } = poi;
+
+    Ok(Element::new("PoiWithAccessPoint")
+        .with_children(write_point_along_line(point)?)
+        .with_child(write_coordinate("Coordinates", coordinate)?))
+}
+
+fn read_poi(element: &Element) -> Result<Poi, DeserializeError> {
+    Ok(Poi {
+        point: read_point_along_line(element)?,
+        coordinate: read_coordinate(element.child("Coordinates")?)?,
+    })
+}
+
+fn write_circle(circle: &Circle) -> Result<Element, SerializeError> {
+    let Circle { center, radius } = circle;
+
+    Ok(Element::new("CircleLocationReference")
+        .with_child(
+            Element::new("GeoCoordinate").with_child(write_coordinate("Coordinates", center)?),
+        )
+        .with_child(Element::value("Radius", write_meters(*radius)?)))
+}
+
+fn read_circle(element: &Element) -> Result<Circle, DeserializeError> {
+    let center = element.child("GeoCoordinate")?.child("Coordinates")?;
+
+    Ok(Circle {
+        center: read_coordinate(center)?,
+        radius: read_meters(element.child("Radius")?)?,
+    })
+}
+
+fn write_rectangle(rectangle: &Rectangle) -> Result<Element, SerializeError> {
+    let Rectangle {
+        lower_left,
+        upper_right,
+    } = rectangle;
+
+    if lower_left == upper_right {
+        return Err(SerializeError::InvalidRectangle(*rectangle));
+    }
+
+    Ok(Element::new("Rectangle")
+        .with_child(write_coordinate("LowerLeft", lower_left)?)
+        .with_child(write_coordinate("UpperRight", upper_right)?))
+}
+
+fn read_rectangle(element: &Element) -> Result<Rectangle, DeserializeError> {
+    Ok(Rectangle {
+        lower_left: read_coordinate(element.child("LowerLeft")?)?,
+        upper_right: read_coordinate(element.child("UpperRight")?)?,
+    })
+}
+
+fn write_grid(grid: &Grid) -> Result<Element, SerializeError> {
+    let Grid { rect, size } = grid;
+
+    if size.columns <= 1 && size.rows <= 1 {
+        return Err(SerializeError::InvalidGridSize(*size));
+    }
+
+    Ok(Element::new("GridLocationReference")
+        .with_child(write_rectangle(rect)?)
+        .with_child(Element::value("NumColumns", size.columns))
+        .with_child(Element::value("NumRows", size.rows)))
+}
+
+fn read_grid(element: &Element) -> Result<Grid, DeserializeError> {
+    Ok(Grid {
+        rect: read_rectangle(element.child("Rectangle")?)?,
+        size: GridSize {
+            columns: element.child("NumColumns")?.parse()?,
+            rows: element.child("NumRows")?.parse()?,
+        },
+    })
+}
+
+fn write_polygon(polygon: &Polygon) -> Result<Element, SerializeError> {
+    if polygon.corners.len() < 3 {
+        return Err(SerializeError::InvalidPolygon);
+    }
+
+    let corners = polygon
+        .corners
+        .iter()
+        .map(|corner| write_coordinate("Coordinates", corner))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Element::new("PolygonLocationReference")
+        .with_child(Element::new("PolygonCorners").with_children(corners)))
+}
+
+fn read_polygon(element: &Element) -> Result<Polygon, DeserializeError> {
+    let corners = element
+        .child("PolygonCorners")?
+        .children("Coordinates")
+        .map(read_coordinate)
+        .collect::<Result<_, _>>()?;
+
+    Ok(Polygon { corners })
+}
+
+fn write_closed_line(line: &ClosedLine) -> Result<Element, SerializeError> {
+    let ClosedLine { points, last_line } = line;
+    if points.len() < 2 {
+        return Err(SerializeError::InvalidLine);
+    }
+
+    let points = points
+        .iter()
+        .map(|point| write_point("LocationReferencePoint", point))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Element::new("ClosedLineLocationReference")
+        .with_children(points)
+        .with_child(Element::new("LastLine").with_child(write_line_attributes(last_line)?)))
+}
+
+fn read_closed_line(element: &Element) -> Result<ClosedLine, DeserializeError> {
+    let points = element
+        .children("LocationReferencePoint")
+        .map(read_point)
+        .collect::<Result<_, _>>()?;
+
+    let last_line = element.child("LastLine")?.child("LineAttributes")?;
+
+    Ok(ClosedLine {
+        points,
+        last_line: read_line_attributes(last_line)?,
+    })
+}
+
+fn write_point(name: &'static str, point: &Point) -> Result<Element, SerializeError> {
+    let Point {
+        coordinate,
+        line,
+        path,
+    } = point;
+
+    let mut element = Element::new(name)
+        .with_child(write_coordinate("Coordinates", coordinate)?)
+        .with_child(write_line_attributes(line)?);
+
+    if let Some(PathAttributes { lfrcnp, dnp }) = path {
+        element = element.with_child(
+            Element::new("PathAttributes")
+                .with_child(Element::value("LFRCNP", frc_name(*lfrcnp)))
+                .with_child(Element::value("DNP", write_meters(*dnp)?)),
+        );
+    }
+
+    Ok(element)
+}
+
+fn read_point(element: &Element) -> Result<Point, DeserializeError> {
+    let path = element
+        .find("PathAttributes")
+        .map(|path| {
+            Ok::<_, DeserializeError>(PathAttributes {
+                lfrcnp: parse_frc(path.child("LFRCNP")?.text())?,
+                dnp: read_meters(path.child("DNP")?)?,
+            })
+        })
+        .transpose()?;
+
+    Ok(Point {
+        coordinate: read_coordinate(element.child("Coordinates")?)?,
+        line: read_line_attributes(element.child("LineAttributes")?)?,
+        path,
+    })
+}
+
+fn write_line_attributes(line: &LineAttributes) -> Result<Element, SerializeError> {
+    let LineAttributes { frc, fow, bearing } = line;
+    if bearing.degrees() >= 360 {
+        return Err(SerializeError::InvalidBearing(*bearing));
+    }
+
+    Ok(Element::new("LineAttributes")
+        .with_child(Element::value("FRC", frc_name(*frc)))
+        .with_child(Element::value("FOW", fow_name(*fow)))
+        .with_child(Element::value("BEAR", bearing.degrees())))
+}
+
+fn read_line_attributes(element: &Element) -> Result<LineAttributes, DeserializeError> {
+    let bearing: u16 = element.child("BEAR")?.parse()?;
+    if bearing >= 360 {
+        return Err(DeserializeError::InvalidXml);
+    }
+
+    Ok(LineAttributes {
+        frc: parse_frc(element.child("FRC")?.text())?,
+        fow: parse_fow(element.child("FOW")?.text())?,
+        bearing: Bearing::from_degrees(bearing),
+    })
+}
+
+fn write_coordinate(
+    name: &'static str,
+    coordinate: &Coordinate,
+) -> Result<Element, SerializeError> {
+    let coordinate = Coordinate::new(coordinate.lon, coordinate.lat)?;
+
+    Ok(Element::new(name)
+        .with_child(Element::value("Longitude", coordinate.lon))
+        .with_child(Element::value("Latitude", coordinate.lat)))
+}
+
+fn read_coordinate(element: &Element) -> Result<Coordinate, DeserializeError> {
+    let lon = element.child("Longitude")?.parse()?;
+    let lat = element.child("Latitude")?.parse()?;
+    Ok(Coordinate::new(lon, lat)?)
+}
+
+fn write_offsets(
+    offsets: &Offsets,
+    pos_dnp: Length,
+    neg_dnp: Length,
+) -> Result<Option<Element>, SerializeError> {
+    let pos_offset = offsets.distance_from_start(pos_dnp);
+    let neg_offset = offsets.distance_to_end(neg_dnp);
+
+    let mut element = Element::new("Offsets");
+    if !pos_offset.round().is_zero() {
+        element = element.with_child(Element::value("PosOff", write_meters(pos_offset)?));
+    }
+    if !neg_offset.round().is_zero() {
+        element = element.with_child(Element::value("NegOff", write_meters(neg_offset)?));
+    }
+
+    Ok(Some(element).filter(|e| !e.children.is_empty()))
+}
+
+fn read_offsets(
+    element: &Element,
+    pos_dnp: Length,
+    neg_dnp: Length,
+) -> Result<Offsets, DeserializeError> {
+    let Some(offsets) = element.find("Offsets") else {
+        return Ok(Offsets::default());
+    };
+
+    let offset = |name, dnp| match offsets.find(name) {
+        Some(offset) => Ok::<_, DeserializeError>(Offset::relative(read_meters(offset)?, dnp)),
+        None => Ok(Offset::ZERO),
+    };
+
+    Ok(Offsets {
+        pos: offset("PosOff", pos_dnp)?,
+        neg: offset("NegOff", neg_dnp)?,
+    })
+}
+
+/// Lengths are always serialized as integer meters.
+fn write_meters(length: Length) -> Result<i64, SerializeError> {
+    let meters = length.round().meters();
+    if !meters.is_finite() || meters < 0.0 || meters > i64::MAX as f64 {
+        return Err(SerializeError::InvalidLength(length));
+    }
+
+    Ok(meters as i64)
+}
+
+fn read_meters(element: &Element) -> Result<Length, DeserializeError> {
+    let meters: u32 = element.parse()?;
+    Ok(Length::from_meters(meters as f64))
+}
+
+const fn frc_name(frc: Frc) -> &'static str {
+    match frc {
+        Frc::Frc0 => "FRC0",
+        Frc::Frc1 => "FRC1",
+        Frc::Frc2 => "FRC2",
+        Frc::Frc3 => "FRC3",
+        Frc::Frc4 => "FRC4",
+        Frc::Frc5 => "FRC5",
+        Frc::Frc6 => "FRC6",
+        Frc::Frc7 => "FRC7",
+    }
+}
+
+fn parse_frc(name: &str) -> Result<Frc, DeserializeError> {
+    Frc::iter()
+        .find(|&frc| frc_name(frc) == name)
+        .ok_or(DeserializeError::InvalidXml)
+}
+
+const fn fow_name(fow: Fow) -> &'static str {
+    match fow {
+        Fow::Undefined => "UNDEFINED",
+        Fow::Motorway => "MOTORWAY",
+        Fow::MultipleCarriageway => "MULTIPLE_CARRIAGEWAY",
+        Fow::SingleCarriageway => "SINGLE_CARRIAGEWAY",
+        Fow::Roundabout => "ROUNDABOUT",
+        Fow::TrafficSquare => "TRAFFICSQUARE",
+        Fow::SlipRoad => "SLIPROAD",
+        Fow::Other => "OTHER",
+    }
+}
+
+fn parse_fow(name: &str) -> Result<Fow, DeserializeError> {
+    Fow::iter()
+        .find(|&fow| fow_name(fow) == name)
+        .ok_or(DeserializeError::InvalidXml)
+}
+
+const fn side_of_road_name(side: SideOfRoad) -> &'static str {
+    match side {
+        SideOfRoad::OnRoadOrUnknown => "ON_ROAD_OR_UNKNOWN",
+        SideOfRoad::Right => "RIGHT",
+        SideOfRoad::Left => "LEFT",
+        SideOfRoad::Both => "BOTH",
+    }
+}
+
+fn parse_side_of_road(name: &str) -> Result<SideOfRoad, DeserializeError> {
+    [
+        SideOfRoad::OnRoadOrUnknown,
+        SideOfRoad::Right,
+        SideOfRoad::Left,
+        SideOfRoad::Both,
+    ]
+    .into_iter()
+    .find(|&side| side_of_road_name(side) == name)
+    .ok_or(DeserializeError::InvalidXml)
+}
+
+const fn orientation_name(orientation: Orientation) -> &'static str {
+    match orientation {
+        Orientation::Unknown => "NO_ORIENTATION_OR_UNKNOWN",
+        Orientation::Forward => "WITH_LINE_DIRECTION",
+        Orientation::Backward => "AGAINST_LINE_DIRECTION",
+        Orientation::Both => "BOTH",
+    }
+}
+
+fn parse_orientation(name: &str) -> Result<Orientation, DeserializeError> {
+    [
+        Orientation::Unknown,
+        Orientation::Forward,
+        Orientation::Backward,
+        Orientation::Both,
+    ]
+    .into_iter()
+    .find(|&orientation| orientation_name(orientation) == name)
+    .ok_or(DeserializeError::InvalidXml)
+}
+
+/// Minimal XML element tree, OpenLR XML documents don't make use of attributes (other than the
+/// namespace declaration) nor of mixed content.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Element {
+    name: String,
+    text: String,
+    children: Vec<Element>,
+}
+
+impl Element {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    fn value(name: &str, value: impl ToString) -> Self {
+        Self {
+            name: name.to_string(),
+            text: value.to_string(),
+            children: vec![],
+        }
+    }
+
+    fn with_child(mut self, child: Element) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    fn with_children(mut self, children: impl IntoIterator<Item = Element>) -> Self {
+        self.children.extend(children);
+        self
+    }
+
+    fn find(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn child(&self, name: &'static str) -> Result<&Element, DeserializeError> {
+        self.find(name)
+            .ok_or(DeserializeError::MissingXmlElement(name))
+    }
+
+    fn children(&self, name: &str) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    fn text(&self) -> &str {
+        self.text.trim()
+    }
+
+    fn parse<T: std::str::FromStr>(&self) -> Result<T, DeserializeError> {
+        self.text()
+            .parse()
+            .map_err(|_| DeserializeError::InvalidXml)
+    }
+
+    /// Parses the root element of the XML document, ignoring namespace prefixes.
+    fn parse_document(xml: &str) -> Result<Self, DeserializeError> {
+        let mut reader = Reader::from_str(xml);
+        let mut stack: Vec<Element> = vec![];
+
+        loop {
+            let event = reader
+                .read_event()
+                .map_err(|_| DeserializeError::InvalidXml)?;
+
+            match event {
+                Event::Start(start) => stack.push(Element::new(start.local_name().as_ref())),
+                Event::Empty(empty) => {
+                    let element = Element::new(empty.local_name().as_ref());
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element),
+                    }
+                }
+                Event::End(_) => {
+                    let element = stack.pop().ok_or(DeserializeError::InvalidXml)?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element),
+                    }
+                }
+                Event::Text(text) => {
+                    if let Some(element) = stack.last_mut() {
+                        let text = unescape(&text.into_inner())
+                            .map_err(|_| DeserializeError::InvalidXml)?
+                            .into_owned();
+                        element.text.push_str(&text);
+                    }
+                }
+                Event::CData(data) => {
+                    if let Some(element) = stack.last_mut() {
+                        element.text.push_str(&data.into_inner());
+                    }
+                }
+                Event::Eof | Event::GeneralRef(_) => return Err(DeserializeError::InvalidXml),
+                Event::Comment(_) | Event::Decl(_) | Event::PI(_) | Event::DocType(_) => {}
+            }
+        }
+    }
+
+    fn write(&self, xml: &mut String, depth: usize, namespace: Option<&str>) {
+        let indent = "  ".repeat(depth);
+        let _ = write!(xml, "\n{indent}<{}", self.name);
+        if let Some(namespace) = namespace {
+            let _ = write!(xml, r#" xmlns="{namespace}""#);
+        }
+
+        if self.children.is_empty() {
+            let _ = write!(xml, ">{}</{}>", escape(self.text.as_str()), self.name);
+            return;
+        }
+
+        xml.push('>');
+        for child in &self.children {
+            child.write(xml, depth + 1, None);
+        }
+        let _ = write!(xml, "\n{indent}</{}>", self.name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    fn point(lon: f64, lat: f64, dnp: Option<f64>) -> Point {
+        Point {
+            coordinate: Coordinate { lon, lat },
+            line: LineAttributes {
+                frc: Frc::Frc3,
+                fow: Fow::SingleCarriageway,
+                bearing: Bearing::from_degrees(141),
+            },
+            path: dnp.map(|dnp| PathAttributes {
+                lfrcnp: Frc::Frc5,
+                dnp: Length::from_meters(dnp),
+            }),
+        }
+    }
+
+    fn assert_round_trip(location: LocationReference) {
+        let xml = serialize_xml_openlr(&location).unwrap();
+        assert_eq!(deserialize_xml_openlr(&xml).unwrap(), location, "{xml}");
+    }
+
+    #[test]
+    fn openlr_xml_line_001() {
+        let line = LocationReference::Line(Line {
+            points: vec![
+                point(6.1268198, 49.6085178, Some(557.0)),
+                point(6.1283698, 49.6039878, Some(264.0)),
+                point(6.1281511, 49.6030303, None),
+            ],
+            offsets: Offsets {
+                pos: Offset::from_bucket(10),
+                neg: Offset::from_bucket(200),
+            },
+        });
+
+        let xml = serialize_xml_openlr(&line).unwrap();
+        assert!(xml.contains("<LineLocationReference>"));
+        assert!(xml.contains("<PosOff>23</PosOff>"));
+        assert!(xml.contains("<NegOff>207</NegOff>"));
+
+        assert_round_trip(line);
+    }
+
+    #[test]
+    fn openlr_xml_point_locations_001() {
+        assert_round_trip(LocationReference::GeoCoordinate(Coordinate {
+            lon: 5.1019064,
+            lat: 52.1055673,
+        }));
+
+        let point_along_line = PointAlongLine {
+            points: [
+                point(6.1268198, 49.6085178, Some(557.0)),
+                point(6.1283698, 49.6039878, None),
+            ],
+            offset: Offset::from_bucket(128),
+            orientation: Orientation::Backward,
+            side: SideOfRoad::Left,
+        };
+        assert_round_trip(LocationReference::PointAlongLine(point_along_line.clone()));
+
+        assert_round_trip(LocationReference::Poi(Poi {
+            point: point_along_line,
+            coordinate: Coordinate {
+                lon: 6.1275,
+                lat: 49.6066,
+            },
+        }));
+    }
+
+    #[test]
+    fn openlr_xml_area_locations_001() {
+        let rect = Rectangle {
+            lower_left: Coordinate {
+                lon: 5.0998,
+                lat: 52.1034,
+            },
+            upper_right: Coordinate {
+                lon: 5.1045,
+                lat: 52.1065,
+            },
+        };
+
+        assert_round_trip(LocationReference::Circle(Circle {
+            center: Coordinate {
+                lon: 5.1019064,
+                lat: 52.1055673,
+            },
+            radius: Length::from_meters(300.0),
+        }));
+        assert_round_trip(LocationReference::Rectangle(rect));
+        assert_round_trip(LocationReference::Grid(Grid {
+            rect,
+            size: GridSize {
+                columns: 3,
+                rows: 2,
+            },
+        }));
+        assert_round_trip(LocationReference::Polygon(Polygon {
+            corners: vec![
+                rect.lower_left,
+                rect.upper_right,
+                Coordinate {
+                    lon: 5.1,
+                    lat: 52.11,
+                },
+            ],
+        }));
+        assert_round_trip(LocationReference::ClosedLine(ClosedLine {
+            points: vec![
+                point(6.1268198, 49.6085178, Some(557.0)),
+                point(6.1283698, 49.6039878, Some(264.0)),
+            ],
+            last_line: LineAttributes {
+                frc: Frc::Frc2,
+                fow: Fow::Roundabout,
+                bearing: Bearing::from_degrees(11),
+            },
+        }));
+    }
+
+    #[test]
+    fn openlr_xml_deserialize_001() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <ns:OpenLR xmlns:ns="http://www.openlr.org/openlr">
+              <ns:LocationID>1</ns:LocationID>
+              <ns:XMLLocationReference>
+                <ns:PointLocationReference>
+                  <ns:GeoCoordinate>
+                    <ns:Coordinates>
+                      <ns:Longitude>13.4050</ns:Longitude>
+                      <ns:Latitude>52.5200</ns:Latitude>
+                    </ns:Coordinates>
+                  </ns:GeoCoordinate>
+                </ns:PointLocationReference>
+              </ns:XMLLocationReference>
+            </ns:OpenLR>"#;
+
+        assert_eq!(
+            deserialize_xml_openlr(xml).unwrap(),
+            LocationReference::GeoCoordinate(Coordinate {
+                lon: 13.405,
+                lat: 52.52
+            })
+        );
+
+        assert_eq!(
+            deserialize_xml_openlr("<OpenLR><XMLLocationReference/></OpenLR>"),
+            Err(DeserializeError::MissingXmlElement("AreaLocationReference"))
+        );
+        assert_eq!(
+            deserialize_xml_openlr("<OpenLR>"),
+            Err(DeserializeError::InvalidXml)
+        );
+    }
+}
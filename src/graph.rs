@@ -2,11 +2,17 @@ use std::error::Error;
 use std::fmt::Debug;
 use std::hash::Hash;
 
+use crate::graph::path::Path;
+use crate::routing::RouteCost;
 use crate::{Bearing, Coordinate, Fow, Frc, Length};
 
 /// Directed graph.
 /// Exposes the behavior of a Geospatial Index and of a Road Network Graph.
 /// Should be implemented by the graph the represents the map the decoder and encoder run on.
+///
+/// The nearest vertices and edges queries of the geospatial index can be delegated to an
+/// `RTreeIndex` built from the vertices coordinates and the edges geometry (with the
+/// `spatial-index` feature).
 pub trait DirectedGraph {
     /// Custom error associated type.
     type Error: Error;
@@ -27,6 +33,15 @@ pub trait DirectedGraph {
     /// Gets the total length of the directed edge.
     fn get_edge_length(&self, edge: Self::EdgeId) -> Result<Length, Self::Error>;
 
+    /// Gets the cost of traveling the whole directed edge, for example a travel time weighted
+    /// length, used by the route searches configured with [`RouteCost::Graph`].
+    /// Returns the length of the edge by default.
+    ///
+    /// [`RouteCost::Graph`]: crate::routing::RouteCost::Graph
+    fn get_edge_cost(&self, edge: Self::EdgeId) -> Result<Length, Self::Error> {
+        self.get_edge_length(edge)
+    }
+
     /// Gets the Functional Road Class (FRC) of the directed edge.
     fn get_edge_frc(&self, edge: Self::EdgeId) -> Result<Frc, Self::Error>;
 
@@ -47,6 +62,26 @@ pub trait DirectedGraph {
         vertex: Self::VertexId,
     ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error>;
 
+    /// Gets an iterator over the outgoing edges from the given vertex whose FRC is not lower than
+    /// the given lowest FRC (the FRC value is lower or equal), as searched by the decoder routes.
+    /// For each edge returns the edge ID and the edge end vertex.
+    ///
+    /// By default looks up the FRC of each exiting edge, graphs that partition their edges by FRC
+    /// can skip the ineligible edges instead (see [`crate::GraphView`]).
+    fn vertex_exiting_edges_within_frc(
+        &self,
+        vertex: Self::VertexId,
+        lowest_frc: Frc,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        let mut edges = vec![];
+        for (edge, vertex) in self.vertex_exiting_edges(vertex)? {
+            if self.get_edge_frc(edge)? <= lowest_frc {
+                edges.push((edge, vertex));
+            }
+        }
+        Ok(edges.into_iter())
+    }
+
     /// Gets an iterator over all the vertices that are within a max distance from the coordinate.
     /// For each vertex also returns the distance from the coordinate.
     /// Vertices must be returned sorted by their distance to the coordinate.
@@ -91,6 +126,18 @@ pub trait DirectedGraph {
         distance: Length,
     ) -> Result<Coordinate, Self::Error>;
 
+    /// Gets the elevation of the edge geometry at the given distance from the edge start vertex
+    /// (clamped within the edge length), only if the graph has elevation data.
+    /// Returns None by default.
+    fn get_elevation_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        distance: Length,
+    ) -> Result<Option<f64>, Self::Error> {
+        let _ = (edge, distance);
+        Ok(None)
+    }
+
     /// Gets the bearing of a subsection A-B of the edge that goes from the coordinate (A) at the
     /// given distance from the start vertex, and the coordinate (B) that is at the given distance
     /// from A. The segment length can be negative.
@@ -101,6 +148,53 @@ pub trait DirectedGraph {
         segment_length: Length,
     ) -> Result<Bearing, Self::Error>;
 
+    /// Gets the bearing of the edge at its start vertex (following the edge for the given segment
+    /// length) or at its end vertex (going backward along the edge for the given segment length),
+    /// if the graph has it precomputed for this segment length.
+    ///
+    /// When available, this bearing is used in place of [`DirectedGraph::get_edge_bearing`] which
+    /// usually requires interpolating the edge geometry.
+    /// Returns None by default, meaning that the bearing has to be computed.
+    fn get_edge_precomputed_bearing(
+        &self,
+        edge: Self::EdgeId,
+        at: EdgeEnd,
+        segment_length: Length,
+    ) -> Result<Option<Bearing>, Self::Error> {
+        let _ = (edge, at, segment_length);
+        Ok(None)
+    }
+
+    /// Gets the dense index of the directed edge, if the graph numbers its edges contiguously from
+    /// zero (for example when the edges are stored in an array).
+    ///
+    /// When available, the shortest path searches track the edges in flat reusable buffers instead
+    /// of hash maps, which reduces the overhead of each expansion on large graphs.
+    /// Returns None by default.
+    fn get_edge_index(&self, edge: Self::EdgeId) -> Option<usize> {
+        let _ = edge;
+        None
+    }
+
+    /// Finds the shortest path between the origin and the destination edges with the given search,
+    /// allowing the graph to memoize its results: the decoder searches paths that only depend on
+    /// the two edges, on the lowest FRC, on the max length of the path and on the minimized cost.
+    ///
+    /// Useful to share the searches across many decodings on the same graph (see
+    /// [`crate::Decoder`]). Runs the search by default.
+    fn memoize_shortest_path<E>(
+        &self,
+        origin: Self::EdgeId,
+        destination: Self::EdgeId,
+        lowest_frc: Frc,
+        max_length: Length,
+        cost: RouteCost,
+        search: impl FnOnce() -> Result<Option<Path<Self::EdgeId>>, E>,
+    ) -> Result<Option<Path<Self::EdgeId>>, E> {
+        let _ = (origin, destination, lowest_frc, max_length, cost);
+        search()
+    }
+
     /// Returns true if turning from the start edge to the end edge is not allowed.
     fn is_turn_restricted(
         &self,
@@ -108,6 +202,20 @@ pub trait DirectedGraph {
         end: Self::EdgeId,
     ) -> Result<bool, Self::Error>;
 
+    /// Gets the via-way restrictions that end with the given edge: each restriction is the
+    /// sequence of edges (the edge where the restriction starts followed by one or more via edges)
+    /// that cannot be traveled right before the given edge. Restrictions between two edges only
+    /// are given by [`DirectedGraph::is_turn_restricted`] instead.
+    ///
+    /// By default the graph has no via-way restrictions.
+    fn get_via_way_restrictions(
+        &self,
+        edge: Self::EdgeId,
+    ) -> Result<Vec<Vec<Self::EdgeId>>, Self::Error> {
+        let _ = edge;
+        Ok(vec![])
+    }
+
     /// Returns the total number of edges that are connected to the vertex, that is, the sum of the
     /// number of entering edges and the exiting edges.
     fn vertex_degree(&self, vertex: Self::VertexId) -> Result<usize, Self::Error> {
@@ -127,16 +235,214 @@ pub trait DirectedGraph {
     }
 }
 
+impl<G: DirectedGraph> DirectedGraph for &G {
+    type Error = G::Error;
+    type VertexId = G::VertexId;
+    type EdgeId = G::EdgeId;
+
+    fn get_vertex_coordinate(&self, vertex: Self::VertexId) -> Result<Coordinate, Self::Error> {
+        (*self).get_vertex_coordinate(vertex)
+    }
+
+    fn get_edge_start_vertex(&self, edge: Self::EdgeId) -> Result<Self::VertexId, Self::Error> {
+        (*self).get_edge_start_vertex(edge)
+    }
+
+    fn get_edge_end_vertex(&self, edge: Self::EdgeId) -> Result<Self::VertexId, Self::Error> {
+        (*self).get_edge_end_vertex(edge)
+    }
+
+    fn get_edge_length(&self, edge: Self::EdgeId) -> Result<Length, Self::Error> {
+        (*self).get_edge_length(edge)
+    }
+
+    fn get_edge_cost(&self, edge: Self::EdgeId) -> Result<Length, Self::Error> {
+        (*self).get_edge_cost(edge)
+    }
+
+    fn get_edge_frc(&self, edge: Self::EdgeId) -> Result<Frc, Self::Error> {
+        (*self).get_edge_frc(edge)
+    }
+
+    fn get_edge_fow(&self, edge: Self::EdgeId) -> Result<Fow, Self::Error> {
+        (*self).get_edge_fow(edge)
+    }
+
+    fn vertex_exiting_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        (*self).vertex_exiting_edges(vertex)
+    }
+
+    fn vertex_entering_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        (*self).vertex_entering_edges(vertex)
+    }
+
+    fn vertex_exiting_edges_within_frc(
+        &self,
+        vertex: Self::VertexId,
+        lowest_frc: Frc,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        (*self).vertex_exiting_edges_within_frc(vertex, lowest_frc)
+    }
+
+    fn nearest_vertices_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> Result<impl Iterator<Item = (Self::VertexId, Length)>, Self::Error> {
+        (*self).nearest_vertices_within_distance(coordinate, max_distance)
+    }
+
+    fn nearest_edges_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Length)>, Self::Error> {
+        (*self).nearest_edges_within_distance(coordinate, max_distance)
+    }
+
+    fn get_distance_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        coordinate: Coordinate,
+    ) -> Result<Length, Self::Error> {
+        (*self).get_distance_along_edge(edge, coordinate)
+    }
+
+    fn get_coordinate_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        distance: Length,
+    ) -> Result<Coordinate, Self::Error> {
+        (*self).get_coordinate_along_edge(edge, distance)
+    }
+
+    fn get_elevation_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        distance: Length,
+    ) -> Result<Option<f64>, Self::Error> {
+        (*self).get_elevation_along_edge(edge, distance)
+    }
+
+    fn get_edge_bearing(
+        &self,
+        edge: Self::EdgeId,
+        distance_from_start: Length,
+        segment_length: Length,
+    ) -> Result<Bearing, Self::Error> {
+        (*self).get_edge_bearing(edge, distance_from_start, segment_length)
+    }
+
+    fn get_edge_precomputed_bearing(
+        &self,
+        edge: Self::EdgeId,
+        at: EdgeEnd,
+        segment_length: Length,
+    ) -> Result<Option<Bearing>, Self::Error> {
+        (*self).get_edge_precomputed_bearing(edge, at, segment_length)
+    }
+
+    fn get_edge_index(&self, edge: Self::EdgeId) -> Option<usize> {
+        (*self).get_edge_index(edge)
+    }
+
+    fn memoize_shortest_path<E>(
+        &self,
+        origin: Self::EdgeId,
+        destination: Self::EdgeId,
+        lowest_frc: Frc,
+        max_length: Length,
+        cost: RouteCost,
+        search: impl FnOnce() -> Result<Option<Path<Self::EdgeId>>, E>,
+    ) -> Result<Option<Path<Self::EdgeId>>, E> {
+        (*self).memoize_shortest_path(origin, destination, lowest_frc, max_length, cost, search)
+    }
+
+    fn is_turn_restricted(
+        &self,
+        start: Self::EdgeId,
+        end: Self::EdgeId,
+    ) -> Result<bool, Self::Error> {
+        (*self).is_turn_restricted(start, end)
+    }
+
+    fn get_via_way_restrictions(
+        &self,
+        edge: Self::EdgeId,
+    ) -> Result<Vec<Vec<Self::EdgeId>>, Self::Error> {
+        (*self).get_via_way_restrictions(edge)
+    }
+
+    fn vertex_degree(&self, vertex: Self::VertexId) -> Result<usize, Self::Error> {
+        (*self).vertex_degree(vertex)
+    }
+
+    fn vertex_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        (*self).vertex_edges(vertex)
+    }
+}
+
+/// Vertex of a directed edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeEnd {
+    /// The start vertex of the edge.
+    Start,
+    /// The end vertex of the edge.
+    End,
+}
+
+/// Gets the bearing of the edge at its start or end vertex, using the precomputed bearing of the
+/// graph if available. The segment length is always positive.
+pub(crate) fn get_edge_end_bearing<G: DirectedGraph>(
+    graph: &G,
+    edge: G::EdgeId,
+    at: EdgeEnd,
+    segment_length: Length,
+) -> Result<Bearing, G::Error> {
+    if let Some(bearing) = graph.get_edge_precomputed_bearing(edge, at, segment_length)? {
+        return Ok(bearing);
+    }
+
+    match at {
+        EdgeEnd::Start => graph.get_edge_bearing(edge, Length::ZERO, segment_length),
+        EdgeEnd::End => {
+            let length = graph.get_edge_length(edge)?;
+            graph.get_edge_bearing(edge, length, segment_length.reverse())
+        }
+    }
+}
+
 pub mod dijkstra;
+pub mod memo;
+#[cfg(any(test, feature = "network"))]
+pub mod network;
+#[cfg(feature = "osm")]
+pub mod osm;
 pub mod path;
+pub mod remote;
+#[cfg(any(test, feature = "spatial-index"))]
+pub mod spatial;
+pub mod tiled;
+pub mod view;
 
 #[cfg(test)]
 pub mod tests {
     #![allow(clippy::panic)]
     #![allow(clippy::disallowed_types)]
 
-    mod geojson;
     mod network;
 
-    pub use network::{EdgeId, NETWORK_GRAPH, NetworkGraph, VertexId};
+    pub use network::{
+        ELEVATED_NETWORK_GRAPH, EdgeId, NETWORK_GRAPH, NetworkGraph, PARALLEL_EDGE,
+        PARALLEL_NETWORK_GRAPH, PrecomputedBearingGraph, RESTRICTED_NETWORK_GRAPH, VertexId,
+    };
 }
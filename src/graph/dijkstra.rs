@@ -1,10 +1,14 @@
+use std::cell::RefCell;
 use std::hash::Hash;
+use std::iter;
+use std::mem;
 
 use radix_heap::Radix;
 use rustc_hash::FxHashMap;
 
-use crate::Length;
+use crate::{DirectedGraph, Length};
 
+#[cfg(not(feature = "fixed-point-length"))]
 impl Radix for Length {
     const RADIX_BITS: u32 = u64::RADIX_BITS;
     fn radix_similarity(&self, other: &Self) -> u32 {
@@ -12,15 +16,157 @@ impl Radix for Length {
     }
 }
 
+#[cfg(feature = "fixed-point-length")]
+impl Radix for Length {
+    const RADIX_BITS: u32 = i64::RADIX_BITS;
+    fn radix_similarity(&self, other: &Self) -> u32 {
+        self.millimeters().radix_similarity(&other.millimeters())
+    }
+}
+
+thread_local! {
+    static DENSE_DISTANCES: RefCell<DenseDistances> = RefCell::default();
+}
+
+/// Distances of the edges indexed by their dense index, with the position of the previous edge of
+/// the path in the list of previous edges of the search.
+/// A distance is only valid if tagged with the current generation, therefore the buffer can be
+/// reset in constant time (by bumping the generation) and reused by the next search.
+#[derive(Debug, Default)]
+struct DenseDistances {
+    generation: u32,
+    distances: Vec<(u32, Length, Option<usize>)>,
+}
+
+/// Shortest distances from the origin of a search to the end of the edges, and the previous edges
+/// of the shortest paths.
+///
+/// Edges that have a dense index (see [`DirectedGraph::get_edge_index`]) are tracked in a thread
+/// local buffer shared by all the searches, the other edges are tracked in a hash map.
+pub struct ShortestDistances<'a, G: DirectedGraph> {
+    graph: &'a G,
+    dense: DenseDistances,
+    /// Previous edges referenced by the dense distances.
+    previous: Vec<G::EdgeId>,
+    sparse: FxHashMap<G::EdgeId, (Length, Option<G::EdgeId>)>,
+}
+
+impl<'a, G: DirectedGraph> ShortestDistances<'a, G> {
+    pub fn new(graph: &'a G) -> Self {
+        let mut dense = DENSE_DISTANCES.with_borrow_mut(mem::take);
+
+        dense.generation = dense.generation.wrapping_add(1);
+        if dense.generation == 0 {
+            // all the stale distances could be mistaken for current ones
+            dense.distances.clear();
+            dense.generation = 1;
+        }
+
+        Self {
+            graph,
+            dense,
+            previous: vec![],
+            sparse: FxHashMap::default(),
+        }
+    }
+
+    /// Gets the shortest distance to the edge, or the max length if the edge has not been reached.
+    pub fn get(&self, edge: G::EdgeId) -> Length {
+        let distance = match self.graph.get_edge_index(edge) {
+            Some(index) => self.dense_entry(index).map(|(distance, _)| distance),
+            None => self.sparse.get(&edge).map(|&(distance, _)| distance),
+        };
+
+        distance.unwrap_or(Length::MAX)
+    }
+
+    /// Gets the previous edge of the shortest path to the edge, None if the edge is the origin of
+    /// the search or has not been reached.
+    pub fn get_previous(&self, edge: G::EdgeId) -> Option<G::EdgeId> {
+        match self.graph.get_edge_index(edge) {
+            Some(index) => {
+                let (_, previous) = self.dense_entry(index)?;
+                self.previous.get(previous?).copied()
+            }
+            None => self.sparse.get(&edge).and_then(|&(_, previous)| previous),
+        }
+    }
+
+    /// Sets the shortest distance to the edge, that has no previous edge.
+    pub fn insert(&mut self, edge: G::EdgeId, distance: Length) {
+        self.insert_entry(edge, distance, None);
+    }
+
+    /// Sets the shortest distance to the edge, reached from the previous edge.
+    pub fn insert_with_previous(&mut self, edge: G::EdgeId, distance: Length, previous: G::EdgeId) {
+        self.insert_entry(edge, distance, Some(previous));
+    }
+
+    fn dense_entry(&self, index: usize) -> Option<(Length, Option<usize>)> {
+        self.dense
+            .distances
+            .get(index)
+            .filter(|(generation, ..)| *generation == self.dense.generation)
+            .map(|&(_, distance, previous)| (distance, previous))
+    }
+
+    fn insert_entry(&mut self, edge: G::EdgeId, distance: Length, previous: Option<G::EdgeId>) {
+        let Some(index) = self.graph.get_edge_index(edge) else {
+            self.sparse.insert(edge, (distance, previous));
+            return;
+        };
+
+        let previous = previous.map(|previous| {
+            self.previous.push(previous);
+            self.previous.len() - 1
+        });
+
+        if index >= self.dense.distances.len() {
+            self.dense
+                .distances
+                .resize(index + 1, (0, Length::MAX, None));
+        }
+
+        if let Some(entry) = self.dense.distances.get_mut(index) {
+            *entry = (self.dense.generation, distance, previous);
+        }
+    }
+}
+
+impl<G: DirectedGraph> Drop for ShortestDistances<'_, G> {
+    fn drop(&mut self) {
+        let dense = mem::take(&mut self.dense);
+        DENSE_DISTANCES.with_borrow_mut(|buffer| *buffer = dense);
+    }
+}
+
+/// Previous edges of the shortest paths found by a search.
+pub trait PreviousEdges<EdgeId> {
+    /// Gets the previous edge of the shortest path to the edge, None if the edge is the origin.
+    fn previous(&self, edge: EdgeId) -> Option<EdgeId>;
+}
+
+impl<EdgeId: Copy + Eq + Hash> PreviousEdges<EdgeId> for FxHashMap<EdgeId, EdgeId> {
+    fn previous(&self, edge: EdgeId) -> Option<EdgeId> {
+        self.get(&edge).copied()
+    }
+}
+
+impl<G: DirectedGraph> PreviousEdges<G::EdgeId> for ShortestDistances<'_, G> {
+    fn previous(&self, edge: G::EdgeId) -> Option<G::EdgeId> {
+        self.get_previous(edge)
+    }
+}
+
 /// Unpacks the shortest path from destination back to origin.
-pub fn unpack_path<EdgeId: Copy + Eq + Hash>(
-    previous_edges: &FxHashMap<EdgeId, EdgeId>,
+pub fn unpack_path<EdgeId: Copy>(
+    previous_edges: &impl PreviousEdges<EdgeId>,
     destination: EdgeId,
 ) -> Vec<EdgeId> {
     let mut edges = vec![destination];
     let mut next = destination;
 
-    while let Some(&e) = previous_edges.get(&next) {
+    while let Some(e) = previous_edges.previous(next) {
         next = e;
         edges.push(e);
     }
@@ -28,3 +174,76 @@ pub fn unpack_path<EdgeId: Copy + Eq + Hash>(
     edges.reverse();
     edges
 }
+
+/// Iterates the shortest path from the given edge back to origin, without unpacking it.
+pub fn previous_edges<EdgeId: Copy>(
+    previous_edges: &impl PreviousEdges<EdgeId>,
+    edge: EdgeId,
+) -> impl Iterator<Item = EdgeId> + Clone {
+    iter::successors(Some(edge), |&e| previous_edges.previous(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+
+    #[test]
+    fn shortest_distances_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let edge = EdgeId(8717174);
+        assert!(graph.get_edge_index(edge).is_some());
+        assert_ne!(graph.get_edge_index(edge), graph.get_edge_index(-edge));
+
+        let mut distances = ShortestDistances::new(graph);
+        assert_eq!(distances.get(edge), Length::MAX);
+
+        distances.insert(edge, Length::from_meters(10.0));
+        assert_eq!(distances.get(edge), Length::from_meters(10.0));
+        assert_eq!(distances.get(-edge), Length::MAX);
+        drop(distances);
+
+        // distances of the previous search are reset
+        let distances = ShortestDistances::new(graph);
+        assert_eq!(distances.get(edge), Length::MAX);
+    }
+
+    #[test]
+    fn shortest_distances_previous() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let path = [EdgeId(8717174), EdgeId(8717175), EdgeId(109783)];
+
+        let mut distances = ShortestDistances::new(graph);
+        distances.insert(path[0], Length::from_meters(10.0));
+        distances.insert_with_previous(path[1], Length::from_meters(20.0), path[0]);
+        distances.insert_with_previous(path[2], Length::from_meters(30.0), path[1]);
+        // sparse edges are tracked along the dense ones
+        distances.insert_with_previous(EdgeId(i64::MAX), Length::from_meters(40.0), path[2]);
+
+        assert_eq!(distances.get_previous(path[0]), None);
+        assert_eq!(distances.get_previous(path[2]), Some(path[1]));
+        assert_eq!(
+            unpack_path(&distances, EdgeId(i64::MAX)),
+            [path.as_slice(), &[EdgeId(i64::MAX)]].concat()
+        );
+        drop(distances);
+
+        // previous edges of the previous search are reset
+        let distances = ShortestDistances::new(graph);
+        assert_eq!(distances.get_previous(path[2]), None);
+    }
+
+    #[test]
+    fn shortest_distances_002() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let edge = EdgeId(i64::MAX);
+        assert_eq!(graph.get_edge_index(edge), None);
+
+        let mut distances = ShortestDistances::new(graph);
+        distances.insert(edge, Length::from_meters(10.0));
+        assert_eq!(distances.get(edge), Length::from_meters(10.0));
+        assert!(distances.dense.distances.is_empty());
+    }
+}
@@ -0,0 +1,758 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use rustc_hash::{FxBuildHasher, FxHashMap};
+
+use crate::graph::path::Path;
+use crate::routing::RouteCost;
+use crate::{Bearing, Coordinate, DirectedGraph, EdgeEnd, Fow, Frc, Length};
+
+/// Graph that memoizes the edge lengths of the underlying graph.
+///
+/// The same edge lengths are requested many times while rating the candidates, resolving the
+/// routes and computing the offsets, which is expensive for graphs that are backed by a database.
+/// Meant to live for a single decoding of a location, or for many encodings on the same graph.
+pub struct EdgeLengthMemo<'a, G: DirectedGraph> {
+    graph: &'a G,
+    lengths: RefCell<FxHashMap<G::EdgeId, Length>>,
+}
+
+impl<'a, G: DirectedGraph> EdgeLengthMemo<'a, G> {
+    pub fn new(graph: &'a G) -> Self {
+        Self {
+            graph,
+            lengths: RefCell::default(),
+        }
+    }
+
+    /// Gets the underlying graph.
+    pub fn graph(&self) -> &'a G {
+        self.graph
+    }
+}
+
+impl<G: DirectedGraph> DirectedGraph for EdgeLengthMemo<'_, G> {
+    type Error = G::Error;
+    type VertexId = G::VertexId;
+    type EdgeId = G::EdgeId;
+
+    fn get_vertex_coordinate(&self, vertex: Self::VertexId) -> Result<Coordinate, Self::Error> {
+        self.graph.get_vertex_coordinate(vertex)
+    }
+
+    fn get_edge_start_vertex(&self, edge: Self::EdgeId) -> Result<Self::VertexId, Self::Error> {
+        self.graph.get_edge_start_vertex(edge)
+    }
+
+    fn get_edge_end_vertex(&self, edge: Self::EdgeId) -> Result<Self::VertexId, Self::Error> {
+        self.graph.get_edge_end_vertex(edge)
+    }
+
+    fn get_edge_length(&self, edge: Self::EdgeId) -> Result<Length, Self::Error> {
+        if let Some(&length) = self.lengths.borrow().get(&edge) {
+            return Ok(length);
+        }
+
+        let length = self.graph.get_edge_length(edge)?;
+        self.lengths.borrow_mut().insert(edge, length);
+        Ok(length)
+    }
+
+    fn get_edge_cost(&self, edge: Self::EdgeId) -> Result<Length, Self::Error> {
+        self.graph.get_edge_cost(edge)
+    }
+
+    fn get_edge_frc(&self, edge: Self::EdgeId) -> Result<Frc, Self::Error> {
+        self.graph.get_edge_frc(edge)
+    }
+
+    fn get_edge_fow(&self, edge: Self::EdgeId) -> Result<Fow, Self::Error> {
+        self.graph.get_edge_fow(edge)
+    }
+
+    fn vertex_exiting_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        self.graph.vertex_exiting_edges(vertex)
+    }
+
+    fn vertex_entering_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        self.graph.vertex_entering_edges(vertex)
+    }
+
+    fn vertex_exiting_edges_within_frc(
+        &self,
+        vertex: Self::VertexId,
+        lowest_frc: Frc,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        self.graph
+            .vertex_exiting_edges_within_frc(vertex, lowest_frc)
+    }
+
+    fn nearest_vertices_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> Result<impl Iterator<Item = (Self::VertexId, Length)>, Self::Error> {
+        self.graph
+            .nearest_vertices_within_distance(coordinate, max_distance)
+    }
+
+    fn nearest_edges_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Length)>, Self::Error> {
+        self.graph
+            .nearest_edges_within_distance(coordinate, max_distance)
+    }
+
+    fn get_distance_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        coordinate: Coordinate,
+    ) -> Result<Length, Self::Error> {
+        self.graph.get_distance_along_edge(edge, coordinate)
+    }
+
+    fn get_coordinate_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        distance: Length,
+    ) -> Result<Coordinate, Self::Error> {
+        self.graph.get_coordinate_along_edge(edge, distance)
+    }
+
+    fn get_elevation_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        distance: Length,
+    ) -> Result<Option<f64>, Self::Error> {
+        self.graph.get_elevation_along_edge(edge, distance)
+    }
+
+    fn get_edge_bearing(
+        &self,
+        edge: Self::EdgeId,
+        distance_from_start: Length,
+        segment_length: Length,
+    ) -> Result<Bearing, Self::Error> {
+        self.graph
+            .get_edge_bearing(edge, distance_from_start, segment_length)
+    }
+
+    fn get_edge_precomputed_bearing(
+        &self,
+        edge: Self::EdgeId,
+        at: EdgeEnd,
+        segment_length: Length,
+    ) -> Result<Option<Bearing>, Self::Error> {
+        self.graph
+            .get_edge_precomputed_bearing(edge, at, segment_length)
+    }
+
+    fn get_edge_index(&self, edge: Self::EdgeId) -> Option<usize> {
+        self.graph.get_edge_index(edge)
+    }
+
+    fn memoize_shortest_path<E>(
+        &self,
+        origin: Self::EdgeId,
+        destination: Self::EdgeId,
+        lowest_frc: Frc,
+        max_length: Length,
+        cost: RouteCost,
+        search: impl FnOnce() -> Result<Option<Path<Self::EdgeId>>, E>,
+    ) -> Result<Option<Path<Self::EdgeId>>, E> {
+        self.graph
+            .memoize_shortest_path(origin, destination, lowest_frc, max_length, cost, search)
+    }
+
+    fn is_turn_restricted(
+        &self,
+        start: Self::EdgeId,
+        end: Self::EdgeId,
+    ) -> Result<bool, Self::Error> {
+        self.graph.is_turn_restricted(start, end)
+    }
+
+    fn get_via_way_restrictions(
+        &self,
+        edge: Self::EdgeId,
+    ) -> Result<Vec<Vec<Self::EdgeId>>, Self::Error> {
+        self.graph.get_via_way_restrictions(edge)
+    }
+
+    fn vertex_degree(&self, vertex: Self::VertexId) -> Result<usize, Self::Error> {
+        self.graph.vertex_degree(vertex)
+    }
+
+    fn vertex_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        self.graph.vertex_edges(vertex)
+    }
+}
+
+/// Floating point values are hashed by their bits.
+pub(crate) type CoordinateKey = (u64, u64);
+pub(crate) type LengthKey = u64;
+
+pub(crate) fn coordinate_key(coordinate: Coordinate) -> CoordinateKey {
+    (coordinate.lon.to_bits(), coordinate.lat.to_bits())
+}
+
+pub(crate) fn length_key(length: Length) -> LengthKey {
+    length.meters().to_bits()
+}
+
+/// Limits of the shortest path searches: the max visited nodes and the max search time.
+pub(crate) type SearchLimitsKey = (Option<usize>, Option<Duration>);
+
+/// The paths depend on the cost they minimize, and whether they are found at all on the limits of
+/// the searches: the paths are only shared by the decoders with the same route cost and limits.
+type ShortestPathKey<EdgeId> = (EdgeId, EdgeId, Frc, LengthKey, RouteCost, SearchLimitsKey);
+
+/// Cache that evicts the least recently used entry when full.
+struct LruCache<K, V> {
+    capacity: usize,
+    entries: lru::LruCache<K, V, FxBuildHasher>,
+}
+
+impl<K: Eq + Hash, V> LruCache<K, V> {
+    fn new(capacity: usize) -> Self {
+        // a cache without capacity keeps nothing (see insert)
+        let bound = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            capacity,
+            entries: lru::LruCache::with_hasher(bound, FxBuildHasher),
+        }
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> LruCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<V> {
+        self.entries.get(key).cloned()
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        if self.capacity > 0 {
+            self.entries.put(key, value);
+        }
+    }
+}
+
+/// Memoized results of a [`DecoderMemo`], computed on the given version of the graph.
+struct DecoderCaches<VertexId, EdgeId> {
+    graph_version: u64,
+    lengths: FxHashMap<EdgeId, Length>,
+    nearest_vertices: LruCache<(CoordinateKey, LengthKey), Vec<(VertexId, Length)>>,
+    nearest_edges: LruCache<(CoordinateKey, LengthKey), Vec<(EdgeId, Length)>>,
+    shortest_paths: FxHashMap<ShortestPathKey<EdgeId>, Option<Path<EdgeId>>>,
+}
+
+impl<VertexId, EdgeId> DecoderCaches<VertexId, EdgeId> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            graph_version: 0,
+            lengths: FxHashMap::default(),
+            nearest_vertices: LruCache::new(capacity),
+            nearest_edges: LruCache::new(capacity),
+            shortest_paths: FxHashMap::default(),
+        }
+    }
+}
+
+/// Caches of the nearest vertices and edges queries, of the edge lengths and of the shortest paths
+/// searched by the decoder, that can be shared (in an [`Arc`]) by the decoders of many threads,
+/// for example by the request handlers of a server (see [`crate::Decoder::with_context`]).
+///
+/// The cached results are tagged with the version of the graph they were computed on: a decoder
+/// on a newer version of the graph (for example after a map update) clears the caches, while the
+/// decoders still running on an older version bypass them. The shortest paths are also keyed by the
+/// route cost and by the search limits of the decoders, that can therefore share the context
+/// whatever their configs.
+#[allow(clippy::type_complexity)]
+pub struct DecoderContext<VertexId, EdgeId> {
+    caches: Mutex<DecoderCaches<VertexId, EdgeId>>,
+}
+
+impl<VertexId, EdgeId> DecoderContext<VertexId, EdgeId> {
+    /// Default maximum number of memoized nearest vertices (and edges) queries.
+    pub const DEFAULT_SPATIAL_CAPACITY: usize = 1 << 16;
+
+    pub fn new() -> Self {
+        Self::with_spatial_capacity(Self::DEFAULT_SPATIAL_CAPACITY)
+    }
+
+    /// Creates the context that keeps at most the given number of nearest vertices (and edges)
+    /// queries.
+    pub fn with_spatial_capacity(capacity: usize) -> Self {
+        Self {
+            caches: Mutex::new(DecoderCaches::new(capacity)),
+        }
+    }
+
+    /// Gets the version of the graph the cached results were computed on.
+    pub fn graph_version(&self) -> u64 {
+        self.lock().graph_version
+    }
+
+    fn lock(&self) -> MutexGuard<'_, DecoderCaches<VertexId, EdgeId>> {
+        // the caches are always left consistent, even by a panicking thread
+        self.caches.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<VertexId, EdgeId> Default for DecoderContext<VertexId, EdgeId> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Graph that memoizes the nearest vertices and edges queries, the edge lengths and the shortest
+/// paths searched by the decoder on the underlying graph, in a [`DecoderContext`].
+///
+/// Consecutive location references of a traffic feed are usually close to each other (or the same
+/// over time), so they query the same areas of the graph and search the same paths.
+/// The nearest queries are keyed by the exact coordinate of the LRP and the least recently used
+/// are evicted beyond the capacity, LRPs at identical coordinates (very common in TMC derived
+/// feeds) don't query the spatial index again.
+/// Meant to live for many decodings on the same graph, memory grows with the decoded area.
+pub struct DecoderMemo<'a, G: DirectedGraph> {
+    graph: &'a G,
+    graph_version: u64,
+    search_limits: SearchLimitsKey,
+    context: Arc<DecoderContext<G::VertexId, G::EdgeId>>,
+}
+
+impl<'a, G: DirectedGraph> DecoderMemo<'a, G> {
+    pub fn new(graph: &'a G) -> Self {
+        Self::with_context(graph, 0, Arc::default())
+    }
+
+    /// Creates the memo that keeps at most the given number of nearest vertices (and edges)
+    /// queries.
+    pub fn with_spatial_capacity(graph: &'a G, capacity: usize) -> Self {
+        Self::with_context(
+            graph,
+            0,
+            Arc::new(DecoderContext::with_spatial_capacity(capacity)),
+        )
+    }
+
+    /// Creates the memo that shares the caches of the context, with the given version of the
+    /// graph.
+    pub fn with_context(
+        graph: &'a G,
+        graph_version: u64,
+        context: Arc<DecoderContext<G::VertexId, G::EdgeId>>,
+    ) -> Self {
+        Self {
+            graph,
+            graph_version,
+            search_limits: (None, None),
+            context,
+        }
+    }
+
+    /// Sets the limits of the shortest path searches of the decoder, that are part of the keys of
+    /// the memoized paths.
+    pub(crate) const fn with_search_limits(mut self, search_limits: SearchLimitsKey) -> Self {
+        self.search_limits = search_limits;
+        self
+    }
+
+    /// Gets the underlying graph.
+    pub fn graph(&self) -> &'a G {
+        self.graph
+    }
+
+    /// Locks the caches of the context, cleared first if they belong to an older version of the
+    /// graph. Returns None if they belong to a newer version of the graph.
+    fn caches(&self) -> Option<MutexGuard<'_, DecoderCaches<G::VertexId, G::EdgeId>>> {
+        let mut caches = self.context.lock();
+        match caches.graph_version.cmp(&self.graph_version) {
+            Ordering::Less => {
+                let capacity = caches.nearest_edges.capacity;
+                *caches = DecoderCaches::new(capacity);
+                caches.graph_version = self.graph_version;
+                Some(caches)
+            }
+            Ordering::Equal => Some(caches),
+            Ordering::Greater => None,
+        }
+    }
+}
+
+impl<G: DirectedGraph> DirectedGraph for DecoderMemo<'_, G> {
+    type Error = G::Error;
+    type VertexId = G::VertexId;
+    type EdgeId = G::EdgeId;
+
+    fn get_vertex_coordinate(&self, vertex: Self::VertexId) -> Result<Coordinate, Self::Error> {
+        self.graph.get_vertex_coordinate(vertex)
+    }
+
+    fn get_edge_start_vertex(&self, edge: Self::EdgeId) -> Result<Self::VertexId, Self::Error> {
+        self.graph.get_edge_start_vertex(edge)
+    }
+
+    fn get_edge_end_vertex(&self, edge: Self::EdgeId) -> Result<Self::VertexId, Self::Error> {
+        self.graph.get_edge_end_vertex(edge)
+    }
+
+    fn get_edge_length(&self, edge: Self::EdgeId) -> Result<Length, Self::Error> {
+        let cached = self
+            .caches()
+            .and_then(|caches| caches.lengths.get(&edge).copied());
+        if let Some(length) = cached {
+            return Ok(length);
+        }
+
+        let length = self.graph.get_edge_length(edge)?;
+        if let Some(mut caches) = self.caches() {
+            caches.lengths.insert(edge, length);
+        }
+        Ok(length)
+    }
+
+    fn get_edge_cost(&self, edge: Self::EdgeId) -> Result<Length, Self::Error> {
+        self.graph.get_edge_cost(edge)
+    }
+
+    fn get_edge_frc(&self, edge: Self::EdgeId) -> Result<Frc, Self::Error> {
+        self.graph.get_edge_frc(edge)
+    }
+
+    fn get_edge_fow(&self, edge: Self::EdgeId) -> Result<Fow, Self::Error> {
+        self.graph.get_edge_fow(edge)
+    }
+
+    fn vertex_exiting_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        self.graph.vertex_exiting_edges(vertex)
+    }
+
+    fn vertex_entering_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        self.graph.vertex_entering_edges(vertex)
+    }
+
+    fn vertex_exiting_edges_within_frc(
+        &self,
+        vertex: Self::VertexId,
+        lowest_frc: Frc,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        self.graph
+            .vertex_exiting_edges_within_frc(vertex, lowest_frc)
+    }
+
+    fn nearest_vertices_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> Result<impl Iterator<Item = (Self::VertexId, Length)>, Self::Error> {
+        let key = (coordinate_key(coordinate), length_key(max_distance));
+        let cached = self
+            .caches()
+            .and_then(|mut caches| caches.nearest_vertices.get(&key));
+        if let Some(vertices) = cached {
+            return Ok(vertices.into_iter());
+        }
+
+        let vertices: Vec<_> = self
+            .graph
+            .nearest_vertices_within_distance(coordinate, max_distance)?
+            .collect();
+        if let Some(mut caches) = self.caches() {
+            caches.nearest_vertices.insert(key, vertices.clone());
+        }
+        Ok(vertices.into_iter())
+    }
+
+    fn nearest_edges_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Length)>, Self::Error> {
+        let key = (coordinate_key(coordinate), length_key(max_distance));
+        let cached = self
+            .caches()
+            .and_then(|mut caches| caches.nearest_edges.get(&key));
+        if let Some(edges) = cached {
+            return Ok(edges.into_iter());
+        }
+
+        let edges: Vec<_> = self
+            .graph
+            .nearest_edges_within_distance(coordinate, max_distance)?
+            .collect();
+        if let Some(mut caches) = self.caches() {
+            caches.nearest_edges.insert(key, edges.clone());
+        }
+        Ok(edges.into_iter())
+    }
+
+    fn get_distance_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        coordinate: Coordinate,
+    ) -> Result<Length, Self::Error> {
+        self.graph.get_distance_along_edge(edge, coordinate)
+    }
+
+    fn get_coordinate_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        distance: Length,
+    ) -> Result<Coordinate, Self::Error> {
+        self.graph.get_coordinate_along_edge(edge, distance)
+    }
+
+    fn get_elevation_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        distance: Length,
+    ) -> Result<Option<f64>, Self::Error> {
+        self.graph.get_elevation_along_edge(edge, distance)
+    }
+
+    fn get_edge_bearing(
+        &self,
+        edge: Self::EdgeId,
+        distance_from_start: Length,
+        segment_length: Length,
+    ) -> Result<Bearing, Self::Error> {
+        self.graph
+            .get_edge_bearing(edge, distance_from_start, segment_length)
+    }
+
+    fn get_edge_precomputed_bearing(
+        &self,
+        edge: Self::EdgeId,
+        at: EdgeEnd,
+        segment_length: Length,
+    ) -> Result<Option<Bearing>, Self::Error> {
+        self.graph
+            .get_edge_precomputed_bearing(edge, at, segment_length)
+    }
+
+    fn get_edge_index(&self, edge: Self::EdgeId) -> Option<usize> {
+        self.graph.get_edge_index(edge)
+    }
+
+    fn memoize_shortest_path<E>(
+        &self,
+        origin: Self::EdgeId,
+        destination: Self::EdgeId,
+        lowest_frc: Frc,
+        max_length: Length,
+        cost: RouteCost,
+        search: impl FnOnce() -> Result<Option<Path<Self::EdgeId>>, E>,
+    ) -> Result<Option<Path<Self::EdgeId>>, E> {
+        let key = (
+            origin,
+            destination,
+            lowest_frc,
+            length_key(max_length),
+            cost,
+            self.search_limits,
+        );
+        let cached = self
+            .caches()
+            .and_then(|caches| caches.shortest_paths.get(&key).cloned());
+        if let Some(path) = cached {
+            return Ok(path);
+        }
+
+        // the search runs on this graph, the caches must not be locked meanwhile
+        let path = self.graph.memoize_shortest_path(
+            origin,
+            destination,
+            lowest_frc,
+            max_length,
+            cost,
+            search,
+        )?;
+        if let Some(mut caches) = self.caches() {
+            caches.shortest_paths.insert(key, path.clone());
+        }
+        Ok(path)
+    }
+
+    fn is_turn_restricted(
+        &self,
+        start: Self::EdgeId,
+        end: Self::EdgeId,
+    ) -> Result<bool, Self::Error> {
+        self.graph.is_turn_restricted(start, end)
+    }
+
+    fn get_via_way_restrictions(
+        &self,
+        edge: Self::EdgeId,
+    ) -> Result<Vec<Vec<Self::EdgeId>>, Self::Error> {
+        self.graph.get_via_way_restrictions(edge)
+    }
+
+    fn vertex_degree(&self, vertex: Self::VertexId) -> Result<usize, Self::Error> {
+        self.graph.vertex_degree(vertex)
+    }
+
+    fn vertex_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        self.graph.vertex_edges(vertex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+
+    #[test]
+    fn edge_length_memo_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let memo = EdgeLengthMemo::new(graph);
+
+        for edge in [EdgeId(8717174), EdgeId(8717175), EdgeId(8717174)] {
+            assert_eq!(
+                memo.get_edge_length(edge).unwrap(),
+                graph.get_edge_length(edge).unwrap()
+            );
+        }
+
+        assert_eq!(memo.lengths.borrow().len(), 2);
+    }
+
+    #[test]
+    fn decoder_memo_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let memo = DecoderMemo::new(graph);
+        let coordinate = Coordinate {
+            lon: 13.46112,
+            lat: 52.51711,
+        };
+
+        for _ in 0..2 {
+            let edges: Vec<_> = memo
+                .nearest_edges_within_distance(coordinate, Length::from_meters(100.0))
+                .unwrap()
+                .collect();
+            let expected: Vec<_> = graph
+                .nearest_edges_within_distance(coordinate, Length::from_meters(100.0))
+                .unwrap()
+                .collect();
+            assert_eq!(edges, expected);
+        }
+        assert_eq!(memo.caches().unwrap().nearest_edges.len(), 1);
+
+        // coordinates that differ by less than the binary format resolution are other LRPs
+        let nearby = Coordinate {
+            lon: coordinate.lon + Coordinate::EPSILON / 4.0,
+            lat: coordinate.lat,
+        };
+        let _ = memo
+            .nearest_edges_within_distance(nearby, Length::from_meters(100.0))
+            .unwrap();
+        assert_eq!(memo.caches().unwrap().nearest_edges.len(), 2);
+
+        let mut searches = 0;
+        for _ in 0..2 {
+            let path = memo
+                .memoize_shortest_path(
+                    EdgeId(8717174),
+                    EdgeId(8717175),
+                    Frc::Frc7,
+                    Length::MAX,
+                    RouteCost::Length,
+                    || {
+                        searches += 1;
+                        Ok::<_, ()>(None)
+                    },
+                )
+                .unwrap();
+            assert_eq!(path, None);
+        }
+        assert_eq!(searches, 1);
+
+        // the paths minimizing another cost, or searched with other limits, are searched again
+        let limited = DecoderMemo::with_context(graph, 0, Arc::clone(&memo.context))
+            .with_search_limits((Some(10), None));
+        for (memo, cost) in [(&memo, RouteCost::Graph), (&limited, RouteCost::Length)] {
+            memo.memoize_shortest_path(
+                EdgeId(8717174),
+                EdgeId(8717175),
+                Frc::Frc7,
+                Length::MAX,
+                cost,
+                || {
+                    searches += 1;
+                    Ok::<_, ()>(None)
+                },
+            )
+            .unwrap();
+        }
+        assert_eq!(searches, 3);
+    }
+
+    #[test]
+    fn decoder_memo_002() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let memo = DecoderMemo::with_spatial_capacity(graph, 2);
+        let coordinate = |lon| Coordinate { lon, lat: 52.51711 };
+        let distance = Length::from_meters(100.0);
+
+        for lon in [13.46112, 13.46212, 13.46112, 13.46312] {
+            let vertices: Vec<_> = memo
+                .nearest_vertices_within_distance(coordinate(lon), distance)
+                .unwrap()
+                .collect();
+            let expected: Vec<_> = graph
+                .nearest_vertices_within_distance(coordinate(lon), distance)
+                .unwrap()
+                .collect();
+            assert_eq!(vertices, expected);
+        }
+
+        // the least recently used query is evicted
+        let caches = memo.caches().unwrap();
+        let cache = &caches.nearest_vertices;
+        assert_eq!(cache.len(), 2);
+        let key = |lon| (coordinate_key(coordinate(lon)), length_key(distance));
+        assert!(cache.entries.contains(&key(13.46112)));
+        assert!(!cache.entries.contains(&key(13.46212)));
+        assert!(cache.entries.contains(&key(13.46312)));
+    }
+
+    #[test]
+    fn lru_cache_zero_capacity() {
+        let mut cache = LruCache::new(0);
+        cache.insert(1, 1);
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.len(), 0);
+    }
+}
@@ -0,0 +1,583 @@
+//! Road network graph built from a list of lines (or from a GeoJSON feature collection of lines).
+//!
+//! The adjacency of the vertices is stored in compressed sparse rows, while the vertices and the
+//! edges are spatially indexed with R-trees to find the candidates of the location references.
+
+use geo::{
+    Closest, Coord, Distance, Haversine, HaversineClosestPoint, InterpolatableLine, LineString,
+    Point,
+};
+use rustc_hash::FxHashMap;
+use serde_json::Value;
+
+#[cfg(feature = "snapshot")]
+use crate::error::NetworkSnapshotError;
+use crate::error::{NetworkGeojsonError, NetworkGraphError};
+use crate::graph::spatial::{RTreeIndex, SpatialIndex};
+use crate::model::StoredCoordinate;
+use crate::{Bearing, Coordinate, DirectedGraph, Fow, Frc, Length};
+
+/// Header of the graph snapshots: a magic string and the version of the snapshot format, bumped
+/// whenever the layout of the graph changes.
+#[cfg(feature = "snapshot")]
+const SNAPSHOT_HEADER: &[u8] = b"OPENLR-NETWORK-GRAPH\x01";
+
+/// Vertex of the [`NetworkGraph`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NetworkVertexId(pub u64);
+
+/// Directed edge of the [`NetworkGraph`]: the (positive) ID of a line when traveled from its start
+/// vertex to its end vertex, or the negated ID of the line when traveled in the opposite direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NetworkEdgeId(pub i64);
+
+impl From<i64> for NetworkEdgeId {
+    fn from(id: i64) -> Self {
+        Self(id)
+    }
+}
+
+impl From<NetworkEdgeId> for i64 {
+    fn from(edge: NetworkEdgeId) -> Self {
+        edge.0
+    }
+}
+
+impl std::ops::Neg for NetworkEdgeId {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(-self.0)
+    }
+}
+
+impl NetworkEdgeId {
+    const fn is_reversed(&self) -> bool {
+        self.0.is_negative()
+    }
+
+    const fn undirected(&self) -> Self {
+        Self(self.0.abs())
+    }
+}
+
+/// Directions in which a line can be traveled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineDirection {
+    /// From the start vertex to the end vertex, and the other way around.
+    Both,
+    /// From the start vertex to the end vertex only.
+    Forward,
+    /// From the end vertex to the start vertex only.
+    Backward,
+}
+
+/// Line of the road network, from which the [`NetworkGraph`] edges are built.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkLine {
+    /// Positive ID of the line, the reversed line has the negated ID.
+    pub id: NetworkEdgeId,
+    pub start: NetworkVertexId,
+    pub end: NetworkVertexId,
+    pub length: Length,
+    pub frc: Frc,
+    pub fow: Fow,
+    pub direction: LineDirection,
+    /// Coordinates of the line, from the start vertex to the end vertex.
+    pub geometry: Vec<Coordinate>,
+}
+
+impl NetworkLine {
+    /// Parses the lines of a GeoJSON FeatureCollection.
+    ///
+    /// Every LineString feature is a line with the properties: `id`, `startId` and `endId` (of the
+    /// vertices), `length` (in meters), `frc`, `fow` and `direction` (1 both directions, 2 forward,
+    /// 3 backward). Features of any other geometry type are ignored.
+    pub fn from_geojson(geojson: &str) -> Result<Vec<Self>, NetworkGeojsonError> {
+        let collection: Value =
+            serde_json::from_str(geojson).map_err(|_| NetworkGeojsonError::InvalidGeojson)?;
+
+        let features = collection.get("features").and_then(Value::as_array);
+        let features = features.ok_or(NetworkGeojsonError::InvalidGeojson)?;
+
+        let mut lines = vec![];
+
+        for (feature, value) in features.iter().enumerate() {
+            let geometry = &value["geometry"];
+            if geometry["type"] != "LineString" {
+                continue;
+            }
+
+            let invalid = |property| NetworkGeojsonError::InvalidProperty { feature, property };
+            let property =
+                |name: &'static str| value["properties"][name].as_i64().ok_or(invalid(name));
+
+            let frc = i8::try_from(property("frc")?)
+                .ok()
+                .and_then(Frc::from_value);
+            let fow = i8::try_from(property("fow")?)
+                .ok()
+                .and_then(Fow::from_value);
+            let length = value["properties"]["length"].as_f64();
+
+            let direction = match property("direction")? {
+                1 => LineDirection::Both,
+                2 => LineDirection::Forward,
+                3 => LineDirection::Backward,
+                _ => return Err(invalid("direction")),
+            };
+
+            let position = |position: &Value| {
+                let lon = position.get(0)?.as_f64()?;
+                let lat = position.get(1)?.as_f64()?;
+                Some(Coordinate { lon, lat })
+            };
+
+            let positions = geometry["coordinates"].as_array();
+            let geometry: Option<Vec<_>> = positions
+                .ok_or(invalid("geometry"))?
+                .iter()
+                .map(position)
+                .collect();
+
+            lines.push(NetworkLine {
+                id: NetworkEdgeId(property("id")?),
+                start: NetworkVertexId(property("startId")? as u64),
+                end: NetworkVertexId(property("endId")? as u64),
+                length: Length::from_meters(length.ok_or(invalid("length"))?),
+                frc: frc.ok_or(invalid("frc"))?,
+                fow: fow.ok_or(invalid("fow"))?,
+                direction,
+                geometry: geometry.ok_or(invalid("geometry"))?,
+            });
+        }
+
+        Ok(lines)
+    }
+}
+
+/// Directed graph of a road network, the edges of the graph are the (directed) network lines.
+#[derive(Debug)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub struct NetworkGraph {
+    vertices: FxHashMap<NetworkVertexId, usize>,
+    coordinates: Vec<StoredCoordinate>,
+    exiting_edges: AdjacencyRows,
+    entering_edges: AdjacencyRows,
+    lines: FxHashMap<NetworkEdgeId, LineProperties>,
+    spatial_index: RTreeIndex<NetworkVertexId, NetworkEdgeId>,
+    /// Restricted sequences of edges, by their last edge (without it).
+    restrictions: FxHashMap<NetworkEdgeId, Vec<Vec<NetworkEdgeId>>>,
+    /// The elevation of every edge grows 1 meter for each meter along the edge.
+    #[cfg(test)]
+    pub(crate) has_elevation: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+struct LineProperties {
+    length: Length,
+    frc: Frc,
+    fow: Fow,
+    geometry: LineString,
+    vertices: [NetworkVertexId; 2],
+    index: usize,
+}
+
+/// Compressed sparse rows: the edges of the vertex with dense index `i` are the entries in the
+/// range `offsets[i]..offsets[i + 1]`, each with the vertex at the other end of the edge.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+struct AdjacencyRows {
+    offsets: Vec<usize>,
+    entries: Vec<(NetworkEdgeId, NetworkVertexId)>,
+}
+
+impl AdjacencyRows {
+    fn new(vertex_count: usize, mut entries: Vec<(usize, NetworkEdgeId, NetworkVertexId)>) -> Self {
+        // edges returned in a deterministic order
+        entries.sort_unstable();
+
+        let mut offsets = vec![0; vertex_count + 1];
+        for &(index, ..) in &entries {
+            offsets[index + 1] += 1;
+        }
+        for index in 0..vertex_count {
+            offsets[index + 1] += offsets[index];
+        }
+
+        let entries = entries
+            .into_iter()
+            .map(|(_, edge, vertex)| (edge, vertex))
+            .collect();
+
+        Self { offsets, entries }
+    }
+
+    fn row(&self, index: usize) -> &[(NetworkEdgeId, NetworkVertexId)] {
+        match (self.offsets.get(index), self.offsets.get(index + 1)) {
+            (Some(&start), Some(&end)) => &self.entries[start..end],
+            _ => &[],
+        }
+    }
+}
+
+impl NetworkGraph {
+    /// Builds the graph from the lines of the network.
+    /// The coordinates of the vertices are the first and the last coordinates of the line
+    /// geometries, lines with less than 2 coordinates are ignored.
+    pub fn from_lines(lines: impl IntoIterator<Item = NetworkLine>) -> Self {
+        let mut vertices: FxHashMap<NetworkVertexId, usize> = FxHashMap::default();
+        let mut coordinates = vec![];
+        let mut vertex_index = |vertex, coordinate| {
+            *vertices.entry(vertex).or_insert_with(|| {
+                coordinates.push(StoredCoordinate::store(coordinate));
+                coordinates.len() - 1
+            })
+        };
+
+        let mut line_properties = FxHashMap::default();
+        let (mut exiting, mut entering) = (vec![], vec![]);
+        let mut geospatial_edges = vec![];
+
+        for line in lines {
+            let [mut first, .., mut last] = *line.geometry.as_slice() else {
+                continue;
+            };
+
+            let (id, mut start, mut end) = (line.id.undirected(), line.start, line.end);
+            let mut geometry: LineString = line
+                .geometry
+                .iter()
+                .map(|c| Coord { x: c.lon, y: c.lat })
+                .collect();
+
+            if line.direction == LineDirection::Backward {
+                // lines are only stored in their traveled direction
+                std::mem::swap(&mut start, &mut end);
+                std::mem::swap(&mut first, &mut last);
+                geometry = geometry.coords().rev().copied().collect();
+            }
+
+            let start_index = vertex_index(start, first);
+            let end_index = vertex_index(end, last);
+
+            let mut edges = vec![(id, start, end, start_index, end_index)];
+            if line.direction == LineDirection::Both && start != end {
+                edges.push((-id, end, start, end_index, start_index));
+            }
+
+            for (edge, from, to, from_index, to_index) in edges {
+                exiting.push((from_index, edge, to));
+                entering.push((to_index, edge, from));
+
+                geospatial_edges.push((edge, geometry.clone()));
+            }
+
+            let index = line_properties.len();
+            line_properties.insert(
+                id,
+                LineProperties {
+                    length: line.length,
+                    frc: line.frc,
+                    fow: line.fow,
+                    geometry,
+                    vertices: [start, end],
+                    index,
+                },
+            );
+        }
+
+        let geospatial_vertices = vertices
+            .iter()
+            .filter_map(|(&vertex, &index)| Some((vertex, coordinates.get(index)?.load())));
+        let spatial_index = RTreeIndex::new(geospatial_vertices, geospatial_edges);
+
+        NetworkGraph {
+            exiting_edges: AdjacencyRows::new(coordinates.len(), exiting),
+            entering_edges: AdjacencyRows::new(coordinates.len(), entering),
+            vertices,
+            coordinates,
+            lines: line_properties,
+            spatial_index,
+            restrictions: FxHashMap::default(),
+            #[cfg(test)]
+            has_elevation: false,
+        }
+    }
+
+    /// Builds the graph from the lines of a GeoJSON FeatureCollection (see
+    /// [`NetworkLine::from_geojson`]).
+    #[cfg(feature = "network")]
+    pub fn from_geojson(geojson: &str) -> Result<Self, NetworkGeojsonError> {
+        let lines = NetworkLine::from_geojson(geojson)?;
+        Ok(Self::from_lines(lines))
+    }
+
+    /// Adds a restriction: the sequence of edges cannot be traveled in this order.
+    /// Restrictions of two edges are turn restrictions, longer ones are via-way restrictions.
+    /// Sequences of less than two edges are ignored.
+    pub fn add_restriction(&mut self, edges: impl IntoIterator<Item = NetworkEdgeId>) {
+        let mut edges: Vec<_> = edges.into_iter().collect();
+        if edges.len() < 2 {
+            return;
+        }
+
+        if let Some(last) = edges.pop() {
+            self.restrictions.entry(last).or_default().push(edges);
+        }
+    }
+
+    /// Serializes the built graph (with its spatial index) into a binary snapshot, that
+    /// [`NetworkGraph::from_snapshot`] loads much faster than building the graph again.
+    #[cfg(feature = "snapshot")]
+    pub fn to_snapshot(&self) -> Result<Vec<u8>, NetworkSnapshotError> {
+        let mut data = SNAPSHOT_HEADER.to_vec();
+        bincode::serde::encode_into_std_write(self, &mut data, bincode::config::standard())
+            .map_err(|_| NetworkSnapshotError::Serialize)?;
+        Ok(data)
+    }
+
+    /// Loads the graph from a binary snapshot (see [`NetworkGraph::to_snapshot`]), written by the
+    /// same version of the crate.
+    #[cfg(feature = "snapshot")]
+    pub fn from_snapshot(data: &[u8]) -> Result<Self, NetworkSnapshotError> {
+        let data = data
+            .strip_prefix(SNAPSHOT_HEADER)
+            .ok_or(NetworkSnapshotError::InvalidHeader)?;
+
+        let (graph, length) = bincode::serde::decode_from_slice(data, bincode::config::standard())
+            .map_err(|_| NetworkSnapshotError::InvalidData)?;
+
+        if length != data.len() {
+            return Err(NetworkSnapshotError::InvalidData);
+        }
+
+        Ok(graph)
+    }
+
+    #[cfg(feature = "network")]
+    pub fn vertex_count(&self) -> usize {
+        self.coordinates.len()
+    }
+
+    #[cfg(feature = "network")]
+    pub fn edge_count(&self) -> usize {
+        self.exiting_edges.entries.len()
+    }
+
+    fn vertex_index(&self, vertex: NetworkVertexId) -> Result<usize, NetworkGraphError> {
+        let found = self.vertices.get(&vertex).copied();
+        found.ok_or(NetworkGraphError::VertexNotFound(vertex))
+    }
+
+    fn line(&self, edge: NetworkEdgeId) -> Result<&LineProperties, NetworkGraphError> {
+        let found = self.lines.get(&edge.undirected());
+        found.ok_or(NetworkGraphError::EdgeNotFound(edge))
+    }
+
+    fn edge_line_string(&self, edge: NetworkEdgeId) -> Result<LineString, NetworkGraphError> {
+        let LineProperties { geometry, .. } = self.line(edge)?;
+
+        if edge.is_reversed() {
+            Ok(geometry.coords().rev().copied().collect())
+        } else {
+            Ok(geometry.clone())
+        }
+    }
+}
+
+impl DirectedGraph for NetworkGraph {
+    type Error = NetworkGraphError;
+    type EdgeId = NetworkEdgeId;
+    type VertexId = NetworkVertexId;
+
+    fn get_vertex_coordinate(&self, vertex: Self::VertexId) -> Result<Coordinate, Self::Error> {
+        let coordinate = self.coordinates.get(self.vertex_index(vertex)?);
+        coordinate
+            .map(|coordinate| coordinate.load())
+            .ok_or(NetworkGraphError::VertexNotFound(vertex))
+    }
+
+    fn get_edge_start_vertex(&self, edge: Self::EdgeId) -> Result<Self::VertexId, Self::Error> {
+        let [start, end] = self.line(edge)?.vertices;
+        Ok(if edge.is_reversed() { end } else { start })
+    }
+
+    fn get_edge_end_vertex(&self, edge: Self::EdgeId) -> Result<Self::VertexId, Self::Error> {
+        let [start, end] = self.line(edge)?.vertices;
+        Ok(if edge.is_reversed() { start } else { end })
+    }
+
+    fn get_edge_length(&self, edge: Self::EdgeId) -> Result<Length, Self::Error> {
+        Ok(self.line(edge)?.length)
+    }
+
+    fn get_edge_frc(&self, edge: Self::EdgeId) -> Result<Frc, Self::Error> {
+        Ok(self.line(edge)?.frc)
+    }
+
+    fn get_edge_fow(&self, edge: Self::EdgeId) -> Result<Fow, Self::Error> {
+        Ok(self.line(edge)?.fow)
+    }
+
+    fn vertex_exiting_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        let index = self.vertex_index(vertex)?;
+        Ok(self.exiting_edges.row(index).iter().copied())
+    }
+
+    fn vertex_entering_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        let index = self.vertex_index(vertex)?;
+        Ok(self.entering_edges.row(index).iter().copied())
+    }
+
+    fn nearest_vertices_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> Result<impl Iterator<Item = (Self::VertexId, Length)>, Self::Error> {
+        Ok(self
+            .spatial_index
+            .nearest_vertices_within_distance(coordinate, max_distance))
+    }
+
+    fn nearest_edges_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Length)>, Self::Error> {
+        Ok(self
+            .spatial_index
+            .nearest_edges_within_distance(coordinate, max_distance))
+    }
+
+    fn get_distance_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        coordinate: Coordinate,
+    ) -> Result<Length, Self::Error> {
+        let mut closest_distance = f64::INFINITY;
+        let mut distance_along_edge = 0.0;
+        let mut distance_acc = 0.0;
+
+        let point = Point::new(coordinate.lon, coordinate.lat);
+
+        for line in self.edge_line_string(edge)?.lines() {
+            if let Closest::SinglePoint(p) | Closest::Intersection(p) =
+                line.haversine_closest_point(&point)
+            {
+                let distance_to_line = Haversine.distance(point, p);
+
+                if distance_to_line < closest_distance {
+                    // this is the closest line segment of the whole geometry (so far)
+                    closest_distance = distance_to_line;
+                    distance_along_edge = distance_acc + Haversine.distance(line.start_point(), p);
+                }
+            }
+
+            use geo::Length;
+            distance_acc += Haversine.length(&line);
+        }
+
+        Ok(Length::from_meters(distance_along_edge).min(self.get_edge_length(edge)?))
+    }
+
+    fn get_coordinate_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        distance: Length,
+    ) -> Result<Coordinate, Self::Error> {
+        let length = self.get_edge_length(edge)?;
+        let ratio = if length > Length::ZERO {
+            distance.meters() / length.meters()
+        } else {
+            0.0
+        };
+
+        let geometry = self.edge_line_string(edge)?;
+        let coordinate = match geometry.point_at_ratio_from_start(&Haversine, ratio) {
+            Some(point) => Coordinate {
+                lon: point.x(),
+                lat: point.y(),
+            },
+            None => self.get_vertex_coordinate(self.get_edge_start_vertex(edge)?)?,
+        };
+
+        Ok(coordinate)
+    }
+
+    #[cfg(test)]
+    fn get_elevation_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        distance: Length,
+    ) -> Result<Option<f64>, Self::Error> {
+        if !self.has_elevation {
+            return Ok(None);
+        }
+
+        let length = self.get_edge_length(edge)?;
+        Ok(Some(distance.max(Length::ZERO).min(length).meters()))
+    }
+
+    fn get_edge_bearing(
+        &self,
+        edge: Self::EdgeId,
+        distance_from_start: Length,
+        segment_length: Length,
+    ) -> Result<Bearing, Self::Error> {
+        let edge_length = self.get_edge_length(edge)?;
+        let distance_start = distance_from_start.clamp(Length::ZERO, edge_length);
+        let distance_end = (distance_start + segment_length).clamp(Length::ZERO, edge_length);
+
+        let c1 = self.get_coordinate_along_edge(edge, distance_start)?;
+        let p1 = Point::new(c1.lon, c1.lat);
+
+        let c2 = self.get_coordinate_along_edge(edge, distance_end)?;
+        let p2 = Point::new(c2.lon, c2.lat);
+
+        let degrees = {
+            use geo::Bearing;
+            Haversine.bearing(p1, p2).round() as u16
+        };
+
+        Ok(Bearing::from_degrees(degrees))
+    }
+
+    fn get_edge_index(&self, edge: Self::EdgeId) -> Option<usize> {
+        let LineProperties { index, .. } = self.lines.get(&edge.undirected())?;
+        Some(2 * index + edge.is_reversed() as usize)
+    }
+
+    fn is_turn_restricted(
+        &self,
+        start: Self::EdgeId,
+        end: Self::EdgeId,
+    ) -> Result<bool, Self::Error> {
+        Ok(self
+            .restrictions
+            .get(&end)
+            .into_iter()
+            .flatten()
+            .any(|edges| edges.as_slice() == [start]))
+    }
+
+    fn get_via_way_restrictions(
+        &self,
+        edge: Self::EdgeId,
+    ) -> Result<Vec<Vec<Self::EdgeId>>, Self::Error> {
+        let restrictions = self.restrictions.get(&edge).into_iter().flatten();
+        Ok(restrictions
+            .filter(|edges| edges.len() > 1)
+            .cloned()
+            .collect())
+    }
+}
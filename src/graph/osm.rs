@@ -0,0 +1,659 @@
+//! Road network graph built from OpenStreetMap (OSM) data, for example from a PBF extract.
+//!
+//! The ways tagged as highways (for motor vehicles) become the edges of the graph, split at every
+//! node shared with another way. The FRC and the FOW of the edges are mapped from the highway
+//! tags, and one-way roads have a single directed edge.
+
+mod inflate;
+mod pbf;
+
+use std::io::Read;
+
+use geo::{
+    Closest, Coord, Distance, Haversine, HaversineClosestPoint, InterpolatableLine, LineString,
+    Point,
+};
+use rustc_hash::FxHashMap;
+
+use crate::graph::osm::pbf::read_pbf;
+use crate::graph::spatial::{RTreeIndex, SpatialIndex};
+use crate::model::StoredCoordinate;
+use crate::{Bearing, Coordinate, DirectedGraph, Fow, Frc, Length, OsmError, OsmGraphError};
+
+/// OSM node, only its coordinate is used.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OsmNode {
+    pub id: i64,
+    pub coordinate: Coordinate,
+}
+
+/// OSM way, the sequence of its nodes and its tags (key, value).
+#[derive(Debug, Clone, PartialEq)]
+pub struct OsmWay {
+    pub id: i64,
+    pub nodes: Vec<i64>,
+    pub tags: Vec<(String, String)>,
+}
+
+/// Vertex of the [`OsmGraph`], a node at an intersection or at the end of a way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OsmVertexId(pub u32);
+
+/// Directed edge of the [`OsmGraph`], a part of a way between two vertices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OsmEdgeId(pub u32);
+
+/// Directed graph of the road network of OSM data.
+#[derive(Debug)]
+pub struct OsmGraph {
+    vertices: Vec<Vertex>,
+    edges: Vec<Edge>,
+    spatial_index: RTreeIndex<OsmVertexId, OsmEdgeId>,
+}
+
+#[derive(Debug)]
+struct Vertex {
+    node: i64,
+    coordinate: StoredCoordinate,
+    exiting_edges: Vec<OsmEdgeId>,
+    entering_edges: Vec<OsmEdgeId>,
+}
+
+#[derive(Debug)]
+struct Edge {
+    way: i64,
+    vertices: [OsmVertexId; 2],
+    length: Length,
+    frc: Frc,
+    fow: Fow,
+    geometry: LineString,
+}
+
+/// Attributes of the ways that are part of the road network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct WayAttributes {
+    frc: Frc,
+    fow: Fow,
+    oneway: Oneway,
+}
+
+/// Directions in which a way can be traveled, relative to the order of its nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Oneway {
+    No,
+    Forward,
+    Backward,
+}
+
+impl OsmGraph {
+    /// Builds the graph from the nodes and the ways of an OSM PBF file.
+    /// All the node coordinates are kept in memory while building the graph.
+    pub fn from_pbf(reader: impl Read) -> Result<Self, OsmError> {
+        let (nodes, ways) = read_pbf(reader)?;
+        Ok(Self::from_elements(nodes, ways))
+    }
+
+    /// Builds the graph from the OSM nodes and ways. Ways that are not highways, and references to
+    /// nodes that are missing (for example at the boundary of an extract), are ignored.
+    pub fn from_elements(
+        nodes: impl IntoIterator<Item = OsmNode>,
+        ways: impl IntoIterator<Item = OsmWay>,
+    ) -> Self {
+        let coordinates: FxHashMap<i64, Coordinate> = nodes
+            .into_iter()
+            .map(|node| (node.id, node.coordinate))
+            .collect();
+
+        let ways: Vec<_> = ways
+            .into_iter()
+            .filter_map(|mut way| {
+                let attributes = way_attributes(&way.tags)?;
+                way.nodes.retain(|node| coordinates.contains_key(node));
+                way.nodes.dedup();
+                (way.nodes.len() > 1).then_some((way, attributes))
+            })
+            .collect();
+
+        // the ends of the ways and the nodes shared between ways (or visited twice) are vertices
+        let mut node_uses: FxHashMap<i64, usize> = FxHashMap::default();
+        for (way, _) in &ways {
+            for &node in &way.nodes {
+                *node_uses.entry(node).or_default() += 1;
+            }
+            for node in [way.nodes[0], way.nodes[way.nodes.len() - 1]] {
+                *node_uses.entry(node).or_default() += 1;
+            }
+        }
+
+        let mut builder = GraphBuilder::default();
+
+        for (way, attributes) in &ways {
+            let mut start = 0;
+
+            for end in 1..way.nodes.len() {
+                let is_vertex = node_uses.get(&way.nodes[end]).is_some_and(|&uses| uses > 1);
+                if is_vertex {
+                    let nodes = &way.nodes[start..=end];
+                    let geometry: LineString = nodes
+                        .iter()
+                        .filter_map(|node| coordinates.get(node))
+                        .map(|c| Coord { x: c.lon, y: c.lat })
+                        .collect();
+
+                    builder.add_segment(way.id, nodes, geometry, *attributes, &coordinates);
+                    start = end;
+                }
+            }
+        }
+
+        builder.build()
+    }
+
+    /// Gets the ID of the OSM way the edge is part of.
+    pub fn get_edge_way(&self, edge: OsmEdgeId) -> Result<i64, OsmGraphError> {
+        Ok(self.edge(edge)?.way)
+    }
+
+    /// Gets the ID of the OSM node of the vertex.
+    pub fn get_vertex_node(&self, vertex: OsmVertexId) -> Result<i64, OsmGraphError> {
+        Ok(self.vertex(vertex)?.node)
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    fn vertex(&self, vertex: OsmVertexId) -> Result<&Vertex, OsmGraphError> {
+        let found = self.vertices.get(vertex.0 as usize);
+        found.ok_or(OsmGraphError::VertexNotFound(vertex))
+    }
+
+    fn edge(&self, edge: OsmEdgeId) -> Result<&Edge, OsmGraphError> {
+        let found = self.edges.get(edge.0 as usize);
+        found.ok_or(OsmGraphError::EdgeNotFound(edge))
+    }
+}
+
+#[derive(Default)]
+struct GraphBuilder {
+    vertices: Vec<Vertex>,
+    vertex_ids: FxHashMap<i64, OsmVertexId>,
+    edges: Vec<Edge>,
+}
+
+impl GraphBuilder {
+    fn add_segment(
+        &mut self,
+        way: i64,
+        nodes: &[i64],
+        geometry: LineString,
+        attributes: WayAttributes,
+        coordinates: &FxHashMap<i64, Coordinate>,
+    ) {
+        let (Some(&first), Some(&last)) = (nodes.first(), nodes.last()) else {
+            return;
+        };
+
+        let start = self.vertex(first, coordinates);
+        let end = self.vertex(last, coordinates);
+
+        let length = {
+            use geo::Length;
+            Haversine.length(&geometry)
+        };
+
+        let edge = |vertices, geometry| Edge {
+            way,
+            vertices,
+            length: Length::from_meters(length),
+            frc: attributes.frc,
+            fow: attributes.fow,
+            geometry,
+        };
+
+        if attributes.oneway != Oneway::Backward {
+            self.add_edge(edge([start, end], geometry.clone()));
+        }
+
+        if attributes.oneway != Oneway::Forward {
+            let reversed = geometry.coords().rev().copied().collect();
+            self.add_edge(edge([end, start], reversed));
+        }
+    }
+
+    fn vertex(&mut self, node: i64, coordinates: &FxHashMap<i64, Coordinate>) -> OsmVertexId {
+        let vertices = &mut self.vertices;
+
+        *self.vertex_ids.entry(node).or_insert_with(|| {
+            let id = OsmVertexId(vertices.len() as u32);
+            vertices.push(Vertex {
+                node,
+                coordinate: StoredCoordinate::store(
+                    coordinates.get(&node).copied().unwrap_or_default(),
+                ),
+                exiting_edges: vec![],
+                entering_edges: vec![],
+            });
+            id
+        })
+    }
+
+    fn add_edge(&mut self, edge: Edge) {
+        let id = OsmEdgeId(self.edges.len() as u32);
+        let [start, end] = edge.vertices;
+
+        if let Some(vertex) = self.vertices.get_mut(start.0 as usize) {
+            vertex.exiting_edges.push(id);
+        }
+        if let Some(vertex) = self.vertices.get_mut(end.0 as usize) {
+            vertex.entering_edges.push(id);
+        }
+
+        self.edges.push(edge);
+    }
+
+    fn build(self) -> OsmGraph {
+        let geospatial_vertices = self
+            .vertices
+            .iter()
+            .enumerate()
+            .map(|(index, vertex)| (OsmVertexId(index as u32), vertex.coordinate.load()));
+        let geospatial_edges = self
+            .edges
+            .iter()
+            .enumerate()
+            .map(|(index, edge)| (OsmEdgeId(index as u32), edge.geometry.clone()));
+        let spatial_index = RTreeIndex::new(geospatial_vertices, geospatial_edges);
+
+        OsmGraph {
+            vertices: self.vertices,
+            edges: self.edges,
+            spatial_index,
+        }
+    }
+}
+
+/// Maps the highway tags of a way to its attributes, returns None if the way is not a road.
+fn way_attributes(tags: &[(String, String)]) -> Option<WayAttributes> {
+    let tag = |key: &str| {
+        tags.iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    };
+
+    if tag("area") == Some("yes") {
+        return None;
+    }
+
+    let highway = tag("highway")?;
+    let (frc, is_link) = match highway {
+        "motorway" => (Frc::Frc0, false),
+        "motorway_link" => (Frc::Frc0, true),
+        "trunk" => (Frc::Frc1, false),
+        "trunk_link" => (Frc::Frc1, true),
+        "primary" => (Frc::Frc2, false),
+        "primary_link" => (Frc::Frc2, true),
+        "secondary" => (Frc::Frc3, false),
+        "secondary_link" => (Frc::Frc3, true),
+        "tertiary" => (Frc::Frc4, false),
+        "tertiary_link" => (Frc::Frc4, true),
+        "unclassified" | "residential" => (Frc::Frc5, false),
+        "living_street" | "service" => (Frc::Frc6, false),
+        "road" => (Frc::Frc7, false),
+        _ => return None,
+    };
+
+    let is_roundabout = matches!(tag("junction"), Some("roundabout" | "circular"));
+    let is_motorway = matches!(highway, "motorway" | "motorway_link");
+
+    let oneway = match tag("oneway") {
+        Some("yes" | "true" | "1") => Oneway::Forward,
+        Some("-1" | "reverse") => Oneway::Backward,
+        Some("no" | "false" | "0") => Oneway::No,
+        _ if is_roundabout || is_motorway => Oneway::Forward,
+        _ => Oneway::No,
+    };
+
+    let fow = if is_roundabout {
+        Fow::Roundabout
+    } else if is_link {
+        Fow::SlipRoad
+    } else if highway == "motorway" {
+        Fow::Motorway
+    } else if oneway != Oneway::No && frc <= Frc::Frc2 {
+        // one-way major roads are usually the carriageways of a divided road
+        Fow::MultipleCarriageway
+    } else if highway == "road" {
+        Fow::Other
+    } else {
+        Fow::SingleCarriageway
+    };
+
+    Some(WayAttributes { frc, fow, oneway })
+}
+
+impl DirectedGraph for OsmGraph {
+    type Error = OsmGraphError;
+    type EdgeId = OsmEdgeId;
+    type VertexId = OsmVertexId;
+
+    fn get_vertex_coordinate(&self, vertex: Self::VertexId) -> Result<Coordinate, Self::Error> {
+        Ok(self.vertex(vertex)?.coordinate.load())
+    }
+
+    fn get_edge_start_vertex(&self, edge: Self::EdgeId) -> Result<Self::VertexId, Self::Error> {
+        Ok(self.edge(edge)?.vertices[0])
+    }
+
+    fn get_edge_end_vertex(&self, edge: Self::EdgeId) -> Result<Self::VertexId, Self::Error> {
+        Ok(self.edge(edge)?.vertices[1])
+    }
+
+    fn get_edge_length(&self, edge: Self::EdgeId) -> Result<Length, Self::Error> {
+        Ok(self.edge(edge)?.length)
+    }
+
+    fn get_edge_frc(&self, edge: Self::EdgeId) -> Result<Frc, Self::Error> {
+        Ok(self.edge(edge)?.frc)
+    }
+
+    fn get_edge_fow(&self, edge: Self::EdgeId) -> Result<Fow, Self::Error> {
+        Ok(self.edge(edge)?.fow)
+    }
+
+    fn vertex_exiting_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        let edges = &self.vertex(vertex)?.exiting_edges;
+        Ok(edges.iter().filter_map(|&edge| {
+            let end = self.edges.get(edge.0 as usize)?.vertices[1];
+            Some((edge, end))
+        }))
+    }
+
+    fn vertex_entering_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        let edges = &self.vertex(vertex)?.entering_edges;
+        Ok(edges.iter().filter_map(|&edge| {
+            let start = self.edges.get(edge.0 as usize)?.vertices[0];
+            Some((edge, start))
+        }))
+    }
+
+    fn nearest_vertices_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> Result<impl Iterator<Item = (Self::VertexId, Length)>, Self::Error> {
+        Ok(self
+            .spatial_index
+            .nearest_vertices_within_distance(coordinate, max_distance))
+    }
+
+    fn nearest_edges_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Length)>, Self::Error> {
+        Ok(self
+            .spatial_index
+            .nearest_edges_within_distance(coordinate, max_distance))
+    }
+
+    fn get_distance_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        coordinate: Coordinate,
+    ) -> Result<Length, Self::Error> {
+        let Edge {
+            geometry, length, ..
+        } = self.edge(edge)?;
+
+        let mut closest_distance = f64::INFINITY;
+        let mut distance_along_edge = 0.0;
+        let mut distance_acc = 0.0;
+
+        let point = Point::new(coordinate.lon, coordinate.lat);
+
+        for line in geometry.lines() {
+            if let Closest::SinglePoint(p) | Closest::Intersection(p) =
+                line.haversine_closest_point(&point)
+            {
+                let distance_to_line = Haversine.distance(point, p);
+
+                if distance_to_line < closest_distance {
+                    // this is the closest line segment of the whole geometry (so far)
+                    closest_distance = distance_to_line;
+                    distance_along_edge = distance_acc + Haversine.distance(line.start_point(), p);
+                }
+            }
+
+            use geo::Length;
+            distance_acc += Haversine.length(&line);
+        }
+
+        Ok(Length::from_meters(distance_along_edge).min(*length))
+    }
+
+    fn get_coordinate_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        distance: Length,
+    ) -> Result<Coordinate, Self::Error> {
+        let Edge {
+            geometry, length, ..
+        } = self.edge(edge)?;
+
+        let ratio = if *length > Length::ZERO {
+            (distance.meters() / length.meters()).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let coordinate = match geometry.point_at_ratio_from_start(&Haversine, ratio) {
+            Some(point) => Coordinate {
+                lon: point.x(),
+                lat: point.y(),
+            },
+            None => self.get_vertex_coordinate(self.get_edge_start_vertex(edge)?)?,
+        };
+
+        Ok(coordinate)
+    }
+
+    fn get_edge_bearing(
+        &self,
+        edge: Self::EdgeId,
+        distance_from_start: Length,
+        segment_length: Length,
+    ) -> Result<Bearing, Self::Error> {
+        let edge_length = self.get_edge_length(edge)?;
+        let distance_start = distance_from_start.clamp(Length::ZERO, edge_length);
+        let distance_end = (distance_start + segment_length).clamp(Length::ZERO, edge_length);
+
+        let c1 = self.get_coordinate_along_edge(edge, distance_start)?;
+        let c2 = self.get_coordinate_along_edge(edge, distance_end)?;
+
+        let degrees = {
+            use geo::Bearing;
+            let bearing = Haversine.bearing(Point::new(c1.lon, c1.lat), Point::new(c2.lon, c2.lat));
+            bearing.round().rem_euclid(360.0) as u16
+        };
+
+        Ok(Bearing::from_degrees(degrees))
+    }
+
+    fn get_edge_index(&self, edge: Self::EdgeId) -> Option<usize> {
+        Some(edge.0 as usize)
+    }
+
+    fn is_turn_restricted(
+        &self,
+        _start: Self::EdgeId,
+        _end: Self::EdgeId,
+    ) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::osm::pbf::tests::pbf_file;
+
+    fn node(id: i64, lon: f64, lat: f64) -> OsmNode {
+        OsmNode {
+            id,
+            coordinate: Coordinate { lon, lat },
+        }
+    }
+
+    fn way(id: i64, nodes: &[i64], tags: &[(&str, &str)]) -> OsmWay {
+        OsmWay {
+            id,
+            nodes: nodes.to_vec(),
+            tags: tags
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Crossing of a two-way primary road (1-2-3) and a one-way residential road (4-2-5).
+    fn crossing_graph() -> OsmGraph {
+        OsmGraph::from_elements(
+            [
+                node(1, 13.400, 52.500),
+                node(2, 13.401, 52.500),
+                node(3, 13.402, 52.500),
+                node(4, 13.401, 52.499),
+                node(5, 13.401, 52.501),
+                node(6, 13.402, 52.501),
+            ],
+            [
+                way(10, &[1, 2, 3], &[("highway", "primary")]),
+                way(
+                    11,
+                    &[4, 2, 5],
+                    &[("highway", "residential"), ("oneway", "yes")],
+                ),
+                way(12, &[3, 6, 5], &[("building", "yes")]),
+                way(13, &[5, 7], &[("highway", "service")]),
+            ],
+        )
+    }
+
+    fn vertex_of(graph: &OsmGraph, node: i64) -> OsmVertexId {
+        (0..graph.vertex_count() as u32)
+            .map(OsmVertexId)
+            .find(|&vertex| graph.get_vertex_node(vertex) == Ok(node))
+            .unwrap()
+    }
+
+    #[test]
+    fn osm_graph_001() {
+        let graph = crossing_graph();
+
+        assert_eq!(graph.vertex_count(), 5);
+        assert_eq!(graph.edge_count(), 6);
+
+        let v2 = vertex_of(&graph, 2);
+        assert_eq!(graph.vertex_degree(v2).unwrap(), 6);
+        assert_eq!(graph.vertex_exiting_edges(v2).unwrap().count(), 3);
+        assert_eq!(graph.vertex_entering_edges(v2).unwrap().count(), 3);
+
+        // the one-way road can only be traveled from 4 to 5
+        let v4 = vertex_of(&graph, 4);
+        let v5 = vertex_of(&graph, 5);
+        assert_eq!(graph.vertex_entering_edges(v4).unwrap().count(), 0);
+        assert_eq!(graph.vertex_exiting_edges(v5).unwrap().count(), 0);
+
+        let (edge, end) = graph.vertex_exiting_edges(v4).unwrap().next().unwrap();
+        assert_eq!(end, v2);
+        assert_eq!(graph.get_edge_way(edge), Ok(11));
+        assert_eq!(graph.get_edge_frc(edge), Ok(Frc::Frc5));
+        assert_eq!(graph.get_edge_fow(edge), Ok(Fow::SingleCarriageway));
+        assert!((graph.get_edge_length(edge).unwrap().meters() - 111.2).abs() < 0.5);
+
+        let v1 = vertex_of(&graph, 1);
+        let (edge, end) = graph.vertex_exiting_edges(v1).unwrap().next().unwrap();
+        assert_eq!(end, v2);
+        assert_eq!(graph.get_edge_frc(edge), Ok(Frc::Frc2));
+        assert_eq!(
+            graph.get_edge_bearing(edge, Length::ZERO, Length::from_meters(20.0)),
+            Ok(Bearing::from_degrees(90))
+        );
+
+        assert_eq!(
+            graph.get_vertex_coordinate(OsmVertexId(5)),
+            Err(OsmGraphError::VertexNotFound(OsmVertexId(5)))
+        );
+        assert_eq!(
+            graph.get_edge_way(OsmEdgeId(6)),
+            Err(OsmGraphError::EdgeNotFound(OsmEdgeId(6)))
+        );
+    }
+
+    #[test]
+    fn osm_graph_002() {
+        let graph = crossing_graph();
+
+        let coordinate = Coordinate {
+            lon: 13.4012,
+            lat: 52.5001,
+        };
+
+        let (vertex, distance) = graph
+            .nearest_vertices_within_distance(coordinate, Length::from_meters(50.0))
+            .unwrap()
+            .next()
+            .unwrap();
+        assert_eq!(vertex, vertex_of(&graph, 2));
+        assert!(distance < Length::from_meters(20.0));
+
+        let edges: Vec<_> = graph
+            .nearest_edges_within_distance(coordinate, Length::from_meters(15.0))
+            .unwrap()
+            .map(|(edge, _)| graph.get_edge_way(edge).unwrap())
+            .collect();
+        assert_eq!(edges, [10, 10, 11]);
+
+        let (v2, v3) = (vertex_of(&graph, 2), vertex_of(&graph, 3));
+        let (edge, _) = graph
+            .vertex_exiting_edges(v2)
+            .unwrap()
+            .find(|&(_, end)| end == v3)
+            .unwrap();
+
+        let distance = graph.get_distance_along_edge(edge, coordinate).unwrap();
+        assert!((distance.meters() - 13.5).abs() < 0.5);
+
+        let coordinate = graph.get_coordinate_along_edge(edge, distance).unwrap();
+        assert!((coordinate.lon - 13.4012).abs() < 1e-5);
+        assert!((coordinate.lat - 52.5000).abs() < 1e-5);
+    }
+
+    #[test]
+    fn osm_graph_003() {
+        let data = pbf_file(
+            &["OsmSchema-V0.6", "DenseNodes"],
+            &[(1, 13.4, 52.5), (2, 13.41, 52.5), (3, 13.42, 52.5)],
+            &[(10, &[1, 2, 3], &[("highway", "motorway")])],
+        );
+
+        let graph = OsmGraph::from_pbf(data.as_slice()).unwrap();
+        assert_eq!(graph.vertex_count(), 2);
+        assert_eq!(graph.edge_count(), 1);
+        assert_eq!(graph.get_edge_frc(OsmEdgeId(0)), Ok(Frc::Frc0));
+        assert_eq!(graph.get_edge_fow(OsmEdgeId(0)), Ok(Fow::Motorway));
+    }
+}
@@ -0,0 +1,98 @@
+//! Decompression of the zlib (RFC 1950) compressed blobs of the OSM PBF files.
+
+use miniz_oxide::inflate::decompress_to_vec_zlib_with_limit;
+
+use crate::OsmError;
+
+/// Decompresses the zlib stream, failing if the output grows beyond the max size (so that a
+/// corrupt or malicious blob can't exhaust the memory).
+pub(crate) fn zlib_decompress(data: &[u8], max_size: usize) -> Result<Vec<u8>, OsmError> {
+    let [cmf, flg, ..] = *data else {
+        return Err(OsmError::InvalidBlob);
+    };
+
+    // preset dictionaries are not supported (PBF writers never use them)
+    let is_deflate = cmf & 0x0f == 8 && cmf >> 4 <= 7;
+    let has_dictionary = flg & 0x20 != 0;
+    if !is_deflate || (u16::from(cmf) << 8 | u16::from(flg)) % 31 != 0 || has_dictionary {
+        return Err(OsmError::UnsupportedCompression);
+    }
+
+    decompress_to_vec_zlib_with_limit(data, max_size).map_err(|_| OsmError::InvalidBlob)
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn osm_zlib_decompress_001() {
+        let stored = [
+            0x78, 0x01, 0x01, 0x06, 0x00, 0xf9, 0xff, 0x4f, 0x70, 0x65, 0x6e, 0x4c, 0x52, 0x07,
+            0xd8, 0x02, 0x31,
+        ];
+        assert_eq!(zlib_decompress(&stored, 1024).unwrap(), b"OpenLR");
+
+        let fixed = [
+            0x78, 0xda, 0xf3, 0x2f, 0x48, 0xcd, 0xf3, 0x09, 0x02, 0x00, 0x07, 0xd8, 0x02, 0x31,
+        ];
+        assert_eq!(zlib_decompress(&fixed, 1024).unwrap(), b"OpenLR");
+    }
+
+    #[test]
+    fn osm_zlib_decompress_002() {
+        let dynamic = [
+            0x78, 0xda, 0xed, 0x94, 0x3b, 0x0e, 0x03, 0x21, 0x0c, 0x44, 0xaf, 0x14, 0x60, 0xf9,
+            0x29, 0xca, 0x61, 0x52, 0x65, 0xf7, 0x04, 0x51, 0x6e, 0x9f, 0x26, 0xe2, 0xbd, 0xc2,
+            0x65, 0x4a, 0x3a, 0x0b, 0xcc, 0x30, 0x63, 0x7b, 0x7c, 0x5e, 0xaf, 0xf3, 0xfd, 0xfc,
+            0x3c, 0x6e, 0xf7, 0xf3, 0x17, 0xa5, 0x15, 0x1d, 0x2b, 0x9a, 0xdc, 0xb6, 0x15, 0xe6,
+            0xba, 0xc2, 0xc2, 0xe9, 0x41, 0x6e, 0x03, 0x60, 0x80, 0x5a, 0x00, 0xe0, 0xfe, 0xe8,
+            0x2b, 0xec, 0x99, 0x04, 0x1e, 0xf1, 0xbe, 0x71, 0x3a, 0xc5, 0x00, 0xd8, 0x2e, 0x2d,
+            0xe4, 0x56, 0x12, 0xa6, 0xd8, 0x4a, 0xa3, 0x84, 0x2b, 0x97, 0x84, 0x0a, 0xee, 0x80,
+            0x0d, 0x14, 0x32, 0x1a, 0x06, 0x09, 0x15, 0x80, 0x0c, 0x9b, 0xa1, 0x2a, 0xa9, 0x34,
+            0x80, 0x25, 0x4e, 0x07, 0x74, 0x9b, 0x98, 0xf3, 0x45, 0x01, 0x37, 0x89, 0x19, 0x1a,
+            0x90, 0x33, 0xf8, 0xa1, 0x43, 0xa1, 0xeb, 0xb4, 0x84, 0x61, 0xf8, 0x4c, 0x60, 0xfa,
+            0xa2, 0x45, 0x64, 0x44, 0x51, 0xc4, 0x25, 0x47, 0x22, 0x53, 0x58, 0x10, 0x95, 0x49,
+            0xc5, 0x53, 0x49, 0x55, 0x68, 0x95, 0x5f, 0x4d, 0x51, 0xab, 0x46, 0xd4, 0x54, 0xb5,
+            0xda, 0x03, 0x90, 0xc2, 0x61, 0x69, 0xd1, 0x60, 0x69, 0xdc, 0x34, 0x84, 0x1a, 0x4d,
+            0x0d, 0x6c, 0x0b, 0x87, 0x3b, 0x47, 0x36, 0x90, 0x39, 0x64, 0x99, 0x12, 0x99, 0x4b,
+            0x96, 0x93, 0x11, 0x4b, 0x68, 0x5a, 0x59, 0x79, 0x06, 0x9e, 0x07, 0x74, 0xef, 0x86,
+            0xbd, 0x1b, 0xf6, 0x6e, 0xd8, 0xbb, 0xe1, 0x7f, 0xbb, 0xe1, 0x0b, 0xd4, 0x27, 0xf5,
+            0x73,
+        ];
+
+        let expected: String = (0..200)
+            .map(|i| format!("highway={};", i * i % 97))
+            .collect();
+
+        let data = zlib_decompress(&dynamic, 1024 * 1024).unwrap();
+        assert_eq!(String::from_utf8(data).unwrap(), expected);
+    }
+
+    #[test]
+    fn osm_zlib_decompress_003() {
+        // not a zlib header
+        assert_eq!(
+            zlib_decompress(&[0x1f, 0x8b, 0x08], 1024),
+            Err(OsmError::UnsupportedCompression)
+        );
+
+        // truncated stream
+        let fixed = [0x78, 0xda, 0xf3, 0x2f, 0x48, 0xcd];
+        assert_eq!(zlib_decompress(&fixed, 1024), Err(OsmError::InvalidBlob));
+
+        // wrong checksum
+        let fixed = [
+            0x78, 0xda, 0xf3, 0x2f, 0x48, 0xcd, 0xf3, 0x09, 0x02, 0x00, 0x07, 0xd8, 0x02, 0x32,
+        ];
+        assert_eq!(zlib_decompress(&fixed, 1024), Err(OsmError::InvalidBlob));
+
+        // larger than the max size
+        let fixed = [
+            0x78, 0xda, 0xf3, 0x2f, 0x48, 0xcd, 0xf3, 0x09, 0x02, 0x00, 0x07, 0xd8, 0x02, 0x31,
+        ];
+        assert_eq!(zlib_decompress(&fixed, 5), Err(OsmError::InvalidBlob));
+    }
+}
@@ -0,0 +1,425 @@
+//! Reader of the OSM PBF format: a sequence of (optionally compressed) blobs, each one being
+//! either the file header or a block of OSM primitives (nodes, ways and relations).
+//! See <https://wiki.openstreetmap.org/wiki/PBF_Format>.
+
+use std::io::{ErrorKind, Read};
+
+use prost::Message;
+
+use crate::graph::osm::inflate::zlib_decompress;
+use crate::{Coordinate, OsmError, OsmNode, OsmWay};
+
+/// Required features of the file that the reader supports.
+const SUPPORTED_FEATURES: [&str; 2] = ["OsmSchema-V0.6", "DenseNodes"];
+
+/// Blob headers larger than this are not valid (as defined by the format).
+const MAX_BLOB_HEADER_SIZE: usize = 64 * 1024;
+/// Blobs larger than this are not valid (as defined by the format).
+const MAX_BLOB_SIZE: usize = 32 * 1024 * 1024;
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct BlobHeader {
+    #[prost(string, tag = "1")]
+    pub r#type: String,
+    #[prost(int32, tag = "3")]
+    pub datasize: i32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct Blob {
+    #[prost(bytes = "vec", optional, tag = "1")]
+    pub raw: Option<Vec<u8>>,
+    #[prost(int32, optional, tag = "2")]
+    pub raw_size: Option<i32>,
+    #[prost(bytes = "vec", optional, tag = "3")]
+    pub zlib_data: Option<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct HeaderBlock {
+    #[prost(string, repeated, tag = "4")]
+    pub required_features: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct PrimitiveBlock {
+    #[prost(message, optional, tag = "1")]
+    pub stringtable: Option<StringTable>,
+    #[prost(message, repeated, tag = "2")]
+    pub primitivegroup: Vec<PrimitiveGroup>,
+    #[prost(int32, optional, tag = "17")]
+    pub granularity: Option<i32>,
+    #[prost(int64, optional, tag = "19")]
+    pub lat_offset: Option<i64>,
+    #[prost(int64, optional, tag = "20")]
+    pub lon_offset: Option<i64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct StringTable {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub s: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct PrimitiveGroup {
+    #[prost(message, repeated, tag = "1")]
+    pub nodes: Vec<Node>,
+    #[prost(message, optional, tag = "2")]
+    pub dense: Option<DenseNodes>,
+    #[prost(message, repeated, tag = "3")]
+    pub ways: Vec<Way>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct Node {
+    #[prost(sint64, tag = "1")]
+    pub id: i64,
+    #[prost(sint64, tag = "8")]
+    pub lat: i64,
+    #[prost(sint64, tag = "9")]
+    pub lon: i64,
+}
+
+/// Nodes whose IDs and coordinates are delta coded.
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct DenseNodes {
+    #[prost(sint64, repeated, tag = "1")]
+    pub id: Vec<i64>,
+    #[prost(sint64, repeated, tag = "8")]
+    pub lat: Vec<i64>,
+    #[prost(sint64, repeated, tag = "9")]
+    pub lon: Vec<i64>,
+}
+
+/// Way whose node references are delta coded, and whose tags are indices of the string table.
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct Way {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    #[prost(uint32, repeated, tag = "2")]
+    pub keys: Vec<u32>,
+    #[prost(uint32, repeated, tag = "3")]
+    pub vals: Vec<u32>,
+    #[prost(sint64, repeated, tag = "8")]
+    pub refs: Vec<i64>,
+}
+
+/// Reads all the nodes and the ways of the PBF data.
+/// Only the ways tagged as highways keep their tags, the tags of the other ways are dropped.
+pub(crate) fn read_pbf(mut reader: impl Read) -> Result<(Vec<OsmNode>, Vec<OsmWay>), OsmError> {
+    let (mut nodes, mut ways) = (vec![], vec![]);
+
+    while let Some(header) = read_blob_header(&mut reader)? {
+        let size = usize::try_from(header.datasize).map_err(|_| OsmError::InvalidBlob)?;
+        if size > MAX_BLOB_SIZE {
+            return Err(OsmError::InvalidBlob);
+        }
+
+        let blob = Blob::decode(read_exact(&mut reader, size)?.as_slice())?;
+
+        match header.r#type.as_str() {
+            "OSMHeader" => {
+                let block = HeaderBlock::decode(blob_data(blob)?.as_slice())?;
+                let is_supported = |feature: &String| SUPPORTED_FEATURES.contains(&&**feature);
+                if !block.required_features.iter().all(is_supported) {
+                    return Err(OsmError::UnsupportedFeature);
+                }
+            }
+            "OSMData" => {
+                let block = PrimitiveBlock::decode(blob_data(blob)?.as_slice())?;
+                read_primitive_block(block, &mut nodes, &mut ways);
+            }
+            // unknown blobs must be skipped
+            _ => continue,
+        }
+    }
+
+    Ok((nodes, ways))
+}
+
+fn read_blob_header(reader: &mut impl Read) -> Result<Option<BlobHeader>, OsmError> {
+    let mut size = [0; 4];
+    match reader.read_exact(&mut size) {
+        Ok(()) => (),
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+
+    let size = u32::from_be_bytes(size) as usize;
+    if size > MAX_BLOB_HEADER_SIZE {
+        return Err(OsmError::InvalidBlob);
+    }
+
+    Ok(Some(BlobHeader::decode(
+        read_exact(reader, size)?.as_slice(),
+    )?))
+}
+
+fn read_exact(reader: &mut impl Read, size: usize) -> Result<Vec<u8>, OsmError> {
+    let mut buffer = vec![0; size];
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
+
+fn blob_data(blob: Blob) -> Result<Vec<u8>, OsmError> {
+    match blob {
+        Blob { raw: Some(raw), .. } => Ok(raw),
+        Blob {
+            zlib_data: Some(data),
+            ..
+        } => zlib_decompress(&data, MAX_BLOB_SIZE),
+        Blob { .. } => Err(OsmError::UnsupportedCompression),
+    }
+}
+
+fn read_primitive_block(block: PrimitiveBlock, nodes: &mut Vec<OsmNode>, ways: &mut Vec<OsmWay>) {
+    let granularity = i64::from(block.granularity.unwrap_or(100));
+    let (lat_offset, lon_offset) = (
+        block.lat_offset.unwrap_or_default(),
+        block.lon_offset.unwrap_or_default(),
+    );
+
+    let coordinate = |lat: i64, lon: i64| Coordinate {
+        lon: 1e-9 * (lon_offset + granularity * lon) as f64,
+        lat: 1e-9 * (lat_offset + granularity * lat) as f64,
+    };
+
+    let strings = block.stringtable.map(|table| table.s).unwrap_or_default();
+    let string = |index: u32| {
+        let bytes = strings.get(index as usize).map(Vec::as_slice);
+        String::from_utf8_lossy(bytes.unwrap_or_default()).into_owned()
+    };
+
+    for group in block.primitivegroup {
+        nodes.extend(group.nodes.into_iter().map(|node| OsmNode {
+            id: node.id,
+            coordinate: coordinate(node.lat, node.lon),
+        }));
+
+        if let Some(dense) = group.dense {
+            let (mut id, mut lat, mut lon) = (0, 0, 0);
+
+            for ((id_delta, lat_delta), lon_delta) in
+                dense.id.into_iter().zip(dense.lat).zip(dense.lon)
+            {
+                id += id_delta;
+                lat += lat_delta;
+                lon += lon_delta;
+                nodes.push(OsmNode {
+                    id,
+                    coordinate: coordinate(lat, lon),
+                });
+            }
+        }
+
+        for way in group.ways {
+            let tags: Vec<_> = way
+                .keys
+                .iter()
+                .zip(&way.vals)
+                .map(|(&key, &value)| (string(key), string(value)))
+                .collect();
+
+            if !tags.iter().any(|(key, _)| key == "highway") {
+                continue;
+            }
+
+            let refs = way.refs.into_iter().scan(0, |id, delta| {
+                *id += delta;
+                Some(*id)
+            });
+
+            ways.push(OsmWay {
+                id: way.id,
+                nodes: refs.collect(),
+                tags,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    fn pbf_blob(r#type: &str, data: &[u8], compressed: bool) -> Vec<u8> {
+        let blob = if compressed {
+            Blob {
+                raw: None,
+                raw_size: Some(data.len() as i32),
+                zlib_data: Some(miniz_oxide::deflate::compress_to_vec_zlib(data, 6)),
+            }
+        } else {
+            Blob {
+                raw: Some(data.to_vec()),
+                ..Default::default()
+            }
+        };
+
+        let blob = blob.encode_to_vec();
+        let header = BlobHeader {
+            r#type: r#type.to_string(),
+            datasize: blob.len() as i32,
+        }
+        .encode_to_vec();
+
+        [(header.len() as u32).to_be_bytes().to_vec(), header, blob].concat()
+    }
+
+    /// Way of a test file: its ID, node IDs and tags.
+    pub(crate) type PbfWay<'a> = (i64, &'a [i64], &'a [(&'a str, &'a str)]);
+
+    pub(crate) fn pbf_file(
+        required_features: &[&str],
+        nodes: &[(i64, f64, f64)],
+        ways: &[PbfWay],
+    ) -> Vec<u8> {
+        let header = HeaderBlock {
+            required_features: required_features.iter().map(|f| f.to_string()).collect(),
+        };
+
+        let mut strings = vec![String::new()];
+        let mut string_index = |s: &str| match strings.iter().position(|string| string == s) {
+            Some(index) => index as u32,
+            None => {
+                strings.push(s.to_string());
+                strings.len() as u32 - 1
+            }
+        };
+
+        let ways = ways
+            .iter()
+            .map(|&(id, refs, tags)| Way {
+                id,
+                keys: tags.iter().map(|(k, _)| string_index(k)).collect(),
+                vals: tags.iter().map(|(_, v)| string_index(v)).collect(),
+                refs: refs
+                    .iter()
+                    .scan(0, |previous, &id| {
+                        let delta = id - *previous;
+                        *previous = id;
+                        Some(delta)
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        // the first node is plain, the others are dense (delta coded)
+        let to_nanodegrees = |degrees: f64| (degrees * 1e7).round() as i64;
+        let (first, rest) = nodes.split_first().unwrap();
+        let node = Node {
+            id: first.0,
+            lon: to_nanodegrees(first.1),
+            lat: to_nanodegrees(first.2),
+        };
+
+        let delta = |values: Vec<i64>| {
+            let deltas = values.windows(2).map(|w| w[1] - w[0]);
+            values.first().copied().into_iter().chain(deltas).collect()
+        };
+
+        let dense = DenseNodes {
+            id: delta(rest.iter().map(|n| n.0).collect()),
+            lon: delta(rest.iter().map(|n| to_nanodegrees(n.1)).collect()),
+            lat: delta(rest.iter().map(|n| to_nanodegrees(n.2)).collect()),
+        };
+
+        let data = PrimitiveBlock {
+            stringtable: Some(StringTable {
+                s: strings.into_iter().map(String::into_bytes).collect(),
+            }),
+            primitivegroup: vec![
+                PrimitiveGroup {
+                    nodes: vec![node],
+                    dense: Some(dense),
+                    ways: vec![],
+                },
+                PrimitiveGroup {
+                    ways,
+                    ..Default::default()
+                },
+            ],
+            granularity: None,
+            lat_offset: None,
+            lon_offset: None,
+        };
+
+        [
+            pbf_blob("OSMHeader", &header.encode_to_vec(), false),
+            pbf_blob("OSMData", &data.encode_to_vec(), true),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn osm_read_pbf_001() {
+        let data = pbf_file(
+            &SUPPORTED_FEATURES,
+            &[(1, 13.4, 52.5), (2, 13.41, 52.5), (3, 13.42, 52.51)],
+            &[
+                (10, &[1, 2, 3], &[("highway", "primary"), ("name", "A")]),
+                (11, &[3, 1], &[("building", "yes")]),
+            ],
+        );
+
+        let (nodes, ways) = read_pbf(data.as_slice()).unwrap();
+
+        assert_eq!(
+            nodes,
+            [
+                OsmNode {
+                    id: 1,
+                    coordinate: Coordinate {
+                        lon: 13.4,
+                        lat: 52.5
+                    }
+                },
+                OsmNode {
+                    id: 2,
+                    coordinate: Coordinate {
+                        lon: 13.41,
+                        lat: 52.5
+                    }
+                },
+                OsmNode {
+                    id: 3,
+                    coordinate: Coordinate {
+                        lon: 13.42,
+                        lat: 52.51
+                    }
+                }
+            ]
+        );
+
+        assert_eq!(
+            ways,
+            [OsmWay {
+                id: 10,
+                nodes: vec![1, 2, 3],
+                tags: vec![
+                    ("highway".into(), "primary".into()),
+                    ("name".into(), "A".into())
+                ]
+            }]
+        );
+    }
+
+    #[test]
+    fn osm_read_pbf_002() {
+        let data = pbf_file(
+            &["OsmSchema-V0.6", "HistoricalInformation"],
+            &[(1, 0.0, 0.0)],
+            &[],
+        );
+        assert_eq!(read_pbf(data.as_slice()), Err(OsmError::UnsupportedFeature));
+
+        let data = pbf_file(&SUPPORTED_FEATURES, &[(1, 0.0, 0.0)], &[]);
+        assert_eq!(
+            read_pbf(&data[..data.len() - 1]),
+            Err(OsmError::IO(ErrorKind::UnexpectedEof))
+        );
+    }
+}
@@ -1,8 +1,12 @@
+use std::fmt::Debug;
+use std::ops::{Neg, Range};
+
 use rustc_hash::FxHashSet;
 use tracing::debug;
 
-use crate::{DirectedGraph, Length};
+use crate::{Coordinate, DirectedGraph, Length, LineLocation, LocationError};
 
+/// Sequence of directed edges of the graph and their total length.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Path<EdgeId> {
     pub length: Length,
@@ -18,6 +22,88 @@ impl<EdgeId> Default for Path<EdgeId> {
     }
 }
 
+impl<EdgeId: Copy> Path<EdgeId> {
+    /// Creates the path of the given edges, its length is the sum of the edges length.
+    pub fn from_edges<G>(graph: &G, edges: Vec<EdgeId>) -> Result<Self, G::Error>
+    where
+        G: DirectedGraph<EdgeId = EdgeId>,
+    {
+        let length = path_length(graph, &edges)?;
+        Ok(Self { length, edges })
+    }
+
+    /// Appends the other path at the end of this path.
+    /// The paths are not checked to be connected.
+    pub fn concat(mut self, other: Self) -> Self {
+        self.length += other.length;
+        self.edges.extend(other.edges);
+        self
+    }
+
+    /// Gets the path that goes in the opposite direction following the signed-edge convention:
+    /// the edge that goes in the opposite direction of the edge `e` is identified by `-e`.
+    /// The opposite edges are not checked to exist in the graph.
+    pub fn reverse(&self) -> Self
+    where
+        EdgeId: Neg<Output = EdgeId>,
+    {
+        Self {
+            length: self.length,
+            edges: self.edges.iter().rev().map(|&e| -e).collect(),
+        }
+    }
+
+    /// Gets the part of the path within the given range of distances from the path start, as a
+    /// line location where the offsets are already trimmed (the range is clamped to the path).
+    pub fn slice<G>(
+        &self,
+        graph: &G,
+        range: Range<Length>,
+    ) -> Result<LineLocation<EdgeId>, LocationError<G::Error>>
+    where
+        G: DirectedGraph<EdgeId = EdgeId>,
+        EdgeId: PartialEq + Debug,
+    {
+        let pos_offset = range.start.max(Length::ZERO);
+        let neg_offset = (self.length - range.end).max(Length::ZERO);
+
+        LineLocation {
+            path: self.edges.clone(),
+            pos_offset,
+            neg_offset,
+        }
+        .trim(graph)
+    }
+
+    /// Gets the coordinates of the path: the start vertex of the first edge and the end vertex of
+    /// every edge. The geometry of the edges is approximated by the segments between their
+    /// vertices.
+    pub fn geometry<G>(&self, graph: &G) -> Result<Vec<Coordinate>, G::Error>
+    where
+        G: DirectedGraph<EdgeId = EdgeId>,
+    {
+        let Some(&first_edge) = self.edges.first() else {
+            return Ok(vec![]);
+        };
+
+        let mut coordinates = Vec::with_capacity(self.edges.len() + 1);
+        coordinates.push(graph.get_vertex_coordinate(graph.get_edge_start_vertex(first_edge)?)?);
+
+        for &edge in &self.edges {
+            coordinates.push(graph.get_vertex_coordinate(graph.get_edge_end_vertex(edge)?)?);
+        }
+
+        Ok(coordinates)
+    }
+}
+
+/// Returns the length of the path: the sum of the length of its edges.
+pub fn path_length<G: DirectedGraph>(graph: &G, path: &[G::EdgeId]) -> Result<Length, G::Error> {
+    path.iter().try_fold(Length::ZERO, |acc, &edge| {
+        Ok(acc + graph.get_edge_length(edge)?)
+    })
+}
+
 /// Returns true only if the path contains a loop when considering positive and negative offsets.
 pub fn is_path_loop<G: DirectedGraph>(
     graph: &G,
@@ -55,27 +141,65 @@ pub fn is_path_loop<G: DirectedGraph>(
 }
 
 /// Returns true only if all the edges of the path are sequentially connected in the given graph.
-/// If turning between any of the sequentlay edges is not allowed returns false.
+/// If turning between any of the sequential edges is not allowed returns false.
 pub fn is_path_connected<G: DirectedGraph>(
     graph: &G,
     path: &[G::EdgeId],
 ) -> Result<bool, G::Error> {
-    for window in path.windows(2) {
+    Ok(find_path_disconnection(graph, path)?.is_none())
+}
+
+/// Returns the index of the first edge of the path that is not connected to its previous edge
+/// (or that cannot be reached by turning from its previous edges), if any.
+pub fn find_path_disconnection<G: DirectedGraph>(
+    graph: &G,
+    path: &[G::EdgeId],
+) -> Result<Option<usize>, G::Error> {
+    for (i, window) in path.windows(2).enumerate() {
         let [e1, e2] = [window[0], window[1]];
 
-        if graph.is_turn_restricted(e1, e2)? {
-            return Ok(false);
+        if is_turn_restricted_after(graph, path[..=i].iter().rev().copied(), e2)? {
+            return Ok(Some(i + 1));
         }
 
         if !graph
             .vertex_exiting_edges(graph.get_edge_end_vertex(e1)?)?
             .any(|(e, _)| e == e2)
         {
-            return Ok(false);
+            return Ok(Some(i + 1));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Returns true if the edge cannot be traveled right after the previous edges (given from the last
+/// traveled one backwards), because of a turn restriction or a via-way restriction.
+pub fn is_turn_restricted_after<G: DirectedGraph>(
+    graph: &G,
+    previous: impl Iterator<Item = G::EdgeId> + Clone,
+    edge: G::EdgeId,
+) -> Result<bool, G::Error> {
+    let Some(last) = previous.clone().next() else {
+        return Ok(false);
+    };
+
+    if graph.is_turn_restricted(last, edge)? {
+        return Ok(true);
+    }
+
+    for restriction in graph.get_via_way_restrictions(edge)? {
+        let mut previous = previous.clone();
+        let matches = restriction
+            .iter()
+            .rev()
+            .all(|&e| previous.next() == Some(e));
+        if matches && !restriction.is_empty() {
+            return Ok(true);
         }
     }
 
-    Ok(true)
+    Ok(false)
 }
 
 /// Returns true if a node is valid and therefore the path starting/ending from/into this node
@@ -106,10 +230,15 @@ pub fn is_node_valid<G: DirectedGraph>(graph: &G, vertex: G::VertexId) -> Result
 
             for (index, &edge) in edges.iter().skip(1).enumerate() {
                 if is_opposite_direction(graph, edges[0], edge)? {
+                    let adjacent_vertex = get_adjacent_vertex(graph, edges[0], vertex)?;
+
                     // check the remaining edges
                     edges.swap_remove(index + 1);
                     edges.swap_remove(0);
-                    return Ok(!is_opposite_direction(graph, edges[0], edges[1])?);
+
+                    // parallel edges to the same adjacent vertex are alternative lines
+                    return Ok(!is_opposite_direction(graph, edges[0], edges[1])?
+                        || get_adjacent_vertex(graph, edges[0], vertex)? == adjacent_vertex);
                 }
             }
 
@@ -119,18 +248,64 @@ pub fn is_node_valid<G: DirectedGraph>(graph: &G, vertex: G::VertexId) -> Result
     }
 }
 
+/// Max difference of the lengths and of the geometries of the two directions of the same line,
+/// that the maps may store separately with different rounding.
+const OPPOSITE_DIRECTION_TOLERANCE: Length = Length::from_meters(1.0);
+
 /// Returns true only if the first edge is the directed edge that goes into the opposite
 /// direction of the second edge, and they both connect at the same vertices.
+/// Parallel edges (multigraph) that connect the same vertices are distinct lines, and therefore
+/// not considered opposite directions of the same line: the opposite directions must have the same
+/// length and the same coordinate halfway along them (both within 1 meter).
 pub fn is_opposite_direction<G: DirectedGraph>(
     graph: &G,
     e1: G::EdgeId,
     e2: G::EdgeId,
 ) -> Result<bool, G::Error> {
     // n1 < ==== > n2
-    let is_opposite = graph.get_edge_start_vertex(e1)? == graph.get_edge_end_vertex(e2)?
+    let is_connected = graph.get_edge_start_vertex(e1)? == graph.get_edge_end_vertex(e2)?
         && graph.get_edge_end_vertex(e1)? == graph.get_edge_start_vertex(e2)?;
+    if !is_connected {
+        return Ok(false);
+    }
+
+    let (length1, length2) = (graph.get_edge_length(e1)?, graph.get_edge_length(e2)?);
+    if length1.abs_diff(length2) > OPPOSITE_DIRECTION_TOLERANCE {
+        return Ok(false);
+    }
+
+    let middle1 = graph.get_coordinate_along_edge(e1, length1 / 2.0)?;
+    let middle2 = graph.get_coordinate_along_edge(e2, length2 / 2.0)?;
+    Ok(middle1.distance(&middle2) <= OPPOSITE_DIRECTION_TOLERANCE)
+}
 
-    Ok(is_opposite)
+/// Gets the edge that goes into the opposite direction of the given edge (see
+/// [`is_opposite_direction`]), if the edge is not one-way.
+pub fn find_opposite_edge<G: DirectedGraph>(
+    graph: &G,
+    edge: G::EdgeId,
+) -> Result<Option<G::EdgeId>, G::Error> {
+    for (exiting_edge, _) in graph.vertex_exiting_edges(graph.get_edge_end_vertex(edge)?)? {
+        if is_opposite_direction(graph, exiting_edge, edge)? {
+            return Ok(Some(exiting_edge));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Gets the vertex of the edge that is not the given vertex.
+fn get_adjacent_vertex<G: DirectedGraph>(
+    graph: &G,
+    edge: G::EdgeId,
+    vertex: G::VertexId,
+) -> Result<G::VertexId, G::Error> {
+    let start = graph.get_edge_start_vertex(edge)?;
+    if start == vertex {
+        graph.get_edge_end_vertex(edge)
+    } else {
+        Ok(start)
+    }
 }
 
 #[cfg(test)]
@@ -138,7 +313,12 @@ mod tests {
     use test_log::test;
 
     use super::*;
-    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph, VertexId};
+    use crate::graph::network::{LineDirection, NetworkLine};
+    use crate::graph::tests::{
+        EdgeId, NETWORK_GRAPH, NetworkGraph, PARALLEL_EDGE, PARALLEL_NETWORK_GRAPH,
+        RESTRICTED_NETWORK_GRAPH, VertexId,
+    };
+    use crate::{Fow, Frc};
 
     #[test]
     fn is_opposite_direction_001() {
@@ -154,6 +334,68 @@ mod tests {
         assert!(is_opposite_direction(graph, EdgeId(8345025), EdgeId(-8345025)).unwrap());
     }
 
+    #[test]
+    fn is_opposite_direction_tolerance() {
+        let (a, b) = (
+            Coordinate {
+                lon: 13.46112,
+                lat: 52.51711,
+            },
+            Coordinate {
+                lon: 13.46312,
+                lat: 52.51711,
+            },
+        );
+        let middle = Coordinate {
+            lon: 13.46212,
+            lat: 52.51751,
+        };
+        let length = Length::from_meters(135.5);
+        let line = |id, start, end, length, geometry| NetworkLine {
+            id: EdgeId(id),
+            start: VertexId(start),
+            end: VertexId(end),
+            length,
+            frc: Frc::Frc5,
+            fow: Fow::SingleCarriageway,
+            direction: LineDirection::Forward,
+            geometry,
+        };
+
+        let graph = NetworkGraph::from_lines([
+            line(1, 1, 2, length, vec![a, b]),
+            // the opposite direction, whose length differs by rounding
+            line(
+                2,
+                2,
+                1,
+                Length::from_meters(f64::from_bits(length.meters().to_bits() + 1)),
+                vec![b, a],
+            ),
+            // a parallel line with the same length that deviates from the first one
+            line(3, 2, 1, length, vec![b, middle, a]),
+        ]);
+
+        assert!(is_opposite_direction(&graph, EdgeId(1), EdgeId(2)).unwrap());
+        assert!(is_opposite_direction(&graph, EdgeId(2), EdgeId(1)).unwrap());
+        assert!(!is_opposite_direction(&graph, EdgeId(1), EdgeId(3)).unwrap());
+        assert_eq!(
+            find_opposite_edge(&graph, EdgeId(1)).unwrap(),
+            Some(EdgeId(2))
+        );
+    }
+
+    #[test]
+    fn is_opposite_direction_002() {
+        let graph: &NetworkGraph = &PARALLEL_NETWORK_GRAPH;
+        let reversed_parallel_edge = EdgeId(-PARALLEL_EDGE.0);
+
+        assert!(is_opposite_direction(graph, EdgeId(8717175), EdgeId(-8717175)).unwrap());
+        assert!(is_opposite_direction(graph, PARALLEL_EDGE, reversed_parallel_edge).unwrap());
+        assert!(!is_opposite_direction(graph, EdgeId(8717175), reversed_parallel_edge).unwrap());
+        assert!(!is_opposite_direction(graph, PARALLEL_EDGE, EdgeId(-8717175)).unwrap());
+    }
+
     #[test]
     fn is_valid_node_001() {
         let graph: &NetworkGraph = &NETWORK_GRAPH;
@@ -195,4 +437,111 @@ mod tests {
         assert_eq!(graph.vertex_degree(VertexId(68)).unwrap(), 8);
         assert!(is_node_valid(graph, VertexId(68)).unwrap());
     }
+
+    #[test]
+    fn path_length_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let path = [EdgeId(8717174), EdgeId(8717175), EdgeId(109783)];
+        assert_eq!(
+            path_length(graph, &path).unwrap(),
+            Length::from_meters(379.0)
+        );
+        assert_eq!(
+            path_length(graph, &path[..1]).unwrap(),
+            Length::from_meters(136.0)
+        );
+        assert_eq!(path_length(graph, &[]).unwrap(), Length::ZERO);
+    }
+
+    #[test]
+    fn path_concat_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let head = Path::from_edges(graph, vec![EdgeId(8717174)]).unwrap();
+        let tail = Path::from_edges(graph, vec![EdgeId(8717175), EdgeId(109783)]).unwrap();
+        let path = Path::from_edges(
+            graph,
+            vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+        )
+        .unwrap();
+
+        assert_eq!(head.clone().concat(tail), path);
+        assert_eq!(head.clone().concat(Path::default()), head);
+    }
+
+    #[test]
+    fn path_reverse_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let path = Path::from_edges(graph, vec![EdgeId(8717174), EdgeId(8717175)]).unwrap();
+        let reversed = path.reverse();
+
+        assert_eq!(reversed.edges, [EdgeId(-8717175), EdgeId(-8717174)]);
+        assert_eq!(reversed.length, path.length);
+        assert!(is_path_connected(graph, &reversed.edges).unwrap());
+        assert_eq!(reversed.reverse(), path);
+    }
+
+    #[test]
+    fn path_slice_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let edges = vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)];
+        let path = Path::from_edges(graph, edges.clone()).unwrap();
+        let first_length = graph.get_edge_length(EdgeId(8717174)).unwrap();
+        let last_length = graph.get_edge_length(EdgeId(109783)).unwrap();
+
+        let line = path.slice(graph, Length::ZERO..path.length).unwrap();
+        assert_eq!(line.path, edges);
+        assert_eq!(line.pos_offset, Length::ZERO);
+        assert_eq!(line.neg_offset, Length::ZERO);
+
+        let start = first_length + Length::from_meters(1.0);
+        let end = path.length - last_length - Length::from_meters(2.0);
+        let line = path.slice(graph, start..end).unwrap();
+        assert_eq!(line.path, [EdgeId(8717175)]);
+        assert_eq!(line.pos_offset, Length::from_meters(1.0));
+        assert_eq!(line.neg_offset, Length::from_meters(2.0));
+
+        assert!(matches!(
+            path.slice(graph, end..start),
+            Err(LocationError::OffsetsExceedLength { .. })
+        ));
+    }
+
+    #[test]
+    fn path_geometry_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let path = Path::from_edges(graph, vec![EdgeId(8717174), EdgeId(8717175)]).unwrap();
+        let vertex_coordinate = |vertex| graph.get_vertex_coordinate(vertex).unwrap();
+
+        assert_eq!(
+            path.geometry(graph).unwrap(),
+            [
+                vertex_coordinate(graph.get_edge_start_vertex(EdgeId(8717174)).unwrap()),
+                vertex_coordinate(graph.get_edge_end_vertex(EdgeId(8717174)).unwrap()),
+                vertex_coordinate(graph.get_edge_end_vertex(EdgeId(8717175)).unwrap()),
+            ]
+        );
+        assert!(
+            Path::<EdgeId>::default()
+                .geometry(graph)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn find_path_disconnection_001() {
+        let graph: &NetworkGraph = &RESTRICTED_NETWORK_GRAPH;
+
+        let path = [EdgeId(8717174), EdgeId(8717175), EdgeId(109783)];
+        assert_eq!(find_path_disconnection(graph, &path).unwrap(), Some(2));
+        assert_eq!(find_path_disconnection(graph, &path[1..]).unwrap(), None);
+
+        let path = [EdgeId(8717174), PARALLEL_EDGE, EdgeId(109783)];
+        assert_eq!(find_path_disconnection(graph, &path).unwrap(), None);
+    }
 }
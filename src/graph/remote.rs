@@ -0,0 +1,801 @@
+//! Graphs whose data is fetched asynchronously, for example from a database or a network tile
+//! service.
+//!
+//! The decoder and the encoder are synchronous: they run on a [`ReplayGraph`] that answers from
+//! the graph data fetched so far and records the data that is missing. Once a run ends, the
+//! missing data is fetched and the run is replayed, until a run doesn't miss any data.
+
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::error::Error;
+use std::fmt::Debug;
+use std::future::Future;
+use std::hash::Hash;
+
+use rustc_hash::FxHashMap;
+
+use crate::graph::memo::{CoordinateKey, LengthKey, coordinate_key, length_key};
+use crate::{AsyncGraphError, Bearing, Coordinate, DirectedGraph, EdgeEnd, Fow, Frc, Length};
+
+/// Directed graph whose data is fetched asynchronously.
+/// Async variant of [`DirectedGraph`], see its documentation for the behavior of each method.
+///
+/// The async decoder and encoder replay the whole decoding (or encoding) each time they need
+/// data that has not been fetched yet: graph data is requested in batches, but implementations
+/// should still be able to serve many requests per location.
+#[allow(clippy::type_complexity)]
+pub trait AsyncDirectedGraph {
+    /// Custom error associated type.
+    type Error: Error;
+    /// Uniquely identify a vertex that belongs to the graph.
+    type VertexId: Debug + Copy + Ord + Hash + Send;
+    /// Uniquely identify a directed edge that belongs to the graph.
+    type EdgeId: Debug + Copy + Ord + Hash + Send;
+
+    /// Gets the vertex coordinate.
+    fn get_vertex_coordinate(
+        &self,
+        vertex: Self::VertexId,
+    ) -> impl Future<Output = Result<Coordinate, Self::Error>> + Send;
+
+    /// Gets the start vertex of the directed edge.
+    fn get_edge_start_vertex(
+        &self,
+        edge: Self::EdgeId,
+    ) -> impl Future<Output = Result<Self::VertexId, Self::Error>> + Send;
+
+    /// Gets the end vertex of the directed edge.
+    fn get_edge_end_vertex(
+        &self,
+        edge: Self::EdgeId,
+    ) -> impl Future<Output = Result<Self::VertexId, Self::Error>> + Send;
+
+    /// Gets the total length of the directed edge.
+    fn get_edge_length(
+        &self,
+        edge: Self::EdgeId,
+    ) -> impl Future<Output = Result<Length, Self::Error>> + Send;
+
+    /// Gets the cost of traveling the whole directed edge.
+    /// Returns the length of the edge by default.
+    fn get_edge_cost(
+        &self,
+        edge: Self::EdgeId,
+    ) -> impl Future<Output = Result<Length, Self::Error>> + Send {
+        self.get_edge_length(edge)
+    }
+
+    /// Gets the Functional Road Class (FRC) of the directed edge.
+    fn get_edge_frc(
+        &self,
+        edge: Self::EdgeId,
+    ) -> impl Future<Output = Result<Frc, Self::Error>> + Send;
+
+    /// Gets the Form of Way (FOW) of the directed edge.
+    fn get_edge_fow(
+        &self,
+        edge: Self::EdgeId,
+    ) -> impl Future<Output = Result<Fow, Self::Error>> + Send;
+
+    /// Gets all the outgoing edges from the given vertex, each with its end vertex.
+    fn vertex_exiting_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> impl Future<Output = Result<Vec<(Self::EdgeId, Self::VertexId)>, Self::Error>> + Send;
+
+    /// Gets all the incoming edges to the given vertex, each with its start vertex.
+    fn vertex_entering_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> impl Future<Output = Result<Vec<(Self::EdgeId, Self::VertexId)>, Self::Error>> + Send;
+
+    /// Gets all the vertices that are within a max distance from the coordinate, sorted by their
+    /// distance to the coordinate.
+    fn nearest_vertices_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> impl Future<Output = Result<Vec<(Self::VertexId, Length)>, Self::Error>> + Send;
+
+    /// Gets all the edges that are within a max distance from the coordinate, sorted by their
+    /// distance to the coordinate.
+    fn nearest_edges_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> impl Future<Output = Result<Vec<(Self::EdgeId, Length)>, Self::Error>> + Send;
+
+    /// Gets the distance of the projected coordinate to the start vertex of the edge when following
+    /// the edge coordinates.
+    fn get_distance_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        coordinate: Coordinate,
+    ) -> impl Future<Output = Result<Length, Self::Error>> + Send;
+
+    /// Gets the coordinate along the edge geometry which is at the given distance from the edge
+    /// start vertex.
+    fn get_coordinate_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        distance: Length,
+    ) -> impl Future<Output = Result<Coordinate, Self::Error>> + Send;
+
+    /// Gets the elevation of the edge geometry at the given distance from the edge start vertex,
+    /// only if the graph has elevation data.
+    /// Returns None by default.
+    fn get_elevation_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        distance: Length,
+    ) -> impl Future<Output = Result<Option<f64>, Self::Error>> + Send {
+        let _ = (edge, distance);
+        async { Ok(None) }
+    }
+
+    /// Gets the bearing of a subsection A-B of the edge that goes from the coordinate (A) at the
+    /// given distance from the start vertex, and the coordinate (B) that is at the given distance
+    /// from A.
+    fn get_edge_bearing(
+        &self,
+        edge: Self::EdgeId,
+        distance_from_start: Length,
+        segment_length: Length,
+    ) -> impl Future<Output = Result<Bearing, Self::Error>> + Send;
+
+    /// Gets the bearing of the edge at its start or end vertex, if the graph has it precomputed for
+    /// this segment length.
+    /// Returns None by default.
+    fn get_edge_precomputed_bearing(
+        &self,
+        edge: Self::EdgeId,
+        at: EdgeEnd,
+        segment_length: Length,
+    ) -> impl Future<Output = Result<Option<Bearing>, Self::Error>> + Send {
+        let _ = (edge, at, segment_length);
+        async { Ok(None) }
+    }
+
+    /// Returns true if turning from the start edge to the end edge is not allowed.
+    fn is_turn_restricted(
+        &self,
+        start: Self::EdgeId,
+        end: Self::EdgeId,
+    ) -> impl Future<Output = Result<bool, Self::Error>> + Send;
+
+    /// Gets the via-way restrictions that end with the given edge.
+    fn get_via_way_restrictions(
+        &self,
+        edge: Self::EdgeId,
+    ) -> impl Future<Output = Result<Vec<Vec<Self::EdgeId>>, Self::Error>> + Send {
+        let _ = edge;
+        async { Ok(vec![]) }
+    }
+}
+
+/// Graph data requested by a run of the decoder (or encoder) that has not been fetched yet.
+#[derive(Debug, Clone, Copy)]
+enum Request<VertexId, EdgeId> {
+    VertexCoordinate(VertexId),
+    /// All the attributes of the edge: start and end vertices, length, cost, FRC and FOW.
+    Edge(EdgeId),
+    ExitingEdges(VertexId),
+    EnteringEdges(VertexId),
+    NearestVertices(Coordinate, Length),
+    NearestEdges(Coordinate, Length),
+    DistanceAlongEdge(EdgeId, Coordinate),
+    CoordinateAlongEdge(EdgeId, Length),
+    ElevationAlongEdge(EdgeId, Length),
+    EdgeBearing(EdgeId, Length, Length),
+    PrecomputedBearing(EdgeId, EdgeEnd, Length),
+    TurnRestriction(EdgeId, EdgeId),
+    ViaWayRestrictions(EdgeId),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct EdgeAttributes<VertexId> {
+    start: VertexId,
+    end: VertexId,
+    length: Length,
+    cost: Length,
+    frc: Frc,
+    fow: Fow,
+}
+
+/// Graph that answers from the data of the async graph fetched so far, and records the requests
+/// of the data that has not been fetched yet.
+///
+/// Missing adjacencies and nearest elements are answered as empty, and missing turn restrictions
+/// (and via-way restrictions) as not restricted, so that a single run collects as many missing requests as possible. All
+/// the other missing data fails the run. Results of runs that missed any data are discarded.
+#[allow(clippy::type_complexity)]
+pub(crate) struct ReplayGraph<'a, G: AsyncDirectedGraph> {
+    graph: &'a G,
+    missing: RefCell<Vec<Request<G::VertexId, G::EdgeId>>>,
+    vertices: FxHashMap<G::VertexId, Coordinate>,
+    edges: FxHashMap<G::EdgeId, EdgeAttributes<G::VertexId>>,
+    exiting_edges: FxHashMap<G::VertexId, Vec<(G::EdgeId, G::VertexId)>>,
+    entering_edges: FxHashMap<G::VertexId, Vec<(G::EdgeId, G::VertexId)>>,
+    nearest_vertices: FxHashMap<(CoordinateKey, LengthKey), Vec<(G::VertexId, Length)>>,
+    nearest_edges: FxHashMap<(CoordinateKey, LengthKey), Vec<(G::EdgeId, Length)>>,
+    distances_along_edge: FxHashMap<(G::EdgeId, CoordinateKey), Length>,
+    coordinates_along_edge: FxHashMap<(G::EdgeId, LengthKey), Coordinate>,
+    elevations_along_edge: FxHashMap<(G::EdgeId, LengthKey), Option<f64>>,
+    bearings: FxHashMap<(G::EdgeId, LengthKey, LengthKey), Bearing>,
+    precomputed_bearings: FxHashMap<(G::EdgeId, EdgeEnd, LengthKey), Option<Bearing>>,
+    turn_restrictions: FxHashMap<(G::EdgeId, G::EdgeId), bool>,
+    via_way_restrictions: FxHashMap<G::EdgeId, Vec<Vec<G::EdgeId>>>,
+}
+
+impl<'a, G: AsyncDirectedGraph> ReplayGraph<'a, G> {
+    pub fn new(graph: &'a G) -> Self {
+        Self {
+            graph,
+            missing: RefCell::default(),
+            vertices: FxHashMap::default(),
+            edges: FxHashMap::default(),
+            exiting_edges: FxHashMap::default(),
+            entering_edges: FxHashMap::default(),
+            nearest_vertices: FxHashMap::default(),
+            nearest_edges: FxHashMap::default(),
+            distances_along_edge: FxHashMap::default(),
+            coordinates_along_edge: FxHashMap::default(),
+            elevations_along_edge: FxHashMap::default(),
+            bearings: FxHashMap::default(),
+            precomputed_bearings: FxHashMap::default(),
+            turn_restrictions: FxHashMap::default(),
+            via_way_restrictions: FxHashMap::default(),
+        }
+    }
+
+    /// Runs the function on the graph data fetched so far.
+    /// Returns None if the run requested any data that has not been fetched yet.
+    pub fn replay<T>(&self, run: impl FnOnce(&Self) -> T) -> Option<T> {
+        self.missing.borrow_mut().clear();
+        let result = run(self);
+        self.missing.borrow().is_empty().then_some(result)
+    }
+
+    /// Fetches all the data missed by the last run.
+    pub async fn fetch_missing(&mut self) -> Result<(), G::Error> {
+        let missing = self.missing.take();
+
+        for request in missing {
+            self.fetch(request).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn fetch(&mut self, request: Request<G::VertexId, G::EdgeId>) -> Result<(), G::Error> {
+        let graph = self.graph;
+
+        match request {
+            Request::VertexCoordinate(vertex) => {
+                if let Entry::Vacant(entry) = self.vertices.entry(vertex) {
+                    entry.insert(graph.get_vertex_coordinate(vertex).await?);
+                }
+            }
+            Request::Edge(edge) => self.fetch_edge(edge).await?,
+            Request::ExitingEdges(vertex) => {
+                if !self.exiting_edges.contains_key(&vertex) {
+                    let edges = graph.vertex_exiting_edges(vertex).await?;
+                    self.fetch_edges(edges.iter().map(|&(edge, _)| edge))
+                        .await?;
+                    self.exiting_edges.insert(vertex, edges);
+                }
+            }
+            Request::EnteringEdges(vertex) => {
+                if !self.entering_edges.contains_key(&vertex) {
+                    let edges = graph.vertex_entering_edges(vertex).await?;
+                    self.fetch_edges(edges.iter().map(|&(edge, _)| edge))
+                        .await?;
+                    self.entering_edges.insert(vertex, edges);
+                }
+            }
+            Request::NearestVertices(coordinate, max_distance) => {
+                let key = (coordinate_key(coordinate), length_key(max_distance));
+                if let Entry::Vacant(entry) = self.nearest_vertices.entry(key) {
+                    entry.insert(
+                        graph
+                            .nearest_vertices_within_distance(coordinate, max_distance)
+                            .await?,
+                    );
+                }
+            }
+            Request::NearestEdges(coordinate, max_distance) => {
+                let key = (coordinate_key(coordinate), length_key(max_distance));
+                if !self.nearest_edges.contains_key(&key) {
+                    let edges = graph
+                        .nearest_edges_within_distance(coordinate, max_distance)
+                        .await?;
+                    self.fetch_edges(edges.iter().map(|&(edge, _)| edge))
+                        .await?;
+                    self.nearest_edges.insert(key, edges);
+                }
+            }
+            Request::DistanceAlongEdge(edge, coordinate) => {
+                let key = (edge, coordinate_key(coordinate));
+                if let Entry::Vacant(entry) = self.distances_along_edge.entry(key) {
+                    entry.insert(graph.get_distance_along_edge(edge, coordinate).await?);
+                }
+            }
+            Request::CoordinateAlongEdge(edge, distance) => {
+                let key = (edge, length_key(distance));
+                if let Entry::Vacant(entry) = self.coordinates_along_edge.entry(key) {
+                    entry.insert(graph.get_coordinate_along_edge(edge, distance).await?);
+                }
+            }
+            Request::ElevationAlongEdge(edge, distance) => {
+                let key = (edge, length_key(distance));
+                if let Entry::Vacant(entry) = self.elevations_along_edge.entry(key) {
+                    entry.insert(graph.get_elevation_along_edge(edge, distance).await?);
+                }
+            }
+            Request::EdgeBearing(edge, distance_from_start, segment_length) => {
+                let key = (
+                    edge,
+                    length_key(distance_from_start),
+                    length_key(segment_length),
+                );
+                if let Entry::Vacant(entry) = self.bearings.entry(key) {
+                    entry.insert(
+                        graph
+                            .get_edge_bearing(edge, distance_from_start, segment_length)
+                            .await?,
+                    );
+                }
+            }
+            Request::PrecomputedBearing(edge, at, segment_length) => {
+                let key = (edge, at, length_key(segment_length));
+                if let Entry::Vacant(entry) = self.precomputed_bearings.entry(key) {
+                    entry.insert(
+                        graph
+                            .get_edge_precomputed_bearing(edge, at, segment_length)
+                            .await?,
+                    );
+                }
+            }
+            Request::TurnRestriction(start, end) => {
+                if let Entry::Vacant(entry) = self.turn_restrictions.entry((start, end)) {
+                    entry.insert(graph.is_turn_restricted(start, end).await?);
+                }
+            }
+            Request::ViaWayRestrictions(edge) => {
+                if let Entry::Vacant(entry) = self.via_way_restrictions.entry(edge) {
+                    entry.insert(graph.get_via_way_restrictions(edge).await?);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fetches the attributes of the edges together with the adjacencies and the nearest edges, as
+    /// the decoder (and encoder) always need them.
+    async fn fetch_edges(
+        &mut self,
+        edges: impl Iterator<Item = G::EdgeId>,
+    ) -> Result<(), G::Error> {
+        for edge in edges {
+            self.fetch_edge(edge).await?;
+        }
+        Ok(())
+    }
+
+    async fn fetch_edge(&mut self, edge: G::EdgeId) -> Result<(), G::Error> {
+        if self.edges.contains_key(&edge) {
+            return Ok(());
+        }
+
+        let attributes = EdgeAttributes {
+            start: self.graph.get_edge_start_vertex(edge).await?,
+            end: self.graph.get_edge_end_vertex(edge).await?,
+            length: self.graph.get_edge_length(edge).await?,
+            cost: self.graph.get_edge_cost(edge).await?,
+            frc: self.graph.get_edge_frc(edge).await?,
+            fow: self.graph.get_edge_fow(edge).await?,
+        };
+
+        self.edges.insert(edge, attributes);
+        Ok(())
+    }
+
+    fn get<K: Eq + Hash, T: Clone>(
+        &self,
+        data: &FxHashMap<K, T>,
+        key: &K,
+        request: Request<G::VertexId, G::EdgeId>,
+    ) -> Result<T, AsyncGraphError<G::Error>> {
+        data.get(key).cloned().ok_or_else(|| {
+            self.missing.borrow_mut().push(request);
+            AsyncGraphError::Missing
+        })
+    }
+
+    fn get_or_default<K: Eq + Hash, T: Clone>(
+        &self,
+        data: &FxHashMap<K, T>,
+        key: &K,
+        request: Request<G::VertexId, G::EdgeId>,
+        default: T,
+    ) -> T {
+        self.get(data, key, request).unwrap_or(default)
+    }
+
+    fn get_edge(
+        &self,
+        edge: G::EdgeId,
+    ) -> Result<EdgeAttributes<G::VertexId>, AsyncGraphError<G::Error>> {
+        self.get(&self.edges, &edge, Request::Edge(edge))
+    }
+}
+
+impl<G: AsyncDirectedGraph> DirectedGraph for ReplayGraph<'_, G> {
+    type Error = AsyncGraphError<G::Error>;
+    type VertexId = G::VertexId;
+    type EdgeId = G::EdgeId;
+
+    fn get_vertex_coordinate(&self, vertex: Self::VertexId) -> Result<Coordinate, Self::Error> {
+        self.get(&self.vertices, &vertex, Request::VertexCoordinate(vertex))
+    }
+
+    fn get_edge_start_vertex(&self, edge: Self::EdgeId) -> Result<Self::VertexId, Self::Error> {
+        Ok(self.get_edge(edge)?.start)
+    }
+
+    fn get_edge_end_vertex(&self, edge: Self::EdgeId) -> Result<Self::VertexId, Self::Error> {
+        Ok(self.get_edge(edge)?.end)
+    }
+
+    fn get_edge_length(&self, edge: Self::EdgeId) -> Result<Length, Self::Error> {
+        Ok(self.get_edge(edge)?.length)
+    }
+
+    fn get_edge_cost(&self, edge: Self::EdgeId) -> Result<Length, Self::Error> {
+        Ok(self.get_edge(edge)?.cost)
+    }
+
+    fn get_edge_frc(&self, edge: Self::EdgeId) -> Result<Frc, Self::Error> {
+        Ok(self.get_edge(edge)?.frc)
+    }
+
+    fn get_edge_fow(&self, edge: Self::EdgeId) -> Result<Fow, Self::Error> {
+        Ok(self.get_edge(edge)?.fow)
+    }
+
+    fn vertex_exiting_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        let request = Request::ExitingEdges(vertex);
+        let edges = self.get_or_default(&self.exiting_edges, &vertex, request, vec![]);
+        Ok(edges.into_iter())
+    }
+
+    fn vertex_entering_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        let request = Request::EnteringEdges(vertex);
+        let edges = self.get_or_default(&self.entering_edges, &vertex, request, vec![]);
+        Ok(edges.into_iter())
+    }
+
+    fn nearest_vertices_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> Result<impl Iterator<Item = (Self::VertexId, Length)>, Self::Error> {
+        let key = (coordinate_key(coordinate), length_key(max_distance));
+        let request = Request::NearestVertices(coordinate, max_distance);
+        let vertices = self.get_or_default(&self.nearest_vertices, &key, request, vec![]);
+        Ok(vertices.into_iter())
+    }
+
+    fn nearest_edges_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Length)>, Self::Error> {
+        let key = (coordinate_key(coordinate), length_key(max_distance));
+        let request = Request::NearestEdges(coordinate, max_distance);
+        let edges = self.get_or_default(&self.nearest_edges, &key, request, vec![]);
+        Ok(edges.into_iter())
+    }
+
+    fn get_distance_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        coordinate: Coordinate,
+    ) -> Result<Length, Self::Error> {
+        let key = (edge, coordinate_key(coordinate));
+        let request = Request::DistanceAlongEdge(edge, coordinate);
+        self.get(&self.distances_along_edge, &key, request)
+    }
+
+    fn get_coordinate_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        distance: Length,
+    ) -> Result<Coordinate, Self::Error> {
+        let key = (edge, length_key(distance));
+        let request = Request::CoordinateAlongEdge(edge, distance);
+        self.get(&self.coordinates_along_edge, &key, request)
+    }
+
+    fn get_elevation_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        distance: Length,
+    ) -> Result<Option<f64>, Self::Error> {
+        let key = (edge, length_key(distance));
+        let request = Request::ElevationAlongEdge(edge, distance);
+        self.get(&self.elevations_along_edge, &key, request)
+    }
+
+    fn get_edge_bearing(
+        &self,
+        edge: Self::EdgeId,
+        distance_from_start: Length,
+        segment_length: Length,
+    ) -> Result<Bearing, Self::Error> {
+        let key = (
+            edge,
+            length_key(distance_from_start),
+            length_key(segment_length),
+        );
+        let request = Request::EdgeBearing(edge, distance_from_start, segment_length);
+        self.get(&self.bearings, &key, request)
+    }
+
+    fn get_edge_precomputed_bearing(
+        &self,
+        edge: Self::EdgeId,
+        at: EdgeEnd,
+        segment_length: Length,
+    ) -> Result<Option<Bearing>, Self::Error> {
+        let key = (edge, at, length_key(segment_length));
+        let request = Request::PrecomputedBearing(edge, at, segment_length);
+        self.get(&self.precomputed_bearings, &key, request)
+    }
+
+    fn is_turn_restricted(
+        &self,
+        start: Self::EdgeId,
+        end: Self::EdgeId,
+    ) -> Result<bool, Self::Error> {
+        let request = Request::TurnRestriction(start, end);
+        Ok(self.get_or_default(&self.turn_restrictions, &(start, end), request, false))
+    }
+
+    fn get_via_way_restrictions(
+        &self,
+        edge: Self::EdgeId,
+    ) -> Result<Vec<Vec<Self::EdgeId>>, Self::Error> {
+        let request = Request::ViaWayRestrictions(edge);
+        Ok(self.get_or_default(&self.via_way_restrictions, &edge, request, vec![]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph, VertexId};
+    use crate::{
+        DecoderConfig, EncoderConfig, LineLocation, Location, decode_binary_openlr,
+        decode_binary_openlr_async, encode_binary_openlr, encode_binary_openlr_async,
+    };
+
+    /// Serves the network graph asynchronously, as a remote map backend would.
+    struct RemoteGraph(&'static NetworkGraph);
+
+    impl AsyncDirectedGraph for RemoteGraph {
+        type Error = <NetworkGraph as DirectedGraph>::Error;
+        type EdgeId = EdgeId;
+        type VertexId = VertexId;
+
+        async fn get_vertex_coordinate(&self, vertex: VertexId) -> Result<Coordinate, Self::Error> {
+            self.0.get_vertex_coordinate(vertex)
+        }
+
+        async fn get_edge_start_vertex(&self, edge: EdgeId) -> Result<VertexId, Self::Error> {
+            self.0.get_edge_start_vertex(edge)
+        }
+
+        async fn get_edge_end_vertex(&self, edge: EdgeId) -> Result<VertexId, Self::Error> {
+            self.0.get_edge_end_vertex(edge)
+        }
+
+        async fn get_edge_length(&self, edge: EdgeId) -> Result<Length, Self::Error> {
+            self.0.get_edge_length(edge)
+        }
+
+        async fn get_edge_frc(&self, edge: EdgeId) -> Result<Frc, Self::Error> {
+            self.0.get_edge_frc(edge)
+        }
+
+        async fn get_edge_fow(&self, edge: EdgeId) -> Result<Fow, Self::Error> {
+            self.0.get_edge_fow(edge)
+        }
+
+        async fn vertex_exiting_edges(
+            &self,
+            vertex: VertexId,
+        ) -> Result<Vec<(EdgeId, VertexId)>, Self::Error> {
+            Ok(self.0.vertex_exiting_edges(vertex)?.collect())
+        }
+
+        async fn vertex_entering_edges(
+            &self,
+            vertex: VertexId,
+        ) -> Result<Vec<(EdgeId, VertexId)>, Self::Error> {
+            Ok(self.0.vertex_entering_edges(vertex)?.collect())
+        }
+
+        async fn nearest_vertices_within_distance(
+            &self,
+            coordinate: Coordinate,
+            max_distance: Length,
+        ) -> Result<Vec<(VertexId, Length)>, Self::Error> {
+            Ok(self
+                .0
+                .nearest_vertices_within_distance(coordinate, max_distance)?
+                .collect())
+        }
+
+        async fn nearest_edges_within_distance(
+            &self,
+            coordinate: Coordinate,
+            max_distance: Length,
+        ) -> Result<Vec<(EdgeId, Length)>, Self::Error> {
+            Ok(self
+                .0
+                .nearest_edges_within_distance(coordinate, max_distance)?
+                .collect())
+        }
+
+        async fn get_distance_along_edge(
+            &self,
+            edge: EdgeId,
+            coordinate: Coordinate,
+        ) -> Result<Length, Self::Error> {
+            self.0.get_distance_along_edge(edge, coordinate)
+        }
+
+        async fn get_coordinate_along_edge(
+            &self,
+            edge: EdgeId,
+            distance: Length,
+        ) -> Result<Coordinate, Self::Error> {
+            self.0.get_coordinate_along_edge(edge, distance)
+        }
+
+        async fn get_elevation_along_edge(
+            &self,
+            edge: EdgeId,
+            distance: Length,
+        ) -> Result<Option<f64>, Self::Error> {
+            self.0.get_elevation_along_edge(edge, distance)
+        }
+
+        async fn get_edge_bearing(
+            &self,
+            edge: EdgeId,
+            distance_from_start: Length,
+            segment_length: Length,
+        ) -> Result<Bearing, Self::Error> {
+            self.0
+                .get_edge_bearing(edge, distance_from_start, segment_length)
+        }
+
+        async fn get_edge_precomputed_bearing(
+            &self,
+            edge: EdgeId,
+            at: EdgeEnd,
+            segment_length: Length,
+        ) -> Result<Option<Bearing>, Self::Error> {
+            self.0
+                .get_edge_precomputed_bearing(edge, at, segment_length)
+        }
+
+        async fn is_turn_restricted(
+            &self,
+            start: EdgeId,
+            end: EdgeId,
+        ) -> Result<bool, Self::Error> {
+            self.0.is_turn_restricted(start, end)
+        }
+    }
+
+    fn line_location() -> Location<EdgeId> {
+        Location::Line(LineLocation {
+            path: vec![
+                EdgeId(1653344),
+                EdgeId(4997411),
+                EdgeId(5359424),
+                EdgeId(5359425),
+            ],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        })
+    }
+
+    #[test(tokio::test)]
+    async fn remote_graph_decode_001() {
+        let graph = RemoteGraph(&NETWORK_GRAPH);
+        let config = DecoderConfig::default();
+        let data =
+            encode_binary_openlr(&EncoderConfig::default(), graph.0, line_location()).unwrap();
+
+        // the decoding can run on multi-threaded runtimes
+        fn assert_send<T: Send>(future: T) -> T {
+            future
+        }
+
+        let location = assert_send(decode_binary_openlr_async(&config, &graph, &data))
+            .await
+            .unwrap();
+        assert_eq!(
+            location,
+            decode_binary_openlr(&config, graph.0, &data).unwrap()
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn remote_graph_encode_001() {
+        let graph = RemoteGraph(&NETWORK_GRAPH);
+        let config = EncoderConfig::default();
+
+        let data = encode_binary_openlr_async(&config, &graph, line_location())
+            .await
+            .unwrap();
+        assert_eq!(
+            data,
+            encode_binary_openlr(&config, graph.0, line_location()).unwrap()
+        );
+    }
+
+    #[test]
+    fn remote_graph_replay_001() {
+        let graph = RemoteGraph(&NETWORK_GRAPH);
+        let replay = ReplayGraph::new(&graph);
+
+        // missing adjacencies don't fail the run, other missing data does
+        let result = replay.replay(|graph| {
+            assert_eq!(graph.vertex_exiting_edges(VertexId(1)).unwrap().count(), 0);
+            graph.get_edge_length(EdgeId(1653344))
+        });
+
+        assert!(result.is_none());
+        assert_eq!(replay.missing.borrow().len(), 2);
+    }
+
+    #[test(tokio::test)]
+    async fn remote_graph_replay_002() {
+        let graph = RemoteGraph(&NETWORK_GRAPH);
+        let mut replay = ReplayGraph::new(&graph);
+        let edge = EdgeId(1653344);
+
+        let run = |graph: &ReplayGraph<_>| {
+            let vertex = graph.get_edge_end_vertex(edge)?;
+            let edges: Vec<_> = graph.vertex_exiting_edges(vertex)?.collect();
+            let lengths: Result<Vec<_>, _> = edges
+                .iter()
+                .map(|&(e, _)| graph.get_edge_length(e))
+                .collect();
+            lengths
+        };
+
+        assert!(replay.replay(run).is_none());
+        replay.fetch_missing().await.unwrap();
+        assert!(replay.replay(run).is_none());
+        replay.fetch_missing().await.unwrap();
+
+        // the attributes of the exiting edges are fetched together with the adjacency
+        let lengths = replay.replay(run).unwrap().unwrap();
+        assert!(!lengths.is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+//! Spatial index of the vertices and of the edges of a graph, to find the candidates of the
+//! location references.
+//!
+//! Graphs that only know the coordinates of their vertices and the geometry of their edges can
+//! build an [`RTreeIndex`] and delegate to it the nearest vertices and edges queries of
+//! [`DirectedGraph`](crate::DirectedGraph).
+
+use geo::{BoundingRect, Closest, Distance, Haversine, HaversineClosestPoint, LineString, Point};
+use rstar::{AABB, PointDistance, RTree, RTreeObject};
+
+use crate::{Coordinate, Length};
+
+/// Index of the vertices and of the edges of a graph by their location.
+pub trait SpatialIndex {
+    /// Uniquely identify an indexed vertex.
+    type VertexId;
+    /// Uniquely identify an indexed edge.
+    type EdgeId;
+
+    /// Gets an iterator over all the vertices that are within a max distance from the coordinate,
+    /// sorted by their distance to the coordinate.
+    /// For each vertex also returns the distance from the coordinate.
+    fn nearest_vertices_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> impl Iterator<Item = (Self::VertexId, Length)>;
+
+    /// Gets an iterator over all the edges that are within a max distance from the coordinate,
+    /// sorted by their distance to the coordinate.
+    /// For each edge also returns the distance from the coordinate.
+    fn nearest_edges_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> impl Iterator<Item = (Self::EdgeId, Length)>;
+}
+
+/// Spatial index backed by R-trees: the distances are haversine distances to the vertices and to
+/// the closest point of the edges geometry.
+#[derive(Debug)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub struct RTreeIndex<VertexId, EdgeId> {
+    vertices: RTree<GeospatialVertex<VertexId>>,
+    edges: RTree<GeospatialEdge<EdgeId>>,
+}
+
+impl<VertexId, EdgeId> RTreeIndex<VertexId, EdgeId> {
+    /// Builds the index from the coordinates of the vertices and the geometry of the edges.
+    /// Edges with an empty geometry are not indexed.
+    pub fn new(
+        vertices: impl IntoIterator<Item = (VertexId, Coordinate)>,
+        edges: impl IntoIterator<Item = (EdgeId, LineString)>,
+    ) -> Self {
+        let vertices = vertices
+            .into_iter()
+            .map(|(vertex, coordinate)| GeospatialVertex { vertex, coordinate })
+            .collect();
+
+        let edges = edges
+            .into_iter()
+            .filter_map(|(edge, geometry)| {
+                Some(GeospatialEdge {
+                    edge,
+                    envelope: envelope(&geometry)?,
+                    geometry,
+                })
+            })
+            .collect();
+
+        Self {
+            vertices: RTree::bulk_load(vertices),
+            edges: RTree::bulk_load(edges),
+        }
+    }
+}
+
+impl<VertexId: Copy, EdgeId: Copy> SpatialIndex for RTreeIndex<VertexId, EdgeId> {
+    type VertexId = VertexId;
+    type EdgeId = EdgeId;
+
+    fn nearest_vertices_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> impl Iterator<Item = (VertexId, Length)> {
+        let max_distance_2 = max_distance.meters() * max_distance.meters();
+        let point = Point::new(coordinate.lon, coordinate.lat);
+
+        self.vertices
+            .nearest_neighbor_iter_with_distance_2(&point)
+            .take_while(move |(_, distance_2)| *distance_2 <= max_distance_2)
+            .map(|(vertex, distance_2)| (vertex.vertex, Length::from_meters(distance_2.sqrt())))
+    }
+
+    fn nearest_edges_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> impl Iterator<Item = (EdgeId, Length)> {
+        let max_distance_2 = max_distance.meters() * max_distance.meters();
+        let point = Point::new(coordinate.lon, coordinate.lat);
+
+        self.edges
+            .nearest_neighbor_iter_with_distance_2(&point)
+            .take_while(move |(_, distance_2)| *distance_2 <= max_distance_2)
+            .map(|(edge, distance_2)| (edge.edge, Length::from_meters(distance_2.sqrt())))
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+struct GeospatialVertex<VertexId> {
+    vertex: VertexId,
+    coordinate: Coordinate,
+}
+
+impl<VertexId> RTreeObject for GeospatialVertex<VertexId> {
+    type Envelope = AABB<Point>;
+    fn envelope(&self) -> Self::Envelope {
+        Point::new(self.coordinate.lon, self.coordinate.lat).envelope()
+    }
+}
+
+impl<VertexId> PointDistance for GeospatialVertex<VertexId> {
+    fn distance_2(&self, destination: &Point) -> f64 {
+        let origin = Point::new(self.coordinate.lon, self.coordinate.lat);
+        Haversine.distance(origin, *destination).powi(2)
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+struct GeospatialEdge<EdgeId> {
+    edge: EdgeId,
+    geometry: LineString,
+    envelope: AABB<Point>,
+}
+
+impl<EdgeId> RTreeObject for GeospatialEdge<EdgeId> {
+    type Envelope = AABB<Point>;
+    fn envelope(&self) -> Self::Envelope {
+        self.envelope
+    }
+}
+
+impl<EdgeId> PointDistance for GeospatialEdge<EdgeId> {
+    fn distance_2(&self, point: &Point) -> f64 {
+        use Closest::*;
+        match self.geometry.haversine_closest_point(point) {
+            SinglePoint(p) | Intersection(p) => Haversine.distance(p, *point).powi(2),
+            Indeterminate => f64::INFINITY,
+        }
+    }
+}
+
+fn envelope(geometry: &LineString) -> Option<AABB<Point>> {
+    let bbox = geometry.bounding_rect()?;
+    Some(AABB::from_corners(
+        Point::new(bbox.min().x, bbox.min().y),
+        Point::new(bbox.max().x, bbox.max().y),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn rtree_index_nearest() {
+        let coordinate = |lon, lat| Coordinate { lon, lat };
+        let line = |coordinates: &[Coordinate]| -> LineString {
+            coordinates.iter().map(|c| (c.lon, c.lat)).collect()
+        };
+
+        let (a, b, c) = (
+            coordinate(13.0, 52.0),
+            coordinate(13.001, 52.0),
+            coordinate(13.01, 52.0),
+        );
+        let index = RTreeIndex::new(
+            [(1, a), (2, b), (3, c)],
+            [(10, line(&[a, b])), (20, line(&[b, c])), (30, line(&[]))],
+        );
+
+        let query = coordinate(13.0002, 52.0001);
+
+        let vertices: Vec<_> = index
+            .nearest_vertices_within_distance(query, Length::from_meters(100.0))
+            .map(|(vertex, _)| vertex)
+            .collect();
+        assert_eq!(vertices, [1, 2]);
+
+        let edges: Vec<_> = index
+            .nearest_edges_within_distance(query, Length::from_meters(100.0))
+            .collect();
+        assert_eq!(edges.len(), 2);
+        assert_eq!(edges[0].0, 10);
+        assert_eq!(edges[0].1.round(), Length::from_meters(11.0));
+        assert!(edges[0].1 <= edges[1].1);
+
+        let edges = index.nearest_edges_within_distance(query, Length::from_meters(1.0));
+        assert_eq!(edges.count(), 0);
+    }
+}
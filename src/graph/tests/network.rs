@@ -1,394 +1,278 @@
-use std::collections::{HashMap, HashSet};
 use std::sync::LazyLock;
 
-use geo::{
-    BoundingRect, Closest, Distance, Haversine, HaversineClosestPoint, InterpolatableLine,
-    LineString, Point,
+use crate::error::{NetworkGeojsonError, NetworkGraphError};
+use crate::graph::get_edge_end_bearing;
+use crate::graph::network::{LineDirection, NetworkLine};
+pub use crate::graph::network::{
+    NetworkEdgeId as EdgeId, NetworkGraph, NetworkVertexId as VertexId,
 };
-use graph::prelude::{DirectedCsrGraph, DirectedNeighborsWithValues};
-use rstar::{AABB, PointDistance, RTree, RTreeObject};
-use thiserror::Error;
+use crate::{Bearing, Coordinate, DirectedGraph, EdgeEnd, Fow, Frc, Length};
 
-use crate::graph::tests::geojson::{GEOJSON_GRAPH, GeojsonGraph};
-use crate::{Bearing, Coordinate, DirectedGraph, Fow, Frc, Length};
+static NETWORK_LINES: LazyLock<Vec<NetworkLine>> =
+    LazyLock::new(|| NetworkLine::from_geojson(include_str!("graph.geojson")).unwrap());
 
 pub static NETWORK_GRAPH: LazyLock<NetworkGraph> =
-    LazyLock::new(|| NetworkGraph::from_geojson_graph(&GEOJSON_GRAPH));
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub struct VertexId(pub u64);
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub struct EdgeId(pub i64);
-
-impl EdgeId {
-    const fn is_reversed(&self) -> bool {
-        self.0.is_negative()
-    }
-
-    const fn undirected(&self) -> Self {
-        Self(self.0.abs())
-    }
-}
-
-pub struct NetworkGraph {
-    network: DirectedCsrGraph<u64, (), EdgeId>,
-    geospatial_nodes: RTree<GeospatialNode>,
-    geospatial_edges: RTree<GeospatialEdge>,
-    edge_properties: HashMap<EdgeId, EdgeProperties>,
-}
-
-#[derive(Debug, Clone, PartialEq)]
-struct EdgeProperties {
-    length: Length,
-    frc: Frc,
-    fow: Fow,
-    geometry: LineString,
-    vertices: [VertexId; 2],
-}
-
-#[derive(Debug)]
-struct GeospatialNode {
-    vertex: VertexId,
-    coordinate: Coordinate,
-}
-
-impl RTreeObject for GeospatialNode {
-    type Envelope = AABB<Point>;
-    fn envelope(&self) -> Self::Envelope {
-        Point::new(self.coordinate.lon, self.coordinate.lat).envelope()
-    }
-}
-
-impl PointDistance for GeospatialNode {
-    fn distance_2(&self, destination: &Point) -> f64 {
-        let origin = Point::new(self.coordinate.lon, self.coordinate.lat);
-        Haversine.distance(origin, *destination).powf(2.0)
-    }
-}
-
-#[derive(Debug)]
-struct GeospatialEdge {
-    edge: EdgeId,
-    geometry: LineString,
-}
-
-impl RTreeObject for GeospatialEdge {
-    type Envelope = AABB<Point>;
-    fn envelope(&self) -> Self::Envelope {
-        let bbox = self.geometry.bounding_rect().unwrap();
-        AABB::from_corners(
-            Point::new(bbox.min().x, bbox.min().y),
-            Point::new(bbox.max().x, bbox.max().y),
-        )
-    }
-}
-
-impl PointDistance for GeospatialEdge {
-    fn distance_2(&self, point: &Point) -> f64 {
-        use Closest::*;
-        match self.geometry.haversine_closest_point(point) {
-            SinglePoint(p) | Intersection(p) => Haversine.distance(p, *point).powf(2.0),
-            Indeterminate => f64::INFINITY,
-        }
-    }
-}
+    LazyLock::new(|| NetworkGraph::from_lines(NETWORK_LINES.clone()));
+
+/// Edge that runs in parallel to the edge 8717175 (same start and end vertices) in the graph
+/// [`PARALLEL_NETWORK_GRAPH`]: a longer (two-way) service road that deviates from the main road.
+pub const PARALLEL_EDGE: EdgeId = EdgeId(90000001);
+
+/// The network lines with an additional [`PARALLEL_EDGE`].
+static PARALLEL_NETWORK_LINES: LazyLock<Vec<NetworkLine>> = LazyLock::new(|| {
+    let mut lines = NETWORK_LINES.clone();
+
+    let line = lines
+        .iter()
+        .find(|line| line.id == EdgeId(8717175))
+        .cloned()
+        .unwrap();
+    let (start, end) = (line.geometry[0], line.geometry[line.geometry.len() - 1]);
+    let middle = Coordinate {
+        lon: (start.lon + end.lon) / 2.0 - 0.0004,
+        lat: (start.lat + end.lat) / 2.0 - 0.0002,
+    };
 
-#[derive(Debug, Error, PartialEq)]
-#[error("NetworkGraph internal error")]
-pub struct NetworkGraphError;
+    let length = {
+        use geo::{Haversine, Length, LineString};
+        let geometry = LineString::from(vec![
+            (start.lon, start.lat),
+            (middle.lon, middle.lat),
+            (end.lon, end.lat),
+        ]);
+        Haversine.length(&geometry)
+    };
 
-impl DirectedGraph for NetworkGraph {
+    lines.push(NetworkLine {
+        id: PARALLEL_EDGE,
+        length: Length::from_meters(length),
+        frc: Frc::Frc7,
+        fow: Fow::Other,
+        direction: LineDirection::Both,
+        geometry: vec![start, middle, end],
+        ..line
+    });
+
+    lines
+});
+
+/// The network graph with an additional [`PARALLEL_EDGE`] (multigraph).
+pub static PARALLEL_NETWORK_GRAPH: LazyLock<NetworkGraph> =
+    LazyLock::new(|| NetworkGraph::from_lines(PARALLEL_NETWORK_LINES.clone()));
+
+/// The network graph with an additional [`PARALLEL_EDGE`] and a via-way restriction: the edge
+/// 109783 cannot be traveled after the edges 8717174 and 8717175.
+pub static RESTRICTED_NETWORK_GRAPH: LazyLock<NetworkGraph> = LazyLock::new(|| {
+    let mut graph = NetworkGraph::from_lines(PARALLEL_NETWORK_LINES.clone());
+    graph.add_restriction([EdgeId(8717174), EdgeId(8717175), EdgeId(109783)]);
+    graph
+});
+
+/// The network graph with elevation data: the elevation of every edge grows 1 meter for each meter
+/// along the edge (from 0 meters at its start vertex).
+pub static ELEVATED_NETWORK_GRAPH: LazyLock<NetworkGraph> = LazyLock::new(|| {
+    let mut graph = NetworkGraph::from_lines(NETWORK_LINES.clone());
+    graph.has_elevation = true;
+    graph
+});
+
+/// Graph that delegates to [`NETWORK_GRAPH`], but has the bearing of every edge end precomputed
+/// to the given bearing (so that it can be told apart from the bearing computed along the edge).
+pub struct PrecomputedBearingGraph(pub Bearing);
+
+impl DirectedGraph for PrecomputedBearingGraph {
     type Error = NetworkGraphError;
-    type EdgeId = EdgeId;
     type VertexId = VertexId;
+    type EdgeId = EdgeId;
 
-    fn get_vertex_coordinate(&self, vertex: Self::VertexId) -> Result<Coordinate, Self::Error> {
-        self.vertex_exiting_edges(vertex)?
-            .next()
-            .and_then(|(e, _)| {
-                debug_assert_eq!(self.get_edge_start_vertex(e).unwrap(), vertex);
-                self.edge_line_string(e).coords().copied().next()
-            })
-            .map(|coord| {
-                Ok(Coordinate {
-                    lon: coord.x,
-                    lat: coord.y,
-                })
-            })
-            .unwrap()
+    fn get_vertex_coordinate(&self, vertex: VertexId) -> Result<Coordinate, Self::Error> {
+        NETWORK_GRAPH.get_vertex_coordinate(vertex)
     }
 
-    fn get_edge_start_vertex(&self, edge: Self::EdgeId) -> Result<Self::VertexId, Self::Error> {
-        self.edge_properties
-            .get(&edge.undirected())
-            .map(|EdgeProperties { vertices, .. }| {
-                if edge.is_reversed() {
-                    Ok(vertices[1])
-                } else {
-                    Ok(vertices[0])
-                }
-            })
-            .unwrap()
+    fn get_edge_start_vertex(&self, edge: EdgeId) -> Result<VertexId, Self::Error> {
+        NETWORK_GRAPH.get_edge_start_vertex(edge)
     }
 
-    fn get_edge_end_vertex(&self, edge: Self::EdgeId) -> Result<Self::VertexId, Self::Error> {
-        self.edge_properties
-            .get(&edge.undirected())
-            .map(|EdgeProperties { vertices, .. }| {
-                if edge.is_reversed() {
-                    Ok(vertices[0])
-                } else {
-                    Ok(vertices[1])
-                }
-            })
-            .unwrap()
+    fn get_edge_end_vertex(&self, edge: EdgeId) -> Result<VertexId, Self::Error> {
+        NETWORK_GRAPH.get_edge_end_vertex(edge)
     }
 
-    fn get_edge_length(&self, edge: Self::EdgeId) -> Result<Length, Self::Error> {
-        self.edge_properties
-            .get(&edge.undirected())
-            .map(|EdgeProperties { length, .. }| Ok(*length))
-            .unwrap()
+    fn get_edge_length(&self, edge: EdgeId) -> Result<Length, Self::Error> {
+        NETWORK_GRAPH.get_edge_length(edge)
     }
 
-    fn get_edge_frc(&self, edge: Self::EdgeId) -> Result<Frc, Self::Error> {
-        self.edge_properties
-            .get(&edge.undirected())
-            .map(|EdgeProperties { frc, .. }| Ok(*frc))
-            .unwrap()
+    fn get_edge_frc(&self, edge: EdgeId) -> Result<Frc, Self::Error> {
+        NETWORK_GRAPH.get_edge_frc(edge)
     }
 
-    fn get_edge_fow(&self, edge: Self::EdgeId) -> Result<Fow, Self::Error> {
-        self.edge_properties
-            .get(&edge.undirected())
-            .map(|EdgeProperties { fow, .. }| Ok(*fow))
-            .unwrap()
+    fn get_edge_fow(&self, edge: EdgeId) -> Result<Fow, Self::Error> {
+        NETWORK_GRAPH.get_edge_fow(edge)
     }
 
     fn vertex_exiting_edges(
         &self,
-        vertex: Self::VertexId,
-    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
-        let mut edges: Vec<_> = self
-            .network
-            .out_neighbors_with_values(vertex.0)
-            .map(|item| (item.value, VertexId(item.target)))
-            .collect();
-
-        // edges returned in a deterministic order
-        edges.sort();
-        Ok(edges.into_iter())
+        vertex: VertexId,
+    ) -> Result<impl Iterator<Item = (EdgeId, VertexId)>, Self::Error> {
+        NETWORK_GRAPH.vertex_exiting_edges(vertex)
     }
 
     fn vertex_entering_edges(
         &self,
-        vertex: Self::VertexId,
-    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
-        let mut edges: Vec<_> = self
-            .network
-            .in_neighbors_with_values(vertex.0)
-            .map(|item| (item.value, VertexId(item.target)))
-            .collect();
-
-        // edges returned in a deterministic order
-        edges.sort();
-        Ok(edges.into_iter())
+        vertex: VertexId,
+    ) -> Result<impl Iterator<Item = (EdgeId, VertexId)>, Self::Error> {
+        NETWORK_GRAPH.vertex_entering_edges(vertex)
     }
 
     fn nearest_vertices_within_distance(
         &self,
         coordinate: Coordinate,
         max_distance: Length,
-    ) -> Result<impl Iterator<Item = (Self::VertexId, Length)>, Self::Error> {
-        let max_distance_2 = max_distance.meters() * max_distance.meters();
-        let point = Point::new(coordinate.lon, coordinate.lat);
-
-        Ok(self
-            .geospatial_nodes
-            .nearest_neighbor_iter_with_distance_2(&point)
-            .take_while(move |(_, distance_2)| *distance_2 <= max_distance_2)
-            .map(|(node, distance_2)| {
-                let length = Length::from_meters(distance_2.sqrt());
-                (node.vertex, length)
-            }))
+    ) -> Result<impl Iterator<Item = (VertexId, Length)>, Self::Error> {
+        NETWORK_GRAPH.nearest_vertices_within_distance(coordinate, max_distance)
     }
 
     fn nearest_edges_within_distance(
         &self,
         coordinate: Coordinate,
         max_distance: Length,
-    ) -> Result<impl Iterator<Item = (Self::EdgeId, Length)>, Self::Error> {
-        let max_distance_2 = max_distance.meters() * max_distance.meters();
-        let point = Point::new(coordinate.lon, coordinate.lat);
-
-        Ok(self
-            .geospatial_edges
-            .nearest_neighbor_iter_with_distance_2(&point)
-            .take_while(move |(_, distance_2)| *distance_2 <= max_distance_2)
-            .map(|(node, distance_2)| {
-                let length = Length::from_meters(distance_2.sqrt());
-                (node.edge, length)
-            }))
+    ) -> Result<impl Iterator<Item = (EdgeId, Length)>, Self::Error> {
+        NETWORK_GRAPH.nearest_edges_within_distance(coordinate, max_distance)
     }
 
     fn get_distance_along_edge(
         &self,
-        edge: Self::EdgeId,
+        edge: EdgeId,
         coordinate: Coordinate,
     ) -> Result<Length, Self::Error> {
-        let mut closest_distance = f64::INFINITY;
-        let mut distance_along_edge = 0.0;
-        let mut distance_acc = 0.0;
-
-        let point = Point::new(coordinate.lon, coordinate.lat);
-
-        for line in self.edge_line_string(edge).lines() {
-            match line.haversine_closest_point(&point) {
-                Closest::SinglePoint(p) | Closest::Intersection(p) => {
-                    let distance_to_line = Haversine.distance(point, p);
-
-                    if distance_to_line < closest_distance {
-                        // this is the closest line segment of the whole geometry (so far)
-                        closest_distance = distance_to_line;
-                        let distance = Haversine.distance(line.start_point(), p);
-                        distance_along_edge = distance_acc + distance;
-                    }
-
-                    use geo::Length;
-                    distance_acc += Haversine.length(&line);
-                }
-                Closest::Indeterminate => panic!("Cannot project {coordinate:?} onto {edge:?}"),
-            }
-        }
-
-        Ok(Length::from_meters(distance_along_edge).min(self.get_edge_length(edge)?))
+        NETWORK_GRAPH.get_distance_along_edge(edge, coordinate)
     }
 
     fn get_coordinate_along_edge(
         &self,
-        edge: Self::EdgeId,
+        edge: EdgeId,
         distance: Length,
     ) -> Result<Coordinate, Self::Error> {
-        let ratio = distance.meters() / self.get_edge_length(edge)?.meters();
-
-        let geometry = self.edge_line_string(edge);
-        let point = geometry
-            .point_at_ratio_from_start(&Haversine, ratio)
-            .unwrap();
-
-        Ok(Coordinate {
-            lon: point.x(),
-            lat: point.y(),
-        })
+        NETWORK_GRAPH.get_coordinate_along_edge(edge, distance)
     }
 
     fn get_edge_bearing(
         &self,
-        edge: Self::EdgeId,
+        edge: EdgeId,
         distance_from_start: Length,
         segment_length: Length,
     ) -> Result<Bearing, Self::Error> {
-        let edge_length = self.get_edge_length(edge)?;
-        let distance_start = distance_from_start.clamp(Length::ZERO, edge_length);
-        let distance_end = (distance_start + segment_length).clamp(Length::ZERO, edge_length);
-
-        let c1 = self.get_coordinate_along_edge(edge, distance_start)?;
-        let p1 = Point::new(c1.lon, c1.lat);
-
-        let c2 = self.get_coordinate_along_edge(edge, distance_end)?;
-        let p2 = Point::new(c2.lon, c2.lat);
-
-        let degrees = {
-            use geo::Bearing;
-            Haversine.bearing(p1, p2).round() as u16
-        };
+        NETWORK_GRAPH.get_edge_bearing(edge, distance_from_start, segment_length)
+    }
 
-        Ok(Bearing::from_degrees(degrees))
+    fn is_turn_restricted(&self, start: EdgeId, end: EdgeId) -> Result<bool, Self::Error> {
+        NETWORK_GRAPH.is_turn_restricted(start, end)
     }
 
-    fn is_turn_restricted(
+    fn get_edge_precomputed_bearing(
         &self,
-        _start: Self::EdgeId,
-        _end: Self::EdgeId,
-    ) -> Result<bool, Self::Error> {
-        Ok(false)
+        _: EdgeId,
+        _: EdgeEnd,
+        _: Length,
+    ) -> Result<Option<Bearing>, Self::Error> {
+        Ok(Some(self.0))
     }
 }
 
-impl NetworkGraph {
-    fn edge_line_string(&self, edge: EdgeId) -> LineString {
-        let EdgeProperties { geometry, .. } = self.edge_properties.get(&edge.undirected()).unwrap();
+#[test]
+fn network_graph_lines_from_geojson() {
+    let lines = &NETWORK_LINES;
+    assert_eq!(lines.len(), 162);
+
+    let line = lines.iter().find(|line| line.id == EdgeId(16218)).unwrap();
+    assert_eq!(line.start, VertexId(1));
+    assert_eq!(line.end, VertexId(2));
+    assert_eq!(line.length, Length::from_meters(217.0));
+    assert_eq!(line.frc, Frc::Frc2);
+    assert_eq!(line.fow, Fow::SingleCarriageway);
+    assert_eq!(line.direction, LineDirection::Forward);
+    assert_eq!(line.geometry.len(), 7);
+
+    let line = lines
+        .iter()
+        .find(|line| line.id == EdgeId(8323959))
+        .unwrap();
+    assert_eq!(line.start, VertexId(129));
+    assert_eq!(line.end, VertexId(126));
+    assert_eq!(line.length, Length::from_meters(11.0));
+    assert_eq!(line.frc, Frc::Frc6);
+    assert_eq!(line.fow, Fow::SingleCarriageway);
+    assert_eq!(line.direction, LineDirection::Both);
+    assert_eq!(line.geometry.len(), 2);
+
+    assert_eq!(
+        NetworkLine::from_geojson("[]"),
+        Err(NetworkGeojsonError::InvalidGeojson)
+    );
+
+    let geojson = r#"{"type": "FeatureCollection", "features": [{
+        "type": "Feature",
+        "properties": {"id": 1, "startId": 1, "endId": 2, "length": 10, "frc": 9, "fow": 3, "direction": 1},
+        "geometry": {"type": "LineString", "coordinates": [[13.4, 52.5], [13.41, 52.5]]}
+    }]}"#;
+    assert_eq!(
+        NetworkLine::from_geojson(geojson),
+        Err(NetworkGeojsonError::InvalidProperty {
+            feature: 0,
+            property: "frc"
+        })
+    );
+}
 
-        if edge.is_reversed() {
-            geometry.coords().rev().copied().collect()
-        } else {
-            geometry.coords().copied().collect()
-        }
-    }
+#[test]
+fn network_graph_vertex_edges() {
+    let graph = &NETWORK_GRAPH;
 
-    fn from_geojson_graph(graph: &GeojsonGraph) -> NetworkGraph {
-        let edge_properties = graph
-            .lines
-            .iter()
-            .map(|(&line_id, line)| {
-                let property = EdgeProperties {
-                    length: line.length,
-                    frc: line.frc,
-                    fow: line.fow,
-                    geometry: line.geometry.clone(),
-                    vertices: [VertexId(line.start_node_id), VertexId(line.end_node_id)],
-                };
-
-                (EdgeId(line_id), property)
-            })
-            .collect();
-
-        let network_edges = graph.nodes.iter().flat_map(|(&from_id, node)| {
-            node.exiting_lines
-                .iter()
-                .map(move |&(line_id, to_id)| (from_id, to_id, EdgeId(line_id)))
-        });
-
-        let geospatial_nodes: Vec<GeospatialNode> = graph
-            .nodes
-            .iter()
-            .map(|(&from_id, node)| GeospatialNode {
-                vertex: VertexId(from_id),
-                coordinate: node.coordinate,
-            })
-            .collect();
-
-        let directed_edges: HashSet<EdgeId> = graph
-            .nodes
-            .iter()
-            .flat_map(|(_, node)| {
-                node.exiting_lines
-                    .iter()
-                    .map(|&(line_id, _)| EdgeId(line_id))
-            })
-            .collect();
-
-        let geospatial_edges: Vec<GeospatialEdge> = directed_edges
-            .into_iter()
-            .map(|edge_id| {
-                let line = graph.lines.get(&edge_id.undirected().0).unwrap();
-                GeospatialEdge {
-                    edge: edge_id,
-                    geometry: line.geometry.clone(),
-                }
-            })
-            .collect();
-
-        NetworkGraph {
-            network: graph::prelude::GraphBuilder::new()
-                .edges_with_values(network_edges)
-                .build(),
-            geospatial_nodes: RTree::bulk_load(geospatial_nodes),
-            geospatial_edges: RTree::bulk_load(geospatial_edges),
-            edge_properties,
-        }
-    }
+    let edges = |vertex| -> Vec<_> {
+        graph
+            .vertex_exiting_edges(VertexId(vertex))
+            .unwrap()
+            .collect()
+    };
+    assert_eq!(edges(1), [(EdgeId(16218), VertexId(2))]);
+    assert_eq!(
+        edges(2),
+        [
+            (EdgeId(-3622025), VertexId(58)),
+            (EdgeId(16219), VertexId(3))
+        ]
+    );
+    assert_eq!(
+        edges(29),
+        [
+            (EdgeId(-2711304), VertexId(51)),
+            (EdgeId(580854), VertexId(30)),
+            (EdgeId(2711305), VertexId(48))
+        ]
+    );
+    assert_eq!(
+        edges(126),
+        [
+            (EdgeId(-8323959), VertexId(129)),
+            (EdgeId(8323953), VertexId(127))
+        ]
+    );
+    // loop
+    assert_eq!(
+        edges(134),
+        [
+            (EdgeId(8345025), VertexId(134)),
+            (EdgeId(8345026), VertexId(123))
+        ]
+    );
+
+    assert_eq!(
+        graph.vertex_exiting_edges(VertexId(0)).map(|_| ()),
+        Err(NetworkGraphError::VertexNotFound(VertexId(0)))
+    );
+    assert_eq!(
+        graph.get_edge_length(EdgeId(1)),
+        Err(NetworkGraphError::EdgeNotFound(EdgeId(1)))
+    );
 }
 
 #[test]
@@ -504,6 +388,45 @@ fn network_graph_vertex_coordinate() {
     );
 }
 
+#[cfg(feature = "snapshot")]
+#[test]
+fn network_graph_snapshot() {
+    use crate::error::NetworkSnapshotError;
+
+    let graph = &NETWORK_GRAPH;
+    let snapshot = graph.to_snapshot().unwrap();
+    let loaded = NetworkGraph::from_snapshot(&snapshot).unwrap();
+
+    assert_eq!(loaded.vertex_count(), graph.vertex_count());
+    assert_eq!(loaded.edge_count(), graph.edge_count());
+    assert_eq!(
+        loaded.get_vertex_coordinate(VertexId(58)),
+        graph.get_vertex_coordinate(VertexId(58))
+    );
+
+    let coordinate = graph.get_vertex_coordinate(VertexId(58)).unwrap();
+    let max_distance = Length::from_meters(100.0);
+    assert_eq!(
+        loaded
+            .nearest_edges_within_distance(coordinate, max_distance)
+            .unwrap()
+            .collect::<Vec<_>>(),
+        graph
+            .nearest_edges_within_distance(coordinate, max_distance)
+            .unwrap()
+            .collect::<Vec<_>>()
+    );
+
+    assert_eq!(
+        NetworkGraph::from_snapshot(&snapshot[1..]).err(),
+        Some(NetworkSnapshotError::InvalidHeader)
+    );
+    assert_eq!(
+        NetworkGraph::from_snapshot(&snapshot[..snapshot.len() - 1]).err(),
+        Some(NetworkSnapshotError::InvalidData)
+    );
+}
+
 #[test]
 fn network_graph_vertex_degree() {
     let graph = &NETWORK_GRAPH;
@@ -638,6 +561,60 @@ fn network_graph_edge_bearing() {
     );
 }
 
+#[test]
+fn network_graph_edge_end_bearing() {
+    let graph: &NetworkGraph = &NETWORK_GRAPH;
+    let segment_length = Length::from_meters(20.0);
+
+    for edge in [EdgeId(109783), EdgeId(-5359425), EdgeId(8717174)] {
+        assert_eq!(
+            graph
+                .get_edge_precomputed_bearing(edge, EdgeEnd::Start, segment_length)
+                .unwrap(),
+            None
+        );
+
+        assert_eq!(
+            get_edge_end_bearing(graph, edge, EdgeEnd::Start, segment_length).unwrap(),
+            graph
+                .get_edge_bearing(edge, Length::ZERO, segment_length)
+                .unwrap()
+        );
+
+        let length = graph.get_edge_length(edge).unwrap();
+        assert_eq!(
+            get_edge_end_bearing(graph, edge, EdgeEnd::End, segment_length).unwrap(),
+            graph
+                .get_edge_bearing(edge, length, segment_length.reverse())
+                .unwrap()
+        );
+    }
+}
+
+#[test]
+fn network_graph_edge_precomputed_bearing() {
+    let bearing = Bearing::from_degrees(42);
+    let graph = PrecomputedBearingGraph(bearing);
+    let segment_length = Length::from_meters(20.0);
+
+    for edge in [EdgeId(109783), EdgeId(-5359425), EdgeId(8717174)] {
+        for at in [EdgeEnd::Start, EdgeEnd::End] {
+            assert_eq!(
+                get_edge_end_bearing(&graph, edge, at, segment_length).unwrap(),
+                bearing
+            );
+        }
+    }
+
+    // the precomputed bearing differs from the computed one
+    assert_ne!(
+        graph
+            .get_edge_bearing(EdgeId(8717174), Length::ZERO, segment_length)
+            .unwrap(),
+        bearing
+    );
+}
+
 #[test]
 fn network_graph_distance_along_edge() {
     let graph = &NETWORK_GRAPH;
@@ -1006,3 +983,39 @@ fn network_graph_edge_properties() {
         ]
     );
 }
+
+#[test]
+fn network_graph_restrictions() {
+    let graph: &NetworkGraph = &RESTRICTED_NETWORK_GRAPH;
+
+    assert!(
+        !graph
+            .is_turn_restricted(EdgeId(8717175), EdgeId(109783))
+            .unwrap()
+    );
+    assert_eq!(
+        graph.get_via_way_restrictions(EdgeId(109783)).unwrap(),
+        [[EdgeId(8717174), EdgeId(8717175)]]
+    );
+    assert!(
+        graph
+            .get_via_way_restrictions(EdgeId(8717175))
+            .unwrap()
+            .is_empty()
+    );
+
+    let mut graph = NetworkGraph::from_lines(NETWORK_LINES.clone());
+    graph.add_restriction([EdgeId(8717175), EdgeId(109783)]);
+
+    assert!(
+        graph
+            .is_turn_restricted(EdgeId(8717175), EdgeId(109783))
+            .unwrap()
+    );
+    assert!(
+        graph
+            .get_via_way_restrictions(EdgeId(109783))
+            .unwrap()
+            .is_empty()
+    );
+}
@@ -0,0 +1,460 @@
+//! Graph of the maps that don't fit in memory (for example planet-scale maps), split into tiles
+//! that are loaded on demand.
+//!
+//! Each vertex and each edge belongs to one tile, that answers all the queries about it: the tile
+//! of a vertex has its coordinate and all its entering and exiting edges (also the ones that
+//! belong to the neighboring tiles), the tile of an edge has all its attributes, its geometry and
+//! the restrictions that end with it. The nearest vertices and edges of a coordinate are searched
+//! in all the tiles within the max distance from it.
+
+use std::cell::{Cell, RefCell};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{Bearing, Coordinate, DirectedGraph, EdgeEnd, Fow, Frc, Length, LocationReference};
+
+type TileError<L> = <<L as TileLoader>::Tile as DirectedGraph>::Error;
+type TileVertexId<L> = <<L as TileLoader>::Tile as DirectedGraph>::VertexId;
+type TileEdgeId<L> = <<L as TileLoader>::Tile as DirectedGraph>::EdgeId;
+
+/// Loads the tiles of a [`TiledGraph`], for example from files or from a remote tile server.
+///
+/// The vertex and edge IDs are shared by all the tiles, and the loader must know the tile of each
+/// of them (map tile formats usually encode the tile into the IDs).
+pub trait TileLoader {
+    /// Uniquely identify a tile of the map.
+    type TileId: Debug + Copy + Eq + Hash;
+    /// Graph of the vertices and edges of a tile.
+    type Tile: DirectedGraph;
+
+    /// Gets the tile the vertex belongs to.
+    fn vertex_tile(&self, vertex: TileVertexId<Self>) -> Self::TileId;
+
+    /// Gets the tile the edge belongs to.
+    fn edge_tile(&self, edge: TileEdgeId<Self>) -> Self::TileId;
+
+    /// Gets the tiles that intersect the circle of the given radius around the coordinate.
+    fn tiles_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> Vec<Self::TileId>;
+
+    /// Loads the tile.
+    fn load_tile(&self, tile: Self::TileId) -> Result<Self::Tile, TileError<Self>>;
+}
+
+/// Tile kept in memory by a [`TiledGraph`].
+struct LoadedTile<T> {
+    tile: Rc<T>,
+    last_used: u64,
+}
+
+/// Graph that loads the tiles of the map on demand with a [`TileLoader`].
+///
+/// At most the given number of tiles are kept in memory: the least recently used tile is evicted
+/// when another one has to be loaded. The tiles in use by a query are kept alive until the query
+/// returns, even if evicted. The tiles of a location reference can be loaded ahead of its
+/// decoding with [`TiledGraph::prefetch`].
+pub struct TiledGraph<L: TileLoader> {
+    loader: L,
+    max_tiles: usize,
+    tiles: RefCell<FxHashMap<L::TileId, LoadedTile<L::Tile>>>,
+    clock: Cell<u64>,
+}
+
+impl<L: TileLoader> TiledGraph<L> {
+    /// Creates a graph that keeps at most the given number of tiles in memory (at least one).
+    pub fn new(loader: L, max_tiles: usize) -> Self {
+        Self {
+            loader,
+            max_tiles: max_tiles.max(1),
+            tiles: RefCell::default(),
+            clock: Cell::new(0),
+        }
+    }
+
+    pub const fn loader(&self) -> &L {
+        &self.loader
+    }
+
+    /// Gets the number of tiles currently kept in memory.
+    pub fn loaded_tiles(&self) -> usize {
+        self.tiles.borrow().len()
+    }
+
+    /// Loads the tiles of the corridor of the location reference: the tiles within the given
+    /// distance (usually the max distance of the candidate lines of the decoder) from the LRPs and
+    /// from the straight lines between consecutive LRPs.
+    /// The routes that leave the corridor load the other tiles on demand.
+    pub fn prefetch(
+        &self,
+        location: &LocationReference,
+        max_distance: Length,
+    ) -> Result<(), TileError<L>> {
+        let step = max_distance.max(Length::from_meters(1.0));
+        let mut tiles = FxHashSet::default();
+        let mut previous: Option<Coordinate> = None;
+
+        for point in location.points() {
+            let coordinate = point.coordinate;
+            if let Some(previous) = previous {
+                let steps = (previous.distance(&coordinate) / step).ceil() as usize;
+                for i in 1..steps {
+                    let fraction = i as f64 / steps as f64;
+                    let sample = Coordinate {
+                        lon: previous.lon + (coordinate.lon - previous.lon) * fraction,
+                        lat: previous.lat + (coordinate.lat - previous.lat) * fraction,
+                    };
+                    tiles.extend(self.loader.tiles_within_distance(sample, max_distance));
+                }
+            }
+            tiles.extend(self.loader.tiles_within_distance(coordinate, max_distance));
+            previous = Some(coordinate);
+        }
+
+        for tile in tiles {
+            self.tile(tile)?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets the tile from memory, or loads it (evicting the least recently used tile if needed).
+    fn tile(&self, id: L::TileId) -> Result<Rc<L::Tile>, TileError<L>> {
+        let now = self.clock.get() + 1;
+        self.clock.set(now);
+
+        if let Some(loaded) = self.tiles.borrow_mut().get_mut(&id) {
+            loaded.last_used = now;
+            return Ok(Rc::clone(&loaded.tile));
+        }
+
+        let tile = Rc::new(self.loader.load_tile(id)?);
+
+        let mut tiles = self.tiles.borrow_mut();
+        if tiles.len() >= self.max_tiles {
+            let least_recently_used = tiles
+                .iter()
+                .min_by_key(|(_, loaded)| loaded.last_used)
+                .map(|(&id, _)| id);
+            if let Some(id) = least_recently_used {
+                tiles.remove(&id);
+            }
+        }
+        tiles.insert(
+            id,
+            LoadedTile {
+                tile: Rc::clone(&tile),
+                last_used: now,
+            },
+        );
+
+        Ok(tile)
+    }
+
+    fn vertex_tile(&self, vertex: TileVertexId<L>) -> Result<Rc<L::Tile>, TileError<L>> {
+        self.tile(self.loader.vertex_tile(vertex))
+    }
+
+    fn edge_tile(&self, edge: TileEdgeId<L>) -> Result<Rc<L::Tile>, TileError<L>> {
+        self.tile(self.loader.edge_tile(edge))
+    }
+
+    /// Merges the nearest elements of the tiles around the coordinate, sorted by their distance to
+    /// the coordinate. The elements found in more than one tile are kept once.
+    fn nearest_within_distance<T: Copy + Ord + Hash>(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+        nearest: impl Fn(&L::Tile) -> Result<Vec<(T, Length)>, TileError<L>>,
+    ) -> Result<Vec<(T, Length)>, TileError<L>> {
+        let mut elements = vec![];
+        for tile in self.loader.tiles_within_distance(coordinate, max_distance) {
+            elements.extend(nearest(&*self.tile(tile)?)?);
+        }
+
+        elements.sort_unstable_by_key(|&(element, distance)| (distance, element));
+        let mut visited = FxHashSet::default();
+        elements.retain(|&(element, _)| visited.insert(element));
+
+        Ok(elements)
+    }
+}
+
+impl<L: TileLoader> DirectedGraph for TiledGraph<L> {
+    type Error = TileError<L>;
+    type VertexId = TileVertexId<L>;
+    type EdgeId = TileEdgeId<L>;
+
+    fn get_vertex_coordinate(&self, vertex: Self::VertexId) -> Result<Coordinate, Self::Error> {
+        self.vertex_tile(vertex)?.get_vertex_coordinate(vertex)
+    }
+
+    fn get_edge_start_vertex(&self, edge: Self::EdgeId) -> Result<Self::VertexId, Self::Error> {
+        self.edge_tile(edge)?.get_edge_start_vertex(edge)
+    }
+
+    fn get_edge_end_vertex(&self, edge: Self::EdgeId) -> Result<Self::VertexId, Self::Error> {
+        self.edge_tile(edge)?.get_edge_end_vertex(edge)
+    }
+
+    fn get_edge_length(&self, edge: Self::EdgeId) -> Result<Length, Self::Error> {
+        self.edge_tile(edge)?.get_edge_length(edge)
+    }
+
+    fn get_edge_cost(&self, edge: Self::EdgeId) -> Result<Length, Self::Error> {
+        self.edge_tile(edge)?.get_edge_cost(edge)
+    }
+
+    fn get_edge_frc(&self, edge: Self::EdgeId) -> Result<Frc, Self::Error> {
+        self.edge_tile(edge)?.get_edge_frc(edge)
+    }
+
+    fn get_edge_fow(&self, edge: Self::EdgeId) -> Result<Fow, Self::Error> {
+        self.edge_tile(edge)?.get_edge_fow(edge)
+    }
+
+    fn vertex_exiting_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        let tile = self.vertex_tile(vertex)?;
+        let edges: Vec<_> = tile.vertex_exiting_edges(vertex)?.collect();
+        Ok(edges.into_iter())
+    }
+
+    fn vertex_entering_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        let tile = self.vertex_tile(vertex)?;
+        let edges: Vec<_> = tile.vertex_entering_edges(vertex)?.collect();
+        Ok(edges.into_iter())
+    }
+
+    fn vertex_exiting_edges_within_frc(
+        &self,
+        vertex: Self::VertexId,
+        lowest_frc: Frc,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        let tile = self.vertex_tile(vertex)?;
+        let edges: Vec<_> = tile
+            .vertex_exiting_edges_within_frc(vertex, lowest_frc)?
+            .collect();
+        Ok(edges.into_iter())
+    }
+
+    fn nearest_vertices_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> Result<impl Iterator<Item = (Self::VertexId, Length)>, Self::Error> {
+        let vertices = self.nearest_within_distance(coordinate, max_distance, |tile| {
+            Ok(tile
+                .nearest_vertices_within_distance(coordinate, max_distance)?
+                .collect())
+        })?;
+        Ok(vertices.into_iter())
+    }
+
+    fn nearest_edges_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Length)>, Self::Error> {
+        let edges = self.nearest_within_distance(coordinate, max_distance, |tile| {
+            Ok(tile
+                .nearest_edges_within_distance(coordinate, max_distance)?
+                .collect())
+        })?;
+        Ok(edges.into_iter())
+    }
+
+    fn get_distance_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        coordinate: Coordinate,
+    ) -> Result<Length, Self::Error> {
+        self.edge_tile(edge)?
+            .get_distance_along_edge(edge, coordinate)
+    }
+
+    fn get_coordinate_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        distance: Length,
+    ) -> Result<Coordinate, Self::Error> {
+        self.edge_tile(edge)?
+            .get_coordinate_along_edge(edge, distance)
+    }
+
+    fn get_elevation_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        distance: Length,
+    ) -> Result<Option<f64>, Self::Error> {
+        self.edge_tile(edge)?
+            .get_elevation_along_edge(edge, distance)
+    }
+
+    fn get_edge_bearing(
+        &self,
+        edge: Self::EdgeId,
+        distance_from_start: Length,
+        segment_length: Length,
+    ) -> Result<Bearing, Self::Error> {
+        self.edge_tile(edge)?
+            .get_edge_bearing(edge, distance_from_start, segment_length)
+    }
+
+    fn get_edge_precomputed_bearing(
+        &self,
+        edge: Self::EdgeId,
+        at: EdgeEnd,
+        segment_length: Length,
+    ) -> Result<Option<Bearing>, Self::Error> {
+        self.edge_tile(edge)?
+            .get_edge_precomputed_bearing(edge, at, segment_length)
+    }
+
+    fn is_turn_restricted(
+        &self,
+        start: Self::EdgeId,
+        end: Self::EdgeId,
+    ) -> Result<bool, Self::Error> {
+        self.edge_tile(end)?.is_turn_restricted(start, end)
+    }
+
+    fn get_via_way_restrictions(
+        &self,
+        edge: Self::EdgeId,
+    ) -> Result<Vec<Vec<Self::EdgeId>>, Self::Error> {
+        self.edge_tile(edge)?.get_via_way_restrictions(edge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::error::NetworkGraphError;
+    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph, VertexId};
+    use crate::{DecoderConfig, decode_base64_openlr, deserialize_base64_openlr};
+
+    /// Size of the tiles in degrees.
+    const TILE_SIZE: f64 = 0.002;
+
+    /// Splits the test network into a grid of tiles, every tile serves the whole network.
+    #[derive(Debug, Default)]
+    struct GridLoader {
+        loads: Cell<usize>,
+    }
+
+    impl GridLoader {
+        fn coordinate_tile(coordinate: Coordinate) -> (i64, i64) {
+            (
+                (coordinate.lon / TILE_SIZE).floor() as i64,
+                (coordinate.lat / TILE_SIZE).floor() as i64,
+            )
+        }
+    }
+
+    impl TileLoader for GridLoader {
+        type TileId = (i64, i64);
+        type Tile = &'static NetworkGraph;
+
+        fn vertex_tile(&self, vertex: VertexId) -> Self::TileId {
+            let coordinate = NETWORK_GRAPH.get_vertex_coordinate(vertex).unwrap();
+            Self::coordinate_tile(coordinate)
+        }
+
+        fn edge_tile(&self, edge: EdgeId) -> Self::TileId {
+            let vertex = NETWORK_GRAPH.get_edge_start_vertex(edge).unwrap();
+            self.vertex_tile(vertex)
+        }
+
+        fn tiles_within_distance(
+            &self,
+            coordinate: Coordinate,
+            max_distance: Length,
+        ) -> Vec<Self::TileId> {
+            let lat_delta = max_distance.meters() / 111_320.0;
+            let lon_delta = lat_delta / coordinate.lat.to_radians().cos();
+            let (min_x, min_y) = Self::coordinate_tile(Coordinate {
+                lon: coordinate.lon - lon_delta,
+                lat: coordinate.lat - lat_delta,
+            });
+            let (max_x, max_y) = Self::coordinate_tile(Coordinate {
+                lon: coordinate.lon + lon_delta,
+                lat: coordinate.lat + lat_delta,
+            });
+
+            (min_x..=max_x)
+                .flat_map(|x| (min_y..=max_y).map(move |y| (x, y)))
+                .collect()
+        }
+
+        fn load_tile(&self, _: Self::TileId) -> Result<Self::Tile, NetworkGraphError> {
+            self.loads.set(self.loads.get() + 1);
+            Ok(&NETWORK_GRAPH)
+        }
+    }
+
+    #[test]
+    fn tiled_graph_decode_line() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let config = DecoderConfig::default();
+        let code = "CwmShiVYczPJBgCs/y0zAQ==";
+
+        let tiled = TiledGraph::new(GridLoader::default(), 64);
+        assert_eq!(
+            decode_base64_openlr(&config, &tiled, code).unwrap(),
+            decode_base64_openlr(&config, graph, code).unwrap()
+        );
+        assert!(tiled.loaded_tiles() > 0);
+    }
+
+    #[test]
+    fn tiled_graph_prefetch() {
+        let config = DecoderConfig::default();
+        let code = "CwmShiVYczPJBgCs/y0zAQ==";
+        let location = deserialize_base64_openlr(code).unwrap();
+
+        let tiled = TiledGraph::new(GridLoader::default(), 64);
+        tiled.prefetch(&location, config.max_node_distance).unwrap();
+        let prefetched = tiled.loader().loads.get();
+        assert!(prefetched > 0);
+        assert_eq!(tiled.loaded_tiles(), prefetched);
+
+        // nearest queries around the LRPs don't load any other tile
+        for point in location.points() {
+            tiled
+                .nearest_edges_within_distance(point.coordinate, config.max_node_distance)
+                .unwrap()
+                .for_each(drop);
+        }
+        assert_eq!(tiled.loader().loads.get(), prefetched);
+    }
+
+    #[test]
+    fn tiled_graph_eviction() {
+        let tiled = TiledGraph::new(GridLoader::default(), 1);
+        let vertex = NETWORK_GRAPH
+            .get_edge_start_vertex(EdgeId(8717174))
+            .unwrap();
+        let coordinate = NETWORK_GRAPH.get_vertex_coordinate(vertex).unwrap();
+
+        let edges: Vec<_> = tiled
+            .nearest_edges_within_distance(coordinate, Length::from_meters(500.0))
+            .unwrap()
+            .collect();
+        assert!(!edges.is_empty());
+        assert!(edges.is_sorted_by_key(|&(_, distance)| distance));
+        assert_eq!(tiled.loaded_tiles(), 1);
+    }
+}
@@ -0,0 +1,287 @@
+use std::cell::RefCell;
+
+use rustc_hash::FxHashMap;
+
+use crate::graph::path::Path;
+use crate::routing::RouteCost;
+use crate::{Bearing, Coordinate, DirectedGraph, EdgeEnd, Fow, Frc, Length};
+
+/// Exiting edges of a vertex sorted by FRC, from the most important road class.
+type ExitingEdges<EdgeId, VertexId> = Vec<(EdgeId, VertexId, Frc)>;
+
+/// Graph that partitions the exiting edges of the vertices of the underlying graph by FRC.
+///
+/// The decoder searches the routes between the LRPs along the edges whose FRC is not lower than the
+/// LFRCNP of the LRP: the view serves the eligible edges of a vertex without looking up the FRC of
+/// each of them, which is expensive on very large graphs.
+/// The exiting edges of a vertex are partitioned the first time they are requested, or upfront
+/// with [`GraphView::with_vertices`]. Memory grows with the number of partitioned vertices.
+#[allow(clippy::type_complexity)]
+pub struct GraphView<'a, G: DirectedGraph> {
+    graph: &'a G,
+    exiting_edges: RefCell<FxHashMap<G::VertexId, ExitingEdges<G::EdgeId, G::VertexId>>>,
+}
+
+impl<'a, G: DirectedGraph> GraphView<'a, G> {
+    pub fn new(graph: &'a G) -> Self {
+        Self {
+            graph,
+            exiting_edges: RefCell::default(),
+        }
+    }
+
+    /// Creates the view partitioning the exiting edges of the given vertices upfront.
+    pub fn with_vertices(
+        graph: &'a G,
+        vertices: impl IntoIterator<Item = G::VertexId>,
+    ) -> Result<Self, G::Error> {
+        let view = Self::new(graph);
+        {
+            let mut exiting_edges = view.exiting_edges.borrow_mut();
+            for vertex in vertices {
+                exiting_edges.insert(vertex, view.partition_exiting_edges(vertex)?);
+            }
+        }
+        Ok(view)
+    }
+
+    /// Gets the underlying graph.
+    pub fn graph(&self) -> &'a G {
+        self.graph
+    }
+
+    fn partition_exiting_edges(
+        &self,
+        vertex: G::VertexId,
+    ) -> Result<ExitingEdges<G::EdgeId, G::VertexId>, G::Error> {
+        let mut edges = vec![];
+        for (edge, end_vertex) in self.graph.vertex_exiting_edges(vertex)? {
+            edges.push((edge, end_vertex, self.graph.get_edge_frc(edge)?));
+        }
+        edges.sort_by_key(|&(_, _, frc)| frc);
+        Ok(edges)
+    }
+}
+
+impl<G: DirectedGraph> DirectedGraph for GraphView<'_, G> {
+    type Error = G::Error;
+    type VertexId = G::VertexId;
+    type EdgeId = G::EdgeId;
+
+    fn get_vertex_coordinate(&self, vertex: Self::VertexId) -> Result<Coordinate, Self::Error> {
+        self.graph.get_vertex_coordinate(vertex)
+    }
+
+    fn get_edge_start_vertex(&self, edge: Self::EdgeId) -> Result<Self::VertexId, Self::Error> {
+        self.graph.get_edge_start_vertex(edge)
+    }
+
+    fn get_edge_end_vertex(&self, edge: Self::EdgeId) -> Result<Self::VertexId, Self::Error> {
+        self.graph.get_edge_end_vertex(edge)
+    }
+
+    fn get_edge_length(&self, edge: Self::EdgeId) -> Result<Length, Self::Error> {
+        self.graph.get_edge_length(edge)
+    }
+
+    fn get_edge_cost(&self, edge: Self::EdgeId) -> Result<Length, Self::Error> {
+        self.graph.get_edge_cost(edge)
+    }
+
+    fn get_edge_frc(&self, edge: Self::EdgeId) -> Result<Frc, Self::Error> {
+        self.graph.get_edge_frc(edge)
+    }
+
+    fn get_edge_fow(&self, edge: Self::EdgeId) -> Result<Fow, Self::Error> {
+        self.graph.get_edge_fow(edge)
+    }
+
+    fn vertex_exiting_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        self.graph.vertex_exiting_edges(vertex)
+    }
+
+    fn vertex_entering_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        self.graph.vertex_entering_edges(vertex)
+    }
+
+    fn vertex_exiting_edges_within_frc(
+        &self,
+        vertex: Self::VertexId,
+        lowest_frc: Frc,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        if !self.exiting_edges.borrow().contains_key(&vertex) {
+            let edges = self.partition_exiting_edges(vertex)?;
+            self.exiting_edges.borrow_mut().insert(vertex, edges);
+        }
+
+        let exiting_edges = self.exiting_edges.borrow();
+        let edges = exiting_edges.get(&vertex).map_or(&[][..], Vec::as_slice);
+        let eligible = edges.partition_point(|&(_, _, frc)| frc <= lowest_frc);
+
+        let edges: Vec<_> = edges[..eligible]
+            .iter()
+            .map(|&(edge, end_vertex, _)| (edge, end_vertex))
+            .collect();
+        Ok(edges.into_iter())
+    }
+
+    fn nearest_vertices_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> Result<impl Iterator<Item = (Self::VertexId, Length)>, Self::Error> {
+        self.graph
+            .nearest_vertices_within_distance(coordinate, max_distance)
+    }
+
+    fn nearest_edges_within_distance(
+        &self,
+        coordinate: Coordinate,
+        max_distance: Length,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Length)>, Self::Error> {
+        self.graph
+            .nearest_edges_within_distance(coordinate, max_distance)
+    }
+
+    fn get_distance_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        coordinate: Coordinate,
+    ) -> Result<Length, Self::Error> {
+        self.graph.get_distance_along_edge(edge, coordinate)
+    }
+
+    fn get_coordinate_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        distance: Length,
+    ) -> Result<Coordinate, Self::Error> {
+        self.graph.get_coordinate_along_edge(edge, distance)
+    }
+
+    fn get_elevation_along_edge(
+        &self,
+        edge: Self::EdgeId,
+        distance: Length,
+    ) -> Result<Option<f64>, Self::Error> {
+        self.graph.get_elevation_along_edge(edge, distance)
+    }
+
+    fn get_edge_bearing(
+        &self,
+        edge: Self::EdgeId,
+        distance_from_start: Length,
+        segment_length: Length,
+    ) -> Result<Bearing, Self::Error> {
+        self.graph
+            .get_edge_bearing(edge, distance_from_start, segment_length)
+    }
+
+    fn get_edge_precomputed_bearing(
+        &self,
+        edge: Self::EdgeId,
+        at: EdgeEnd,
+        segment_length: Length,
+    ) -> Result<Option<Bearing>, Self::Error> {
+        self.graph
+            .get_edge_precomputed_bearing(edge, at, segment_length)
+    }
+
+    fn get_edge_index(&self, edge: Self::EdgeId) -> Option<usize> {
+        self.graph.get_edge_index(edge)
+    }
+
+    fn memoize_shortest_path<E>(
+        &self,
+        origin: Self::EdgeId,
+        destination: Self::EdgeId,
+        lowest_frc: Frc,
+        max_length: Length,
+        cost: RouteCost,
+        search: impl FnOnce() -> Result<Option<Path<Self::EdgeId>>, E>,
+    ) -> Result<Option<Path<Self::EdgeId>>, E> {
+        self.graph
+            .memoize_shortest_path(origin, destination, lowest_frc, max_length, cost, search)
+    }
+
+    fn is_turn_restricted(
+        &self,
+        start: Self::EdgeId,
+        end: Self::EdgeId,
+    ) -> Result<bool, Self::Error> {
+        self.graph.is_turn_restricted(start, end)
+    }
+
+    fn get_via_way_restrictions(
+        &self,
+        edge: Self::EdgeId,
+    ) -> Result<Vec<Vec<Self::EdgeId>>, Self::Error> {
+        self.graph.get_via_way_restrictions(edge)
+    }
+
+    fn vertex_degree(&self, vertex: Self::VertexId) -> Result<usize, Self::Error> {
+        self.graph.vertex_degree(vertex)
+    }
+
+    fn vertex_edges(
+        &self,
+        vertex: Self::VertexId,
+    ) -> Result<impl Iterator<Item = (Self::EdgeId, Self::VertexId)>, Self::Error> {
+        self.graph.vertex_edges(vertex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{NETWORK_GRAPH, NetworkGraph, VertexId};
+
+    #[test]
+    fn graph_view_exiting_edges_within_frc_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let view = GraphView::new(graph);
+
+        for vertex in [VertexId(1), VertexId(68)] {
+            for frc in Frc::iter() {
+                let mut edges: Vec<_> = view
+                    .vertex_exiting_edges_within_frc(vertex, frc)
+                    .unwrap()
+                    .collect();
+                edges.sort();
+
+                let mut expected: Vec<_> = graph
+                    .vertex_exiting_edges_within_frc(vertex, frc)
+                    .unwrap()
+                    .collect();
+                expected.sort();
+
+                assert_eq!(edges, expected);
+            }
+        }
+
+        assert_eq!(view.exiting_edges.borrow().len(), 2);
+    }
+
+    #[test]
+    fn graph_view_with_vertices_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let view = GraphView::with_vertices(graph, [VertexId(68)]).unwrap();
+        assert_eq!(view.exiting_edges.borrow().len(), 1);
+
+        let edges: Vec<_> = view
+            .vertex_exiting_edges_within_frc(VertexId(68), Frc::Frc7)
+            .unwrap()
+            .collect();
+        assert_eq!(edges.len(), 4);
+        assert_eq!(view.exiting_edges.borrow().len(), 1);
+    }
+}
@@ -0,0 +1,581 @@
+//! gRPC service exposing the decoder and the encoder of a graph (see `proto/openlr.proto`).
+//!
+//! Edges are identified by 64-bit integers in the messages, so the graph edge IDs must be
+//! convertible from and into `i64`.
+
+// tonic services return Status errors
+#![allow(clippy::result_large_err)]
+
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+
+use crate::grpc::proto::open_lr_server::{OpenLr, OpenLrServer};
+use crate::grpc::proto::{
+    DecodeBatchRequest, DecodeBatchResponse, DecodeRequest, DecodeResponse, DecodeResult,
+    EncodeBatchRequest, EncodeBatchResponse, EncodeRequest, EncodeResponse, EncodeResult,
+    area_location, decode_result, encode_result, location,
+};
+use crate::{
+    Area, AreaLocation, Circle, ClosedLineLocation, Coordinate, DecodeError, DecoderConfig,
+    DirectedGraph, EncodeError, EncoderConfig, Grid, GridSize, Length, LineLocation, Location,
+    Orientation, PoiLocation, PointAlongLineLocation, Polygon, Rectangle, SideOfRoad,
+    decode_base64_openlr_with_warnings, encode_base64_openlr_with_warnings,
+};
+
+/// Messages and service definitions generated from `proto/openlr.proto`.
+pub mod proto {
+    #![allow(clippy::all)]
+    #![allow(clippy::wildcard_enum_match_arm)]
+    tonic::include_proto!("openlr");
+}
+
+/// Implementation of the OpenLR gRPC service for a graph.
+///
+/// Decoding and encoding are CPU-bound, they run on the blocking threads of the runtime (that
+/// share the graph) so that they don't stall the other requests.
+pub struct OpenLrService<G> {
+    pub graph: Arc<G>,
+    pub decoder_config: DecoderConfig,
+    pub encoder_config: EncoderConfig,
+}
+
+impl<G> OpenLrService<G>
+where
+    G: DirectedGraph + Send + Sync + 'static,
+    G::EdgeId: From<i64> + Into<i64>,
+{
+    /// Wraps the service into the gRPC server that can be added to a tonic router.
+    pub fn into_server(self) -> OpenLrServer<Self> {
+        OpenLrServer::new(self)
+    }
+
+    /// Runs the handler with the graph and the configurations on the blocking threads.
+    async fn spawn_blocking<T: Send + 'static>(
+        &self,
+        handler: impl FnOnce(&G, &DecoderConfig, &EncoderConfig) -> T + Send + 'static,
+    ) -> Result<T, Status> {
+        let graph = Arc::clone(&self.graph);
+        let decoder_config = self.decoder_config;
+        let encoder_config = self.encoder_config;
+
+        tokio::task::spawn_blocking(move || handler(&graph, &decoder_config, &encoder_config))
+            .await
+            .map_err(|e| Status::internal(e.to_string()))
+    }
+}
+
+fn decode<G: DirectedGraph>(
+    config: &DecoderConfig,
+    graph: &G,
+    request: DecodeRequest,
+) -> Result<DecodeResponse, Status>
+where
+    G::EdgeId: Into<i64>,
+{
+    let (location, warnings) =
+        decode_base64_openlr_with_warnings(config, graph, request.code).map_err(decode_status)?;
+
+    Ok(DecodeResponse {
+        location: Some(location.into()),
+        warnings: warnings.iter().map(ToString::to_string).collect(),
+    })
+}
+
+fn encode<G: DirectedGraph>(
+    config: &EncoderConfig,
+    graph: &G,
+    request: EncodeRequest,
+) -> Result<EncodeResponse, Status>
+where
+    G::EdgeId: From<i64>,
+{
+    let location = request
+        .location
+        .ok_or_else(|| Status::invalid_argument("Missing location"))?
+        .try_into()?;
+
+    let (code, warnings) =
+        encode_base64_openlr_with_warnings(config, graph, location).map_err(encode_status)?;
+
+    Ok(EncodeResponse {
+        code,
+        warnings: warnings.iter().map(ToString::to_string).collect(),
+    })
+}
+
+#[tonic::async_trait]
+impl<G> OpenLr for OpenLrService<G>
+where
+    G: DirectedGraph + Send + Sync + 'static,
+    G::EdgeId: From<i64> + Into<i64>,
+{
+    async fn decode(
+        &self,
+        request: Request<DecodeRequest>,
+    ) -> Result<Response<DecodeResponse>, Status> {
+        let request = request.into_inner();
+        self.spawn_blocking(move |graph, config, _| decode(config, graph, request))
+            .await?
+            .map(Response::new)
+    }
+
+    async fn encode(
+        &self,
+        request: Request<EncodeRequest>,
+    ) -> Result<Response<EncodeResponse>, Status> {
+        let request = request.into_inner();
+        self.spawn_blocking(move |graph, _, config| encode(config, graph, request))
+            .await?
+            .map(Response::new)
+    }
+
+    async fn decode_batch(
+        &self,
+        request: Request<DecodeBatchRequest>,
+    ) -> Result<Response<DecodeBatchResponse>, Status> {
+        let requests = request.into_inner().requests;
+        let results = self
+            .spawn_blocking(move |graph, config, _| {
+                requests
+                    .into_iter()
+                    .map(|request| {
+                        let result = match decode(config, graph, request) {
+                            Ok(response) => decode_result::Result::Response(response),
+                            Err(status) => {
+                                decode_result::Result::Error(status.message().to_string())
+                            }
+                        };
+                        DecodeResult {
+                            result: Some(result),
+                        }
+                    })
+                    .collect()
+            })
+            .await?;
+
+        Ok(Response::new(DecodeBatchResponse { results }))
+    }
+
+    async fn encode_batch(
+        &self,
+        request: Request<EncodeBatchRequest>,
+    ) -> Result<Response<EncodeBatchResponse>, Status> {
+        let requests = request.into_inner().requests;
+        let results = self
+            .spawn_blocking(move |graph, _, config| {
+                requests
+                    .into_iter()
+                    .map(|request| {
+                        let result = match encode(config, graph, request) {
+                            Ok(response) => encode_result::Result::Response(response),
+                            Err(status) => {
+                                encode_result::Result::Error(status.message().to_string())
+                            }
+                        };
+                        EncodeResult {
+                            result: Some(result),
+                        }
+                    })
+                    .collect()
+            })
+            .await?;
+
+        Ok(Response::new(EncodeBatchResponse { results }))
+    }
+}
+
+fn decode_status<E: std::error::Error>(error: DecodeError<E>) -> Status {
+    match error {
+        DecodeError::GraphError(_) | DecodeError::InconsistentGraph(_) => {
+            Status::internal(error.to_string())
+        }
+        DecodeError::InvalidLocation(_)
+        | DecodeError::LocationTypeNotSupported(_)
+        | DecodeError::DeserializeError(_)
+        | DecodeError::CandidatesNotFound { .. }
+        | DecodeError::RouteNotFound { .. }
+        | DecodeError::CorruptReference { .. } => Status::invalid_argument(error.to_string()),
+        DecodeError::SearchLimitExceeded { .. } => Status::resource_exhausted(error.to_string()),
+    }
+}
+
+fn encode_status<E: std::error::Error>(error: EncodeError<E>) -> Status {
+    match error {
+        EncodeError::GraphError(_) => Status::internal(error.to_string()),
+        EncodeError::InvalidLocation(_)
+        | EncodeError::SerializeError(_)
+        | EncodeError::IntermediateError(_)
+        | EncodeError::RouteNotFound { .. }
+        | EncodeError::InvalidLrp { .. }
+        | EncodeError::MaxDistanceExceeded
+        | EncodeError::InvalidLrpOffsets { .. }
+        | EncodeError::PoiOutOfRange { .. }
+        | EncodeError::TraceNotMatched(_) => Status::invalid_argument(error.to_string()),
+        EncodeError::SearchLimitExceeded { .. } => Status::resource_exhausted(error.to_string()),
+    }
+}
+
+impl<EdgeId: Into<i64>> From<Location<EdgeId>> for proto::Location {
+    fn from(location: Location<EdgeId>) -> Self {
+        let location = match location {
+            Location::Line(line) => location::Location::Line(proto::LineLocation {
+                path: into_path(line.path),
+                pos_offset: line.pos_offset.meters(),
+                neg_offset: line.neg_offset.meters(),
+            }),
+            Location::GeoCoordinate(coordinate) => {
+                location::Location::GeoCoordinate(coordinate.into())
+            }
+            Location::PointAlongLine(point) => location::Location::PointAlongLine(point.into()),
+            Location::Poi(poi) => location::Location::Poi(proto::PoiLocation {
+                point: Some(poi.point.into()),
+                coordinate: Some(poi.coordinate.into()),
+            }),
+            Location::ClosedLine(line) => {
+                location::Location::ClosedLine(proto::ClosedLineLocation {
+                    path: into_path(line.path),
+                })
+            }
+            Location::Area(area) => location::Location::Area(area.into()),
+        };
+
+        Self {
+            location: Some(location),
+        }
+    }
+}
+
+impl<EdgeId: From<i64>> TryFrom<proto::Location> for Location<EdgeId> {
+    type Error = Status;
+
+    fn try_from(location: proto::Location) -> Result<Self, Self::Error> {
+        let location = location
+            .location
+            .ok_or_else(|| Status::invalid_argument("Missing location type"))?;
+
+        Ok(match location {
+            location::Location::Line(line) => Location::Line(LineLocation {
+                path: from_path(line.path),
+                pos_offset: Length::from_meters(line.pos_offset),
+                neg_offset: Length::from_meters(line.neg_offset),
+            }),
+            location::Location::GeoCoordinate(coordinate) => {
+                Location::GeoCoordinate(coordinate.into())
+            }
+            location::Location::PointAlongLine(point) => Location::PointAlongLine(point.into()),
+            location::Location::Poi(poi) => Location::Poi(PoiLocation {
+                point: poi
+                    .point
+                    .ok_or_else(|| Status::invalid_argument("Missing POI point"))?
+                    .into(),
+                coordinate: poi
+                    .coordinate
+                    .ok_or_else(|| Status::invalid_argument("Missing POI coordinate"))?
+                    .into(),
+            }),
+            location::Location::ClosedLine(line) => Location::ClosedLine(ClosedLineLocation {
+                path: from_path(line.path),
+            }),
+            location::Location::Area(area) => Location::Area(area.try_into()?),
+        })
+    }
+}
+
+impl<EdgeId: Into<i64>> From<PointAlongLineLocation<EdgeId>> for proto::PointAlongLineLocation {
+    fn from(point: PointAlongLineLocation<EdgeId>) -> Self {
+        let orientation = match point.orientation {
+            Orientation::Unknown => proto::Orientation::Unknown,
+            Orientation::Forward => proto::Orientation::Forward,
+            Orientation::Backward => proto::Orientation::Backward,
+            Orientation::Both => proto::Orientation::Both,
+        };
+
+        let side = match point.side {
+            SideOfRoad::OnRoadOrUnknown => proto::SideOfRoad::OnRoadOrUnknown,
+            SideOfRoad::Right => proto::SideOfRoad::Right,
+            SideOfRoad::Left => proto::SideOfRoad::Left,
+            SideOfRoad::Both => proto::SideOfRoad::Both,
+        };
+
+        Self {
+            path: into_path(point.path),
+            offset: point.offset.meters(),
+            orientation: orientation.into(),
+            side: side.into(),
+            coordinate: point.coordinate.map(Into::into),
+        }
+    }
+}
+
+impl<EdgeId: From<i64>> From<proto::PointAlongLineLocation> for PointAlongLineLocation<EdgeId> {
+    fn from(point: proto::PointAlongLineLocation) -> Self {
+        let orientation = match point.orientation() {
+            proto::Orientation::Unknown => Orientation::Unknown,
+            proto::Orientation::Forward => Orientation::Forward,
+            proto::Orientation::Backward => Orientation::Backward,
+            proto::Orientation::Both => Orientation::Both,
+        };
+
+        let side = match point.side() {
+            proto::SideOfRoad::OnRoadOrUnknown => SideOfRoad::OnRoadOrUnknown,
+            proto::SideOfRoad::Right => SideOfRoad::Right,
+            proto::SideOfRoad::Left => SideOfRoad::Left,
+            proto::SideOfRoad::Both => SideOfRoad::Both,
+        };
+
+        Self {
+            path: from_path(point.path),
+            offset: Length::from_meters(point.offset),
+            orientation,
+            side,
+            coordinate: point.coordinate.map(Into::into),
+        }
+    }
+}
+
+impl<EdgeId: Into<i64>> From<AreaLocation<EdgeId>> for proto::AreaLocation {
+    fn from(location: AreaLocation<EdgeId>) -> Self {
+        let area = match location.area {
+            Area::Circle(circle) => area_location::Area::Circle(proto::Circle {
+                center: Some(circle.center.into()),
+                radius: circle.radius.meters(),
+            }),
+            Area::Rectangle(rectangle) => area_location::Area::Rectangle(rectangle.into()),
+            Area::Grid(grid) => area_location::Area::Grid(proto::Grid {
+                rect: Some(grid.rect.into()),
+                columns: grid.size.columns.into(),
+                rows: grid.size.rows.into(),
+            }),
+            Area::Polygon(polygon) => area_location::Area::Polygon(proto::Polygon {
+                corners: polygon.corners.into_iter().map(Into::into).collect(),
+            }),
+        };
+
+        Self {
+            edges: into_path(location.edges),
+            area: Some(area),
+        }
+    }
+}
+
+impl<EdgeId: From<i64>> TryFrom<proto::AreaLocation> for AreaLocation<EdgeId> {
+    type Error = Status;
+
+    fn try_from(location: proto::AreaLocation) -> Result<Self, Self::Error> {
+        let missing = |field| move || Status::invalid_argument(format!("Missing {field}"));
+        let grid_size = |value: u32| {
+            u16::try_from(value).map_err(|_| Status::invalid_argument("Grid size out of range"))
+        };
+
+        let area = match location.area.ok_or_else(missing("area type"))? {
+            area_location::Area::Circle(circle) => Area::Circle(Circle {
+                center: circle.center.ok_or_else(missing("circle center"))?.into(),
+                radius: Length::from_meters(circle.radius),
+            }),
+            area_location::Area::Rectangle(rectangle) => Area::Rectangle(rectangle.try_into()?),
+            area_location::Area::Grid(grid) => Area::Grid(Grid {
+                rect: grid.rect.ok_or_else(missing("grid rect"))?.try_into()?,
+                size: GridSize {
+                    columns: grid_size(grid.columns)?,
+                    rows: grid_size(grid.rows)?,
+                },
+            }),
+            area_location::Area::Polygon(polygon) => Area::Polygon(Polygon {
+                corners: polygon.corners.into_iter().map(Into::into).collect(),
+            }),
+        };
+
+        Ok(Self {
+            area,
+            edges: from_path(location.edges),
+        })
+    }
+}
+
+impl From<Rectangle> for proto::Rectangle {
+    fn from(rectangle: Rectangle) -> Self {
+        Self {
+            lower_left: Some(rectangle.lower_left.into()),
+            upper_right: Some(rectangle.upper_right.into()),
+        }
+    }
+}
+
+impl TryFrom<proto::Rectangle> for Rectangle {
+    type Error = Status;
+
+    fn try_from(rectangle: proto::Rectangle) -> Result<Self, Self::Error> {
+        let missing = || Status::invalid_argument("Missing rectangle corner");
+
+        Ok(Self {
+            lower_left: rectangle.lower_left.ok_or_else(missing)?.into(),
+            upper_right: rectangle.upper_right.ok_or_else(missing)?.into(),
+        })
+    }
+}
+
+impl From<Coordinate> for proto::Coordinate {
+    fn from(Coordinate { lon, lat }: Coordinate) -> Self {
+        Self { lon, lat }
+    }
+}
+
+impl From<proto::Coordinate> for Coordinate {
+    fn from(proto::Coordinate { lon, lat }: proto::Coordinate) -> Self {
+        Self { lon, lat }
+    }
+}
+
+fn into_path<EdgeId: Into<i64>>(path: Vec<EdgeId>) -> Vec<i64> {
+    path.into_iter().map(Into::into).collect()
+}
+
+fn from_path<EdgeId: From<i64>>(path: Vec<i64>) -> Vec<EdgeId> {
+    path.into_iter().map(EdgeId::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+
+    fn test_service() -> OpenLrService<&'static NetworkGraph> {
+        OpenLrService {
+            graph: Arc::new(&NETWORK_GRAPH),
+            decoder_config: DecoderConfig::default(),
+            encoder_config: EncoderConfig::default(),
+        }
+    }
+
+    fn line_location() -> proto::Location {
+        proto::Location {
+            location: Some(location::Location::Line(proto::LineLocation {
+                path: vec![8717174, 8717175, 109783],
+                pos_offset: 0.0,
+                neg_offset: 0.0,
+            })),
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn grpc_decode_001() {
+        let request = Request::new(DecodeRequest {
+            code: "CwmShiVYczPJBgCs/y0zAQ==".to_string(),
+        });
+
+        let response = OpenLr::decode(&test_service(), request).await.unwrap();
+
+        assert_eq!(
+            response.into_inner(),
+            DecodeResponse {
+                location: Some(line_location()),
+                warnings: vec![],
+            }
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn grpc_decode_002() {
+        let request = Request::new(DecodeRequest {
+            code: "invalid".to_string(),
+        });
+
+        let status = OpenLr::decode(&test_service(), request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test(tokio::test)]
+    async fn grpc_encode_001() {
+        let request = Request::new(EncodeRequest {
+            location: Some(line_location()),
+        });
+
+        let response = OpenLr::encode(&test_service(), request).await.unwrap();
+
+        assert_eq!(
+            response.into_inner(),
+            EncodeResponse {
+                code: "CwmShiVYcjPJBgCs/y0zAQ==".to_string(),
+                warnings: vec![],
+            }
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn grpc_batch_001() {
+        let request = Request::new(DecodeBatchRequest {
+            requests: vec![
+                DecodeRequest {
+                    code: "CwmShiVYczPJBgCs/y0zAQ==".to_string(),
+                },
+                DecodeRequest {
+                    code: "invalid".to_string(),
+                },
+            ],
+        });
+
+        let response = test_service().decode_batch(request).await.unwrap();
+        let results = response.into_inner().results;
+
+        assert_eq!(results.len(), 2);
+        assert!(matches!(
+            results[0].result,
+            Some(decode_result::Result::Response(_))
+        ));
+        assert!(matches!(
+            results[1].result,
+            Some(decode_result::Result::Error(_))
+        ));
+
+        let request = Request::new(EncodeBatchRequest {
+            requests: vec![
+                EncodeRequest {
+                    location: Some(line_location()),
+                },
+                EncodeRequest { location: None },
+            ],
+        });
+
+        let response = test_service().encode_batch(request).await.unwrap();
+        let results = response.into_inner().results;
+
+        assert_eq!(results.len(), 2);
+        assert!(matches!(
+            results[0].result,
+            Some(encode_result::Result::Response(_))
+        ));
+        assert!(matches!(
+            results[1].result,
+            Some(encode_result::Result::Error(_))
+        ));
+    }
+
+    #[test]
+    fn grpc_area_location_001() {
+        let area = AreaLocation {
+            area: Area::Grid(Grid {
+                rect: Rectangle {
+                    lower_left: Coordinate {
+                        lon: 13.46,
+                        lat: 52.51,
+                    },
+                    upper_right: Coordinate {
+                        lon: 13.47,
+                        lat: 52.52,
+                    },
+                },
+                size: GridSize {
+                    columns: 2,
+                    rows: 3,
+                },
+            }),
+            edges: vec![EdgeId(-8717174), EdgeId(8717174)],
+        };
+
+        let location = proto::Location::from(Location::Area(area.clone()));
+        assert_eq!(Location::try_from(location).unwrap(), Location::Area(area));
+    }
+}
@@ -4,29 +4,130 @@
 #![deny(clippy::panic)]
 #![deny(clippy::wildcard_enum_match_arm)]
 
+#[cfg(feature = "proptest")]
+mod arbitrary;
+#[cfg(feature = "batch")]
+mod batch;
+mod compare;
+#[cfg(feature = "conformance")]
+pub mod conformance;
 mod decoder;
 mod encoder;
 mod error;
 mod format;
 mod graph;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 mod location;
 mod model;
+mod reencode;
+pub mod routing;
+#[cfg(feature = "server")]
+mod server;
+mod warning;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
-pub use decoder::{DecoderConfig, decode_base64_openlr, decode_binary_openlr};
-pub use encoder::{EncoderConfig, encode_base64_openlr, encode_binary_openlr};
+#[cfg(feature = "proptest")]
+pub use arbitrary::{arbitrary_location_reference, arbitrary_location_reference_of};
+#[cfg(feature = "batch")]
+pub use batch::{BatchMode, BatchSummary, process_file};
+pub use compare::{
+    Divergence, Tolerances, compare_base64_openlr, compare_location_references, compare_locations,
+};
+#[cfg(feature = "rayon")]
+pub use decoder::find_area_coverage_parallel;
+pub use decoder::{
+    AreaCoverage, CandidateFinder, CandidateLine, CandidateLinePair, CandidateLineReport,
+    CandidateLines, CandidateNodeReport, CandidateRoute, CandidateRoutes, DecodeReport, Decoder,
+    DecoderConfig, DecoderMetrics, DecoderPipeline, DecoderStages, DefaultCandidateFinder,
+    DefaultOffsetResolver, DefaultPairSelector, DefaultRouteResolver, LocationDecoder,
+    LocationVisitor, LrpCandidates, OffsetResolver, PairSelector, PartialMatch, Projection,
+    RejectedPair, RejectionReason, RouteGap, RouteResolver, UTurnPolicy, decode_base64_openlr,
+    decode_base64_openlr_with_report, decode_base64_openlr_with_visitor,
+    decode_base64_openlr_with_warnings, decode_binary_openlr, decode_binary_openlr_async,
+    decode_binary_openlr_with_report, decode_binary_openlr_with_visitor,
+    decode_binary_openlr_with_warnings, decode_to_geometry, find_area_coverage,
+    find_candidate_lines,
+};
+#[cfg(feature = "rayon")]
+pub use encoder::encode_batch_parallel;
+pub use encoder::{
+    CoverageStep, EncodeReport, Encoder, EncoderConfig, EncoderMetrics, IntermediateLrp,
+    IntermediateReason, LocationEncoder, ResolvedLine, ResolvedLrp, encode_base64_openlr,
+    encode_base64_openlr_with_report, encode_base64_openlr_with_warnings, encode_binary_openlr,
+    encode_binary_openlr_async, encode_binary_openlr_with_report,
+    encode_binary_openlr_with_warnings, encode_from_trace, lrp_line_attributes,
+    lrp_path_attributes, lrp_projected_line_attributes, match_trace, resolve_line_lrps,
+    simplify_line_location,
+};
+#[cfg(feature = "conformance")]
+pub use error::ConformanceError;
+#[cfg(feature = "snapshot")]
+pub use error::NetworkSnapshotError;
 pub use error::{
-    CoordinateError, DecodeError, DeserializeError, EncodeError, LocationError, SerializeError,
+    AsyncGraphError, CoordinateError, DecodeError, DeserializeError, EncodeError,
+    GraphConsistencyError, LocationError, ReencodeError, RouteSearchStats, SerializeError,
+};
+#[cfg(feature = "network")]
+pub use error::{NetworkGeojsonError, NetworkGraphError};
+#[cfg(feature = "osm")]
+pub use error::{OsmError, OsmGraphError};
+#[cfg(feature = "arrow")]
+pub use format::arrow::{decode_batch_schema, decode_record_batch, write_parquet};
+pub use format::binary::{
+    DeserializeOptions, deserialize_base64_openlr, deserialize_base64_openlr_compatible,
+    deserialize_base64_openlr_with_options, deserialize_binary_openlr,
+    deserialize_binary_openlr_compatible, deserialize_binary_openlr_with_options,
+    deserialize_openlr_from_reader, serialize_base64_openlr, serialize_binary_openlr,
+    serialize_openlr_to_writer,
 };
+#[cfg(feature = "extended-precision")]
 pub use format::binary::{
-    deserialize_base64_openlr, deserialize_binary_openlr, serialize_base64_openlr,
-    serialize_binary_openlr,
+    EXTENDED_VERSION, serialize_base64_openlr_extended, serialize_binary_openlr_extended,
 };
-pub use graph::DirectedGraph;
+pub use format::container::{
+    datex2_openlr_payloads, deserialize_datex2_openlr, deserialize_tpeg_openlr,
+    tpeg_openlr_payloads,
+};
+#[cfg(feature = "geojson")]
+pub use format::geojson::{location_geojson, location_reference_geojson};
+#[cfg(feature = "postgis")]
+pub use format::wkb::{
+    SRID_WGS84, copy_row, location_copy_row, location_ewkb, location_wkb, location_wkt,
+};
+#[cfg(feature = "xml")]
+pub use format::xml::{deserialize_xml_openlr, serialize_xml_openlr};
+pub use graph::memo::DecoderContext;
+#[cfg(feature = "network")]
+pub use graph::network::{
+    LineDirection, NetworkEdgeId, NetworkGraph, NetworkLine, NetworkVertexId,
+};
+#[cfg(feature = "osm")]
+pub use graph::osm::{OsmEdgeId, OsmGraph, OsmNode, OsmVertexId, OsmWay};
+pub use graph::path::{
+    Path, find_opposite_edge, find_path_disconnection, is_node_valid, is_opposite_direction,
+    is_path_connected, is_path_loop, is_turn_restricted_after, path_length,
+};
+pub use graph::remote::AsyncDirectedGraph;
+#[cfg(feature = "spatial-index")]
+pub use graph::spatial::{RTreeIndex, SpatialIndex};
+pub use graph::tiled::{TileLoader, TiledGraph};
+pub use graph::view::GraphView;
+pub use graph::{DirectedGraph, EdgeEnd};
 pub use location::{
-    ClosedLineLocation, LineLocation, Location, PoiLocation, PointAlongLineLocation,
+    AreaLocation, ClosedLineLocation, LineLocation, Location, PoiLocation, PointAlongLineLocation,
 };
 pub use model::{
-    Bearing, Circle, ClosedLine, Coordinate, Fow, Frc, Grid, GridSize, Length, Line,
-    LineAttributes, LocationReference, LocationType, Offset, Offsets, Orientation, PathAttributes,
-    Poi, Point, PointAlongLine, Polygon, Rating, RatingScore, Rectangle, SideOfRoad,
+    Area, Bearing, BearingRounding, Circle, ClosedLine, ClosedLineBuilder, Coordinate,
+    Coordinate3d, CoordinatePolicy, Fow, Frc, FrcVarianceTable, Grid, GridSize, Length, Line,
+    LineAttributes, LineBuilder, LocationGeometry, LocationReference, LocationType, Offset,
+    OffsetBucketPosition, OffsetValue, Offsets, Orientation, PathAttributes, Poi, Point,
+    PointAlongLine, PointAlongLineBuilder, Polygon, Rating, RatingScore, Rectangle, SideOfRoad,
+};
+pub use reencode::{ReencodeConfig, reencode};
+#[cfg(feature = "server")]
+pub use server::{
+    DecodeRequest, DecodeResponse, EncodeResponse, ResponseFormat, ServerState, router,
 };
+pub use warning::{DecodeWarning, EncodeWarning, LocationProblem};
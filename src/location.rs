@@ -2,22 +2,34 @@ use std::fmt::Debug;
 
 use tracing::{debug, warn};
 
-use crate::graph::path::is_path_connected;
-use crate::{Coordinate, DirectedGraph, Length, LocationError, Orientation, SideOfRoad};
+use crate::graph::path::{
+    find_opposite_edge, find_path_disconnection, is_opposite_direction, path_length,
+};
+use crate::{
+    Area, Coordinate, Coordinate3d, DirectedGraph, EncoderConfig, Length, LocationError,
+    LocationProblem, Orientation, SideOfRoad,
+};
 
 /// Defines a location (in a map) that can be encoded using the OpenLR encoder
 /// and is also the result of the decoding process.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type")
+)]
 pub enum Location<EdgeId> {
     Line(LineLocation<EdgeId>),
     GeoCoordinate(Coordinate),
     PointAlongLine(PointAlongLineLocation<EdgeId>),
     Poi(PoiLocation<EdgeId>),
     ClosedLine(ClosedLineLocation<EdgeId>),
+    Area(AreaLocation<EdgeId>),
 }
 
 /// Location (in a map) that represents a Line Location Reference.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LineLocation<EdgeId> {
     /// Complete list of edges that form the line.
     pub path: Vec<EdgeId>,
@@ -29,6 +41,7 @@ pub struct LineLocation<EdgeId> {
 
 /// Location of a point (in a map) along a line.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PointAlongLineLocation<EdgeId> {
     /// Complete list of edges that form the line.
     pub path: Vec<EdgeId>,
@@ -41,10 +54,14 @@ pub struct PointAlongLineLocation<EdgeId> {
     /// The point can be on the right side of the line, on the left side of the line, on both sides
     /// of the line, or directly on the line.
     pub side: SideOfRoad,
+    /// Coordinate of the point on the line, resolved by the decoder on the matched edge.
+    /// Ignored by the encoder.
+    pub coordinate: Option<Coordinate>,
 }
 
 /// Location of a point of interest (in a map) with access point along a line.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PoiLocation<EdgeId> {
     /// The access point along a line.
     pub point: PointAlongLineLocation<EdgeId>,
@@ -54,19 +71,185 @@ pub struct PoiLocation<EdgeId> {
 
 /// Location (in a map) that represents a closed Line Location Reference.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClosedLineLocation<EdgeId> {
     /// Complete list of edges that form the line.
     pub path: Vec<EdgeId>,
 }
 
-impl<EdgeId: Copy + Debug> LineLocation<EdgeId> {
+/// Location (in a map) that represents an area Location Reference (other than a closed line).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AreaLocation<EdgeId> {
+    /// The shape of the area.
+    pub area: Area,
+    /// Edges covered by the area: the edges that intersect the area, sorted by ID (see
+    /// [`crate::find_area_coverage`], that also finds the vertices inside the area).
+    pub edges: Vec<EdgeId>,
+}
+
+impl<EdgeId: Copy> Location<EdgeId> {
+    /// Gets the coordinates of the location: a single coordinate for point locations, the
+    /// coordinates of the line for line locations (starting and ending at the offsets), or the
+    /// corners of the area boundary for area locations.
+    /// The geometry of the edges is approximated by the segments between their vertices.
+    pub fn coordinates<G>(&self, graph: &G) -> Result<Vec<Coordinate>, G::Error>
+    where
+        G: DirectedGraph<EdgeId = EdgeId>,
+    {
+        let (path, pos_offset, neg_offset) = match self {
+            Self::GeoCoordinate(coordinate) => return Ok(vec![*coordinate]),
+            Self::Area(area) => return Ok(area.area.boundary()),
+            Self::Poi(poi) => return Ok(vec![poi.coordinate]),
+            Self::PointAlongLine(point) => {
+                return Ok(get_path_coordinate(graph, &point.path, point.offset)?
+                    .into_iter()
+                    .collect());
+            }
+            Self::Line(line) => (&line.path, line.pos_offset, line.neg_offset),
+            Self::ClosedLine(line) => (&line.path, Length::ZERO, Length::ZERO),
+        };
+
+        get_path_coordinates(graph, path, pos_offset, neg_offset)
+    }
+
+    /// Gets the coordinates of the location (see [`Location::coordinates`]) with the elevation of
+    /// the graph at each coordinate, if the graph has elevation data.
+    pub fn coordinates_3d<G>(&self, graph: &G) -> Result<Vec<Coordinate3d>, G::Error>
+    where
+        G: DirectedGraph<EdgeId = EdgeId>,
+    {
+        let coordinates = self.coordinates(graph)?;
+
+        let (path, pos_offset, neg_offset) = match self {
+            Self::GeoCoordinate(_) | Self::Poi(_) | Self::Area(_) => {
+                return Ok(coordinates.into_iter().map(Coordinate3d::from).collect());
+            }
+            Self::PointAlongLine(point) => {
+                let elevation = get_path_position(graph, &point.path, point.offset)?
+                    .map(|(edge, distance)| graph.get_elevation_along_edge(edge, distance))
+                    .transpose()?
+                    .flatten();
+
+                return Ok(coordinates
+                    .into_iter()
+                    .map(|c| Coordinate3d::new(c, elevation))
+                    .collect());
+            }
+            Self::Line(line) => (&line.path, line.pos_offset, line.neg_offset),
+            Self::ClosedLine(line) => (&line.path, Length::ZERO, Length::ZERO),
+        };
+
+        let (Some(&first_edge), Some(&last_edge)) = (path.first(), path.last()) else {
+            return Ok(vec![]);
+        };
+
+        let mut elevations = Vec::with_capacity(coordinates.len());
+        elevations.push(graph.get_elevation_along_edge(first_edge, pos_offset)?);
+
+        for &edge in &path[..path.len() - 1] {
+            let length = graph.get_edge_length(edge)?;
+            elevations.push(graph.get_elevation_along_edge(edge, length)?);
+        }
+
+        let distance = graph.get_edge_length(last_edge)? - neg_offset;
+        elevations.push(graph.get_elevation_along_edge(last_edge, distance)?);
+
+        Ok(coordinates
+            .into_iter()
+            .zip(elevations)
+            .map(|(coordinate, elevation)| Coordinate3d::new(coordinate, elevation))
+            .collect())
+    }
+}
+
+impl<EdgeId: Copy + PartialEq + Debug> LineLocation<EdgeId> {
     pub fn path_length<G>(&self, graph: &G) -> Result<Length, G::Error>
     where
         G: DirectedGraph<EdgeId = EdgeId>,
     {
-        self.path
+        path_length(graph, &self.path)
+    }
+
+    /// Checks whether the line location can be encoded as it is, and returns all the problems
+    /// found: an empty result means that the encoder will neither fail because of the location
+    /// itself nor alter it.
+    ///
+    /// Zero-length edges are only a problem at the start and at the end of the path, where the
+    /// encoder removes them.
+    pub fn validate<G>(
+        &self,
+        graph: &G,
+        config: &EncoderConfig,
+    ) -> Result<Vec<LocationProblem>, G::Error>
+    where
+        G: DirectedGraph<EdgeId = EdgeId>,
+    {
+        let Self {
+            ref path,
+            pos_offset,
+            neg_offset,
+        } = *self;
+
+        if path.is_empty() {
+            return Ok(vec![LocationProblem::EmptyPath]);
+        }
+
+        let mut disconnections = vec![];
+        let mut start = 0;
+        while let Some(i) = find_path_disconnection(graph, &path[start..])? {
+            start += i;
+            disconnections.push(start);
+        }
+
+        let mut problems = vec![];
+
+        for (i, window) in path.windows(2).enumerate() {
+            let [e1, e2] = [window[0], window[1]];
+            let index = i + 1;
+
+            if e1 == e2 {
+                problems.push(LocationProblem::DuplicateEdge { index });
+            } else if disconnections.contains(&index) {
+                problems.push(LocationProblem::DisconnectedPath { index });
+            } else if is_opposite_direction(graph, e1, e2)? {
+                problems.push(LocationProblem::OppositeDirection { index });
+            }
+        }
+
+        let lengths = path
             .iter()
-            .try_fold(Length::ZERO, |acc, &e| Ok(acc + graph.get_edge_length(e)?))
+            .map(|&e| graph.get_edge_length(e))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let head = lengths.iter().take_while(|length| length.is_zero()).count();
+        let tail = lengths[head..]
+            .iter()
+            .rev()
+            .take_while(|l| l.is_zero())
+            .count();
+        let zero_length_edges = (0..head).chain(lengths.len() - tail..lengths.len());
+        problems.extend(zero_length_edges.map(|index| LocationProblem::ZeroLengthEdge { index }));
+
+        let length: Length = lengths.iter().copied().sum();
+        if pos_offset + neg_offset >= length {
+            problems.push(LocationProblem::OffsetsExceedLength {
+                pos_offset,
+                neg_offset,
+                length,
+            });
+        }
+
+        for offset in [pos_offset, neg_offset] {
+            if offset > config.max_lrp_distance {
+                problems.push(LocationProblem::OffsetExceedsMaxLrpDistance {
+                    offset,
+                    max_lrp_distance: config.max_lrp_distance,
+                });
+            }
+        }
+
+        Ok(problems)
     }
 
     /// Construct a valid Line location from the path trimed by its offsets.
@@ -87,6 +270,10 @@ impl<EdgeId: Copy + Debug> LineLocation<EdgeId> {
         G: DirectedGraph<EdgeId = EdgeId>,
     {
         debug!("Trimming {self:?}");
+        if self.path.is_empty() {
+            return Err(LocationError::EmptyPath);
+        }
+
         let path_length = self.path_length(graph)?;
 
         let Self {
@@ -96,7 +283,11 @@ impl<EdgeId: Copy + Debug> LineLocation<EdgeId> {
         } = self;
 
         if pos_offset + neg_offset >= path_length {
-            return Err(LocationError::InvalidOffsets((pos_offset, neg_offset)));
+            return Err(LocationError::OffsetsExceedLength {
+                pos_offset,
+                neg_offset,
+                length: path_length,
+            });
         }
 
         let start_cut = get_path_cut(graph, path.iter().copied(), pos_offset)?;
@@ -125,12 +316,37 @@ impl<EdgeId: Copy + Debug> LineLocation<EdgeId> {
 
         Ok(line)
     }
+
+    /// Gets the same stretch of road in the opposite direction: the path follows the opposite
+    /// direction of every edge backwards, and the positive and negative offsets are swapped.
+    /// Fails if any edge of the path is one-way.
+    pub fn reversed<G>(&self, graph: &G) -> Result<LineLocation<G::EdgeId>, LocationError<G::Error>>
+    where
+        G: DirectedGraph<EdgeId = EdgeId>,
+    {
+        let path = self
+            .path
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(index, &edge)| {
+                find_opposite_edge(graph, edge)?.ok_or(LocationError::OneWayEdge { index })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(LineLocation {
+            path,
+            pos_offset: self.neg_offset,
+            neg_offset: self.pos_offset,
+        })
+    }
 }
 
 /// Returns an error if the Line location is not valid.
 ///
 /// A line location is valid if the following constraints are fulfilled:
-/// - The location is a connected path.
+/// - The location is a non empty path.
+/// - The location is a connected path without consecutive duplicated edges.
 /// - The location is traversable from its start to its end.
 /// - The sum of the positive and negative offset cannot be greater than the total length of the
 ///   location lines.
@@ -145,9 +361,11 @@ fn ensure_line_is_valid<G: DirectedGraph>(
     } = *line;
 
     if path.is_empty() {
-        return Err(LocationError::Empty);
-    } else if !is_path_connected(graph, path)? {
-        return Err(LocationError::NotConnected);
+        return Err(LocationError::EmptyPath);
+    } else if let Some(index) = (1..path.len()).find(|&i| path[i - 1] == path[i]) {
+        return Err(LocationError::DuplicateEdge { index });
+    } else if let Some(index) = find_path_disconnection(graph, path)? {
+        return Err(LocationError::DisconnectedPath { index });
     }
 
     if pos_offset > Length::MAX_BINARY_LRP_DISTANCE {
@@ -166,18 +384,77 @@ fn ensure_line_is_valid<G: DirectedGraph>(
         );
     }
 
+    let length = line.path_length(graph)?;
     if pos_offset >= graph.get_edge_length(path[0])?
         || neg_offset >= graph.get_edge_length(path[path.len() - 1])?
-        || pos_offset + neg_offset >= line.path_length(graph)?
+        || pos_offset + neg_offset >= length
     {
-        return Err(LocationError::InvalidOffsets((pos_offset, neg_offset)));
+        return Err(LocationError::OffsetsExceedLength {
+            pos_offset,
+            neg_offset,
+            length,
+        });
     }
 
     Ok(())
 }
 
+/// Gets the coordinate along the path at the given distance from its start.
+pub(crate) fn get_path_coordinate<G: DirectedGraph>(
+    graph: &G,
+    path: &[G::EdgeId],
+    offset: Length,
+) -> Result<Option<Coordinate>, G::Error> {
+    get_path_position(graph, path, offset)?
+        .map(|(edge, distance)| graph.get_coordinate_along_edge(edge, distance))
+        .transpose()
+}
+
+/// Gets the edge of the path and the distance along it that is at the given distance from the
+/// start of the path.
+fn get_path_position<G: DirectedGraph>(
+    graph: &G,
+    path: &[G::EdgeId],
+    mut offset: Length,
+) -> Result<Option<(G::EdgeId, Length)>, G::Error> {
+    for (i, &edge) in path.iter().enumerate() {
+        let length = graph.get_edge_length(edge)?;
+        if offset <= length || i == path.len() - 1 {
+            return Ok(Some((edge, offset)));
+        }
+        offset -= length;
+    }
+
+    Ok(None)
+}
+
+/// Gets the coordinates of the path starting and ending at the offsets, with the vertices in
+/// between (empty if the path is empty).
+pub(crate) fn get_path_coordinates<G: DirectedGraph>(
+    graph: &G,
+    path: &[G::EdgeId],
+    pos_offset: Length,
+    neg_offset: Length,
+) -> Result<Vec<Coordinate>, G::Error> {
+    let (Some(&first_edge), Some(&last_edge)) = (path.first(), path.last()) else {
+        return Ok(vec![]);
+    };
+
+    let mut coordinates = Vec::with_capacity(path.len() + 1);
+    coordinates.push(graph.get_coordinate_along_edge(first_edge, pos_offset)?);
+
+    for &edge in &path[..path.len() - 1] {
+        coordinates.push(graph.get_vertex_coordinate(graph.get_edge_end_vertex(edge)?)?);
+    }
+
+    let distance = graph.get_edge_length(last_edge)? - neg_offset;
+    coordinates.push(graph.get_coordinate_along_edge(last_edge, distance)?);
+
+    Ok(coordinates)
+}
+
 /// Returns the cut index and the total cut length.
-fn get_path_cut<G, I>(
+pub(crate) fn get_path_cut<G, I>(
     graph: &G,
     edges: I,
     offset: Length,
@@ -209,7 +486,123 @@ mod tests {
     use test_log::test;
 
     use super::*;
-    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+    use crate::graph::tests::{ELEVATED_NETWORK_GRAPH, EdgeId, NETWORK_GRAPH, NetworkGraph};
+
+    #[test]
+    fn validate_line_location_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let config = EncoderConfig::default();
+
+        let location = LineLocation {
+            path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+            pos_offset: Length::from_meters(10.0),
+            neg_offset: Length::from_meters(20.0),
+        };
+
+        assert_eq!(location.validate(graph, &config).unwrap(), []);
+
+        let location = LineLocation {
+            path: vec![],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        };
+
+        assert_eq!(
+            location.validate(graph, &config).unwrap(),
+            [LocationProblem::EmptyPath]
+        );
+    }
+
+    #[test]
+    fn validate_line_location_002() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let config = EncoderConfig::default();
+
+        let location = LineLocation {
+            path: vec![
+                EdgeId(8717174),
+                EdgeId(109783),
+                EdgeId(8717175),
+                EdgeId(8717175),
+            ],
+            pos_offset: Length::from_meters(350.0),
+            neg_offset: Length::from_meters(100.0),
+        };
+
+        assert_eq!(
+            location.validate(graph, &config).unwrap(),
+            [
+                LocationProblem::DisconnectedPath { index: 1 },
+                LocationProblem::DisconnectedPath { index: 2 },
+                LocationProblem::DuplicateEdge { index: 3 },
+                LocationProblem::OffsetsExceedLength {
+                    pos_offset: Length::from_meters(350.0),
+                    neg_offset: Length::from_meters(100.0),
+                    length: Length::from_meters(430.0),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_line_location_003() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let config = EncoderConfig {
+            max_lrp_distance: Length::from_meters(100.0),
+            ..Default::default()
+        };
+
+        let location = LineLocation {
+            path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(-8717175)],
+            pos_offset: Length::from_meters(120.0),
+            neg_offset: Length::ZERO,
+        };
+
+        assert_eq!(
+            location.validate(graph, &config).unwrap(),
+            [
+                LocationProblem::OppositeDirection { index: 2 },
+                LocationProblem::OffsetExceedsMaxLrpDistance {
+                    offset: Length::from_meters(120.0),
+                    max_lrp_distance: Length::from_meters(100.0),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reversed_line_location_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let location = LineLocation {
+            path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+            pos_offset: Length::from_meters(10.0),
+            neg_offset: Length::from_meters(20.0),
+        };
+
+        let reversed = location.reversed(graph).unwrap();
+        assert_eq!(
+            reversed,
+            LineLocation {
+                path: vec![EdgeId(-109783), EdgeId(-8717175), EdgeId(-8717174)],
+                pos_offset: Length::from_meters(20.0),
+                neg_offset: Length::from_meters(10.0),
+            }
+        );
+        assert_eq!(reversed.reversed(graph).unwrap(), location);
+
+        // one-way edges
+        let location = LineLocation {
+            path: vec![EdgeId(16218), EdgeId(16219)],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        };
+
+        assert_eq!(
+            location.reversed(graph).unwrap_err(),
+            LocationError::OneWayEdge { index: 1 }
+        );
+    }
 
     #[test]
     fn trim_line_location_001() {
@@ -310,9 +703,14 @@ mod tests {
             neg_offset,
         };
 
+        let length = location.path_length(graph).unwrap();
         assert_eq!(
             location.trim(graph),
-            Err(LocationError::InvalidOffsets((pos_offset, neg_offset)))
+            Err(LocationError::OffsetsExceedLength {
+                pos_offset,
+                neg_offset,
+                length,
+            })
         );
     }
 
@@ -329,9 +727,137 @@ mod tests {
             neg_offset,
         };
 
+        let length = location.path_length(graph).unwrap();
         assert_eq!(
             location.trim(graph),
-            Err(LocationError::InvalidOffsets((pos_offset, neg_offset)))
+            Err(LocationError::OffsetsExceedLength {
+                pos_offset,
+                neg_offset,
+                length,
+            })
+        );
+    }
+
+    #[test]
+    fn trim_line_location_008() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let location = LineLocation {
+            path: vec![],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        };
+
+        assert_eq!(location.trim(graph), Err(LocationError::EmptyPath));
+    }
+
+    #[test]
+    fn trim_line_location_009() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let location = LineLocation {
+            path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(8717175)],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        };
+
+        assert_eq!(
+            location.trim(graph),
+            Err(LocationError::DuplicateEdge { index: 2 })
+        );
+    }
+
+    #[test]
+    fn trim_line_location_010() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let location = LineLocation {
+            path: vec![EdgeId(8717174), EdgeId(109783), EdgeId(8717175)],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        };
+
+        assert_eq!(
+            location.trim(graph),
+            Err(LocationError::DisconnectedPath { index: 1 })
+        );
+    }
+
+    #[test]
+    fn location_coordinates_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let location = Location::Line(LineLocation {
+            path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+            pos_offset: Length::from_meters(10.0),
+            neg_offset: Length::ZERO,
+        });
+
+        let coordinates = location.coordinates(graph).unwrap();
+        assert_eq!(coordinates.len(), 4);
+        assert_eq!(
+            coordinates[0],
+            graph
+                .get_coordinate_along_edge(EdgeId(8717174), Length::from_meters(10.0))
+                .unwrap()
+        );
+        assert_eq!(
+            coordinates[3],
+            graph
+                .get_vertex_coordinate(graph.get_edge_end_vertex(EdgeId(109783)).unwrap())
+                .unwrap()
+        );
+
+        let location = Location::PointAlongLine(PointAlongLineLocation {
+            path: vec![EdgeId(8717174), EdgeId(8717175)],
+            offset: Length::from_meters(146.0),
+            orientation: Orientation::Unknown,
+            side: SideOfRoad::OnRoadOrUnknown,
+            coordinate: None,
+        });
+
+        let coordinates = location.coordinates(graph).unwrap();
+        assert_eq!(coordinates.len(), 1);
+        assert_eq!(
+            coordinates[0],
+            graph
+                .get_coordinate_along_edge(
+                    EdgeId(8717175),
+                    Length::from_meters(146.0) - graph.get_edge_length(EdgeId(8717174)).unwrap()
+                )
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn location_coordinates_3d_001() {
+        let location = Location::Line(LineLocation {
+            path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+            pos_offset: Length::from_meters(10.0),
+            neg_offset: Length::ZERO,
+        });
+
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let coordinates = location.coordinates(graph).unwrap();
+        let coordinates_3d = location.coordinates_3d(graph).unwrap();
+        assert_eq!(coordinates_3d.len(), coordinates.len());
+        for (coordinate_3d, &coordinate) in coordinates_3d.iter().zip(&coordinates) {
+            assert_eq!(*coordinate_3d, Coordinate3d::new(coordinate, None));
+        }
+
+        let graph: &NetworkGraph = &ELEVATED_NETWORK_GRAPH;
+        let coordinates_3d = location.coordinates_3d(graph).unwrap();
+        assert_eq!(coordinates_3d.len(), coordinates.len());
+        assert_eq!(
+            coordinates_3d[0],
+            Coordinate3d::new(coordinates[0], Some(10.0))
+        );
+        assert_eq!(
+            coordinates_3d[3],
+            Coordinate3d::new(
+                coordinates[3],
+                Some(graph.get_edge_length(EdgeId(109783)).unwrap().meters())
+            )
         );
     }
 }
@@ -1,6 +1,8 @@
+mod builder;
+
 use std::fmt;
 use std::iter::Sum;
-use std::ops::{Add, AddAssign, Mul, MulAssign, Range, Sub, SubAssign};
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Range, Sub, SubAssign};
 use std::str::FromStr;
 
 use approx::abs_diff_eq;
@@ -8,6 +10,9 @@ use ordered_float::OrderedFloat;
 use strum::IntoEnumIterator;
 
 use crate::CoordinateError;
+pub use crate::model::builder::{ClosedLineBuilder, LineBuilder, PointAlongLineBuilder};
+
+pub(crate) const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, strum::EnumIter)]
 #[repr(u8)]
@@ -90,6 +95,7 @@ impl MulAssign<f64> for RatingScore {
 /// The functional road class (FRC) of a line is a road classification
 /// based on the importance of the road represented by the line.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, strum::EnumIter)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum Frc {
     /// Main road, highest importance
@@ -132,10 +138,6 @@ impl Frc {
         }
     }
 
-    pub(crate) const fn is_within_variance(&self, other: &Self) -> bool {
-        self.value() <= other.value() + other.variance()
-    }
-
     pub(crate) fn rating(&self, other: &Self) -> Rating {
         if *self >= Frc::Frc6 && *other >= Frc::Frc6 {
             return Rating::Excellent;
@@ -165,9 +167,80 @@ impl Frc {
     }
 }
 
+/// Map FRCs that are acceptable for each FRC of a location reference point.
+///
+/// The maps used by the encoder and by the decoder often classify the same roads differently, by
+/// one or two classes: the table tells the decoder which lines of its own map can match the lowest
+/// FRC to the next point of an LRP. The default table accepts all the classes up to 3 lower than
+/// [`Frc::Frc0`], [`Frc::Frc1`] and [`Frc::Frc2`], and up to 4 lower than the other classes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FrcVarianceTable {
+    /// Bitset of the accepted map FRCs, indexed by LRP FRC.
+    accepted: [u8; 8],
+}
+
+impl Default for FrcVarianceTable {
+    fn default() -> Self {
+        let mut table = Self::empty();
+        for lrp_frc in Frc::iter() {
+            let accepted =
+                Frc::iter().filter(|frc| frc.value() <= lrp_frc.value() + lrp_frc.variance());
+            table = table.with(lrp_frc, accepted);
+        }
+        table
+    }
+}
+
+impl FrcVarianceTable {
+    /// Table that doesn't accept any map FRC.
+    pub const fn empty() -> Self {
+        Self { accepted: [0; 8] }
+    }
+
+    /// Table that accepts the map FRCs up to the given number of classes lower or higher than
+    /// every LRP FRC.
+    pub fn symmetric(variance: u8) -> Self {
+        let mut table = Self::empty();
+        for lrp_frc in Frc::iter() {
+            let accepted =
+                Frc::iter().filter(|frc| lrp_frc.value().abs_diff(frc.value()) <= variance);
+            table = table.with(lrp_frc, accepted);
+        }
+        table
+    }
+
+    /// Replaces the map FRCs accepted for the LRP FRC.
+    pub fn with(mut self, lrp_frc: Frc, map_frcs: impl IntoIterator<Item = Frc>) -> Self {
+        let accepted = map_frcs
+            .into_iter()
+            .fold(0, |accepted, frc| accepted | (1 << frc.into_byte()));
+        if let Some(entry) = self.accepted.get_mut(usize::from(lrp_frc.into_byte())) {
+            *entry = accepted;
+        }
+        self
+    }
+
+    /// Returns true if a line of the map with the given FRC can match the LRP FRC.
+    pub fn accepts(&self, lrp_frc: Frc, map_frc: Frc) -> bool {
+        let accepted = self.accepted.get(usize::from(lrp_frc.into_byte()));
+        accepted.is_some_and(|accepted| accepted & (1 << map_frc.into_byte()) != 0)
+    }
+
+    /// Gets the map FRCs accepted for the LRP FRC, from the highest to the lowest importance.
+    pub fn accepted(&self, lrp_frc: Frc) -> impl Iterator<Item = Frc> + '_ {
+        Frc::iter().filter(move |&frc| self.accepts(lrp_frc, frc))
+    }
+
+    /// Gets the lowest importance map FRC accepted for the LRP FRC, if any.
+    pub fn lowest_accepted(&self, lrp_frc: Frc) -> Option<Frc> {
+        self.accepted(lrp_frc).last()
+    }
+}
+
 /// Form of Way.
 /// The form of way (FOW) describes the physical road type of a line.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, strum::EnumIter)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum Fow {
     /// The physical road type is unknown.
@@ -245,6 +318,7 @@ impl Fow {
 /// The side of road information (SOR) describes the relationship between the
 /// point of interest and a referenced line.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum SideOfRoad {
     /// Point is directly on (or above) the road, or determination of right/left
@@ -259,9 +333,22 @@ pub enum SideOfRoad {
     Both = 3,
 }
 
+impl SideOfRoad {
+    /// Swaps the left and the right side (see
+    /// [`DecoderConfig::left_hand_traffic`](crate::DecoderConfig::left_hand_traffic)).
+    pub const fn mirrored(self) -> Self {
+        match self {
+            Self::Right => Self::Left,
+            Self::Left => Self::Right,
+            Self::OnRoadOrUnknown | Self::Both => self,
+        }
+    }
+}
+
 /// The orientation information (ORI) describes the relationship between the
 /// point of interest and the direction of a referenced line.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum Orientation {
     /// Point has no sense of orientation, or determination of orientation
@@ -276,56 +363,185 @@ pub enum Orientation {
     Both = 3,
 }
 
+/// Length (or distance) in meters.
+///
+/// By default the length is a floating point number of meters. With the `fixed-point-length`
+/// feature the length is stored as an integer number of millimeters instead: summing lengths is
+/// then exact and independent of the summation order and of the platform floating point
+/// arithmetic, so that the same location is always encoded into the same code.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
-pub struct Length(OrderedFloat<f64>);
+pub struct Length(
+    #[cfg(not(feature = "fixed-point-length"))] OrderedFloat<f64>,
+    #[cfg(feature = "fixed-point-length")] i64,
+);
+
+/// Lengths are (de)serialized as a number of meters.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Length {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.meters())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Length {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f64::deserialize(deserializer).map(Self::from_meters)
+    }
+}
 
+/// Formats the length in meters with one decimal by default (for example `12.3m`), or with the
+/// given precision. The alternate flag formats lengths of at least one kilometer in kilometers
+/// (for example `{:#}` gives `1.2km`).
 impl fmt::Display for Length {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:.1}m", self.meters())
+        let precision = f.precision().unwrap_or(1);
+        if f.alternate() && self.meters().abs() >= 1000.0 {
+            write!(f, "{:.*}km", precision, self.kilometers())
+        } else {
+            write!(f, "{:.*}m", precision, self.meters())
+        }
     }
 }
 
+#[cfg(not(feature = "fixed-point-length"))]
 impl Length {
-    pub const ZERO: Self = Self(OrderedFloat(0.0));
-    pub const MAX: Self = Self(OrderedFloat(f64::MAX));
+    pub const fn from_meters(meters: f64) -> Self {
+        Self(OrderedFloat(meters))
+    }
 
-    /// Binary format version 3 doesn't allow LRPs distances over 15000m.
-    pub const MAX_BINARY_LRP_DISTANCE: Self = Self(OrderedFloat(15000.0));
+    pub const fn meters(&self) -> f64 {
+        self.0.0
+    }
+
+    /// Returns None if the sum is not finite.
+    pub const fn checked_add(self, other: Self) -> Option<Self> {
+        Self::finite(self.meters() + other.meters())
+    }
+
+    /// Returns None if the difference is not finite.
+    pub const fn checked_sub(self, other: Self) -> Option<Self> {
+        Self::finite(self.meters() - other.meters())
+    }
+
+    const fn finite(meters: f64) -> Option<Self> {
+        if meters.is_finite() {
+            Some(Self::from_meters(meters))
+        } else {
+            None
+        }
+    }
+}
 
+#[cfg(feature = "fixed-point-length")]
+impl Length {
+    /// The given meters are rounded to the nearest millimeter (saturating at the bounds).
     pub const fn from_meters(meters: f64) -> Self {
-        Self(OrderedFloat(meters))
+        let millimeters = meters * 1000.0;
+        if millimeters < 0.0 {
+            Self((millimeters - 0.5) as i64)
+        } else {
+            Self((millimeters + 0.5) as i64)
+        }
     }
 
-    pub const fn from_kilometers(kilometers: f64) -> Self {
-        Self::from_meters(kilometers * 1000.0)
+    pub const fn from_millimeters(millimeters: i64) -> Self {
+        Self(millimeters)
     }
 
     pub const fn meters(&self) -> f64 {
-        self.0.0
+        self.0 as f64 / 1000.0
+    }
+
+    pub const fn millimeters(&self) -> i64 {
+        self.0
+    }
+
+    /// Returns None if the sum overflows.
+    pub const fn checked_add(self, other: Self) -> Option<Self> {
+        match self.0.checked_add(other.0) {
+            Some(millimeters) => Some(Self(millimeters)),
+            None => None,
+        }
+    }
+
+    /// Returns None if the difference overflows.
+    pub const fn checked_sub(self, other: Self) -> Option<Self> {
+        match self.0.checked_sub(other.0) {
+            Some(millimeters) => Some(Self(millimeters)),
+            None => None,
+        }
+    }
+}
+
+impl Length {
+    pub const ZERO: Self = Self::from_meters(0.0);
+    pub const MAX: Self = Self::from_meters(f64::MAX);
+
+    /// Binary format version 3 doesn't allow LRPs distances over 15000m.
+    pub const MAX_BINARY_LRP_DISTANCE: Self = Self::from_meters(15000.0);
+
+    pub const fn from_kilometers(kilometers: f64) -> Self {
+        Self::from_meters(kilometers * 1000.0)
     }
 
     pub const fn kilometers(&self) -> f64 {
         self.meters() / 1000.0
     }
 
+    /// Length of the international mile.
+    const MILE_METERS: f64 = 1609.344;
+
+    pub const fn from_miles(miles: f64) -> Self {
+        Self::from_meters(miles * Self::MILE_METERS)
+    }
+
+    pub const fn miles(&self) -> f64 {
+        self.meters() / Self::MILE_METERS
+    }
+
+    /// Returns None if the product is not finite (or overflows).
+    pub fn checked_mul(self, rhs: f64) -> Option<Self> {
+        let meters = self.meters() * rhs;
+        if meters.is_finite() && meters.abs() <= Self::MAX.meters() {
+            Some(Self::from_meters(meters))
+        } else {
+            None
+        }
+    }
+
+    /// Returns None if the quotient is not finite (division by zero).
+    pub fn checked_div(self, rhs: f64) -> Option<Self> {
+        self.checked_mul(rhs.recip())
+    }
+
+    pub fn abs(self) -> Self {
+        self.max(self.reverse())
+    }
+
+    /// Gets the absolute difference between the two lengths.
+    pub fn abs_diff(self, other: Self) -> Self {
+        (self - other).abs()
+    }
+
     pub fn is_zero(&self) -> bool {
         *self == Self::ZERO
     }
 
     pub fn round(self) -> Self {
-        Self(self.0.round().into())
+        Self::from_meters(self.meters().round())
     }
 
     pub fn ceil(self) -> Self {
-        Self(self.0.ceil().into())
+        Self::from_meters(self.meters().ceil())
     }
 
     pub fn floor(self) -> Self {
-        Self(self.0.floor().into())
+        Self::from_meters(self.meters().floor())
     }
 
     pub fn reverse(self) -> Self {
-        Self(self.0 * -1.0)
+        Self::from_meters(-self.meters())
     }
 
     pub fn clamp(self, min: Self, max: Self) -> Self {
@@ -333,6 +549,7 @@ impl Length {
     }
 }
 
+#[cfg(not(feature = "fixed-point-length"))]
 impl Add for Length {
     type Output = Self;
     fn add(self, other: Self) -> Self {
@@ -340,12 +557,21 @@ impl Add for Length {
     }
 }
 
+#[cfg(feature = "fixed-point-length")]
+impl Add for Length {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+}
+
 impl AddAssign for Length {
     fn add_assign(&mut self, rhs: Self) {
-        self.0 += rhs.0;
+        *self = *self + rhs;
     }
 }
 
+#[cfg(not(feature = "fixed-point-length"))]
 impl Sub for Length {
     type Output = Self;
     fn sub(self, other: Self) -> Self {
@@ -353,9 +579,17 @@ impl Sub for Length {
     }
 }
 
+#[cfg(feature = "fixed-point-length")]
+impl Sub for Length {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self(self.0.saturating_sub(other.0))
+    }
+}
+
 impl SubAssign for Length {
     fn sub_assign(&mut self, rhs: Self) {
-        self.0 -= rhs.0;
+        *self = *self - rhs;
     }
 }
 
@@ -368,14 +602,36 @@ impl Sum for Length {
 impl Mul<f64> for Length {
     type Output = Self;
     fn mul(self, rhs: f64) -> Self::Output {
-        Self(self.0 * rhs)
+        Self::from_meters(self.meters() * rhs)
     }
 }
 
 impl Mul<Length> for f64 {
     type Output = Length;
     fn mul(self, rhs: Length) -> Self::Output {
-        Length(OrderedFloat(self) * rhs.0)
+        rhs * self
+    }
+}
+
+impl Div<f64> for Length {
+    type Output = Self;
+    fn div(self, rhs: f64) -> Self::Output {
+        Self::from_meters(self.meters() / rhs)
+    }
+}
+
+/// Ratio between two lengths.
+impl Div for Length {
+    type Output = f64;
+    fn div(self, rhs: Self) -> Self::Output {
+        self.meters() / rhs.meters()
+    }
+}
+
+impl Neg for Length {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        self.reverse()
     }
 }
 
@@ -383,6 +639,7 @@ impl Mul<Length> for f64 {
 /// The physical data format defines the bearing field as an integer value between 0
 /// and 360 whereby “0” is included and “360” is excluded from that range.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bearing(u16);
 
 impl fmt::Display for Bearing {
@@ -393,6 +650,9 @@ impl fmt::Display for Bearing {
 
 impl Bearing {
     pub const NORTH: Self = Self(0);
+    pub const EAST: Self = Self(90);
+    pub const SOUTH: Self = Self(180);
+    pub const WEST: Self = Self(270);
 
     pub const fn from_degrees(degrees: u16) -> Self {
         Self(degrees % 360)
@@ -407,6 +667,41 @@ impl Bearing {
         self.0
     }
 
+    pub fn radians(&self) -> f64 {
+        f64::from(self.0).to_radians()
+    }
+
+    /// Returns None if the degrees are not in the range [0, 360).
+    pub const fn checked_from_degrees(degrees: u16) -> Option<Self> {
+        if degrees < 360 {
+            Some(Self(degrees))
+        } else {
+            None
+        }
+    }
+
+    /// Rotates the bearing clockwise by the given degrees (counterclockwise if negative).
+    pub const fn rotate(&self, degrees: i32) -> Self {
+        Self(((self.0 as i32 + degrees).rem_euclid(360)) as u16)
+    }
+
+    /// Gets the bearing of the opposite direction.
+    pub const fn reverse(&self) -> Self {
+        self.rotate(180)
+    }
+
+    /// Gets the clockwise rotation in the range (-180, 180] that turns the other bearing into this
+    /// bearing (negative if the rotation is counterclockwise).
+    pub const fn signed_difference(&self, other: &Self) -> i32 {
+        let delta = (self.0 as i32 - other.0 as i32).rem_euclid(360);
+        if delta > 180 { delta - 360 } else { delta }
+    }
+
+    /// Returns true if the difference between the bearings is at most the tolerance.
+    pub const fn is_within(&self, other: &Self, tolerance: Self) -> bool {
+        self.difference(other).0 <= tolerance.0
+    }
+
     pub const fn difference(&self, other: &Self) -> Self {
         let delta = (self.0 as i32 - other.0 as i32).unsigned_abs() as u16;
         let degrees = if delta > 180 { 360 - delta } else { delta };
@@ -448,11 +743,91 @@ impl Bearing {
 /// This coordinate pair specifies a geometric point in a digital map.
 /// The lon and lat values are stored in decamicrodegree resolution (five decimals).
 #[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coordinate {
     pub lon: f64,
     pub lat: f64,
 }
 
+/// Coordinate with an optional elevation (in meters above the WGS84 ellipsoid, or any other datum
+/// used by the graph). The elevation is not part of OpenLR, it's ignored by the binary format and
+/// only exposed in the geometry of the locations if the graph has elevation data.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Coordinate3d {
+    pub lon: f64,
+    pub lat: f64,
+    pub elevation: Option<f64>,
+}
+
+impl Coordinate3d {
+    pub const fn new(coordinate: Coordinate, elevation: Option<f64>) -> Self {
+        Self {
+            lon: coordinate.lon,
+            lat: coordinate.lat,
+            elevation,
+        }
+    }
+}
+
+impl From<Coordinate> for Coordinate3d {
+    fn from(coordinate: Coordinate) -> Self {
+        Self::new(coordinate, None)
+    }
+}
+
+impl From<Coordinate3d> for Coordinate {
+    fn from(Coordinate3d { lon, lat, .. }: Coordinate3d) -> Self {
+        Self { lon, lat }
+    }
+}
+
+/// Coordinate of a vertex as stored by the graphs of the crate.
+///
+/// By default the coordinate is stored in double precision. With the `precision-f32` feature it's
+/// stored in single precision instead, halving the memory of the vertices coordinates of large
+/// graphs: the error (up to about 1 meter at the antimeridian) is within the resolution of the
+/// binary format. Coordinates are always computed in double precision.
+///
+/// Only the vertices are affected: the edge geometries and the spatial indices of the graphs are
+/// always stored in double precision, therefore the memory of a whole graph shrinks much less.
+#[cfg(any(test, feature = "network", feature = "osm"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct StoredCoordinate(
+    #[cfg(not(feature = "precision-f32"))] [f64; 2],
+    #[cfg(feature = "precision-f32")] [f32; 2],
+);
+
+#[cfg(any(test, feature = "network", feature = "osm"))]
+#[cfg(not(feature = "precision-f32"))]
+impl StoredCoordinate {
+    pub(crate) const fn store(coordinate: Coordinate) -> Self {
+        Self([coordinate.lon, coordinate.lat])
+    }
+
+    pub(crate) const fn load(self) -> Coordinate {
+        let [lon, lat] = self.0;
+        Coordinate { lon, lat }
+    }
+}
+
+#[cfg(any(test, feature = "network", feature = "osm"))]
+#[cfg(feature = "precision-f32")]
+impl StoredCoordinate {
+    pub(crate) const fn store(coordinate: Coordinate) -> Self {
+        Self([coordinate.lon as f32, coordinate.lat as f32])
+    }
+
+    pub(crate) const fn load(self) -> Coordinate {
+        let [lon, lat] = self.0;
+        Coordinate {
+            lon: lon as f64,
+            lat: lat as f64,
+        }
+    }
+}
+
 impl fmt::Display for Coordinate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:.7},{:.7}", self.lon, self.lat)
@@ -496,6 +871,19 @@ impl From<Coordinate> for (f64, f64) {
     }
 }
 
+/// Handling of the coordinates out of the valid longitude and latitude ranges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoordinatePolicy {
+    /// Out of range coordinates are rejected.
+    #[default]
+    Reject,
+    /// Longitude and latitude are clamped to their ranges.
+    Clamp,
+    /// Coordinates are wrapped around the antimeridian and over the poles (see
+    /// [`Coordinate::normalized`]).
+    Normalize,
+}
+
 impl Coordinate {
     pub const EPSILON: f64 = 180.0 / (1 << 24) as f64;
 
@@ -513,6 +901,57 @@ impl Coordinate {
         }
     }
 
+    /// Constructs a coordinate handling out of range longitude and latitude with the given policy.
+    /// Coordinates that are not finite are always rejected.
+    pub fn with_policy(
+        lon: f64,
+        lat: f64,
+        policy: CoordinatePolicy,
+    ) -> Result<Self, CoordinateError> {
+        let coordinate = Self { lon, lat };
+        if !lon.is_finite() || !lat.is_finite() {
+            return Err(CoordinateError::InvalidLocation(coordinate));
+        }
+
+        match policy {
+            CoordinatePolicy::Reject => Self::new(lon, lat),
+            CoordinatePolicy::Clamp => Ok(Self {
+                lon: lon.clamp(Self::MIN_LON, Self::MAX_LON),
+                lat: lat.clamp(Self::MIN_LAT, Self::MAX_LAT),
+            }),
+            CoordinatePolicy::Normalize => Ok(coordinate.normalized()),
+        }
+    }
+
+    /// Wraps the coordinate into the valid ranges: latitudes beyond a pole continue on the
+    /// opposite meridian, and longitudes are wrapped around the antimeridian into [-180, 180).
+    /// Valid coordinates are left unchanged (including the longitude 180).
+    pub fn normalized(self) -> Self {
+        if self.is_valid() {
+            return self;
+        }
+
+        let Self { mut lon, lat } = self;
+
+        // latitude in [-180, 180) where beyond ±90 goes over the pole
+        let mut lat = (lat + 180.0).rem_euclid(360.0) - 180.0;
+        if lat > Self::MAX_LAT {
+            lat = 180.0 - lat;
+            lon += 180.0;
+        } else if lat < Self::MIN_LAT {
+            lat = -180.0 - lat;
+            lon += 180.0;
+        }
+
+        let lon = if (Self::MIN_LON..=Self::MAX_LON).contains(&lon) {
+            lon
+        } else {
+            (lon + 180.0).rem_euclid(360.0) - 180.0
+        };
+
+        Self { lon, lat }
+    }
+
     /// Returns true only if the coordinate bounds are valid.
     pub const fn is_valid(&self) -> bool {
         self.lon >= Self::MIN_LON
@@ -520,6 +959,57 @@ impl Coordinate {
             && self.lat >= Self::MIN_LAT
             && self.lat <= Self::MAX_LAT
     }
+
+    /// Returns the great-circle (haversine) distance between the two coordinates.
+    pub fn distance(&self, other: &Self) -> Length {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let delta_lat = lat2 - lat1;
+        let delta_lon = (other.lon - self.lon).to_radians();
+
+        let a = (delta_lat / 2.0).sin().powi(2)
+            + lat1.cos() * lat2.cos() * (delta_lon / 2.0).sin().powi(2);
+        let c = 2.0 * a.sqrt().asin();
+
+        Length::from_meters(EARTH_RADIUS_METERS * c)
+    }
+
+    /// Gets the coordinate reached travelling the given distance along the great circle that
+    /// starts with the given bearing (in degrees clockwise from North).
+    pub(crate) fn destination(&self, bearing: f64, distance: Length) -> Self {
+        let (lon, lat) = (self.lon.to_radians(), self.lat.to_radians());
+        let bearing = bearing.to_radians();
+        let angle = distance.meters() / EARTH_RADIUS_METERS;
+
+        let dest_lat = (lat.sin() * angle.cos() + lat.cos() * angle.sin() * bearing.cos()).asin();
+        let dest_lon = lon
+            + (bearing.sin() * angle.sin() * lat.cos())
+                .atan2(angle.cos() - lat.sin() * dest_lat.sin());
+
+        Self {
+            lon: dest_lon.to_degrees(),
+            lat: dest_lat.to_degrees(),
+        }
+        .normalized()
+    }
+
+    /// Gets the initial bearing of the great circle from this coordinate to the other one.
+    pub(crate) fn bearing_to(&self, other: &Self) -> Bearing {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let delta_lon = (other.lon - self.lon).to_radians();
+
+        let y = delta_lon.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * delta_lon.cos();
+
+        Bearing::from_radians(y.atan2(x))
+    }
+
+    /// Linearly interpolates the coordinate at the given fraction of the way to the other one.
+    pub(crate) fn interpolate(&self, other: &Self, fraction: f64) -> Self {
+        Self {
+            lon: self.lon + (other.lon - self.lon) * fraction,
+            lat: self.lat + (other.lat - self.lat) * fraction,
+        }
+    }
 }
 
 impl PartialEq for Coordinate {
@@ -532,6 +1022,7 @@ impl PartialEq for Coordinate {
 /// Line attributes are part of a location reference point and consist of functional road
 /// class (FRC), form of way (FOW) and bearing (BEAR) data.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LineAttributes {
     pub frc: Frc,
     pub fow: Fow,
@@ -542,6 +1033,7 @@ pub struct LineAttributes {
 /// location reference point) and consists of lowest functional road class to next point
 /// (LFRCNP) and distance to next point (DNP) data.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PathAttributes {
     /// Lowest functional road class to next point.
     pub lfrcnp: Frc,
@@ -554,6 +1046,7 @@ pub struct PathAttributes {
 /// refer to a node or line within the road network. The coordinates refer to a node of
 /// a line or a point on a line and the additional attributes refer to attributes of a line.
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     pub coordinate: Coordinate,
     pub line: LineAttributes,
@@ -584,6 +1077,7 @@ impl Point {
 /// Both offsets operate along the lines of the location and are measured in meters.
 /// The offset values are optional and a missing offset value means an offset of 0 meters.
 #[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Offset(f64);
 
 impl fmt::Display for Offset {
@@ -629,6 +1123,131 @@ impl Offset {
     pub const fn range(&self) -> f64 {
         self.0
     }
+
+    /// Converts the offset value into the relative offset of the given length (the distance to the
+    /// next point). Only bucket values are rounded to the binary format resolution, meters and
+    /// ratios are kept as they are until serialized.
+    pub fn from_value(value: OffsetValue, length: Length) -> Self {
+        match value {
+            OffsetValue::Meters(_) if length.is_zero() => Self::ZERO,
+            OffsetValue::Meters(meters) => Self::from_range(meters / length),
+            OffsetValue::Ratio(ratio) => Self::from_range(f64::from(ratio)),
+            OffsetValue::Bucket(bucket) => Self::from_bucket(bucket),
+        }
+    }
+
+    /// Gets the offset in meters along the given length (the distance to the next point).
+    pub fn meters(&self, length: Length) -> Length {
+        Length::from_meters(self.0 * length.meters())
+    }
+
+    /// Gets the offset value in meters along the given length.
+    pub fn to_meters(&self, length: Length) -> OffsetValue {
+        OffsetValue::Meters(self.meters(length))
+    }
+
+    /// Gets the offset value as ratio of the length.
+    pub fn to_ratio(&self) -> OffsetValue {
+        OffsetValue::Ratio(self.0 as f32)
+    }
+
+    /// Gets the offset value as bucket of the binary format.
+    pub fn to_bucket(&self) -> OffsetValue {
+        OffsetValue::Bucket(self.bucket())
+    }
+
+    /// Gets the distance between the offset and the middle of its bucket along the given length:
+    /// the precision lost when the offset is serialized into the binary format.
+    pub fn bucket_error(&self, length: Length) -> Length {
+        if self.0 == 0.0 {
+            return Length::ZERO;
+        }
+
+        let bucketed = Self::from_bucket(self.bucket());
+        self.meters(length).abs_diff(bucketed.meters(length))
+    }
+
+    /// Gets the index of the bucket the offset falls in.
+    pub fn bucket(&self) -> u8 {
+        (self.0 * Self::BUCKETS)
+            .floor()
+            .clamp(0.0, Self::BUCKETS - 1.0) as u8
+    }
+
+    /// Moves the offset to the given position of its bucket. Zero offsets are left unchanged.
+    pub fn with_bucket_position(self, position: OffsetBucketPosition) -> Self {
+        if self.0 == 0.0 {
+            return self;
+        }
+
+        let bucket = f64::from(self.bucket());
+        let range = match position {
+            OffsetBucketPosition::Start => bucket / Self::BUCKETS,
+            OffsetBucketPosition::Middle => (bucket + 0.5) / Self::BUCKETS,
+            OffsetBucketPosition::End => (bucket + 1.0) / Self::BUCKETS,
+        };
+
+        Self::from_range(range)
+    }
+}
+
+/// Value of an offset in one of its representations: the logical format measures the offsets in
+/// meters, while the binary format stores them as buckets of 1/256 of the distance to the next
+/// point. Conversions between the representations depend on that distance (see
+/// [`Offset::from_value`]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OffsetValue {
+    /// Distance from the LRP.
+    Meters(Length),
+    /// Ratio of the distance to the next point, in [0, 1].
+    Ratio(f32),
+    /// Bucket of the binary format.
+    Bucket(u8),
+}
+
+impl OffsetValue {
+    /// Converts the value into meters along the given length (the distance to the next point).
+    pub fn meters(self, length: Length) -> Length {
+        Offset::from_value(self, length).meters(length)
+    }
+
+    /// Converts the value into the ratio of the given length (the distance to the next point).
+    pub fn ratio(self, length: Length) -> f64 {
+        Offset::from_value(self, length).range()
+    }
+
+    /// Converts the value into the bucket of the binary format, which loses precision unless the
+    /// value is already a bucket.
+    pub fn bucket(self, length: Length) -> u8 {
+        Offset::from_value(self, length).bucket()
+    }
+}
+
+/// Position of the decoded offset within its bucket: the binary format encodes offsets as buckets
+/// of 1/256 of the distance to the next point, therefore the offset in meters is only known up
+/// to the bucket length.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OffsetBucketPosition {
+    /// The shortest offset of the bucket.
+    Start,
+    /// The middle of the bucket, which minimizes the maximum error.
+    #[default]
+    Middle,
+    /// The longest offset of the bucket.
+    End,
+}
+
+/// Rounding of the bearing into one of the 32 sectors of 11.25° of the binary format.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BearingRounding {
+    /// The sector containing the bearing, as specified by the binary format.
+    #[default]
+    Floor,
+    /// The sector starting closest to the bearing, ties rounded away from zero.
+    Round,
+    /// The sector starting closest to the bearing, ties rounded to the even sector. Only differs
+    /// from [`BearingRounding::Round`] for bearings that are not whole degrees.
+    HalfEven,
 }
 
 /// A positive offset (POFF) is used to locate the precise start of a location.
@@ -637,6 +1256,7 @@ impl Offset {
 /// precise end of the location and it defines the distance between the end of the
 /// location and the end of the location reference path.
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Offsets {
     pub pos: Offset,
     pub neg: Offset,
@@ -662,12 +1282,20 @@ impl Offsets {
         }
     }
 
+    /// Moves both offsets to the given position of their buckets.
+    pub fn with_bucket_position(self, position: OffsetBucketPosition) -> Self {
+        Self {
+            pos: self.pos.with_bucket_position(position),
+            neg: self.neg.with_bucket_position(position),
+        }
+    }
+
     pub fn distance_from_start(&self, length: Length) -> Length {
-        Length::from_meters(self.pos.range() * length.meters())
+        self.pos.meters(length)
     }
 
     pub fn distance_to_end(&self, length: Length) -> Length {
-        Length::from_meters(self.neg.range() * length.meters())
+        self.neg.meters(length)
     }
 }
 
@@ -676,12 +1304,31 @@ impl Offsets {
 /// There must be at least one location reference point and exactly one last location
 /// reference point. The offset field is optional.
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Line {
     pub points: Vec<Point>,
     pub offsets: Offsets,
 }
 
 impl Line {
+    /// Gets the polyline through the LRPs coordinates trimmed by the offsets: the positive offset
+    /// is relative to the first segment and the negative offset to the last one, as the offsets
+    /// are relative to the distance to next point of the first and of the last but one LRPs.
+    fn approximate_coordinates(&self) -> Vec<Coordinate> {
+        let mut coordinates: Vec<_> = self.points.iter().map(|p| p.coordinate).collect();
+        let n = coordinates.len();
+
+        if n >= 2 {
+            let first = coordinates[0].interpolate(&coordinates[1], self.offsets.pos.range());
+            let last =
+                coordinates[n - 1].interpolate(&coordinates[n - 2], self.offsets.neg.range());
+            coordinates[0] = first;
+            coordinates[n - 1] = last;
+        }
+
+        coordinates
+    }
+
     pub(crate) fn with_capacity(capacity: usize) -> Self {
         Self {
             points: Vec::with_capacity(capacity),
@@ -693,6 +1340,7 @@ impl Line {
 /// A closed line location references the area defined by a closed path (i.e. a circuit)
 /// in the road network. The boundary always consists of road segments.
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClosedLine {
     /// The last LRP refers to the end line of the location whereby the end node of the end line
     /// equals the start node of the start line.
@@ -715,6 +1363,7 @@ impl ClosedLine {
 /// the side of the road where the point is located and the orientation with respect
 /// to the direction of the line can be added.
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PointAlongLine {
     pub points: [Point; 2],
     pub offset: Offset,
@@ -729,6 +1378,7 @@ pub struct PointAlongLine {
 /// Additionally information about the side of the road where the point is located and
 /// the orientation with respect to the direction of the line can be added.
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Poi {
     pub point: PointAlongLine,
     pub coordinate: Coordinate,
@@ -739,32 +1389,272 @@ pub struct Poi {
 /// values that can be everywhere on the surface. The radius is integer-valued and
 /// given in meters.
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Circle {
     pub center: Coordinate,
     pub radius: Length,
 }
 
-/// A rectangle location reference consists of the lower left corner point as a pair
-/// of WGS84 coordinates in absolute format and the upper right corner point, given in
-/// absolute format (large rectangle) or relative format (standard rectangle).
-/// The lower left coordinate must be southwestern of the upper right coordinate.
-#[derive(Debug, Clone, Copy, PartialEq, Default)]
-pub struct Rectangle {
-    pub lower_left: Coordinate,
-    pub upper_right: Coordinate,
-}
+impl Circle {
+    const APPROXIMATE_CORNERS: usize = 32;
+
+    /// Returns true if the coordinate is inside the circle (or on its boundary).
+    pub fn contains(&self, coordinate: &Coordinate) -> bool {
+        self.center.distance(coordinate) <= self.radius
+    }
+
+    /// Returns true if the circle and the rectangle share at least one coordinate.
+    pub fn intersects(&self, rectangle: &Rectangle) -> bool {
+        let nearest = Coordinate {
+            lon: self
+                .center
+                .lon
+                .clamp(rectangle.lower_left.lon, rectangle.upper_right.lon),
+            lat: self
+                .center
+                .lat
+                .clamp(rectangle.lower_left.lat, rectangle.upper_right.lat),
+        };
 
-/// A grid location is a special instance of a rectangle location. It is given
-/// by a base rectangular shape. This base rectangle is the lower left cell of
-/// the grid and can be multiplied to the North (by defining the number of rows)
-/// and to the East (by defining the number of columns).
-#[derive(Debug, Clone, PartialEq)]
+        self.contains(&nearest)
+    }
+
+    /// Gets the area of the circle in square meters (as a spherical cap).
+    pub fn area(&self) -> f64 {
+        let angle = self.radius.meters() / EARTH_RADIUS_METERS;
+        std::f64::consts::TAU * EARTH_RADIUS_METERS.powi(2) * (1.0 - angle.cos())
+    }
+
+    /// Gets the rectangle enclosing the circle.
+    pub fn bounds(&self) -> Rectangle {
+        let (lon_radius, lat_radius) = self.radius_degrees();
+
+        Rectangle {
+            lower_left: Coordinate {
+                lon: self.center.lon - lon_radius,
+                lat: self.center.lat - lat_radius,
+            },
+            upper_right: Coordinate {
+                lon: self.center.lon + lon_radius,
+                lat: self.center.lat + lat_radius,
+            },
+        }
+    }
+
+    /// Gets a polygon approximating the circle, with the given number of corners (at least 3)
+    /// placed at the radius distance from the center along the great circles, clockwise from the
+    /// northernmost one.
+    pub fn to_polygon(&self, n_vertices: usize) -> Polygon {
+        let n_vertices = n_vertices.max(3);
+
+        let corners = (0..n_vertices)
+            .map(|i| {
+                let bearing = 360.0 * i as f64 / n_vertices as f64;
+                self.center.destination(bearing, self.radius)
+            })
+            .collect();
+
+        Polygon { corners }
+    }
+
+    /// Gets the radius in degrees of longitude and latitude.
+    fn radius_degrees(&self) -> (f64, f64) {
+        let lat_radius = (self.radius.meters() / EARTH_RADIUS_METERS).to_degrees();
+        let lon_radius = lat_radius / self.center.lat.to_radians().cos();
+        (lon_radius, lat_radius)
+    }
+
+    /// Gets the corners of a regular polygon inscribed in the circle (using an equirectangular
+    /// approximation, which is accurate enough for the radii of circle locations).
+    fn approximate_corners(&self) -> Vec<Coordinate> {
+        let (lon_radius, lat_radius) = self.radius_degrees();
+
+        (0..Self::APPROXIMATE_CORNERS)
+            .map(|i| {
+                let angle = std::f64::consts::TAU * i as f64 / Self::APPROXIMATE_CORNERS as f64;
+                Coordinate {
+                    lon: self.center.lon + lon_radius * angle.sin(),
+                    lat: self.center.lat + lat_radius * angle.cos(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// A rectangle location reference consists of the lower left corner point as a pair
+/// of WGS84 coordinates in absolute format and the upper right corner point, given in
+/// absolute format (large rectangle) or relative format (standard rectangle).
+/// The lower left coordinate must be southwestern of the upper right coordinate.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rectangle {
+    pub lower_left: Coordinate,
+    pub upper_right: Coordinate,
+}
+
+impl Rectangle {
+    /// Returns true if the coordinate is inside the rectangle (or on its boundary).
+    pub fn contains(&self, coordinate: &Coordinate) -> bool {
+        (self.lower_left.lon..=self.upper_right.lon).contains(&coordinate.lon)
+            && (self.lower_left.lat..=self.upper_right.lat).contains(&coordinate.lat)
+    }
+
+    /// Returns true if the rectangles share at least one coordinate.
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.lower_left.lon <= other.upper_right.lon
+            && other.lower_left.lon <= self.upper_right.lon
+            && self.lower_left.lat <= other.upper_right.lat
+            && other.lower_left.lat <= self.upper_right.lat
+    }
+
+    /// Gets the area of the rectangle in square meters (bounded by meridians and parallels on the
+    /// sphere).
+    pub fn area(&self) -> f64 {
+        let Self {
+            lower_left,
+            upper_right,
+        } = self;
+
+        let lon = (upper_right.lon - lower_left.lon).to_radians();
+        let lat = upper_right.lat.to_radians().sin() - lower_left.lat.to_radians().sin();
+        EARTH_RADIUS_METERS.powi(2) * (lon * lat).abs()
+    }
+
+    /// Gets the corners of the rectangle counterclockwise, starting from the lower left one.
+    pub const fn corners(&self) -> [Coordinate; 4] {
+        let Self {
+            lower_left,
+            upper_right,
+        } = *self;
+
+        [
+            lower_left,
+            Coordinate {
+                lon: upper_right.lon,
+                lat: lower_left.lat,
+            },
+            upper_right,
+            Coordinate {
+                lon: lower_left.lon,
+                lat: upper_right.lat,
+            },
+        ]
+    }
+}
+
+/// A grid location is a special instance of a rectangle location. It is given
+/// by a base rectangular shape. This base rectangle is the lower left cell of
+/// the grid and can be multiplied to the North (by defining the number of rows)
+/// and to the East (by defining the number of columns).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Grid {
     pub rect: Rectangle,
     pub size: GridSize,
 }
 
+impl Grid {
+    /// Gets the rectangle covering all the cells of the grid.
+    pub fn bounds(&self) -> Rectangle {
+        let Rectangle {
+            lower_left,
+            upper_right,
+        } = self.rect;
+
+        Rectangle {
+            lower_left,
+            upper_right: Coordinate {
+                lon: lower_left.lon + (upper_right.lon - lower_left.lon) * self.size.columns as f64,
+                lat: lower_left.lat + (upper_right.lat - lower_left.lat) * self.size.rows as f64,
+            },
+        }
+    }
+
+    /// Returns true if the coordinate is inside any cell of the grid (or on its boundary).
+    pub fn contains(&self, coordinate: &Coordinate) -> bool {
+        self.bounds().contains(coordinate)
+    }
+
+    /// Returns true if any cell of the grid shares at least one coordinate with the rectangle.
+    pub fn intersects(&self, rectangle: &Rectangle) -> bool {
+        self.bounds().intersects(rectangle)
+    }
+
+    /// Gets the area of all the cells of the grid in square meters.
+    pub fn area(&self) -> f64 {
+        self.bounds().area()
+    }
+
+    /// Gets an iterator over the cells of the grid, row by row from the lower left cell (from
+    /// South to North and from West to East).
+    pub fn cells(&self) -> impl Iterator<Item = Rectangle> {
+        let columns = self.size.columns;
+        (0..self.size.rows)
+            .flat_map(move |row| (0..columns).map(move |column| self.cell(column, row)))
+    }
+
+    /// Gets the cell of the grid the coordinate is inside of, if any. Coordinates on the boundary
+    /// between two cells belong to the northern or eastern one, except on the boundary of the grid.
+    pub fn cell_containing(&self, coordinate: &Coordinate) -> Option<Rectangle> {
+        if !self.contains(coordinate) {
+            return None;
+        }
+
+        let Rectangle {
+            lower_left,
+            upper_right,
+        } = self.rect;
+
+        let index = |offset: f64, size: f64, count: u16| {
+            let index = if size > 0.0 {
+                (offset / size).floor()
+            } else {
+                0.0
+            };
+            (index as u16).min(count.saturating_sub(1))
+        };
+
+        let column = index(
+            coordinate.lon - lower_left.lon,
+            upper_right.lon - lower_left.lon,
+            self.size.columns,
+        );
+        let row = index(
+            coordinate.lat - lower_left.lat,
+            upper_right.lat - lower_left.lat,
+            self.size.rows,
+        );
+
+        Some(self.cell(column, row))
+    }
+
+    /// Gets the cell of the grid at the given column (from West) and row (from South).
+    fn cell(&self, column: u16, row: u16) -> Rectangle {
+        let Rectangle {
+            lower_left,
+            upper_right,
+        } = self.rect;
+
+        let width = upper_right.lon - lower_left.lon;
+        let height = upper_right.lat - lower_left.lat;
+
+        let lower_left = Coordinate {
+            lon: lower_left.lon + width * column as f64,
+            lat: lower_left.lat + height * row as f64,
+        };
+
+        Rectangle {
+            lower_left,
+            upper_right: Coordinate {
+                lon: lower_left.lon + width,
+                lat: lower_left.lat + height,
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GridSize {
     pub columns: u16,
     pub rows: u16,
@@ -778,6 +1668,7 @@ pub struct GridSize {
 /// the first corner.
 /// The minimum number of coordinate pairs is three and there exists no maximum number.
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Polygon {
     /// The border is the concatenation of all direct line connections between two subsequent
     /// coordinate pairs and the direct line connection between the last and the first coordinate
@@ -786,6 +1677,66 @@ pub struct Polygon {
 }
 
 impl Polygon {
+    /// Returns true if the coordinate is inside the polygon (ray casting on the longitude and
+    /// latitude plane, coordinates on the boundary may be considered either inside or outside).
+    pub fn contains(&self, coordinate: &Coordinate) -> bool {
+        let Coordinate { lon, lat } = *coordinate;
+        let mut inside = false;
+
+        let edges = self.corners.iter().zip(self.corners.iter().cycle().skip(1));
+        for (a, b) in edges {
+            if (a.lat > lat) != (b.lat > lat)
+                && lon < a.lon + (lat - a.lat) * (b.lon - a.lon) / (b.lat - a.lat)
+            {
+                inside = !inside;
+            }
+        }
+
+        inside
+    }
+
+    /// Returns true if the polygon and the rectangle share at least one coordinate.
+    pub fn intersects(&self, rectangle: &Rectangle) -> bool {
+        if !self.bounds().intersects(rectangle) {
+            return false;
+        }
+
+        let rectangle_corners = rectangle.corners();
+
+        self.corners.iter().any(|corner| rectangle.contains(corner))
+            || rectangle_corners.iter().any(|corner| self.contains(corner))
+            || self.edges().any(|(a, b)| {
+                rectangle_corners
+                    .iter()
+                    .zip(rectangle_corners.iter().cycle().skip(1))
+                    .any(|(c, d)| segments_intersect(a, b, c, d))
+            })
+    }
+
+    /// Gets the area of the polygon in square meters (on the sphere, assuming the edges are short
+    /// enough to be approximated by straight lines in longitude and latitude).
+    pub fn area(&self) -> f64 {
+        let sum: f64 = self
+            .edges()
+            .map(|(a, b)| {
+                (b.lon - a.lon).to_radians()
+                    * (2.0 + a.lat.to_radians().sin() + b.lat.to_radians().sin())
+            })
+            .sum();
+
+        (sum * EARTH_RADIUS_METERS.powi(2) / 2.0).abs()
+    }
+
+    /// Gets the rectangle enclosing the polygon (the default rectangle if it has no corners).
+    pub fn bounds(&self) -> Rectangle {
+        bounds(&self.corners)
+    }
+
+    /// Gets the edges of the polygon boundary, including the one closing the polygon.
+    fn edges(&self) -> impl Iterator<Item = (&Coordinate, &Coordinate)> {
+        self.corners.iter().zip(self.corners.iter().cycle().skip(1))
+    }
+
     pub(crate) fn with_capacity(capacity: usize) -> Self {
         Self {
             corners: Vec::with_capacity(capacity),
@@ -793,6 +1744,56 @@ impl Polygon {
     }
 }
 
+/// Gets the rectangle enclosing the coordinates (the default rectangle if there are none).
+fn bounds(coordinates: &[Coordinate]) -> Rectangle {
+    let Some(first) = coordinates.first() else {
+        return Rectangle::default();
+    };
+
+    coordinates.iter().fold(
+        Rectangle {
+            lower_left: *first,
+            upper_right: *first,
+        },
+        |Rectangle {
+             lower_left,
+             upper_right,
+         },
+         c| Rectangle {
+            lower_left: Coordinate {
+                lon: lower_left.lon.min(c.lon),
+                lat: lower_left.lat.min(c.lat),
+            },
+            upper_right: Coordinate {
+                lon: upper_right.lon.max(c.lon),
+                lat: upper_right.lat.max(c.lat),
+            },
+        },
+    )
+}
+
+/// Returns true if the segments a-b and c-d (on the longitude and latitude plane) share at least
+/// one coordinate.
+fn segments_intersect(a: &Coordinate, b: &Coordinate, c: &Coordinate, d: &Coordinate) -> bool {
+    let cross = |o: &Coordinate, p: &Coordinate, q: &Coordinate| {
+        (p.lon - o.lon) * (q.lat - o.lat) - (p.lat - o.lat) * (q.lon - o.lon)
+    };
+
+    let (d1, d2) = (cross(c, d, a), cross(c, d, b));
+    let (d3, d4) = (cross(a, b, c), cross(a, b, d));
+
+    if d1 * d2 < 0.0 && d3 * d4 < 0.0 {
+        return true;
+    }
+
+    let on_segment = |o: &Coordinate, p: &Coordinate, q: &Coordinate| bounds(&[*o, *p]).contains(q);
+
+    (d1 == 0.0 && on_segment(c, d, a))
+        || (d2 == 0.0 && on_segment(c, d, b))
+        || (d3 == 0.0 && on_segment(a, b, c))
+        || (d4 == 0.0 && on_segment(a, b, d))
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(u8)]
 pub enum LocationType {
@@ -825,6 +1826,11 @@ pub enum LocationType {
 /// size of the location path. Offsets are also used to define a location on a line more precisely
 /// (e.g. point locations along a line) than using the start and end node of that line.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type")
+)]
 pub enum LocationReference {
     // Line Locations
     Line(Line),
@@ -854,6 +1860,150 @@ impl LocationReference {
             Self::ClosedLine(_) => LocationType::ClosedLine,
         }
     }
+
+    /// Gets the location reference points of the location, regardless of its type.
+    /// Locations that are not bound to the road network don't have any points.
+    pub fn points(&self) -> impl ExactSizeIterator<Item = &Point> {
+        let points: &[Point] = match self {
+            Self::Line(line) => &line.points,
+            Self::ClosedLine(line) => &line.points,
+            Self::PointAlongLine(point) => &point.points,
+            Self::Poi(poi) => &poi.point.points,
+            Self::GeoCoordinate(_)
+            | Self::Circle(_)
+            | Self::Rectangle(_)
+            | Self::Grid(_)
+            | Self::Polygon(_) => &[],
+        };
+
+        points.iter()
+    }
+
+    /// Builds an approximation of the location geometry only from the coordinates of the location
+    /// reference (without a map): line locations become a polyline through their LRPs trimmed by
+    /// the offsets, point locations are interpolated between their LRPs and area locations become
+    /// polygons.
+    pub fn approximate_geometry(&self) -> LocationGeometry {
+        match self {
+            Self::Line(line) => LocationGeometry::LineString(line.approximate_coordinates()),
+            Self::ClosedLine(line) => {
+                LocationGeometry::Polygon(line.points.iter().map(|p| p.coordinate).collect())
+            }
+            Self::GeoCoordinate(coordinate) => LocationGeometry::Point(*coordinate),
+            Self::PointAlongLine(point) => {
+                let [first, last] = point.points.map(|p| p.coordinate);
+                LocationGeometry::Point(first.interpolate(&last, point.offset.range()))
+            }
+            Self::Poi(poi) => LocationGeometry::Point(poi.coordinate),
+            Self::Circle(circle) => LocationGeometry::Polygon(circle.approximate_corners()),
+            Self::Rectangle(rectangle) => LocationGeometry::Polygon(rectangle.corners().into()),
+            Self::Grid(grid) => LocationGeometry::Polygon(grid.bounds().corners().into()),
+            Self::Polygon(polygon) => LocationGeometry::Polygon(polygon.corners.clone()),
+        }
+    }
+}
+
+/// Shape of an area location: the area location references that are not a closed line.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type")
+)]
+pub enum Area {
+    Circle(Circle),
+    Rectangle(Rectangle),
+    Grid(Grid),
+    Polygon(Polygon),
+}
+
+impl From<Area> for LocationReference {
+    fn from(area: Area) -> Self {
+        match area {
+            Area::Circle(circle) => Self::Circle(circle),
+            Area::Rectangle(rectangle) => Self::Rectangle(rectangle),
+            Area::Grid(grid) => Self::Grid(grid),
+            Area::Polygon(polygon) => Self::Polygon(polygon),
+        }
+    }
+}
+
+impl Area {
+    /// Returns true if the coordinate is inside the area (or on its boundary).
+    pub fn contains(&self, coordinate: &Coordinate) -> bool {
+        match self {
+            Self::Circle(circle) => circle.contains(coordinate),
+            Self::Rectangle(rectangle) => rectangle.contains(coordinate),
+            Self::Grid(grid) => grid.contains(coordinate),
+            Self::Polygon(polygon) => polygon.contains(coordinate),
+        }
+    }
+
+    /// Returns true if the area and the rectangle share at least one coordinate.
+    pub fn intersects(&self, rectangle: &Rectangle) -> bool {
+        match self {
+            Self::Circle(circle) => circle.intersects(rectangle),
+            Self::Rectangle(area) => area.intersects(rectangle),
+            Self::Grid(grid) => grid.intersects(rectangle),
+            Self::Polygon(polygon) => polygon.intersects(rectangle),
+        }
+    }
+
+    /// Gets the surface of the area in square meters.
+    pub fn area(&self) -> f64 {
+        match self {
+            Self::Circle(circle) => circle.area(),
+            Self::Rectangle(rectangle) => rectangle.area(),
+            Self::Grid(grid) => grid.area(),
+            Self::Polygon(polygon) => polygon.area(),
+        }
+    }
+
+    /// Gets the corners of the area boundary (the circle boundary is approximated by a polygon),
+    /// the first corner is not repeated at the end.
+    pub fn boundary(&self) -> Vec<Coordinate> {
+        match self {
+            Self::Circle(circle) => circle.approximate_corners(),
+            Self::Rectangle(rectangle) => rectangle.corners().into(),
+            Self::Grid(grid) => grid.bounds().corners().into(),
+            Self::Polygon(polygon) => polygon.corners.clone(),
+        }
+    }
+
+    /// Gets the center and the radius of a circle that encloses the area.
+    pub fn bounding_circle(&self) -> (Coordinate, Length) {
+        if let Self::Circle(circle) = self {
+            return (circle.center, circle.radius);
+        }
+
+        let boundary = self.boundary();
+        let Rectangle {
+            lower_left: min,
+            upper_right: max,
+        } = bounds(&boundary);
+
+        let center = Coordinate {
+            lon: (min.lon + max.lon) / 2.0,
+            lat: (min.lat + max.lat) / 2.0,
+        };
+
+        let radius = boundary
+            .iter()
+            .map(|corner| center.distance(corner))
+            .max()
+            .unwrap_or(Length::ZERO);
+
+        (center, radius.ceil())
+    }
+}
+
+/// Geometry of a location reference approximated from its coordinates only.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LocationGeometry {
+    Point(Coordinate),
+    LineString(Vec<Coordinate>),
+    /// The exterior ring of the polygon, the first corner is not repeated at the end.
+    Polygon(Vec<Coordinate>),
 }
 
 #[cfg(test)]
@@ -872,6 +2022,95 @@ mod tests {
         }
     }
 
+    #[test]
+    fn frc_variance_table() {
+        let table = FrcVarianceTable::default();
+        assert!(table.accepts(Frc::Frc2, Frc::Frc0));
+        assert!(table.accepts(Frc::Frc2, Frc::Frc5));
+        assert!(!table.accepts(Frc::Frc2, Frc::Frc6));
+        assert!(table.accepts(Frc::Frc3, Frc::Frc7));
+        assert_eq!(table.lowest_accepted(Frc::Frc1), Some(Frc::Frc4));
+        assert_eq!(table.lowest_accepted(Frc::Frc5), Some(Frc::Frc7));
+
+        let table = FrcVarianceTable::symmetric(1);
+        assert_eq!(
+            table.accepted(Frc::Frc3).collect::<Vec<_>>(),
+            [Frc::Frc2, Frc::Frc3, Frc::Frc4]
+        );
+        assert_eq!(
+            table.accepted(Frc::Frc0).collect::<Vec<_>>(),
+            [Frc::Frc0, Frc::Frc1]
+        );
+
+        let table = table.with(Frc::Frc3, [Frc::Frc5]);
+        assert!(!table.accepts(Frc::Frc3, Frc::Frc3));
+        assert_eq!(table.lowest_accepted(Frc::Frc3), Some(Frc::Frc5));
+        assert_eq!(FrcVarianceTable::empty().lowest_accepted(Frc::Frc3), None);
+    }
+
+    #[test]
+    fn offset_value_conversions() {
+        let length = Length::from_meters(512.0);
+
+        let offset = Offset::from_value(OffsetValue::Meters(Length::from_meters(100.0)), length);
+        assert_eq!(offset.meters(length), Length::from_meters(100.0));
+        assert_eq!(
+            offset.to_meters(length),
+            OffsetValue::Meters(Length::from_meters(100.0))
+        );
+        assert_eq!(offset.to_bucket(), OffsetValue::Bucket(50));
+        assert_eq!(offset.bucket_error(length), Length::from_meters(1.0));
+
+        let offset = Offset::from_value(OffsetValue::Ratio(0.25), length);
+        assert_eq!(offset.to_ratio(), OffsetValue::Ratio(0.25));
+        assert_eq!(offset.meters(length), Length::from_meters(128.0));
+
+        assert_eq!(
+            OffsetValue::Bucket(50).meters(length),
+            Length::from_meters(101.0)
+        );
+        assert_eq!(OffsetValue::Ratio(0.5).bucket(length), 128);
+        assert_eq!(
+            OffsetValue::Meters(Length::from_meters(64.0)).ratio(length),
+            0.125
+        );
+        assert_eq!(
+            Offset::from_value(OffsetValue::Meters(Length::from_meters(10.0)), Length::ZERO),
+            Offset::ZERO
+        );
+        assert_eq!(Offset::ZERO.bucket_error(length), Length::ZERO);
+    }
+
+    #[test]
+    fn offset_bucket_position() {
+        let offset = Offset::from_bucket(10);
+        assert_eq!(offset.bucket(), 10);
+        assert_eq!(
+            offset.with_bucket_position(OffsetBucketPosition::Middle),
+            offset
+        );
+        assert_eq!(
+            offset.with_bucket_position(OffsetBucketPosition::Start),
+            Offset::from_range(10.0 / 256.0)
+        );
+        assert_eq!(
+            offset.with_bucket_position(OffsetBucketPosition::End),
+            Offset::from_range(11.0 / 256.0)
+        );
+        assert_eq!(
+            Offset::from_bucket(10)
+                .with_bucket_position(OffsetBucketPosition::Start)
+                .bucket(),
+            10
+        );
+        assert_eq!(Offset::from_bucket(255).bucket(), 255);
+        assert_eq!(Offset::from_range(1.0).bucket(), 255);
+        assert_eq!(
+            Offset::ZERO.with_bucket_position(OffsetBucketPosition::End),
+            Offset::ZERO
+        );
+    }
+
     #[test]
     fn bearing_degrees() {
         assert_eq!(Bearing::from_degrees(0).degrees(), 0);
@@ -893,6 +2132,64 @@ mod tests {
         assert_eq!(Bearing::from_radians(-PI - FRAC_PI_2).degrees(), 90);
     }
 
+    #[test]
+    fn bearing_arithmetic() {
+        assert_eq!(Bearing::checked_from_degrees(359), Some(Bearing(359)));
+        assert_eq!(Bearing::checked_from_degrees(360), None);
+
+        assert_eq!(Bearing::NORTH.rotate(-90), Bearing::WEST);
+        assert_eq!(Bearing::WEST.rotate(450), Bearing::NORTH);
+        assert_eq!(Bearing::EAST.reverse(), Bearing::WEST);
+        assert_eq!(Bearing::SOUTH.reverse(), Bearing::NORTH);
+        assert!((Bearing::SOUTH.radians() - PI).abs() < 1e-12);
+
+        assert_eq!(Bearing::EAST.signed_difference(&Bearing::NORTH), 90);
+        assert_eq!(Bearing::NORTH.signed_difference(&Bearing::EAST), -90);
+        assert_eq!(
+            Bearing::from_degrees(10).signed_difference(&Bearing::from_degrees(350)),
+            20
+        );
+        assert_eq!(Bearing::SOUTH.signed_difference(&Bearing::NORTH), 180);
+        assert_eq!(Bearing::NORTH.signed_difference(&Bearing::SOUTH), 180);
+
+        let tolerance = Bearing::from_degrees(30);
+        assert!(Bearing::from_degrees(345).is_within(&Bearing::from_degrees(10), tolerance));
+        assert!(!Bearing::from_degrees(345).is_within(&Bearing::EAST, tolerance));
+
+        assert_eq!(Bearing::from_degrees(45).to_string(), "45°");
+    }
+
+    #[test]
+    fn length_arithmetic() {
+        assert_eq!(Length::from_kilometers(1.5), Length::from_meters(1500.0));
+        assert_eq!(Length::from_miles(1.0), Length::from_meters(1609.344));
+        assert!((Length::from_meters(1609.344).miles() - 1.0).abs() < 1e-9);
+
+        let length = Length::from_meters(100.0);
+        assert_eq!(length.checked_add(length), Some(Length::from_meters(200.0)));
+        assert_eq!(length.checked_sub(length), Some(Length::ZERO));
+        assert_eq!(length.checked_mul(2.5), Some(Length::from_meters(250.0)));
+        assert_eq!(length.checked_mul(f64::NAN), None);
+        assert_eq!(Length::MAX.checked_mul(2.0), None);
+        assert_eq!(length.checked_div(4.0), Some(Length::from_meters(25.0)));
+        assert_eq!(length.checked_div(0.0), None);
+
+        assert_eq!(length / 4.0, Length::from_meters(25.0));
+        assert_eq!(length / Length::from_meters(400.0), 0.25);
+        assert_eq!(-length, Length::from_meters(-100.0));
+        assert_eq!((-length).abs(), length);
+        assert_eq!(
+            Length::from_meters(30.0).abs_diff(Length::from_meters(50.0)),
+            Length::from_meters(20.0)
+        );
+
+        assert_eq!(Length::from_meters(12.34).to_string(), "12.3m");
+        assert_eq!(format!("{:.2}", Length::from_meters(12.346)), "12.35m");
+        assert_eq!(format!("{:#}", Length::from_meters(1234.0)), "1.2km");
+        assert_eq!(format!("{:#.3}", Length::from_meters(1234.0)), "1.234km");
+        assert_eq!(format!("{:#}", Length::from_meters(999.0)), "999.0m");
+    }
+
     #[test]
     fn invalid_coordinate() {
         assert!(Coordinate::new(180.1, 46.78186).is_err());
@@ -901,6 +2198,75 @@ mod tests {
         assert!(Coordinate::new(-180.1, -90.1).is_err());
     }
 
+    #[test]
+    fn coordinate_policy() {
+        let new = |lon, lat, policy| Coordinate::with_policy(lon, lat, policy);
+
+        for policy in [
+            CoordinatePolicy::Reject,
+            CoordinatePolicy::Clamp,
+            CoordinatePolicy::Normalize,
+        ] {
+            assert_eq!(
+                new(13.4, 52.5, policy).unwrap(),
+                Coordinate {
+                    lon: 13.4,
+                    lat: 52.5
+                }
+            );
+            assert!(new(f64::NAN, 52.5, policy).is_err());
+            assert!(new(13.4, f64::INFINITY, policy).is_err());
+        }
+
+        assert!(new(181.0, 91.0, CoordinatePolicy::Reject).is_err());
+        assert_eq!(
+            new(181.0, -91.0, CoordinatePolicy::Clamp).unwrap(),
+            Coordinate {
+                lon: 180.0,
+                lat: -90.0
+            }
+        );
+        assert_eq!(
+            new(181.0, 45.0, CoordinatePolicy::Normalize).unwrap(),
+            Coordinate {
+                lon: -179.0,
+                lat: 45.0
+            }
+        );
+        assert_eq!(
+            new(-540.5, 45.0, CoordinatePolicy::Normalize).unwrap(),
+            Coordinate {
+                lon: 179.5,
+                lat: 45.0
+            }
+        );
+        assert_eq!(
+            new(10.0, 91.0, CoordinatePolicy::Normalize).unwrap(),
+            Coordinate {
+                lon: -170.0,
+                lat: 89.0
+            }
+        );
+        assert_eq!(
+            new(-10.0, -100.0, CoordinatePolicy::Normalize).unwrap(),
+            Coordinate {
+                lon: 170.0,
+                lat: -80.0
+            }
+        );
+        assert_eq!(
+            Coordinate {
+                lon: 180.0,
+                lat: 0.0
+            }
+            .normalized(),
+            Coordinate {
+                lon: 180.0,
+                lat: 0.0
+            }
+        );
+    }
+
     #[test]
     fn coordinate_parse_to_string() {
         assert_eq!(
@@ -924,4 +2290,401 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn coordinate_distance() {
+        let berlin = Coordinate::new(13.4050, 52.5200).unwrap();
+        let paris = Coordinate::new(2.3522, 48.8566).unwrap();
+
+        assert_eq!(berlin.distance(&berlin), Length::ZERO);
+        assert_eq!(
+            berlin.distance(&paris).round(),
+            paris.distance(&berlin).round()
+        );
+        assert_eq!((berlin.distance(&paris).meters() / 1000.0).round(), 877.0);
+    }
+
+    #[test]
+    fn coordinate_stored() {
+        for coordinate in [
+            Coordinate::new(6.12683, 49.60851).unwrap(),
+            Coordinate::new(-179.99999, -89.99999).unwrap(),
+            Coordinate::new(179.99999, 89.99999).unwrap(),
+        ] {
+            let stored = StoredCoordinate::store(coordinate).load();
+            assert!((stored.lon - coordinate.lon).abs() < 1e-5);
+            assert!((stored.lat - coordinate.lat).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn location_reference_points() {
+        let point = |lon| Point {
+            coordinate: Coordinate { lon, lat: 0.0 },
+            ..Point::default()
+        };
+
+        let line = LocationReference::Line(Line {
+            points: vec![point(1.0), point(2.0), point(3.0)],
+            offsets: Offsets::default(),
+        });
+        let lons: Vec<_> = line.points().map(|p| p.coordinate.lon).collect();
+        assert_eq!(lons, [1.0, 2.0, 3.0]);
+
+        let poi = LocationReference::Poi(Poi {
+            point: PointAlongLine {
+                points: [point(4.0), point(5.0)],
+                ..PointAlongLine::default()
+            },
+            coordinate: Coordinate::default(),
+        });
+        let lons: Vec<_> = poi.points().map(|p| p.coordinate.lon).collect();
+        assert_eq!(lons, [4.0, 5.0]);
+
+        let circle = LocationReference::Circle(Circle::default());
+        assert_eq!(circle.points().len(), 0);
+    }
+
+    #[test]
+    fn location_reference_approximate_geometry() {
+        let point = |lon, lat| Point {
+            coordinate: Coordinate { lon, lat },
+            ..Point::default()
+        };
+
+        let line = LocationReference::Line(Line {
+            points: vec![point(1.0, 1.0), point(2.0, 2.0)],
+            offsets: Offsets::default(),
+        });
+        assert_eq!(
+            line.approximate_geometry(),
+            LocationGeometry::LineString(vec![
+                Coordinate { lon: 1.0, lat: 1.0 },
+                Coordinate { lon: 2.0, lat: 2.0 }
+            ])
+        );
+
+        let line = LocationReference::Line(Line {
+            points: vec![point(1.0, 1.0), point(2.0, 2.0), point(3.0, 2.0)],
+            offsets: Offsets {
+                pos: Offset::from_range(0.5),
+                neg: Offset::from_range(0.25),
+            },
+        });
+        assert_eq!(
+            crate::decode_to_geometry(&line),
+            LocationGeometry::LineString(vec![
+                Coordinate { lon: 1.5, lat: 1.5 },
+                Coordinate { lon: 2.0, lat: 2.0 },
+                Coordinate {
+                    lon: 2.75,
+                    lat: 2.0
+                }
+            ])
+        );
+
+        let point_along_line = LocationReference::PointAlongLine(PointAlongLine {
+            points: [point(1.0, 1.0), point(2.0, 3.0)],
+            offset: Offset::from_range(0.5),
+            ..PointAlongLine::default()
+        });
+        assert_eq!(
+            point_along_line.approximate_geometry(),
+            LocationGeometry::Point(Coordinate { lon: 1.5, lat: 2.0 })
+        );
+
+        let grid = LocationReference::Grid(Grid {
+            rect: Rectangle {
+                lower_left: Coordinate { lon: 1.0, lat: 1.0 },
+                upper_right: Coordinate { lon: 2.0, lat: 1.5 },
+            },
+            size: GridSize {
+                columns: 3,
+                rows: 2,
+            },
+        });
+        assert_eq!(
+            grid.approximate_geometry(),
+            LocationGeometry::Polygon(vec![
+                Coordinate { lon: 1.0, lat: 1.0 },
+                Coordinate { lon: 4.0, lat: 1.0 },
+                Coordinate { lon: 4.0, lat: 2.0 },
+                Coordinate { lon: 1.0, lat: 2.0 }
+            ])
+        );
+
+        let center = Coordinate::new(13.4050, 52.5200).unwrap();
+        let circle = LocationReference::Circle(Circle {
+            center,
+            radius: Length::from_meters(500.0),
+        });
+        let LocationGeometry::Polygon(corners) = circle.approximate_geometry() else {
+            unreachable!()
+        };
+        assert_eq!(corners.len(), 32);
+        for corner in corners {
+            assert_eq!(center.distance(&corner).round(), Length::from_meters(500.0));
+        }
+    }
+
+    #[test]
+    fn area_contains() {
+        let rectangle = Area::Rectangle(Rectangle {
+            lower_left: Coordinate { lon: 0.0, lat: 0.0 },
+            upper_right: Coordinate { lon: 2.0, lat: 2.0 },
+        });
+        assert!(rectangle.contains(&Coordinate { lon: 1.0, lat: 1.0 }));
+        assert!(rectangle.contains(&Coordinate { lon: 2.0, lat: 0.0 }));
+        assert!(!rectangle.contains(&Coordinate { lon: 2.1, lat: 1.0 }));
+
+        // L-shaped polygon
+        let polygon = Area::Polygon(Polygon {
+            corners: vec![
+                Coordinate { lon: 0.0, lat: 0.0 },
+                Coordinate { lon: 2.0, lat: 0.0 },
+                Coordinate { lon: 2.0, lat: 1.0 },
+                Coordinate { lon: 1.0, lat: 1.0 },
+                Coordinate { lon: 1.0, lat: 2.0 },
+                Coordinate { lon: 0.0, lat: 2.0 },
+            ],
+        });
+        assert!(polygon.contains(&Coordinate { lon: 0.5, lat: 1.5 }));
+        assert!(polygon.contains(&Coordinate { lon: 1.5, lat: 0.5 }));
+        assert!(!polygon.contains(&Coordinate { lon: 1.5, lat: 1.5 }));
+        assert!(!polygon.contains(&Coordinate { lon: 3.0, lat: 0.5 }));
+
+        let circle = Area::Circle(Circle {
+            center: Coordinate { lon: 0.0, lat: 0.0 },
+            radius: Length::from_meters(1000.0),
+        });
+        assert!(circle.contains(&Coordinate {
+            lon: 0.005,
+            lat: 0.0
+        }));
+        assert!(!circle.contains(&Coordinate {
+            lon: 0.01,
+            lat: 0.0
+        }));
+
+        let (center, radius) = polygon.bounding_circle();
+        assert_eq!(center, Coordinate { lon: 1.0, lat: 1.0 });
+        for corner in polygon.boundary() {
+            assert!(center.distance(&corner) <= radius);
+        }
+    }
+
+    #[test]
+    fn area_intersects() {
+        let rectangle = Rectangle {
+            lower_left: Coordinate { lon: 1.5, lat: 1.5 },
+            upper_right: Coordinate { lon: 3.0, lat: 3.0 },
+        };
+
+        assert!(
+            Rectangle {
+                lower_left: Coordinate { lon: 0.0, lat: 0.0 },
+                upper_right: Coordinate { lon: 2.0, lat: 2.0 },
+            }
+            .intersects(&rectangle)
+        );
+        assert!(
+            !Rectangle {
+                lower_left: Coordinate { lon: 0.0, lat: 0.0 },
+                upper_right: Coordinate { lon: 1.0, lat: 1.0 },
+            }
+            .intersects(&rectangle)
+        );
+
+        // L-shaped polygon: its bounds intersect the rectangle, but not the polygon itself
+        let polygon = Polygon {
+            corners: vec![
+                Coordinate { lon: 0.0, lat: 0.0 },
+                Coordinate { lon: 2.0, lat: 0.0 },
+                Coordinate { lon: 2.0, lat: 1.0 },
+                Coordinate { lon: 1.0, lat: 1.0 },
+                Coordinate { lon: 1.0, lat: 2.0 },
+                Coordinate { lon: 0.0, lat: 2.0 },
+            ],
+        };
+        assert!(!polygon.intersects(&rectangle));
+        assert!(polygon.intersects(&Rectangle {
+            lower_left: Coordinate { lon: 0.5, lat: 0.5 },
+            upper_right: Coordinate { lon: 0.6, lat: 0.6 },
+        }));
+        assert!(polygon.intersects(&Rectangle {
+            lower_left: Coordinate {
+                lon: 0.5,
+                lat: -1.0
+            },
+            upper_right: Coordinate { lon: 0.6, lat: 3.0 },
+        }));
+
+        let circle = Circle {
+            center: Coordinate { lon: 0.0, lat: 0.0 },
+            radius: Length::from_meters(1000.0),
+        };
+        assert!(circle.intersects(&Rectangle {
+            lower_left: Coordinate {
+                lon: 0.005,
+                lat: -1.0
+            },
+            upper_right: Coordinate { lon: 1.0, lat: 1.0 },
+        }));
+        assert!(!circle.intersects(&Rectangle {
+            lower_left: Coordinate {
+                lon: 0.01,
+                lat: -1.0
+            },
+            upper_right: Coordinate { lon: 1.0, lat: 1.0 },
+        }));
+        assert!(circle.bounds().contains(&Coordinate {
+            lon: 0.0089,
+            lat: -0.0089
+        }));
+    }
+
+    #[test]
+    fn area_surface() {
+        // 1 degree of latitude (and of longitude at the equator) is ~111.2 km
+        let rectangle = Rectangle {
+            lower_left: Coordinate { lon: 0.0, lat: 0.0 },
+            upper_right: Coordinate { lon: 1.0, lat: 1.0 },
+        };
+        assert_eq!((rectangle.area() / 1e6).round(), 12364.0);
+
+        let triangle = Polygon {
+            corners: vec![
+                Coordinate { lon: 0.0, lat: 0.0 },
+                Coordinate { lon: 1.0, lat: 0.0 },
+                Coordinate { lon: 1.0, lat: 1.0 },
+            ],
+        };
+        assert_eq!((triangle.area() / 1e6).round(), 6182.0);
+
+        let circle = Circle {
+            center: Coordinate { lon: 0.0, lat: 0.0 },
+            radius: Length::from_meters(1000.0),
+        };
+        assert_eq!(circle.area().round(), (PI * 1e6).round());
+
+        let grid = Grid {
+            rect: rectangle,
+            size: GridSize {
+                columns: 3,
+                rows: 2,
+            },
+        };
+        assert_eq!((Area::Grid(grid).area() / 1e6).round(), 74171.0);
+    }
+
+    #[test]
+    fn circle_to_polygon() {
+        let circle = Circle {
+            center: Coordinate::new(13.4050, 52.5200).unwrap(),
+            radius: Length::from_meters(5000.0),
+        };
+
+        let polygon = circle.to_polygon(16);
+        assert_eq!(polygon.corners.len(), 16);
+        assert!(polygon.contains(&circle.center));
+        for corner in &polygon.corners {
+            let distance = circle.center.distance(corner).meters();
+            assert!((distance - 5000.0).abs() < 1e-6, "{distance}");
+        }
+
+        assert!((polygon.corners[0].lon - circle.center.lon).abs() < 1e-9);
+        assert!(polygon.corners[0].lat > circle.center.lat);
+        assert!(polygon.corners[4].lon > circle.center.lon);
+
+        assert_eq!(circle.to_polygon(0).corners.len(), 3);
+    }
+
+    #[test]
+    fn grid_cells() {
+        let grid = Grid {
+            rect: Rectangle {
+                lower_left: Coordinate { lon: 0.0, lat: 0.0 },
+                upper_right: Coordinate { lon: 1.0, lat: 2.0 },
+            },
+            size: GridSize {
+                columns: 3,
+                rows: 2,
+            },
+        };
+
+        let cells: Vec<_> = grid.cells().map(|cell| cell.lower_left).collect();
+        assert_eq!(
+            cells,
+            [
+                Coordinate { lon: 0.0, lat: 0.0 },
+                Coordinate { lon: 1.0, lat: 0.0 },
+                Coordinate { lon: 2.0, lat: 0.0 },
+                Coordinate { lon: 0.0, lat: 2.0 },
+                Coordinate { lon: 1.0, lat: 2.0 },
+                Coordinate { lon: 2.0, lat: 2.0 },
+            ]
+        );
+
+        assert!(grid.contains(&Coordinate { lon: 2.5, lat: 3.5 }));
+        assert!(!grid.contains(&Coordinate { lon: 3.5, lat: 3.5 }));
+        assert!(grid.cells().all(|cell| grid.bounds().intersects(&cell)));
+    }
+
+    #[test]
+    fn grid_cell_containing() {
+        let grid = Grid {
+            rect: Rectangle {
+                lower_left: Coordinate { lon: 0.0, lat: 0.0 },
+                upper_right: Coordinate { lon: 1.0, lat: 2.0 },
+            },
+            size: GridSize {
+                columns: 3,
+                rows: 2,
+            },
+        };
+
+        let cell = |coordinate| {
+            grid.cell_containing(&coordinate)
+                .map(|cell| cell.lower_left)
+        };
+        assert_eq!(
+            cell(Coordinate { lon: 2.5, lat: 3.5 }),
+            Some(Coordinate { lon: 2.0, lat: 2.0 })
+        );
+        assert_eq!(
+            cell(Coordinate { lon: 1.0, lat: 0.5 }),
+            Some(Coordinate { lon: 1.0, lat: 0.0 })
+        );
+        assert_eq!(
+            cell(Coordinate { lon: 3.0, lat: 4.0 }),
+            Some(Coordinate { lon: 2.0, lat: 2.0 })
+        );
+        assert_eq!(cell(Coordinate { lon: 3.5, lat: 3.5 }), None);
+
+        assert!(grid.cells().all(|cell| {
+            let Rectangle {
+                lower_left,
+                upper_right,
+            } = cell;
+            let center = Coordinate {
+                lon: (lower_left.lon + upper_right.lon) / 2.0,
+                lat: (lower_left.lat + upper_right.lat) / 2.0,
+            };
+            grid.cell_containing(&center) == Some(cell)
+        }));
+    }
+
+    #[test]
+    #[cfg(feature = "fixed-point-length")]
+    fn length_fixed_point_sum() {
+        let lengths = [0.1, 0.2, 0.3, 1234.5678, 0.0004, 99.9996].map(Length::from_meters);
+
+        let forward: Length = lengths.iter().copied().sum();
+        let backward: Length = lengths.iter().rev().copied().sum();
+
+        assert_eq!(forward, backward);
+        assert_eq!(forward.millimeters(), 1335168);
+        assert_eq!(Length::from_meters(-0.0015).millimeters(), -2);
+        assert_eq!(Length::MAX + Length::from_meters(1.0), Length::MAX);
+    }
 }
@@ -0,0 +1,416 @@
+//! Builders of the location references that validate them as the binary format does, so that the
+//! built location references can always be serialized.
+
+use crate::{
+    ClosedLine, Coordinate, CoordinateError, Length, Line, LineAttributes, Offset, Offsets,
+    Orientation, PathAttributes, Poi, Point, PointAlongLine, SerializeError, SideOfRoad,
+};
+
+/// Builder of a [`Line`] location reference: one or more LRPs, each with the path to the next one,
+/// followed by the last LRP.
+#[derive(Debug, Clone, Default)]
+pub struct LineBuilder {
+    points: Vec<Point>,
+    last_point: Option<Point>,
+    offsets: Offsets,
+}
+
+impl LineBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an LRP (but the last one) with the attributes of its line and of the path to the next
+    /// LRP.
+    pub fn point(
+        mut self,
+        coordinate: Coordinate,
+        line: LineAttributes,
+        path: PathAttributes,
+    ) -> Self {
+        self.points.push(Point {
+            coordinate,
+            line,
+            path: Some(path),
+        });
+        self
+    }
+
+    /// Sets the last LRP with the attributes of its line.
+    pub const fn last_point(mut self, coordinate: Coordinate, line: LineAttributes) -> Self {
+        self.last_point = Some(Point {
+            coordinate,
+            line,
+            path: None,
+        });
+        self
+    }
+
+    /// Sets the positive offset, relative to the DNP of the first LRP.
+    pub const fn pos_offset(mut self, offset: Offset) -> Self {
+        self.offsets.pos = offset;
+        self
+    }
+
+    /// Sets the negative offset, relative to the DNP of the last but one LRP.
+    pub const fn neg_offset(mut self, offset: Offset) -> Self {
+        self.offsets.neg = offset;
+        self
+    }
+
+    /// Builds the line, failing if it has less than 2 LRPs (including the last one) or if any of
+    /// its values cannot be serialized.
+    pub fn build(self) -> Result<Line, SerializeError> {
+        let Self {
+            mut points,
+            last_point,
+            offsets,
+        } = self;
+
+        let Some(last_point) = last_point.filter(|_| !points.is_empty()) else {
+            return Err(SerializeError::InvalidLine);
+        };
+        points.push(last_point);
+
+        check_points(&points)?;
+        offsets.pos.try_into_byte()?;
+        offsets.neg.try_into_byte()?;
+
+        Ok(Line { points, offsets })
+    }
+}
+
+/// Builder of a [`ClosedLine`] location reference: two or more LRPs, each with the path to the
+/// next one (the path of the last LRP leads back to the first one), followed by the attributes of
+/// the line that closes the location.
+#[derive(Debug, Clone, Default)]
+pub struct ClosedLineBuilder {
+    points: Vec<Point>,
+    last_line: Option<LineAttributes>,
+}
+
+impl ClosedLineBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an LRP with the attributes of its line and of the path to the next LRP.
+    pub fn point(
+        mut self,
+        coordinate: Coordinate,
+        line: LineAttributes,
+        path: PathAttributes,
+    ) -> Self {
+        self.points.push(Point {
+            coordinate,
+            line,
+            path: Some(path),
+        });
+        self
+    }
+
+    /// Sets the attributes of the line that ends at the first LRP.
+    pub const fn last_line(mut self, line: LineAttributes) -> Self {
+        self.last_line = Some(line);
+        self
+    }
+
+    /// Builds the closed line, failing if it has less than 2 LRPs, has no last line or if any of
+    /// its values cannot be serialized.
+    pub fn build(self) -> Result<ClosedLine, SerializeError> {
+        let Some(last_line) = self.last_line.filter(|_| self.points.len() >= 2) else {
+            return Err(SerializeError::InvalidLine);
+        };
+
+        check_points(&self.points)?;
+        last_line.bearing.try_into_byte()?;
+
+        Ok(ClosedLine {
+            points: self.points,
+            last_line,
+        })
+    }
+}
+
+/// Builder of a [`PointAlongLine`] (or of a [`Poi`]) location reference: the first LRP with the
+/// path to the last one and the last LRP, with the offset of the point along the path.
+#[derive(Debug, Clone, Default)]
+pub struct PointAlongLineBuilder {
+    first_point: Option<Point>,
+    last_point: Option<Point>,
+    offset: Offset,
+    orientation: Orientation,
+    side: SideOfRoad,
+}
+
+impl PointAlongLineBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the first LRP with the attributes of its line and of the path to the last LRP.
+    pub const fn first_point(
+        mut self,
+        coordinate: Coordinate,
+        line: LineAttributes,
+        path: PathAttributes,
+    ) -> Self {
+        self.first_point = Some(Point {
+            coordinate,
+            line,
+            path: Some(path),
+        });
+        self
+    }
+
+    /// Sets the last LRP with the attributes of its line.
+    pub const fn last_point(mut self, coordinate: Coordinate, line: LineAttributes) -> Self {
+        self.last_point = Some(Point {
+            coordinate,
+            line,
+            path: None,
+        });
+        self
+    }
+
+    /// Sets the positive offset of the point, relative to the DNP of the first LRP.
+    pub const fn offset(mut self, offset: Offset) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub const fn orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    pub const fn side(mut self, side: SideOfRoad) -> Self {
+        self.side = side;
+        self
+    }
+
+    /// Builds the point along line, failing if any of the LRPs is missing or if any of its values
+    /// cannot be serialized.
+    pub fn build(self) -> Result<PointAlongLine, SerializeError> {
+        let (Some(first_point), Some(last_point)) = (self.first_point, self.last_point) else {
+            return Err(SerializeError::InvalidLine);
+        };
+
+        let points = [first_point, last_point];
+        check_points(&points)?;
+        self.offset.try_into_byte()?;
+
+        Ok(PointAlongLine {
+            points,
+            offset: self.offset,
+            orientation: self.orientation,
+            side: self.side,
+        })
+    }
+
+    /// Builds the point of interest at the coordinate, with the point along line as its access
+    /// point (see [`PointAlongLineBuilder::build`]).
+    pub fn build_poi(self, coordinate: Coordinate) -> Result<Poi, SerializeError> {
+        let point = self.build()?;
+        check_coordinate(coordinate, Some(point.points[0].coordinate))?;
+        Ok(Poi { point, coordinate })
+    }
+}
+
+/// Checks the values of the LRPs, whose coordinates are serialized relative to the previous one.
+fn check_points(points: &[Point]) -> Result<(), SerializeError> {
+    let mut previous = None;
+
+    for point in points {
+        check_coordinate(point.coordinate, previous)?;
+        point.line.bearing.try_into_byte()?;
+        if let Some(path) = point.path {
+            // longer DNPs would saturate the DNP byte
+            if path.dnp > Length::MAX_BINARY_LRP_DISTANCE {
+                return Err(SerializeError::InvalidLength(path.dnp));
+            }
+            path.dnp.try_dnp_into_byte()?;
+        }
+        previous = Some(point.coordinate);
+    }
+
+    Ok(())
+}
+
+fn check_coordinate(
+    coordinate: Coordinate,
+    previous: Option<Coordinate>,
+) -> Result<(), SerializeError> {
+    if !coordinate.is_valid() {
+        return Err(CoordinateError::InvalidLocation(coordinate).into());
+    }
+
+    if previous.is_some_and(|previous| !coordinate.is_relative_in_range(&previous)) {
+        return Err(SerializeError::RelativeCoordinateOutOfRange(coordinate));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::{
+        Bearing, Fow, Frc, LocationReference, deserialize_base64_openlr, serialize_base64_openlr,
+    };
+
+    const fn attributes(bearing: u16) -> LineAttributes {
+        LineAttributes {
+            frc: Frc::Frc3,
+            fow: Fow::MultipleCarriageway,
+            bearing: Bearing::from_degrees(bearing),
+        }
+    }
+
+    const fn path(dnp: f64) -> PathAttributes {
+        PathAttributes {
+            lfrcnp: Frc::Frc3,
+            dnp: Length::from_meters(dnp),
+        }
+    }
+
+    #[test]
+    fn builder_line() {
+        let code = "CwRbWyNG9RpsCQCb/jsbtAT/6/+jK1lE";
+        let LocationReference::Line(expected) = deserialize_base64_openlr(code).unwrap() else {
+            unreachable!()
+        };
+
+        let [first, second, last] = expected.points.as_slice() else {
+            unreachable!()
+        };
+        let line = LineBuilder::new()
+            .point(first.coordinate, first.line, first.path.unwrap())
+            .point(second.coordinate, second.line, second.path.unwrap())
+            .last_point(last.coordinate, last.line)
+            .pos_offset(expected.offsets.pos)
+            .neg_offset(expected.offsets.neg)
+            .build()
+            .unwrap();
+        assert_eq!(line, expected);
+
+        let line = LocationReference::Line(line);
+        assert_eq!(serialize_base64_openlr(&line).unwrap(), code);
+    }
+
+    #[test]
+    fn builder_line_invalid() {
+        let a = Coordinate {
+            lon: 6.1268198,
+            lat: 49.6085178,
+        };
+        let b = Coordinate {
+            lon: 6.1283698,
+            lat: 49.6039878,
+        };
+
+        assert_eq!(
+            LineBuilder::new().last_point(b, attributes(0)).build(),
+            Err(SerializeError::InvalidLine)
+        );
+        assert_eq!(
+            LineBuilder::new()
+                .point(a, attributes(0), path(100.0))
+                .build(),
+            Err(SerializeError::InvalidLine)
+        );
+        assert_eq!(
+            LineBuilder::new()
+                .point(a, attributes(0), path(100.0))
+                .last_point(b, attributes(0))
+                .neg_offset(Offset::from_range(1.0))
+                .build(),
+            Err(SerializeError::InvalidOffset(Offset::from_range(1.0)))
+        );
+        assert_eq!(
+            LineBuilder::new()
+                .point(a, attributes(0), path(20000.0))
+                .last_point(b, attributes(0))
+                .build(),
+            Err(SerializeError::InvalidLength(Length::from_meters(20000.0)))
+        );
+        assert_eq!(
+            LineBuilder::new()
+                .point(a, attributes(0), path(-1.0))
+                .last_point(b, attributes(0))
+                .build(),
+            Err(SerializeError::InvalidLength(Length::from_meters(-1.0)))
+        );
+
+        let far = Coordinate {
+            lon: 7.0,
+            lat: 49.0,
+        };
+        assert_eq!(
+            LineBuilder::new()
+                .point(a, attributes(0), path(100.0))
+                .last_point(far, attributes(0))
+                .build(),
+            Err(SerializeError::RelativeCoordinateOutOfRange(far))
+        );
+
+        let line = LineBuilder::new()
+            .point(a, attributes(0), path(100.0))
+            .last_point(b, attributes(0))
+            .pos_offset(Offset::from_range(0.5))
+            .build()
+            .unwrap();
+        assert_eq!(line.offsets.pos, Offset::from_range(0.5));
+    }
+
+    #[test]
+    fn builder_closed_line_and_point_along_line() {
+        let a = Coordinate {
+            lon: 6.1268198,
+            lat: 49.6085178,
+        };
+        let b = Coordinate {
+            lon: 6.1283698,
+            lat: 49.6039878,
+        };
+
+        assert_eq!(
+            ClosedLineBuilder::new()
+                .point(a, attributes(0), path(100.0))
+                .last_line(attributes(90))
+                .build(),
+            Err(SerializeError::InvalidLine)
+        );
+        let closed_line = ClosedLineBuilder::new()
+            .point(a, attributes(0), path(100.0))
+            .point(b, attributes(180), path(100.0))
+            .last_line(attributes(90))
+            .build()
+            .unwrap();
+        assert_eq!(closed_line.points.len(), 2);
+        assert!(serialize_base64_openlr(&LocationReference::ClosedLine(closed_line)).is_ok());
+
+        assert_eq!(
+            PointAlongLineBuilder::new()
+                .first_point(a, attributes(0), path(100.0))
+                .build(),
+            Err(SerializeError::InvalidLine)
+        );
+        let builder = PointAlongLineBuilder::new()
+            .first_point(a, attributes(0), path(100.0))
+            .last_point(b, attributes(180))
+            .offset(Offset::from_range(0.25))
+            .orientation(Orientation::Forward)
+            .side(SideOfRoad::Right);
+
+        let point = builder.clone().build().unwrap();
+        assert_eq!(point.orientation, Orientation::Forward);
+        assert_eq!(point.side, SideOfRoad::Right);
+
+        let poi = builder.build_poi(b).unwrap();
+        assert_eq!(poi.point, point);
+        assert!(serialize_base64_openlr(&LocationReference::Poi(poi)).is_ok());
+    }
+}
@@ -0,0 +1,206 @@
+//! Canonicalization of location references: a location reference is decoded and immediately
+//! re-encoded on the local map, so that the references of the same location produced by different
+//! encoders (or on different maps) become equal, a common normalization step before aggregating
+//! traffic information.
+
+use crate::decoder::decode_location_reference;
+use crate::{
+    DecoderConfig, DecoderPipeline, DecoderStages, DirectedGraph, EncoderConfig, LocationReference,
+    Point, ReencodeError, deserialize_binary_openlr, encode_binary_openlr,
+};
+
+/// Configuration of [`reencode`].
+#[derive(Debug, Clone, Copy)]
+pub struct ReencodeConfig<S = DecoderPipeline> {
+    pub decoder: DecoderConfig<S>,
+    pub encoder: EncoderConfig,
+    /// Keeps the offsets of the location reference exactly, as long as the re-encoded reference
+    /// has the same LRPs (coordinates and DNPs) the offsets are relative to (otherwise the offsets
+    /// are re-encoded).
+    /// The offsets decoded in meters and encoded back could otherwise move by one bucket.
+    /// The offset edges are kept by the decoder (see [`DecoderConfig::trim_offset_edges`]), so
+    /// that the re-encoded reference covers the same path.
+    pub preserve_offsets: bool,
+}
+
+impl Default for ReencodeConfig {
+    fn default() -> Self {
+        Self {
+            decoder: DecoderConfig::default(),
+            encoder: EncoderConfig::default(),
+            preserve_offsets: false,
+        }
+    }
+}
+
+/// Decodes the location reference on the graph and encodes the decoded location back, producing
+/// the canonical location reference of the location on the graph.
+pub fn reencode<G: DirectedGraph, S: DecoderStages + Clone>(
+    config: &ReencodeConfig<S>,
+    graph: &G,
+    location: &LocationReference,
+) -> Result<LocationReference, ReencodeError<G::Error>> {
+    let mut decoder = config.decoder.clone();
+    if config.preserve_offsets {
+        decoder.trim_offset_edges = false;
+    }
+
+    let decoded = decode_location_reference(&decoder, graph, location.clone())
+        .map_err(ReencodeError::DecodeError)?;
+    let data = encode_binary_openlr(&config.encoder, graph, decoded)
+        .map_err(ReencodeError::EncodeError)?;
+    let mut reencoded =
+        deserialize_binary_openlr(&data).map_err(ReencodeError::DeserializeError)?;
+
+    if config.preserve_offsets {
+        preserve_offsets(location, &mut reencoded);
+    }
+
+    Ok(reencoded)
+}
+
+/// Gets the first two LRPs of a line, the ones the positive offset is relative to.
+fn first(points: &[Point]) -> &[Point] {
+    points.get(..2).unwrap_or_default()
+}
+
+/// Gets the last two LRPs of a line, the ones the negative offset is relative to.
+fn last(points: &[Point]) -> &[Point] {
+    points
+        .get(points.len().saturating_sub(2)..)
+        .unwrap_or_default()
+}
+
+/// Copies the offsets of the original location reference into the re-encoded one, if they are
+/// relative to the same LRPs: the same coordinates and DNPs.
+fn preserve_offsets(original: &LocationReference, reencoded: &mut LocationReference) {
+    let same_lrps = |a: &[Point], b: &[Point]| match (a, b) {
+        ([a1, a2], [b1, b2]) => {
+            let dnp = |point: &Point| point.path.map(|path| path.dnp);
+            dnp(a1).is_some()
+                && dnp(a1) == dnp(b1)
+                && a1.coordinate == b1.coordinate
+                && a2.coordinate == b2.coordinate
+        }
+        _ => false,
+    };
+
+    match (original, reencoded) {
+        (LocationReference::Line(original), LocationReference::Line(reencoded)) => {
+            if same_lrps(first(&original.points), first(&reencoded.points)) {
+                reencoded.offsets.pos = original.offsets.pos;
+            }
+            if same_lrps(last(&original.points), last(&reencoded.points)) {
+                reencoded.offsets.neg = original.offsets.neg;
+            }
+        }
+        (LocationReference::PointAlongLine(original), LocationReference::PointAlongLine(point)) => {
+            if same_lrps(&original.points, &point.points) {
+                point.offset = original.offset;
+            }
+        }
+        (LocationReference::Poi(original), LocationReference::Poi(poi)) => {
+            if same_lrps(&original.point.points, &poi.point.points) {
+                poi.point.offset = original.point.offset;
+            }
+        }
+        (LocationReference::Line(_), _)
+        | (LocationReference::PointAlongLine(_), _)
+        | (LocationReference::Poi(_), _)
+        | (LocationReference::GeoCoordinate(_), _)
+        | (LocationReference::Circle(_), _)
+        | (LocationReference::Rectangle(_), _)
+        | (LocationReference::Grid(_), _)
+        | (LocationReference::Polygon(_), _)
+        | (LocationReference::ClosedLine(_), _) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::tests::{NETWORK_GRAPH, NetworkGraph};
+    use crate::{Length, Offset, deserialize_base64_openlr};
+
+    #[test]
+    fn reencode_line() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let config = ReencodeConfig::default();
+
+        let location = deserialize_base64_openlr("CwmShiVYczPJBgCs/y0zAQ==").unwrap();
+        let reencoded = reencode(&config, graph, &location).unwrap();
+
+        // the canonical reference is stable
+        assert_eq!(reencode(&config, graph, &reencoded).unwrap(), reencoded);
+    }
+
+    #[test]
+    fn reencode_line_preserve_offsets() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let config = ReencodeConfig {
+            preserve_offsets: true,
+            ..ReencodeConfig::default()
+        };
+
+        let location = deserialize_base64_openlr("CwmTaSVYpTPZCP4a/5UjYQUH").unwrap();
+        let LocationReference::Line(line) = &location else {
+            unreachable!()
+        };
+        assert_ne!(line.offsets.pos, Offset::ZERO);
+
+        // the canonical LRPs on the graph with the original offsets
+        let LocationReference::Line(mut canonical) =
+            reencode(&ReencodeConfig::default(), graph, &location).unwrap()
+        else {
+            unreachable!()
+        };
+        canonical.offsets = line.offsets;
+        let canonical = LocationReference::Line(canonical);
+
+        let reencoded = reencode(&config, graph, &canonical).unwrap();
+        assert_eq!(reencoded, canonical);
+    }
+
+    #[test]
+    fn reencode_preserve_offsets_same_dnp() {
+        let original = deserialize_base64_openlr("CwmTaSVYpTPZCP4a/5UjYQUH").unwrap();
+
+        let mut reencoded = original.clone();
+        let LocationReference::Line(line) = &mut reencoded else {
+            unreachable!()
+        };
+        line.offsets.pos = Offset::ZERO;
+        line.offsets.neg = Offset::ZERO;
+        preserve_offsets(&original, &mut reencoded);
+        assert_eq!(reencoded, original);
+
+        // offsets relative to other DNPs are not preserved
+        let LocationReference::Line(line) = &mut reencoded else {
+            unreachable!()
+        };
+        line.offsets.pos = Offset::ZERO;
+        if let Some(path) = &mut line.points[0].path {
+            path.dnp += Length::from_meters(100.0);
+        }
+        preserve_offsets(&original, &mut reencoded);
+        let LocationReference::Line(line) = &reencoded else {
+            unreachable!()
+        };
+        assert_eq!(line.offsets.pos, Offset::ZERO);
+
+        // offsets relative to other LRP coordinates with the same DNPs are not preserved
+        let mut reencoded = original.clone();
+        let LocationReference::Line(line) = &mut reencoded else {
+            unreachable!()
+        };
+        line.offsets.pos = Offset::ZERO;
+        line.points[0].coordinate.lon += 0.001;
+        preserve_offsets(&original, &mut reencoded);
+        let LocationReference::Line(line) = &reencoded else {
+            unreachable!()
+        };
+        assert_eq!(line.offsets.pos, Offset::ZERO);
+    }
+}
@@ -0,0 +1,335 @@
+//! Shortest path searches on a [`DirectedGraph`] with the same semantics the decoder uses to
+//! resolve the routes between consecutive LRPs, to verify routes, compute distances to next point
+//! or pre-validate locations before encoding them.
+//!
+//! The searches are edge based: a path starts with the origin edge and ends with the destination
+//! edge, and its cost includes the cost of both. Only the edges with an FRC up to the lowest FRC
+//! are expanded (the origin and the destination edges are always part of the path), the turn
+//! restrictions of the graph (including via-way restrictions) are never violated and the edges
+//! that would make the path length exceed the max length are not expanded.
+//!
+//! The edges exiting a vertex are expanded in edge ID order, whatever the order the graph returns
+//! them in: among paths of equal cost, the path found is the same on every run and platform.
+
+use std::cmp::Reverse;
+
+use radix_heap::RadixHeapMap;
+use tracing::trace;
+
+use crate::graph::dijkstra::{ShortestDistances, previous_edges, unpack_path};
+use crate::graph::path::{is_path_connected, is_turn_restricted_after};
+use crate::{DirectedGraph, Frc, Length, Path};
+
+/// Cost of traveling the edges of a graph: the searches find the path that minimizes the sum of the
+/// costs of its edges (expressed as lengths). Costs must not be negative.
+///
+/// Implemented by any function of the graph and of the edge.
+pub trait EdgeCost<G: DirectedGraph> {
+    /// Gets the cost of traveling the whole edge.
+    fn cost(&self, graph: &G, edge: G::EdgeId) -> Result<Length, G::Error>;
+}
+
+/// The length of the edges, the cost used by the encoder and by the decoder by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LengthCost;
+
+impl<G: DirectedGraph> EdgeCost<G> for LengthCost {
+    fn cost(&self, graph: &G, edge: G::EdgeId) -> Result<Length, G::Error> {
+        graph.get_edge_length(edge)
+    }
+}
+
+/// Cost minimized by the route searches of the encoder and of the decoder (see
+/// [`crate::EncoderConfig::route_cost`] and [`crate::DecoderConfig::route_cost`]).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RouteCost {
+    /// The length of the edges: the routes are the shortest paths, as the specification requires.
+    #[default]
+    Length,
+    /// The cost of the edges given by the graph (see [`DirectedGraph::get_edge_cost`]), for example
+    /// a travel time weighted length for references encoded along the fastest paths.
+    Graph,
+}
+
+impl<G: DirectedGraph> EdgeCost<G> for RouteCost {
+    fn cost(&self, graph: &G, edge: G::EdgeId) -> Result<Length, G::Error> {
+        match self {
+            Self::Length => graph.get_edge_length(edge),
+            Self::Graph => graph.get_edge_cost(edge),
+        }
+    }
+}
+
+impl<G, F> EdgeCost<G> for F
+where
+    G: DirectedGraph,
+    F: Fn(&G, G::EdgeId) -> Result<Length, G::Error>,
+{
+    fn cost(&self, graph: &G, edge: G::EdgeId) -> Result<Length, G::Error> {
+        self(graph, edge)
+    }
+}
+
+/// Gets the cost of the path: the sum of the costs of its edges.
+pub(crate) fn path_cost<G: DirectedGraph>(
+    graph: &G,
+    path: &[G::EdgeId],
+    cost: &impl EdgeCost<G>,
+) -> Result<Length, G::Error> {
+    path.iter()
+        .try_fold(Length::ZERO, |acc, &edge| Ok(acc + cost.cost(graph, edge)?))
+}
+
+/// Finds the path of least cost from the origin to the destination edge with Dijkstra's algorithm.
+/// Returns None if no path is found within the lowest FRC and the max length.
+pub fn dijkstra<G: DirectedGraph>(
+    graph: &G,
+    origin: G::EdgeId,
+    destination: G::EdgeId,
+    lowest_frc: Frc,
+    max_length: Length,
+    cost: &impl EdgeCost<G>,
+) -> Result<Option<Path<G::EdgeId>>, G::Error> {
+    search_path(
+        graph,
+        origin,
+        destination,
+        lowest_frc,
+        max_length,
+        cost,
+        |_| Ok(Length::ZERO),
+        |_| Ok(()),
+    )
+}
+
+/// Finds the path of least cost from the origin to the destination edge with the A* algorithm,
+/// guided by the great-circle distance to the start of the destination edge.
+///
+/// The path is the same found by [`dijkstra`] as long as the cost of every edge is not lower than
+/// the distance between its vertices (such as the [`LengthCost`]), usually expanding fewer edges.
+pub fn astar<G: DirectedGraph>(
+    graph: &G,
+    origin: G::EdgeId,
+    destination: G::EdgeId,
+    lowest_frc: Frc,
+    max_length: Length,
+    cost: &impl EdgeCost<G>,
+) -> Result<Option<Path<G::EdgeId>>, G::Error> {
+    let target_vertex = graph.get_edge_start_vertex(destination)?;
+    let target = graph.get_vertex_coordinate(target_vertex)?;
+
+    let heuristic = |edge| {
+        let vertex = graph.get_edge_end_vertex(edge)?;
+        Ok(graph.get_vertex_coordinate(vertex)?.distance(&target))
+    };
+
+    search_path(
+        graph,
+        origin,
+        destination,
+        lowest_frc,
+        max_length,
+        cost,
+        heuristic,
+        |_| Ok(()),
+    )
+}
+
+/// Finds the path of least cost from the origin to the destination edge. The heuristic estimates
+/// the cost from the end of an edge to the destination (zero for Dijkstra's algorithm), and the
+/// callback is called with the length of the path to each settled edge, before expanding it.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn search_path<G, E>(
+    graph: &G,
+    origin: G::EdgeId,
+    destination: G::EdgeId,
+    lowest_frc: Frc,
+    max_length: Length,
+    cost: &impl EdgeCost<G>,
+    heuristic: impl Fn(G::EdgeId) -> Result<Length, G::Error>,
+    mut on_settled: impl FnMut(Length) -> Result<(), E>,
+) -> Result<Option<Path<G::EdgeId>>, E>
+where
+    G: DirectedGraph,
+    E: From<G::Error>,
+{
+    let origin_length = graph.get_edge_length(origin)?;
+    let origin_cost = cost.cost(graph, origin)?;
+    let mut shortest_costs = ShortestDistances::new(graph);
+    shortest_costs.insert(origin, origin_cost);
+    let origin_estimate = origin_cost + heuristic(origin)?;
+    let mut heap = RadixHeapMap::from_iter([(
+        Reverse(origin_estimate),
+        (origin, origin_cost, origin_length),
+    )]);
+    let mut exiting_edges = vec![];
+
+    while let Some((Reverse(h_estimate), (h_edge, h_cost, h_distance))) = heap.pop() {
+        if h_edge == destination {
+            // Unpacking: the shortest path from destination back to origin
+            let edges = unpack_path(&shortest_costs, destination);
+            debug_assert!(is_path_connected(graph, &edges)?, "{edges:?}");
+
+            return Ok(Some(Path {
+                length: h_distance,
+                edges,
+            }));
+        }
+
+        // check if we already know a cheaper way to get to the end of this path from the origin
+        if h_cost > shortest_costs.get(h_edge) {
+            continue;
+        }
+
+        on_settled(h_distance)?;
+
+        let end_vertex = graph.get_edge_end_vertex(h_edge)?;
+        // ties between paths of equal cost are broken by the order the edges are relaxed in
+        exiting_edges.clear();
+        exiting_edges.extend(graph.vertex_exiting_edges_within_frc(end_vertex, lowest_frc)?);
+        exiting_edges.sort_unstable_by_key(|&(edge, _)| edge);
+
+        for &(edge, _) in &exiting_edges {
+            // the path to the settled edge is final: via-way restrictions are checked against it
+            // only, even if another path to the same edge could have avoided the restriction
+            let previous = previous_edges(&shortest_costs, h_edge);
+            if is_turn_restricted_after(graph, previous, edge)? {
+                continue;
+            }
+
+            let distance = h_distance + graph.get_edge_length(edge)?;
+
+            if distance > max_length {
+                trace!("Element distance too far: {edge:?} {distance} > {max_length}");
+                continue;
+            }
+
+            let edge_cost = h_cost + cost.cost(graph, edge)?;
+
+            // check if we can follow the current path to reach the neighbor in a cheaper way
+            if edge_cost < shortest_costs.get(edge) {
+                // Relax: we have now found a better way that we are going to explore
+                shortest_costs.insert_with_previous(edge, edge_cost, h_edge);
+                // the estimates of a consistent heuristic never decrease along the search, but
+                // rounding errors could make them slightly lower than the last popped one
+                let estimate = (edge_cost + heuristic(edge)?).max(h_estimate);
+                heap.push(Reverse(estimate), (edge, edge_cost, distance));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::graph::path::path_length;
+    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+    use crate::{
+        DecoderConfig, EncoderConfig, Location, decode_base64_openlr, encode_base64_openlr,
+    };
+
+    #[test]
+    fn routing_dijkstra_astar_001() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        let expected = Path {
+            length: Length::from_meters(379.0),
+            edges: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+        };
+
+        let search = |origin, destination, max_cost| {
+            let path = dijkstra(graph, origin, destination, Frc::Frc7, max_cost, &LengthCost);
+            let astar_path = astar(graph, origin, destination, Frc::Frc7, max_cost, &LengthCost);
+            assert_eq!(path, astar_path);
+            path.unwrap()
+        };
+
+        assert_eq!(
+            search(EdgeId(8717174), EdgeId(109783), Length::MAX),
+            Some(expected.clone())
+        );
+        assert_eq!(
+            search(EdgeId(8717174), EdgeId(8717174), Length::MAX),
+            Some(Path {
+                length: Length::from_meters(136.0),
+                edges: vec![EdgeId(8717174)],
+            })
+        );
+        assert_eq!(
+            search(EdgeId(8717174), EdgeId(109783), Length::from_meters(378.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn routing_dijkstra_cost_function() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        // every edge costs the same: the path with the fewest edges is the cheapest
+        let hops = |_: &NetworkGraph, _: EdgeId| Ok(Length::from_meters(1.0));
+
+        let path = dijkstra(
+            graph,
+            EdgeId(8717174),
+            EdgeId(109783),
+            Frc::Frc7,
+            Length::MAX,
+            &hops,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(path.length, path_length(graph, &path.edges).unwrap());
+        assert_eq!(path.edges.first(), Some(&EdgeId(8717174)));
+        assert_eq!(path.edges.last(), Some(&EdgeId(109783)));
+    }
+
+    #[test]
+    fn routing_route_cost_graph() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        // the network graph has no costs: they default to the lengths of the edges
+        for edge in [EdgeId(8717174), EdgeId(109783)] {
+            assert_eq!(
+                RouteCost::Graph.cost(graph, edge),
+                graph.get_edge_length(edge)
+            );
+        }
+
+        let path = dijkstra(
+            graph,
+            EdgeId(8717174),
+            EdgeId(109783),
+            Frc::Frc7,
+            Length::MAX,
+            &RouteCost::Graph,
+        )
+        .unwrap();
+        assert_eq!(
+            path.map(|path| path.edges),
+            Some(vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)])
+        );
+
+        let decoder_config = DecoderConfig {
+            route_cost: RouteCost::Graph,
+            ..DecoderConfig::default()
+        };
+        let encoder_config = EncoderConfig {
+            route_cost: RouteCost::Graph,
+            ..EncoderConfig::default()
+        };
+
+        let location = decode_base64_openlr(&decoder_config, graph, "CwmShiVYczPJBgCs/y0zAQ==");
+        let Ok(Location::Line(line)) = location else {
+            unreachable!()
+        };
+
+        let code = encode_base64_openlr(&encoder_config, graph, Location::Line(line.clone()));
+        let decoded = decode_base64_openlr(&decoder_config, graph, code.unwrap());
+        assert_eq!(decoded, Ok(Location::Line(line)));
+    }
+}
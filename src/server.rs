@@ -0,0 +1,330 @@
+//! HTTP service exposing the decoder and the encoder of a graph.
+//!
+//! - `POST /decode` takes a `{"code": "<base64>"}` JSON body and returns the decoded location, or a
+//!   GeoJSON feature of the location geometry with the `?format=geojson` query parameter.
+//! - `POST /encode` takes a location JSON body and returns its `{"code": "<base64>"}`.
+//!
+//! Non-fatal warnings are returned along with the results, errors are returned as
+//! `{"error": "<message>"}` with an unprocessable entity (422) status code.
+//!
+//! Decoding and encoding are CPU-bound, they run on the blocking threads of the runtime so that
+//! they don't stall the other requests.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::format::geojson::location_geometry;
+use crate::{
+    DecoderConfig, DirectedGraph, EncoderConfig, Location, decode_base64_openlr_with_warnings,
+    encode_base64_openlr_with_warnings,
+};
+
+/// Graph and configurations shared by all the requests handled by the service.
+pub struct ServerState<G> {
+    pub graph: G,
+    pub decoder_config: DecoderConfig,
+    pub encoder_config: EncoderConfig,
+}
+
+impl<G> ServerState<G> {
+    /// Max number of nodes visited by a single route search of the default decoder configuration.
+    pub const DEFAULT_MAX_VISITED_NODES: usize = 100_000;
+    /// Max time spent by a single route search of the default decoder configuration.
+    pub const DEFAULT_MAX_SEARCH_TIME: Duration = Duration::from_secs(1);
+    /// Max time spent by all the route searches of a location reference of the default decoder
+    /// configuration.
+    pub const DEFAULT_MAX_TOTAL_SEARCH_TIME: Duration = Duration::from_secs(5);
+    /// Max number of nodes expanded by all the route searches of a location of the default encoder
+    /// configuration.
+    pub const DEFAULT_MAX_EXPANDED_NODES: usize = 1_000_000;
+    /// Max number of shortest paths computed to cover a location of the default encoder
+    /// configuration.
+    pub const DEFAULT_MAX_COVERAGE_ITERATIONS: usize = 1_000;
+
+    /// Creates the state of the service with the default configurations, the route searches of
+    /// the decoder and of the encoder are bounded so that untrusted location references and
+    /// locations can't exhaust the service.
+    pub fn new(graph: G) -> Self {
+        Self {
+            graph,
+            decoder_config: DecoderConfig {
+                max_visited_nodes: Some(Self::DEFAULT_MAX_VISITED_NODES),
+                max_search_time: Some(Self::DEFAULT_MAX_SEARCH_TIME),
+                max_total_search_time: Some(Self::DEFAULT_MAX_TOTAL_SEARCH_TIME),
+                ..DecoderConfig::default()
+            },
+            encoder_config: EncoderConfig {
+                max_expanded_nodes: Some(Self::DEFAULT_MAX_EXPANDED_NODES),
+                max_coverage_iterations: Some(Self::DEFAULT_MAX_COVERAGE_ITERATIONS),
+                ..EncoderConfig::default()
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecodeRequest {
+    /// Base64 OpenLR code.
+    pub code: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecodeResponse<EdgeId> {
+    pub location: Location<EdgeId>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EncodeResponse {
+    /// Base64 OpenLR code.
+    pub code: String,
+    pub warnings: Vec<String>,
+}
+
+/// Format of the decoded location returned by the decode endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResponseFormat {
+    #[default]
+    Json,
+    GeoJson,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+struct DecodeQuery {
+    #[serde(default)]
+    format: ResponseFormat,
+}
+
+/// Creates the router of the service with the `/decode` and `/encode` endpoints.
+pub fn router<G>(state: Arc<ServerState<G>>) -> Router
+where
+    G: DirectedGraph + Send + Sync + 'static,
+    G::EdgeId: Serialize + DeserializeOwned + Send + Sync,
+    G::Error: Send,
+{
+    Router::new()
+        .route("/decode", post(decode::<G>))
+        .route("/encode", post(encode::<G>))
+        .with_state(state)
+}
+
+async fn decode<G>(
+    State(state): State<Arc<ServerState<G>>>,
+    Query(query): Query<DecodeQuery>,
+    Json(request): Json<DecodeRequest>,
+) -> Response
+where
+    G: DirectedGraph + Send + Sync + 'static,
+    G::EdgeId: Serialize + Send,
+{
+    spawn_blocking(move || decode_blocking(&state, query, &request)).await
+}
+
+fn decode_blocking<G>(
+    state: &ServerState<G>,
+    query: DecodeQuery,
+    request: &DecodeRequest,
+) -> Response
+where
+    G: DirectedGraph,
+    G::EdgeId: Serialize,
+{
+    let ServerState {
+        graph,
+        decoder_config,
+        ..
+    } = state;
+
+    let (location, warnings) =
+        match decode_base64_openlr_with_warnings(decoder_config, graph, &request.code) {
+            Ok(decoded) => decoded,
+            Err(e) => return error_response(e),
+        };
+
+    let warnings = warnings.iter().map(ToString::to_string).collect();
+
+    match query.format {
+        ResponseFormat::Json => Json(DecodeResponse { location, warnings }).into_response(),
+        ResponseFormat::GeoJson => match location_geometry(graph, &location) {
+            Ok(geometry) => Json(json!({
+                "type": "Feature",
+                "geometry": geometry,
+                "properties": { "location": location, "warnings": warnings },
+            }))
+            .into_response(),
+            Err(e) => error_response(e),
+        },
+    }
+}
+
+async fn encode<G>(
+    State(state): State<Arc<ServerState<G>>>,
+    Json(location): Json<Location<G::EdgeId>>,
+) -> Response
+where
+    G: DirectedGraph + Send + Sync + 'static,
+    G::EdgeId: Send,
+{
+    spawn_blocking(move || encode_blocking(&state, location)).await
+}
+
+fn encode_blocking<G: DirectedGraph>(
+    state: &ServerState<G>,
+    location: Location<G::EdgeId>,
+) -> Response {
+    let ServerState {
+        graph,
+        encoder_config,
+        ..
+    } = state;
+
+    match encode_base64_openlr_with_warnings(encoder_config, graph, location) {
+        Ok((code, warnings)) => Json(EncodeResponse {
+            code,
+            warnings: warnings.iter().map(ToString::to_string).collect(),
+        })
+        .into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+/// Runs the handler on the blocking threads of the runtime.
+async fn spawn_blocking(handler: impl FnOnce() -> Response + Send + 'static) -> Response {
+    match tokio::task::spawn_blocking(handler).await {
+        Ok(response) => response,
+        Err(e) => {
+            let body = json!({ "error": e.to_string() });
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(body)).into_response()
+        }
+    }
+}
+
+fn error_response(error: impl ToString) -> Response {
+    let body = json!({ "error": error.to_string() });
+    (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use serde_json::Value;
+    use test_log::test;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::graph::tests::{EdgeId, NETWORK_GRAPH, NetworkGraph};
+    use crate::{Length, LineLocation};
+
+    fn test_router() -> Router {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+
+        router(Arc::new(ServerState::new(graph)))
+    }
+
+    async fn post(router: Router, uri: &str, body: Value) -> (StatusCode, Value) {
+        let request = Request::post(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[test]
+    fn server_state_search_limits() {
+        let graph: &NetworkGraph = &NETWORK_GRAPH;
+        let state = ServerState::new(graph);
+
+        assert!(state.decoder_config.max_visited_nodes.is_some());
+        assert!(state.decoder_config.max_search_time.is_some());
+        assert!(state.decoder_config.max_total_search_time.is_some());
+        assert!(state.encoder_config.max_expanded_nodes.is_some());
+        assert!(state.encoder_config.max_coverage_iterations.is_some());
+    }
+
+    #[test(tokio::test)]
+    async fn server_decode_001() {
+        let body = json!({ "code": "CwmShiVYczPJBgCs/y0zAQ==" });
+        let (status, response) = post(test_router(), "/decode", body).await;
+
+        assert_eq!(status, StatusCode::OK);
+
+        let response: DecodeResponse<EdgeId> = serde_json::from_value(response).unwrap();
+        assert_eq!(
+            response,
+            DecodeResponse {
+                location: Location::Line(LineLocation {
+                    path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+                    pos_offset: Length::ZERO,
+                    neg_offset: Length::ZERO,
+                }),
+                warnings: vec![],
+            }
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn server_decode_002() {
+        let body = json!({ "code": "CwmShiVYczPJBgCs/y0zAQ==" });
+        let (status, response) = post(test_router(), "/decode?format=geojson", body).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response["type"], "Feature");
+        assert_eq!(response["geometry"]["type"], "LineString");
+        assert_eq!(
+            response["geometry"]["coordinates"]
+                .as_array()
+                .unwrap()
+                .len(),
+            4
+        );
+        assert_eq!(response["properties"]["location"]["type"], "Line");
+    }
+
+    #[test(tokio::test)]
+    async fn server_decode_003() {
+        let body = json!({ "code": "invalid" });
+        let (status, response) = post(test_router(), "/decode", body).await;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(response["error"].is_string());
+    }
+
+    #[test(tokio::test)]
+    async fn server_encode_001() {
+        let location = Location::Line(LineLocation {
+            path: vec![EdgeId(8717174), EdgeId(8717175), EdgeId(109783)],
+            pos_offset: Length::ZERO,
+            neg_offset: Length::ZERO,
+        });
+
+        let body = serde_json::to_value(&location).unwrap();
+        let (status, response) = post(test_router(), "/encode", body).await;
+
+        assert_eq!(status, StatusCode::OK);
+
+        let response: EncodeResponse = serde_json::from_value(response).unwrap();
+        assert_eq!(
+            response,
+            EncodeResponse {
+                code: "CwmShiVYcjPJBgCs/y0zAQ==".to_string(),
+                warnings: vec![]
+            }
+        );
+    }
+}
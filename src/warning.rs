@@ -0,0 +1,75 @@
+use thiserror::Error;
+
+use crate::{Coordinate, Length};
+
+/// Non-fatal issue found by the encoder: the location was encoded but the resulting location
+/// reference may be degraded.
+#[derive(Error, Debug, PartialEq, Clone, Copy)]
+pub enum EncodeWarning {
+    #[error("Positive offset {requested} rounded to {encoded}")]
+    PositiveOffsetRounded { requested: Length, encoded: Length },
+    #[error("Negative offset {requested} rounded to {encoded}")]
+    NegativeOffsetRounded { requested: Length, encoded: Length },
+    #[error("LRP placed on invalid node at {0}")]
+    LrpOnInvalidNode(Coordinate),
+    #[error("Backward expansion of the location stopped before reaching a valid node")]
+    BackwardExpansionTruncated,
+    #[error("Forward expansion of the location stopped before reaching a valid node")]
+    ForwardExpansionTruncated,
+    #[error("Duplicate edge at location index {0} removed")]
+    DuplicateEdgeRemoved(usize),
+    #[error("{0} zero-length edges removed from the location")]
+    ZeroLengthEdgesRemoved(usize),
+}
+
+/// Non-fatal issue found by the decoder: the location was decoded but the match against the graph
+/// may be degraded.
+#[derive(Error, Debug, PartialEq, Clone, Copy)]
+pub enum DecodeWarning {
+    #[error("Positive offset {0} trimmed to zero")]
+    PositiveOffsetTrimmed(Length),
+    #[error("Negative offset {0} trimmed to zero")]
+    NegativeOffsetTrimmed(Length),
+    #[error("Route from LRP {index} deviates from DNP {dnp} by {deviation}")]
+    DnpDeviation {
+        index: usize,
+        dnp: Length,
+        deviation: Length,
+    },
+    #[error("Route from LRP {0} resolved without the best rated candidate lines")]
+    CandidateFallback(usize),
+    #[error("First LRP at {0} has no candidate lines, location decoded without it")]
+    HeadTrimmed(Coordinate),
+    #[error("Last LRP at {0} has no candidate lines, location decoded without it")]
+    TailTrimmed(Coordinate),
+    #[error("No route from LRP {first} to LRP {last}, location partially decoded")]
+    RouteGap { first: usize, last: usize },
+}
+
+/// Problem of a line location found by
+/// [`LineLocation::validate`](crate::LineLocation::validate): the location cannot be encoded, or
+/// the encoder has to alter it.
+#[derive(Error, Debug, PartialEq, Clone, Copy)]
+pub enum LocationProblem {
+    #[error("Location path is empty")]
+    EmptyPath,
+    #[error("Location path is not connected at edge index {index}")]
+    DisconnectedPath { index: usize },
+    #[error("Location path repeats the previous edge at index {index}")]
+    DuplicateEdge { index: usize },
+    #[error("Location path turns back along the previous edge at index {index}")]
+    OppositeDirection { index: usize },
+    #[error("Zero-length edge at location index {index}")]
+    ZeroLengthEdge { index: usize },
+    #[error("Location offsets {pos_offset} + {neg_offset} exceed the path length {length}")]
+    OffsetsExceedLength {
+        pos_offset: Length,
+        neg_offset: Length,
+        length: Length,
+    },
+    #[error("Location offset {offset} exceeds the maximum LRP distance {max_lrp_distance}")]
+    OffsetExceedsMaxLrpDistance {
+        offset: Length,
+        max_lrp_distance: Length,
+    },
+}
@@ -0,0 +1,73 @@
+//! JavaScript bindings built with [wasm-bindgen](https://docs.rs/wasm-bindgen), to handle location
+//! references client-side (for example in web dashboards).
+//!
+//! - `deserialize(code)` returns the JSON of the location reference of a Base64 code.
+//! - `serialize(json)` returns the Base64 code of a location reference JSON.
+//! - `decodeGeometry(code)` returns the GeoJSON feature collection of the location reference,
+//!   approximated from its coordinates without a map (see [`location_reference_geojson`]).
+//!
+//! Only the format and model modules are exposed: they don't depend on the platform. Decoding on a
+//! graph is not, since the route searches measure their time with [`std::time::Instant`], which is
+//! not available on `wasm32-unknown-unknown`.
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    LocationReference, deserialize_base64_openlr, location_reference_geojson,
+    serialize_base64_openlr,
+};
+
+/// Deserializes an OpenLR Location Reference encoded in Base64 into its JSON.
+#[wasm_bindgen]
+pub fn deserialize(code: &str) -> Result<String, JsError> {
+    let location = deserialize_base64_openlr(code)?;
+    Ok(serde_json::to_string(&location)?)
+}
+
+/// Serializes the JSON of an OpenLR Location Reference into Base64.
+#[wasm_bindgen]
+pub fn serialize(json: &str) -> Result<String, JsError> {
+    let location: LocationReference = serde_json::from_str(json)?;
+    Ok(serialize_base64_openlr(&location)?)
+}
+
+/// Decodes an OpenLR Location Reference encoded in Base64 into the GeoJSON FeatureCollection of
+/// its LRPs and of its geometry, without a map.
+#[wasm_bindgen(js_name = decodeGeometry)]
+pub fn decode_geometry(code: &str) -> Result<String, JsError> {
+    let location = deserialize_base64_openlr(code)?;
+    Ok(location_reference_geojson(&location).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn wasm_deserialize_serialize() {
+        for (code, location_type) in [
+            ("CwRbWyNG9RpsCQCb/jsbtAT/6/+jK1lE", "Line"),
+            ("I+djotZ9eA==", "GeoCoordinate"),
+            ("K/6P+SKSuBJGGAUn/1gSUyM=", "PointAlongLine"),
+        ] {
+            let json = deserialize(code).unwrap();
+            let value: Value = serde_json::from_str(&json).unwrap();
+            assert_eq!(value["type"], location_type);
+            assert_eq!(serialize(&json).unwrap(), code);
+        }
+    }
+
+    #[test]
+    fn wasm_decode_geometry() {
+        let geojson = decode_geometry("CwRbWyNG9RpsCQCb/jsbtAT/6/+jK1lE").unwrap();
+        let geojson: Value = serde_json::from_str(&geojson).unwrap();
+
+        assert_eq!(geojson["type"], "FeatureCollection");
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 4);
+        assert_eq!(features[3]["geometry"]["type"], "LineString");
+    }
+}